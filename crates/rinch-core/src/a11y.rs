@@ -0,0 +1,254 @@
+//! Screen reader announcements and ARIA semantics.
+//!
+//! Status changes that happen away from the focused element (a file saved,
+//! a form error, a toast) aren't noticed by screen reader users unless
+//! they're announced. [`announce`] queues a message directly; elements with
+//! an `aria-live` attribute (or `role: "status"` / `"alert"`) announce their
+//! text whenever a re-render changes it, as live regions do on the web.
+//!
+//! Announcements are delivered by the runtime once per frame. Until rinch
+//! exposes an accessibility tree to the platform, it only logs them (at
+//! debug level, target `rinch::a11y`); the test harness collects them so
+//! tests can check what would be spoken.
+//!
+//! Elements describe themselves with `role` and the `aria_*` props, which
+//! `rsx!` checks against WAI-ARIA at compile time. Dynamic values are
+//! typed through [`AriaValue`]: `aria_expanded: open.get()` renders
+//! `"true"` or `"false"`, and a `None` leaves the attribute out.
+
+use std::cell::RefCell;
+
+thread_local! {
+    static ANNOUNCEMENTS: RefCell<Vec<Announcement>> = const { RefCell::new(Vec::new()) };
+}
+
+/// How urgently an announcement is spoken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Politeness {
+    /// Spoken once the user is idle, after what's being read.
+    Polite,
+    /// Spoken right away, interrupting, and dropping polite announcements
+    /// still waiting. Keep it for errors and time-critical warnings.
+    Assertive,
+}
+
+impl Politeness {
+    /// The politeness an `aria-live` value asks for; `None` for `"off"` and
+    /// anything unknown.
+    pub fn from_aria_live(value: &str) -> Option<Self> {
+        match value.trim() {
+            "polite" => Some(Self::Polite),
+            "assertive" => Some(Self::Assertive),
+            _ => None,
+        }
+    }
+}
+
+/// A message waiting to be spoken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    pub message: String,
+    pub politeness: Politeness,
+}
+
+/// Ask screen readers to speak `message`.
+///
+/// Can be called from anywhere: handlers, effects, timers, or render.
+/// Empty messages are ignored.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::a11y::{announce, Politeness};
+///
+/// button {
+///     onclick: move || {
+///         save(&document.get());
+///         announce("File saved", Politeness::Polite);
+///     },
+///     "Save"
+/// }
+/// ```
+pub fn announce(message: impl Into<String>, politeness: Politeness) {
+    let message = message.into();
+    if message.trim().is_empty() {
+        return;
+    }
+    ANNOUNCEMENTS.with(|announcements| {
+        let mut announcements = announcements.borrow_mut();
+        if politeness == Politeness::Assertive {
+            announcements.retain(|queued| queued.politeness == Politeness::Assertive);
+        }
+        announcements.push(Announcement {
+            message,
+            politeness,
+        });
+    });
+}
+
+/// Take the announcements queued since the last call (called by the
+/// runtime).
+pub fn take_announcements() -> Vec<Announcement> {
+    ANNOUNCEMENTS.with(|announcements| std::mem::take(&mut *announcements.borrow_mut()))
+}
+
+/// A value for `role` or an `aria_*` prop.
+///
+/// Booleans render as `"true"` and `"false"`, numbers and strings as
+/// themselves, and `None` leaves the attribute out, so optional states
+/// such as `aria_pressed: Option<bool>` only appear when they apply.
+pub trait AriaValue {
+    /// The attribute's value, or `None` to leave it out.
+    fn aria_value(&self) -> Option<String>;
+}
+
+impl<T: AriaValue + ?Sized> AriaValue for &T {
+    fn aria_value(&self) -> Option<String> {
+        (**self).aria_value()
+    }
+}
+
+impl<T: AriaValue> AriaValue for Option<T> {
+    fn aria_value(&self) -> Option<String> {
+        self.as_ref().and_then(AriaValue::aria_value)
+    }
+}
+
+macro_rules! aria_value_to_string {
+    ($($ty:ty),*) => {
+        $(impl AriaValue for $ty {
+            fn aria_value(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
+    };
+}
+
+aria_value_to_string!(
+    bool, str, String, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64
+);
+
+/// The role an element has without a `role` attribute, as in HTML-AAM:
+/// `button` for `<button>`, `link` for `<a href>`, `checkbox` for
+/// `<input type="checkbox">`, and so on. `attr` looks up the element's
+/// attributes.
+pub fn implicit_role<'a>(
+    tag: &str,
+    attr: impl Fn(&str) -> Option<&'a str>,
+) -> Option<&'static str> {
+    let role = match tag {
+        "a" | "area" if attr("href").is_some() => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" | "summary" => "button",
+        "dialog" => "dialog",
+        "fieldset" | "details" | "optgroup" => "group",
+        "figure" => "figure",
+        "footer" => "contentinfo",
+        "form" => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" if attr("alt") == Some("") => "presentation",
+        "img" => "img",
+        "input" => match attr("type").unwrap_or("text").to_ascii_lowercase().as_str() {
+            "button" | "image" | "reset" | "submit" => "button",
+            "checkbox" => "checkbox",
+            "radio" => "radio",
+            "range" => "slider",
+            "number" => "spinbutton",
+            "search" => "searchbox",
+            "email" | "tel" | "text" | "url" => "textbox",
+            _ => return None,
+        },
+        "li" => "listitem",
+        "main" => "main",
+        "menu" | "ol" | "ul" => "list",
+        "meter" => "meter",
+        "nav" => "navigation",
+        "option" => "option",
+        "output" => "status",
+        "progress" => "progressbar",
+        "section" if attr("aria-label").is_some() || attr("aria-labelledby").is_some() => "region",
+        "select" if attr("multiple").is_some() => "listbox",
+        "select" => "combobox",
+        "table" => "table",
+        "tbody" | "thead" | "tfoot" => "rowgroup",
+        "td" => "cell",
+        "textarea" => "textbox",
+        "th" => "columnheader",
+        "tr" => "row",
+        _ => return None,
+    };
+    Some(role)
+}
+
+/// An element's role: the first role in its `role` attribute, or else its
+/// [implicit role](implicit_role).
+pub fn role<'a>(tag: &str, attr: impl Fn(&str) -> Option<&'a str>) -> Option<&'a str> {
+    match attr("role").and_then(|roles| roles.split_whitespace().next()) {
+        Some(role) => Some(role),
+        None => implicit_role(tag, attr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assertive_announcements_drop_waiting_polite_ones() {
+        announce("Saving", Politeness::Polite);
+        announce("   ", Politeness::Polite);
+        announce("Disk full", Politeness::Assertive);
+        announce("Retrying", Politeness::Polite);
+        let messages: Vec<_> = take_announcements()
+            .into_iter()
+            .map(|announcement| announcement.message)
+            .collect();
+        assert_eq!(messages, ["Disk full", "Retrying"]);
+        assert!(take_announcements().is_empty());
+    }
+
+    #[test]
+    fn aria_live_values() {
+        assert_eq!(
+            Politeness::from_aria_live("polite"),
+            Some(Politeness::Polite)
+        );
+        assert_eq!(
+            Politeness::from_aria_live(" assertive "),
+            Some(Politeness::Assertive)
+        );
+        assert_eq!(Politeness::from_aria_live("off"), None);
+    }
+
+    #[test]
+    fn aria_values() {
+        assert_eq!(true.aria_value().as_deref(), Some("true"));
+        assert_eq!(3.aria_value().as_deref(), Some("3"));
+        assert_eq!("menu".aria_value().as_deref(), Some("menu"));
+        assert_eq!(Some(false).aria_value().as_deref(), Some("false"));
+        assert_eq!(None::<bool>.aria_value(), None);
+    }
+
+    #[test]
+    fn roles() {
+        let attrs = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+        };
+        assert_eq!(role("button", attrs(&[])), Some("button"));
+        assert_eq!(role("a", attrs(&[])), None);
+        assert_eq!(role("a", attrs(&[("href", "#")])), Some("link"));
+        assert_eq!(
+            role("input", attrs(&[("type", "checkbox")])),
+            Some("checkbox")
+        );
+        assert_eq!(role("input", attrs(&[])), Some("textbox"));
+        assert_eq!(
+            role("div", attrs(&[("role", "switch checkbox")])),
+            Some("switch")
+        );
+        assert_eq!(role("div", attrs(&[])), None);
+    }
+}
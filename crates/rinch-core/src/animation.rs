@@ -0,0 +1,1073 @@
+//! Frame-driven animation.
+//!
+//! The runtime keeps a frame clock: callbacks passed to
+//! [`request_animation_frame`] run at the start of the next frame, about 60
+//! times a second, and the app re-renders after each frame that ran any.
+//! Animations request another frame from their callback until they settle,
+//! so an idle app doesn't wake up at all. Callbacks passed to
+//! [`request_paint_frame`] run on the same clock without re-rendering, for
+//! animations that only change [`AnimatedStyle`](crate::node_ref::AnimatedStyle)s.
+//!
+//! [`use_spring`](crate::hooks::use_spring),
+//! [`use_animated`](crate::hooks::use_animated) and
+//! [`use_animation_frame`](crate::hooks::use_animation_frame) are built on it.
+//!
+//! When the user has asked their OS to reduce motion
+//! ([`prefers_reduced_motion`]), springs, tweens, transition groups and
+//! smooth scrolls skip straight to where they end up, unless started inside
+//! [`with_full_motion`].
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use crate::clock::{self, Instant};
+use crate::reactive::Signal;
+
+/// Time between animation frames.
+pub const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// Longest time step a spring takes in one go; longer frames are split up so
+/// stiff springs stay stable.
+const MAX_SPRING_STEP: f64 = 0.004;
+
+/// Distance from the target and speed below which a spring is at rest.
+const REST_THRESHOLD: f64 = 0.001;
+
+/// A callback waiting for the next animation frame, and whether the app
+/// re-renders after it.
+type FrameCallback = (Box<dyn FnOnce(Instant)>, bool);
+
+thread_local! {
+    static FRAME_REQUESTS: RefCell<Vec<FrameCallback>> = const { RefCell::new(Vec::new()) };
+    /// The OS reduced-motion setting.
+    static SYSTEM_REDUCED_MOTION: Cell<bool> = const { Cell::new(false) };
+    /// The app's override of the OS setting.
+    static REDUCED_MOTION_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+    /// The setting in effect: the override, or the OS setting.
+    static REDUCED_MOTION: Signal<bool> = Signal::new(false);
+    /// How many [`with_full_motion`] calls are running.
+    static FULL_MOTION_DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Run `callback` at the start of the next animation frame, with the frame's
+/// time. The app re-renders after the frame.
+///
+/// To keep animating, request another frame from the callback.
+pub fn request_animation_frame(callback: impl FnOnce(Instant) + 'static) {
+    FRAME_REQUESTS.with(|requests| requests.borrow_mut().push((Box::new(callback), true)));
+}
+
+/// Like [`request_animation_frame`], but the app doesn't re-render for it:
+/// the callback should only set
+/// [`AnimatedStyle`](crate::node_ref::AnimatedStyle)s, which just repaint.
+pub fn request_paint_frame(callback: impl FnOnce(Instant) + 'static) {
+    FRAME_REQUESTS.with(|requests| requests.borrow_mut().push((Box::new(callback), false)));
+}
+
+/// Whether anything is waiting for an animation frame (called by the runtime).
+pub fn animation_frame_requested() -> bool {
+    FRAME_REQUESTS.with(|requests| !requests.borrow().is_empty())
+}
+
+/// Run the callbacks waiting for this frame (called by the runtime).
+///
+/// Frames they request run next time. Returns whether the app should
+/// re-render.
+pub fn run_animation_frame(now: Instant) -> bool {
+    let callbacks = FRAME_REQUESTS.with(|requests| std::mem::take(&mut *requests.borrow_mut()));
+    let mut render = false;
+    for (callback, renders) in callbacks {
+        callback(now);
+        render |= renders;
+    }
+    render
+}
+
+/// Whether the user prefers reduced motion, from the OS accessibility
+/// setting or the app's [`override_reduced_motion`]. Reactive: reading it
+/// during render re-renders the app when it changes.
+///
+/// Built-in animations already honour it; check it for custom animations,
+/// such as to swap a slide for a fade.
+pub fn prefers_reduced_motion() -> bool {
+    REDUCED_MOTION.with(|reduced| reduced.get())
+}
+
+/// Replace the OS reduced-motion setting with the app's own (such as from a
+/// settings page), or go back to the OS setting with `None`.
+pub fn override_reduced_motion(reduced: Option<bool>) {
+    REDUCED_MOTION_OVERRIDE.with(|value| value.set(reduced));
+    update_reduced_motion();
+}
+
+/// Record the OS reduced-motion setting (called by the runtime).
+pub fn set_system_reduced_motion(reduced: bool) {
+    SYSTEM_REDUCED_MOTION.with(|value| value.set(reduced));
+    update_reduced_motion();
+}
+
+fn update_reduced_motion() {
+    let reduced = REDUCED_MOTION_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| SYSTEM_REDUCED_MOTION.with(Cell::get));
+    REDUCED_MOTION.with(|value| {
+        if crate::reactive::untracked(|| value.get()) != reduced {
+            value.set(reduced);
+        }
+    });
+}
+
+/// Run `f`, playing animations it starts in full even when the user prefers
+/// reduced motion.
+///
+/// For motion that carries meaning, such as a progress indicator, and
+/// doesn't just decorate:
+///
+/// ```ignore
+/// let progress = with_full_motion(|| use_spring(loaded, SpringConfig::GENTLE));
+/// ```
+pub fn with_full_motion<R>(f: impl FnOnce() -> R) -> R {
+    FULL_MOTION_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = f();
+    FULL_MOTION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    result
+}
+
+/// Whether animations starting now should skip to their end.
+pub(crate) fn reduce_motion() -> bool {
+    FULL_MOTION_DEPTH.with(Cell::get) == 0
+        && REDUCED_MOTION.with(|reduced| crate::reactive::untracked(|| reduced.get()))
+}
+
+/// How a [`use_spring`](crate::hooks::use_spring) value moves.
+///
+/// The value behaves like a unit mass on a spring attached to the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringConfig {
+    /// How strongly the value is pulled towards the target. Stiffer springs
+    /// are faster.
+    pub stiffness: f64,
+    /// How strongly motion is resisted. Less damping bounces more; about
+    /// `2 * sqrt(stiffness)` settles fastest without overshooting.
+    pub damping: f64,
+}
+
+impl SpringConfig {
+    /// Quick, with a slight overshoot.
+    pub const DEFAULT: Self = Self {
+        stiffness: 170.0,
+        damping: 26.0,
+    };
+    /// Slow and smooth.
+    pub const GENTLE: Self = Self {
+        stiffness: 120.0,
+        damping: 14.0,
+    };
+    /// Bouncy.
+    pub const WOBBLY: Self = Self {
+        stiffness: 180.0,
+        damping: 12.0,
+    };
+    /// Fast and firm.
+    pub const STIFF: Self = Self {
+        stiffness: 210.0,
+        damping: 20.0,
+    };
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Advance a spring by `dt` seconds, returning its new value and velocity.
+fn step_spring(
+    value: f64,
+    velocity: f64,
+    target: f64,
+    config: SpringConfig,
+    dt: f64,
+) -> (f64, f64) {
+    let (mut value, mut velocity) = (value, velocity);
+    let steps = (dt / MAX_SPRING_STEP).ceil().max(1.0);
+    let step = dt / steps;
+    for _ in 0..steps as usize {
+        let force = -config.stiffness * (value - target) - config.damping * velocity;
+        velocity += force * step;
+        value += velocity * step;
+    }
+    (value, velocity)
+}
+
+/// A value animating towards a target on the frame clock.
+pub(crate) struct Spring {
+    /// The current value, for rendering.
+    value: Signal<f64>,
+    state: RefCell<SpringState>,
+}
+
+struct SpringState {
+    velocity: f64,
+    target: f64,
+    config: SpringConfig,
+    /// When the spring last moved, while it's moving.
+    last_frame: Option<Instant>,
+    /// Whether to jump to the target on the next frame (for reduced motion).
+    jump: bool,
+}
+
+impl Spring {
+    /// A spring at rest at `value`.
+    pub(crate) fn new(value: f64, config: SpringConfig) -> Rc<Self> {
+        Rc::new(Self {
+            value: Signal::new(value),
+            state: RefCell::new(SpringState {
+                velocity: 0.0,
+                target: value,
+                config,
+                last_frame: None,
+                jump: false,
+            }),
+        })
+    }
+
+    /// The current value. Reactive.
+    pub(crate) fn value(&self) -> f64 {
+        self.value.get()
+    }
+
+    /// Head for a new target, keeping the current value and velocity, so a
+    /// spring retargeted mid-flight curves smoothly towards the new one.
+    pub(crate) fn set_target(self: &Rc<Self>, target: f64, config: SpringConfig) {
+        let mut state = self.state.borrow_mut();
+        state.config = config;
+        if state.target == target {
+            return;
+        }
+        state.target = target;
+        state.jump = reduce_motion();
+        if state.last_frame.is_none() {
+            state.last_frame = Some(clock::now());
+            let spring = self.clone();
+            request_animation_frame(move |now| spring.frame(now));
+        }
+    }
+
+    fn frame(self: Rc<Self>, now: Instant) {
+        let mut state = self.state.borrow_mut();
+        let Some(last) = state.last_frame else {
+            return;
+        };
+        // After a stall, carry on from where the spring was
+        let dt = now
+            .duration_since(last)
+            .as_secs_f64()
+            .min(4.0 * FRAME_INTERVAL.as_secs_f64());
+        let (value, velocity) = step_spring(
+            self.value.with(|value| *value),
+            state.velocity,
+            state.target,
+            state.config,
+            dt,
+        );
+
+        let at_rest = state.jump
+            || ((value - state.target).abs() < REST_THRESHOLD && velocity.abs() < REST_THRESHOLD);
+        if at_rest {
+            state.velocity = 0.0;
+            state.last_frame = None;
+            let target = state.target;
+            drop(state);
+            self.value.set(target);
+        } else {
+            state.velocity = velocity;
+            state.last_frame = Some(now);
+            drop(state);
+            self.value.set(value);
+            let spring = self.clone();
+            request_animation_frame(move |now| spring.frame(now));
+        }
+    }
+}
+
+/// An easing curve, mapping a tween's progress (0 to 1) to how far along
+/// the value is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly.
+    QuadIn,
+    /// Ends slowly.
+    QuadOut,
+    /// Starts and ends slowly.
+    QuadInOut,
+    /// Starts slowly, more pronounced than `QuadIn`.
+    CubicIn,
+    /// Ends slowly, more pronounced than `QuadOut`. Good for things arriving.
+    #[default]
+    CubicOut,
+    /// Starts and ends slowly, more pronounced than `QuadInOut`.
+    CubicInOut,
+}
+
+impl Easing {
+    /// How far along the value is at progress `t` (clamped to 0..=1).
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A value [`use_animated`](crate::hooks::use_animated) can tween.
+pub trait Animatable: Clone + PartialEq + 'static {
+    /// The value `t` of the way from `self` to `to`.
+    fn lerp(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Animatable for f64 {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Animatable for f32 {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t as f32
+    }
+}
+
+impl<A: Animatable, B: Animatable> Animatable for (A, B) {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        (self.0.lerp(&to.0, t), self.1.lerp(&to.1, t))
+    }
+}
+
+/// An sRGB color with alpha, for animating colors.
+///
+/// Displays as a CSS `rgba()` color, so it can go straight into a style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// Opacity, from 0 to 1.
+    pub a: f32,
+}
+
+impl Color {
+    /// An opaque color.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    /// A color with opacity `a` (0 to 1).
+    pub const fn rgba(r: u8, g: u8, b: u8, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Parse a `#rgb`, `#rrggbb` or `#rrggbbaa` hex color.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize, len: usize| {
+            let digits = hex.get(i * len..(i + 1) * len)?;
+            let value = u8::from_str_radix(digits, 16).ok()?;
+            Some(if len == 1 { value * 17 } else { value })
+        };
+        match hex.len() {
+            3 => Some(Self::rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+            6 => Some(Self::rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+            8 => Some(Self::rgba(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                channel(3, 2)? as f32 / 255.0,
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        let channel = |from: u8, to: u8| (from as f64).lerp(&(to as f64), t).round() as u8;
+        Self {
+            r: channel(self.r, to.r),
+            g: channel(self.g, to.g),
+            b: channel(self.b, to.b),
+            a: self.a.lerp(&to.a, t),
+        }
+    }
+}
+
+/// A value tweening towards a target on the frame clock.
+pub(crate) struct Tween<T> {
+    /// The current value, for rendering.
+    value: Signal<T>,
+    state: RefCell<TweenState<T>>,
+}
+
+struct TweenState<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    easing: Easing,
+    /// When the current tween started, while it's running.
+    start: Option<Instant>,
+    /// Called when a tween reaches its target.
+    on_complete: Option<Rc<dyn Fn()>>,
+}
+
+impl<T: Animatable> Tween<T> {
+    /// A tween at rest at `value`.
+    pub(crate) fn new(value: T) -> Rc<Self> {
+        Rc::new(Self {
+            value: Signal::new(value.clone()),
+            state: RefCell::new(TweenState {
+                from: value.clone(),
+                to: value,
+                duration: Duration::ZERO,
+                easing: Easing::Linear,
+                start: None,
+                on_complete: None,
+            }),
+        })
+    }
+
+    /// The current value. Reactive.
+    pub(crate) fn value(&self) -> T {
+        self.value.get()
+    }
+
+    /// Head for a new target over `duration`, from wherever the value is
+    /// now. Doesn't restart when the target is unchanged.
+    pub(crate) fn set_target(
+        self: &Rc<Self>,
+        to: T,
+        duration: Duration,
+        easing: Easing,
+        on_complete: Option<Rc<dyn Fn()>>,
+    ) {
+        let mut state = self.state.borrow_mut();
+        state.on_complete = on_complete;
+        if state.to == to {
+            return;
+        }
+        state.from = self.value.with(T::clone);
+        state.to = to;
+        state.duration = if reduce_motion() {
+            Duration::ZERO
+        } else {
+            duration
+        };
+        state.easing = easing;
+        if state.start.replace(clock::now()).is_none() {
+            let tween = self.clone();
+            request_animation_frame(move |now| tween.frame(now));
+        }
+    }
+
+    fn frame(self: Rc<Self>, now: Instant) {
+        let state = self.state.borrow();
+        let Some(start) = state.start else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        let progress = if state.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f64() / state.duration.as_secs_f64()
+        };
+
+        if progress >= 1.0 {
+            let to = state.to.clone();
+            let on_complete = state.on_complete.clone();
+            drop(state);
+            self.state.borrow_mut().start = None;
+            self.value.set(to);
+            if let Some(on_complete) = on_complete {
+                on_complete();
+            }
+        } else {
+            let value = state.from.lerp(&state.to, state.easing.apply(progress));
+            drop(state);
+            self.value.set(value);
+            let tween = self.clone();
+            request_animation_frame(move |now| tween.frame(now));
+        }
+    }
+}
+
+/// Timing of an animation frame, passed to
+/// [`use_animation_frame`](crate::hooks::use_animation_frame) callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTime {
+    /// When the frame started.
+    pub now: Instant,
+    /// Time since the previous frame; zero on the first.
+    pub delta: Duration,
+    /// Time since the first frame since the callback started running.
+    pub elapsed: Duration,
+}
+
+/// A callback run every animation frame while it's running.
+pub(crate) struct FrameLoop {
+    callback: RefCell<Box<dyn FnMut(FrameTime)>>,
+    running: Cell<bool>,
+    /// Whether a frame has been requested for the loop.
+    scheduled: Cell<bool>,
+    /// When the loop's first and latest frames ran, since it started.
+    frames: Cell<Option<(Instant, Instant)>>,
+}
+
+impl FrameLoop {
+    /// A stopped loop.
+    pub(crate) fn new() -> Rc<Self> {
+        Rc::new(Self {
+            callback: RefCell::new(Box::new(|_| {})),
+            running: Cell::new(false),
+            scheduled: Cell::new(false),
+            frames: Cell::new(None),
+        })
+    }
+
+    /// Replace the callback, and start or stop the loop.
+    ///
+    /// Frames hold the loop weakly, so it stops once dropped.
+    pub(crate) fn update(self: &Rc<Self>, running: bool, callback: Box<dyn FnMut(FrameTime)>) {
+        *self.callback.borrow_mut() = callback;
+        self.running.set(running);
+        if !running {
+            self.frames.set(None);
+        } else if !self.scheduled.get() {
+            self.schedule();
+        }
+    }
+
+    fn schedule(self: &Rc<Self>) {
+        self.scheduled.set(true);
+        let frame_loop = Rc::downgrade(self);
+        request_animation_frame(move |now| {
+            if let Some(frame_loop) = Weak::upgrade(&frame_loop) {
+                frame_loop.frame(now);
+            }
+        });
+    }
+
+    fn frame(self: Rc<Self>, now: Instant) {
+        self.scheduled.set(false);
+        if !self.running.get() {
+            return;
+        }
+        let (first, last) = self.frames.get().unwrap_or((now, now));
+        self.frames.set(Some((first, now)));
+        (self.callback.borrow_mut())(FrameTime {
+            now,
+            delta: now.saturating_duration_since(last),
+            elapsed: now.saturating_duration_since(first),
+        });
+        if self.running.get() {
+            self.schedule();
+        }
+    }
+}
+
+/// Where an item of a [`use_transition_group`](crate::hooks::use_transition_group)
+/// is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionPhase {
+    /// Just added, for the group's duration.
+    Entering,
+    /// Settled in.
+    Entered,
+    /// Removed, but still shown for the group's duration so it can animate
+    /// out.
+    Exiting,
+}
+
+/// An item of a [`use_transition_group`](crate::hooks::use_transition_group),
+/// with its phase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionItem<T> {
+    pub item: T,
+    pub phase: TransitionPhase,
+}
+
+impl<T> TransitionItem<T> {
+    /// The class for the item's phase: `"enter"` while entering, `"exit"`
+    /// while exiting, and empty once entered.
+    pub fn class(&self) -> &'static str {
+        match self.phase {
+            TransitionPhase::Entering => "enter",
+            TransitionPhase::Entered => "",
+            TransitionPhase::Exiting => "exit",
+        }
+    }
+
+    /// Whether the item has been removed and is on its way out.
+    pub fn is_exiting(&self) -> bool {
+        self.phase == TransitionPhase::Exiting
+    }
+}
+
+/// A list whose removed items linger while they animate out.
+pub(crate) struct TransitionGroup<T, K> {
+    /// Items as last shown, with their phase and when it started.
+    entries: Vec<(K, TransitionItem<T>, Instant)>,
+    /// Whether the group has been shown, so first items don't animate in.
+    shown: bool,
+    /// Whether a frame is checking for phases ending.
+    checking: bool,
+}
+
+impl<T: Clone, K: PartialEq + Clone> TransitionGroup<T, K> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            shown: false,
+            checking: false,
+        }
+    }
+
+    /// Merge in the current items, keeping exiting ones next to their old
+    /// neighbours, and return what to show.
+    pub(crate) fn update(
+        &mut self,
+        items: Vec<T>,
+        key: impl Fn(&T) -> K,
+        duration: Duration,
+        now: Instant,
+    ) -> Vec<TransitionItem<T>> {
+        let next: Vec<(K, T)> = items.into_iter().map(|item| (key(&item), item)).collect();
+        let ended = |since: Instant| now.saturating_duration_since(since) >= duration;
+        // Without a duration, items appear and disappear at once
+        let entering = if self.shown && !duration.is_zero() {
+            TransitionPhase::Entering
+        } else {
+            TransitionPhase::Entered
+        };
+        self.shown = true;
+
+        let previous = std::mem::take(&mut self.entries);
+        let phase_of = |key: &K| match previous.iter().find(|(k, _, _)| k == key) {
+            Some((_, old, since)) if old.phase == TransitionPhase::Entering && !ended(*since) => {
+                (TransitionPhase::Entering, *since)
+            }
+            Some((_, old, _)) if old.phase != TransitionPhase::Exiting => {
+                (TransitionPhase::Entered, now)
+            }
+            // New, or back before it finished leaving
+            _ => (entering, now),
+        };
+
+        let next_keys: Vec<K> = next.iter().map(|(k, _)| k.clone()).collect();
+        let mut next = next;
+        let mut merged = Vec::with_capacity(next.len());
+        let push = |merged: &mut Vec<_>, key: K, item: T| {
+            let (phase, since) = phase_of(&key);
+            merged.push((key, TransitionItem { item, phase }, since));
+        };
+        for (key, old, since) in &previous {
+            if let Some(position) = next.iter().position(|(k, _)| k == key) {
+                for (k, item) in next.drain(..=position) {
+                    push(&mut merged, k, item);
+                }
+            } else if next_keys.contains(key) {
+                // Moved earlier, and already shown
+            } else if old.phase != TransitionPhase::Exiting && !duration.is_zero() {
+                let item = TransitionItem {
+                    item: old.item.clone(),
+                    phase: TransitionPhase::Exiting,
+                };
+                merged.push((key.clone(), item, now));
+            } else if !ended(*since) {
+                merged.push((key.clone(), old.clone(), *since));
+            }
+        }
+        for (k, item) in next {
+            push(&mut merged, k, item);
+        }
+
+        self.entries = merged;
+        self.entries
+            .iter()
+            .map(|(_, item, _)| item.clone())
+            .collect()
+    }
+
+    /// When the next entering or exiting phase ends.
+    fn next_deadline(&self, duration: Duration) -> Option<Instant> {
+        self.entries
+            .iter()
+            .filter(|(_, item, _)| item.phase != TransitionPhase::Entered)
+            .map(|(_, _, since)| *since + duration)
+            .min()
+    }
+}
+
+/// Re-render when a group's next phase ends. Checks run on paint frames, so
+/// waiting costs no renders.
+pub(crate) fn watch_transitions<T: Clone + 'static, K: PartialEq + Clone + 'static>(
+    group: &Rc<RefCell<TransitionGroup<T, K>>>,
+    duration: Duration,
+) {
+    let Some(deadline) = group.borrow().next_deadline(duration) else {
+        return;
+    };
+    if std::mem::replace(&mut group.borrow_mut().checking, true) {
+        return;
+    }
+    let group = Rc::downgrade(group);
+    fn check<T: Clone + 'static, K: PartialEq + Clone + 'static>(
+        group: Weak<RefCell<TransitionGroup<T, K>>>,
+        deadline: Instant,
+    ) {
+        request_paint_frame(move |now| {
+            let Some(strong) = group.upgrade() else {
+                return;
+            };
+            if now >= deadline {
+                strong.borrow_mut().checking = false;
+                // Re-render, which updates the group and watches again
+                request_animation_frame(|_| {});
+            } else {
+                check(group, deadline);
+            }
+        });
+    }
+    check(group, deadline);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_run_once_and_can_chain() {
+        let count = Rc::new(RefCell::new(0));
+        let counter = count.clone();
+        request_animation_frame(move |_| {
+            *counter.borrow_mut() += 1;
+            let counter = counter.clone();
+            request_animation_frame(move |_| *counter.borrow_mut() += 1);
+        });
+
+        assert!(animation_frame_requested());
+        assert!(run_animation_frame(Instant::now()));
+        assert_eq!(*count.borrow(), 1);
+        assert!(run_animation_frame(Instant::now()));
+        assert_eq!(*count.borrow(), 2);
+        assert!(!animation_frame_requested());
+        assert!(!run_animation_frame(Instant::now()));
+
+        // Paint frames run without re-rendering
+        let counter = count.clone();
+        request_paint_frame(move |_| *counter.borrow_mut() += 1);
+        assert!(animation_frame_requested());
+        assert!(!run_animation_frame(Instant::now()));
+        assert_eq!(*count.borrow(), 3);
+    }
+
+    #[test]
+    fn springs_settle_on_the_target() {
+        let (mut value, mut velocity) = (0.0, 0.0);
+        let mut peak: f64 = 0.0;
+        for _ in 0..120 {
+            (value, velocity) =
+                step_spring(value, velocity, 100.0, SpringConfig::WOBBLY, 1.0 / 60.0);
+            peak = peak.max(value);
+        }
+        assert!((value - 100.0).abs() < 0.5, "{value}");
+        // Light damping overshoots
+        assert!(peak > 110.0, "{peak}");
+
+        // Critically damped springs don't
+        let config = SpringConfig {
+            stiffness: 100.0,
+            damping: 20.0,
+        };
+        let (mut value, mut velocity) = (0.0, 0.0);
+        for _ in 0..120 {
+            (value, velocity) = step_spring(value, velocity, 100.0, config, 1.0 / 60.0);
+            assert!(value <= 100.0);
+        }
+    }
+
+    #[test]
+    fn springs_animate_on_frames_and_retarget() {
+        let spring = Spring::new(0.0, SpringConfig::STIFF);
+        spring.set_target(100.0, SpringConfig::STIFF);
+        assert_eq!(spring.value(), 0.0);
+
+        let start = Instant::now();
+        let mut frame = 0;
+        let mut run = |frames: u32| {
+            for _ in 0..frames {
+                frame += 1;
+                run_animation_frame(start + FRAME_INTERVAL * frame);
+            }
+        };
+        run(10);
+        let midway = spring.value();
+        assert!(midway > 0.0 && midway < 100.0, "{midway}");
+
+        // Retargeting keeps the value and momentum
+        spring.set_target(-50.0, SpringConfig::STIFF);
+        run(1);
+        assert!(spring.value() > midway - 10.0);
+        run(300);
+        assert_eq!(spring.value(), -50.0);
+        assert!(!animation_frame_requested());
+    }
+
+    #[test]
+    fn easing_curves_run_from_zero_to_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::CubicInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert_eq!(Easing::QuadInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::CubicOut.apply(0.5), 0.875);
+        assert!(Easing::CubicIn.apply(0.5) < Easing::Linear.apply(0.5));
+    }
+
+    #[test]
+    fn colors_parse_and_interpolate() {
+        assert_eq!(Color::from_hex("#f80"), Some(Color::rgb(255, 136, 0)));
+        assert_eq!(Color::from_hex("1e90ff"), Some(Color::rgb(30, 144, 255)));
+        assert_eq!(
+            Color::from_hex("#00000080"),
+            Some(Color::rgba(0, 0, 0, 128.0 / 255.0))
+        );
+        assert_eq!(Color::from_hex("#12345"), None);
+        assert_eq!(Color::from_hex("#ggg"), None);
+
+        let mid = Color::rgb(0, 0, 0).lerp(&Color::rgba(255, 100, 10, 0.0), 0.5);
+        assert_eq!(mid, Color::rgba(128, 50, 5, 0.5));
+        assert_eq!(mid.to_string(), "rgba(128, 50, 5, 0.5)");
+    }
+
+    #[test]
+    fn tweens_reach_the_target_and_complete() {
+        let tween = Tween::new(0.0);
+        let completed = Rc::new(RefCell::new(0));
+        let counter = completed.clone();
+        let on_complete: Rc<dyn Fn()> = Rc::new(move || *counter.borrow_mut() += 1);
+        tween.set_target(
+            100.0,
+            Duration::from_millis(100),
+            Easing::Linear,
+            Some(on_complete.clone()),
+        );
+
+        let start = tween.state.borrow().start.unwrap();
+        run_animation_frame(start + Duration::from_millis(25));
+        assert_eq!(tween.value(), 25.0);
+
+        // Retargeting starts from where the value is
+        tween.set_target(
+            0.0,
+            Duration::from_millis(100),
+            Easing::Linear,
+            Some(on_complete),
+        );
+        let start = tween.state.borrow().start.unwrap();
+        run_animation_frame(start + Duration::from_millis(50));
+        assert_eq!(tween.value(), 12.5);
+        assert_eq!(*completed.borrow(), 0);
+
+        run_animation_frame(start + Duration::from_millis(150));
+        assert_eq!(tween.value(), 0.0);
+        assert_eq!(*completed.borrow(), 1);
+        assert!(!animation_frame_requested());
+    }
+
+    #[test]
+    fn a_stopped_clock_starts_animations_on_time() {
+        let clock = clock::Clock::manual();
+        clock.advance(Duration::from_secs(60));
+        let start = clock.now();
+        let tween = Tween::new(0.0);
+        tween.set_target(10.0, Duration::from_millis(100), Easing::Linear, None);
+        drop(clock);
+
+        run_animation_frame(start + Duration::from_millis(30));
+        assert_eq!(tween.value(), 3.0);
+        run_animation_frame(start + Duration::from_millis(100));
+        assert_eq!(tween.value(), 10.0);
+    }
+
+    #[test]
+    fn frame_loops_run_until_stopped_or_dropped() {
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let frame_loop = FrameLoop::new();
+        let seen = frames.clone();
+        frame_loop.update(true, Box::new(move |time| seen.borrow_mut().push(time)));
+
+        let start = Instant::now();
+        run_animation_frame(start);
+        run_animation_frame(start + FRAME_INTERVAL);
+        run_animation_frame(start + FRAME_INTERVAL * 3);
+        let deltas: Vec<_> = frames.borrow().iter().map(|time| time.delta).collect();
+        assert_eq!(deltas, [Duration::ZERO, FRAME_INTERVAL, FRAME_INTERVAL * 2]);
+        assert_eq!(frames.borrow()[2].elapsed, FRAME_INTERVAL * 3);
+
+        // Stopping ends the loop; restarting counts from zero again
+        frame_loop.update(false, Box::new(|_| {}));
+        run_animation_frame(start + FRAME_INTERVAL * 4);
+        assert!(!animation_frame_requested());
+        let seen = frames.clone();
+        frame_loop.update(true, Box::new(move |time| seen.borrow_mut().push(time)));
+        run_animation_frame(start + FRAME_INTERVAL * 5);
+        assert_eq!(frames.borrow().len(), 4);
+        assert_eq!(frames.borrow()[3].elapsed, Duration::ZERO);
+
+        drop(frame_loop);
+        run_animation_frame(start + FRAME_INTERVAL * 6);
+        assert_eq!(frames.borrow().len(), 4);
+        assert!(!animation_frame_requested());
+    }
+
+    #[test]
+    fn transition_groups_keep_leaving_items_in_place() {
+        let duration = Duration::from_millis(200);
+        let start = Instant::now();
+        let mut group = TransitionGroup::new();
+        let phases = |items: Vec<TransitionItem<&'static str>>| -> Vec<_> {
+            items
+                .into_iter()
+                .map(|item| (item.item, item.class()))
+                .collect()
+        };
+
+        // The first items don't animate in
+        let shown = group.update(vec!["a", "b", "c"], |item| *item, duration, start);
+        assert_eq!(phases(shown), [("a", ""), ("b", ""), ("c", "")]);
+        assert_eq!(group.next_deadline(duration), None);
+
+        // Removing b keeps it in place while it exits; d enters
+        let later = start + Duration::from_millis(50);
+        let shown = group.update(vec!["a", "c", "d"], |item| *item, duration, later);
+        assert_eq!(
+            phases(shown),
+            [("a", ""), ("b", "exit"), ("c", ""), ("d", "enter")]
+        );
+        assert_eq!(group.next_deadline(duration), Some(later + duration));
+
+        // Once the duration has passed, b is gone and d has entered
+        let done = later + duration;
+        let shown = group.update(vec!["a", "c", "d"], |item| *item, duration, done);
+        assert_eq!(phases(shown), [("a", ""), ("c", ""), ("d", "")]);
+        assert_eq!(group.next_deadline(duration), None);
+    }
+
+    #[test]
+    fn transition_groups_handle_reorders_and_returns() {
+        let duration = Duration::from_millis(200);
+        let now = Instant::now();
+        let mut group = TransitionGroup::new();
+        group.update(vec![1, 2, 3], |item| *item, duration, now);
+
+        let shown = group.update(vec![3, 1], |item| *item, duration, now);
+        let order: Vec<_> = shown.iter().map(|item| (item.item, item.phase)).collect();
+        assert_eq!(
+            order,
+            [
+                (3, TransitionPhase::Entered),
+                (1, TransitionPhase::Entered),
+                (2, TransitionPhase::Exiting)
+            ]
+        );
+
+        // An item that comes back while leaving enters again
+        let shown = group.update(vec![3, 1, 2], |item| *item, duration, now);
+        assert_eq!(shown[2].phase, TransitionPhase::Entering);
+    }
+
+    #[test]
+    fn reduced_motion_skips_to_the_end() {
+        set_system_reduced_motion(true);
+        assert!(prefers_reduced_motion());
+        let now = Instant::now();
+
+        let spring = Spring::new(0.0, SpringConfig::WOBBLY);
+        spring.set_target(100.0, SpringConfig::WOBBLY);
+        let tween = Tween::new(0.0);
+        tween.set_target(1.0, Duration::from_secs(1), Easing::Linear, None);
+        run_animation_frame(now + FRAME_INTERVAL);
+        assert_eq!((spring.value(), tween.value()), (100.0, 1.0));
+        assert!(!animation_frame_requested());
+
+        // Unless asked to animate in full
+        with_full_motion(|| spring.set_target(0.0, SpringConfig::WOBBLY));
+        run_animation_frame(now + FRAME_INTERVAL * 2);
+        assert!(spring.value() > 0.0 && spring.value() < 100.0);
+        assert!(animation_frame_requested());
+
+        // The app's override wins over the OS setting
+        override_reduced_motion(Some(false));
+        assert!(!prefers_reduced_motion() && !reduce_motion());
+        override_reduced_motion(None);
+        assert!(prefers_reduced_motion());
+        set_system_reduced_motion(false);
+        assert!(!prefers_reduced_motion());
+    }
+
+    #[test]
+    fn transition_groups_without_a_duration_change_at_once() {
+        let mut group = TransitionGroup::new();
+        let now = Instant::now();
+        let key = |item: &&'static str| *item;
+        group.update(vec!["a", "b"], key, Duration::ZERO, now);
+        let shown = group.update(vec!["b", "c"], key, Duration::ZERO, now);
+        let shown: Vec<_> = shown.iter().map(|item| (item.item, item.phase)).collect();
+        assert_eq!(
+            shown,
+            [
+                ("b", TransitionPhase::Entered),
+                ("c", TransitionPhase::Entered)
+            ]
+        );
+        assert_eq!(group.next_deadline(Duration::ZERO), None);
+    }
+}
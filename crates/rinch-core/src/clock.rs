@@ -0,0 +1,294 @@
+//! The clock everything time-based reads.
+//!
+//! Timers ([`set_timeout`]), springs and tweens, transition groups, the
+//! frame clock behind CSS animations, and
+//! [`use_debounced`](crate::hooks::use_debounced) all get the time from
+//! [`now`]. It's the system clock unless a test takes over with
+//! [`Clock::manual`]: the clock then stands still until the test moves it
+//! with [`Clock::advance`], which fires timers as their time comes, so
+//! debounces and animations can be tested without sleeping.
+//!
+//! The clock is thread-local, like hooks and handlers.
+
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+/// A point in time: `std::time::Instant`, except in the browser, where std
+/// has no clock and `web_time` reads `performance.now()` instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+/// Shortest timer delay. As in browsers, a timer never fires in the instant
+/// it was set, so a timer that sets itself again can't hang the app.
+const MIN_DELAY: Duration = Duration::from_millis(1);
+
+/// A timer waiting to fire.
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    callback: Box<dyn FnOnce()>,
+}
+
+thread_local! {
+    /// The time, while a test controls the clock.
+    static MANUAL_NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+    static TIMERS: RefCell<Vec<Timer>> = const { RefCell::new(Vec::new()) };
+    static NEXT_TIMER_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The current time.
+pub fn now() -> Instant {
+    MANUAL_NOW.with(Cell::get).unwrap_or_else(Instant::now)
+}
+
+/// Identifies a timer, for [`clear_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+/// Run `callback` once, `delay` from now.
+///
+/// The callback runs outside render, so it can set signals; the app
+/// re-renders after it.
+///
+/// # Example
+///
+/// ```ignore
+/// let saved = use_signal(|| false);
+/// let onclick = move || {
+///     save();
+///     saved.set(true);
+///     set_timeout(Duration::from_secs(2), move || saved.set(false));
+/// };
+/// ```
+pub fn set_timeout(delay: Duration, callback: impl FnOnce() + 'static) -> TimerId {
+    let id = TimerId(NEXT_TIMER_ID.with(|next| next.replace(next.get() + 1)));
+    let timer = Timer {
+        id,
+        deadline: now() + delay.max(MIN_DELAY),
+        callback: Box::new(callback),
+    };
+    TIMERS.with(|timers| timers.borrow_mut().push(timer));
+    id
+}
+
+/// Cancel a timer that hasn't fired yet.
+pub fn clear_timeout(id: TimerId) {
+    TIMERS.with(|timers| timers.borrow_mut().retain(|timer| timer.id != id));
+}
+
+/// When the next timer is due (called by the runtime, to wake up for it).
+pub fn next_timer() -> Option<Instant> {
+    TIMERS.with(|timers| timers.borrow().iter().map(|timer| timer.deadline).min())
+}
+
+/// Fire the timers due at `now`, in order (called by the runtime). Returns
+/// whether any fired, in which case the app should re-render.
+pub fn run_timers(now: Instant) -> bool {
+    let mut fired = false;
+    while let Some(timer) = take_due_timer(now) {
+        (timer.callback)();
+        fired = true;
+    }
+    fired
+}
+
+/// Remove and return the earliest timer due at `now`.
+fn take_due_timer(now: Instant) -> Option<Timer> {
+    TIMERS.with(|timers| {
+        let mut timers = timers.borrow_mut();
+        let index = timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.deadline <= now)
+            .min_by_key(|(_, timer)| (timer.deadline, timer.id.0))
+            .map(|(index, _)| index)?;
+        Some(timers.remove(index))
+    })
+}
+
+/// Control of the thread's clock, for tests.
+///
+/// While a `Clock` exists, [`now`] stands still except when moved with
+/// [`advance`](Self::advance). Dropping it returns to the system clock.
+///
+/// # Example
+///
+/// ```ignore
+/// let clock = Clock::manual();
+/// let fired = Rc::new(Cell::new(false));
+/// let flag = fired.clone();
+/// set_timeout(Duration::from_millis(300), move || flag.set(true));
+/// clock.advance(Duration::from_millis(299));
+/// assert!(!fired.get());
+/// clock.advance(Duration::from_millis(1));
+/// assert!(fired.get());
+/// ```
+#[derive(Debug)]
+pub struct Clock {
+    /// The clock belongs to one thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Clock {
+    /// Stop the clock at the current time.
+    pub fn manual() -> Self {
+        MANUAL_NOW.with(|manual| manual.set(Some(Instant::now())));
+        Self {
+            _thread: PhantomData,
+        }
+    }
+
+    /// The time the clock stands at.
+    pub fn now(&self) -> Instant {
+        now()
+    }
+
+    /// Move the clock forward, firing the timers that come due, each with
+    /// the clock at its deadline. Returns whether any fired.
+    pub fn advance(&self, by: Duration) -> bool {
+        let end = now() + by;
+        let mut fired = false;
+        while let Some(timer) = take_due_timer(end) {
+            MANUAL_NOW.with(|manual| manual.set(Some(timer.deadline.max(now()))));
+            (timer.callback)();
+            fired = true;
+        }
+        MANUAL_NOW.with(|manual| manual.set(Some(end)));
+        fired
+    }
+}
+
+impl Drop for Clock {
+    fn drop(&mut self) {
+        MANUAL_NOW.with(|manual| manual.set(None));
+    }
+}
+
+/// State for [`use_debounced`](crate::hooks::use_debounced).
+pub(crate) struct Debounce<T> {
+    /// The value returned, which catches up once changes stop.
+    settled: RefCell<T>,
+    /// The latest value passed in.
+    latest: RefCell<T>,
+    timer: Cell<Option<TimerId>>,
+}
+
+impl<T: Clone + PartialEq + 'static> Debounce<T> {
+    pub(crate) fn new(value: T) -> Rc<Self> {
+        Rc::new(Self {
+            settled: RefCell::new(value.clone()),
+            latest: RefCell::new(value),
+            timer: Cell::new(None),
+        })
+    }
+
+    /// Note the value passed in this render, restarting the wait if it
+    /// changed.
+    ///
+    /// The timer holds the state weakly, so it does nothing once dropped.
+    pub(crate) fn update(self: &Rc<Self>, value: T, delay: Duration) {
+        if *self.latest.borrow() == value {
+            return;
+        }
+        if let Some(timer) = self.timer.take() {
+            clear_timeout(timer);
+        }
+        if *self.settled.borrow() != value {
+            let debounce = Rc::downgrade(self);
+            self.timer.set(Some(set_timeout(delay, move || {
+                if let Some(debounce) = Weak::upgrade(&debounce) {
+                    debounce.timer.set(None);
+                    let latest = debounce.latest.borrow().clone();
+                    *debounce.settled.borrow_mut() = latest;
+                }
+            })));
+        }
+        *self.latest.borrow_mut() = value;
+    }
+
+    pub(crate) fn value(&self) -> T {
+        self.settled.borrow().clone()
+    }
+}
+
+impl<T> Drop for Debounce<T> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            clear_timeout(timer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_fire_in_order_as_the_clock_advances() {
+        let clock = Clock::manual();
+        let start = clock.now();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        for (name, delay) in [("b", 200), ("a", 100), ("c", 300)] {
+            let fired = fired.clone();
+            set_timeout(Duration::from_millis(delay), move || {
+                fired.borrow_mut().push((name, now() - start));
+            });
+        }
+        let cancelled = set_timeout(Duration::from_millis(150), || panic!("cancelled"));
+        clear_timeout(cancelled);
+
+        assert!(!clock.advance(Duration::from_millis(99)));
+        assert!(clock.advance(Duration::from_millis(151)));
+        assert_eq!(
+            *fired.borrow(),
+            [
+                ("a", Duration::from_millis(100)),
+                ("b", Duration::from_millis(200))
+            ]
+        );
+        assert_eq!(clock.now() - start, Duration::from_millis(250));
+        assert_eq!(next_timer(), Some(start + Duration::from_millis(300)));
+        assert!(run_timers(start + Duration::from_millis(300)));
+        assert_eq!(next_timer(), None);
+    }
+
+    #[test]
+    fn timers_set_by_timers_fire_in_the_same_advance() {
+        let clock = Clock::manual();
+        let ticks = Rc::new(Cell::new(0));
+        fn tick(ticks: Rc<Cell<u32>>) {
+            ticks.set(ticks.get() + 1);
+            set_timeout(Duration::from_millis(100), move || tick(ticks));
+        }
+        let first = ticks.clone();
+        set_timeout(Duration::from_millis(100), move || tick(first));
+        clock.advance(Duration::from_millis(450));
+        assert_eq!(ticks.get(), 4);
+        TIMERS.with(|timers| timers.borrow_mut().clear());
+    }
+
+    #[test]
+    fn debounced_values_wait_for_changes_to_stop() {
+        let clock = Clock::manual();
+        let delay = Duration::from_millis(300);
+        let debounce = Debounce::new("");
+        for query in ["r", "ri", "rin"] {
+            debounce.update(query, delay);
+            clock.advance(Duration::from_millis(200));
+            assert_eq!(debounce.value(), "");
+        }
+        // Re-rendering with the same value doesn't restart the wait
+        debounce.update("rin", delay);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(debounce.value(), "rin");
+
+        // Changing back before the wait ends cancels it
+        debounce.update("rinch", delay);
+        debounce.update("rin", delay);
+        assert_eq!(next_timer(), None);
+    }
+}
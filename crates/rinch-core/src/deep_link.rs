@@ -0,0 +1,24 @@
+//! Deep links: URLs in the app's own scheme that the OS opened it with.
+//!
+//! The runtime records links from the command line it started with and
+//! from later launches forwarded to it, in the order they arrived. Apps
+//! read them with [`use_deep_links`](crate::hooks::use_deep_links), or all
+//! at once with [`deep_links`].
+
+use crate::reactive::Signal;
+
+thread_local! {
+    static LINKS: Signal<Vec<String>> = Signal::new(Vec::new());
+}
+
+/// Every deep link received so far, oldest first. Reactive: reading it
+/// during render re-renders the app when a link arrives.
+pub fn deep_links() -> Vec<String> {
+    LINKS.with(|links| links.get())
+}
+
+/// Record a deep link (called by the runtime).
+pub fn push_deep_link(url: impl Into<String>) {
+    let url = url.into();
+    LINKS.with(|links| links.update(|links| links.push(url)));
+}
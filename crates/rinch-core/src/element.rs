@@ -25,6 +25,28 @@ pub enum Element {
 
 pub type Children = Vec<Element>;
 
+/// Whether this is a phone or tablet build, where windows default to
+/// overlay scrollbars and overscroll bounce, as touch apps there do.
+pub const TOUCH_FIRST: bool = cfg!(any(target_os = "android", target_os = "ios"));
+
+/// Join a window's children into the HTML its document is built from.
+/// Menus and components render nothing.
+pub fn children_to_html(children: &[Element]) -> String {
+    let mut html = String::new();
+    for child in children {
+        match child {
+            Element::Html(content) => {
+                html.push_str(content);
+            }
+            Element::Fragment(kids) => {
+                html.push_str(&children_to_html(kids));
+            }
+            _ => {}
+        }
+    }
+    html
+}
+
 /// Properties for the Window component.
 #[derive(Debug, Clone)]
 pub struct WindowProps {
@@ -38,6 +60,22 @@ pub struct WindowProps {
     pub transparent: bool,
     pub always_on_top: bool,
     pub visible: bool,
+    /// Replace the default user-agent styles with a structural reset.
+    pub reset: bool,
+    /// Lay out the window's content right-to-left.
+    pub rtl: bool,
+    /// How the window's shapes and text are antialiased.
+    pub antialiasing: Antialiasing,
+    /// Draw thin scrollbars over the content that fade out when idle,
+    /// instead of scrollbars in a gutter beside it.
+    pub overlay_scrollbars: bool,
+    /// Let scrolled content stretch past its edges and spring back, like
+    /// on macOS and mobile.
+    pub overscroll_bounce: bool,
+    /// Scale of the content on top of the display's scale factor, like
+    /// browser zoom: 1.0 is 100%. Users change it with Ctrl+Plus/Minus/0
+    /// and Ctrl+wheel.
+    pub content_scale: f32,
 }
 
 impl Default for WindowProps {
@@ -53,10 +91,33 @@ impl Default for WindowProps {
             transparent: false,
             always_on_top: false,
             visible: true,
+            reset: false,
+            rtl: false,
+            antialiasing: Antialiasing::default(),
+            overlay_scrollbars: TOUCH_FIRST,
+            overscroll_bounce: TOUCH_FIRST,
+            content_scale: 1.0,
         }
     }
 }
 
+/// Antialiasing method for a window's rendering.
+///
+/// Text is rendered with grayscale antialiasing; these trade sharpness
+/// against speed and edge quality. This is the only text rendering setting:
+/// the GPU renderer has no subpixel antialiasing, hinting levels or gamma
+/// adjustment to expose.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Antialiasing {
+    /// Analytic area coverage. Crispest small text at 100% scale.
+    Area,
+    /// 8x multisampling. Faster than 16x, with coarser edges.
+    Msaa8,
+    /// 16x multisampling.
+    #[default]
+    Msaa16,
+}
+
 /// Properties for the AppMenu component.
 #[derive(Debug, Clone)]
 pub struct AppMenuProps {
@@ -100,11 +161,60 @@ impl std::fmt::Debug for MenuItemCallback {
     }
 }
 
+/// A menu item's keyboard shortcut, such as `"Cmd+S"` or `"Ctrl+Shift+Z"`.
+///
+/// Letters and punctuation match the key that types them on the user's
+/// keyboard layout, so `Cmd+Z` is the key labelled Z on AZERTY too. On
+/// layouts that don't type Latin letters, such as Cyrillic ones, they match
+/// the key in the same place as on a US keyboard, as digits and named keys
+/// always do. [`Shortcut::physical`] matches by place on every layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    /// Modifiers and key, joined with `+`.
+    pub keys: String,
+    /// Whether the key is matched by its place on the keyboard.
+    pub physical: bool,
+}
+
+impl Shortcut {
+    /// A shortcut for the key in the given place, whatever the layout types
+    /// there: `Shortcut::physical("Ctrl+KeyW")` is the key above S on every
+    /// keyboard, for shortcuts chosen for where keys sit. Keys are named as
+    /// on a US keyboard (`"W"` or `"KeyW"`, `"Digit1"`, `"Semicolon"`).
+    ///
+    /// Menus show the key the user's layout has there, once they've typed
+    /// on it.
+    pub fn physical(keys: impl Into<String>) -> Self {
+        Self {
+            keys: keys.into(),
+            physical: true,
+        }
+    }
+}
+
+impl From<&str> for Shortcut {
+    fn from(keys: &str) -> Self {
+        Self {
+            keys: keys.to_string(),
+            physical: false,
+        }
+    }
+}
+
+impl From<String> for Shortcut {
+    fn from(keys: String) -> Self {
+        Self {
+            keys,
+            physical: false,
+        }
+    }
+}
+
 /// Properties for a MenuItem.
 #[derive(Debug, Clone)]
 pub struct MenuItemProps {
     pub label: String,
-    pub shortcut: Option<String>,
+    pub shortcut: Option<Shortcut>,
     pub enabled: bool,
     pub checked: Option<bool>,
     /// Callback to invoke when the menu item is activated.
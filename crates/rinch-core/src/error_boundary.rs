@@ -0,0 +1,205 @@
+//! Error boundaries: a fallback shown in place of content that panicked.
+//!
+//! [`use_error_boundary`](crate::hooks::use_error_boundary), which the
+//! `ErrorBoundary` component expands to, renders its content with hooks of
+//! its own and catches a panic while it renders, or in an event handler it
+//! rendered. From then on it renders its fallback with the [`CaughtError`]
+//! instead, until [`CaughtError::reset`] renders the content again from
+//! scratch. The rest of the window keeps working; a panic outside any
+//! boundary still shows the crash panel.
+//!
+//! Handlers are matched to boundaries by their IDs: IDs count up through a
+//! render, so the handlers a boundary's content registers are the range of
+//! IDs taken while it rendered.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::{Rc, Weak};
+
+use crate::element::Element;
+use crate::events::{peek_handler_id, EventHandlerId};
+use crate::hooks::{render_with_hooks, HookRegistry};
+use crate::reactive::{runtime_mark, RuntimeMark};
+
+thread_local! {
+    /// The handler IDs each boundary's content registered this render,
+    /// inner boundaries before the ones around them.
+    static HANDLER_RANGES: RefCell<Vec<(Range<usize>, Weak<ErrorBoundary>)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// A panic caught by an error boundary, passed to its fallback.
+#[derive(Clone)]
+pub struct CaughtError {
+    message: String,
+    boundary: Weak<ErrorBoundary>,
+}
+
+impl CaughtError {
+    /// The panic message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Render the boundary's content again, with fresh hooks, in place of
+    /// the fallback.
+    pub fn reset(&self) {
+        if let Some(boundary) = self.boundary.upgrade() {
+            boundary.error.borrow_mut().take();
+        }
+    }
+}
+
+impl fmt::Display for CaughtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Debug for CaughtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaughtError")
+            .field("message", &self.message)
+            .finish_non_exhaustive()
+    }
+}
+
+/// State for [`use_error_boundary`](crate::hooks::use_error_boundary).
+#[derive(Default)]
+pub(crate) struct ErrorBoundary {
+    /// The message of the panic caught, until reset.
+    error: RefCell<Option<String>>,
+    /// The content's hooks, dropped when it panics.
+    hooks: RefCell<Option<HookRegistry>>,
+}
+
+impl ErrorBoundary {
+    /// The error caught, if the fallback should be shown.
+    pub(crate) fn error(self: &Rc<Self>) -> Option<CaughtError> {
+        let message = self.error.borrow().clone()?;
+        Some(CaughtError {
+            message,
+            boundary: Rc::downgrade(self),
+        })
+    }
+
+    /// Render `content` with its own hooks, catching a panic in it.
+    pub(crate) fn render(
+        self: &Rc<Self>,
+        content: impl FnOnce() -> Element,
+    ) -> Result<Element, CaughtError> {
+        let first = peek_handler_id().0;
+        let mark = runtime_mark();
+        let result =
+            panic::catch_unwind(AssertUnwindSafe(|| render_with_hooks(&self.hooks, content)));
+        let handlers = first..peek_handler_id().0;
+        HANDLER_RANGES.with(|ranges| ranges.borrow_mut().push((handlers, Rc::downgrade(self))));
+        result.map_err(|payload| {
+            self.fail(&*payload, mark);
+            self.error().expect("the boundary just failed")
+        })
+    }
+
+    /// Show the fallback for a panic with `payload`, putting the reactive
+    /// runtime back as it was at `mark`, before the panicking code ran.
+    fn fail(&self, payload: &(dyn std::any::Any + Send), mark: RuntimeMark) {
+        mark.restore();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "the component panicked".into());
+        *self.error.borrow_mut() = Some(message);
+        self.hooks.borrow_mut().take();
+    }
+}
+
+/// Run the handler with ID `id`, catching a panic in it if it was rendered
+/// inside an error boundary. Returns `None` if it panicked.
+pub(crate) fn run_handler<R>(id: EventHandlerId, handler: impl FnOnce() -> R) -> Option<R> {
+    let boundary = HANDLER_RANGES.with(|ranges| {
+        ranges
+            .borrow()
+            .iter()
+            .find(|(handlers, _)| handlers.contains(&id.0))
+            .and_then(|(_, boundary)| boundary.upgrade())
+    });
+    let Some(boundary) = boundary else {
+        return Some(handler());
+    };
+    let mark = runtime_mark();
+    panic::catch_unwind(AssertUnwindSafe(handler))
+        .map_err(|payload| boundary.fail(&*payload, mark))
+        .ok()
+}
+
+/// Forget which boundaries rendered which handlers, as the handlers are
+/// being cleared.
+pub(crate) fn clear_handler_ranges() {
+    HANDLER_RANGES.with(|ranges| ranges.borrow_mut().clear());
+}
+
+/// Panic with `error`'s message, for an `Err` the nearest error boundary
+/// should show. Its fallback gets the message as [`CaughtError::message`].
+///
+/// # Example
+///
+/// ```ignore
+/// let config = load_config().unwrap_or_else(|error| throw_error(error));
+/// ```
+pub fn throw_error(error: impl fmt::Display) -> ! {
+    panic::panic_any(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::events::{clear_handlers, dispatch_event, register_handler};
+    use crate::reactive::{batch, Effect, Signal};
+
+    #[test]
+    fn panics_in_content_and_handlers_show_the_fallback() {
+        clear_handlers();
+        let boundary = Rc::new(ErrorBoundary::default());
+        let error = boundary.render(|| throw_error("no rows")).unwrap_err();
+        assert_eq!(error.message(), "no rows");
+        assert!(boundary.error().is_some());
+
+        error.reset();
+        assert!(boundary.error().is_none());
+        let mut id = None;
+        let element = boundary.render(|| {
+            id = Some(register_handler(Box::new(|| panic!("clicked"))));
+            Element::Html("content".into())
+        });
+        assert!(element.is_ok());
+
+        assert!(dispatch_event(id.unwrap()));
+        assert_eq!(boundary.error().unwrap().message(), "clicked");
+        clear_handlers();
+    }
+
+    #[test]
+    fn a_caught_panic_leaves_the_effect_around_it_running() {
+        let boundary = Rc::new(ErrorBoundary::default());
+        let count = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+        let _effect = Effect::new({
+            let (boundary, count, runs) = (boundary.clone(), count.clone(), runs.clone());
+            move || {
+                runs.set(runs.get() + 1);
+                let _ = boundary.render(|| batch(|| throw_error("no rows")));
+                // Still read by this effect
+                count.get();
+            }
+        });
+
+        count.set(1);
+        assert_eq!(runs.get(), 2);
+        assert_eq!(boundary.error().unwrap().message(), "no rows");
+    }
+}
@@ -3,9 +3,11 @@
 //! This module provides the event handler registry that maps element IDs
 //! to Rust callbacks, enabling reactive event handling in the UI.
 
-use std::cell::RefCell;
+use crate::error_boundary::run_handler;
+use crate::reactive::batch;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 /// Escape HTML special characters in a string.
 ///
@@ -30,17 +32,182 @@ impl std::fmt::Display for EventHandlerId {
 /// Type alias for event handler callbacks.
 pub type EventCallback = Box<dyn Fn() + 'static>;
 
-/// Global counter for generating unique event handler IDs.
-static NEXT_HANDLER_ID: AtomicUsize = AtomicUsize::new(0);
+/// Scroll position and extent of a scroll container.
+///
+/// Passed to `onscroll` handlers. All values are in logical pixels.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ScrollEvent {
+    /// Horizontal scroll offset.
+    pub scroll_x: f64,
+    /// Vertical scroll offset.
+    pub scroll_y: f64,
+    /// Width of the scrollable content.
+    pub content_width: f64,
+    /// Height of the scrollable content.
+    pub content_height: f64,
+    /// Width of the visible area.
+    pub client_width: f64,
+    /// Height of the visible area.
+    pub client_height: f64,
+}
+
+impl ScrollEvent {
+    /// Furthest the container can scroll vertically.
+    pub fn max_scroll_y(&self) -> f64 {
+        (self.content_height - self.client_height).max(0.0)
+    }
+
+    /// Furthest the container can scroll horizontally.
+    pub fn max_scroll_x(&self) -> f64 {
+        (self.content_width - self.client_width).max(0.0)
+    }
+
+    /// How far down the container is scrolled, from 0 at the top to 1 at
+    /// the bottom. 0 if it can't scroll.
+    pub fn progress_y(&self) -> f64 {
+        progress(self.scroll_y, self.max_scroll_y())
+    }
+
+    /// How far across the container is scrolled, from 0 at the start to 1
+    /// at the end. 0 if it can't scroll.
+    pub fn progress_x(&self) -> f64 {
+        progress(self.scroll_x, self.max_scroll_x())
+    }
+
+    /// Whether the container is scrolled to the bottom, to within a pixel,
+    /// or can't scroll. Chat views check this before new messages arrive to
+    /// decide whether to follow them down.
+    pub fn is_at_bottom(&self) -> bool {
+        self.scroll_y >= self.max_scroll_y() - 1.0
+    }
+}
+
+fn progress(offset: f64, max: f64) -> f64 {
+    if max > 0.0 {
+        (offset / max).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Type alias for `onscroll` handler callbacks.
+pub type ScrollCallback = Box<dyn Fn(ScrollEvent) + 'static>;
+
+/// Pointer position during a drag.
+///
+/// Passed to `ondragstart`, `ondrag` and `ondragend` handlers. All values
+/// are in logical pixels, in window coordinates.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct DragEvent {
+    /// Where the pointer is now.
+    pub x: f64,
+    /// Where the pointer is now.
+    pub y: f64,
+    /// Where the pointer was pressed.
+    pub start_x: f64,
+    /// Where the pointer was pressed.
+    pub start_y: f64,
+}
+
+impl DragEvent {
+    /// How far the pointer has moved since it was pressed.
+    pub fn delta(&self) -> (f64, f64) {
+        (self.x - self.start_x, self.y - self.start_y)
+    }
+}
+
+/// Type alias for `ondragstart`, `ondrag` and `ondragend` handler callbacks.
+pub type DragCallback = Box<dyn Fn(DragEvent) + 'static>;
+
+/// Type alias for `oninput`, `onchange` and `bind` handler callbacks, which
+/// are passed a text field's value.
+pub type InputCallback = Box<dyn Fn(String) + 'static>;
+
+/// A key pressed or released while an element, or one inside it, has focus.
+///
+/// Passed to `onkeydown`, `onkeyup` and `onkeypress` handlers. `key` and
+/// `code` use the web's names.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct KeyboardEvent {
+    /// What the key means: the text it types with the current modifiers
+    /// (`"a"`, `"A"`, `"?"`), or its name (`"Enter"`, `"ArrowLeft"`,
+    /// `"Escape"`, `"F2"`).
+    pub key: String,
+    /// Where the key is, whatever the keyboard layout (`"KeyA"`,
+    /// `"Digit1"`, `"Enter"`).
+    pub code: String,
+    /// Whether Ctrl is held.
+    pub ctrl: bool,
+    /// Whether Alt (Option on macOS) is held.
+    pub alt: bool,
+    /// Whether Shift is held.
+    pub shift: bool,
+    /// Whether the Windows or Cmd key is held.
+    pub meta: bool,
+    /// Whether this is a repeat from the key being held down.
+    pub repeat: bool,
+}
+
+impl KeyboardEvent {
+    /// Whether Ctrl or Cmd is held, for shortcuts that use Ctrl on Windows
+    /// and Linux and Cmd on macOS.
+    pub fn ctrl_or_cmd(&self) -> bool {
+        self.ctrl || self.meta
+    }
+}
+
+/// Type alias for `onkeydown`, `onkeyup` and `onkeypress` handler callbacks.
+pub type KeyCallback = Box<dyn Fn(KeyboardEvent) + 'static>;
+
+/// An image on the clipboard.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ClipboardImage {
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// RGBA pixel data, 4 bytes per pixel, row by row.
+    pub rgba: Arc<[u8]>,
+}
+
+/// What's being copied, cut or pasted.
+///
+/// Passed to `oncopy`, `oncut` and `onpaste` handlers, which run when
+/// Ctrl+C, Ctrl+X or Ctrl+V (Cmd on macOS) is pressed while the element, or
+/// one inside it, has focus.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ClipboardEvent {
+    /// The selected text being copied or cut, or the text on the clipboard
+    /// being pasted.
+    pub text: Option<String>,
+    /// The image on the clipboard being pasted. Always `None` for copy and
+    /// cut.
+    pub image: Option<ClipboardImage>,
+}
+
+/// Type alias for `oncopy`, `oncut` and `onpaste` handler callbacks.
+pub type ClipboardCallback = Box<dyn Fn(ClipboardEvent) + 'static>;
+
+thread_local! {
+    /// Counter for generating unique event handler IDs. Error boundaries
+    /// find their handlers by the IDs taken while they rendered, so each
+    /// thread counts its own.
+    static NEXT_HANDLER_ID: Cell<usize> = const { Cell::new(0) };
+}
 
 /// Generate a new unique event handler ID.
 pub fn next_handler_id() -> EventHandlerId {
-    EventHandlerId(NEXT_HANDLER_ID.fetch_add(1, Ordering::SeqCst))
+    EventHandlerId(NEXT_HANDLER_ID.replace(NEXT_HANDLER_ID.get() + 1))
+}
+
+/// The ID the next handler registered will get.
+pub(crate) fn peek_handler_id() -> EventHandlerId {
+    EventHandlerId(NEXT_HANDLER_ID.get())
 }
 
 /// Reset the handler ID counter (useful for testing or re-rendering).
 pub fn reset_handler_ids() {
-    NEXT_HANDLER_ID.store(0, Ordering::SeqCst);
+    NEXT_HANDLER_ID.set(0);
 }
 
 // Thread-local event handler registry.
@@ -51,12 +218,22 @@ thread_local! {
 /// Registry that maps event handler IDs to callbacks.
 pub struct EventRegistry {
     handlers: HashMap<EventHandlerId, EventCallback>,
+    scroll_handlers: HashMap<EventHandlerId, ScrollCallback>,
+    drag_handlers: HashMap<EventHandlerId, DragCallback>,
+    input_handlers: HashMap<EventHandlerId, InputCallback>,
+    key_handlers: HashMap<EventHandlerId, KeyCallback>,
+    clipboard_handlers: HashMap<EventHandlerId, ClipboardCallback>,
 }
 
 impl EventRegistry {
     fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            scroll_handlers: HashMap::new(),
+            drag_handlers: HashMap::new(),
+            input_handlers: HashMap::new(),
+            key_handlers: HashMap::new(),
+            clipboard_handlers: HashMap::new(),
         }
     }
 }
@@ -82,13 +259,155 @@ pub fn register_handler(callback: EventCallback) -> EventHandlerId {
     id
 }
 
-/// Dispatch an event to the handler with the given ID.
+/// Dispatch an event to the handler with the given ID, in a
+/// [`batch`](crate::reactive::batch) like every handler, so effects run once
+/// however many signals it sets. A panic in a handler rendered inside an
+/// error boundary is caught, and the boundary shows its fallback.
 ///
 /// Returns `true` if a handler was found and called, `false` otherwise.
 pub fn dispatch_event(id: EventHandlerId) -> bool {
     EVENT_REGISTRY.with(|registry| {
         if let Some(handler) = registry.borrow().handlers.get(&id) {
-            handler();
+            run_handler(id, || batch(handler));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `onscroll` handler and return its ID.
+///
+/// The handler will be called when an element with the corresponding
+/// `data-onscroll` attribute scrolls.
+pub fn register_scroll_handler(callback: ScrollCallback) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().scroll_handlers.insert(id, callback);
+    });
+    id
+}
+
+/// Dispatch a scroll event to the `onscroll` handler with the given ID.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_scroll_event(id: EventHandlerId, event: ScrollEvent) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().scroll_handlers.get(&id) {
+            run_handler(id, || batch(|| handler(event)));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `ondragstart`, `ondrag` or `ondragend` handler and return
+/// its ID.
+///
+/// The handler will be called when an element with the corresponding
+/// `data-ondragstart`, `data-ondrag` or `data-ondragend` attribute is
+/// dragged.
+pub fn register_drag_handler(callback: DragCallback) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().drag_handlers.insert(id, callback);
+    });
+    id
+}
+
+/// Dispatch a drag event to the handler with the given ID.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_drag_event(id: EventHandlerId, event: DragEvent) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().drag_handlers.get(&id) {
+            run_handler(id, || batch(|| handler(event)));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `oninput`, `onchange` or `bind` handler and return its ID.
+///
+/// The handler will be called with a text field's value when the field
+/// with the corresponding `data-oninput`, `data-onchange` or `data-bind`
+/// attribute is edited.
+pub fn register_input_handler(callback: InputCallback) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().input_handlers.insert(id, callback);
+    });
+    id
+}
+
+/// Dispatch a text field's value to the handler with the given ID.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_input_event(id: EventHandlerId, value: String) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().input_handlers.get(&id) {
+            run_handler(id, || batch(|| handler(value)));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `onkeydown`, `onkeyup` or `onkeypress` handler and return
+/// its ID.
+///
+/// The handler will be called when a key is pressed or released while the
+/// element with the corresponding `data-onkeydown`, `data-onkeyup` or
+/// `data-onkeypress` attribute, or an element inside it, has focus.
+pub fn register_key_handler(callback: KeyCallback) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry.borrow_mut().key_handlers.insert(id, callback);
+    });
+    id
+}
+
+/// Dispatch a keyboard event to the handler with the given ID.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_key_event(id: EventHandlerId, event: KeyboardEvent) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().key_handlers.get(&id) {
+            run_handler(id, || batch(|| handler(event)));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Register an `oncopy`, `oncut` or `onpaste` handler and return its ID.
+///
+/// The handler will be called when text is copied, cut or pasted while the
+/// element with the corresponding `data-oncopy`, `data-oncut` or
+/// `data-onpaste` attribute, or an element inside it, has focus.
+pub fn register_clipboard_handler(callback: ClipboardCallback) -> EventHandlerId {
+    let id = next_handler_id();
+    EVENT_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .clipboard_handlers
+            .insert(id, callback);
+    });
+    id
+}
+
+/// Dispatch a clipboard event to the handler with the given ID.
+///
+/// Returns `true` if a handler was found and called, `false` otherwise.
+pub fn dispatch_clipboard_event(id: EventHandlerId, event: ClipboardEvent) -> bool {
+    EVENT_REGISTRY.with(|registry| {
+        if let Some(handler) = registry.borrow().clipboard_handlers.get(&id) {
+            run_handler(id, || batch(|| handler(event)));
             true
         } else {
             false
@@ -101,14 +420,30 @@ pub fn dispatch_event(id: EventHandlerId) -> bool {
 /// This should be called before re-rendering to avoid stale handlers.
 pub fn clear_handlers() {
     EVENT_REGISTRY.with(|registry| {
-        registry.borrow_mut().handlers.clear();
+        let mut registry = registry.borrow_mut();
+        registry.handlers.clear();
+        registry.scroll_handlers.clear();
+        registry.drag_handlers.clear();
+        registry.input_handlers.clear();
+        registry.key_handlers.clear();
+        registry.clipboard_handlers.clear();
     });
     reset_handler_ids();
+    crate::error_boundary::clear_handler_ranges();
+    crate::lifecycle::clear_lifecycle_handlers();
 }
 
 /// Get the number of registered handlers (for debugging).
 pub fn handler_count() -> usize {
-    EVENT_REGISTRY.with(|registry| registry.borrow().handlers.len())
+    EVENT_REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        registry.handlers.len()
+            + registry.scroll_handlers.len()
+            + registry.drag_handlers.len()
+            + registry.input_handlers.len()
+            + registry.key_handlers.len()
+            + registry.clipboard_handlers.len()
+    })
 }
 
 #[cfg(test)]
@@ -133,6 +468,28 @@ mod tests {
         assert!(called.get());
     }
 
+    #[test]
+    fn test_handlers_run_in_a_batch() {
+        use crate::reactive::{Effect, Signal};
+        clear_handlers();
+
+        let first = Signal::new(0);
+        let second = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+        let (first_clone, second_clone, runs_clone) = (first.clone(), second.clone(), runs.clone());
+        Effect::new(move || {
+            let _ = first_clone.get() + second_clone.get();
+            runs_clone.set(runs_clone.get() + 1);
+        });
+
+        let id = register_handler(Box::new(move || {
+            first.set(1);
+            second.set(2);
+        }));
+        assert!(dispatch_event(id));
+        assert_eq!(runs.get(), 2);
+    }
+
     #[test]
     fn test_dispatch_unknown_id() {
         clear_handlers();
@@ -152,4 +509,144 @@ mod tests {
         assert_eq!(handler_count(), 0);
         assert!(!dispatch_event(id));
     }
+
+    #[test]
+    fn test_scroll_handlers() {
+        clear_handlers();
+
+        let seen = Rc::new(Cell::new(0.0));
+        let seen_clone = seen.clone();
+        let id = register_scroll_handler(Box::new(move |event| {
+            seen_clone.set(event.scroll_y);
+        }));
+
+        let event = ScrollEvent {
+            scroll_y: 42.0,
+            content_height: 500.0,
+            client_height: 200.0,
+            ..Default::default()
+        };
+        assert!(dispatch_scroll_event(id, event));
+        assert_eq!(seen.get(), 42.0);
+        assert_eq!(event.max_scroll_y(), 300.0);
+
+        // Click handlers and scroll handlers don't share IDs.
+        assert!(!dispatch_event(id));
+
+        clear_handlers();
+        assert!(!dispatch_scroll_event(id, event));
+    }
+
+    #[test]
+    fn test_drag_handlers() {
+        clear_handlers();
+
+        let seen = Rc::new(Cell::new((0.0, 0.0)));
+        let seen_clone = seen.clone();
+        let id = register_drag_handler(Box::new(move |event| {
+            seen_clone.set(event.delta());
+        }));
+
+        let event = DragEvent {
+            x: 30.0,
+            y: 15.0,
+            start_x: 10.0,
+            start_y: 20.0,
+        };
+        assert!(dispatch_drag_event(id, event));
+        assert_eq!(seen.get(), (20.0, -5.0));
+        assert!(!dispatch_event(id));
+
+        clear_handlers();
+        assert!(!dispatch_drag_event(id, event));
+    }
+
+    #[test]
+    fn test_input_handlers() {
+        clear_handlers();
+
+        let seen = Rc::new(RefCell::new(String::new()));
+        let seen_clone = seen.clone();
+        let id = register_input_handler(Box::new(move |value| {
+            *seen_clone.borrow_mut() = value;
+        }));
+
+        assert!(dispatch_input_event(id, "hello".into()));
+        assert_eq!(*seen.borrow(), "hello");
+        assert!(!dispatch_event(id));
+
+        clear_handlers();
+        assert!(!dispatch_input_event(id, "again".into()));
+    }
+
+    #[test]
+    fn test_key_handlers() {
+        clear_handlers();
+
+        let seen = Rc::new(RefCell::new(String::new()));
+        let seen_clone = seen.clone();
+        let id = register_key_handler(Box::new(move |event| {
+            *seen_clone.borrow_mut() = event.key;
+        }));
+
+        let event = KeyboardEvent {
+            key: "Enter".into(),
+            code: "Enter".into(),
+            ..Default::default()
+        };
+        assert!(dispatch_key_event(id, event.clone()));
+        assert_eq!(*seen.borrow(), "Enter");
+        assert!(!dispatch_input_event(id, "Enter".into()));
+
+        clear_handlers();
+        assert!(!dispatch_key_event(id, event));
+    }
+
+    #[test]
+    fn test_clipboard_handlers() {
+        clear_handlers();
+
+        let seen = Rc::new(RefCell::new(None));
+        let seen_clone = seen.clone();
+        let id = register_clipboard_handler(Box::new(move |event| {
+            *seen_clone.borrow_mut() = event.text;
+        }));
+
+        let event = ClipboardEvent {
+            text: Some("pasted".into()),
+            image: None,
+        };
+        assert!(dispatch_clipboard_event(id, event.clone()));
+        assert_eq!(seen.borrow().as_deref(), Some("pasted"));
+        assert!(!dispatch_key_event(id, KeyboardEvent::default()));
+
+        clear_handlers();
+        assert!(!dispatch_clipboard_event(id, event));
+    }
+
+    #[test]
+    fn scroll_progress_runs_from_zero_to_one() {
+        let event = ScrollEvent {
+            scroll_y: 150.0,
+            content_height: 500.0,
+            client_height: 200.0,
+            ..Default::default()
+        };
+        assert_eq!(event.progress_y(), 0.5);
+        // Overscroll is clamped, and containers that can't scroll are at 0
+        let past_end = ScrollEvent {
+            scroll_y: 400.0,
+            ..event
+        };
+        assert_eq!(past_end.progress_y(), 1.0);
+        assert_eq!(event.progress_x(), 0.0);
+
+        assert!(!event.is_at_bottom());
+        assert!(ScrollEvent {
+            scroll_y: 299.5,
+            ..event
+        }
+        .is_at_bottom());
+        assert!(ScrollEvent::default().is_at_bottom());
+    }
 }
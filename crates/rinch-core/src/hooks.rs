@@ -38,7 +38,23 @@
 //! | [`use_signal`] | Reactive state that triggers re-renders |
 //! | [`use_state`] | Simple state with React-style `(value, setter)` API |
 //! | [`use_ref`] | Mutable reference that doesn't trigger re-renders |
-//! | [`use_effect`] | Side effects that run when dependencies change |
+//! | [`use_node_ref`] | Reference to a rendered element (e.g. its scroll offset) |
+//! | [`use_infinite_scroll`] | Load more content as a scroll container nears its end |
+//! | [`use_element_size`] | An element's own size, updated after layout |
+//! | [`use_visibility`] | Whether, and how much of, an element is visible |
+//! | [`use_scroll_progress`] | How far a scroll container is scrolled, from 0 to 1 |
+//! | [`use_spring`] | A value that springs towards a target, animated per frame |
+//! | [`use_animated`] | A value that tweens to each new value along an easing curve |
+//! | [`use_animation_frame`] | A callback run every animation frame |
+//! | [`use_transition_group`] | A list whose items animate in and out |
+//! | [`use_keyed_list`] | A list whose items keep their own signal and scope |
+//! | [`use_debounced`] | A value that catches up once it stops changing |
+//! | [`use_hotkey`] | A keyboard shortcut that isn't a menu item |
+//! | [`use_lazy`] | A subtree built the first time it's shown (`Lazy` in `rsx!`) |
+//! | [`use_error_boundary`] | A fallback in place of content that panicked (`ErrorBoundary` in `rsx!`) |
+//! | [`use_store`] | State whose fields are tracked one by one |
+//! | [`use_effect`] | Side effects run after the render, with cleanups |
+//! | [`use_effect_with`] | Side effects that run when dependencies change |
 //! | [`use_effect_cleanup`] | Effects with cleanup functions |
 //! | [`use_mount`] | One-time effect on first render |
 //! | [`use_memo`] | Memoized expensive computations |
@@ -239,10 +255,10 @@
 //!     let render_count = use_ref(|| 0);
 //!     *render_count.borrow_mut() += 1;
 //!
-//!     // Side effect that runs when count changes
-//!     use_effect(|| {
+//!     // Side effect that runs again when count changes
+//!     use_effect(move || {
 //!         println!("Count is now: {}", count.get());
-//!     }, count.get());
+//!     });
 //!
 //!     // One-time setup on mount
 //!     use_mount(|| {
@@ -282,10 +298,26 @@
 //! }
 //! ```
 
-use crate::reactive::{Memo, Signal};
+use crate::animation::{
+    reduce_motion, watch_transitions, Animatable, Easing, FrameLoop, FrameTime, Spring,
+    SpringConfig, TransitionGroup, TransitionItem, Tween,
+};
+use crate::clock::{self, Debounce};
+use crate::element::Element;
+use crate::error_boundary::{CaughtError, ErrorBoundary};
+use crate::hot_state::{self, SavedHook};
+use crate::keyed_list::{KeyedItem, KeyedList};
+use crate::node_ref::{Margins, NodeRef, Visibility};
+use crate::reactive::{self, Memo, Signal};
+use crate::resource::Resource;
+use crate::store::Store;
+use crate::suspense;
 use std::any::{Any, TypeId};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::time::Duration;
 
 // ============================================================================
 // Hook Registry
@@ -298,6 +330,10 @@ pub struct HookMeta {
     pub hook_type: &'static str,
     /// The type of value stored (from std::any::type_name)
     pub value_type: &'static str,
+    /// The hook's current value, formatted with `Debug`. Only filled in by
+    /// [`get_hooks_debug_info`], for signals, memos and refs of common
+    /// types (numbers, strings, `bool`, and options and vectors of them).
+    pub value: Option<String>,
 }
 
 /// Internal storage for a single hook.
@@ -321,6 +357,9 @@ pub struct HookRegistry {
     expected_count: Option<usize>,
     /// Number of completed renders (for debugging)
     render_count: usize,
+    /// Hooks saved by the process this one replaced, whose values new hooks
+    /// take during the first render
+    restoring: Vec<SavedHook>,
 }
 
 impl HookRegistry {
@@ -332,6 +371,7 @@ impl HookRegistry {
             is_rendering: false,
             expected_count: None,
             render_count: 0,
+            restoring: Vec::new(),
         }
     }
 
@@ -361,6 +401,7 @@ impl HookRegistry {
         }
 
         // Remember hook count for next render
+        self.restoring.clear();
         self.expected_count = Some(self.current_index);
         self.is_rendering = false;
         self.render_count += 1;
@@ -412,9 +453,11 @@ impl HookRegistry {
         } else {
             // First render - create new hook
             let value = init();
+            self.restore(index, hook_type, &value);
             let meta = HookMeta {
                 hook_type,
                 value_type: std::any::type_name::<T>(),
+                value: None,
             };
 
             self.hooks.push(HookEntry {
@@ -426,6 +469,22 @@ impl HookRegistry {
         }
     }
 
+    /// Give a new hook the value saved for it, if it's the same kind of
+    /// hook holding the same type. Once one isn't, the hooks after it may
+    /// have moved, so none of them are restored.
+    fn restore<T: 'static>(&mut self, index: usize, hook_type: &'static str, value: &T) {
+        let Some(saved) = self.restoring.get(index) else {
+            return;
+        };
+        if saved.hook_type != hook_type || saved.value_type != std::any::type_name::<T>() {
+            self.restoring.clear();
+            return;
+        }
+        if let Some(text) = &saved.value {
+            hot_state::load_value(value, text);
+        }
+    }
+
     /// Clear all hooks (for app restart).
     fn clear(&mut self) {
         self.hooks.clear();
@@ -433,6 +492,7 @@ impl HookRegistry {
         self.is_rendering = false;
         self.expected_count = None;
         self.render_count = 0;
+        self.restoring.clear();
     }
 }
 
@@ -553,9 +613,12 @@ fn clear_context() {
 ///
 /// This resets the hook index to 0 so hooks are called in order.
 pub fn begin_render() {
-    HOOK_REGISTRY.with(|registry| {
-        registry.borrow_mut().begin_render();
+    let render = HOOK_REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.begin_render();
+        registry.render_count
     });
+    reactive::set_render(Some(render));
 }
 
 /// End a render cycle. Call this after running the app function.
@@ -563,11 +626,67 @@ pub fn begin_render() {
 /// This validates that the hook count matches the previous render
 /// and updates internal state.
 pub fn end_render() {
+    reactive::set_render(None);
     HOOK_REGISTRY.with(|registry| {
         registry.borrow_mut().end_render();
     });
 }
 
+thread_local! {
+    /// Hook registries of roots rendered apart from the app, by root ID.
+    static ROOT_REGISTRIES: RefCell<HashMap<u64, HookRegistry>> = RefCell::new(HashMap::new());
+    /// The root [`render_root`] is rendering, if any.
+    static CURRENT_ROOT: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// The root being rendered: `None` for the app, or the ID passed to
+/// [`render_root`].
+pub(crate) fn current_root() -> Option<u64> {
+    CURRENT_ROOT.with(Cell::get)
+}
+
+/// Render a root of its own, such as a window opened with a component,
+/// running `render` between [`begin_render`] and [`end_render`].
+///
+/// The root's hooks are kept apart from the app's and other roots', so
+/// roots can come and go without disturbing anyone's hook order. Contexts
+/// are shared with the app.
+pub fn render_root<T>(root: u64, render: impl FnOnce() -> T) -> T {
+    /// Puts the app's registry back, even if `render` panics.
+    struct Swap {
+        root: u64,
+        app: Option<HookRegistry>,
+        outer_root: Option<u64>,
+    }
+
+    impl Drop for Swap {
+        fn drop(&mut self) {
+            let app = self.app.take().unwrap_or_default();
+            let root = HOOK_REGISTRY.with(|registry| registry.replace(app));
+            ROOT_REGISTRIES.with(|roots| roots.borrow_mut().insert(self.root, root));
+            CURRENT_ROOT.with(|current| current.set(self.outer_root));
+        }
+    }
+
+    let registry = ROOT_REGISTRIES
+        .with(|roots| roots.borrow_mut().remove(&root))
+        .unwrap_or_default();
+    let _swap = Swap {
+        root,
+        app: Some(HOOK_REGISTRY.with(|current| current.replace(registry))),
+        outer_root: CURRENT_ROOT.with(|current| current.replace(Some(root))),
+    };
+    begin_render();
+    let rendered = render();
+    end_render();
+    rendered
+}
+
+/// Drop a root's hooks once it's gone, such as when its window closes.
+pub fn drop_root(root: u64) {
+    ROOT_REGISTRIES.with(|roots| roots.borrow_mut().remove(&root));
+}
+
 /// Clear all hook state. Call this when restarting the app.
 ///
 /// This also clears all context values created with `create_context`.
@@ -575,24 +694,105 @@ pub fn clear_hooks() {
     HOOK_REGISTRY.with(|registry| {
         registry.borrow_mut().clear();
     });
+    ROOT_REGISTRIES.with(|roots| roots.borrow_mut().clear());
     clear_context();
 }
 
+/// Save the app's hooks, with the values of signals and refs of types
+/// kept on reload, for [`restore_hooks`] in the process hot reload starts
+/// next. See [`hot_state`](crate::hot_state).
+pub fn save_hooks() -> Vec<SavedHook> {
+    HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .hooks
+            .iter()
+            .map(|entry| SavedHook {
+                hook_type: entry.meta.hook_type.to_string(),
+                value_type: entry.meta.value_type.to_string(),
+                value: hot_state::save_value(&*entry.value),
+            })
+            .collect()
+    })
+}
+
+/// Give the hooks created by the next render the values [`save_hooks`]
+/// saved. Call it after [`clear_hooks`], before the first render.
+pub fn restore_hooks(saved: Vec<SavedHook>) {
+    HOOK_REGISTRY.with(|registry| registry.borrow_mut().restoring = saved);
+}
+
 /// Get debug information about registered hooks.
 ///
-/// Returns a list of HookMeta describing each registered hook.
-/// Useful for devtools inspection.
+/// Returns a list of HookMeta describing each registered hook, with its
+/// current value where it can be shown. Useful for devtools inspection.
 pub fn get_hooks_debug_info() -> Vec<HookMeta> {
     HOOK_REGISTRY.with(|registry| {
         registry
             .borrow()
             .hooks
             .iter()
-            .map(|entry| entry.meta.clone())
+            .map(|entry| HookMeta {
+                value: describe_value(&*entry.value),
+                ..entry.meta.clone()
+            })
             .collect()
     })
 }
 
+/// Format a hook's value for debugging, if it's a signal, memo or ref
+/// holding a common type. Reads don't subscribe the caller.
+fn describe_value(value: &dyn Any) -> Option<String> {
+    macro_rules! describe {
+        ($($ty:ty),* $(,)?) => {$(
+            if let Some(value) = value.downcast_ref::<$ty>() {
+                return Some(format!("{value:?}"));
+            }
+            if let Some(signal) = value.downcast_ref::<Signal<$ty>>() {
+                return Some(reactive::untracked(|| signal.with(|value| format!("{value:?}"))));
+            }
+            if let Some(memo) = value.downcast_ref::<Memo<$ty>>() {
+                return Some(format!("{:?}", reactive::untracked(|| memo.get())));
+            }
+            if let Some(cell) = value.downcast_ref::<std::rc::Rc<RefCell<$ty>>>() {
+                return cell.try_borrow().ok().map(|value| format!("{:?}", *value));
+            }
+        )*};
+    }
+    describe!(
+        bool,
+        char,
+        i8,
+        i16,
+        i32,
+        i64,
+        isize,
+        u8,
+        u16,
+        u32,
+        u64,
+        usize,
+        f32,
+        f64,
+        String,
+        &'static str,
+        Option<bool>,
+        Option<i32>,
+        Option<i64>,
+        Option<usize>,
+        Option<f64>,
+        Option<String>,
+        Vec<bool>,
+        Vec<i32>,
+        Vec<i64>,
+        Vec<usize>,
+        Vec<f64>,
+        Vec<String>,
+        (f64, f64),
+    );
+    None
+}
+
 // ============================================================================
 // Public API - Hook functions
 // ============================================================================
@@ -652,26 +852,694 @@ pub fn use_state<T: Clone + 'static>(init: impl FnOnce() -> T) -> (T, impl Fn(T)
 
 /// Create or retrieve a mutable reference that persists across renders.
 ///
-/// Unlike signals, refs don't trigger re-renders when mutated. Use them
-/// for values that need to persist but shouldn't cause UI updates.
+/// Unlike signals, refs don't trigger re-renders when mutated. Use them
+/// for values that need to persist but shouldn't cause UI updates.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let render_count = use_ref(|| 0);
+///     render_count.borrow_mut().map(|mut n| *n += 1);
+///
+///     // render_count changes don't cause re-renders
+/// }
+/// ```
+pub fn use_ref<T: Clone + 'static>(init: impl FnOnce() -> T) -> RefHandle<T> {
+    let cell = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_ref", || std::rc::Rc::new(RefCell::new(init())))
+    });
+    RefHandle { inner: cell }
+}
+
+/// Create or retrieve a reference to a rendered element.
+///
+/// Pass it to an element's `node_ref` prop, then read the element's state
+/// through it. Reads are reactive, so the app re-renders when they change.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let list = use_node_ref();
+///     let (_, y) = list.scroll_offset();
+///
+///     rsx! {
+///         div { "Scrolled " {y} "px" }
+///         div { class: "list", node_ref: list, /* ... */ }
+///     }
+/// }
+/// ```
+pub fn use_node_ref() -> NodeRef {
+    HOOK_REGISTRY.with(|registry| registry.borrow_mut().use_hook("use_node_ref", NodeRef::new))
+}
+
+/// The content-box size of the element a node ref is attached to, as
+/// `(width, height)` in logical pixels.
+///
+/// Reactive: the app re-renders when layout resizes the element, so a
+/// component can pick its layout from its own size rather than the window's.
+/// `(0, 0)` until the element has been laid out. Avoid layouts that flip
+/// back and forth at a threshold (e.g. a toolbar whose overflow menu makes it
+/// wide enough not to need one), as each change renders again.
+///
+/// # Example
+///
+/// ```ignore
+/// fn toolbar() -> Element {
+///     let bar = use_node_ref();
+///     let (width, _) = use_element_size(&bar);
+///     let compact = width > 0.0 && width < 480.0;
+///
+///     rsx! {
+///         div { class: if compact { "toolbar compact" } else { "toolbar" }, node_ref: bar,
+///             /* ... */
+///         }
+///     }
+/// }
+/// ```
+pub fn use_element_size(node_ref: &NodeRef) -> (f64, f64) {
+    node_ref.size()
+}
+
+/// Whether, and how much of, the element a node ref is attached to is
+/// visible.
+///
+/// `root` is the scroll container whose visible area counts (by its ref), or
+/// `None` for the window, in which case scroll containers between the element
+/// and the window clip it too. `margins` grow (or, negative, shrink) the
+/// root's visible area. Reactive: the app re-renders when the element's
+/// visibility changes, whether through scrolling, resizing, or layout.
+/// Invisible until first laid out.
+///
+/// # Example
+///
+/// ```ignore
+/// fn photo(src: &str) -> Element {
+///     let img = use_node_ref();
+///     // Start loading a screen's worth of scrolling early
+///     let near = use_visibility(&img, None, Margins::all(600.0)).visible;
+///     let loaded = use_signal(|| false);
+///     if near && !loaded.get() {
+///         loaded.set(true);
+///     }
+///
+///     rsx! {
+///         img { node_ref: img, src: if loaded.get() { src } else { "placeholder.png" } }
+///     }
+/// }
+/// ```
+pub fn use_visibility(node_ref: &NodeRef, root: Option<&NodeRef>, margins: Margins) -> Visibility {
+    node_ref.watch_visibility(root, margins);
+    node_ref.visibility()
+}
+
+/// Load more content when a scroll container nears its end.
+///
+/// Calls `on_load_more` during render once the container is scrolled to
+/// within `threshold` logical pixels of its bottom. It fires once per content
+/// height: after it has loaded more, the content grows and it can fire again
+/// as soon as the user nears the new end. A container that still doesn't fill
+/// up keeps loading, so a short first page tops itself up. If nothing more
+/// arrives, it waits until the user scrolls away from the end and back.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let items = use_signal(Vec::<Item>::new);
+///     let loading = use_signal(|| false);
+///     let feed = use_node_ref();
+///
+///     use_infinite_scroll(&feed, 200.0, || {
+///         if !loading.get() {
+///             loading.set(true);
+///             fetch_page(items.get().len(), move |page| {
+///                 items.update(|items| items.extend(page));
+///                 loading.set(false);
+///             });
+///         }
+///     });
+///
+///     rsx! {
+///         div { class: "feed", node_ref: feed,
+///             /* render items */
+///         }
+///     }
+/// }
+/// ```
+pub fn use_infinite_scroll(container: &NodeRef, threshold: f64, on_load_more: impl FnOnce()) {
+    // Content height the last load was triggered at
+    let fired_at = HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().use_hook("use_infinite_scroll", || {
+            std::rc::Rc::new(Cell::new(None::<f64>))
+        })
+    });
+
+    let scroll = container.scroll_state();
+    if scroll.content_height <= 0.0 {
+        // Not rendered yet
+        return;
+    }
+    if scroll.max_scroll_y() - scroll.scroll_y > threshold {
+        fired_at.set(None);
+    } else if fired_at.get() != Some(scroll.content_height) {
+        fired_at.set(Some(scroll.content_height));
+        on_load_more();
+    }
+}
+
+/// How far a scroll container has been scrolled down, from 0 at the top to
+/// 1 at the bottom (0 if it can't scroll).
+///
+/// Reactive: the app re-renders as the container scrolls, so this suits
+/// reading progress (a progress bar, a "back to top" button that appears
+/// halfway down). For effects that follow every pixel, such as parallax,
+/// bind an animated style with [`NodeRef::bind_scroll`] instead, which
+/// doesn't re-render.
+///
+/// # Example
+///
+/// ```ignore
+/// let article = use_node_ref();
+/// let progress = use_scroll_progress(&article);
+///
+/// rsx! {
+///     div { class: "reading-progress", style: format!("width: {}%", progress * 100.0) }
+///     div { class: "article", node_ref: article, /* ... */ }
+/// }
+/// ```
+pub fn use_scroll_progress(container: &NodeRef) -> f64 {
+    container.scroll_state().progress_y()
+}
+
+/// A value that springs towards `target`.
+///
+/// Starts at the first `target`. When the target changes, the value moves
+/// towards it on the animation frame clock, re-rendering the app each frame
+/// until it settles, with `config` deciding how quickly and how bouncily
+/// (see [`SpringConfig`]). A target that changes mid-flight is picked up
+/// without a jolt, keeping the value's momentum. Use the value for anything
+/// a style can take: sizes, offsets, opacity, transforms.
+///
+/// When the user prefers reduced motion, the value jumps to each new target
+/// on the next frame (see [`with_full_motion`](crate::animation::with_full_motion)).
+///
+/// # Example
+///
+/// ```ignore
+/// fn drawer() -> Element {
+///     let open = use_signal(|| false);
+///     let offset = use_spring(if open.get() { 0.0 } else { -320.0 }, SpringConfig::default());
+///
+///     rsx! {
+///         button { onclick: move || open.update(|open| *open = !*open), "Menu" }
+///         nav { class: "drawer", style: format!("transform: translateX({offset}px)"),
+///             /* ... */
+///         }
+///     }
+/// }
+/// ```
+pub fn use_spring(target: f64, config: SpringConfig) -> f64 {
+    let spring = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_spring", || Spring::new(target, config))
+    });
+    spring.set_target(target, config);
+    spring.value()
+}
+
+/// A value that tweens to `value` over `duration` whenever it changes.
+///
+/// Starts at the first `value`. When `value` changes, the result
+/// interpolates from wherever it is towards the new one along the `easing`
+/// curve, re-rendering the app each animation frame until it arrives.
+/// Numbers, [`Color`]s and pairs of them can be animated (anything
+/// [`Animatable`]). When the user prefers reduced motion, the value arrives
+/// on the next frame.
+///
+/// [`Color`]: crate::animation::Color
+///
+/// # Example
+///
+/// ```ignore
+/// fn card(selected: bool) -> Element {
+///     let background = use_animated(
+///         if selected { Color::rgb(30, 144, 255) } else { Color::rgb(255, 255, 255) },
+///         Duration::from_millis(200),
+///         Easing::CubicOut,
+///     );
+///
+///     rsx! {
+///         div { class: "card", style: format!("background-color: {background}"),
+///             /* ... */
+///         }
+///     }
+/// }
+/// ```
+pub fn use_animated<T: Animatable>(value: T, duration: Duration, easing: Easing) -> T {
+    animated(value, duration, easing, None)
+}
+
+/// Like [`use_animated`], calling `on_complete` whenever the value arrives
+/// at its target.
+///
+/// `on_complete` runs after the last frame of a tween, outside render, so it
+/// can set signals (to remove an element once it has faded out, say). A
+/// tween that's interrupted by a new target doesn't complete; the new one
+/// does.
+///
+/// # Example
+///
+/// ```ignore
+/// let closing = use_signal(|| false);
+/// let open = use_signal(|| true);
+/// let opacity = use_animated_then(
+///     if closing.get() { 0.0 } else { 1.0 },
+///     Duration::from_millis(150),
+///     Easing::QuadOut,
+///     move || if closing.get() { open.set(false) },
+/// );
+/// ```
+pub fn use_animated_then<T: Animatable>(
+    value: T,
+    duration: Duration,
+    easing: Easing,
+    on_complete: impl Fn() + 'static,
+) -> T {
+    animated(value, duration, easing, Some(std::rc::Rc::new(on_complete)))
+}
+
+fn animated<T: Animatable>(
+    value: T,
+    duration: Duration,
+    easing: Easing,
+    on_complete: Option<std::rc::Rc<dyn Fn()>>,
+) -> T {
+    let tween = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_animated", || Tween::new(value.clone()))
+    });
+    tween.set_target(value, duration, easing, on_complete);
+    tween.value()
+}
+
+/// Run `callback` every animation frame while `running`.
+///
+/// The callback gets the frame's [`FrameTime`]: when it started, the time
+/// since the previous frame, and the time since the callback started
+/// running. It runs on the same frame clock as the renderer, before the
+/// app re-renders for the frame, so signals it sets show up in that frame.
+/// Use it for physics, games, or custom animation that doesn't fit
+/// [`use_spring`] or [`use_animated`].
+///
+/// Each render passes the latest callback, so it can capture current
+/// values. Frames stop when `running` is false, and when the hook's state
+/// is dropped. While running, the app re-renders every frame, so stop when
+/// there's nothing to animate.
+///
+/// # Example
+///
+/// ```ignore
+/// fn ball() -> Element {
+///     let y = use_signal(|| 0.0);
+///     let velocity = use_signal(|| 0.0);
+///     let bouncing = use_signal(|| true);
+///
+///     use_animation_frame(bouncing.get(), move |frame| {
+///         let dt = frame.delta.as_secs_f64();
+///         velocity.update(|v| *v += 2000.0 * dt);
+///         y.update(|y| *y += velocity.get() * dt);
+///         if y.get() > 400.0 {
+///             y.set(400.0);
+///             velocity.update(|v| *v *= -0.7);
+///             if velocity.get().abs() < 50.0 {
+///                 bouncing.set(false);
+///             }
+///         }
+///     });
+///
+///     rsx! { div { class: "ball", style: format!("top: {}px", y.get()) } }
+/// }
+/// ```
+pub fn use_animation_frame(running: bool, callback: impl FnMut(FrameTime) + 'static) {
+    let frame_loop = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_animation_frame", FrameLoop::new)
+    });
+    frame_loop.update(running, Box::new(callback));
+}
+
+/// A list whose removed items stay for `duration` so they can animate out.
+///
+/// Pass the current items and how to key them (keys must be unique and stay
+/// with their item). Returns the items to render, each with its
+/// [`TransitionPhase`](crate::animation::TransitionPhase): new items are
+/// entering for `duration`, and removed ones are kept in their old place,
+/// exiting, for `duration` before they're dropped. Items present on the
+/// first render don't animate in. [`TransitionItem::class`] gives `"enter"`
+/// or `"exit"` to hang CSS animations on. When the user prefers reduced
+/// motion, items come and go at once.
+///
+/// # Example
+///
+/// ```ignore
+/// fn toasts(messages: Vec<Toast>) -> Element {
+///     let shown = use_transition_group(messages, |toast| toast.id, Duration::from_millis(300));
+///
+///     rsx! {
+///         div { class: "toasts",
+///             {shown.iter().map(|toast| rsx! {
+///                 div { class: format!("toast {}", toast.class()), {toast.item.text.clone()} }
+///             }).collect::<Vec<_>>()}
+///         }
+///     }
+/// }
+/// ```
+///
+/// ```css
+/// .toast.enter { animation: slide-in 300ms ease-out; }
+/// .toast.exit { animation: fade-out 300ms ease-in forwards; }
+/// ```
+pub fn use_transition_group<T, K>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> K,
+    duration: Duration,
+) -> Vec<TransitionItem<T>>
+where
+    T: Clone + 'static,
+    K: PartialEq + Clone + 'static,
+{
+    let group = HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().use_hook("use_transition_group", || {
+            std::rc::Rc::new(RefCell::new(TransitionGroup::new()))
+        })
+    });
+    let duration = if reduce_motion() {
+        Duration::ZERO
+    } else {
+        duration
+    };
+    let shown = group
+        .borrow_mut()
+        .update(items, key, duration, clock::now());
+    watch_transitions(&group, duration);
+    shown
+}
+
+/// A list whose items keep their own [`Signal`] and [`Scope`](reactive::Scope)
+/// from render to render, matched by key.
+///
+/// Pass the current items and how to key them (keys must be unique and stay
+/// with their item). Returns the items in order, each with a signal that's
+/// only set when a render passes a different item for its key, so effects
+/// and memos reading one row don't re-run when another row is edited.
+/// Effects created in an item's [`scope`](crate::keyed_list::KeyedItem::scope)
+/// are disposed of when the item leaves the list.
+///
+/// # Example
+///
+/// ```ignore
+/// fn todo_list(todos: Vec<Todo>) -> Element {
+///     let rows = use_keyed_list(todos, |todo| todo.id);
+///
+///     rsx! {
+///         ul {
+///             {rows.iter().map(|row| rsx! {
+///                 li { key: row.key, {row.item.with(|todo| todo.title.clone())} }
+///             }).collect::<Vec<_>>()}
+///         }
+///     }
+/// }
+/// ```
+pub fn use_keyed_list<T, K>(items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<KeyedItem<K, T>>
+where
+    T: PartialEq + 'static,
+    K: Eq + Hash + Clone + 'static,
+{
+    let list = HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().use_hook("use_keyed_list", || {
+            std::rc::Rc::new(RefCell::new(KeyedList::default()))
+        })
+    });
+    list.borrow_mut().update(items, key)
+}
+
+/// `value`, once it has stopped changing for `delay`.
+///
+/// While `value` keeps changing, this returns what it was before the
+/// changes started; `delay` after the last change, the app re-renders with
+/// the latest value. Use it to act on input once the user pauses, rather
+/// than on every keystroke.
+///
+/// # Example
+///
+/// ```ignore
+/// fn search() -> Element {
+///     let query = use_signal(String::new);
+///     let settled = use_debounced(query.get(), Duration::from_millis(300));
+///     let results = use_memo(|| find(&settled), settled.clone());
+///     // ...
+/// }
+/// ```
+pub fn use_debounced<T: Clone + PartialEq + 'static>(value: T, delay: Duration) -> T {
+    let debounce = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_debounced", || Debounce::new(value.clone()))
+    });
+    debounce.update(value, delay);
+    debounce.value()
+}
+
+/// The deep links that arrived since this hook last returned, oldest first.
+///
+/// Each link is returned by one render only, so handle it there (such as
+/// by finishing a sign-in with the code an OAuth redirect carries). That
+/// includes the link the app was launched with, on its first render.
+/// Reactive: the app re-renders when a link arrives. See
+/// [`deep_link`](crate::deep_link).
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let session = use_signal(|| None::<Session>);
+///     for url in use_deep_links() {
+///         if let Some(code) = url.strip_prefix("myapp://auth?code=") {
+///             session.set(Some(exchange_code(code)));
+///         }
+///     }
+///     // ...
+/// }
+/// ```
+pub fn use_deep_links() -> Vec<String> {
+    let seen = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_deep_links", || std::rc::Rc::new(Cell::new(0)))
+    });
+    let links = crate::deep_link::deep_links();
+    let new = links[seen.get().min(links.len())..].to_vec();
+    seen.set(links.len());
+    new
+}
+
+/// Whether the user hasn't used the app for `threshold` or longer. Reactive:
+/// the app re-renders when the time passes, and when the user comes back.
+/// See [`power`](crate::power).
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let locked = use_signal(|| false);
+///     if use_idle(Duration::from_secs(5 * 60)) && !locked.get() {
+///         locked.set(true);
+///     }
+///     // ...
+/// }
+/// ```
+pub fn use_idle(threshold: Duration) -> bool {
+    let watch = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_idle", crate::power::IdleWatch::new)
+    });
+    watch.check(threshold)
+}
+
+/// Run `callback` when `shortcut` is pressed in this component's window,
+/// for shortcuts that aren't menu items, such as a command palette's
+/// `"Ctrl+K"`. See [`hotkey`](crate::hotkey).
+///
+/// Shortcuts are written as for a `MenuItem` (`"Ctrl+Shift+P"`, `"Alt+1"`,
+/// `"F2"`, `"Escape"`), and Ctrl and Cmd are the same. A menu item with the
+/// same shortcut takes it instead. Every matching hotkey of the window
+/// runs. Shortcuts without Ctrl, Cmd or Alt don't fire while a text field
+/// has focus, so they don't get in the way of typing.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let palette_open = use_signal(|| false);
+///     let toggle = palette_open.clone();
+///     use_hotkey("Ctrl+K", move || toggle.update(|open| *open = !*open));
+///     // ...
+/// }
+/// ```
+pub fn use_hotkey(shortcut: &str, callback: impl Fn() + 'static) {
+    let hotkey = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_hotkey", crate::hotkey::Hotkey::new)
+    });
+    hotkey.update(shortcut, std::rc::Rc::new(callback));
+}
+
+/// Defer building an expensive subtree until it's first shown.
+///
+/// `Lazy` in `rsx!` expands to this. While `when` is false and the subtree
+/// hasn't been shown, nothing is rendered and `loader` isn't called, so the
+/// subtree's signals and effects don't exist yet. The first render with
+/// `when` true shows `placeholder` and loads the subtree on the next one,
+/// so the window appears before the subtree is built. From then on
+/// `loader` runs every render `when` is true; while it's false, nothing is
+/// rendered but the subtree keeps its state. Inside a `Suspense`, the
+/// boundary's fallback is shown in place of `placeholder`.
+///
+/// The subtree's hooks are kept apart from the app's, so `loader` can call
+/// hooks even though it only runs some of the time. `use_lazy` itself is a
+/// hook, so it must be called every render.
 ///
 /// # Example
 ///
 /// ```ignore
 /// fn app() -> Element {
-///     let render_count = use_ref(|| 0);
-///     render_count.borrow_mut().map(|mut n| *n += 1);
-///
-///     // render_count changes don't cause re-renders
+///     let tab = use_signal(|| 0);
+///     rsx! {
+///         Window { title: "Editor",
+///             Lazy { when: tab.get() == 0, loader: || editor_tab() }
+///             Lazy {
+///                 when: tab.get() == 1,
+///                 placeholder: rsx! { div { class: "spinner" } },
+///                 loader: || reports_tab(),
+///             }
+///         }
+///     }
 /// }
 /// ```
-pub fn use_ref<T: Clone + 'static>(init: impl FnOnce() -> T) -> RefHandle<T> {
-    let cell = HOOK_REGISTRY.with(|registry| {
+pub fn use_lazy(when: bool, placeholder: Element, loader: impl FnOnce() -> Element) -> Element {
+    let lazy = HOOK_REGISTRY.with(|registry| {
         registry
             .borrow_mut()
-            .use_hook("use_ref", || std::rc::Rc::new(RefCell::new(init())))
+            .use_hook("use_lazy", || std::rc::Rc::new(Lazy::default()))
     });
-    RefHandle { inner: cell }
+    if !when {
+        lazy.cancel_load();
+        return Element::Fragment(Vec::new());
+    }
+    if lazy.hooks.borrow().is_none() && !lazy.ready.get() {
+        lazy.schedule_load();
+        suspense::mark_pending();
+        return placeholder;
+    }
+    lazy.render(loader)
+}
+
+/// State for [`use_lazy`].
+#[derive(Default)]
+struct Lazy {
+    /// Whether the subtree loads on the next render.
+    ready: Cell<bool>,
+    /// The timer that makes it ready, after the placeholder is shown.
+    timer: Cell<Option<clock::TimerId>>,
+    /// The subtree's hooks, once it has been loaded.
+    hooks: RefCell<Option<HookRegistry>>,
+}
+
+impl Lazy {
+    /// Load the subtree on the render after this one. The timer holds the
+    /// state weakly, so it does nothing once dropped.
+    fn schedule_load(self: &std::rc::Rc<Self>) {
+        if self.timer.get().is_some() {
+            return;
+        }
+        let lazy = std::rc::Rc::downgrade(self);
+        let timer = clock::set_timeout(Duration::ZERO, move || {
+            if let Some(lazy) = lazy.upgrade() {
+                lazy.timer.set(None);
+                lazy.ready.set(true);
+            }
+        });
+        self.timer.set(Some(timer));
+    }
+
+    /// Stop a load that's waiting, as the subtree was hidden again first.
+    fn cancel_load(&self) {
+        if let Some(timer) = self.timer.take() {
+            clock::clear_timeout(timer);
+        }
+        self.ready.set(false);
+    }
+
+    /// Run `loader` with the subtree's own hooks in place of the app's.
+    fn render(&self, loader: impl FnOnce() -> Element) -> Element {
+        render_with_hooks(&self.hooks, loader)
+    }
+}
+
+/// Run `render` with the hooks in `hooks` in place of the ones being
+/// rendered, keeping them for next time. Starts with fresh hooks if there
+/// are none.
+pub(crate) fn render_with_hooks(
+    hooks: &RefCell<Option<HookRegistry>>,
+    render: impl FnOnce() -> Element,
+) -> Element {
+    let mut own = hooks.borrow_mut().take().unwrap_or_default();
+    own.begin_render();
+    let outer = HOOK_REGISTRY.with(|registry| registry.replace(own));
+    // Put the outer hooks back even if `render` panics; its own hooks are
+    // then dropped, so it starts over on the next render.
+    let mut restore = RestoreHooks(Some(outer));
+    let element = render();
+    let mut own = restore.restore();
+    own.end_render();
+    *hooks.borrow_mut() = Some(own);
+    element
+}
+
+impl Drop for Lazy {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            clock::clear_timeout(timer);
+        }
+    }
+}
+
+/// Puts a hook registry back in place when dropped.
+struct RestoreHooks(Option<HookRegistry>);
+
+impl RestoreHooks {
+    /// Put the registry back, returning the one it replaced.
+    fn restore(&mut self) -> HookRegistry {
+        let outer = self.0.take().expect("hooks restored twice");
+        HOOK_REGISTRY.with(|registry| registry.replace(outer))
+    }
+}
+
+impl Drop for RestoreHooks {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            self.restore();
+        }
+    }
 }
 
 /// Handle to a ref value created by `use_ref`.
@@ -704,6 +1572,162 @@ impl<T: Clone> RefHandle<T> {
     }
 }
 
+/// An effect from [`use_effect`], held by its hook.
+struct CommitEffect {
+    /// The effect, from the latest render.
+    body: RefCell<std::rc::Rc<dyn Fn()>>,
+    /// Cleanups [`on_cleanup`] registered the last time the effect ran.
+    cleanups: RefCell<Vec<Box<dyn FnOnce()>>>,
+    /// Tracks the signals the effect reads.
+    effect: RefCell<Option<reactive::Effect>>,
+    /// Whether it's waiting in `COMMIT_QUEUE`.
+    queued: Cell<bool>,
+}
+
+thread_local! {
+    /// Effects waiting for the render that mounted them, or followed a
+    /// change to the signals they read, to be on screen.
+    static COMMIT_QUEUE: RefCell<Vec<std::rc::Weak<CommitEffect>>> =
+        const { RefCell::new(Vec::new()) };
+    /// Whether [`run_effects`] is about to run an effect, rather than one of
+    /// its signals having changed.
+    static COMMITTING: Cell<bool> = const { Cell::new(false) };
+    /// Cleanups registered by the effect running, if one is.
+    static CLEANUPS: RefCell<Option<Vec<Box<dyn FnOnce()>>>> = const { RefCell::new(None) };
+}
+
+impl CommitEffect {
+    /// An effect queued to run after the render that's creating it.
+    fn new(body: std::rc::Rc<dyn Fn()>) -> std::rc::Rc<Self> {
+        let commit_effect = std::rc::Rc::new(Self {
+            body: RefCell::new(body),
+            cleanups: RefCell::new(Vec::new()),
+            effect: RefCell::new(None),
+            queued: Cell::new(false),
+        });
+        let weak = std::rc::Rc::downgrade(&commit_effect);
+        let effect = reactive::Effect::new_deferred(move || {
+            let Some(commit_effect) = weak.upgrade() else {
+                return;
+            };
+            if COMMITTING.with(|committing| committing.replace(false)) {
+                commit_effect.run();
+            } else {
+                commit_effect.queue();
+            }
+        });
+        commit_effect.effect.replace(Some(effect));
+        commit_effect.queue();
+        commit_effect
+    }
+
+    /// Wait for the next render to be on screen.
+    fn queue(self: &std::rc::Rc<Self>) {
+        if !self.queued.replace(true) {
+            COMMIT_QUEUE.with(|queue| queue.borrow_mut().push(std::rc::Rc::downgrade(self)));
+        }
+    }
+
+    /// Clean up after the last run and run the effect again. Run by the
+    /// reactive effect, so the signals it reads are tracked.
+    fn run(&self) {
+        self.clean_up();
+        let body = self.body.borrow().clone();
+        let outer = CLEANUPS.with(|cleanups| cleanups.replace(Some(Vec::new())));
+        body();
+        let cleanups = CLEANUPS.with(|cleanups| cleanups.replace(outer));
+        *self.cleanups.borrow_mut() = cleanups.unwrap_or_default();
+    }
+
+    fn clean_up(&self) {
+        let cleanups = std::mem::take(&mut *self.cleanups.borrow_mut());
+        for cleanup in cleanups {
+            cleanup();
+        }
+    }
+}
+
+impl Drop for CommitEffect {
+    fn drop(&mut self) {
+        if let Some(effect) = self.effect.take() {
+            effect.dispose();
+        }
+        self.clean_up();
+    }
+}
+
+/// Run a side effect after the render that mounts the component, and again
+/// after the signals it read change.
+///
+/// The effect runs once the render is on screen, so node refs point at the
+/// new elements, rather than while the component builds its elements. It
+/// tracks the signals it reads, the same way as an [`Effect`](reactive::Effect), and a
+/// change to one of them runs it again after the next render. Read a signal
+/// with [`untracked`](reactive::untracked) to use its value without
+/// running again when it changes.
+///
+/// Register cleanups with [`on_cleanup`] while the effect runs; they run
+/// before it runs again, and when its hook is dropped, such as when the
+/// window that rendered it closes.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let room = use_signal(|| String::from("general"));
+///
+///     use_effect(move || {
+///         let connection = chat::join(&room.get());
+///         on_cleanup(move || connection.leave());
+///     });
+/// }
+/// ```
+pub fn use_effect(effect: impl Fn() + 'static) {
+    let body: std::rc::Rc<dyn Fn()> = std::rc::Rc::new(effect);
+    let commit_effect = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_effect", || CommitEffect::new(body.clone()))
+    });
+    commit_effect.body.replace(body);
+}
+
+/// Run `cleanup` before the [`use_effect`] effect running now runs again,
+/// and when its hook is dropped.
+///
+/// # Panics
+///
+/// If no `use_effect` effect is running.
+pub fn on_cleanup(cleanup: impl FnOnce() + 'static) {
+    CLEANUPS.with(|cleanups| {
+        cleanups
+            .borrow_mut()
+            .as_mut()
+            .expect("on_cleanup called outside a use_effect effect")
+            .push(Box::new(cleanup));
+    });
+}
+
+/// Run the [`use_effect`] effects waiting for a render to be on screen.
+/// The runtime calls this once a render's content is in its windows.
+///
+/// Returns whether any ran, as they may have changed state to render.
+/// Effects queued while these run wait for the next render.
+pub fn run_effects() -> bool {
+    let queued = COMMIT_QUEUE.with(|queue| std::mem::take(&mut *queue.borrow_mut()));
+    let mut ran = false;
+    for commit_effect in queued.iter().filter_map(std::rc::Weak::upgrade) {
+        commit_effect.queued.set(false);
+        if let Some(effect) = &*commit_effect.effect.borrow() {
+            COMMITTING.with(|committing| committing.set(true));
+            effect.run();
+            COMMITTING.with(|committing| committing.set(false));
+            ran = true;
+        }
+    }
+    ran
+}
+
 /// Storage for effect dependencies and cleanup function.
 struct EffectState<D> {
     deps: Option<D>,
@@ -712,9 +1736,9 @@ struct EffectState<D> {
 
 /// Run a side effect when dependencies change.
 ///
-/// The effect function runs after render when dependencies change.
-/// If the effect returns a cleanup function, it will be called before
-/// the next effect run or when the component is unmounted.
+/// The effect function runs during the render when `deps` differ from the
+/// last render's, compared by equality. Use [`use_effect`] for an effect
+/// that runs after the render and tracks the signals it reads.
 ///
 /// # Example
 ///
@@ -722,27 +1746,26 @@ struct EffectState<D> {
 /// fn app() -> Element {
 ///     let count = use_signal(|| 0);
 ///
-///     use_effect(|| {
+///     use_effect_with(|| {
 ///         println!("Count changed to: {}", count.get());
-///         // Optional cleanup
-///         || println!("Cleaning up...")
 ///     }, count.get());
 /// }
 /// ```
-pub fn use_effect<F, D>(effect_fn: F, deps: D)
+pub fn use_effect_with<F, D>(effect_fn: F, deps: D)
 where
     F: FnOnce() + 'static,
     D: PartialEq + Clone + 'static,
 {
     // Get or create the effect state
     let state_ref = HOOK_REGISTRY.with(|registry| {
-        registry.borrow_mut().use_hook::<std::rc::Rc<RefCell<EffectState<D>>>>(
-            "use_effect",
-            || std::rc::Rc::new(RefCell::new(EffectState {
-                deps: None,
-                cleanup: None,
-            })),
-        )
+        registry
+            .borrow_mut()
+            .use_hook::<std::rc::Rc<RefCell<EffectState<D>>>>("use_effect_with", || {
+                std::rc::Rc::new(RefCell::new(EffectState {
+                    deps: None,
+                    cleanup: None,
+                }))
+            })
     });
 
     let mut state = state_ref.borrow_mut();
@@ -762,8 +1785,6 @@ where
         // Update deps
         state.deps = Some(deps);
 
-        // Run the effect
-        // Note: In a full implementation, this would be scheduled after render
         effect_fn();
     }
 }
@@ -792,13 +1813,14 @@ where
 {
     // Get or create the effect state
     let state_ref = HOOK_REGISTRY.with(|registry| {
-        registry.borrow_mut().use_hook::<std::rc::Rc<RefCell<EffectState<D>>>>(
-            "use_effect_cleanup",
-            || std::rc::Rc::new(RefCell::new(EffectState {
-                deps: None,
-                cleanup: None,
-            })),
-        )
+        registry
+            .borrow_mut()
+            .use_hook::<std::rc::Rc<RefCell<EffectState<D>>>>("use_effect_cleanup", || {
+                std::rc::Rc::new(RefCell::new(EffectState {
+                    deps: None,
+                    cleanup: None,
+                }))
+            })
     });
 
     let mut state = state_ref.borrow_mut();
@@ -879,13 +1901,14 @@ where
 {
     // Get or create the memo state
     let state_ref = HOOK_REGISTRY.with(|registry| {
-        registry.borrow_mut().use_hook::<std::rc::Rc<RefCell<MemoState<T, D>>>>(
-            "use_memo",
-            || std::rc::Rc::new(RefCell::new(MemoState {
-                value: None,
-                deps: None,
-            })),
-        )
+        registry
+            .borrow_mut()
+            .use_hook::<std::rc::Rc<RefCell<MemoState<T, D>>>>("use_memo", || {
+                std::rc::Rc::new(RefCell::new(MemoState {
+                    value: None,
+                    deps: None,
+                }))
+            })
     });
 
     let mut state = state_ref.borrow_mut();
@@ -973,9 +1996,136 @@ where
     })
 }
 
+/// Render `content`, or `fallback` once it has panicked.
+///
+/// `ErrorBoundary` in `rsx!` expands to this. A panic while `content`
+/// renders, or in an event handler it rendered, is caught instead of
+/// showing the window's crash panel, and from then on `fallback` is
+/// rendered with the [`CaughtError`] in its place. Calling
+/// [`CaughtError::reset`], such as from a "Try again" button, renders
+/// `content` again on the next render.
+///
+/// `content` has hooks of its own, like a `Lazy` subtree's, so it can stop
+/// rendering part way without upsetting the hooks around it. They're
+/// dropped when it panics, so it starts over once reset. `use_error_boundary`
+/// itself is a hook, so it must be called every render.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     rsx! {
+///         Window { title: "Reports",
+///             ErrorBoundary {
+///                 fallback: |error: CaughtError| rsx! {
+///                     p { "The report failed: " {error.message()} }
+///                     button { onclick: move || error.reset(), "Try again" }
+///                 },
+///                 {report()}
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn use_error_boundary(
+    fallback: impl FnOnce(CaughtError) -> Element,
+    content: impl FnOnce() -> Element,
+) -> Element {
+    let boundary = HOOK_REGISTRY.with(|registry| {
+        registry.borrow_mut().use_hook("use_error_boundary", || {
+            std::rc::Rc::new(ErrorBoundary::default())
+        })
+    });
+    if let Some(error) = boundary.error() {
+        return fallback(error);
+    }
+    boundary.render(content).unwrap_or_else(fallback)
+}
+
+/// Load a value with a future, re-running it when the signals it reads
+/// change.
+///
+/// `load` is called on the first render, and again whenever a signal it
+/// read changes, dropping the future still running. The future runs on the
+/// UI thread (see [`spawn_local`](crate::task::spawn_local)), so slow or
+/// blocking work should happen elsewhere, with the future awaiting it.
+///
+/// While it's loading, the [`suspense`](crate::suspense::suspense)
+/// boundary it's rendered in, if any, shows its fallback.
+///
+/// # Example
+///
+/// ```ignore
+/// fn app() -> Element {
+///     let user_id = use_signal(|| 1);
+///     let user = use_resource({
+///         let user_id = user_id.clone();
+///         move || {
+///             let id = user_id.get();
+///             async move { fetch_user(id).await }
+///         }
+///     });
+///
+///     rsx! {
+///         match user.state() {
+///             ResourceState::Loading => rsx! { p { "Loading..." } },
+///             ResourceState::Ready(user) => rsx! { p { {user.name} } },
+///             ResourceState::Error(e) => rsx! { p { "Failed: " {e} } },
+///         }
+///     }
+/// }
+/// ```
+pub fn use_resource<T, E, Fut>(load: impl Fn() -> Fut + 'static) -> Resource<T, E>
+where
+    T: 'static,
+    E: 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+{
+    let resource = HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_resource", || Resource::new(load))
+    });
+    if resource.loading() {
+        suspense::mark_pending();
+    }
+    resource
+}
+
+/// A [`Store`] kept across renders, starting out as what `init` returns.
+///
+/// Reads of the store's fields are tracked one by one, so setting one
+/// field doesn't re-run effects and memos that read only the others.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Clone, Default, Store)]
+/// struct AppState {
+///     query: String,
+///     results: Vec<String>,
+/// }
+///
+/// fn app() -> Element {
+///     let state = use_store(AppState::default);
+///     let query = state.query();
+///     rsx! {
+///         input { value: query.get(), oninput: move |text| query.set(text) }
+///     }
+/// }
+/// ```
+pub fn use_store<T: 'static>(init: impl FnOnce() -> T) -> Store<T> {
+    HOOK_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .use_hook("use_store", || Store::new(init()))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::resource::ResourceState;
 
     fn reset_registry() {
         HOOK_REGISTRY.with(|registry| {
@@ -1001,6 +2151,186 @@ mod tests {
         end_render();
     }
 
+    #[test]
+    fn roots_keep_their_own_hooks() {
+        reset_registry();
+        let render_app = || {
+            begin_render();
+            let count = use_signal(|| 1);
+            end_render();
+            count
+        };
+
+        let count = render_app();
+        count.set(2);
+        let title = render_root(7, || use_signal(|| String::from("Settings")));
+        title.set("Preferences".into());
+
+        // Neither disturbs the other's hooks
+        assert_eq!(render_app().get(), 2);
+        let title = render_root(7, || use_signal(String::new));
+        assert_eq!(title.get(), "Preferences");
+
+        drop_root(7);
+        let title = render_root(7, || use_signal(|| String::from("New")));
+        assert_eq!(title.get(), "New");
+        drop_root(7);
+    }
+
+    #[test]
+    fn deep_links_are_returned_once() {
+        reset_registry();
+        crate::deep_link::push_deep_link("myapp://launch");
+
+        begin_render();
+        assert_eq!(use_deep_links(), ["myapp://launch"]);
+        end_render();
+
+        crate::deep_link::push_deep_link("myapp://auth?code=1");
+        begin_render();
+        assert_eq!(use_deep_links(), ["myapp://auth?code=1"]);
+        end_render();
+
+        begin_render();
+        assert!(use_deep_links().is_empty());
+        end_render();
+    }
+
+    #[test]
+    fn lazy_subtrees_load_after_first_shown() {
+        reset_registry();
+        let clock = clock::Clock::manual();
+        let loads = std::rc::Rc::new(Cell::new(0));
+        let render = |shown: bool| {
+            begin_render();
+            let loads = loads.clone();
+            let element = use_lazy(shown, Element::Html("loading".into()), move || {
+                loads.set(loads.get() + 1);
+                let count = use_signal(|| 0);
+                count.update(|n| *n += 1);
+                Element::Html(format!("count {}", count.get()))
+            });
+            let other = use_signal(|| "after");
+            end_render();
+            assert_eq!(other.get(), "after");
+            crate::element::children_to_html(&[element])
+        };
+
+        // Hidden: nothing is built
+        assert_eq!(render(false), "");
+        assert!(!clock.advance(Duration::from_millis(10)));
+        // First shown: the placeholder, then the subtree a moment later
+        assert_eq!(render(true), "loading");
+        assert_eq!(loads.get(), 0);
+        assert!(clock.advance(Duration::from_millis(1)));
+        assert_eq!(render(true), "count 1");
+        assert_eq!(render(true), "count 2");
+        // Hidden again: the subtree keeps its hooks
+        assert_eq!(render(false), "");
+        assert_eq!(render(true), "count 3");
+        assert_eq!(loads.get(), 3);
+    }
+
+    #[test]
+    fn restored_hooks_keep_values_until_one_changes() {
+        reset_registry();
+        begin_render();
+        use_signal(|| 1).set(10);
+        use_ref(|| String::from("a")).set("b".into());
+        use_signal(|| 0.5f64).set(2.5);
+        use_signal(|| 3).set(30);
+        end_render();
+        let saved = save_hooks();
+        assert_eq!(saved[1].value.as_deref(), Some("b"));
+
+        // The new code changed the third hook's type
+        reset_registry();
+        restore_hooks(saved);
+        begin_render();
+        assert_eq!(use_signal(|| 1).get(), 10);
+        assert_eq!(use_ref(|| String::from("a")).get(), "b");
+        assert_eq!(use_signal(|| 0.5f32).get(), 0.5);
+        assert_eq!(use_signal(|| 3).get(), 3);
+        end_render();
+    }
+
+    #[test]
+    fn debug_info_shows_hook_values() {
+        reset_registry();
+
+        begin_render();
+        let count = use_signal(|| 3);
+        let name = use_signal(|| String::from("rinch"));
+        let _doubled = use_derived(move || count.get() * 2);
+        let _size = use_signal(|| std::time::Duration::ZERO);
+        end_render();
+
+        let values: Vec<_> = get_hooks_debug_info()
+            .into_iter()
+            .map(|hook| hook.value)
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Some("3".to_string()),
+                Some("\"rinch\"".to_string()),
+                Some("6".to_string()),
+                None,
+            ]
+        );
+        name.set(String::new());
+        assert_eq!(get_hooks_debug_info()[1].value.as_deref(), Some("\"\""));
+    }
+
+    #[test]
+    fn use_infinite_scroll_fires_once_per_content_height() {
+        use crate::events::ScrollEvent;
+        use crate::node_ref::update_node_ref_scroll;
+
+        reset_registry();
+        let feed = NodeRef::new();
+        let mut loads = 0;
+        let render = |loads: &mut i32| {
+            begin_render();
+            use_infinite_scroll(&feed, 100.0, || *loads += 1);
+            end_render();
+        };
+        let scrolled = |scroll_y, content_height| ScrollEvent {
+            scroll_y,
+            content_height,
+            client_height: 500.0,
+            ..Default::default()
+        };
+
+        // Not laid out yet
+        render(&mut loads);
+        assert_eq!(loads, 0);
+
+        // Far from the end, then near it
+        update_node_ref_scroll(feed.id(), scrolled(0.0, 2000.0));
+        render(&mut loads);
+        assert_eq!(loads, 0);
+        update_node_ref_scroll(feed.id(), scrolled(1450.0, 2000.0));
+        render(&mut loads);
+        render(&mut loads);
+        assert_eq!(loads, 1);
+
+        // More content arrived, and the user nears the new end
+        update_node_ref_scroll(feed.id(), scrolled(1450.0, 3000.0));
+        render(&mut loads);
+        assert_eq!(loads, 1);
+        update_node_ref_scroll(feed.id(), scrolled(2480.0, 3000.0));
+        render(&mut loads);
+        assert_eq!(loads, 2);
+
+        // Nothing more arrived: scrolling away and back tries again
+        update_node_ref_scroll(feed.id(), scrolled(1000.0, 3000.0));
+        render(&mut loads);
+        update_node_ref_scroll(feed.id(), scrolled(2500.0, 3000.0));
+        render(&mut loads);
+        assert_eq!(loads, 3);
+    }
+
     #[test]
     fn use_memo_caches_value() {
         reset_registry();
@@ -1114,7 +2444,7 @@ mod tests {
     }
 
     #[test]
-    fn use_effect_runs_when_deps_change() {
+    fn use_effect_with_runs_when_deps_change() {
         reset_registry();
         use std::cell::Cell;
         use std::rc::Rc;
@@ -1124,7 +2454,7 @@ mod tests {
 
         // First render - effect runs
         begin_render();
-        use_effect(
+        use_effect_with(
             move || {
                 run_count_clone.set(run_count_clone.get() + 1);
             },
@@ -1136,7 +2466,7 @@ mod tests {
         // Second render - same deps, effect should not run again
         let run_count_clone2 = Rc::clone(&run_count);
         begin_render();
-        use_effect(
+        use_effect_with(
             move || {
                 run_count_clone2.set(run_count_clone2.get() + 1);
             },
@@ -1148,7 +2478,7 @@ mod tests {
         // Third render - different deps, effect should run
         let run_count_clone3 = Rc::clone(&run_count);
         begin_render();
-        use_effect(
+        use_effect_with(
             move || {
                 run_count_clone3.set(run_count_clone3.get() + 1);
             },
@@ -1158,6 +2488,52 @@ mod tests {
         assert_eq!(run_count.get(), 2);
     }
 
+    #[test]
+    fn use_effect_runs_after_the_render_and_cleans_up() {
+        reset_registry();
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let runs = Rc::new(Cell::new(0));
+        let cleanups = Rc::new(Cell::new(0));
+        let count = Signal::new(0);
+        let other = Signal::new(0);
+        let render = || {
+            begin_render();
+            let (runs, cleanups, count, other) =
+                (runs.clone(), cleanups.clone(), count.clone(), other.clone());
+            use_effect(move || {
+                count.get();
+                reactive::untracked(|| other.get());
+                runs.set(runs.get() + 1);
+                let cleanups = cleanups.clone();
+                on_cleanup(move || cleanups.set(cleanups.get() + 1));
+            });
+            end_render();
+        };
+
+        // Not during the render, but once it's committed
+        render();
+        assert_eq!(runs.get(), 0);
+        assert!(run_effects());
+        assert_eq!(runs.get(), 1);
+        render();
+        assert!(!run_effects());
+
+        // A signal it read runs it again after the next render
+        count.set(1);
+        assert_eq!(runs.get(), 1);
+        render();
+        assert!(run_effects());
+        assert_eq!((runs.get(), cleanups.get()), (2, 1));
+
+        other.set(1);
+        assert!(!run_effects());
+
+        reset_registry();
+        assert_eq!(cleanups.get(), 2);
+    }
+
     #[test]
     fn use_derived_tracks_dependencies() {
         reset_registry();
@@ -1237,4 +2613,190 @@ mod tests {
         assert_eq!(c2.get(), 3);
         end_render();
     }
+
+    #[test]
+    fn use_spring_moves_towards_new_targets() {
+        reset_registry();
+
+        begin_render();
+        assert_eq!(use_spring(10.0, SpringConfig::default()), 10.0);
+        end_render();
+        assert!(!crate::animation::animation_frame_requested());
+
+        begin_render();
+        assert_eq!(use_spring(50.0, SpringConfig::default()), 10.0);
+        end_render();
+
+        let start = std::time::Instant::now();
+        for frame in 1..=300 {
+            crate::animation::run_animation_frame(start + crate::animation::FRAME_INTERVAL * frame);
+        }
+        begin_render();
+        assert_eq!(use_spring(50.0, SpringConfig::default()), 50.0);
+        end_render();
+    }
+
+    #[test]
+    fn use_animated_tweens_changes_and_completes() {
+        reset_registry();
+        let completed = std::rc::Rc::new(Cell::new(false));
+
+        let render = |value: f64| {
+            let completed = completed.clone();
+            begin_render();
+            let animated = use_animated_then(
+                value,
+                Duration::from_millis(100),
+                Easing::Linear,
+                move || completed.set(true),
+            );
+            end_render();
+            animated
+        };
+        assert_eq!(render(1.0), 1.0);
+        assert_eq!(render(3.0), 1.0);
+
+        let later = std::time::Instant::now() + Duration::from_millis(200);
+        crate::animation::run_animation_frame(later);
+        assert!(completed.get());
+        assert_eq!(render(3.0), 3.0);
+    }
+
+    #[test]
+    fn use_animation_frame_runs_the_latest_callback() {
+        reset_registry();
+        let frames = std::rc::Rc::new(Cell::new(0));
+
+        let render = |running: bool, step: u32| {
+            let frames = frames.clone();
+            begin_render();
+            use_animation_frame(running, move |_| frames.set(frames.get() + step));
+            end_render();
+        };
+        render(true, 1);
+        crate::animation::run_animation_frame(std::time::Instant::now());
+        render(true, 10);
+        crate::animation::run_animation_frame(std::time::Instant::now());
+        assert_eq!(frames.get(), 11);
+
+        render(false, 100);
+        crate::animation::run_animation_frame(std::time::Instant::now());
+        assert_eq!(frames.get(), 11);
+        assert!(!crate::animation::animation_frame_requested());
+    }
+
+    #[test]
+    fn use_transition_group_re_renders_when_exits_end() {
+        reset_registry();
+        let render = |items: Vec<u32>| {
+            begin_render();
+            let shown = use_transition_group(items, |item| *item, Duration::from_millis(100));
+            end_render();
+            shown
+                .into_iter()
+                .map(|item| (item.item, item.class()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(render(vec![1, 2]), [(1, ""), (2, "")]);
+        assert_eq!(render(vec![2]), [(1, "exit"), (2, "")]);
+
+        // A paint frame watches for the exit to end, then re-renders
+        let later = std::time::Instant::now() + Duration::from_millis(150);
+        assert!(!crate::animation::run_animation_frame(later));
+        assert!(crate::animation::run_animation_frame(later));
+    }
+
+    #[test]
+    fn error_boundaries_keep_the_hooks_around_them() {
+        reset_registry();
+        let fail = Cell::new(false);
+        let caught = RefCell::new(None);
+        let render = || {
+            begin_render();
+            let element = use_error_boundary(
+                |error| {
+                    let html = Element::Html(format!("failed: {error}"));
+                    caught.replace(Some(error));
+                    html
+                },
+                || {
+                    let count = use_signal(|| 0);
+                    count.update(|n| *n += 1);
+                    if fail.get() {
+                        panic!("no rows");
+                    }
+                    Element::Html(format!("count {}", count.get()))
+                },
+            );
+            let after = use_signal(|| "after");
+            end_render();
+            assert_eq!(after.get(), "after");
+            crate::element::children_to_html(&[element])
+        };
+
+        assert_eq!(render(), "count 1");
+        fail.set(true);
+        assert_eq!(render(), "failed: no rows");
+        fail.set(false);
+        assert_eq!(render(), "failed: no rows");
+        // Reset: the content starts over with fresh hooks
+        caught.take().unwrap().reset();
+        assert_eq!(render(), "count 1");
+    }
+
+    #[test]
+    fn use_resource_reloads_when_its_signals_change() {
+        reset_registry();
+        let id = Signal::new(1);
+        let render = || {
+            begin_render();
+            let id = id.clone();
+            let resource = use_resource(move || {
+                let id = id.get();
+                async move {
+                    if id > 0 {
+                        Ok(id * 10)
+                    } else {
+                        Err("no such user")
+                    }
+                }
+            });
+            end_render();
+            resource
+        };
+
+        let resource = render();
+        assert_eq!(resource.state(), ResourceState::Loading);
+        crate::task::poll_tasks();
+        assert_eq!(render().value(), Some(10));
+
+        id.set(2);
+        assert!(resource.loading());
+        crate::task::poll_tasks();
+        assert_eq!(resource.value(), Some(20));
+
+        id.set(0);
+        crate::task::poll_tasks();
+        assert_eq!(resource.error(), Some("no such user"));
+    }
+
+    #[test]
+    fn suspense_waits_for_resources_inside_it() {
+        reset_registry();
+        let render = || {
+            begin_render();
+            let element = crate::suspense::suspense(Element::Html("loading".into()), || {
+                let name = use_resource(|| async { Ok::<_, ()>("Ada") });
+                let count = use_resource(|| async { Ok::<_, ()>(3) });
+                Element::Html(format!("{:?} {:?}", name.value(), count.value()))
+            });
+            end_render();
+            crate::element::children_to_html(&[element])
+        };
+
+        assert_eq!(render(), "loading");
+        // Both resources are in, so the content shows in one go
+        crate::task::poll_tasks();
+        assert_eq!(render(), "Some(\"Ada\") Some(3)");
+    }
 }
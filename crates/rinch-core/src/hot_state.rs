@@ -0,0 +1,342 @@
+//! Hook state carried across a hot restart.
+//!
+//! When hot reload rebuilds the app, the running process saves its hooks'
+//! values with [`save_hooks`](crate::hooks::save_hooks) and the new process
+//! puts them back with [`restore_hooks`](crate::hooks::restore_hooks)
+//! before its first render. A hook keeps its value if it's the same kind of
+//! hook holding the same type as before, and every hook before it did too:
+//! a hook added, removed or changed starts over, along with the hooks after
+//! it.
+//!
+//! Signals and refs of common types are kept: numbers, `bool`, `char`,
+//! strings, and options, vectors and pairs of them. Other types are kept
+//! once they implement [`HotValue`] and are named with [`keep_on_reload`]:
+//!
+//! ```ignore
+//! #[derive(Clone)]
+//! enum Route { Home, Project(u32) }
+//!
+//! impl HotValue for Route {
+//!     fn to_hot(&self) -> String {
+//!         match self {
+//!             Route::Home => "home".into(),
+//!             Route::Project(id) => format!("project/{id}"),
+//!         }
+//!     }
+//!
+//!     fn from_hot(text: &str) -> Option<Self> {
+//!         match text.split_once('/') {
+//!             None if text == "home" => Some(Route::Home),
+//!             Some(("project", id)) => id.parse().ok().map(Route::Project),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     keep_on_reload::<Route>();
+//!     rinch::run_with_hot_reload(app);
+//! }
+//! ```
+
+use crate::reactive::{self, Signal};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A value that can be written as text and read back, to carry it across
+/// a hot restart.
+pub trait HotValue: Clone + 'static {
+    /// Write the value as text.
+    fn to_hot(&self) -> String;
+    /// Read a value written by [`to_hot`](HotValue::to_hot), or `None` if
+    /// the text isn't one.
+    fn from_hot(text: &str) -> Option<Self>;
+}
+
+macro_rules! hot_value_from_str {
+    ($($ty:ty),* $(,)?) => {$(
+        impl HotValue for $ty {
+            fn to_hot(&self) -> String {
+                self.to_string()
+            }
+
+            fn from_hot(text: &str) -> Option<Self> {
+                text.parse().ok()
+            }
+        }
+    )*};
+}
+
+hot_value_from_str!(
+    bool, char, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, String,
+);
+
+impl<T: HotValue> HotValue for Option<T> {
+    fn to_hot(&self) -> String {
+        match self {
+            Some(value) => format!("+{}", value.to_hot()),
+            None => "-".to_string(),
+        }
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        match text.strip_prefix('+') {
+            Some(value) => T::from_hot(value).map(Some),
+            None => (text == "-").then_some(None),
+        }
+    }
+}
+
+impl<T: HotValue> HotValue for Vec<T> {
+    fn to_hot(&self) -> String {
+        join(self.iter().map(T::to_hot))
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        split(text)?.into_iter().map(T::from_hot).collect()
+    }
+}
+
+impl<A: HotValue, B: HotValue> HotValue for (A, B) {
+    fn to_hot(&self) -> String {
+        join([self.0.to_hot(), self.1.to_hot()])
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        match split(text)?.as_slice() {
+            [a, b] => Some((A::from_hot(a)?, B::from_hot(b)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<A: HotValue, B: HotValue, C: HotValue> HotValue for (A, B, C) {
+    fn to_hot(&self) -> String {
+        join([self.0.to_hot(), self.1.to_hot(), self.2.to_hot()])
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        match split(text)?.as_slice() {
+            [a, b, c] => Some((A::from_hot(a)?, B::from_hot(b)?, C::from_hot(c)?)),
+            _ => None,
+        }
+    }
+}
+
+/// Join texts so [`split`] can take them apart, each prefixed with its
+/// length.
+fn join(items: impl IntoIterator<Item = String>) -> String {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(&item.len().to_string());
+        text.push(':');
+        text.push_str(&item);
+    }
+    text
+}
+
+/// Take apart texts joined with [`join`].
+fn split(mut text: &str) -> Option<Vec<&str>> {
+    let mut items = Vec::new();
+    while !text.is_empty() {
+        let (len, rest) = text.split_once(':')?;
+        let len: usize = len.parse().ok()?;
+        items.push(rest.get(..len)?);
+        text = rest.get(len..)?;
+    }
+    Some(items)
+}
+
+/// A hook as saved by [`save_hooks`](crate::hooks::save_hooks).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedHook {
+    /// The hook function, such as `use_signal`.
+    pub hook_type: String,
+    /// The type it stores.
+    pub value_type: String,
+    /// Its value, if it's a signal or ref of a type kept on reload.
+    pub value: Option<String>,
+}
+
+impl HotValue for SavedHook {
+    fn to_hot(&self) -> String {
+        join([
+            self.hook_type.clone(),
+            self.value_type.clone(),
+            self.value.to_hot(),
+        ])
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        let (hook_type, value_type, value) = <(String, String, Option<String>)>::from_hot(text)?;
+        Some(Self {
+            hook_type,
+            value_type,
+            value,
+        })
+    }
+}
+
+/// Reads and writes the values of hooks holding one type.
+struct Kind {
+    type_id: TypeId,
+    save: fn(&dyn Any) -> Option<String>,
+    load: fn(&dyn Any, &str) -> bool,
+}
+
+thread_local! {
+    /// Types named with `keep_on_reload`, besides the built-in ones.
+    static KINDS: RefCell<Vec<Kind>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keep signals and refs holding `T` across hot restarts, as the built-in
+/// types are. Call it before `run_with_hot_reload`.
+pub fn keep_on_reload<T: HotValue>() {
+    let kind = Kind {
+        type_id: TypeId::of::<T>(),
+        save: save_as::<T>,
+        load: load_as::<T>,
+    };
+    KINDS.with(|kinds| {
+        let mut kinds = kinds.borrow_mut();
+        if !kinds.iter().any(|known| known.type_id == kind.type_id) {
+            kinds.push(kind);
+        }
+    });
+}
+
+/// Call `$apply!` with the types kept without `keep_on_reload`.
+macro_rules! builtin_types {
+    ($apply:ident) => {
+        $apply!(
+            bool,
+            char,
+            i8,
+            i16,
+            i32,
+            i64,
+            isize,
+            u8,
+            u16,
+            u32,
+            u64,
+            usize,
+            f32,
+            f64,
+            String,
+            Option<bool>,
+            Option<i32>,
+            Option<i64>,
+            Option<usize>,
+            Option<f64>,
+            Option<String>,
+            Vec<bool>,
+            Vec<i32>,
+            Vec<i64>,
+            Vec<usize>,
+            Vec<f64>,
+            Vec<String>,
+            (f64, f64),
+        )
+    };
+}
+
+/// Write a hook's value as text, if it's a signal or ref of a kept type.
+/// Reads don't subscribe the caller.
+pub(crate) fn save_value(value: &dyn Any) -> Option<String> {
+    macro_rules! save {
+        ($($ty:ty),* $(,)?) => {$(
+            if let Some(text) = save_as::<$ty>(value) {
+                return Some(text);
+            }
+        )*};
+    }
+    builtin_types!(save);
+    KINDS.with(|kinds| kinds.borrow().iter().find_map(|kind| (kind.save)(value)))
+}
+
+/// Set a new hook's value from text written by [`save_value`]. Returns
+/// whether it was one of a kept type, and the text could be read.
+pub(crate) fn load_value(value: &dyn Any, text: &str) -> bool {
+    macro_rules! load {
+        ($($ty:ty),* $(,)?) => {$(
+            if load_as::<$ty>(value, text) {
+                return true;
+            }
+        )*};
+    }
+    builtin_types!(load);
+    KINDS.with(|kinds| kinds.borrow().iter().any(|kind| (kind.load)(value, text)))
+}
+
+fn save_as<T: HotValue>(value: &dyn Any) -> Option<String> {
+    if let Some(signal) = value.downcast_ref::<Signal<T>>() {
+        return Some(reactive::untracked(|| signal.with(T::to_hot)));
+    }
+    if let Some(cell) = value.downcast_ref::<Rc<RefCell<T>>>() {
+        return cell.try_borrow().ok().map(|value| value.to_hot());
+    }
+    None
+}
+
+fn load_as<T: HotValue>(value: &dyn Any, text: &str) -> bool {
+    if let Some(signal) = value.downcast_ref::<Signal<T>>() {
+        return T::from_hot(text).map(|loaded| signal.set(loaded)).is_some();
+    }
+    if let Some(cell) = value.downcast_ref::<Rc<RefCell<T>>>() {
+        return T::from_hot(text)
+            .map(|loaded| *cell.borrow_mut() = loaded)
+            .is_some();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_survive_the_trip_through_text() {
+        let value = (
+            vec![Some("a:b".to_string()), None, Some(String::new())],
+            (-1.5f64, 7usize),
+        );
+        let text = value.to_hot();
+        assert_eq!(
+            <(Vec<Option<String>>, (f64, usize))>::from_hot(&text),
+            Some(value)
+        );
+        assert_eq!(<Vec<String>>::from_hot("3:ab"), None);
+        assert_eq!(<Option<u32>>::from_hot("+x"), None);
+    }
+
+    #[test]
+    fn signals_of_kept_types_are_saved_and_loaded() {
+        let saved = Signal::new(vec![1i32, 2, 3]);
+        let text = save_value(&saved).unwrap();
+        let loaded = Signal::new(Vec::<i32>::new());
+        assert!(load_value(&loaded, &text));
+        assert_eq!(loaded.get(), [1, 2, 3]);
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Route(u32);
+        impl HotValue for Route {
+            fn to_hot(&self) -> String {
+                self.0.to_hot()
+            }
+            fn from_hot(text: &str) -> Option<Self> {
+                u32::from_hot(text).map(Route)
+            }
+        }
+        let route = Rc::new(RefCell::new(Route(4)));
+        assert_eq!(save_value(&route), None);
+        keep_on_reload::<Route>();
+        keep_on_reload::<Route>();
+        let text = save_value(&route).unwrap();
+        let loaded = Rc::new(RefCell::new(Route(0)));
+        assert!(load_value(&loaded, &text));
+        assert_eq!(*loaded.borrow(), Route(4));
+        assert_eq!(KINDS.with(|kinds| kinds.borrow().len()), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Keyboard shortcuts that aren't menu items.
+//!
+//! [`use_hotkey`](crate::hooks::use_hotkey) registers a shortcut such as
+//! `"Ctrl+K"` for as long as the component calling it is rendered. The
+//! runtime parses the shortcuts, the same way as a `MenuItem`'s, and runs
+//! the matching hotkeys of the window a key was pressed in when no menu
+//! item has the shortcut.
+//!
+//! Hotkeys belong to the root that rendered them: those registered by the
+//! app function fire in the app's windows, and those registered by a window
+//! opened with a component only fire in that window.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use crate::hooks::current_root;
+
+/// A shortcut and what it does, held by its hook.
+pub(crate) struct Hotkey {
+    shortcut: RefCell<String>,
+    callback: RefCell<Rc<dyn Fn()>>,
+    root: Option<u64>,
+}
+
+thread_local! {
+    /// Registered hotkeys, in the order they were first rendered. Hooks own
+    /// them, so they drop out when their hooks do.
+    static HOTKEYS: RefCell<Vec<Weak<Hotkey>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Hotkey {
+    /// A hotkey for the root being rendered, with no shortcut yet.
+    pub(crate) fn new() -> Rc<Self> {
+        let hotkey = Rc::new(Self {
+            shortcut: RefCell::new(String::new()),
+            callback: RefCell::new(Rc::new(|| {})),
+            root: current_root(),
+        });
+        HOTKEYS.with(|hotkeys| {
+            let mut hotkeys = hotkeys.borrow_mut();
+            hotkeys.retain(|hotkey| hotkey.strong_count() > 0);
+            hotkeys.push(Rc::downgrade(&hotkey));
+        });
+        hotkey
+    }
+
+    /// Replace the shortcut and callback with this render's.
+    pub(crate) fn update(&self, shortcut: &str, callback: Rc<dyn Fn()>) {
+        if *self.shortcut.borrow() != shortcut {
+            *self.shortcut.borrow_mut() = shortcut.to_string();
+        }
+        *self.callback.borrow_mut() = callback;
+    }
+}
+
+/// A registered hotkey, for the runtime to match key presses against.
+#[derive(Clone)]
+pub struct HotkeyBinding {
+    /// The shortcut, as passed to `use_hotkey`.
+    pub shortcut: String,
+    /// The root that registered it: `None` for the app, or the ID of the
+    /// window opened with a component.
+    pub root: Option<u64>,
+    callback: Rc<dyn Fn()>,
+}
+
+impl HotkeyBinding {
+    /// Run the hotkey's callback.
+    pub fn invoke(&self) {
+        (self.callback)();
+    }
+}
+
+impl std::fmt::Debug for HotkeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HotkeyBinding")
+            .field("shortcut", &self.shortcut)
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The hotkeys of rendered components, in the order they were first
+/// rendered.
+pub fn hotkeys() -> Vec<HotkeyBinding> {
+    HOTKEYS.with(|hotkeys| {
+        hotkeys
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|hotkey| HotkeyBinding {
+                shortcut: hotkey.shortcut.borrow().clone(),
+                root: hotkey.root,
+                callback: hotkey.callback.borrow().clone(),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn hotkeys_last_as_long_as_their_hooks() {
+        let pressed = Rc::new(Cell::new(0));
+        let hotkey = Hotkey::new();
+        let count = pressed.clone();
+        hotkey.update("Ctrl+K", Rc::new(move || count.set(count.get() + 1)));
+
+        let bindings = hotkeys();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].shortcut, "Ctrl+K");
+        assert_eq!(bindings[0].root, None);
+        bindings[0].invoke();
+        assert_eq!(pressed.get(), 1);
+
+        hotkey.update("Ctrl+Shift+K", Rc::new(|| {}));
+        assert_eq!(hotkeys()[0].shortcut, "Ctrl+Shift+K");
+
+        drop(hotkey);
+        assert!(hotkeys().is_empty());
+    }
+}
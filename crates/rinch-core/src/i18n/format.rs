@@ -0,0 +1,943 @@
+//! Locale-aware formatting of numbers, dates, relative times and lists.
+//!
+//! Each helper formats for the current [`locale`](super::locale) and, like
+//! [`t!`](crate::t), is reactive: text formatted during render updates when
+//! the locale changes.
+//!
+//! Rinch carries the data for English (US and UK), German, French, Spanish,
+//! Italian, Portuguese (Brazil and Portugal), Dutch, Russian, Japanese and
+//! Chinese. Other locales format as English.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{language, locale, plural_category};
+
+/// How to format a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberOptions {
+    /// Fraction digits always shown, padded with zeros.
+    pub min_fraction_digits: usize,
+    /// Fraction digits shown at most, rounding the rest.
+    pub max_fraction_digits: usize,
+    /// Whether to separate thousands.
+    pub grouping: bool,
+}
+
+impl Default for NumberOptions {
+    fn default() -> Self {
+        Self {
+            min_fraction_digits: 0,
+            max_fraction_digits: 3,
+            grouping: true,
+        }
+    }
+}
+
+/// A calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    /// 1 to 12.
+    pub month: u8,
+    /// 1 to 31.
+    pub day: u8,
+}
+
+impl Date {
+    /// A date, or `None` if the month or day is out of range.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days_in_month)
+            .contains(&day)
+            .then_some(Self { year, month, day })
+    }
+
+    /// The date (in UTC) of a point in time.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+        };
+        Self::from_days(seconds.div_euclid(86_400))
+    }
+
+    /// The date `days` days after 1970-01-01.
+    fn from_days(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    /// Days since 1970-01-01.
+    fn days(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Day of the week, from 0 (Monday) to 6 (Sunday).
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was a Thursday
+        (self.days() + 3).rem_euclid(7) as u8
+    }
+}
+
+/// How much of a date to spell out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateStyle {
+    /// All numbers: 3/14/25.
+    Short,
+    /// Abbreviated month: Mar 14, 2025.
+    #[default]
+    Medium,
+    /// Full month: March 14, 2025.
+    Long,
+    /// With the weekday: Friday, March 14, 2025.
+    Full,
+}
+
+/// How the items of a list relate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListStyle {
+    /// All of them: "a, b, and c".
+    #[default]
+    And,
+    /// One of them: "a, b, or c".
+    Or,
+}
+
+/// Format a number with the locale's separators, grouping thousands and
+/// showing up to 3 fraction digits: 1,234.5 in English, 1.234,5 in German.
+pub fn format_number(value: f64) -> String {
+    format_number_with(value, NumberOptions::default())
+}
+
+/// Format a number with the locale's separators, as `options` says.
+///
+/// # Example
+///
+/// ```ignore
+/// // "1,234.50" in English, "1.234,50" in German
+/// let price = format_number_with(1234.5, NumberOptions {
+///     min_fraction_digits: 2,
+///     max_fraction_digits: 2,
+///     ..Default::default()
+/// });
+/// ```
+pub fn format_number_with(value: f64, options: NumberOptions) -> String {
+    number(&locale(), value, options)
+}
+
+/// Format a date: `Mar 14, 2025` (medium, in English) or `14 mars 2025`
+/// (in French).
+pub fn format_date(date: Date, style: DateStyle) -> String {
+    date_string(&locale(), date, style)
+}
+
+/// Describe a time `seconds` from now (negative for the past) in the
+/// largest unit that fits: "in 3 days", "2 hours ago", "vor 5 Minuten".
+pub fn format_relative_time(seconds: f64) -> String {
+    relative_time(&locale(), seconds)
+}
+
+/// Join items into a list: "a, b, and c" in English, "a, b und c" in
+/// German.
+pub fn format_list<S: AsRef<str>>(items: &[S], style: ListStyle) -> String {
+    list(&locale(), items, style)
+}
+
+/// Formatting data for a locale.
+struct LocaleData {
+    decimal: &'static str,
+    group: &'static str,
+    /// Digits needed before thousands are grouped at all, beyond three.
+    min_grouping: usize,
+    months: [&'static str; 12],
+    months_short: [&'static str; 12],
+    /// Monday first.
+    weekdays: [&'static str; 7],
+    /// Short, medium, long and full date patterns. `{d}`/`{dd}` is the day
+    /// (zero-padded), `{M}`/`{MM}` the month, `{MMM}`/`{MMMM}` its short or
+    /// full name, `{y}`/`{yy}` the year and `{EEEE}` the weekday.
+    dates: [&'static str; 4],
+    /// Second, minute, hour, day, week, month and year, singular and plural.
+    units: [(&'static str, &'static str); 7],
+    /// The same units' forms for the "few" plural category, in languages
+    /// that have one; the plural takes its place in the rest.
+    units_few: Option<[&'static str; 7]>,
+    /// Future and past relative times, with `{n}` and `{u}` for the count
+    /// and unit.
+    relative: (&'static str, &'static str),
+    /// Separator between list items, and before the last with "and" and
+    /// "or".
+    list: (&'static str, &'static str, &'static str),
+}
+
+const EN: LocaleData = LocaleData {
+    decimal: ".",
+    group: ",",
+    min_grouping: 1,
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    months_short: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    dates: [
+        "{M}/{d}/{yy}",
+        "{MMM} {d}, {y}",
+        "{MMMM} {d}, {y}",
+        "{EEEE}, {MMMM} {d}, {y}",
+    ],
+    units: [
+        ("second", "seconds"),
+        ("minute", "minutes"),
+        ("hour", "hours"),
+        ("day", "days"),
+        ("week", "weeks"),
+        ("month", "months"),
+        ("year", "years"),
+    ],
+    units_few: None,
+    relative: ("in {n} {u}", "{n} {u} ago"),
+    list: (", ", ", and ", ", or "),
+};
+
+const EN_GB: LocaleData = LocaleData {
+    dates: [
+        "{dd}/{MM}/{y}",
+        "{d} {MMM} {y}",
+        "{d} {MMMM} {y}",
+        "{EEEE} {d} {MMMM} {y}",
+    ],
+    list: (", ", " and ", " or "),
+    ..EN
+};
+
+const DE: LocaleData = LocaleData {
+    decimal: ",",
+    group: ".",
+    min_grouping: 1,
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    months_short: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    dates: [
+        "{dd}.{MM}.{yy}",
+        "{dd}.{MM}.{y}",
+        "{d}. {MMMM} {y}",
+        "{EEEE}, {d}. {MMMM} {y}",
+    ],
+    units: [
+        ("Sekunde", "Sekunden"),
+        ("Minute", "Minuten"),
+        ("Stunde", "Stunden"),
+        ("Tag", "Tagen"),
+        ("Woche", "Wochen"),
+        ("Monat", "Monaten"),
+        ("Jahr", "Jahren"),
+    ],
+    units_few: None,
+    relative: ("in {n} {u}", "vor {n} {u}"),
+    list: (", ", " und ", " oder "),
+};
+
+const FR: LocaleData = LocaleData {
+    decimal: ",",
+    group: "\u{202f}",
+    min_grouping: 1,
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    months_short: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+    weekdays: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    dates: [
+        "{dd}/{MM}/{y}",
+        "{d} {MMM} {y}",
+        "{d} {MMMM} {y}",
+        "{EEEE} {d} {MMMM} {y}",
+    ],
+    units: [
+        ("seconde", "secondes"),
+        ("minute", "minutes"),
+        ("heure", "heures"),
+        ("jour", "jours"),
+        ("semaine", "semaines"),
+        ("mois", "mois"),
+        ("an", "ans"),
+    ],
+    units_few: None,
+    relative: ("dans {n} {u}", "il y a {n} {u}"),
+    list: (", ", " et ", " ou "),
+};
+
+const ES: LocaleData = LocaleData {
+    decimal: ",",
+    group: ".",
+    min_grouping: 2,
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    months_short: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+    ],
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    dates: [
+        "{d}/{M}/{yy}",
+        "{d} {MMM} {y}",
+        "{d} de {MMMM} de {y}",
+        "{EEEE}, {d} de {MMMM} de {y}",
+    ],
+    units: [
+        ("segundo", "segundos"),
+        ("minuto", "minutos"),
+        ("hora", "horas"),
+        ("día", "días"),
+        ("semana", "semanas"),
+        ("mes", "meses"),
+        ("año", "años"),
+    ],
+    units_few: None,
+    relative: ("dentro de {n} {u}", "hace {n} {u}"),
+    list: (", ", " y ", " o "),
+};
+
+const IT: LocaleData = LocaleData {
+    decimal: ",",
+    group: ".",
+    min_grouping: 1,
+    months: [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ],
+    months_short: [
+        "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+    ],
+    weekdays: [
+        "lunedì",
+        "martedì",
+        "mercoledì",
+        "giovedì",
+        "venerdì",
+        "sabato",
+        "domenica",
+    ],
+    dates: [
+        "{dd}/{MM}/{yy}",
+        "{d} {MMM} {y}",
+        "{d} {MMMM} {y}",
+        "{EEEE} {d} {MMMM} {y}",
+    ],
+    units: [
+        ("secondo", "secondi"),
+        ("minuto", "minuti"),
+        ("ora", "ore"),
+        ("giorno", "giorni"),
+        ("settimana", "settimane"),
+        ("mese", "mesi"),
+        ("anno", "anni"),
+    ],
+    units_few: None,
+    relative: ("tra {n} {u}", "{n} {u} fa"),
+    list: (", ", " e ", " o "),
+};
+
+const PT: LocaleData = LocaleData {
+    decimal: ",",
+    group: ".",
+    min_grouping: 1,
+    months: [
+        "janeiro",
+        "fevereiro",
+        "março",
+        "abril",
+        "maio",
+        "junho",
+        "julho",
+        "agosto",
+        "setembro",
+        "outubro",
+        "novembro",
+        "dezembro",
+    ],
+    months_short: [
+        "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.",
+        "dez.",
+    ],
+    weekdays: [
+        "segunda-feira",
+        "terça-feira",
+        "quarta-feira",
+        "quinta-feira",
+        "sexta-feira",
+        "sábado",
+        "domingo",
+    ],
+    dates: [
+        "{dd}/{MM}/{y}",
+        "{d} de {MMM} de {y}",
+        "{d} de {MMMM} de {y}",
+        "{EEEE}, {d} de {MMMM} de {y}",
+    ],
+    units: [
+        ("segundo", "segundos"),
+        ("minuto", "minutos"),
+        ("hora", "horas"),
+        ("dia", "dias"),
+        ("semana", "semanas"),
+        ("mês", "meses"),
+        ("ano", "anos"),
+    ],
+    units_few: None,
+    relative: ("em {n} {u}", "há {n} {u}"),
+    list: (", ", " e ", " ou "),
+};
+
+const PT_PT: LocaleData = LocaleData {
+    group: "\u{a0}",
+    min_grouping: 2,
+    relative: ("dentro de {n} {u}", "há {n} {u}"),
+    ..PT
+};
+
+const NL: LocaleData = LocaleData {
+    decimal: ",",
+    group: ".",
+    min_grouping: 1,
+    months: [
+        "januari",
+        "februari",
+        "maart",
+        "april",
+        "mei",
+        "juni",
+        "juli",
+        "augustus",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_short: [
+        "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+    ],
+    weekdays: [
+        "maandag",
+        "dinsdag",
+        "woensdag",
+        "donderdag",
+        "vrijdag",
+        "zaterdag",
+        "zondag",
+    ],
+    dates: [
+        "{dd}-{MM}-{y}",
+        "{d} {MMM} {y}",
+        "{d} {MMMM} {y}",
+        "{EEEE} {d} {MMMM} {y}",
+    ],
+    units: [
+        ("seconde", "seconden"),
+        ("minuut", "minuten"),
+        ("uur", "uur"),
+        ("dag", "dagen"),
+        ("week", "weken"),
+        ("maand", "maanden"),
+        ("jaar", "jaar"),
+    ],
+    units_few: None,
+    relative: ("over {n} {u}", "{n} {u} geleden"),
+    list: (", ", " en ", " of "),
+};
+
+const RU: LocaleData = LocaleData {
+    decimal: ",",
+    group: "\u{a0}",
+    min_grouping: 1,
+    // Genitive, as they appear in dates
+    months: [
+        "января",
+        "февраля",
+        "марта",
+        "апреля",
+        "мая",
+        "июня",
+        "июля",
+        "августа",
+        "сентября",
+        "октября",
+        "ноября",
+        "декабря",
+    ],
+    months_short: [
+        "янв.",
+        "февр.",
+        "мар.",
+        "апр.",
+        "мая",
+        "июн.",
+        "июл.",
+        "авг.",
+        "сент.",
+        "окт.",
+        "нояб.",
+        "дек.",
+    ],
+    weekdays: [
+        "понедельник",
+        "вторник",
+        "среда",
+        "четверг",
+        "пятница",
+        "суббота",
+        "воскресенье",
+    ],
+    dates: [
+        "{dd}.{MM}.{y}",
+        "{d} {MMM} {y} г.",
+        "{d} {MMMM} {y} г.",
+        "{EEEE}, {d} {MMMM} {y} г.",
+    ],
+    // Counts ending in 2-4 take the genitive singular, the rest the
+    // genitive plural
+    units: [
+        ("секунду", "секунд"),
+        ("минуту", "минут"),
+        ("час", "часов"),
+        ("день", "дней"),
+        ("неделю", "недель"),
+        ("месяц", "месяцев"),
+        ("год", "лет"),
+    ],
+    units_few: Some([
+        "секунды",
+        "минуты",
+        "часа",
+        "дня",
+        "недели",
+        "месяца",
+        "года",
+    ]),
+    relative: ("через {n} {u}", "{n} {u} назад"),
+    list: (", ", " и ", " или "),
+};
+
+const JA: LocaleData = LocaleData {
+    decimal: ".",
+    group: ",",
+    min_grouping: 1,
+    months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    months_short: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    weekdays: [
+        "月曜日",
+        "火曜日",
+        "水曜日",
+        "木曜日",
+        "金曜日",
+        "土曜日",
+        "日曜日",
+    ],
+    dates: [
+        "{y}/{MM}/{dd}",
+        "{y}/{MM}/{dd}",
+        "{y}年{M}月{d}日",
+        "{y}年{M}月{d}日{EEEE}",
+    ],
+    units: [
+        ("秒", "秒"),
+        ("分", "分"),
+        ("時間", "時間"),
+        ("日", "日"),
+        ("週間", "週間"),
+        ("か月", "か月"),
+        ("年", "年"),
+    ],
+    units_few: None,
+    relative: ("{n}{u}後", "{n}{u}前"),
+    list: ("、", "、", "、または"),
+};
+
+const ZH: LocaleData = LocaleData {
+    weekdays: [
+        "星期一",
+        "星期二",
+        "星期三",
+        "星期四",
+        "星期五",
+        "星期六",
+        "星期日",
+    ],
+    dates: [
+        "{y}/{M}/{d}",
+        "{y}年{M}月{d}日",
+        "{y}年{M}月{d}日",
+        "{y}年{M}月{d}日{EEEE}",
+    ],
+    units: [
+        ("秒", "秒"),
+        ("分钟", "分钟"),
+        ("小时", "小时"),
+        ("天", "天"),
+        ("周", "周"),
+        ("个月", "个月"),
+        ("年", "年"),
+    ],
+    relative: ("{n}{u}后", "{n}{u}前"),
+    list: ("、", "和", "或"),
+    ..JA
+};
+
+fn data(locale: &str) -> &'static LocaleData {
+    match locale {
+        "en-GB" | "en-AU" | "en-NZ" | "en-IE" | "en-IN" => return &EN_GB,
+        "pt-PT" => return &PT_PT,
+        _ => {}
+    }
+    match language(locale) {
+        "de" => &DE,
+        "fr" => &FR,
+        "es" => &ES,
+        "it" => &IT,
+        "pt" => &PT,
+        "nl" => &NL,
+        "ru" => &RU,
+        "ja" => &JA,
+        "zh" => &ZH,
+        _ => &EN,
+    }
+}
+
+fn number(locale: &str, value: f64, options: NumberOptions) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+    if value.is_infinite() {
+        return format!("{sign}∞");
+    }
+    let data = data(locale);
+    let max = options.max_fraction_digits.max(options.min_fraction_digits);
+    let digits = format!("{:.*}", max, value.abs());
+    let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+    let mut fraction = fraction.to_string();
+    while fraction.len() > options.min_fraction_digits && fraction.ends_with('0') {
+        fraction.pop();
+    }
+    // Rounded to zero isn't negative
+    let sign = if integer.bytes().all(|b| b == b'0') && fraction.bytes().all(|b| b == b'0') {
+        ""
+    } else {
+        sign
+    };
+    let mut out = sign.to_string();
+    if options.grouping && integer.len() >= 3 + data.min_grouping {
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                out.push_str(data.group);
+            }
+            out.push(digit);
+        }
+    } else {
+        out.push_str(integer);
+    }
+    if !fraction.is_empty() {
+        out.push_str(data.decimal);
+        out.push_str(&fraction);
+    }
+    out
+}
+
+fn date_string(locale: &str, date: Date, style: DateStyle) -> String {
+    let data = data(locale);
+    let pattern = data.dates[style as usize];
+    let month = usize::from(date.month.clamp(1, 12)) - 1;
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let field = &rest[start + 1..start + end];
+        match field {
+            "d" => out.push_str(&date.day.to_string()),
+            "dd" => out.push_str(&format!("{:02}", date.day)),
+            "M" => out.push_str(&date.month.to_string()),
+            "MM" => out.push_str(&format!("{:02}", date.month)),
+            "MMM" => out.push_str(data.months_short[month]),
+            "MMMM" => out.push_str(data.months[month]),
+            "y" => out.push_str(&date.year.to_string()),
+            "yy" => out.push_str(&format!("{:02}", date.year.rem_euclid(100))),
+            "EEEE" => out.push_str(data.weekdays[usize::from(date.weekday())]),
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn relative_time(locale: &str, seconds: f64) -> String {
+    const UNITS: [f64; 7] = [
+        1.0,
+        60.0,
+        3_600.0,
+        86_400.0,
+        604_800.0,
+        2_629_746.0,
+        31_556_952.0,
+    ];
+    // Largest unit with at least one whole count, weeks only under a month
+    let magnitude = seconds.abs();
+    let mut unit = match magnitude {
+        m if m < UNITS[1] => 0,
+        m if m < UNITS[2] => 1,
+        m if m < UNITS[3] => 2,
+        m if m < UNITS[4] => 3,
+        m if m < UNITS[5] => 4,
+        m if m < UNITS[6] => 5,
+        _ => 6,
+    };
+    // Round before settling on the unit, so 59.6 seconds is a minute
+    while unit < UNITS.len() - 1
+        && (magnitude / UNITS[unit]).round() * UNITS[unit] >= UNITS[unit + 1]
+    {
+        unit += 1;
+    }
+    let count = (magnitude / UNITS[unit]).round();
+    let data = data(locale);
+    let (one, other) = data.units[unit];
+    let name = match plural_category(locale, count) {
+        "one" => one,
+        "few" => data.units_few.map_or(other, |few| few[unit]),
+        _ => other,
+    };
+    let template = if seconds < 0.0 {
+        data.relative.1
+    } else {
+        data.relative.0
+    };
+    let count = number(locale, count, NumberOptions::default());
+    template.replace("{n}", &count).replace("{u}", name)
+}
+
+fn list<S: AsRef<str>>(locale: &str, items: &[S], style: ListStyle) -> String {
+    let data = data(locale);
+    let (separator, and, or) = data.list;
+    let last_separator = match style {
+        ListStyle::And => and,
+        ListStyle::Or => or,
+    };
+    // Two items never get the serial comma
+    let last_separator = if items.len() == 2 && last_separator.starts_with(", ") {
+        &last_separator[1..]
+    } else {
+        last_separator
+    };
+    let mut out = String::new();
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(if i == items.len() - 1 {
+                last_separator
+            } else {
+                separator
+            });
+        }
+        out.push_str(item.as_ref());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_use_the_locales_separators() {
+        let default = NumberOptions::default();
+        assert_eq!(number("en", 1234.5, default), "1,234.5");
+        assert_eq!(number("de", 1234.5, default), "1.234,5");
+        assert_eq!(number("fr", 1234567.0, default), "1\u{202f}234\u{202f}567");
+        // Spanish leaves four digits alone
+        assert_eq!(number("es", 1234.0, default), "1234");
+        assert_eq!(number("es", 12345.0, default), "12.345");
+        assert_eq!(number("en", -0.0001, default), "0");
+        assert_eq!(number("en", 2.0 / 3.0, default), "0.667");
+        let cents = NumberOptions {
+            min_fraction_digits: 2,
+            max_fraction_digits: 2,
+            grouping: false,
+        };
+        assert_eq!(number("en", 1234.5, cents), "1234.50");
+    }
+
+    #[test]
+    fn dates_follow_the_locales_patterns() {
+        let date = Date::new(2025, 3, 14).unwrap();
+        assert_eq!(date.weekday(), 4);
+        assert_eq!(date_string("en", date, DateStyle::Short), "3/14/25");
+        assert_eq!(
+            date_string("en", date, DateStyle::Full),
+            "Friday, March 14, 2025"
+        );
+        assert_eq!(date_string("en-GB", date, DateStyle::Short), "14/03/2025");
+        assert_eq!(date_string("de", date, DateStyle::Long), "14. März 2025");
+        assert_eq!(date_string("fr", date, DateStyle::Medium), "14 mars 2025");
+        assert_eq!(date_string("ja", date, DateStyle::Long), "2025年3月14日");
+        assert_eq!(Date::new(2025, 2, 29), None);
+        assert_eq!(Date::from_days(date.days()), date);
+        assert_eq!(
+            Date::from_system_time(UNIX_EPOCH),
+            Date::new(1970, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn relative_times_pick_a_unit() {
+        assert_eq!(relative_time("en", 30.0), "in 30 seconds");
+        assert_eq!(relative_time("en", -3_600.0), "1 hour ago");
+        assert_eq!(relative_time("en", 3.0 * 86_400.0), "in 3 days");
+        assert_eq!(relative_time("de", -300.0), "vor 5 Minuten");
+        assert_eq!(relative_time("fr", -86_400.0), "il y a 1 jour");
+        assert_eq!(relative_time("ja", 7_200.0), "2時間後");
+        // Rounding up can reach the next unit
+        assert_eq!(relative_time("en", 59.6), "in 1 minute");
+        assert_eq!(relative_time("en", -86_000.0), "1 day ago");
+    }
+
+    #[test]
+    fn relative_times_use_the_plural_forms() {
+        assert_eq!(relative_time("ru", 3_600.0), "через 1 час");
+        assert_eq!(relative_time("ru", 2.0 * 3_600.0), "через 2 часа");
+        assert_eq!(relative_time("ru", 5.0 * 3_600.0), "через 5 часов");
+        assert_eq!(relative_time("ru", 21.0 * 3_600.0), "через 21 час");
+        assert_eq!(relative_time("ru", -22.0 * 3_600.0), "22 часа назад");
+        assert_eq!(relative_time("ru", -12.0 * 60.0), "12 минут назад");
+        assert_eq!(relative_time("ru", 2.0 * 86_400.0), "через 2 дня");
+    }
+
+    #[test]
+    fn lists_join_with_the_locales_words() {
+        assert_eq!(list("en", &["a", "b", "c"], ListStyle::And), "a, b, and c");
+        assert_eq!(list("en", &["a", "b"], ListStyle::Or), "a or b");
+        assert_eq!(
+            list("en-GB", &["a", "b", "c"], ListStyle::And),
+            "a, b and c"
+        );
+        assert_eq!(list("de", &["a", "b", "c"], ListStyle::And), "a, b und c");
+        assert_eq!(list("en", &["a"], ListStyle::And), "a");
+        assert_eq!(list::<&str>("en", &[], ListStyle::And), "");
+    }
+}
@@ -0,0 +1,1123 @@
+//! Translations.
+//!
+//! Translations are written in [Fluent](https://projectfluent.org) syntax,
+//! one resource per locale:
+//!
+//! ```ftl
+//! # en.ftl
+//! -app = Smyeditor
+//! save-file = Save
+//! about = About { -app }
+//! files-selected = { $count ->
+//!     [one] One file selected
+//!    *[other] { $count } files selected
+//! }
+//! ```
+//!
+//! Load them with [`add_translations`] (usually with `include_str!`) or
+//! [`load_translations`], then look messages up with [`t!`](crate::t), which
+//! also works as a child in `rsx!`. The current locale is reactive: text
+//! translated during render updates when [`set_locale`] switches it.
+//!
+//! A locale falls back to its language (`fr-CA` to `fr`), then to the
+//! fallback locale (the first one added, unless [`set_fallback_locale`]
+//! says otherwise). A message that's missing from the current locale is
+//! reported once (see [`take_missing_translations`]; the runtime logs them
+//! as warnings), and [`untranslated`] lists what a locale lacks, for tests.
+//!
+//! Rinch supports a subset of Fluent: messages, terms, attributes,
+//! variables, string and number literals, message and term references, and
+//! select expressions, which match numbers by their CLDR plural category.
+//! Functions such as `NUMBER()` aren't supported.
+//!
+//! [`format_number`], [`format_date`], [`format_relative_time`] and
+//! [`format_list`] format values for the current locale.
+
+mod format;
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::reactive::{untracked, Signal};
+
+pub use format::{
+    format_date, format_list, format_number, format_number_with, format_relative_time, Date,
+    DateStyle, ListStyle, NumberOptions,
+};
+
+/// How deep message references can nest, which also stops cycles.
+const MAX_DEPTH: usize = 10;
+
+thread_local! {
+    /// Messages (and terms, with their `-`) by locale.
+    static BUNDLES: RefCell<HashMap<String, Messages>> = RefCell::new(HashMap::new());
+    /// The locale set by [`set_locale`]; `None` follows the system.
+    static LOCALE: Signal<Option<String>> = Signal::new(None);
+    /// Bumped whenever translations are added, so translated text updates.
+    static REVISION: Signal<u64> = Signal::new(0);
+    static FALLBACK: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Missing translations already reported.
+    static REPORTED: RefCell<BTreeSet<(String, String)>> = const { RefCell::new(BTreeSet::new()) };
+    static MISSING: RefCell<Vec<MissingTranslation>> = const { RefCell::new(Vec::new()) };
+}
+
+type Messages = HashMap<String, Pattern>;
+
+/// A value passed to a message, as `$name`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    String(String),
+    Number(f64),
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arg::String(s) => f.write_str(s),
+            Arg::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Arg::Number(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Arg::String(value.to_string())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(value: String) -> Self {
+        Arg::String(value)
+    }
+}
+
+impl From<&String> for Arg {
+    fn from(value: &String) -> Self {
+        Arg::String(value.clone())
+    }
+}
+
+macro_rules! number_args {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for Arg {
+            fn from(value: $ty) -> Self {
+                Arg::Number(value as f64)
+            }
+        })*
+    };
+}
+
+number_args!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// A syntax error in a Fluent resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtlError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FtlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for FtlError {}
+
+/// Why a translation file couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file or directory couldn't be read.
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// The file has syntax errors; the messages without any were loaded.
+    Syntax {
+        path: PathBuf,
+        errors: Vec<FtlError>,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, error } => write!(f, "{}: {error}", path.display()),
+            LoadError::Syntax { path, errors } => {
+                write!(f, "{}: ", path.display())?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// A message the current locale doesn't have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingTranslation {
+    pub locale: String,
+    pub id: String,
+    /// The locale the message came from instead, if any had it.
+    pub fallback: Option<String>,
+}
+
+/// Add translations for `locale` from Fluent source. Messages already
+/// loaded for the locale with the same ids are replaced.
+///
+/// On syntax errors, the messages without errors are still added.
+///
+/// # Example
+///
+/// ```ignore
+/// i18n::add_translations("en", include_str!("../locales/en.ftl")).unwrap();
+/// i18n::add_translations("fr", include_str!("../locales/fr.ftl")).unwrap();
+/// ```
+pub fn add_translations(locale: &str, source: &str) -> Result<(), Vec<FtlError>> {
+    let (messages, errors) = parse(source);
+    let locale = normalize_locale(locale);
+    FALLBACK.with(|fallback| {
+        fallback.borrow_mut().get_or_insert_with(|| locale.clone());
+    });
+    BUNDLES.with(|bundles| {
+        bundles
+            .borrow_mut()
+            .entry(locale)
+            .or_default()
+            .extend(messages)
+    });
+    REVISION.with(|revision| revision.update(|revision| *revision += 1));
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Load every `.ftl` file in `dir`: `dir/fr.ftl` holds `fr`'s translations,
+/// and so does every `.ftl` file in `dir/fr/`.
+///
+/// Files that can't be read or have syntax errors are reported; the rest
+/// (and the messages without errors) are still loaded. Locales load in
+/// alphabetical order, so call [`set_fallback_locale`] unless the first
+/// should be the fallback.
+pub fn load_translations(dir: impl AsRef<Path>) -> Result<(), Vec<LoadError>> {
+    let mut errors = Vec::new();
+    let mut files = Vec::new();
+    collect_ftl_files(dir.as_ref(), None, &mut files, &mut errors);
+    files.sort();
+    for (locale, path) in files {
+        match std::fs::read_to_string(&path) {
+            Ok(source) => {
+                if let Err(syntax) = add_translations(&locale, &source) {
+                    errors.push(LoadError::Syntax {
+                        path,
+                        errors: syntax,
+                    });
+                }
+            }
+            Err(error) => errors.push(LoadError::Io { path, error }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn collect_ftl_files(
+    dir: &Path,
+    locale: Option<&str>,
+    files: &mut Vec<(String, PathBuf)>,
+    errors: &mut Vec<LoadError>,
+) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            errors.push(LoadError::Io {
+                path: dir.to_path_buf(),
+                error,
+            });
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if locale.is_none()
+                && let Some(name) = path.file_name().and_then(|name| name.to_str())
+            {
+                collect_ftl_files(&path, Some(name), files, errors);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "ftl") {
+            let locale = match locale {
+                Some(locale) => Some(locale.to_string()),
+                None => path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(str::to_string),
+            };
+            if let Some(locale) = locale {
+                files.push((locale, path));
+            }
+        }
+    }
+}
+
+/// Switch the locale (such as `"fr"` or `"pt-BR"`). Text translated during
+/// render updates.
+pub fn set_locale(locale: &str) {
+    let locale = normalize_locale(locale);
+    LOCALE.with(|current| {
+        if untracked(|| current.get()).as_deref() != Some(locale.as_str()) {
+            current.set(Some(locale));
+        }
+    });
+}
+
+/// Follow the system's locale again, after [`set_locale`].
+pub fn use_system_locale() {
+    LOCALE.with(|current| {
+        if untracked(|| current.get()).is_some() {
+            current.set(None);
+        }
+    });
+}
+
+/// The locale in effect: the one set with [`set_locale`], else the
+/// system's, else the fallback locale. Reactive.
+pub fn locale() -> String {
+    LOCALE
+        .with(|current| current.get())
+        .or_else(system_locale)
+        .or_else(fallback_locale)
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Set the locale to use for messages the current one lacks. Defaults to
+/// the first locale translations were added for.
+pub fn set_fallback_locale(locale: &str) {
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = Some(normalize_locale(locale)));
+    REVISION.with(|revision| revision.update(|revision| *revision += 1));
+}
+
+/// The fallback locale, if any translations have been added.
+pub fn fallback_locale() -> Option<String> {
+    FALLBACK.with(|fallback| fallback.borrow().clone())
+}
+
+/// The locales translations have been added for, sorted.
+pub fn available_locales() -> Vec<String> {
+    let mut locales: Vec<_> = BUNDLES.with(|bundles| bundles.borrow().keys().cloned().collect());
+    locales.sort();
+    locales
+}
+
+/// The system's locale, from `LC_ALL`, `LC_MESSAGES` or `LANG`
+/// (`fr_CA.UTF-8` becomes `fr-CA`).
+pub fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .map(|value| {
+            let value = value.split(['.', '@']).next().unwrap_or_default();
+            normalize_locale(value)
+        })
+}
+
+/// Translate message `id` (or `id.attribute`) into the current locale.
+/// Usually called through [`t!`](crate::t).
+///
+/// A message missing from every locale translates to its id.
+pub fn translate(id: &str, args: &[(&str, Arg)]) -> String {
+    REVISION.with(|revision| revision.get());
+    let locale = locale();
+    let chain = locale_chain(&locale);
+    BUNDLES.with(|bundles| {
+        let bundles = bundles.borrow();
+        let resolver = Resolver {
+            bundles: &bundles,
+            chain: &chain,
+            args,
+        };
+        let found = chain
+            .iter()
+            .position(|locale| bundles.get(locale).is_some_and(|m| m.contains_key(id)));
+        // Another language's translation means this one is missing, if
+        // the app is translated into this language at all
+        let translated = bundles
+            .keys()
+            .any(|other| language(other) == language(&locale));
+        if translated && found.is_none_or(|index| language(&chain[index]) != language(&locale)) {
+            report_missing(&locale, id, found.map(|index| chain[index].clone()));
+        }
+        match found {
+            Some(index) => {
+                let mut out = String::new();
+                let pattern = &bundles[&chain[index]][id];
+                resolver.format(pattern, &mut out, 0);
+                out
+            }
+            None => id.to_string(),
+        }
+    })
+}
+
+/// Missing translations found since the last call, each reported once
+/// (called by the runtime, which logs them).
+pub fn take_missing_translations() -> Vec<MissingTranslation> {
+    MISSING.with(|missing| std::mem::take(&mut *missing.borrow_mut()))
+}
+
+/// The messages the fallback locale has that `locale` (or its language)
+/// doesn't, sorted. For tests that check every locale is complete.
+pub fn untranslated(locale: &str) -> Vec<String> {
+    let locale = normalize_locale(locale);
+    let Some(fallback) = fallback_locale() else {
+        return Vec::new();
+    };
+    let chain: Vec<_> = locale_chain(&locale)
+        .into_iter()
+        .filter(|chained| language(chained) == language(&locale))
+        .collect();
+    BUNDLES.with(|bundles| {
+        let bundles = bundles.borrow();
+        let Some(reference) = bundles.get(&fallback) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<_> = reference
+            .keys()
+            .filter(|id| !id.starts_with('-'))
+            .filter(|id| {
+                !chain
+                    .iter()
+                    .any(|locale| bundles.get(locale).is_some_and(|m| m.contains_key(*id)))
+            })
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    })
+}
+
+fn report_missing(locale: &str, id: &str, fallback: Option<String>) {
+    let new = REPORTED.with(|reported| {
+        reported
+            .borrow_mut()
+            .insert((locale.to_string(), id.to_string()))
+    });
+    if new {
+        MISSING.with(|missing| {
+            missing.borrow_mut().push(MissingTranslation {
+                locale: locale.to_string(),
+                id: id.to_string(),
+                fallback,
+            });
+        });
+    }
+}
+
+/// `fr_ca` and `FR-ca` become `fr-CA`.
+fn normalize_locale(locale: &str) -> String {
+    locale
+        .trim()
+        .split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| match (i, part.len()) {
+            (0, _) => part.to_ascii_lowercase(),
+            (_, 2) => part.to_ascii_uppercase(),
+            (_, 4) => {
+                let mut script = part.to_ascii_lowercase();
+                script[..1].make_ascii_uppercase();
+                script
+            }
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+/// The locales to look messages up in: `locale`, its parents and the
+/// fallback locale.
+fn locale_chain(locale: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut current = locale;
+    loop {
+        chain.push(current.to_string());
+        match current.rfind('-') {
+            Some(end) => current = &current[..end],
+            None => break,
+        }
+    }
+    if let Some(fallback) = fallback_locale()
+        && !chain.contains(&fallback)
+    {
+        chain.push(fallback);
+    }
+    chain
+}
+
+// =============================================================================
+// Syntax
+// =============================================================================
+
+type Pattern = Vec<PatternElement>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternElement {
+    Text(String),
+    Placeable(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Variable(String),
+    String(String),
+    Number(f64),
+    /// A message or term (with its `-`), and attribute.
+    Reference(String, Option<String>),
+    Select {
+        selector: Box<Expression>,
+        variants: Vec<(VariantKey, Pattern)>,
+        default: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum VariantKey {
+    Name(String),
+    Number(f64),
+}
+
+/// Parse a Fluent resource into patterns by id (attributes as
+/// `id.attribute`), and the errors in it.
+fn parse(source: &str) -> (Messages, Vec<FtlError>) {
+    let chars: Vec<char> = source.chars().filter(|&c| c != '\r').collect();
+    let mut parser = Parser { chars, pos: 0 };
+    let mut messages = Messages::new();
+    let mut errors = Vec::new();
+    while parser.pos < parser.chars.len() {
+        match parser.peek() {
+            Some('\n') | Some(' ') | Some('\t') => parser.skip_line_if_blank(),
+            Some('#') => parser.skip_line(),
+            Some(_) => {
+                let start = parser.pos;
+                match parser.entry() {
+                    Ok(entry) => messages.extend(entry),
+                    Err(message) => {
+                        errors.push(FtlError {
+                            line: parser.line_at(parser.pos.max(start)),
+                            message,
+                        });
+                        parser.skip_entry();
+                    }
+                }
+            }
+            None => break,
+        }
+    }
+    (messages, errors)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn line_at(&self, pos: usize) -> usize {
+        self.chars[..pos.min(self.chars.len())]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count()
+            + 1
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// Skip a line of only whitespace; an indented line where an entry
+    /// should start is an error, skipped with the entry.
+    fn skip_line_if_blank(&mut self) {
+        let start = self.pos;
+        self.skip_inline_blank();
+        match self.peek() {
+            Some('\n') => self.pos += 1,
+            None => {}
+            Some(_) => {
+                self.pos = start;
+                self.skip_line();
+            }
+        }
+    }
+
+    /// Skip to the next line that starts an entry or comment.
+    fn skip_entry(&mut self) {
+        self.skip_line();
+        while let Some(c) = self.peek() {
+            if c != ' ' && c != '\t' && c != '\n' && c != '}' {
+                break;
+            }
+            self.skip_line();
+        }
+    }
+
+    fn skip_inline_blank(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t') | Some('\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(match self.peek() {
+                Some(found) => format!("expected `{expected}`, found `{found}`"),
+                None => format!("expected `{expected}`, found the end"),
+            })
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        if !self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            return Err("expected an identifier".to_string());
+        }
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            self.pos += 1;
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// A message or term, with its attributes.
+    fn entry(&mut self) -> Result<Vec<(String, Pattern)>, String> {
+        let term = self.peek() == Some('-');
+        if term {
+            self.pos += 1;
+        }
+        let mut id = self.identifier()?;
+        if term {
+            id.insert(0, '-');
+        }
+        self.skip_inline_blank();
+        self.expect('=')?;
+        let value = self.pattern()?;
+        let mut entries = Vec::new();
+        // Attributes: indented `.name = pattern` lines
+        loop {
+            let start = self.pos;
+            if self.peek() != Some('\n') {
+                break;
+            }
+            self.skip_blank();
+            if self.peek() != Some('.') || self.line_at(start) == self.line_at(self.pos) {
+                self.pos = start;
+                break;
+            }
+            self.pos += 1;
+            let attribute = self.identifier()?;
+            self.skip_inline_blank();
+            self.expect('=')?;
+            let pattern = self.pattern()?;
+            if pattern.is_empty() {
+                return Err(format!("attribute `{attribute}` of `{id}` has no value"));
+            }
+            entries.push((format!("{id}.{attribute}"), pattern));
+        }
+        if value.is_empty() && (term || entries.is_empty()) {
+            return Err(format!("`{id}` has no value"));
+        }
+        if self.peek() == Some('}') {
+            return Err("unbalanced `}`".to_string());
+        }
+        if !value.is_empty() {
+            entries.insert(0, (id, value));
+        }
+        Ok(entries)
+    }
+
+    /// Text and placeables, up to the end of the pattern: a line that isn't
+    /// indented, or one that starts with `[`, `*`, `}` or `.`, or a `}`.
+    fn pattern(&mut self) -> Result<Pattern, String> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None | Some('}') => break,
+                Some('{') => {
+                    self.pos += 1;
+                    if !text.is_empty() {
+                        elements.push(PatternElement::Text(std::mem::take(&mut text)));
+                    }
+                    elements.push(PatternElement::Placeable(self.placeable()?));
+                }
+                Some('\n') => {
+                    let start = self.pos;
+                    let mut newlines = 0;
+                    while self.peek() == Some('\n') {
+                        self.pos += 1;
+                        newlines += 1;
+                        self.skip_inline_blank();
+                    }
+                    let indented = matches!(self.chars.get(self.pos - 1), Some(' ') | Some('\t'));
+                    if !indented || matches!(self.peek(), None | Some('[' | '*' | '}' | '.')) {
+                        self.pos = start;
+                        break;
+                    }
+                    text.extend(std::iter::repeat_n('\n', newlines));
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            elements.push(PatternElement::Text(text));
+        }
+        // Blank space around the pattern isn't part of it
+        if let Some(PatternElement::Text(first)) = elements.first_mut() {
+            *first = first.trim_start().to_string();
+        }
+        if let Some(PatternElement::Text(last)) = elements.last_mut() {
+            *last = last.trim_end().to_string();
+        }
+        elements.retain(|element| element != &PatternElement::Text(String::new()));
+        Ok(elements)
+    }
+
+    /// The rest of a placeable, after its `{`.
+    fn placeable(&mut self) -> Result<Expression, String> {
+        self.skip_blank();
+        let expression = self.inline_expression()?;
+        self.skip_blank();
+        let expression = if self.peek() == Some('-') && self.peek_at(1) == Some('>') {
+            self.pos += 2;
+            self.select(expression)?
+        } else {
+            expression
+        };
+        self.skip_blank();
+        self.expect('}')?;
+        Ok(expression)
+    }
+
+    fn inline_expression(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                let mut value = String::new();
+                loop {
+                    match self.peek() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            match self.peek_at(1) {
+                                Some(c @ ('"' | '\\')) => value.push(c),
+                                _ => return Err("unknown escape in string".to_string()),
+                            }
+                            self.pos += 2;
+                        }
+                        Some('\n') | None => return Err("unterminated string".to_string()),
+                        Some(c) => {
+                            value.push(c);
+                            self.pos += 1;
+                        }
+                    }
+                }
+                self.pos += 1;
+                Ok(Expression::String(value))
+            }
+            Some('$') => {
+                self.pos += 1;
+                Ok(Expression::Variable(self.identifier()?))
+            }
+            Some(c)
+                if c.is_ascii_digit()
+                    || (c == '-' && self.peek_at(1).is_some_and(|c| c.is_ascii_digit())) =>
+            {
+                Ok(Expression::Number(self.number()?))
+            }
+            Some('-') => {
+                self.pos += 1;
+                let id = format!("-{}", self.identifier()?);
+                Ok(Expression::Reference(id, self.attribute()?))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let id = self.identifier()?;
+                if self.peek() == Some('(') {
+                    return Err(format!("functions such as `{id}()` aren't supported"));
+                }
+                Ok(Expression::Reference(id, self.attribute()?))
+            }
+            _ => Err("expected a variable, literal or message reference".to_string()),
+        }
+    }
+
+    fn attribute(&mut self) -> Result<Option<String>, String> {
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            Ok(Some(self.identifier()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map_err(|_| format!("invalid number `{text}`"))
+    }
+
+    /// The variants of a select expression, after its `->`.
+    fn select(&mut self, selector: Expression) -> Result<Expression, String> {
+        let mut variants = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                Some('}') | None => break,
+                Some('*') => {
+                    if default.is_some() {
+                        return Err("more than one default variant".to_string());
+                    }
+                    default = Some(variants.len());
+                    self.pos += 1;
+                }
+                _ => {}
+            }
+            self.expect('[')?;
+            self.skip_inline_blank();
+            let key = match self.peek() {
+                Some(c) if c.is_ascii_digit() || c == '-' => VariantKey::Number(self.number()?),
+                _ => VariantKey::Name(self.identifier()?),
+            };
+            self.skip_inline_blank();
+            self.expect(']')?;
+            variants.push((key, self.pattern()?));
+        }
+        match default {
+            Some(default) => Ok(Expression::Select {
+                selector: Box::new(selector),
+                variants,
+                default,
+            }),
+            None => Err("select expression has no default (`*[...]`) variant".to_string()),
+        }
+    }
+}
+
+// =============================================================================
+// Formatting
+// =============================================================================
+
+struct Resolver<'a> {
+    bundles: &'a HashMap<String, Messages>,
+    chain: &'a [String],
+    args: &'a [(&'a str, Arg)],
+}
+
+impl Resolver<'_> {
+    fn format(&self, pattern: &Pattern, out: &mut String, depth: usize) {
+        for element in pattern {
+            match element {
+                PatternElement::Text(text) => out.push_str(text),
+                PatternElement::Placeable(expression) => self.expression(expression, out, depth),
+            }
+        }
+    }
+
+    fn expression(&self, expression: &Expression, out: &mut String, depth: usize) {
+        match expression {
+            Expression::Select {
+                selector,
+                variants,
+                default,
+            } => {
+                let (key, category) = match self.value(selector) {
+                    Some(Arg::Number(n)) => {
+                        let locale = self.chain.first().map(String::as_str).unwrap_or("en");
+                        (Arg::Number(n), Some(plural_category(locale, n)))
+                    }
+                    Some(value) => (value, None),
+                    None => (Arg::String(String::new()), None),
+                };
+                let matches = |variant: &VariantKey| match (variant, &key) {
+                    (VariantKey::Number(a), Arg::Number(b)) => a == b,
+                    (VariantKey::Name(name), Arg::String(s)) => name == s,
+                    (VariantKey::Name(name), Arg::Number(_)) => Some(name.as_str()) == category,
+                    _ => false,
+                };
+                // Exact numbers win over plural categories
+                let index = variants
+                    .iter()
+                    .position(|(variant, _)| {
+                        matches!(variant, VariantKey::Number(_)) && matches(variant)
+                    })
+                    .or_else(|| variants.iter().position(|(variant, _)| matches(variant)))
+                    .unwrap_or(*default);
+                self.format(&variants[index].1, out, depth);
+            }
+            Expression::Reference(id, attribute) => {
+                let id = match attribute {
+                    Some(attribute) => format!("{id}.{attribute}"),
+                    None => id.clone(),
+                };
+                let pattern = self
+                    .chain
+                    .iter()
+                    .find_map(|locale| self.bundles.get(locale)?.get(&id));
+                match pattern {
+                    Some(pattern) if depth < MAX_DEPTH => {
+                        // Terms only see the arguments they're given, which
+                        // rinch doesn't support, so none
+                        if id.starts_with('-') {
+                            let resolver = Resolver { args: &[], ..*self };
+                            resolver.format(pattern, out, depth + 1);
+                        } else {
+                            self.format(pattern, out, depth + 1);
+                        }
+                    }
+                    _ => {
+                        out.push('{');
+                        out.push_str(&id);
+                        out.push('}');
+                    }
+                }
+            }
+            expression => match self.value(expression) {
+                Some(value) => out.push_str(&value.to_string()),
+                None => {
+                    if let Expression::Variable(name) = expression {
+                        out.push_str(&format!("{{${name}}}"));
+                    }
+                }
+            },
+        }
+    }
+
+    /// The value of a variable or literal.
+    fn value(&self, expression: &Expression) -> Option<Arg> {
+        match expression {
+            Expression::Variable(name) => self
+                .args
+                .iter()
+                .find(|(arg, _)| arg == name)
+                .map(|(_, value)| value.clone()),
+            Expression::String(s) => Some(Arg::String(s.clone())),
+            Expression::Number(n) => Some(Arg::Number(*n)),
+            _ => None,
+        }
+    }
+}
+
+/// The CLDR plural category of `n` in `locale`'s language, for the
+/// languages most apps are translated into; others use English rules.
+fn plural_category(locale: &str, n: f64) -> &'static str {
+    let integer = n.fract() == 0.0;
+    let i = n.abs().trunc() as u64;
+    let (mod10, mod100) = (i % 10, i % 100);
+    match language(locale) {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => "other",
+        "fr" | "pt" if i <= 1 => "one",
+        "fr" | "pt" => "other",
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" if !integer => "other",
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" => match (mod10, mod100) {
+            (1, m) if m != 11 => "one",
+            (2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "pl" if !integer => "other",
+        "pl" => match (i, mod10, mod100) {
+            (1, ..) => "one",
+            (_, 2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "cs" | "sk" if !integer => "many",
+        "cs" | "sk" => match i {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        "ar" if !integer => "other",
+        "ar" => match (i, mod100) {
+            (0, _) => "zero",
+            (1, _) => "one",
+            (2, _) => "two",
+            (_, 3..=10) => "few",
+            (_, 11..=99) => "many",
+            _ => "other",
+        },
+        _ if integer && i == 1 => "one",
+        _ => "other",
+    }
+}
+
+/// Translate a message into the current locale, with `name = value`
+/// arguments for its variables.
+///
+/// Works anywhere, and as a child in `rsx!`. Reading it during render
+/// updates the text when the locale changes.
+///
+/// # Example
+///
+/// ```ignore
+/// rsx! {
+///     button { t!("save-file") }
+///     p { t!("files-selected", count = files.len()) }
+/// }
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($id:expr $(,)?) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $id,
+            &[$((stringify!($name), $crate::i18n::Arg::from($value))),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EN: &str = r#"
+# Comment
+-app = Smyeditor
+save-file = Save
+about = About { -app }
+greeting = Hello, { $name }!
+files = { $count ->
+    [0] No files
+    [one] One file
+   *[other] { $count } files
+}
+button = Click
+    .title = Click me
+multiline =
+    First line
+    second line
+"#;
+
+    fn reset() {
+        BUNDLES.with(|bundles| bundles.borrow_mut().clear());
+        FALLBACK.with(|fallback| *fallback.borrow_mut() = None);
+        REPORTED.with(|reported| reported.borrow_mut().clear());
+        take_missing_translations();
+    }
+
+    #[test]
+    fn messages_format_with_arguments() {
+        reset();
+        add_translations("en", EN).unwrap();
+        set_locale("en");
+        assert_eq!(t!("save-file"), "Save");
+        assert_eq!(t!("about"), "About Smyeditor");
+        assert_eq!(t!("greeting", name = "Ana"), "Hello, Ana!");
+        assert_eq!(t!("greeting"), "Hello, {$name}!");
+        assert_eq!(t!("files", count = 0), "No files");
+        assert_eq!(t!("files", count = 1), "One file");
+        assert_eq!(t!("files", count = 12usize), "12 files");
+        assert_eq!(t!("button.title"), "Click me");
+        assert_eq!(t!("multiline"), "First line\nsecond line");
+        assert_eq!(t!("nope"), "nope");
+    }
+
+    #[test]
+    fn locales_fall_back_and_report_missing_messages() {
+        reset();
+        add_translations("en", EN).unwrap();
+        add_translations(
+            "fr",
+            "save-file = Enregistrer\nfiles = { $count ->\n [one] Un fichier\n *[other] { $count } fichiers\n}\n",
+        )
+        .unwrap();
+        set_locale("fr_ca");
+        assert_eq!(locale(), "fr-CA");
+        assert_eq!(t!("save-file"), "Enregistrer");
+        // French counts 0 as one
+        assert_eq!(t!("files", count = 0), "Un fichier");
+        assert_eq!(t!("about"), "About Smyeditor");
+        assert_eq!(t!("about"), "About Smyeditor");
+        assert_eq!(
+            take_missing_translations(),
+            [MissingTranslation {
+                locale: "fr-CA".to_string(),
+                id: "about".to_string(),
+                fallback: Some("en".to_string()),
+            }]
+        );
+        assert!(untranslated("fr").contains(&"greeting".to_string()));
+        assert!(!untranslated("fr").contains(&"save-file".to_string()));
+        assert!(untranslated("en").is_empty());
+    }
+
+    #[test]
+    fn syntax_errors_keep_the_other_messages() {
+        let (messages, errors) =
+            parse("ok = Fine\nbad = { NUMBER($n) }\nsel = { $n ->\n [one] x\n}\nalso-ok = Yes\n");
+        assert_eq!(messages.len(), 2);
+        assert!(messages.contains_key("also-ok"));
+        assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), [2, 5]);
+    }
+
+    #[test]
+    fn plural_categories() {
+        assert_eq!(plural_category("en", 1.0), "one");
+        assert_eq!(plural_category("en", 1.5), "other");
+        assert_eq!(plural_category("ru", 21.0), "one");
+        assert_eq!(plural_category("ru", 12.0), "many");
+        assert_eq!(plural_category("pl", 23.0), "few");
+        assert_eq!(plural_category("ja", 1.0), "other");
+    }
+}
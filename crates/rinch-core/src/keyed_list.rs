@@ -0,0 +1,137 @@
+//! Lists whose items keep their own signal and scope across renders.
+//!
+//! [`use_keyed_list`](crate::hooks::use_keyed_list) matches each render's
+//! items to the last render's by key. An item that's still there keeps its
+//! [`Signal`], which is only set when the item changed, so effects and
+//! memos reading one row don't re-run when another row is edited. Each item
+//! also has a [`Scope`]: effects created in it are disposed of when the item
+//! leaves the list, rather than staying subscribed to signals for good.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::reactive::{batch, untracked, Scope, Signal};
+
+/// An item of a keyed list, from [`use_keyed_list`](crate::hooks::use_keyed_list).
+pub struct KeyedItem<K, T> {
+    /// The item's key.
+    pub key: K,
+    /// The item, set when a render passes a different one for this key.
+    pub item: Signal<T>,
+    scope: Rc<Scope>,
+}
+
+impl<K: Clone, T> Clone for KeyedItem<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            item: self.item.clone(),
+            scope: self.scope.clone(),
+        }
+    }
+}
+
+impl<K, T> KeyedItem<K, T> {
+    /// The item's scope. Effects created in [`Scope::run`] on it are
+    /// disposed of when the item leaves the list.
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+}
+
+impl<K: std::fmt::Debug, T: std::fmt::Debug> std::fmt::Debug for KeyedItem<K, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedItem")
+            .field("key", &self.key)
+            .field("item", &self.item)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The items of a keyed list, as of the last render.
+pub(crate) struct KeyedList<K, T> {
+    items: Vec<KeyedItem<K, T>>,
+}
+
+impl<K, T> Default for KeyedList<K, T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<K, T> KeyedList<K, T>
+where
+    K: Eq + Hash + Clone,
+    T: PartialEq + 'static,
+{
+    /// Match `items` to the current ones by key, setting the signals of
+    /// those that changed and dropping those that are gone. Returns the
+    /// items in their new order.
+    pub(crate) fn update(&mut self, items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<KeyedItem<K, T>> {
+        let mut old: HashMap<K, KeyedItem<K, T>> = self
+            .items
+            .drain(..)
+            .map(|item| (item.key.clone(), item))
+            .collect();
+        batch(|| {
+            for value in items {
+                let key = key(&value);
+                let item = match old.remove(&key) {
+                    Some(item) => {
+                        if untracked(|| item.item.with(|current| *current != value)) {
+                            item.item.set(value);
+                        }
+                        item
+                    }
+                    None => KeyedItem {
+                        key,
+                        item: Signal::new(value),
+                        scope: Rc::new(Scope::new()),
+                    },
+                };
+                self.items.push(item);
+            }
+        });
+        self.items.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactive::Effect;
+    use std::cell::Cell;
+
+    #[test]
+    fn items_keep_their_signals_by_key() {
+        let mut list = KeyedList::default();
+        let rows = list.update(vec![(1, "a"), (2, "b")], |row| row.0);
+        let (first, second) = (rows[0].item.clone(), rows[1].item.clone());
+
+        let runs = Rc::new(Cell::new(0));
+        let (item, count) = (second.clone(), runs.clone());
+        rows[1].scope().run(|| {
+            Effect::new(move || {
+                let _ = item.get();
+                count.set(count.get() + 1);
+            });
+        });
+        drop(rows);
+
+        // Editing and moving the first row doesn't touch the second's
+        let moved = list.update(vec![(2, "b"), (1, "A")], |row| row.0);
+        assert_eq!(moved[0].key, 2);
+        assert_eq!(first.get(), (1, "A"));
+        assert_eq!(runs.get(), 1);
+        drop(moved);
+
+        list.update(vec![(1, "A"), (2, "B")], |row| row.0);
+        assert_eq!(runs.get(), 2);
+
+        // Removing the row disposes of its effects
+        list.update(vec![(1, "A")], |row| row.0);
+        second.set((2, "C"));
+        assert_eq!(runs.get(), 2);
+    }
+}
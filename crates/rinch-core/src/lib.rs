@@ -1,22 +1,91 @@
 //! Core types and traits for rinch.
 
+pub mod a11y;
+pub mod animation;
+pub mod clock;
+pub mod deep_link;
 pub mod element;
+pub mod error_boundary;
 pub mod event;
 pub mod events;
 pub mod hooks;
+pub mod hot_state;
+pub mod hotkey;
+pub mod i18n;
+pub mod keyed_list;
+pub mod lifecycle;
+pub mod node_ref;
+pub mod portal;
+pub mod power;
 pub mod reactive;
+pub mod resource;
+pub mod store;
+pub mod suspense;
+pub mod task;
 
 // Re-export reactive types for convenience
-pub use reactive::{batch, derived, untracked, Effect, Memo, Scope, Signal};
+pub use reactive::{
+    batch, derived, reactive_graph, reactive_graph_version, reset_after_panic, untracked, Effect,
+    Memo, ReactiveGraph, ReactiveNode, ReactiveNodeKind, Scope, Signal,
+};
 
 // Re-export hooks for ergonomic state management
 pub use hooks::{
-    begin_render, clear_hooks, create_context, end_render, get_hooks_debug_info, use_callback,
-    use_context, use_derived, use_effect, use_effect_cleanup, use_memo, use_mount, use_ref,
-    use_signal, use_state, HookMeta, RefHandle,
+    begin_render, clear_hooks, create_context, end_render, get_hooks_debug_info, on_cleanup,
+    run_effects, use_animated, use_animated_then, use_animation_frame, use_callback, use_context,
+    use_debounced, use_deep_links, use_derived, use_effect, use_effect_cleanup, use_effect_with,
+    use_element_size, use_error_boundary, use_hotkey, use_idle, use_infinite_scroll,
+    use_keyed_list, use_lazy, use_memo, use_mount, use_node_ref, use_ref, use_resource,
+    use_scroll_progress, use_signal, use_spring, use_state, use_store, use_transition_group,
+    use_visibility, HookMeta, RefHandle,
+};
+
+// Re-export hot restart state
+pub use hot_state::{keep_on_reload, HotValue};
+
+// Re-export animation types
+pub use animation::{
+    override_reduced_motion, prefers_reduced_motion, request_animation_frame, request_paint_frame,
+    with_full_motion, Animatable, Color, Easing, FrameTime, SpringConfig, TransitionItem,
+    TransitionPhase,
 };
 
+// Re-export async loading
+pub use resource::{Resource, ResourceState};
+pub use task::{poll_tasks, spawn_local, TaskHandle};
+
+// Re-export stores and keyed lists
+pub use keyed_list::KeyedItem;
+pub use store::Store;
+
+// Re-export the clock
+pub use clock::{clear_timeout, set_timeout, Clock, TimerId};
+
 // Re-export event handling types
 pub use events::{
-    clear_handlers, dispatch_event, register_handler, EventCallback, EventHandlerId,
+    clear_handlers, dispatch_clipboard_event, dispatch_drag_event, dispatch_event,
+    dispatch_input_event, dispatch_key_event, dispatch_scroll_event, register_clipboard_handler,
+    register_drag_handler, register_handler, register_input_handler, register_key_handler,
+    register_scroll_handler, ClipboardCallback, ClipboardEvent, ClipboardImage, DragCallback,
+    DragEvent, EventCallback, EventHandlerId, InputCallback, KeyCallback, KeyboardEvent,
+    ScrollCallback, ScrollEvent,
+};
+
+// Re-export lifecycle events
+pub use lifecycle::{on_before_quit, on_resume, on_session_ending, on_suspend};
+
+// Re-export portals
+pub use portal::create_portal;
+
+// Re-export suspense and error boundaries
+pub use error_boundary::{throw_error, CaughtError};
+pub use suspense::{mark_pending, suspense};
+
+// Re-export power and user activity
+pub use power::{idle_time, power, PowerStatus};
+
+// Re-export element references
+pub use node_ref::{
+    request_remeasure, AnimatedStyle, FocusRequest, LayoutRect, Margins, NodeRef, ScrollAlignment,
+    ScrollBehavior, ScrollOptions, ScrollRequest, Visibility,
 };
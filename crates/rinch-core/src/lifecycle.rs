@@ -0,0 +1,159 @@
+//! Application lifecycle events.
+//!
+//! Apps register handlers while rendering, as they do `onclick`s: each
+//! render's handlers replace the previous render's, so a handler can close
+//! over the state it needs (the open documents, say) and stops being called
+//! once the component that registered it stops rendering.
+//!
+//! The runtime calls them when:
+//!
+//! - [`on_before_quit`]: the app is about to quit because its last window
+//!   is closing or it called `rinch::quit`. Any handler can cancel.
+//! - [`on_suspend`] / [`on_resume`]: the OS suspends the app's windows and
+//!   gives them back (on platforms that do, such as mobile ones).
+//! - [`on_session_ending`]: the app is being terminated without being asked
+//!   first, as when macOS quits it at logout or the Dock's Quit. There's no
+//!   cancelling; save what can't be lost.
+
+use std::cell::RefCell;
+
+thread_local! {
+    static HANDLERS: RefCell<Handlers> = RefCell::new(Handlers::default());
+}
+
+#[derive(Default)]
+struct Handlers {
+    before_quit: Vec<Box<dyn Fn() -> bool>>,
+    suspend: Vec<Box<dyn Fn()>>,
+    resume: Vec<Box<dyn Fn()>>,
+    session_ending: Vec<Box<dyn Fn()>>,
+}
+
+/// Call `handler` before the app quits. Return `false` to keep it running,
+/// such as to ask about unsaved changes first.
+///
+/// # Example
+///
+/// ```ignore
+/// let documents = use_signal(Vec::<Document>::new);
+/// let confirming = use_signal(|| false);
+/// on_before_quit(move || {
+///     let unsaved = documents.get().iter().any(|doc| doc.dirty);
+///     if unsaved {
+///         // Show a "Save changes?" prompt, which quits when answered
+///         confirming.set(true);
+///     }
+///     !unsaved
+/// });
+/// ```
+pub fn on_before_quit(handler: impl Fn() -> bool + 'static) {
+    HANDLERS.with(|handlers| handlers.borrow_mut().before_quit.push(Box::new(handler)));
+}
+
+/// Call `handler` when the OS suspends the app.
+pub fn on_suspend(handler: impl Fn() + 'static) {
+    HANDLERS.with(|handlers| handlers.borrow_mut().suspend.push(Box::new(handler)));
+}
+
+/// Call `handler` when the OS resumes the app after suspending it.
+pub fn on_resume(handler: impl Fn() + 'static) {
+    HANDLERS.with(|handlers| handlers.borrow_mut().resume.push(Box::new(handler)));
+}
+
+/// Call `handler` when the app is being terminated without a chance to
+/// cancel.
+pub fn on_session_ending(handler: impl Fn() + 'static) {
+    HANDLERS.with(|handlers| handlers.borrow_mut().session_ending.push(Box::new(handler)));
+}
+
+/// Ask the before-quit handlers whether the app may quit (called by the
+/// runtime). Every handler is called, even after one cancels, so each can
+/// react; the app quits only if none cancelled.
+pub fn run_before_quit() -> bool {
+    with_handlers(|handlers| {
+        let answers: Vec<bool> = handlers
+            .before_quit
+            .iter()
+            .map(|handler| handler())
+            .collect();
+        answers.into_iter().all(|quit| quit)
+    })
+}
+
+/// Call the suspend handlers (called by the runtime).
+pub fn run_suspend() {
+    with_handlers(|handlers| handlers.suspend.iter().for_each(|handler| handler()));
+}
+
+/// Call the resume handlers (called by the runtime).
+pub fn run_resume() {
+    with_handlers(|handlers| handlers.resume.iter().for_each(|handler| handler()));
+}
+
+/// Call the session-ending handlers (called by the runtime).
+pub fn run_session_ending() {
+    with_handlers(|handlers| handlers.session_ending.iter().for_each(|handler| handler()));
+}
+
+/// Forget the registered handlers (called before each render).
+pub fn clear_lifecycle_handlers() {
+    HANDLERS.with(|handlers| *handlers.borrow_mut() = Handlers::default());
+}
+
+/// Run `f` with the handlers taken out, so handlers can register others
+/// (or re-render) without a double borrow. Handlers registered meanwhile
+/// are kept.
+fn with_handlers<R>(f: impl FnOnce(&Handlers) -> R) -> R {
+    let handlers = HANDLERS.with(|handlers| std::mem::take(&mut *handlers.borrow_mut()));
+    let result = f(&handlers);
+    HANDLERS.with(|current| {
+        let mut current = current.borrow_mut();
+        let added = std::mem::replace(&mut *current, handlers);
+        current.before_quit.extend(added.before_quit);
+        current.suspend.extend(added.suspend);
+        current.resume.extend(added.resume);
+        current.session_ending.extend(added.session_ending);
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn any_before_quit_handler_cancels() {
+        clear_lifecycle_handlers();
+        assert!(run_before_quit());
+
+        let asked = Rc::new(Cell::new(0));
+        for allow in [false, true] {
+            let asked = asked.clone();
+            on_before_quit(move || {
+                asked.set(asked.get() + 1);
+                allow
+            });
+        }
+        assert!(!run_before_quit());
+        assert_eq!(asked.get(), 2);
+
+        clear_lifecycle_handlers();
+        assert!(run_before_quit());
+    }
+
+    #[test]
+    fn handlers_can_register_handlers() {
+        clear_lifecycle_handlers();
+        let resumed = Rc::new(Cell::new(0));
+        let counter = resumed.clone();
+        on_resume(move || {
+            let counter = counter.clone();
+            on_resume(move || counter.set(counter.get() + 1));
+        });
+        run_resume();
+        run_resume();
+        assert_eq!(resumed.get(), 1);
+    }
+}
@@ -0,0 +1,810 @@
+//! References to rendered elements.
+//!
+//! A [`NodeRef`] ties an element in the rendered document to reactive state
+//! the app can read, such as its scroll offset, size, and visibility. Attach
+//! one with the `node_ref` prop:
+//!
+//! ```ignore
+//! let list = use_node_ref();
+//! let scrolled = list.scroll_offset().1 > 0.0;
+//!
+//! rsx! {
+//!     div { class: if scrolled { "header shadow" } else { "header" }, "Files" }
+//!     div { class: "list", node_ref: list, /* ... */ }
+//! }
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::events::ScrollEvent;
+use crate::reactive::Signal;
+
+/// Attribute the `node_ref` prop renders to.
+pub const NODE_REF_ATTR: &str = "data-node-ref";
+
+static NEXT_NODE_REF_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Live refs by ID, so the runtime can update them.
+    static NODE_REFS: RefCell<HashMap<u64, NodeRef>> = RefCell::new(HashMap::new());
+    /// Pending scroll calls, for the runtime to carry out.
+    static SCROLL_REQUESTS: RefCell<Vec<(u64, ScrollRequest)>> = const { RefCell::new(Vec::new()) };
+    /// Pending focus calls, for the runtime to carry out.
+    static FOCUS_REQUESTS: RefCell<Vec<(u64, FocusRequest)>> = const { RefCell::new(Vec::new()) };
+    /// Refs whose elements should be measured again, for the runtime.
+    static REMEASURE_REQUESTS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    /// Refs whose [`NodeRef::rect`] has been read, so the runtime reports it.
+    static MEASURED_REFS: RefCell<HashSet<u64>> = RefCell::new(HashSet::new());
+    /// Refs whose visibility the app is watching, and how.
+    static VISIBILITY_QUERIES: RefCell<HashMap<u64, VisibilityQuery>> = RefCell::new(HashMap::new());
+    /// Paint-time styles set through refs.
+    static ANIMATED_STYLES: RefCell<HashMap<u64, AnimatedStyle>> = RefCell::new(HashMap::new());
+    /// Whether `ANIMATED_STYLES` changed since the runtime last took it.
+    static ANIMATED_STYLES_CHANGED: Cell<bool> = const { Cell::new(false) };
+    /// Animated styles driven by scrolling, by target ref.
+    static SCROLL_BINDINGS: RefCell<HashMap<u64, ScrollBinding>> = RefCell::new(HashMap::new());
+}
+
+/// An animated style computed from a scroll container's state, and the ref
+/// of that container.
+pub type ScrollBinding = (u64, std::rc::Rc<dyn Fn(ScrollEvent) -> AnimatedStyle>);
+
+/// Where to align an element along an axis when scrolling it into view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollAlignment {
+    /// Align the element's start edge with the visible area's.
+    Start,
+    /// Center the element in the visible area.
+    Center,
+    /// Align the element's end edge with the visible area's.
+    End,
+    /// Scroll as little as possible to make the element visible, or not at
+    /// all if it already is.
+    #[default]
+    Nearest,
+}
+
+/// How a scroll happens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollBehavior {
+    /// Jump straight to the new position.
+    #[default]
+    Instant,
+    /// Animate to the new position (jump when the user prefers reduced
+    /// motion).
+    Smooth,
+}
+
+/// Options for [`NodeRef::scroll_into_view`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollOptions {
+    /// Vertical alignment.
+    pub block: ScrollAlignment,
+    /// Horizontal alignment.
+    pub inline: ScrollAlignment,
+    /// Whether to jump or animate.
+    pub behavior: ScrollBehavior,
+}
+
+/// A scroll requested through a [`NodeRef`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollRequest {
+    /// Scroll the element's containers so the element is visible.
+    IntoView(ScrollOptions),
+    /// Scroll the element itself to an offset.
+    To {
+        /// Horizontal offset, in logical pixels.
+        x: f64,
+        /// Vertical offset, in logical pixels.
+        y: f64,
+        /// Whether to jump or animate.
+        behavior: ScrollBehavior,
+    },
+}
+
+/// A focus change requested through a [`NodeRef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusRequest {
+    /// Focus the element.
+    Focus,
+    /// Take focus away from the element, if it has it.
+    Blur,
+    /// Put a text field's caret before the character at this index.
+    Caret(usize),
+}
+
+/// How much of an element is visible.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Visibility {
+    /// Whether any of the element is visible (or, for an empty element,
+    /// whether it's inside the visible area).
+    pub visible: bool,
+    /// The fraction of the element's area that's visible, from 0 to 1.
+    pub ratio: f64,
+}
+
+/// Space added around the visible area when checking visibility, in logical
+/// pixels. Positive margins count elements as visible before they scroll
+/// into view, such as to start loading images early; negative margins
+/// shrink the area.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Margins {
+    /// Added above the area.
+    pub top: f64,
+    /// Added to the right of the area.
+    pub right: f64,
+    /// Added below the area.
+    pub bottom: f64,
+    /// Added to the left of the area.
+    pub left: f64,
+}
+
+impl Margins {
+    /// The same margin on every side.
+    pub fn all(margin: f64) -> Self {
+        Self {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+}
+
+/// Where an element's border box is in the window, in logical pixels, after
+/// scrolling.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutRect {
+    /// Left edge, from the window's left edge.
+    pub x: f64,
+    /// Top edge, from the window's top edge.
+    pub y: f64,
+    /// Width, including padding and borders.
+    pub width: f64,
+    /// Height, including padding and borders.
+    pub height: f64,
+}
+
+impl LayoutRect {
+    /// The right edge.
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    /// The bottom edge.
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    /// Whether the point `(x, y)` is inside the rectangle.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
+
+/// What a ref's visibility is checked against.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VisibilityQuery {
+    /// The ref of the scroll container whose visible area counts, or `None`
+    /// for the window (through any scroll containers in between).
+    pub root: Option<u64>,
+    /// Space added around the root's visible area.
+    pub margins: Margins,
+}
+
+/// Adjustments to how an element is painted, set with
+/// [`NodeRef::set_animated_style`].
+///
+/// They take effect at paint time: changing them doesn't re-render the app,
+/// restyle, or lay anything out, so they can change every frame. Layout
+/// still places the element (and everything around it) where it would be
+/// without them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnimatedStyle {
+    /// Horizontal offset, in logical pixels.
+    pub translate_x: f64,
+    /// Vertical offset, in logical pixels.
+    pub translate_y: f64,
+}
+
+impl AnimatedStyle {
+    /// An offset by `(x, y)` logical pixels.
+    pub fn translate(x: f64, y: f64) -> Self {
+        Self {
+            translate_x: x,
+            translate_y: y,
+        }
+    }
+}
+
+/// A reference to a rendered element.
+///
+/// Create one with [`use_node_ref`](crate::hooks::use_node_ref) and pass it
+/// as an element's `node_ref` prop.
+#[derive(Clone)]
+pub struct NodeRef {
+    id: u64,
+    scroll: Signal<ScrollEvent>,
+    size: Signal<(f64, f64)>,
+    rect: Signal<LayoutRect>,
+    visibility: Signal<Visibility>,
+}
+
+impl NodeRef {
+    /// Create a new, unattached reference.
+    pub fn new() -> Self {
+        let id = NEXT_NODE_REF_ID.fetch_add(1, Ordering::SeqCst);
+        let node_ref = Self {
+            id,
+            scroll: Signal::new(ScrollEvent::default()),
+            size: Signal::new((0.0, 0.0)),
+            rect: Signal::new(LayoutRect::default()),
+            visibility: Signal::new(Visibility::default()),
+        };
+        NODE_REFS.with(|refs| refs.borrow_mut().insert(id, node_ref.clone()));
+        node_ref
+    }
+
+    /// The reference's unique ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The element's scroll offset as `(x, y)`, in logical pixels.
+    ///
+    /// Reactive: effects and memos that read it re-run when the element
+    /// scrolls, and the app re-renders.
+    pub fn scroll_offset(&self) -> (f64, f64) {
+        let scroll = self.scroll.get();
+        (scroll.scroll_x, scroll.scroll_y)
+    }
+
+    /// How far the element is scrolled down, in logical pixels, like the
+    /// DOM's `scrollTop`. Reactive.
+    pub fn scroll_top(&self) -> f64 {
+        self.scroll_offset().1
+    }
+
+    /// How far the element is scrolled across, in logical pixels, like the
+    /// DOM's `scrollLeft`. Reactive.
+    pub fn scroll_left(&self) -> f64 {
+        self.scroll_offset().0
+    }
+
+    /// The element's full scroll state: offset, content size, and visible size.
+    pub fn scroll_state(&self) -> ScrollEvent {
+        self.scroll.get()
+    }
+
+    /// The size of the element's content box as `(width, height)`, in
+    /// logical pixels: its layout size without padding and borders. `(0, 0)`
+    /// until it has been laid out.
+    ///
+    /// Reactive, and updated after any layout that resizes the element, so
+    /// components can adapt to their own size rather than the window's.
+    pub fn size(&self) -> (f64, f64) {
+        self.size.get()
+    }
+
+    /// Where the element's border box is in the window, after scrolling, for
+    /// placing popovers and tooltips next to it. All zero until it has been
+    /// laid out.
+    ///
+    /// Reactive. The runtime starts reporting an element's rect the first
+    /// time it's read, and from then on the app re-renders whenever layout
+    /// or scrolling moves or resizes the element.
+    ///
+    /// ```ignore
+    /// let anchor = anchor_ref.rect();
+    /// let style = format!("position: fixed; left: {}px; top: {}px", anchor.x, anchor.bottom() + 4.0);
+    /// ```
+    pub fn rect(&self) -> LayoutRect {
+        MEASURED_REFS.with(|refs| refs.borrow_mut().insert(self.id));
+        self.rect.get()
+    }
+
+    /// How much of the element is visible, once something watches it with
+    /// [`watch_visibility`](Self::watch_visibility) (or
+    /// [`use_visibility`](crate::hooks::use_visibility)). Reactive.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.get()
+    }
+
+    /// Start reporting the element's [`visibility`](Self::visibility) within
+    /// `root` (a scroll container's ref, or `None` for the window), with
+    /// `margins` around the root's visible area. Replaces any earlier query.
+    pub fn watch_visibility(&self, root: Option<&NodeRef>, margins: Margins) {
+        let query = VisibilityQuery {
+            root: root.map(NodeRef::id),
+            margins,
+        };
+        VISIBILITY_QUERIES.with(|queries| queries.borrow_mut().insert(self.id, query));
+    }
+
+    /// Scroll the element's scroll containers, and the window, so the element
+    /// is visible.
+    ///
+    /// Takes effect once the current event has been handled and the app has
+    /// re-rendered, so an element rendered by the same update can be scrolled
+    /// to.
+    ///
+    /// ```ignore
+    /// item.scroll_into_view(ScrollOptions {
+    ///     block: ScrollAlignment::Nearest,
+    ///     behavior: ScrollBehavior::Smooth,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn scroll_into_view(&self, options: ScrollOptions) {
+        self.request_scroll(ScrollRequest::IntoView(options));
+    }
+
+    /// Scroll the element to an offset, in logical pixels, limited to how far
+    /// it can scroll. On `html` or `body` this scrolls the window.
+    ///
+    /// With `animated`, the element eases to the new offset over a few
+    /// frames; the animation stops if the user scrolls it or grabs its
+    /// scrollbar. Like [`scroll_into_view`](Self::scroll_into_view), this
+    /// takes effect after the current event has been handled.
+    ///
+    /// ```ignore
+    /// button { onclick: move || page.scroll_to(0.0, 0.0, true), "Back to top" }
+    /// ```
+    pub fn scroll_to(&self, x: f64, y: f64, animated: bool) {
+        let behavior = if animated {
+            ScrollBehavior::Smooth
+        } else {
+            ScrollBehavior::Instant
+        };
+        self.request_scroll(ScrollRequest::To { x, y, behavior });
+    }
+
+    /// Give the element keyboard focus, as if it had been tabbed to. It must
+    /// be focusable: a link, button or form field, or have a `tabindex`.
+    ///
+    /// Like [`scroll_into_view`](Self::scroll_into_view), this takes effect
+    /// after the current event has been handled, so it can focus an element
+    /// the same handler just added.
+    ///
+    /// ```ignore
+    /// button { onclick: move || search.focus(), "Search" }
+    /// input { node_ref: search, bind: query }
+    /// ```
+    pub fn focus(&self) {
+        FOCUS_REQUESTS.with(|requests| requests.borrow_mut().push((self.id, FocusRequest::Focus)));
+    }
+
+    /// Take keyboard focus away from the element, if it has it.
+    pub fn blur(&self) {
+        FOCUS_REQUESTS.with(|requests| requests.borrow_mut().push((self.id, FocusRequest::Blur)));
+    }
+
+    /// Put the caret of a text field (`input` or `textarea`) before the
+    /// character at `position`, or at the end if its text is shorter.
+    ///
+    /// Like [`focus`](Self::focus), this takes effect after the current
+    /// event has been handled, so it places the caret in the value a
+    /// handler just set. A field that rewrites what's typed is rebuilt,
+    /// which moves its caret, and uses it to put the caret back:
+    ///
+    /// ```ignore
+    /// input {
+    ///     node_ref: field,
+    ///     value: {code.get()},
+    ///     oninput: move |typed: String| {
+    ///         field.set_caret(typed.chars().count());
+    ///         code.set(typed.to_uppercase());
+    ///     }
+    /// }
+    /// ```
+    pub fn set_caret(&self, position: usize) {
+        FOCUS_REQUESTS.with(|requests| {
+            requests
+                .borrow_mut()
+                .push((self.id, FocusRequest::Caret(position)))
+        });
+    }
+
+    /// Set how the element is painted, bypassing re-rendering.
+    ///
+    /// For motion that follows the pointer or runs every frame, where a
+    /// re-render per change would be wasted work: only the window is
+    /// repainted. The style persists across re-renders until replaced; set
+    /// [`AnimatedStyle::default()`] to clear it. Drive it from an event
+    /// handler or a [`request_paint_frame`](crate::animation::request_paint_frame)
+    /// callback.
+    ///
+    /// ```ignore
+    /// // Shake a card for 0.4s, repainting each frame without re-rendering
+    /// fn shake(card: NodeRef, start: Instant) {
+    ///     request_paint_frame(move |now| {
+    ///         let t = (now - start).as_secs_f64();
+    ///         if t < 0.4 {
+    ///             let x = (t * 60.0).sin() * 8.0 * (1.0 - t / 0.4);
+    ///             card.set_animated_style(AnimatedStyle::translate(x, 0.0));
+    ///             shake(card, start);
+    ///         } else {
+    ///             card.set_animated_style(AnimatedStyle::default());
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn set_animated_style(&self, style: AnimatedStyle) {
+        let changed = ANIMATED_STYLES.with(|styles| {
+            let mut styles = styles.borrow_mut();
+            let previous = if style == AnimatedStyle::default() {
+                styles.remove(&self.id)
+            } else {
+                styles.insert(self.id, style)
+            };
+            previous.unwrap_or_default() != style
+        });
+        if changed {
+            ANIMATED_STYLES_CHANGED.with(|flag| flag.set(true));
+        }
+    }
+
+    /// Drive the element's [`AnimatedStyle`] from how far `source` (a scroll
+    /// container's ref) is scrolled.
+    ///
+    /// `style` runs at paint time with the container's scroll state whenever
+    /// it has scrolled, so scroll-linked effects like parallax and collapsing
+    /// headers track every pixel without re-rendering the app. Takes
+    /// precedence over [`set_animated_style`](Self::set_animated_style).
+    /// Binding again replaces the binding, so it's fine to bind during
+    /// render.
+    ///
+    /// ```ignore
+    /// // Slide the header up by half as far as the page scrolls, up to 80px
+    /// header.bind_scroll(&page, |scroll| {
+    ///     AnimatedStyle::translate(0.0, -(scroll.scroll_y / 2.0).min(80.0))
+    /// });
+    /// ```
+    pub fn bind_scroll(
+        &self,
+        source: &NodeRef,
+        style: impl Fn(ScrollEvent) -> AnimatedStyle + 'static,
+    ) {
+        let binding: ScrollBinding = (source.id, std::rc::Rc::new(style));
+        SCROLL_BINDINGS.with(|bindings| bindings.borrow_mut().insert(self.id, binding));
+    }
+
+    /// Remove a [`bind_scroll`](Self::bind_scroll) binding.
+    pub fn unbind_scroll(&self) {
+        SCROLL_BINDINGS.with(|bindings| bindings.borrow_mut().remove(&self.id));
+    }
+
+    /// The style last set with [`set_animated_style`](Self::set_animated_style).
+    /// Not reactive.
+    pub fn animated_style(&self) -> AnimatedStyle {
+        ANIMATED_STYLES.with(|styles| styles.borrow().get(&self.id).copied().unwrap_or_default())
+    }
+
+    fn request_scroll(&self, mut request: ScrollRequest) {
+        if crate::animation::reduce_motion() {
+            match &mut request {
+                ScrollRequest::IntoView(options) => options.behavior = ScrollBehavior::Instant,
+                ScrollRequest::To { behavior, .. } => *behavior = ScrollBehavior::Instant,
+            }
+        }
+        SCROLL_REQUESTS.with(|requests| requests.borrow_mut().push((self.id, request)));
+    }
+}
+
+impl Default for NodeRef {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for NodeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeRef").field("id", &self.id).finish()
+    }
+}
+
+/// Renders as the ID, which is what the `node_ref` attribute holds.
+impl fmt::Display for NodeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+/// Record a referenced element's new scroll state (called by the runtime).
+///
+/// Returns `true` if the ref exists and its state changed.
+pub fn update_node_ref_scroll(id: u64, scroll: ScrollEvent) -> bool {
+    update_signal(id, |node_ref| &node_ref.scroll, scroll)
+}
+
+/// Record a referenced element's new content-box size (called by the runtime).
+///
+/// Returns `true` if the ref exists and its size changed.
+pub fn update_node_ref_size(id: u64, size: (f64, f64)) -> bool {
+    update_signal(id, |node_ref| &node_ref.size, size)
+}
+
+/// Record a referenced element's new border box (called by the runtime).
+///
+/// Returns `true` if the ref exists and its rect changed.
+pub fn update_node_ref_rect(id: u64, rect: LayoutRect) -> bool {
+    update_signal(id, |node_ref| &node_ref.rect, rect)
+}
+
+/// Whether the app has read a ref's [`NodeRef::rect`], so the runtime
+/// should report it (called by the runtime).
+pub fn measures_rect(id: u64) -> bool {
+    MEASURED_REFS.with(|refs| refs.borrow().contains(&id))
+}
+
+/// Record a referenced element's new visibility (called by the runtime).
+///
+/// Returns `true` if the ref exists and its visibility changed.
+pub fn update_node_ref_visibility(id: u64, visibility: Visibility) -> bool {
+    update_signal(id, |node_ref| &node_ref.visibility, visibility)
+}
+
+/// How a ref's visibility should be checked, if the app is watching it
+/// (called by the runtime).
+pub fn visibility_query(id: u64) -> Option<VisibilityQuery> {
+    VISIBILITY_QUERIES.with(|queries| queries.borrow().get(&id).copied())
+}
+
+fn update_signal<T: Clone + PartialEq + 'static>(
+    id: u64,
+    signal: impl Fn(&NodeRef) -> &Signal<T>,
+    value: T,
+) -> bool {
+    let node_ref = NODE_REFS.with(|refs| refs.borrow().get(&id).cloned());
+    match node_ref {
+        Some(node_ref) if signal(&node_ref).with(|current| *current != value) => {
+            signal(&node_ref).set(value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Take pending scroll calls as `(ref ID, request)` (called by the runtime).
+pub fn take_scroll_requests() -> Vec<(u64, ScrollRequest)> {
+    SCROLL_REQUESTS.with(|requests| std::mem::take(&mut *requests.borrow_mut()))
+}
+
+/// Take pending focus calls as `(ref ID, request)` (called by the runtime).
+pub fn take_focus_requests() -> Vec<(u64, FocusRequest)> {
+    FOCUS_REQUESTS.with(|requests| std::mem::take(&mut *requests.borrow_mut()))
+}
+
+/// Take refs whose elements should be measured again (called by the
+/// runtime).
+pub fn take_remeasure_requests() -> Vec<u64> {
+    REMEASURE_REQUESTS.with(|requests| std::mem::take(&mut *requests.borrow_mut()))
+}
+
+/// Measure an element's text and boxes again at the next layout, with
+/// everything inside it and the elements around it.
+///
+/// Rinch does this itself when a `:hover`, `:focus` or in-place stylesheet
+/// change shows an element that `display: none` or `visibility: hidden`
+/// hid. Call it when content is revealed some other way and comes out with
+/// a stale size, such as zero height. Like [`NodeRef::focus`], it takes
+/// effect after the current event has been handled.
+///
+/// ```ignore
+/// button {
+///     onclick: move || {
+///         open.set(true);
+///         request_remeasure(&popover);
+///     },
+///     "Details"
+/// }
+/// div { node_ref: popover, class: if open.get() { "popover open" } else { "popover" }, /* ... */ }
+/// ```
+pub fn request_remeasure(node: &NodeRef) {
+    REMEASURE_REQUESTS.with(|requests| requests.borrow_mut().push(node.id));
+}
+
+/// Every ref's animated style, if any changed since the last call (called
+/// by the runtime).
+pub fn take_animated_styles() -> Option<HashMap<u64, AnimatedStyle>> {
+    if !ANIMATED_STYLES_CHANGED.with(|flag| flag.replace(false)) {
+        return None;
+    }
+    Some(ANIMATED_STYLES.with(|styles| styles.borrow().clone()))
+}
+
+/// Scroll-driven animated styles, as target ref → binding (called by the
+/// window when it paints).
+pub fn scroll_bindings() -> Vec<(u64, ScrollBinding)> {
+    SCROLL_BINDINGS.with(|bindings| {
+        bindings
+            .borrow()
+            .iter()
+            .map(|(&target, binding)| (target, binding.clone()))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_reach_the_ref() {
+        let node_ref = NodeRef::new();
+        assert_eq!(node_ref.scroll_offset(), (0.0, 0.0));
+
+        let scroll = ScrollEvent {
+            scroll_y: 120.0,
+            content_height: 900.0,
+            client_height: 300.0,
+            ..Default::default()
+        };
+        assert!(update_node_ref_scroll(node_ref.id(), scroll));
+        assert!(!update_node_ref_scroll(node_ref.id(), scroll));
+        assert_eq!(node_ref.scroll_offset(), (0.0, 120.0));
+        assert_eq!(
+            (node_ref.scroll_left(), node_ref.scroll_top()),
+            (0.0, 120.0)
+        );
+        assert_eq!(node_ref.to_string(), node_ref.id().to_string());
+
+        assert_eq!(node_ref.size(), (0.0, 0.0));
+        assert!(update_node_ref_size(node_ref.id(), (320.0, 48.0)));
+        assert!(!update_node_ref_size(node_ref.id(), (320.0, 48.0)));
+        assert_eq!(node_ref.size(), (320.0, 48.0));
+        assert!(!update_node_ref_size(u64::MAX, (1.0, 1.0)));
+    }
+
+    #[test]
+    fn rects_are_reported_once_read() {
+        let node_ref = NodeRef::new();
+        assert!(!measures_rect(node_ref.id()));
+        assert_eq!(node_ref.rect(), LayoutRect::default());
+        assert!(measures_rect(node_ref.id()));
+
+        let rect = LayoutRect {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 30.0,
+        };
+        assert!(update_node_ref_rect(node_ref.id(), rect));
+        assert!(!update_node_ref_rect(node_ref.id(), rect));
+        assert_eq!(node_ref.rect(), rect);
+        assert_eq!((rect.right(), rect.bottom()), (110.0, 50.0));
+        assert!(rect.contains(10.0, 49.0));
+        assert!(!rect.contains(110.0, 20.0));
+    }
+
+    #[test]
+    fn visibility_is_reported_once_watched() {
+        let list = NodeRef::new();
+        let item = NodeRef::new();
+        assert_eq!(visibility_query(item.id()), None);
+
+        item.watch_visibility(Some(&list), Margins::all(100.0));
+        assert_eq!(
+            visibility_query(item.id()),
+            Some(VisibilityQuery {
+                root: Some(list.id()),
+                margins: Margins::all(100.0),
+            })
+        );
+
+        let half = Visibility {
+            visible: true,
+            ratio: 0.5,
+        };
+        assert!(update_node_ref_visibility(item.id(), half));
+        assert_eq!(item.visibility(), half);
+    }
+
+    #[test]
+    fn scroll_calls_are_queued_in_order() {
+        let node_ref = NodeRef::new();
+        let options = ScrollOptions {
+            behavior: ScrollBehavior::Smooth,
+            ..Default::default()
+        };
+        node_ref.scroll_into_view(options);
+        node_ref.scroll_to(0.0, 50.0, true);
+
+        assert_eq!(
+            take_scroll_requests(),
+            vec![
+                (node_ref.id(), ScrollRequest::IntoView(options)),
+                (
+                    node_ref.id(),
+                    ScrollRequest::To {
+                        x: 0.0,
+                        y: 50.0,
+                        behavior: ScrollBehavior::Smooth
+                    }
+                ),
+            ]
+        );
+        assert!(take_scroll_requests().is_empty());
+    }
+
+    #[test]
+    fn focus_calls_are_queued_in_order() {
+        let search = NodeRef::new();
+        search.focus();
+        search.set_caret(3);
+        search.blur();
+        assert_eq!(
+            take_focus_requests(),
+            vec![
+                (search.id(), FocusRequest::Focus),
+                (search.id(), FocusRequest::Caret(3)),
+                (search.id(), FocusRequest::Blur)
+            ]
+        );
+        assert!(take_focus_requests().is_empty());
+    }
+
+    #[test]
+    fn remeasure_calls_are_queued() {
+        let popover = NodeRef::new();
+        request_remeasure(&popover);
+        assert_eq!(take_remeasure_requests(), vec![popover.id()]);
+        assert!(take_remeasure_requests().is_empty());
+    }
+
+    #[test]
+    fn animated_styles_are_taken_when_changed() {
+        let node_ref = NodeRef::new();
+        assert_eq!(take_animated_styles(), None);
+
+        node_ref.set_animated_style(AnimatedStyle::translate(4.0, -2.0));
+        assert_eq!(
+            node_ref.animated_style(),
+            AnimatedStyle::translate(4.0, -2.0)
+        );
+        let styles = take_animated_styles().unwrap();
+        assert_eq!(
+            styles.get(&node_ref.id()),
+            Some(&AnimatedStyle::translate(4.0, -2.0))
+        );
+        assert_eq!(take_animated_styles(), None);
+
+        // Setting the same style again isn't a change
+        node_ref.set_animated_style(AnimatedStyle::translate(4.0, -2.0));
+        assert_eq!(take_animated_styles(), None);
+
+        node_ref.set_animated_style(AnimatedStyle::default());
+        assert!(take_animated_styles().unwrap().is_empty());
+    }
+
+    #[test]
+    fn scroll_bindings_compute_styles_from_scroll_state() {
+        let page = NodeRef::new();
+        let header = NodeRef::new();
+        header.bind_scroll(&page, |scroll| {
+            AnimatedStyle::translate(0.0, -scroll.progress_y() * 100.0)
+        });
+
+        let bindings = scroll_bindings();
+        let (target, (source, style)) = bindings
+            .iter()
+            .find(|(target, _)| *target == header.id())
+            .unwrap();
+        assert_eq!((*target, *source), (header.id(), page.id()));
+        let scroll = ScrollEvent {
+            scroll_y: 250.0,
+            content_height: 1500.0,
+            client_height: 500.0,
+            ..Default::default()
+        };
+        assert_eq!(style(scroll), AnimatedStyle::translate(0.0, -25.0));
+
+        header.unbind_scroll();
+        assert!(scroll_bindings()
+            .iter()
+            .all(|(target, _)| *target != header.id()));
+    }
+}
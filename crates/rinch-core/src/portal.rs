@@ -0,0 +1,236 @@
+//! Portals: content rendered at the end of the document, or of another
+//! element, rather than where it appears in the tree.
+//!
+//! Dropdowns, tooltips and modals inside a scroller or an `overflow:
+//! hidden` box get clipped by it, and stack under whatever comes after
+//! it. A portal lifts them out: its children are laid out as the last
+//! children of `body` (or of the element with the target `id`), so only
+//! the viewport clips them and they paint over the rest of the page.
+//!
+//! Only the content moves. It still renders with the component that holds
+//! the portal, so it reads the same signals and contexts, and its event
+//! handlers run as they would in place.
+
+use std::borrow::Cow;
+
+use crate::element::{children_to_html, Element};
+use crate::events::html_escape_string;
+
+/// The tag wrapping a portal's content in rendered HTML, until the window
+/// moves it to its target.
+pub const PORTAL_TAG: &str = "rinch-portal";
+
+/// The attribute on [`PORTAL_TAG`] with the `id` of the element the
+/// content goes into; without it, the content goes into `body`.
+pub const PORTAL_TARGET_ATTR: &str = "data-target";
+
+/// Render `children` into the element with the `id` `target`, or at the
+/// end of `body` if `target` is empty or no element has that `id`. This is
+/// what the `Portal` component expands to.
+///
+/// # Example
+///
+/// ```ignore
+/// fn dropdown(open: Signal<bool>) -> Element {
+///     let menu = rsx! { div { class: "menu", "..." } };
+///     if open.get() { create_portal("", menu) } else { Element::Fragment(vec![]) }
+/// }
+/// ```
+pub fn create_portal(target: &str, children: Element) -> Element {
+    let content = children_to_html(&[children]);
+    let html = if target.is_empty() {
+        format!("<{PORTAL_TAG}>{content}</{PORTAL_TAG}>")
+    } else {
+        format!(
+            "<{PORTAL_TAG} {PORTAL_TARGET_ATTR}=\"{}\">{content}</{PORTAL_TAG}>",
+            html_escape_string(target)
+        )
+    };
+    Element::Html(html)
+}
+
+/// Move the content of the portals in a window's HTML to their targets,
+/// dropping the [`PORTAL_TAG`] wrappers. Portals land in the order they
+/// were rendered, a portal inside another one after it.
+pub fn hoist_portals(html: &str) -> Cow<'_, str> {
+    let open = format!("<{PORTAL_TAG}");
+    if !html.contains(&open) {
+        return Cow::Borrowed(html);
+    }
+
+    let mut portals = Vec::new();
+    let mut html = take_portals(html, &mut portals);
+    for (target, content) in portals {
+        let at = element_end(&html, &target)
+            .or_else(|| html.rfind("</body>"))
+            .unwrap_or(html.len());
+        html.insert_str(at, &content);
+    }
+    Cow::Owned(html)
+}
+
+/// `html` without its portals, which are added to `portals` as their
+/// target and content.
+fn take_portals(html: &str, portals: &mut Vec<(String, String)>) -> String {
+    let open = format!("<{PORTAL_TAG}");
+    let close = format!("</{PORTAL_TAG}>");
+    let mut rest = String::with_capacity(html.len());
+    let mut cursor = 0;
+    while let Some(offset) = html[cursor..].find(&open) {
+        let start = cursor + offset;
+        let Some(tag_len) = html[start..].find('>') else {
+            break;
+        };
+        let content_start = start + tag_len + 1;
+
+        // The matching close tag, past any portals inside this one
+        let mut depth = 1;
+        let mut at = content_start;
+        let content_end = loop {
+            let next_open = html[at..].find(&open).map(|i| at + i);
+            let Some(next_close) = html[at..].find(&close).map(|i| at + i) else {
+                break html.len();
+            };
+            match next_open {
+                Some(next_open) if next_open < next_close => {
+                    depth += 1;
+                    at = next_open + open.len();
+                }
+                _ => {
+                    depth -= 1;
+                    at = next_close + close.len();
+                    if depth == 0 {
+                        break next_close;
+                    }
+                }
+            }
+        };
+
+        rest.push_str(&html[cursor..start]);
+        let target = attr_value(&html[start..content_start], PORTAL_TARGET_ATTR);
+        let index = portals.len();
+        portals.push((target, String::new()));
+        portals[index].1 = take_portals(&html[content_start..content_end], portals);
+        cursor = (content_end + close.len()).min(html.len());
+    }
+    rest.push_str(&html[cursor..]);
+    rest
+}
+
+/// The value of `attr` in a start tag, unescaped, or an empty string.
+fn attr_value(tag: &str, attr: &str) -> String {
+    let prefix = format!(" {attr}=\"");
+    tag.find(&prefix)
+        .map(|i| &tag[i + prefix.len()..])
+        .and_then(|value| value.split('"').next())
+        .map(|value| {
+            value
+                .replace("&quot;", "\"")
+                .replace("&gt;", ">")
+                .replace("&lt;", "<")
+                .replace("&amp;", "&")
+        })
+        .unwrap_or_default()
+}
+
+/// Where the element with the `id` `id` ends: the start of its close tag.
+fn element_end(html: &str, id: &str) -> Option<usize> {
+    if id.is_empty() {
+        return None;
+    }
+    let attr = format!(" id=\"{}\"", html_escape_string(id));
+    let start = html[..html.find(&attr)?].rfind('<')?;
+    let name: String = html[start + 1..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let open = format!("<{name}");
+    let close = format!("</{name}>");
+
+    let mut depth = 0;
+    let mut at = start;
+    loop {
+        let next_close = at + html[at..].find(&close)?;
+        let next_open = html[at..].find(&open).map(|i| at + i);
+        match next_open {
+            Some(next_open)
+                if next_open < next_close
+                    && html[next_open + open.len()..]
+                        .starts_with(|c: char| c == '>' || c.is_ascii_whitespace()) =>
+            {
+                depth += 1;
+                at = next_open + open.len();
+            }
+            Some(next_open) if next_open < next_close => at = next_open + open.len(),
+            _ => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(next_close);
+                }
+                at = next_close + close.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portals_wrap_their_content() {
+        let html = |element: Element| match element {
+            Element::Html(html) => html,
+            _ => panic!("portals render as HTML"),
+        };
+        assert_eq!(
+            html(create_portal("", Element::Html("<p>Hi</p>".into()))),
+            "<rinch-portal><p>Hi</p></rinch-portal>"
+        );
+        assert_eq!(
+            html(create_portal("layer", Element::Html("<p>Hi</p>".into()))),
+            "<rinch-portal data-target=\"layer\"><p>Hi</p></rinch-portal>"
+        );
+    }
+
+    #[test]
+    fn portal_content_moves_to_its_target() {
+        let html = concat!(
+            "<div style=\"overflow: hidden\">",
+            "<rinch-portal><p>Menu</p></rinch-portal>",
+            "<rinch-portal data-target=\"layer\"><p>Tip</p></rinch-portal>",
+            "</div>",
+            "<div id=\"layer\"><div>Other</div></div>",
+            "<p>End</p>",
+        );
+        assert_eq!(
+            hoist_portals(html),
+            concat!(
+                "<div style=\"overflow: hidden\"></div>",
+                "<div id=\"layer\"><div>Other</div><p>Tip</p></div>",
+                "<p>End</p>",
+                "<p>Menu</p>",
+            )
+        );
+
+        let in_body = "<body><rinch-portal><p>Menu</p></rinch-portal><main></main></body>";
+        assert_eq!(
+            hoist_portals(in_body),
+            "<body><main></main><p>Menu</p></body>"
+        );
+    }
+
+    #[test]
+    fn nested_portals_land_after_their_parent() {
+        let html = concat!(
+            "<main>",
+            "<rinch-portal><div>Modal<rinch-portal><p>Tip</p></rinch-portal></div></rinch-portal>",
+            "</main>",
+        );
+        assert_eq!(
+            hoist_portals(html),
+            "<main></main><div>Modal</div><p>Tip</p>"
+        );
+        assert!(matches!(hoist_portals("<p>Plain</p>"), Cow::Borrowed(_)));
+    }
+}
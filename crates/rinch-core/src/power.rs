@@ -0,0 +1,177 @@
+//! Power and user activity.
+//!
+//! [`power`] reports the battery level and whether the computer runs on AC
+//! power, so apps can cut back on animations and background work on
+//! battery. The runtime polls the OS and updates it when it changes.
+//!
+//! [`idle_time`] and [`use_idle`](crate::hooks::use_idle) report how long
+//! the user hasn't touched the app (no key presses, clicks, scrolling,
+//! touches or pointer movement in its windows), for locking after
+//! inactivity, say.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::clock::{clear_timeout, now, set_timeout, Instant, TimerId};
+use crate::reactive::Signal;
+
+/// The computer's power source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerStatus {
+    /// Charge left, from 0.0 to 1.0, or `None` without a battery (or when it
+    /// can't be found out).
+    pub battery_level: Option<f32>,
+    /// Whether the computer is plugged in. Computers without a battery are.
+    pub on_ac_power: bool,
+}
+
+impl PowerStatus {
+    /// Whether the computer is running on its battery.
+    pub fn on_battery(&self) -> bool {
+        !self.on_ac_power
+    }
+}
+
+impl Default for PowerStatus {
+    fn default() -> Self {
+        Self {
+            battery_level: None,
+            on_ac_power: true,
+        }
+    }
+}
+
+thread_local! {
+    static STATUS: Signal<PowerStatus> = Signal::new(PowerStatus::default());
+    /// The last user input, or when it was first asked about before any.
+    static LAST_INPUT: Cell<Option<Instant>> = const { Cell::new(None) };
+    /// Whether a render was told the user is idle.
+    static IDLE_SHOWN: Cell<bool> = const { Cell::new(false) };
+    /// Changed when input ends an idle period a render saw, to re-render.
+    static RETURNS: Signal<u64> = Signal::new(0);
+}
+
+/// The computer's power source. Reactive: reading it during render
+/// re-renders the app when it changes.
+///
+/// # Example
+///
+/// ```ignore
+/// let status = power();
+/// let animate = !status.on_battery() || status.battery_level.is_some_and(|level| level > 0.5);
+/// ```
+pub fn power() -> PowerStatus {
+    STATUS.with(|status| status.get())
+}
+
+/// Record the power source (called by the runtime).
+pub fn set_power_status(status: PowerStatus) {
+    STATUS.with(|current| {
+        if crate::reactive::untracked(|| current.get()) != status {
+            current.set(status);
+        }
+    });
+}
+
+/// How long since the user last used the app. Not reactive; see
+/// [`use_idle`](crate::hooks::use_idle).
+pub fn idle_time() -> Duration {
+    let last = LAST_INPUT.with(|last| {
+        let time = last.get().unwrap_or_else(now);
+        last.set(Some(time));
+        time
+    });
+    now().saturating_duration_since(last)
+}
+
+/// Note that the user just used the app (called by the runtime). Returns
+/// whether the app should re-render, the user having been shown as idle.
+pub fn record_user_input() -> bool {
+    LAST_INPUT.with(|last| last.set(Some(now())));
+    let returned = IDLE_SHOWN.with(|shown| shown.replace(false));
+    if returned {
+        RETURNS.with(|returns| returns.update(|count| *count += 1));
+    }
+    returned
+}
+
+/// State for [`use_idle`](crate::hooks::use_idle).
+pub(crate) struct IdleWatch {
+    timer: Cell<Option<TimerId>>,
+}
+
+impl IdleWatch {
+    pub(crate) fn new() -> Rc<Self> {
+        Rc::new(Self {
+            timer: Cell::new(None),
+        })
+    }
+
+    /// Whether the user is idle, waking the app when they will be
+    /// otherwise. Reading `RETURNS` re-renders the app when they come back.
+    pub(crate) fn check(&self, threshold: Duration) -> bool {
+        RETURNS.with(|returns| returns.get());
+        if let Some(timer) = self.timer.take() {
+            clear_timeout(timer);
+        }
+        let idle = idle_time();
+        if idle >= threshold {
+            IDLE_SHOWN.with(|shown| shown.set(true));
+            true
+        } else {
+            // Timers re-render the app when they fire
+            self.timer.set(Some(set_timeout(threshold - idle, || {})));
+            false
+        }
+    }
+}
+
+impl Drop for IdleWatch {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            clear_timeout(timer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{run_timers, Clock};
+
+    #[test]
+    fn status_is_recorded() {
+        assert_eq!(power(), PowerStatus::default());
+        assert!(!power().on_battery());
+        let on_battery = PowerStatus {
+            battery_level: Some(0.4),
+            on_ac_power: false,
+        };
+        set_power_status(on_battery);
+        assert_eq!(power(), on_battery);
+        assert!(power().on_battery());
+    }
+
+    #[test]
+    fn idle_passes_threshold_and_ends_with_input() {
+        let clock = Clock::manual();
+        assert!(!record_user_input());
+        let watch = IdleWatch::new();
+        let threshold = Duration::from_secs(60);
+        assert!(!watch.check(threshold));
+
+        // The timer set for the threshold re-renders the app then
+        assert!(clock.advance(Duration::from_secs(60)));
+        assert_eq!(idle_time(), threshold);
+        assert!(watch.check(threshold));
+
+        let returns = || RETURNS.with(|returns| crate::reactive::untracked(|| returns.get()));
+        let before = returns();
+        assert!(record_user_input());
+        assert_eq!(returns(), before + 1);
+        assert!(!watch.check(threshold));
+        drop(watch);
+        assert!(!run_timers(clock.now() + threshold));
+    }
+}
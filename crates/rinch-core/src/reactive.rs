@@ -22,11 +22,14 @@
 //! count.set(1); // Prints: "Count is: 1"
 //! ```
 
+use std::any::type_name;
 use std::cell::{Cell, RefCell};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::rc::Rc;
 
+use crate::clock::Instant;
+
 // ============================================================================
 // Runtime Context
 // ============================================================================
@@ -48,8 +51,8 @@ struct Runtime {
     /// Effects that need to run
     pending_effects: Vec<ObserverId>,
 
-    /// Whether we're currently in a batch
-    batching: bool,
+    /// How many batches are running, one inside another
+    batch_depth: usize,
 
     /// Counter for generating unique IDs
     next_id: usize,
@@ -60,7 +63,7 @@ impl Runtime {
         Self {
             observer_stack: Vec::new(),
             pending_effects: Vec::new(),
-            batching: false,
+            batch_depth: 0,
             next_id: 0,
         }
     }
@@ -76,6 +79,19 @@ impl Runtime {
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct ObserverId(usize);
 
+/// The observers subscribed to a signal or memo. Shared with the debug
+/// graph, so it can list them without knowing the value type.
+type Subscribers = Rc<RefCell<HashSet<ObserverId>>>;
+
+/// A new node ID, and the observer running while it's created.
+fn new_node() -> (usize, Option<usize>) {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        let parent = rt.observer_stack.last().map(|observer| observer.0);
+        (rt.next_id(), parent)
+    })
+}
+
 // ============================================================================
 // Signal
 // ============================================================================
@@ -105,17 +121,34 @@ pub struct Signal<T> {
 }
 
 struct SignalInner<T> {
+    id: usize,
     value: RefCell<T>,
-    subscribers: RefCell<HashSet<ObserverId>>,
+    subscribers: Subscribers,
+}
+
+impl<T> Drop for SignalInner<T> {
+    fn drop(&mut self) {
+        forget_node(self.id);
+    }
 }
 
 impl<T> Signal<T> {
     /// Create a new signal with the given initial value.
     pub fn new(value: T) -> Self {
+        let (id, parent) = new_node();
+        let subscribers = Subscribers::default();
+        add_node(
+            id,
+            ReactiveNodeKind::Signal,
+            type_name::<T>(),
+            parent,
+            Some(Rc::clone(&subscribers)),
+        );
         Self {
             inner: Rc::new(SignalInner {
+                id,
                 value: RefCell::new(value),
-                subscribers: RefCell::new(HashSet::new()),
+                subscribers,
             }),
         }
     }
@@ -124,14 +157,17 @@ impl<T> Signal<T> {
     fn track(&self) {
         RUNTIME.with(|rt| {
             let rt = rt.borrow();
-            if let Some(&observer) = rt.observer_stack.last() {
-                self.inner.subscribers.borrow_mut().insert(observer);
+            if let Some(&observer) = rt.observer_stack.last()
+                && self.inner.subscribers.borrow_mut().insert(observer)
+            {
+                graph_changed();
             }
         });
     }
 
     /// Notify all subscribers that the value has changed.
     fn notify(&self) {
+        record_update(self.inner.id);
         let subscribers: Vec<_> = self.inner.subscribers.borrow().iter().copied().collect();
 
         RUNTIME.with(|rt| {
@@ -143,7 +179,7 @@ impl<T> Signal<T> {
             }
 
             // If not batching, flush immediately
-            if !rt.batching {
+            if rt.batch_depth == 0 {
                 drop(rt);
                 flush_effects();
             }
@@ -218,6 +254,17 @@ impl<T: fmt::Display> fmt::Display for Signal<T> {
 // Storage for all effects (needed because effects reference themselves)
 thread_local! {
     static EFFECTS: RefCell<Vec<Option<Rc<EffectInner>>>> = RefCell::new(Vec::new());
+    /// The effects created in each [`Scope::run`] running, innermost last.
+    static SCOPES: RefCell<Vec<Vec<ObserverId>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Give a new effect to the scope running, if any.
+fn add_to_scope(id: ObserverId) {
+    SCOPES.with(|scopes| {
+        if let Some(scope) = scopes.borrow_mut().last_mut() {
+            scope.push(id);
+        }
+    });
 }
 
 /// A side-effect that re-runs when its dependencies change.
@@ -251,10 +298,9 @@ struct EffectInner {
 impl Effect {
     /// Create a new effect that runs immediately and re-runs when dependencies change.
     pub fn new<F: FnMut() + 'static>(f: F) -> Self {
-        let id = RUNTIME.with(|rt| {
-            let mut rt = rt.borrow_mut();
-            ObserverId(rt.next_id())
-        });
+        let (id, parent) = new_node();
+        add_node(id, ReactiveNodeKind::Effect, type_name::<F>(), parent, None);
+        let id = ObserverId(id);
 
         let inner = Rc::new(EffectInner {
             id,
@@ -271,6 +317,7 @@ impl Effect {
             }
             effects[idx] = Some(Rc::clone(&inner));
         });
+        add_to_scope(id);
 
         // Run the effect immediately
         run_effect(id);
@@ -280,10 +327,9 @@ impl Effect {
 
     /// Create an effect that doesn't run immediately.
     pub fn new_deferred<F: FnMut() + 'static>(f: F) -> Self {
-        let id = RUNTIME.with(|rt| {
-            let mut rt = rt.borrow_mut();
-            ObserverId(rt.next_id())
-        });
+        let (id, parent) = new_node();
+        add_node(id, ReactiveNodeKind::Effect, type_name::<F>(), parent, None);
+        let id = ObserverId(id);
 
         let inner = Rc::new(EffectInner {
             id,
@@ -299,6 +345,7 @@ impl Effect {
             }
             effects[idx] = Some(inner);
         });
+        add_to_scope(id);
 
         Effect { id }
     }
@@ -308,13 +355,19 @@ impl Effect {
         run_effect(self.id);
     }
 
-    /// Dispose of this effect, preventing it from running again.
+    /// Dispose of this effect, preventing it from running again and
+    /// dropping its function, with everything it captured.
     pub fn dispose(&self) {
-        EFFECTS.with(|effects| {
-            if let Some(Some(inner)) = effects.borrow().get(self.id.0) {
-                inner.disposed.set(true);
-            }
+        let inner = EFFECTS.with(|effects| {
+            effects
+                .borrow_mut()
+                .get_mut(self.id.0)
+                .and_then(Option::take)
         });
+        if let Some(inner) = inner {
+            inner.disposed.set(true);
+        }
+        forget_node(self.id.0);
     }
 }
 
@@ -327,14 +380,13 @@ impl Drop for Effect {
 
 /// Run a specific effect by ID
 fn run_effect(id: ObserverId) {
-    let effect = EFFECTS.with(|effects| {
-        effects.borrow().get(id.0).and_then(|e| e.clone())
-    });
+    let effect = EFFECTS.with(|effects| effects.borrow().get(id.0).and_then(|e| e.clone()));
 
     if let Some(inner) = effect {
         if inner.disposed.get() {
             return;
         }
+        record_update(id.0);
 
         // Push this effect as the current observer
         RUNTIME.with(|rt| {
@@ -354,9 +406,7 @@ fn run_effect(id: ObserverId) {
 /// Flush all pending effects
 fn flush_effects() {
     loop {
-        let effect_id = RUNTIME.with(|rt| {
-            rt.borrow_mut().pending_effects.pop()
-        });
+        let effect_id = RUNTIME.with(|rt| rt.borrow_mut().pending_effects.pop());
 
         match effect_id {
             Some(id) => run_effect(id),
@@ -394,27 +444,48 @@ struct MemoInner<T> {
     value: RefCell<Option<T>>,
     f: RefCell<Box<dyn Fn() -> T>>,
     dirty: Cell<bool>,
-    subscribers: RefCell<HashSet<ObserverId>>,
+    subscribers: Subscribers,
+}
+
+impl<T> Drop for MemoInner<T> {
+    fn drop(&mut self) {
+        // Drop the effect marking it dirty, which only held it weakly
+        let _ = EFFECTS.try_with(|effects| {
+            if let Ok(mut effects) = effects.try_borrow_mut()
+                && let Some(effect) = effects.get_mut(self.id.0)
+            {
+                *effect = None;
+            }
+        });
+        forget_node(self.id.0);
+    }
 }
 
 impl<T: Clone + 'static> Memo<T> {
     /// Create a new memo with the given computation function.
     pub fn new<F: Fn() -> T + 'static>(f: F) -> Self {
-        let id = RUNTIME.with(|rt| {
-            let mut rt = rt.borrow_mut();
-            ObserverId(rt.next_id())
-        });
+        let (id, parent) = new_node();
+        let subscribers = Subscribers::default();
+        add_node(
+            id,
+            ReactiveNodeKind::Memo,
+            type_name::<T>(),
+            parent,
+            Some(Rc::clone(&subscribers)),
+        );
+        let id = ObserverId(id);
 
         let inner = Rc::new(MemoInner {
             id,
             value: RefCell::new(None),
             f: RefCell::new(Box::new(f)),
             dirty: Cell::new(true),
-            subscribers: RefCell::new(HashSet::new()),
+            subscribers,
         });
 
-        // Store memo as an effect so it can be notified
-        let inner_clone = Rc::clone(&inner);
+        // Store memo as an effect so it can be notified. It holds the memo
+        // weakly, so dropping the memo's last handle drops both.
+        let memo_inner = Rc::downgrade(&inner);
         EFFECTS.with(|effects| {
             let mut effects = effects.borrow_mut();
             let idx = id.0;
@@ -422,13 +493,16 @@ impl<T: Clone + 'static> Memo<T> {
                 effects.resize(idx + 1, None);
             }
             // We store a "marker" effect that marks the memo as dirty
-            let memo_inner = inner_clone;
             effects[idx] = Some(Rc::new(EffectInner {
                 id,
                 f: RefCell::new(Box::new(move || {
+                    let Some(memo_inner) = memo_inner.upgrade() else {
+                        return;
+                    };
                     memo_inner.dirty.set(true);
                     // Notify memo's subscribers
-                    let subscribers: Vec<_> = memo_inner.subscribers.borrow().iter().copied().collect();
+                    let subscribers: Vec<_> =
+                        memo_inner.subscribers.borrow().iter().copied().collect();
                     RUNTIME.with(|rt| {
                         let mut rt = rt.borrow_mut();
                         for observer in subscribers {
@@ -450,8 +524,10 @@ impl<T: Clone + 'static> Memo<T> {
         // Subscribe current observer to this memo
         RUNTIME.with(|rt| {
             let rt = rt.borrow();
-            if let Some(&observer) = rt.observer_stack.last() {
-                self.inner.subscribers.borrow_mut().insert(observer);
+            if let Some(&observer) = rt.observer_stack.last()
+                && self.inner.subscribers.borrow_mut().insert(observer)
+            {
+                graph_changed();
             }
         });
 
@@ -471,7 +547,11 @@ impl<T: Clone + 'static> Memo<T> {
             });
         }
 
-        self.inner.value.borrow().clone().expect("memo should have value after get")
+        self.inner
+            .value
+            .borrow()
+            .clone()
+            .expect("memo should have value after get")
     }
 }
 
@@ -499,7 +579,11 @@ impl<T: fmt::Debug + Clone + 'static> fmt::Debug for Memo<T> {
 /// Batch multiple signal updates to avoid redundant effect runs.
 ///
 /// Effects will only run once after the batch completes, even if multiple
-/// signals they depend on are updated.
+/// signals they depend on are updated. Memos recompute once, when next
+/// read. Batches can be nested: effects wait for the outermost one.
+///
+/// Event handlers already run in a batch, so a click handler that sets
+/// several signals runs each effect once.
 ///
 /// # Example
 ///
@@ -514,19 +598,29 @@ impl<T: fmt::Debug + Clone + 'static> fmt::Debug for Memo<T> {
 /// });
 /// ```
 pub fn batch<R>(f: impl FnOnce() -> R) -> R {
-    RUNTIME.with(|rt| {
-        rt.borrow_mut().batching = true;
-    });
-
-    let result = f();
+    /// Ends the batch, even if `f` panics.
+    struct Batch;
+
+    impl Drop for Batch {
+        fn drop(&mut self) {
+            let outermost = RUNTIME.with(|rt| {
+                let mut rt = rt.borrow_mut();
+                rt.batch_depth = rt.batch_depth.saturating_sub(1);
+                rt.batch_depth == 0
+            });
+            // Effects waiting on a batch that panicked are dropped by
+            // `reset_after_panic`
+            if outermost && !std::thread::panicking() {
+                flush_effects();
+            }
+        }
+    }
 
     RUNTIME.with(|rt| {
-        rt.borrow_mut().batching = false;
+        rt.borrow_mut().batch_depth += 1;
     });
-
-    flush_effects();
-
-    result
+    let _batch = Batch;
+    f()
 }
 
 // ============================================================================
@@ -535,7 +629,8 @@ pub fn batch<R>(f: impl FnOnce() -> R) -> R {
 
 /// A scope that manages the lifetime of reactive primitives.
 ///
-/// When a scope is disposed, all effects created within it are cleaned up.
+/// When a scope is disposed, or dropped, all effects created within it are
+/// disposed, so they stop running and drop what they captured.
 ///
 /// # Example
 ///
@@ -543,12 +638,11 @@ pub fn batch<R>(f: impl FnOnce() -> R) -> R {
 /// let scope = Scope::new();
 ///
 /// scope.run(|| {
-///     let signal = Signal::new(0);
 ///     Effect::new(|| { /* ... */ });
-///     // signal and effect belong to this scope
+///     // the effect belongs to this scope
 /// });
 ///
-/// scope.dispose(); // Cleans up signal and effect
+/// scope.dispose(); // Disposes of the effect
 /// ```
 pub struct Scope {
     effects: RefCell<Vec<Effect>>,
@@ -563,9 +657,23 @@ impl Scope {
     }
 
     /// Run a function within this scope, capturing any effects created.
+    /// Effects created in a scope run inside this one belong to that scope.
     pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
-        // TODO: Implement scope tracking so effects created within
-        // are automatically registered to this scope
+        /// Hands the effects created to the scope, even if `f` panics.
+        struct Capture<'a>(&'a Scope);
+
+        impl Drop for Capture<'_> {
+            fn drop(&mut self) {
+                let created = SCOPES.with(|scopes| scopes.borrow_mut().pop());
+                self.0
+                    .effects
+                    .borrow_mut()
+                    .extend(created.into_iter().flatten().map(|id| Effect { id }));
+            }
+        }
+
+        SCOPES.with(|scopes| scopes.borrow_mut().push(Vec::new()));
+        let _capture = Capture(self);
         f()
     }
 
@@ -611,9 +719,7 @@ pub fn derived<T: Clone + 'static>(f: impl Fn() -> T + 'static) -> Memo<T> {
 /// Useful for reading signals without creating subscriptions.
 pub fn untracked<R>(f: impl FnOnce() -> R) -> R {
     // Temporarily remove the current observer
-    let observer = RUNTIME.with(|rt| {
-        rt.borrow_mut().observer_stack.pop()
-    });
+    let observer = RUNTIME.with(|rt| rt.borrow_mut().observer_stack.pop());
 
     let result = f();
 
@@ -627,6 +733,284 @@ pub fn untracked<R>(f: impl FnOnce() -> R) -> R {
     result
 }
 
+/// Put the reactive runtime back in order after a panic unwound through it,
+/// such as from an effect or a batch. The runtime calls this when it
+/// catches a panic from app code.
+///
+/// Effects that were waiting to run are dropped: they'll run again when
+/// their signals next change.
+pub fn reset_after_panic() {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.observer_stack.clear();
+        rt.pending_effects.clear();
+        rt.batch_depth = 0;
+    });
+}
+
+/// The reactive runtime's state at some point, to put it back to after a
+/// panic caught there. See [`runtime_mark`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RuntimeMark {
+    observers: usize,
+    pending_effects: usize,
+    batch_depth: usize,
+}
+
+/// Mark the reactive runtime's state before running code that may panic,
+/// such as an error boundary's content.
+pub(crate) fn runtime_mark() -> RuntimeMark {
+    RUNTIME.with(|rt| {
+        let rt = rt.borrow();
+        RuntimeMark {
+            observers: rt.observer_stack.len(),
+            pending_effects: rt.pending_effects.len(),
+            batch_depth: rt.batch_depth,
+        }
+    })
+}
+
+impl RuntimeMark {
+    /// Put the runtime back as it was when marked, after a panic unwound
+    /// through it: observers and batches entered since are left and effects
+    /// queued since are dropped. Unlike [`reset_after_panic`], effects and
+    /// batches running around the mark carry on.
+    pub(crate) fn restore(self) {
+        RUNTIME.with(|rt| {
+            let mut rt = rt.borrow_mut();
+            rt.observer_stack.truncate(self.observers);
+            rt.pending_effects.truncate(self.pending_effects);
+            rt.batch_depth = self.batch_depth;
+        });
+    }
+}
+
+// ============================================================================
+// Debug graph
+// ============================================================================
+
+// Every live signal, memo and effect, for the DevTools reactive graph.
+thread_local! {
+    static GRAPH: RefCell<Graph> = RefCell::new(Graph::default());
+    static RENDER: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: HashMap<usize, GraphEntry>,
+    /// Bumped on every change, so viewers know when to refresh.
+    version: u64,
+}
+
+struct GraphEntry {
+    node: ReactiveNode,
+    subscribers: Option<Subscribers>,
+}
+
+/// Run `f` on the debug graph. Does nothing while the thread is shutting
+/// down or the graph is already borrowed.
+fn with_graph(f: impl FnOnce(&mut Graph)) {
+    let _ = GRAPH.try_with(|graph| {
+        if let Ok(mut graph) = graph.try_borrow_mut() {
+            f(&mut graph);
+        }
+    });
+}
+
+fn add_node(
+    id: usize,
+    kind: ReactiveNodeKind,
+    label: &'static str,
+    created_in: Option<usize>,
+    subscribers: Option<Subscribers>,
+) {
+    let created_in_render = RENDER.with(Cell::get);
+    with_graph(|graph| {
+        let node = ReactiveNode {
+            id,
+            kind,
+            label,
+            updates: 0,
+            last_update: None,
+            created_in,
+            created_in_render,
+        };
+        graph.nodes.insert(id, GraphEntry { node, subscribers });
+        graph.version += 1;
+    });
+}
+
+/// Remove a dropped signal or memo, or a disposed effect, from the graph.
+/// A memo or effect is also unsubscribed from what it read.
+fn forget_node(id: usize) {
+    with_graph(|graph| {
+        let Some(entry) = graph.nodes.remove(&id) else {
+            return;
+        };
+        if entry.node.kind != ReactiveNodeKind::Signal {
+            for subscribers in graph.nodes.values().filter_map(|e| e.subscribers.as_ref()) {
+                if let Ok(mut subscribers) = subscribers.try_borrow_mut() {
+                    subscribers.remove(&ObserverId(id));
+                }
+            }
+        }
+        graph.version += 1;
+    });
+}
+
+/// Count an update of a node. Only debug builds count them, as this runs on
+/// every signal set and effect run.
+fn record_update(id: usize) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    with_graph(|graph| {
+        if let Some(entry) = graph.nodes.get_mut(&id) {
+            entry.node.updates += 1;
+            entry.node.last_update = Some(Instant::now());
+            graph.version += 1;
+        }
+    });
+}
+
+fn graph_changed() {
+    with_graph(|graph| graph.version += 1);
+}
+
+/// Record the render in progress, so nodes know which render created them.
+/// Called by [`begin_render`](crate::hooks::begin_render) and
+/// [`end_render`](crate::hooks::end_render).
+pub(crate) fn set_render(render: Option<usize>) {
+    RENDER.with(|current| current.set(render));
+}
+
+/// What a [`ReactiveNode`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactiveNodeKind {
+    /// A [`Signal`].
+    Signal,
+    /// A [`Memo`] (including [`derived`] values).
+    Memo,
+    /// An [`Effect`].
+    Effect,
+}
+
+/// A signal, memo or effect in the [`ReactiveGraph`].
+#[derive(Debug, Clone)]
+pub struct ReactiveNode {
+    /// The node's ID, shared by the graph's edges.
+    pub id: usize,
+    /// What the node is.
+    pub kind: ReactiveNodeKind,
+    /// The value type of a signal or memo, or the closure type of an
+    /// effect, which names the function that created it.
+    pub label: &'static str,
+    /// How many times a signal was set, a memo invalidated or an effect
+    /// run. Only counted in debug builds.
+    pub updates: u64,
+    /// When the node last updated, in debug builds.
+    pub last_update: Option<Instant>,
+    /// The effect or memo that was running when the node was created.
+    pub created_in: Option<usize>,
+    /// The render the node was created in, counting from 0.
+    pub created_in_render: Option<usize>,
+}
+
+impl ReactiveNode {
+    /// Why the node looks leaked, if it does.
+    ///
+    /// Effects live until they're disposed, so one created while another
+    /// effect runs is joined by a new copy on every run, each subscribing to
+    /// the same signals. Hooks only create their values on the first
+    /// render, so nodes created by a later render come from a component
+    /// body and are recreated every render.
+    pub fn warning(&self) -> Option<String> {
+        if let Some(parent) = self.created_in
+            && self.kind != ReactiveNodeKind::Signal
+        {
+            return Some(format!(
+                "created while #{parent} ran, so every run of #{parent} adds another"
+            ));
+        }
+        match self.created_in_render {
+            Some(render) if render > 0 => Some(format!(
+                "created by render {render} outside a hook, so every render adds another"
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A snapshot of the thread's signals, memos and effects and the
+/// subscriptions between them. See [`reactive_graph`].
+#[derive(Debug, Clone, Default)]
+pub struct ReactiveGraph {
+    /// The nodes, oldest first.
+    pub nodes: Vec<ReactiveNode>,
+    /// Subscriptions as `(source, subscriber)` pairs: the subscriber
+    /// updates when the source does.
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl ReactiveGraph {
+    /// The node with the given ID.
+    pub fn node(&self, id: usize) -> Option<&ReactiveNode> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+
+    /// The signals and memos `id` subscribes to.
+    pub fn sources(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |&&(_, subscriber)| subscriber == id)
+            .map(|&(source, _)| source)
+    }
+
+    /// The memos and effects subscribed to `id`.
+    pub fn subscribers(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |&&(source, _)| source == id)
+            .map(|&(_, subscriber)| subscriber)
+    }
+}
+
+/// Take a snapshot of this thread's reactive graph, for debugging tools
+/// such as the DevTools window.
+pub fn reactive_graph() -> ReactiveGraph {
+    GRAPH.with(|graph| {
+        let graph = graph.borrow();
+        let mut nodes: Vec<_> = graph
+            .nodes
+            .values()
+            .map(|entry| entry.node.clone())
+            .collect();
+        nodes.sort_by_key(|node| node.id);
+        let mut edges: Vec<_> = graph
+            .nodes
+            .iter()
+            .filter_map(|(&id, entry)| Some((id, entry.subscribers.as_ref()?)))
+            .flat_map(|(id, subscribers)| {
+                let subscribers: Vec<_> = subscribers.borrow().iter().map(|o| o.0).collect();
+                subscribers
+                    .into_iter()
+                    .map(move |subscriber| (id, subscriber))
+            })
+            .collect();
+        edges.sort_unstable();
+        ReactiveGraph { nodes, edges }
+    })
+}
+
+/// A number that changes whenever the reactive graph does, so viewers can
+/// skip rebuilding an unchanged [`reactive_graph`].
+pub fn reactive_graph_version() -> u64 {
+    GRAPH.with(|graph| graph.borrow().version)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -668,6 +1052,32 @@ mod tests {
         assert_eq!(run_count.get(), 3);
     }
 
+    #[test]
+    fn panics_in_effects_can_be_recovered_from() {
+        let count = Signal::new(0);
+        let seen = Rc::new(Cell::new(0));
+        let effect_count = count.clone();
+        let effect_seen = Rc::clone(&seen);
+        Effect::new(move || {
+            let count = effect_count.get();
+            assert!(count != 1, "one is unlucky");
+            effect_seen.set(count);
+        });
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            batch(|| count.set(1));
+        }));
+        assert!(panicked.is_err());
+        reset_after_panic();
+
+        // Later reads aren't attributed to the panicked effect, and changes
+        // flush as usual
+        let other = Signal::new(0);
+        other.get();
+        count.set(2);
+        assert_eq!(seen.get(), 2);
+    }
+
     #[test]
     fn memo_caches_value() {
         let count = Signal::new(2);
@@ -723,6 +1133,56 @@ mod tests {
         assert_eq!(count.get(), 3);
     }
 
+    #[test]
+    fn scopes_dispose_the_effects_created_in_them() {
+        let count = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+        let scope = Scope::new();
+
+        let (count_clone, runs_clone) = (count.clone(), Rc::clone(&runs));
+        scope.run(|| {
+            Effect::new(move || {
+                let _ = count_clone.get();
+                runs_clone.set(runs_clone.get() + 1);
+            });
+        });
+        count.set(1);
+        assert_eq!(runs.get(), 2);
+
+        drop(scope);
+        count.set(2);
+        assert_eq!(runs.get(), 2);
+        // The effect's captures were dropped with it
+        assert_eq!(Rc::strong_count(&runs), 1);
+    }
+
+    #[test]
+    fn nested_batches_wait_for_the_outermost() {
+        let count = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+
+        let runs_clone = Rc::clone(&runs);
+        let count_clone = count.clone();
+        Effect::new(move || {
+            let _ = count_clone.get();
+            runs_clone.set(runs_clone.get() + 1);
+        });
+
+        batch(|| {
+            count.set(1);
+            batch(|| count.set(2));
+            // The inner batch didn't run it
+            assert_eq!(runs.get(), 1);
+            count.set(3);
+        });
+        assert_eq!(runs.get(), 2);
+
+        // Outside a batch, every set runs it
+        count.set(4);
+        count.set(5);
+        assert_eq!(runs.get(), 4);
+    }
+
     #[test]
     fn untracked_prevents_subscription() {
         let count = Signal::new(0);
@@ -744,4 +1204,113 @@ mod tests {
         count.set(1);
         assert_eq!(run_count.get(), 1);
     }
+
+    #[test]
+    fn graph_records_subscriptions_and_updates() {
+        let count = Signal::new(0);
+        let doubled = Memo::new({
+            let count = count.clone();
+            move || count.get() * 2
+        });
+        let effect = Effect::new({
+            let doubled = doubled.clone();
+            move || {
+                let _ = doubled.get();
+            }
+        });
+        count.set(1);
+
+        let graph = reactive_graph();
+        let signal = count.inner.id;
+        let memo = doubled.inner.id.0;
+        assert_eq!(graph.subscribers(signal).collect::<Vec<_>>(), [memo]);
+        assert_eq!(graph.sources(effect.id.0).collect::<Vec<_>>(), [memo]);
+        let node = graph.node(signal).unwrap();
+        assert_eq!(node.kind, ReactiveNodeKind::Signal);
+        assert_eq!(node.label, "i32");
+        assert_eq!(node.updates, 1);
+        assert!(node.last_update.is_some());
+        assert_eq!(graph.node(effect.id.0).unwrap().updates, 2);
+
+        let version = reactive_graph_version();
+        drop(count);
+        drop(doubled);
+        // The memo's effect still holds the signal
+        assert!(reactive_graph().node(signal).is_some());
+        assert_eq!(reactive_graph_version(), version);
+        let short_lived = Signal::new(String::new());
+        let id = short_lived.inner.id;
+        drop(short_lived);
+        assert!(reactive_graph().node(id).is_none());
+    }
+
+    #[test]
+    fn graph_forgets_dropped_memos_and_disposed_effects() {
+        let count = Signal::new(0);
+        let doubled = Memo::new({
+            let count = count.clone();
+            move || count.get() * 2
+        });
+        let memo = doubled.inner.id.0;
+        let effect = Effect::new({
+            let count = count.clone();
+            move || {
+                let _ = count.get();
+            }
+        });
+        assert_eq!(doubled.get(), 0);
+        let signal = count.inner.id;
+        assert_eq!(
+            reactive_graph().subscribers(signal).collect::<Vec<_>>(),
+            [memo, effect.id.0]
+        );
+
+        drop(doubled);
+        effect.dispose();
+        let graph = reactive_graph();
+        assert!(graph.node(memo).is_none());
+        assert!(graph.node(effect.id.0).is_none());
+        assert_eq!(graph.subscribers(signal).count(), 0);
+        assert!(EFFECTS.with(|effects| effects.borrow()[memo].is_none()));
+        count.set(1);
+    }
+
+    #[test]
+    fn graph_flags_effects_that_leak() {
+        let count = Signal::new(0);
+        let show = Signal::new(true);
+        let outer = Effect::new({
+            let (count, show) = (count.clone(), show.clone());
+            move || {
+                let _ = show.get();
+                let count = count.clone();
+                Effect::new(move || {
+                    let _ = count.get();
+                });
+            }
+        });
+        show.set(true);
+
+        let graph = reactive_graph();
+        assert!(graph.node(outer.id.0).unwrap().warning().is_none());
+        let inner: Vec<_> = graph
+            .subscribers(count.inner.id)
+            .filter_map(|id| graph.node(id))
+            .collect();
+        assert_eq!(inner.len(), 2);
+        for node in inner {
+            assert_eq!(node.created_in, Some(outer.id.0));
+            assert!(node.warning().unwrap().contains("every run"));
+        }
+
+        set_render(Some(0));
+        let first = Effect::new(|| {});
+        set_render(Some(3));
+        let later = Signal::new(0);
+        set_render(None);
+        let graph = reactive_graph();
+        assert!(graph.node(first.id.0).unwrap().warning().is_none());
+        let warning = graph.node(later.inner.id).unwrap().warning().unwrap();
+        assert!(warning.contains("render 3"));
+    }
 }
@@ -0,0 +1,169 @@
+//! Values loaded by futures.
+//!
+//! A [`Resource`] runs a future on the UI thread with
+//! [`spawn_local`](crate::task::spawn_local) and keeps where it's got to in a
+//! signal: [`ResourceState::Loading`], then `Ready` with its value or
+//! `Error`. The function making the future is tracked like an effect, so
+//! when a signal it reads changes, the future still running is dropped and
+//! a new one started. See [`use_resource`](crate::hooks::use_resource).
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::reactive::{untracked, Effect, Signal};
+use crate::task::{spawn_local, TaskHandle};
+
+/// Where a [`Resource`] has got to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceState<T, E> {
+    /// The future is running.
+    Loading,
+    /// The future finished with a value.
+    Ready(T),
+    /// The future failed.
+    Error(E),
+}
+
+impl<T, E> ResourceState<T, E> {
+    /// Whether the future is running.
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Self::Loading)
+    }
+
+    /// The value, once the future has finished with one.
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Self::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The error, if the future failed.
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            Self::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+type LoadFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>>>>;
+
+/// A value loaded by a future. Cheap to clone; clones share it.
+pub struct Resource<T, E> {
+    inner: Rc<ResourceInner<T, E>>,
+}
+
+impl<T, E> Clone for Resource<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct ResourceInner<T, E> {
+    state: Signal<ResourceState<T, E>>,
+    load: Box<dyn Fn() -> LoadFuture<T, E>>,
+    /// Re-runs `load` when the signals it reads change.
+    effect: RefCell<Option<Effect>>,
+    /// The future running, if any.
+    task: RefCell<Option<TaskHandle>>,
+}
+
+impl<T: 'static, E: 'static> Resource<T, E> {
+    /// Start loading with the future `load` returns, and again whenever the
+    /// signals `load` reads change.
+    pub fn new<Fut>(load: impl Fn() -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<T, E>> + 'static,
+    {
+        let inner = Rc::new(ResourceInner {
+            state: Signal::new(ResourceState::Loading),
+            load: Box::new(move || Box::pin(load())),
+            effect: RefCell::new(None),
+            task: RefCell::new(None),
+        });
+        let weak = Rc::downgrade(&inner);
+        let effect = Effect::new(move || {
+            if let Some(inner) = weak.upgrade() {
+                inner.start();
+            }
+        });
+        inner.effect.replace(Some(effect));
+        Self { inner }
+    }
+
+    /// The signal holding the state, to read it without cloning the value
+    /// or to pass it on.
+    pub fn signal(&self) -> Signal<ResourceState<T, E>> {
+        self.inner.state.clone()
+    }
+
+    /// Whether the future is running.
+    pub fn loading(&self) -> bool {
+        self.inner.state.with(ResourceState::is_loading)
+    }
+
+    /// Start the future again, such as for a "Retry" button, dropping the
+    /// one running.
+    pub fn refetch(&self) {
+        untracked(|| self.inner.clone().start());
+    }
+}
+
+impl<T: Clone, E: Clone> Resource<T, E> {
+    /// Where the resource has got to.
+    pub fn state(&self) -> ResourceState<T, E> {
+        self.inner.state.get()
+    }
+
+    /// The value, once the future has finished with one.
+    pub fn value(&self) -> Option<T> {
+        self.inner.state.with(|state| state.value().cloned())
+    }
+
+    /// The error, if the future failed.
+    pub fn error(&self) -> Option<E> {
+        self.inner.state.with(|state| state.error().cloned())
+    }
+}
+
+impl<T: 'static, E: 'static> ResourceInner<T, E> {
+    /// Drop the future running and start a new one. Run by the effect, so
+    /// the signals `load` reads are tracked.
+    fn start(self: Rc<Self>) {
+        let future = (self.load)();
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        if !untracked(|| self.state.with(ResourceState::is_loading)) {
+            self.state.set(ResourceState::Loading);
+        }
+        let weak = Rc::downgrade(&self);
+        let task = spawn_local(async move {
+            let result = future.await;
+            if let Some(inner) = weak.upgrade() {
+                inner.task.take();
+                inner.state.set(match result {
+                    Ok(value) => ResourceState::Ready(value),
+                    Err(error) => ResourceState::Error(error),
+                });
+            }
+        });
+        self.task.replace(Some(task));
+    }
+}
+
+impl<T, E> Drop for ResourceInner<T, E> {
+    fn drop(&mut self) {
+        if let Some(effect) = self.effect.take() {
+            effect.dispose();
+        }
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Stores: state whose fields are tracked one by one.
+//!
+//! A [`Signal`] holding a whole app state notifies everything that read it
+//! whenever any part of it changes. A [`Store`] tracks reads by field
+//! instead: an effect or memo that read `store.user().name()` re-runs when
+//! the name, the user or the whole state is set, but not when the user's
+//! email or another part of the state is.
+//!
+//! `#[derive(Store)]` (from `rinch::prelude`) on a struct with named fields
+//! adds an accessor for each field to `Store<ThatStruct>`, through a trait
+//! named after the struct (`AppStateStoreFields` for `AppState`). Derive it
+//! on nested structs too to reach their fields.
+//!
+//! ```ignore
+//! #[derive(Clone, Default, Store)]
+//! struct AppState {
+//!     user: User,
+//!     unread: u32,
+//! }
+//!
+//! #[derive(Clone, Default, Store)]
+//! struct User {
+//!     name: String,
+//!     email: String,
+//! }
+//!
+//! fn app() -> Element {
+//!     let state = use_store(AppState::default);
+//!     let name = state.user().name();
+//!     rsx! {
+//!         p { "Signed in as " {name.get()} }
+//!         button { onclick: move || state.unread().set(0), "Mark all read" }
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::reactive::{batch, untracked, Signal};
+
+/// The fields from a store's root to a part of its value.
+type Path = Rc<[&'static str]>;
+
+/// A signal for each part of a store's value that's been read, notified
+/// when that part may have changed.
+type Triggers = Rc<RefCell<HashMap<Path, Signal<()>>>>;
+
+/// Calls a function with a shared reference to a part of the value.
+type Read<T> = Rc<dyn Fn(&mut dyn FnMut(&T))>;
+
+/// Calls a function with a mutable reference to a part of the value.
+type Write<T> = Rc<dyn Fn(&mut dyn FnMut(&mut T))>;
+
+/// Reactive state whose fields are tracked one by one: a store, or a part
+/// of one reached through [`field`](Self::field) or the accessors
+/// `#[derive(Store)]` adds. See the [module docs](self).
+///
+/// Handles are cheap to clone, and all of them share the store's value.
+pub struct Store<T> {
+    triggers: Triggers,
+    path: Path,
+    read: Read<T>,
+    write: Write<T>,
+}
+
+impl<T> Clone for Store<T> {
+    fn clone(&self) -> Self {
+        Self {
+            triggers: self.triggers.clone(),
+            path: self.path.clone(),
+            read: self.read.clone(),
+            write: self.write.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Store<T> {
+    /// A store holding `value`. In a component, use
+    /// [`use_store`](crate::hooks::use_store) to keep it across renders.
+    pub fn new(value: T) -> Self {
+        let value = Rc::new(RefCell::new(value));
+        let read_value = value.clone();
+        Self {
+            triggers: Triggers::default(),
+            path: Rc::new([]),
+            read: Rc::new(move |read: &mut dyn FnMut(&T)| read(&read_value.borrow())),
+            write: Rc::new(move |write: &mut dyn FnMut(&mut T)| write(&mut value.borrow_mut())),
+        }
+    }
+
+    /// The part of the value `get` and `get_mut` reach, as a store tracked
+    /// apart from its siblings. `name` tells the parts apart, so give each
+    /// field its own. The accessors `#[derive(Store)]` adds call this.
+    pub fn field<U: 'static>(
+        &self,
+        name: &'static str,
+        get: fn(&T) -> &U,
+        get_mut: fn(&mut T) -> &mut U,
+    ) -> Store<U> {
+        let path: Path = self.path.iter().copied().chain([name]).collect();
+        let read = self.read.clone();
+        let write = self.write.clone();
+        Store {
+            triggers: self.triggers.clone(),
+            path,
+            read: Rc::new(move |read_field: &mut dyn FnMut(&U)| {
+                read(&mut |value: &T| read_field(get(value)))
+            }),
+            write: Rc::new(move |write_field: &mut dyn FnMut(&mut U)| {
+                write(&mut |value: &mut T| write_field(get_mut(value)))
+            }),
+        }
+    }
+
+    /// Call `f` with a reference to the value. Reactive: effects, memos and
+    /// renders that call this re-run when this part of the store is set.
+    ///
+    /// # Panics
+    ///
+    /// If `f` sets the store.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.track();
+        let mut f = Some(f);
+        let mut result = None;
+        (self.read)(&mut |value: &T| result = f.take().map(|f| f(value)));
+        result.expect("a store's value is always there to read")
+    }
+
+    /// Set the value, notifying what read it, what read the parts inside
+    /// it and what read the parts it's in.
+    pub fn set(&self, value: T) {
+        let mut value = Some(value);
+        self.update(|current| {
+            if let Some(value) = value.take() {
+                *current = value;
+            }
+        });
+    }
+
+    /// Change the value in place, notifying as [`set`](Self::set) does.
+    ///
+    /// # Panics
+    ///
+    /// If the store is being read, as inside [`with`](Self::with).
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        let mut f = Some(f);
+        (self.write)(&mut |value: &mut T| {
+            if let Some(f) = f.take() {
+                f(value);
+            }
+        });
+        self.changed();
+    }
+
+    /// Subscribe the observer running, if any, to this part of the store.
+    fn track(&self) {
+        let trigger = self
+            .triggers
+            .borrow_mut()
+            .entry(self.path.clone())
+            .or_insert_with(|| untracked(|| Signal::new(())))
+            .clone();
+        trigger.with(|_| ());
+    }
+
+    /// Notify what read this part of the store, the parts in it, or the
+    /// parts it's in.
+    fn changed(&self) {
+        let triggers: Vec<Signal<()>> = self
+            .triggers
+            .borrow()
+            .iter()
+            .filter(|(path, _)| path.starts_with(&self.path) || self.path.starts_with(path))
+            .map(|(_, trigger)| trigger.clone())
+            .collect();
+        batch(|| {
+            for trigger in triggers {
+                trigger.set(());
+            }
+        });
+    }
+}
+
+impl<T: Clone + 'static> Store<T> {
+    /// A clone of the value. Reactive, like [`with`](Self::with).
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+}
+
+impl<T: std::fmt::Debug + 'static> std::fmt::Debug for Store<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        untracked(|| self.with(|value| f.debug_tuple("Store").field(value).finish()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactive::Effect;
+    use std::cell::Cell;
+
+    #[derive(Clone, Default)]
+    struct State {
+        user: User,
+        unread: u32,
+    }
+
+    #[derive(Clone, Default)]
+    struct User {
+        name: String,
+        email: String,
+    }
+
+    fn user(state: &Store<State>) -> Store<User> {
+        state.field("user", |state| &state.user, |state| &mut state.user)
+    }
+
+    fn name(user: &Store<User>) -> Store<String> {
+        user.field("name", |user| &user.name, |user| &mut user.name)
+    }
+
+    fn email(user: &Store<User>) -> Store<String> {
+        user.field("email", |user| &user.email, |user| &mut user.email)
+    }
+
+    /// How many times an effect reading `store` has run.
+    fn runs<T: 'static>(store: &Store<T>) -> Rc<Cell<u32>> {
+        let runs = Rc::new(Cell::new(0));
+        let (store, count) = (store.clone(), runs.clone());
+        Effect::new(move || {
+            store.with(|_| ());
+            count.set(count.get() + 1);
+        });
+        runs
+    }
+
+    #[test]
+    fn fields_are_tracked_apart() {
+        let state = Store::new(State::default());
+        let name_runs = runs(&name(&user(&state)));
+        let user_runs = runs(&user(&state));
+        let unread_runs = runs(&state.field("unread", |s| &s.unread, |s| &mut s.unread));
+        let counts = || (name_runs.get(), user_runs.get(), unread_runs.get());
+
+        email(&user(&state)).set("ada@example.com".into());
+        assert_eq!(counts(), (1, 2, 1));
+
+        name(&user(&state)).set("Ada".into());
+        assert_eq!(counts(), (2, 3, 1));
+        assert_eq!(user(&state).get().name, "Ada");
+
+        // Setting a part notifies the parts inside it
+        user(&state).update(|user| user.name = "Grace".into());
+        assert_eq!(counts(), (3, 4, 1));
+
+        state.set(State::default());
+        assert_eq!(counts(), (4, 5, 2));
+        assert_eq!(name(&user(&state)).get(), "");
+    }
+}
@@ -0,0 +1,118 @@
+//! Suspense: a fallback shown while the content inside is still loading.
+//!
+//! [`suspense`] renders its content every time, so the hooks inside keep
+//! their order and their loads start, but shows the fallback instead while
+//! anything rendered in it is pending: a [`use_resource`] or `use_fetch`
+//! still loading, or a `Lazy` subtree not yet built. Once nothing is, the
+//! content replaces the fallback in one render, all of it at once.
+//!
+//! The innermost boundary around a pending load is the one that waits for
+//! it, so a nested `Suspense` keeps the rest of its parent's content from
+//! waiting on its own loads.
+//!
+//! [`use_resource`]: crate::hooks::use_resource
+
+use std::cell::RefCell;
+
+use crate::element::Element;
+
+thread_local! {
+    /// Whether each boundary being rendered has anything pending in it,
+    /// innermost last.
+    static BOUNDARIES: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Render `content`, but return `fallback` instead while anything rendered
+/// in it is pending. This is what the `Suspense` component expands to.
+///
+/// # Example
+///
+/// ```ignore
+/// fn profile_page() -> Element {
+///     suspense(rsx! { p { "Loading..." } }, || rsx! {
+///         {profile_header()}
+///         {recent_posts()}
+///     })
+/// }
+/// ```
+pub fn suspense(fallback: Element, content: impl FnOnce() -> Element) -> Element {
+    BOUNDARIES.with(|boundaries| boundaries.borrow_mut().push(false));
+    // Leave the boundary even if the content panics
+    let boundary = Boundary;
+    let content = content();
+    if boundary.leave() {
+        fallback
+    } else {
+        content
+    }
+}
+
+/// Tell the innermost [`suspense`] boundary being rendered, if any, that
+/// something in it is still loading, so it shows its fallback. The hooks
+/// that load things call this while they do; call it from a hook of your
+/// own to make it wait too.
+pub fn mark_pending() {
+    BOUNDARIES.with(|boundaries| {
+        if let Some(pending) = boundaries.borrow_mut().last_mut() {
+            *pending = true;
+        }
+    });
+}
+
+/// The boundary being rendered, left when dropped.
+struct Boundary;
+
+impl Boundary {
+    /// Leave the boundary, returning whether anything in it was pending.
+    fn leave(self) -> bool {
+        let pending = BOUNDARIES.with(|boundaries| boundaries.borrow().last().copied());
+        drop(self);
+        pending.unwrap_or(false)
+    }
+}
+
+impl Drop for Boundary {
+    fn drop(&mut self) {
+        BOUNDARIES.with(|boundaries| boundaries.borrow_mut().pop());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html(element: &Element) -> &str {
+        match element {
+            Element::Html(html) => html,
+            _ => panic!("expected HTML"),
+        }
+    }
+
+    #[test]
+    fn the_innermost_boundary_waits() {
+        let fallback = || Element::Html("loading".into());
+        let content = |pending: bool| {
+            move || {
+                if pending {
+                    mark_pending();
+                }
+                Element::Html("content".into())
+            }
+        };
+
+        assert_eq!(html(&suspense(fallback(), content(false))), "content");
+        assert_eq!(html(&suspense(fallback(), content(true))), "loading");
+
+        // A nested boundary keeps its loads from its parent
+        let outer = suspense(fallback(), || {
+            let inner = suspense(fallback(), content(true));
+            assert_eq!(html(&inner), "loading");
+            Element::Html("outer".into())
+        });
+        assert_eq!(html(&outer), "outer");
+
+        // Outside a boundary, nothing waits
+        mark_pending();
+        assert_eq!(html(&suspense(fallback(), content(false))), "content");
+    }
+}
@@ -0,0 +1,199 @@
+//! Futures run on the UI thread.
+//!
+//! [`spawn_local`] runs a future alongside the app. It's polled on the UI
+//! thread, between events, whenever it's woken, so it can hold `Rc`s and
+//! set signals; work that blocks belongs on another thread, with the future
+//! awaiting its result. The shell polls woken futures with [`poll_tasks`]
+//! once [`set_wake_handler`] has told it there are some; tests call
+//! [`poll_tasks`] themselves.
+//!
+//! Tasks are thread-local, like hooks and handlers.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Wake, Waker};
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    /// Running tasks, by ID. A task's slot is empty while it's being polled.
+    static TASKS: RefCell<HashMap<u64, Option<Task>>> = RefCell::new(HashMap::new());
+    static NEXT_TASK_ID: Cell<u64> = const { Cell::new(0) };
+    /// Tasks woken since they were last polled. Wakers can be sent to other
+    /// threads, so it's shared with them.
+    static WOKEN: Arc<Mutex<Vec<u64>>> = Arc::new(Mutex::new(Vec::new()));
+}
+
+/// Tells the event loop that tasks were woken.
+type WakeHandler = Arc<dyn Fn() + Send + Sync>;
+
+static WAKE_HANDLER: Mutex<Option<WakeHandler>> = Mutex::new(None);
+
+/// Identifies a task started with [`spawn_local`]. Dropping it leaves the
+/// task running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskHandle(u64);
+
+impl TaskHandle {
+    /// Stop the task, dropping its future. Does nothing once it's finished.
+    pub fn abort(&self) {
+        TASKS.with(|tasks| tasks.borrow_mut().remove(&self.0));
+    }
+
+    /// Whether the task finished or was aborted.
+    pub fn is_finished(&self) -> bool {
+        TASKS.with(|tasks| !tasks.borrow().contains_key(&self.0))
+    }
+}
+
+/// Run `future` on the UI thread, from the next time tasks are polled.
+///
+/// # Example
+///
+/// ```ignore
+/// let status = use_signal(String::new);
+/// button {
+///     onclick: move || {
+///         let status = status.clone();
+///         spawn_local(async move {
+///             let report = build_report().await;
+///             status.set(format!("{} rows", report.len()));
+///         });
+///     },
+///     "Build report"
+/// }
+/// ```
+pub fn spawn_local(future: impl Future<Output = ()> + 'static) -> TaskHandle {
+    let id = NEXT_TASK_ID.with(|next| {
+        let id = next.get();
+        next.set(id + 1);
+        id
+    });
+    TASKS.with(|tasks| tasks.borrow_mut().insert(id, Some(Box::pin(future))));
+    wake(&WOKEN.with(Arc::clone), id);
+    TaskHandle(id)
+}
+
+/// Poll the tasks woken since the last call. Returns whether any ran, so
+/// the caller can re-render.
+pub fn poll_tasks() -> bool {
+    let woken = WOKEN.with(Arc::clone);
+    let ids = std::mem::take(&mut *woken.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut ran = false;
+    for id in ids {
+        // Take the task out while it runs, so it can spawn or abort tasks
+        let Some(mut task) =
+            TASKS.with(|tasks| tasks.borrow_mut().get_mut(&id).and_then(Option::take))
+        else {
+            continue;
+        };
+        ran = true;
+        let waker = Waker::from(Arc::new(TaskWaker {
+            id,
+            woken: woken.clone(),
+        }));
+        let done = task
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_ready();
+        TASKS.with(|tasks| {
+            let mut tasks = tasks.borrow_mut();
+            if done {
+                tasks.remove(&id);
+            } else if let Some(slot) = tasks.get_mut(&id) {
+                *slot = Some(task);
+            }
+        });
+    }
+    ran
+}
+
+/// Call `handler` whenever a task is woken, from whichever thread woke it.
+/// The shell uses it to wake the event loop to [`poll_tasks`].
+pub fn set_wake_handler(handler: impl Fn() + Send + Sync + 'static) {
+    *WAKE_HANDLER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(handler));
+}
+
+/// Queue task `id` to be polled and tell the event loop.
+fn wake(woken: &Mutex<Vec<u64>>, id: u64) {
+    woken.lock().unwrap_or_else(|e| e.into_inner()).push(id);
+    let handler = WAKE_HANDLER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(handler) = handler {
+        handler();
+    }
+}
+
+struct TaskWaker {
+    id: u64,
+    woken: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        wake(&self.woken, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::task::Poll;
+
+    /// A future that's pending until `open` is set, then wakes nothing.
+    struct Gate {
+        open: Rc<Cell<bool>>,
+        waker: Rc<RefCell<Option<Waker>>>,
+    }
+
+    impl Future for Gate {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.open.get() {
+                Poll::Ready(())
+            } else {
+                *self.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn tasks_run_when_woken() {
+        let open = Rc::new(Cell::new(false));
+        let waker = Rc::new(RefCell::new(None::<Waker>));
+        let done = Rc::new(Cell::new(false));
+        let gate = Gate {
+            open: open.clone(),
+            waker: waker.clone(),
+        };
+        let task = spawn_local({
+            let done = done.clone();
+            async move {
+                gate.await;
+                done.set(true);
+            }
+        });
+
+        assert!(poll_tasks());
+        assert!(!done.get());
+        assert!(!poll_tasks());
+
+        open.set(true);
+        waker.borrow_mut().take().unwrap().wake();
+        assert!(poll_tasks());
+        assert!(done.get());
+        assert!(task.is_finished());
+
+        let aborted = spawn_local(async { panic!("aborted") });
+        aborted.abort();
+        assert!(!poll_tasks());
+    }
+}
@@ -0,0 +1,191 @@
+//! WAI-ARIA roles and attributes.
+//!
+//! Lets `rsx!` reject misspelled `role` values and `aria_*` props at
+//! compile time, with a suggestion, instead of rendering attributes that
+//! assistive technology silently ignores.
+
+use crate::suggestions::find_closest_prop;
+
+/// The concrete (non-abstract) roles of WAI-ARIA 1.2.
+static ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "application",
+    "article",
+    "banner",
+    "blockquote",
+    "button",
+    "caption",
+    "cell",
+    "checkbox",
+    "code",
+    "columnheader",
+    "combobox",
+    "complementary",
+    "contentinfo",
+    "definition",
+    "deletion",
+    "dialog",
+    "directory",
+    "document",
+    "emphasis",
+    "feed",
+    "figure",
+    "form",
+    "generic",
+    "grid",
+    "gridcell",
+    "group",
+    "heading",
+    "img",
+    "insertion",
+    "link",
+    "list",
+    "listbox",
+    "listitem",
+    "log",
+    "main",
+    "marquee",
+    "math",
+    "menu",
+    "menubar",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "meter",
+    "navigation",
+    "none",
+    "note",
+    "option",
+    "paragraph",
+    "presentation",
+    "progressbar",
+    "radio",
+    "radiogroup",
+    "region",
+    "row",
+    "rowgroup",
+    "rowheader",
+    "scrollbar",
+    "search",
+    "searchbox",
+    "separator",
+    "slider",
+    "spinbutton",
+    "status",
+    "strong",
+    "subscript",
+    "superscript",
+    "switch",
+    "tab",
+    "table",
+    "tablist",
+    "tabpanel",
+    "term",
+    "textbox",
+    "time",
+    "timer",
+    "toolbar",
+    "tooltip",
+    "tree",
+    "treegrid",
+    "treeitem",
+];
+
+/// The states and properties of WAI-ARIA 1.2, as `rsx!` props.
+static ATTRIBUTES: &[&str] = &[
+    "aria_activedescendant",
+    "aria_atomic",
+    "aria_autocomplete",
+    "aria_busy",
+    "aria_checked",
+    "aria_colcount",
+    "aria_colindex",
+    "aria_colspan",
+    "aria_controls",
+    "aria_current",
+    "aria_describedby",
+    "aria_details",
+    "aria_disabled",
+    "aria_errormessage",
+    "aria_expanded",
+    "aria_flowto",
+    "aria_haspopup",
+    "aria_hidden",
+    "aria_invalid",
+    "aria_keyshortcuts",
+    "aria_label",
+    "aria_labelledby",
+    "aria_level",
+    "aria_live",
+    "aria_modal",
+    "aria_multiline",
+    "aria_multiselectable",
+    "aria_orientation",
+    "aria_owns",
+    "aria_placeholder",
+    "aria_posinset",
+    "aria_pressed",
+    "aria_readonly",
+    "aria_relevant",
+    "aria_required",
+    "aria_roledescription",
+    "aria_rowcount",
+    "aria_rowindex",
+    "aria_rowspan",
+    "aria_selected",
+    "aria_setsize",
+    "aria_sort",
+    "aria_valuemax",
+    "aria_valuemin",
+    "aria_valuenow",
+    "aria_valuetext",
+];
+
+/// Check an `aria_*` prop name, returning an error message if it isn't a
+/// WAI-ARIA attribute.
+pub fn check_attribute(prop: &str) -> Option<String> {
+    if ATTRIBUTES.contains(&prop) {
+        return None;
+    }
+    let mut msg = format!("unknown ARIA attribute `{}`", prop);
+    if let Some(suggestion) = find_closest_prop(prop, ATTRIBUTES) {
+        msg.push_str(&format!("\n\nDid you mean `{}`?", suggestion));
+    }
+    Some(msg)
+}
+
+/// Check a literal `role` value, returning an error message if any of its
+/// space-separated roles isn't a WAI-ARIA role.
+pub fn check_role(value: &str) -> Option<String> {
+    let unknown = value
+        .split_whitespace()
+        .find(|role| !ROLES.contains(role))?;
+    let mut msg = format!("unknown ARIA role `{}`", unknown);
+    if let Some(suggestion) = find_closest_prop(unknown, ROLES) {
+        msg.push_str(&format!("\n\nDid you mean `{}`?", suggestion));
+    }
+    Some(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_attribute() {
+        assert_eq!(check_attribute("aria_expanded"), None);
+        let msg = check_attribute("aria_labeledby").unwrap();
+        assert!(msg.contains("Did you mean `aria_labelledby`?"));
+    }
+
+    #[test]
+    fn test_check_role() {
+        assert_eq!(check_role("button"), None);
+        // Fallback roles are allowed
+        assert_eq!(check_role("switch checkbox"), None);
+        let msg = check_role("tab-panel").unwrap();
+        assert!(msg.starts_with("unknown ARIA role `tab-panel`"));
+        assert!(msg.contains("Did you mean `tabpanel`?"));
+    }
+}
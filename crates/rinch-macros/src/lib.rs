@@ -1,13 +1,17 @@
 //! Procedural macros for rinch - RSX syntax.
 //!
-//! Provides the `rsx!` macro for declarative UI definition.
+//! Provides the `rsx!` macro for declarative UI definition, and
+//! `#[derive(Store)]` for stores tracked field by field.
 
+mod aria;
 mod prop_schema;
+mod store;
 mod suggestions;
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::{braced, token, Expr, Ident, LitStr, Result, Token};
 
@@ -37,13 +41,49 @@ pub fn rsx(input: TokenStream) -> TokenStream {
     node.to_element().into()
 }
 
+/// Add an accessor for each field to `Store<ThisStruct>`, returning a
+/// store over that field that's tracked apart from the others.
+///
+/// The accessors are methods of a trait named after the struct, such as
+/// `AppStateStoreFields` for `AppState`, with the struct's visibility.
+/// `Store`'s own methods win over accessors with the same name, so call a
+/// field named `get`, `set`, `with`, `update` or `field` as
+/// `AppStateStoreFields::get(&state)`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::prelude::*;
+///
+/// #[derive(Clone, Default, Store)]
+/// struct AppState {
+///     user: User,
+///     unread: u32,
+/// }
+///
+/// #[derive(Clone, Default, Store)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// let state = Store::new(AppState::default());
+/// state.user().name().set("Ada".into());
+/// ```
+#[proc_macro_derive(Store)]
+pub fn derive_store(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    store::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// A node in the RSX tree.
 enum RsxNode {
     /// A component or HTML element with optional props and children.
     Element(RsxElement),
     /// A text literal.
     Text(LitStr),
-    /// A Rust expression in braces.
+    /// A Rust expression in braces, or a macro call.
     Expr(Expr),
 }
 
@@ -55,6 +95,9 @@ impl Parse for RsxNode {
             let content;
             braced!(content in input);
             Ok(RsxNode::Expr(content.parse()?))
+        } else if input.peek(Ident) && input.peek2(Token![!]) {
+            // A macro call such as `t!("save-file")`, without braces
+            Ok(RsxNode::Expr(Expr::Macro(input.parse()?)))
         } else {
             Ok(RsxNode::Element(input.parse()?))
         }
@@ -76,9 +119,27 @@ impl RsxNode {
         }
     }
 
-    fn to_html_tokens(&self) -> TokenStream2 {
+    /// The node's HTML as a `&str` expression. `root` is whether it's at the
+    /// top of the HTML this `rsx!` makes, for [`Locations`].
+    fn to_html_tokens(&self, root: bool) -> TokenStream2 {
         match self {
-            RsxNode::Element(el) => el.to_html_tokens(),
+            RsxNode::Element(el)
+                if matches!(
+                    el.name.to_string().as_str(),
+                    "ErrorBoundary"
+                        | "Lazy"
+                        | "MaskedInput"
+                        | "PluginSlot"
+                        | "Portal"
+                        | "Suspense"
+                        | "VirtualList"
+                ) =>
+            {
+                // Render the element it expands to into the surrounding HTML
+                let element = el.to_element();
+                quote! { &::rinch::core::element::children_to_html(&[#element]) }
+            }
+            RsxNode::Element(el) => el.to_html_tokens(root),
             RsxNode::Text(lit) => {
                 let text = html_escape(&lit.value());
                 quote! { #text }
@@ -124,8 +185,12 @@ impl Parse for RsxElement {
         let mut children = Vec::new();
 
         while !content.is_empty() {
-            // Try to parse as a prop (name: value)
-            if content.peek(Ident) && content.peek2(Token![:]) && !content.peek2(Token![::]) {
+            // Try to parse as a prop (name: value). Keywords like `loop` and
+            // `type` are fine as prop names.
+            if content.peek(Ident::peek_any)
+                && content.peek2(Token![:])
+                && !content.peek2(Token![::])
+            {
                 let prop: RsxProp = content.parse()?;
                 props.push(prop);
 
@@ -145,6 +210,10 @@ impl Parse for RsxElement {
             }
         }
 
+        if name == "LottiePlayer" {
+            return lottie_player(name, props, children);
+        }
+
         Ok(RsxElement {
             name,
             props,
@@ -153,12 +222,71 @@ impl Parse for RsxElement {
     }
 }
 
+/// Turn a `LottiePlayer` into the `div` the shell plays it in, with its
+/// settings as `data-lottie-*` attributes.
+fn lottie_player(name: Ident, props: Vec<RsxProp>, children: Vec<RsxNode>) -> Result<RsxElement> {
+    let component = name.to_string();
+    let valid_props = get_prop_names(&component);
+    for prop in &props {
+        let prop_name = prop.name.to_string();
+        if !is_valid_prop(&component, &prop_name) {
+            let message = format_unknown_prop_error(&component, &prop_name, &valid_props);
+            return Err(syn::Error::new_spanned(&prop.name, message));
+        }
+    }
+    for required in get_required_props(&component) {
+        if !props.iter().any(|prop| prop.name == required) {
+            let message = format_missing_prop_error(&component, required);
+            return Err(syn::Error::new_spanned(&name, message));
+        }
+    }
+    if !children.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &name,
+            "LottiePlayer doesn't take children",
+        ));
+    }
+
+    let props = props
+        .into_iter()
+        .map(|prop| {
+            let renamed = match prop.name.to_string().as_str() {
+                "src" => "lottie_src",
+                "playing" => "lottie_playing",
+                "loop" => "lottie_loop",
+                _ => return prop,
+            };
+            RsxProp {
+                name: Ident::new(renamed, prop.name.span()),
+                value: prop.value,
+            }
+        })
+        .collect();
+    Ok(RsxElement {
+        name: Ident::new("div", name.span()),
+        props,
+        children,
+    })
+}
+
 impl RsxElement {
     fn is_rinch_component(&self) -> bool {
         let name = self.name.to_string();
         matches!(
             name.as_str(),
-            "Window" | "AppMenu" | "Menu" | "MenuItem" | "MenuSeparator" | "Fragment"
+            "Window"
+                | "AppMenu"
+                | "Menu"
+                | "MenuItem"
+                | "MenuSeparator"
+                | "Fragment"
+                | "ErrorBoundary"
+                | "Lazy"
+                | "MaskedInput"
+                | "PluginSlot"
+                | "Portal"
+                | "Suspense"
+                | "VirtualList"
         )
     }
 
@@ -167,9 +295,9 @@ impl RsxElement {
     fn validate_props(&self) -> Option<TokenStream2> {
         let component_name = self.name.to_string();
 
-        // Skip validation for HTML elements (not rinch components)
+        // HTML elements only have their ARIA semantics checked
         if !self.is_rinch_component() {
-            return None;
+            return self.validate_aria();
         }
 
         // MenuSeparator and Fragment don't have props
@@ -201,8 +329,43 @@ impl RsxElement {
         None
     }
 
+    /// Check the `aria_*` prop names and literal `role` values of an HTML
+    /// element and the HTML elements inside it.
+    fn validate_aria(&self) -> Option<TokenStream2> {
+        for prop in &self.props {
+            let prop_name = prop.name.to_string();
+            let error_msg = if prop_name.starts_with("aria_") {
+                aria::check_attribute(&prop_name)
+            } else if prop_name == "role" && is_literal_expr(&prop.value) {
+                aria::check_role(&expr_to_string(&prop.value))
+            } else {
+                None
+            };
+            if let Some(error_msg) = error_msg {
+                return Some(syn::Error::new_spanned(&prop.name, error_msg).to_compile_error());
+            }
+        }
+        self.children.iter().find_map(|child| match child {
+            RsxNode::Element(el) if !el.is_rinch_component() => el.validate_aria(),
+            _ => None,
+        })
+    }
+
     /// Check if this element or any children have event handlers or dynamic expressions.
     fn has_dynamic_content(&self) -> bool {
+        if matches!(
+            self.name.to_string().as_str(),
+            "ErrorBoundary"
+                | "Lazy"
+                | "MaskedInput"
+                | "PluginSlot"
+                | "Portal"
+                | "Suspense"
+                | "VirtualList"
+        ) {
+            return true;
+        }
+
         // Check for event handlers
         if self.props.iter().any(|p| is_event_prop(&p.name.to_string())) {
             return true;
@@ -232,6 +395,13 @@ impl RsxElement {
             "MenuItem" => self.gen_menu_item(),
             "MenuSeparator" => quote! { Element::MenuSeparator },
             "Fragment" => self.gen_fragment(),
+            "ErrorBoundary" => self.gen_error_boundary(),
+            "Lazy" => self.gen_lazy(),
+            "MaskedInput" => self.gen_masked_input(),
+            "PluginSlot" => self.gen_plugin_slot(),
+            "Portal" => self.gen_portal(),
+            "Suspense" => self.gen_suspense(),
+            "VirtualList" => self.gen_virtual_list(),
             _ => self.gen_html_element(),
         }
     }
@@ -256,6 +426,12 @@ impl RsxElement {
         let mut transparent = quote! { false };
         let mut always_on_top = quote! { false };
         let mut visible = quote! { true };
+        let mut reset = quote! { false };
+        let mut rtl = quote! { false };
+        let mut antialiasing = quote! { ::std::default::Default::default() };
+        let mut overlay_scrollbars = quote! { ::rinch::core::element::TOUCH_FIRST };
+        let mut overscroll_bounce = quote! { ::rinch::core::element::TOUCH_FIRST };
+        let mut content_scale = quote! { 1.0 };
 
         for prop in &self.props {
             let name = prop.name.to_string();
@@ -272,6 +448,12 @@ impl RsxElement {
                 "transparent" => transparent = quote! { #value },
                 "always_on_top" => always_on_top = quote! { #value },
                 "visible" => visible = quote! { #value },
+                "reset" => reset = quote! { #value },
+                "rtl" => rtl = quote! { #value },
+                "antialiasing" => antialiasing = quote! { #value },
+                "overlay_scrollbars" => overlay_scrollbars = quote! { #value },
+                "overscroll_bounce" => overscroll_bounce = quote! { #value },
+                "content_scale" => content_scale = quote! { #value },
                 _ => {}
             }
         }
@@ -288,6 +470,12 @@ impl RsxElement {
                 transparent: #transparent,
                 always_on_top: #always_on_top,
                 visible: #visible,
+                reset: #reset,
+                rtl: #rtl,
+                antialiasing: #antialiasing,
+                overlay_scrollbars: #overlay_scrollbars,
+                overscroll_bounce: #overscroll_bounce,
+                content_scale: #content_scale,
             }
         }
     }
@@ -345,7 +533,7 @@ impl RsxElement {
 
             match name.as_str() {
                 "label" => label = quote! { String::from(#value) },
-                "shortcut" => shortcut = quote! { Some(String::from(#value)) },
+                "shortcut" => shortcut = quote! { Some(Shortcut::from(#value)) },
                 "enabled" => enabled = quote! { #value },
                 "checked" => checked = quote! { Some(#value) },
                 "onclick" => onclick = quote! { Some(MenuItemCallback::new(#value)) },
@@ -369,6 +557,167 @@ impl RsxElement {
         quote! { Element::Fragment(#children) }
     }
 
+    fn gen_error_boundary(&self) -> TokenStream2 {
+        let children = self.gen_children_as_elements();
+        let fallback = self
+            .props
+            .iter()
+            .find(|prop| prop.name == "fallback")
+            .map(|prop| &prop.value);
+
+        quote! {
+            ::rinch::core::hooks::use_error_boundary(#fallback, || Element::Fragment(#children))
+        }
+    }
+
+    fn gen_lazy(&self) -> TokenStream2 {
+        if !self.children.is_empty() {
+            return syn::Error::new_spanned(
+                &self.name,
+                "Lazy doesn't take children; pass them in `loader`",
+            )
+            .to_compile_error();
+        }
+
+        let mut when = quote! { true };
+        let mut placeholder = quote! { Element::Fragment(vec![]) };
+        let mut loader = quote! { || Element::Fragment(vec![]) };
+
+        for prop in &self.props {
+            let name = prop.name.to_string();
+            let value = &prop.value;
+
+            match name.as_str() {
+                "when" => when = quote! { #value },
+                "placeholder" => placeholder = quote! { #value },
+                "loader" => loader = quote! { #value },
+                _ => {}
+            }
+        }
+
+        quote! {
+            ::rinch::core::hooks::use_lazy(#when, #placeholder, #loader)
+        }
+    }
+
+    fn gen_masked_input(&self) -> TokenStream2 {
+        if !self.children.is_empty() {
+            return syn::Error::new_spanned(
+                &self.name,
+                "MaskedInput doesn't take children; its field holds the text",
+            )
+            .to_compile_error();
+        }
+
+        let mut field = quote! {};
+        let mut class = quote! { "" };
+        let mut placeholder = quote! { "" };
+
+        for prop in &self.props {
+            let name = prop.name.to_string();
+            let value = &prop.value;
+
+            match name.as_str() {
+                "field" => field = quote! { #value },
+                "class" => class = quote! { &::std::string::ToString::to_string(&#value) },
+                "placeholder" => {
+                    placeholder = quote! { &::std::string::ToString::to_string(&#value) }
+                }
+                _ => {}
+            }
+        }
+
+        quote! {
+            ::rinch::components::masked_input(&#field, #class, #placeholder)
+        }
+    }
+
+    fn gen_plugin_slot(&self) -> TokenStream2 {
+        if !self.children.is_empty() {
+            return syn::Error::new_spanned(
+                &self.name,
+                "PluginSlot doesn't take children; plugins fill it",
+            )
+            .to_compile_error();
+        }
+
+        let name = self
+            .props
+            .iter()
+            .find(|prop| prop.name == "name")
+            .map(|prop| &prop.value);
+
+        quote! {
+            ::rinch::plugin::render_slot(&::std::string::ToString::to_string(&#name))
+        }
+    }
+
+    fn gen_portal(&self) -> TokenStream2 {
+        let children = self.gen_children_as_elements();
+        let target = self
+            .props
+            .iter()
+            .find(|prop| prop.name == "target")
+            .map(|prop| {
+                let value = &prop.value;
+                quote! { &::std::string::ToString::to_string(&#value) }
+            })
+            .unwrap_or_else(|| quote! { "" });
+
+        quote! {
+            ::rinch::core::portal::create_portal(#target, Element::Fragment(#children))
+        }
+    }
+
+    fn gen_suspense(&self) -> TokenStream2 {
+        let children = self.gen_children_as_elements();
+        let fallback = self
+            .props
+            .iter()
+            .find(|prop| prop.name == "fallback")
+            .map(|prop| {
+                let value = &prop.value;
+                quote! { #value }
+            })
+            .unwrap_or_else(|| quote! { Element::Fragment(vec![]) });
+
+        quote! {
+            ::rinch::core::suspense::suspense(#fallback, || Element::Fragment(#children))
+        }
+    }
+
+    fn gen_virtual_list(&self) -> TokenStream2 {
+        if !self.children.is_empty() {
+            return syn::Error::new_spanned(
+                &self.name,
+                "VirtualList doesn't take children; render rows with `row`",
+            )
+            .to_compile_error();
+        }
+
+        let mut virtualizer = quote! {};
+        let mut class = quote! { "" };
+        let mut style = quote! { "" };
+        let mut row = quote! { |_: usize| Element::Fragment(vec![]) };
+
+        for prop in &self.props {
+            let name = prop.name.to_string();
+            let value = &prop.value;
+
+            match name.as_str() {
+                "virtualizer" => virtualizer = quote! { #value },
+                "class" => class = quote! { &::std::string::ToString::to_string(&#value) },
+                "style" => style = quote! { &::std::string::ToString::to_string(&#value) },
+                "row" => row = quote! { #value },
+                _ => {}
+            }
+        }
+
+        quote! {
+            ::rinch::components::virtual_list(&#virtualizer, #class, #style, #row)
+        }
+    }
+
     fn gen_children_as_elements(&self) -> TokenStream2 {
         if self.children.is_empty() {
             return quote! { vec![] };
@@ -383,8 +732,11 @@ impl RsxElement {
 
             if has_dynamic {
                 // Generate runtime HTML building
-                let html_parts: Vec<TokenStream2> =
-                    self.children.iter().map(|c| c.to_html_tokens()).collect();
+                let html_parts: Vec<TokenStream2> = self
+                    .children
+                    .iter()
+                    .map(|c| c.to_html_tokens(true))
+                    .collect();
 
                 quote! {
                     vec![Element::Html({
@@ -395,7 +747,7 @@ impl RsxElement {
                 }
             } else {
                 // Static HTML string
-                let html: String = self.children.iter().map(|c| node_to_static_html(c)).collect();
+                let html = static_html_tokens(&self.children, Locations::Root);
                 quote! { vec![Element::Html(#html.into())] }
             }
         } else {
@@ -407,10 +759,10 @@ impl RsxElement {
                     if c.is_rinch_component() {
                         c.to_element()
                     } else if c.has_dynamic_content() {
-                        let html_tokens = c.to_html_tokens();
+                        let html_tokens = c.to_html_tokens(true);
                         quote! { Element::Html(#html_tokens.into()) }
                     } else {
-                        let html = node_to_static_html(c);
+                        let html = static_html_tokens(std::slice::from_ref(c), Locations::Root);
                         quote! { Element::Html(#html.into()) }
                     }
                 })
@@ -424,7 +776,10 @@ impl RsxElement {
         if self.has_dynamic_content() {
             self.gen_dynamic_html_element()
         } else {
-            let html = self.to_static_html();
+            let html = located_html(
+                self.to_static_html(Locations::None),
+                self.to_static_html(Locations::Root),
+            );
             quote! { Element::Html(#html.into()) }
         }
     }
@@ -436,19 +791,26 @@ impl RsxElement {
         let (event_props, attr_props): (Vec<_>, Vec<_>) = self
             .props
             .iter()
-            .partition(|p| is_event_prop(&p.name.to_string()));
+            .partition(|p| is_handler_prop(&p.name.to_string()));
 
         // Build attribute string
         let attr_parts: Vec<TokenStream2> = attr_props
             .iter()
             .map(|p| {
-                let name = p.name.to_string();
+                let name = attr_name(&p.name.to_string());
                 let value = &p.value;
                 if is_literal_expr(value) {
                     let val_str = expr_to_string(value);
                     let escaped = html_escape(&val_str);
                     let attr = format!(" {}=\"{}\"", name, escaped);
                     quote! { #attr }
+                } else if is_aria_prop(&p.name.to_string()) {
+                    // Typed ARIA value; `None` leaves the attribute out
+                    quote! {
+                        &::rinch::core::a11y::AriaValue::aria_value(&#value)
+                            .map(|__value| format!(" {}=\"{}\"", #name, ::rinch::core::events::html_escape_string(&__value)))
+                            .unwrap_or_default()
+                    }
                 } else {
                     // Dynamic attribute value
                     quote! {
@@ -459,26 +821,36 @@ impl RsxElement {
             .collect();
 
         // Generate event handler registration
-        let event_registrations: Vec<TokenStream2> = event_props
-            .iter()
-            .map(|p| {
-                let handler = &p.value;
-                quote! {
-                    let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
-                }
-            })
-            .collect();
+        let event_registrations = gen_event_registrations(&event_props);
 
-        // Build the data-rid attribute if we have event handlers
-        let rid_attr = if !event_props.is_empty() {
+        // Build the data-rid attribute if we have click handlers
+        let rid_attr = if has_click_handler(&event_props) {
             quote! { &format!(" data-rid=\"{}\"", __handler_id) }
         } else {
             quote! { "" }
         };
 
+        // Build the data-onscroll attribute if we have a scroll handler
+        let scroll_attr = if has_scroll_handler(&event_props) {
+            quote! { &format!(" data-onscroll=\"{}\"", __scroll_handler_id) }
+        } else {
+            quote! { "" }
+        };
+
+        // Build the data-ondrag*, data-oninput and such attributes for
+        // typed handlers
+        let typed_attrs = typed_handler_attr_parts(&tag, &event_props);
+        let bound_text_parts: Vec<TokenStream2> =
+            bound_text(&tag, &event_props).into_iter().collect();
+
+        let location_attrs = self.location_attrs_tokens(Locations::Root);
+
         // Build children HTML
-        let children_tokens: Vec<TokenStream2> =
-            self.children.iter().map(|c| c.to_html_tokens()).collect();
+        let children_tokens: Vec<TokenStream2> = self
+            .children
+            .iter()
+            .map(|c| c.to_html_tokens(false))
+            .collect();
 
         if is_void_element(&tag) {
             quote! {
@@ -488,8 +860,11 @@ impl RsxElement {
                         let mut __html = String::new();
                         __html.push_str("<");
                         __html.push_str(#tag);
+                        #location_attrs
                         #( __html.push_str(#attr_parts); )*
                         __html.push_str(#rid_attr);
+                        __html.push_str(#scroll_attr);
+                        #( __html.push_str(#typed_attrs); )*
                         __html.push_str(" />");
                         __html
                     })
@@ -503,9 +878,13 @@ impl RsxElement {
                         let mut __html = String::new();
                         __html.push_str("<");
                         __html.push_str(#tag);
+                        #location_attrs
                         #( __html.push_str(#attr_parts); )*
                         __html.push_str(#rid_attr);
+                        __html.push_str(#scroll_attr);
+                        #( __html.push_str(#typed_attrs); )*
                         __html.push_str(">");
+                        #( __html.push_str(#bound_text_parts); )*
                         #( __html.push_str(#children_tokens); )*
                         __html.push_str("</");
                         __html.push_str(#tag);
@@ -517,35 +896,48 @@ impl RsxElement {
         }
     }
 
-    fn to_html_tokens(&self) -> TokenStream2 {
+    fn to_html_tokens(&self, root: bool) -> TokenStream2 {
+        let locations = if root {
+            Locations::Root
+        } else {
+            Locations::Element
+        };
         if self.has_dynamic_content() {
-            self.gen_dynamic_html_tokens()
+            self.gen_dynamic_html_tokens(locations)
         } else {
-            let html = self.to_static_html();
-            quote! { #html }
+            located_html(
+                self.to_static_html(Locations::None),
+                self.to_static_html(locations),
+            )
         }
     }
 
-    fn gen_dynamic_html_tokens(&self) -> TokenStream2 {
+    fn gen_dynamic_html_tokens(&self, locations: Locations) -> TokenStream2 {
         let tag = self.name.to_string();
 
         // Separate event handlers from regular attributes
         let (event_props, attr_props): (Vec<_>, Vec<_>) = self
             .props
             .iter()
-            .partition(|p| is_event_prop(&p.name.to_string()));
+            .partition(|p| is_handler_prop(&p.name.to_string()));
 
         // Build attribute parts
         let attr_parts: Vec<TokenStream2> = attr_props
             .iter()
             .map(|p| {
-                let name = p.name.to_string();
+                let name = attr_name(&p.name.to_string());
                 let value = &p.value;
                 if is_literal_expr(value) {
                     let val_str = expr_to_string(value);
                     let escaped = html_escape(&val_str);
                     let attr = format!(" {}=\"{}\"", name, escaped);
                     quote! { __html.push_str(#attr); }
+                } else if is_aria_prop(&p.name.to_string()) {
+                    quote! {
+                        if let Some(__value) = ::rinch::core::a11y::AriaValue::aria_value(&#value) {
+                            __html.push_str(&format!(" {}=\"{}\"", #name, ::rinch::core::events::html_escape_string(&__value)));
+                        }
+                    }
                 } else {
                     quote! {
                         __html.push_str(&format!(" {}=\"{}\"", #name, ::rinch::core::events::html_escape_string(&::std::string::ToString::to_string(&#value))));
@@ -555,29 +947,35 @@ impl RsxElement {
             .collect();
 
         // Event handler registrations
-        let event_registrations: Vec<TokenStream2> = event_props
-            .iter()
-            .map(|p| {
-                let handler = &p.value;
-                quote! {
-                    let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
-                }
-            })
-            .collect();
+        let event_registrations = gen_event_registrations(&event_props);
 
         // data-rid attribute
-        let rid_attr = if !event_props.is_empty() {
+        let rid_attr = if has_click_handler(&event_props) {
             quote! { __html.push_str(&format!(" data-rid=\"{}\"", __handler_id)); }
         } else {
             quote! {}
         };
 
+        // data-onscroll attribute
+        let scroll_attr = if has_scroll_handler(&event_props) {
+            quote! { __html.push_str(&format!(" data-onscroll=\"{}\"", __scroll_handler_id)); }
+        } else {
+            quote! {}
+        };
+
+        // data-ondrag*, data-oninput and such attributes
+        let typed_attrs = typed_handler_attr_parts(&tag, &event_props);
+        let bound_text_parts: Vec<TokenStream2> =
+            bound_text(&tag, &event_props).into_iter().collect();
+
+        let location_attrs = self.location_attrs_tokens(locations);
+
         // Children
         let children_tokens: Vec<TokenStream2> = self
             .children
             .iter()
             .map(|c| {
-                let tokens = c.to_html_tokens();
+                let tokens = c.to_html_tokens(false);
                 quote! { __html.push_str(#tokens); }
             })
             .collect();
@@ -589,8 +987,11 @@ impl RsxElement {
                     let mut __html = String::new();
                     __html.push_str("<");
                     __html.push_str(#tag);
+                    #location_attrs
                     #( #attr_parts )*
                     #rid_attr
+                    #scroll_attr
+                    #( __html.push_str(#typed_attrs); )*
                     __html.push_str(" />");
                     __html
                 }
@@ -602,9 +1003,13 @@ impl RsxElement {
                     let mut __html = String::new();
                     __html.push_str("<");
                     __html.push_str(#tag);
+                    #location_attrs
                     #( #attr_parts )*
                     #rid_attr
+                    #scroll_attr
+                    #( __html.push_str(#typed_attrs); )*
                     __html.push_str(">");
+                    #( __html.push_str(#bound_text_parts); )*
                     #( #children_tokens )*
                     __html.push_str("</");
                     __html.push_str(#tag);
@@ -615,20 +1020,21 @@ impl RsxElement {
         }
     }
 
-    fn to_static_html(&self) -> String {
+    fn to_static_html(&self, locations: Locations) -> String {
         let tag = self.name.to_string();
 
         // Build attributes (skip event handlers)
-        let attrs: String = self
-            .props
-            .iter()
-            .filter(|p| !is_event_prop(&p.name.to_string()))
-            .map(|p| {
-                let name = p.name.to_string();
-                let value = expr_to_string(&p.value);
-                format!(" {}=\"{}\"", name, html_escape(&value))
-            })
-            .collect();
+        let attrs: String = self.location_attrs(locations)
+            + &self
+                .props
+                .iter()
+                .filter(|p| !is_event_prop(&p.name.to_string()))
+                .map(|p| {
+                    let name = attr_name(&p.name.to_string());
+                    let value = expr_to_string(&p.value);
+                    format!(" {}=\"{}\"", name, html_escape(&value))
+                })
+                .collect::<String>();
 
         // Self-closing tags
         if is_void_element(&tag) {
@@ -636,10 +1042,106 @@ impl RsxElement {
         }
 
         // Build children
-        let children: String = self.children.iter().map(|c| node_to_static_html(c)).collect();
+        let children: String = self
+            .children
+            .iter()
+            .map(|c| node_to_static_html(c, locations.children()))
+            .collect();
 
         format!("<{}{}>{}</{}>", tag, attrs, children, tag)
     }
+
+    /// The `data-rsx-src` attribute locating this element in the source,
+    /// and `data-rsx-block` locating the `rsx!` it's at the top of, for
+    /// DevTools.
+    fn location_attrs(&self, locations: Locations) -> String {
+        let mut attrs = String::new();
+        if locations == Locations::None {
+            return attrs;
+        }
+        if let Some(location) = source_location(self.name.span()) {
+            attrs.push_str(&format!(" data-rsx-src=\"{}\"", html_escape(&location)));
+        }
+        if locations == Locations::Root
+            && let Some(location) = source_location(proc_macro2::Span::call_site())
+        {
+            attrs.push_str(&format!(" data-rsx-block=\"{}\"", html_escape(&location)));
+        }
+        attrs
+    }
+
+    /// Statements adding [`location_attrs`](Self::location_attrs) to
+    /// `__html` in debug builds.
+    fn location_attrs_tokens(&self, locations: Locations) -> TokenStream2 {
+        let attrs = self.location_attrs(locations);
+        if attrs.is_empty() {
+            return quote! {};
+        }
+        quote! {
+            if cfg!(debug_assertions) {
+                __html.push_str(#attrs);
+            }
+        }
+    }
+}
+
+/// Which source locations an element's HTML carries. Debug builds mark
+/// each element with where it is in an `rsx!`, so DevTools can point from
+/// an element back to the code that made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Locations {
+    /// None, for release builds.
+    None,
+    /// The element's own location.
+    Element,
+    /// The element's location and the `rsx!` call's, for elements at the
+    /// top of an `rsx!`'s HTML.
+    Root,
+}
+
+impl Locations {
+    /// What the element's children carry.
+    fn children(self) -> Self {
+        match self {
+            Locations::None => Locations::None,
+            Locations::Element | Locations::Root => Locations::Element,
+        }
+    }
+}
+
+/// Where `span` starts, as `file:line:column`. `None` outside a macro
+/// expansion, where spans have no location.
+fn source_location(span: proc_macro2::Span) -> Option<String> {
+    if !proc_macro::is_available() {
+        return None;
+    }
+    let span = span.unwrap();
+    Some(format!("{}:{}:{}", span.file(), span.line(), span.column()))
+}
+
+/// A `&str` expression for `html`, or `located` (the same HTML with source
+/// locations) in debug builds.
+fn located_html(html: String, located: String) -> TokenStream2 {
+    if html == located {
+        quote! { #html }
+    } else {
+        quote! {
+            if cfg!(debug_assertions) { #located } else { #html }
+        }
+    }
+}
+
+/// [`located_html`] for a run of nodes.
+fn static_html_tokens(nodes: &[RsxNode], locations: Locations) -> TokenStream2 {
+    let html = nodes
+        .iter()
+        .map(|node| node_to_static_html(node, Locations::None))
+        .collect();
+    let located = nodes
+        .iter()
+        .map(|node| node_to_static_html(node, locations))
+        .collect();
+    located_html(html, located)
 }
 
 /// A property in RSX (name: value).
@@ -650,7 +1152,7 @@ struct RsxProp {
 
 impl Parse for RsxProp {
     fn parse(input: ParseStream) -> Result<Self> {
-        let name: Ident = input.parse()?;
+        let name = Ident::parse_any(input)?;
         input.parse::<Token![:]>()?;
         let value: Expr = input.parse()?;
         Ok(RsxProp { name, value })
@@ -662,6 +1164,167 @@ fn is_event_prop(name: &str) -> bool {
     name.starts_with("on")
 }
 
+/// Check if a property name is a scroll handler.
+fn is_scroll_prop(name: &str) -> bool {
+    name == "onscroll"
+}
+
+/// The attribute a handler prop other than `onclick` and `onscroll` renders
+/// as, and the `rinch::core` function registering it, if it is one.
+fn typed_handler(name: &str) -> Option<(&'static str, &'static str)> {
+    match name {
+        "ondragstart" => Some(("data-ondragstart", "register_drag_handler")),
+        "ondrag" => Some(("data-ondrag", "register_drag_handler")),
+        "ondragend" => Some(("data-ondragend", "register_drag_handler")),
+        "oninput" => Some(("data-oninput", "register_input_handler")),
+        "onchange" => Some(("data-onchange", "register_input_handler")),
+        "bind" => Some(("data-bind", "register_input_handler")),
+        "onkeydown" => Some(("data-onkeydown", "register_key_handler")),
+        "onkeyup" => Some(("data-onkeyup", "register_key_handler")),
+        "onkeypress" => Some(("data-onkeypress", "register_key_handler")),
+        "oncopy" => Some(("data-oncopy", "register_clipboard_handler")),
+        "oncut" => Some(("data-oncut", "register_clipboard_handler")),
+        "onpaste" => Some(("data-onpaste", "register_clipboard_handler")),
+        "onfocus" => Some(("data-onfocus", "register_handler")),
+        "onblur" => Some(("data-onblur", "register_handler")),
+        _ => None,
+    }
+}
+
+/// Check if a property name is a two-way binding of a text field's value
+/// to a `Signal<String>`.
+fn is_bind_prop(name: &str) -> bool {
+    name == "bind"
+}
+
+/// Check if a property name registers a handler: an event handler or a
+/// binding.
+fn is_handler_prop(name: &str) -> bool {
+    is_event_prop(name) || is_bind_prop(name)
+}
+
+/// The variable a typed handler's registered ID is bound to.
+fn typed_handler_var(name: &str) -> Ident {
+    format_ident!("__{}_handler_id", name)
+}
+
+/// Whether any of the event props is a click-style handler.
+fn has_click_handler(event_props: &[&RsxProp]) -> bool {
+    event_props.iter().any(|p| {
+        let name = p.name.to_string();
+        !is_scroll_prop(&name) && typed_handler(&name).is_none()
+    })
+}
+
+/// Whether any of the event props is a scroll handler.
+fn has_scroll_handler(event_props: &[&RsxProp]) -> bool {
+    event_props
+        .iter()
+        .any(|p| is_scroll_prop(&p.name.to_string()))
+}
+
+/// The attributes for an element's typed handlers (`data-ondrag`,
+/// `data-oninput` and so on), as `&String` expressions. A binding also
+/// renders the signal's value as the field's `value`, except on a
+/// `textarea`, which holds it as text (see [`bound_text`]).
+fn typed_handler_attr_parts(tag: &str, event_props: &[&RsxProp]) -> Vec<TokenStream2> {
+    event_props
+        .iter()
+        .filter_map(|p| {
+            let name = p.name.to_string();
+            let (attr, _) = typed_handler(&name)?;
+            let var = typed_handler_var(&name);
+            if is_bind_prop(&name) && tag != "textarea" {
+                let signal = &p.value;
+                Some(quote! {
+                    &format!(
+                        " value=\"{}\" {}=\"{}\"",
+                        ::rinch::core::events::html_escape_string(&(#signal).get()),
+                        #attr,
+                        #var
+                    )
+                })
+            } else {
+                Some(quote! { &format!(" {}=\"{}\"", #attr, #var) })
+            }
+        })
+        .collect()
+}
+
+/// The text content a `textarea` bound to a signal starts with, as a `&String`
+/// expression.
+fn bound_text(tag: &str, event_props: &[&RsxProp]) -> Option<TokenStream2> {
+    if tag != "textarea" {
+        return None;
+    }
+    let signal = &event_props
+        .iter()
+        .find(|p| is_bind_prop(&p.name.to_string()))?
+        .value;
+    Some(quote! { &::rinch::core::events::html_escape_string(&(#signal).get()) })
+}
+
+/// Generate the registrations for an element's event handlers.
+///
+/// Click-style handlers bind `__handler_id`, scroll handlers bind
+/// `__scroll_handler_id` and typed handlers bind `__ondrag_handler_id`,
+/// `__bind_handler_id` and so on, after their prop. A binding sets its
+/// signal to the field's value.
+fn gen_event_registrations(event_props: &[&RsxProp]) -> Vec<TokenStream2> {
+    event_props
+        .iter()
+        .map(|p| {
+            let handler = &p.value;
+            let name = p.name.to_string();
+            if let Some((_, register)) = typed_handler(&name) {
+                let var = typed_handler_var(&name);
+                let register = format_ident!("{}", register);
+                let handler = if is_bind_prop(&name) {
+                    quote! {{
+                        let __signal = ::std::clone::Clone::clone(&#handler);
+                        move |__value: String| __signal.set(__value)
+                    }}
+                } else {
+                    quote! { #handler }
+                };
+                quote! {
+                    let #var = ::rinch::core::#register(Box::new(#handler));
+                }
+            } else if is_scroll_prop(&name) {
+                quote! {
+                    let __scroll_handler_id = ::rinch::core::register_scroll_handler(Box::new(#handler));
+                }
+            } else {
+                quote! {
+                    let __handler_id = ::rinch::core::register_handler(Box::new(#handler));
+                }
+            }
+        })
+        .collect()
+}
+
+/// Map a prop name to the HTML attribute it renders as.
+fn attr_name(prop: &str) -> String {
+    match prop {
+        "node_ref" => "data-node-ref".to_string(),
+        "key" => "data-key".to_string(),
+        "animate_moves" => "data-animate-moves".to_string(),
+        "transition_id" => "data-transition-id".to_string(),
+        "motion" => "data-motion".to_string(),
+        "lottie_src" => "data-lottie-src".to_string(),
+        "lottie_playing" => "data-lottie-playing".to_string(),
+        "lottie_loop" => "data-lottie-loop".to_string(),
+        _ if prop.starts_with("aria_") => prop.replace('_', "-"),
+        _ => prop.to_string(),
+    }
+}
+
+/// Whether a prop is `role` or an `aria_*` attribute, whose dynamic values
+/// are rendered through `AriaValue`.
+fn is_aria_prop(prop: &str) -> bool {
+    prop == "role" || prop.starts_with("aria_")
+}
+
 /// Check if an expression is a literal (can be evaluated at compile time).
 fn is_literal_expr(expr: &Expr) -> bool {
     matches!(expr, Expr::Lit(_))
@@ -699,9 +1362,9 @@ fn is_void_element(tag: &str) -> bool {
 }
 
 /// Convert an RSX node to static HTML (for compile-time generation).
-fn node_to_static_html(node: &RsxNode) -> String {
+fn node_to_static_html(node: &RsxNode, locations: Locations) -> String {
     match node {
-        RsxNode::Element(el) => el.to_static_html(),
+        RsxNode::Element(el) => el.to_static_html(locations),
         RsxNode::Text(lit) => html_escape(&lit.value()),
         RsxNode::Expr(_) => String::new(), // Expressions can't be static
     }
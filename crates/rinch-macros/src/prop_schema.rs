@@ -38,6 +38,12 @@ static WINDOW_PROPS: &[PropSchema] = &[
     PropSchema::optional("transparent"),
     PropSchema::optional("always_on_top"),
     PropSchema::optional("visible"),
+    PropSchema::optional("reset"),
+    PropSchema::optional("rtl"),
+    PropSchema::optional("antialiasing"),
+    PropSchema::optional("overlay_scrollbars"),
+    PropSchema::optional("overscroll_bounce"),
+    PropSchema::optional("content_scale"),
 ];
 
 /// AppMenu component properties.
@@ -55,6 +61,53 @@ static MENU_ITEM_PROPS: &[PropSchema] = &[
     PropSchema::optional("onclick"),
 ];
 
+/// LottiePlayer component properties. It renders as a `div`, so it also
+/// takes the attributes that place and style one.
+static LOTTIE_PLAYER_PROPS: &[PropSchema] = &[
+    PropSchema::required("src"),
+    PropSchema::optional("playing"),
+    PropSchema::optional("loop"),
+    PropSchema::optional("motion"),
+    PropSchema::optional("class"),
+    PropSchema::optional("style"),
+    PropSchema::optional("id"),
+    PropSchema::optional("node_ref"),
+];
+
+/// ErrorBoundary component properties.
+static ERROR_BOUNDARY_PROPS: &[PropSchema] = &[PropSchema::required("fallback")];
+
+/// Lazy component properties.
+static LAZY_PROPS: &[PropSchema] = &[
+    PropSchema::required("loader"),
+    PropSchema::optional("placeholder"),
+    PropSchema::optional("when"),
+];
+
+/// MaskedInput component properties.
+static MASKED_INPUT_PROPS: &[PropSchema] = &[
+    PropSchema::required("field"),
+    PropSchema::optional("class"),
+    PropSchema::optional("placeholder"),
+];
+
+/// PluginSlot component properties.
+static PLUGIN_SLOT_PROPS: &[PropSchema] = &[PropSchema::required("name")];
+
+/// Portal component properties.
+static PORTAL_PROPS: &[PropSchema] = &[PropSchema::optional("target")];
+
+/// Suspense component properties.
+static SUSPENSE_PROPS: &[PropSchema] = &[PropSchema::optional("fallback")];
+
+/// VirtualList component properties.
+static VIRTUAL_LIST_PROPS: &[PropSchema] = &[
+    PropSchema::required("virtualizer"),
+    PropSchema::required("row"),
+    PropSchema::optional("class"),
+    PropSchema::optional("style"),
+];
+
 /// Get valid property names for a component.
 pub fn get_valid_props(component: &str) -> Option<&'static [PropSchema]> {
     match component {
@@ -62,6 +115,14 @@ pub fn get_valid_props(component: &str) -> Option<&'static [PropSchema]> {
         "AppMenu" => Some(APP_MENU_PROPS),
         "Menu" => Some(MENU_PROPS),
         "MenuItem" => Some(MENU_ITEM_PROPS),
+        "LottiePlayer" => Some(LOTTIE_PLAYER_PROPS),
+        "ErrorBoundary" => Some(ERROR_BOUNDARY_PROPS),
+        "Lazy" => Some(LAZY_PROPS),
+        "MaskedInput" => Some(MASKED_INPUT_PROPS),
+        "PluginSlot" => Some(PLUGIN_SLOT_PROPS),
+        "Portal" => Some(PORTAL_PROPS),
+        "Suspense" => Some(SUSPENSE_PROPS),
+        "VirtualList" => Some(VIRTUAL_LIST_PROPS),
         _ => None,
     }
 }
@@ -0,0 +1,69 @@
+//! `#[derive(Store)]`.
+//!
+//! Adds an accessor for each field of a struct to `Store<ThatStruct>`,
+//! through a trait named after the struct, so `state.user().name()` reaches
+//! a field tracked apart from the rest of the store.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, Result};
+
+/// Expand `#[derive(Store)]` on `input`.
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "Store can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "Store can only be derived for structs with named fields",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Store can't be derived for generic structs; call `Store::field` instead",
+        ));
+    }
+
+    let vis = &input.vis;
+    let trait_name = format_ident!("{}StoreFields", name);
+    let trait_doc = format!("The fields of a `Store<{name}>`, each tracked apart from the others.");
+    let store = quote! { ::rinch::core::store::Store };
+
+    let mut signatures = Vec::new();
+    let mut accessors = Vec::new();
+    for field in &fields.named {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+        let ty = &field.ty;
+        let field_name = ident.unraw().to_string();
+        let doc = format!("The store's `{field_name}`.");
+        signatures.push(quote! {
+            #[doc = #doc]
+            fn #ident(&self) -> #store<#ty>;
+        });
+        accessors.push(quote! {
+            fn #ident(&self) -> #store<#ty> {
+                self.field(#field_name, |value| &value.#ident, |value| &mut value.#ident)
+            }
+        });
+    }
+
+    Ok(quote! {
+        #[doc = #trait_doc]
+        #vis trait #trait_name {
+            #(#signatures)*
+        }
+
+        impl #trait_name for #store<#name> {
+            #(#accessors)*
+        }
+    })
+}
@@ -1,21 +1,56 @@
 //! Application lifecycle and entry point.
 
-/// Main application container.
+use rinch_core::element::Element;
+
+use crate::plugin::{self, RinchPlugin};
+
+/// An app with its plugins, for when [`rinch::run`](crate::run) alone isn't
+/// enough.
+///
+/// ```ignore
+/// fn main() {
+///     App::new().with_plugin(GitPlugin).run(app);
+/// }
+/// ```
 pub struct App {
-    // TODO: Application state
+    plugins: Vec<Box<dyn RinchPlugin>>,
 }
 
 impl App {
     /// Create a new application.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Add a plugin. Plugins register in the order they're added, when the
+    /// app runs. See [`plugin`](crate::plugin).
+    pub fn with_plugin(self, plugin: impl RinchPlugin + 'static) -> Self {
+        self.with_boxed_plugin(Box::new(plugin))
+    }
+
+    /// Add a plugin that's already boxed, such as one from
+    /// [`load_plugin`](crate::plugin).
+    pub fn with_boxed_plugin(mut self, plugin: Box<dyn RinchPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
     }
 
-    /// Run the application event loop.
-    pub fn run(self) {
-        // TODO: Initialize winit event loop
-        // TODO: Create window with blitz renderer
-        // TODO: Run event loop
+    /// Register the plugins and run `app`, as [`rinch::run`](crate::run)
+    /// does. Plugins' menus are added to its `AppMenu` every render.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "web"))]
+    pub fn run<F>(self, app: F)
+    where
+        F: Fn() -> Element + 'static,
+    {
+        let services = self
+            .plugins
+            .iter()
+            .flat_map(|plugin| plugin::register(plugin.as_ref()))
+            .collect();
+        plugin::start_services(services);
+        crate::run(move || plugin::merge_menus(app()));
     }
 }
 
@@ -0,0 +1,257 @@
+//! Bundled assets.
+//!
+//! [`asset!`](crate::asset) names a file by its path relative to the
+//! crate's `Cargo.toml`. Debug builds load it from there, so hot reload
+//! picks up edits to it; release builds embed it in the executable, so the
+//! app keeps finding it wherever it's installed or moved.
+//!
+//! An [`Asset`] displays as a URL that rinch resolves wherever it loads
+//! local files: stylesheet `<link>`s, `@font-face` sources and Lottie
+//! animations. Anything else can read its bytes.
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//!
+//! fn app() -> Element {
+//!     rsx! {
+//!         Window { title: "Editor",
+//!             link { rel: "stylesheet", href: asset!("styles/editor.css") }
+//!             lottie { src: asset!("animations/saving.json") }
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let font = asset!("fonts/Inter.ttf");
+//!     rinch::fonts::register(font.bytes().unwrap().into_owned());
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! In CSS, which `asset!` can't reach, `url("asset:fonts/Inter.ttf")` finds
+//! an asset once the app has named it with `asset!`.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// URL scheme of embedded assets.
+pub const ASSET_SCHEME: &str = "asset:";
+
+/// Every asset named so far, for resolving `asset:` URLs.
+static ASSETS: Mutex<Vec<Asset>> = Mutex::new(Vec::new());
+
+/// A file bundled with the app. Create one with [`asset!`](crate::asset).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Asset {
+    path: &'static str,
+    source: Source,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// An absolute path, in debug builds.
+    File(&'static str),
+    /// The contents, in release builds.
+    Embedded(&'static [u8]),
+}
+
+/// Name a file bundled with the app, by its path relative to the crate's
+/// `Cargo.toml`: loaded from disk in debug builds, and embedded in the
+/// executable in release builds. See the [`assets`](crate::assets) module.
+///
+/// ```ignore
+/// link { rel: "stylesheet", href: asset!("styles/app.css") }
+/// ```
+#[macro_export]
+macro_rules! asset {
+    ($path:literal) => {{
+        #[cfg(debug_assertions)]
+        let asset =
+            $crate::assets::Asset::on_disk($path, concat!(env!("CARGO_MANIFEST_DIR"), "/", $path));
+        #[cfg(not(debug_assertions))]
+        let asset = $crate::assets::Asset::embedded(
+            $path,
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
+        );
+        asset
+    }};
+}
+
+impl Asset {
+    #[doc(hidden)]
+    pub fn on_disk(path: &'static str, file: &'static str) -> Self {
+        Self::register(Self {
+            path,
+            source: Source::File(file),
+        })
+    }
+
+    #[doc(hidden)]
+    pub fn embedded(path: &'static str, contents: &'static [u8]) -> Self {
+        Self::register(Self {
+            path,
+            source: Source::Embedded(contents),
+        })
+    }
+
+    fn register(asset: Self) -> Self {
+        let mut assets = ASSETS.lock().unwrap_or_else(|e| e.into_inner());
+        if !assets.contains(&asset) {
+            assets.retain(|known| known.path != asset.path);
+            assets.push(asset);
+        }
+        asset
+    }
+
+    /// The path relative to the crate's `Cargo.toml`.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Whether the contents are embedded in the executable.
+    pub fn is_embedded(&self) -> bool {
+        matches!(self.source, Source::Embedded(_))
+    }
+
+    /// The URL rinch loads it from: the file's absolute path in debug
+    /// builds, so changes to it are picked up, or an `asset:` URL.
+    pub fn url(&self) -> String {
+        match self.source {
+            Source::File(file) => file.to_string(),
+            Source::Embedded(_) => format!("{ASSET_SCHEME}{}", self.path),
+        }
+    }
+
+    /// The contents.
+    pub fn bytes(&self) -> io::Result<Cow<'static, [u8]>> {
+        match self.source {
+            Source::File(file) => std::fs::read(file).map(Cow::Owned),
+            Source::Embedded(contents) => Ok(Cow::Borrowed(contents)),
+        }
+    }
+
+    /// The contents as text.
+    pub fn text(&self) -> io::Result<String> {
+        into_string(self.bytes()?)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url())
+    }
+}
+
+impl fmt::Debug for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Asset");
+        debug.field("path", &self.path);
+        match self.source {
+            Source::File(file) => debug.field("file", &file),
+            Source::Embedded(contents) => debug.field("embedded_bytes", &contents.len()),
+        };
+        debug.finish()
+    }
+}
+
+impl From<Asset> for String {
+    fn from(asset: Asset) -> Self {
+        asset.url()
+    }
+}
+
+/// Read a local file or asset by URL: an `asset:` URL, a `file://` URL or
+/// a path.
+pub fn read(url: &str) -> io::Result<Cow<'static, [u8]>> {
+    if let Some(path) = url.strip_prefix(ASSET_SCHEME) {
+        let asset = ASSETS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|asset| asset.path == path)
+            .copied();
+        return match asset {
+            Some(asset) => asset.bytes(),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no asset named {path:?}; assets are found once `asset!` names them"),
+            )),
+        };
+    }
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    std::fs::read(path).map(Cow::Owned)
+}
+
+/// Read a local file or asset by URL as text. See [`read`].
+pub fn read_to_string(url: &str) -> io::Result<String> {
+    into_string(read(url)?)
+}
+
+fn into_string(bytes: Cow<'static, [u8]>) -> io::Result<String> {
+    String::from_utf8(bytes.into_owned()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// The files of the assets loaded from disk, for hot reload to watch.
+pub(crate) fn asset_files() -> Vec<PathBuf> {
+    ASSETS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter_map(|asset| match asset.source {
+            Source::File(file) => Some(PathBuf::from(file)),
+            Source::Embedded(_) => None,
+        })
+        .collect()
+}
+
+/// Whether `path` is the file of an asset loaded from disk.
+pub(crate) fn is_asset_file(path: &Path) -> bool {
+    asset_files().iter().any(|file| paths_match(file, path))
+}
+
+/// Compare paths as the file watcher reports them, which may be canonical.
+fn paths_match(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (a.canonicalize(), b.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_assets_resolve_by_url() {
+        let asset = Asset::embedded("tests/hello.txt", b"hello");
+        assert_eq!(asset.to_string(), "asset:tests/hello.txt");
+        assert_eq!(read_to_string("asset:tests/hello.txt").unwrap(), "hello");
+        assert!(matches!(
+            read("asset:tests/hello.txt").unwrap(),
+            Cow::Borrowed(b"hello")
+        ));
+
+        let missing = read("asset:tests/missing.txt").unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn assets_on_disk_are_read_fresh() {
+        let file = std::env::temp_dir().join(format!("rinch-asset-{}.css", std::process::id()));
+        std::fs::write(&file, "a {}").unwrap();
+        let file_str: &'static str =
+            Box::leak(file.to_string_lossy().into_owned().into_boxed_str());
+        let asset = Asset::on_disk("styles/test.css", file_str);
+        assert_eq!(asset.url(), file_str);
+        assert!(is_asset_file(&file));
+
+        std::fs::write(&file, "b {}").unwrap();
+        assert_eq!(asset.text().unwrap(), "b {}");
+        assert_eq!(read_to_string(&asset.url()).unwrap(), "b {}");
+        let _ = std::fs::remove_file(&file);
+    }
+}
@@ -2,24 +2,43 @@
 //!
 //! This module provides clipboard operations using the `arboard` crate.
 //!
+//! Elements can also handle the clipboard shortcuts themselves: `oncopy`,
+//! `oncut` and `onpaste` run when Ctrl+C, Ctrl+X or Ctrl+V (Cmd on macOS)
+//! is pressed while they, or an element inside them, have focus, with a
+//! [`ClipboardEvent`] holding the selected text, or what's being pasted.
+//!
 //! # Example
 //!
 //! ```ignore
-//! use rinch::clipboard::{copy_text, paste_text, has_text};
+//! use rinch::clipboard::{read_text, write_text, has_text};
 //!
 //! // Copy text to clipboard
-//! copy_text("Hello, clipboard!").unwrap();
+//! write_text("Hello, clipboard!").unwrap();
 //!
 //! // Check if clipboard has text
 //! if has_text() {
 //!     // Paste text from clipboard
-//!     if let Ok(text) = paste_text() {
+//!     if let Ok(text) = read_text() {
 //!         println!("Clipboard: {}", text);
 //!     }
 //! }
+//!
+//! // Paste into a list, text or image
+//! rsx! {
+//!     div { tabindex: "0",
+//!         onpaste: move |event: ClipboardEvent| {
+//!             if let Some(image) = event.image {
+//!                 images.update(|images| images.push(image));
+//!             } else if let Some(text) = event.text {
+//!                 notes.update(|notes| notes.push(text));
+//!             }
+//!         },
+//!     }
+//! }
 //! ```
 
 use arboard::Clipboard;
+use rinch_core::events::{ClipboardEvent, ClipboardImage};
 use std::sync::Mutex;
 
 /// Clipboard error type.
@@ -62,43 +81,53 @@ fn with_clipboard<T, F: FnOnce(&mut Clipboard) -> ClipboardResult<T>>(f: F) -> C
     f(guard.as_mut().unwrap())
 }
 
-/// Copy text to the clipboard.
+/// Write text to the clipboard, replacing what's on it.
 ///
 /// # Example
 ///
 /// ```ignore
-/// use rinch::clipboard::copy_text;
+/// use rinch::clipboard::write_text;
 ///
-/// copy_text("Hello, world!").unwrap();
+/// write_text("Hello, world!").unwrap();
 /// ```
-pub fn copy_text(text: impl AsRef<str>) -> ClipboardResult<()> {
+pub fn write_text(text: impl AsRef<str>) -> ClipboardResult<()> {
     with_clipboard(|clipboard| {
         clipboard.set_text(text.as_ref())?;
         Ok(())
     })
 }
 
-/// Paste text from the clipboard.
+/// Read the text on the clipboard.
 ///
 /// Returns `Err` if the clipboard doesn't contain text.
 ///
 /// # Example
 ///
 /// ```ignore
-/// use rinch::clipboard::paste_text;
+/// use rinch::clipboard::read_text;
 ///
-/// match paste_text() {
+/// match read_text() {
 ///     Ok(text) => println!("Pasted: {}", text),
 ///     Err(e) => println!("Failed to paste: {}", e),
 /// }
 /// ```
-pub fn paste_text() -> ClipboardResult<String> {
+pub fn read_text() -> ClipboardResult<String> {
     with_clipboard(|clipboard| {
         let text = clipboard.get_text()?;
         Ok(text)
     })
 }
 
+/// Copy text to the clipboard. The same as [`write_text`].
+pub fn copy_text(text: impl AsRef<str>) -> ClipboardResult<()> {
+    write_text(text)
+}
+
+/// Paste text from the clipboard. The same as [`read_text`].
+pub fn paste_text() -> ClipboardResult<String> {
+    read_text()
+}
+
 /// Check if the clipboard contains text.
 ///
 /// # Example
@@ -111,7 +140,7 @@ pub fn paste_text() -> ClipboardResult<String> {
 /// }
 /// ```
 pub fn has_text() -> bool {
-    paste_text().is_ok()
+    read_text().is_ok()
 }
 
 /// Clear the clipboard contents.
@@ -130,23 +159,23 @@ pub fn clear() -> ClipboardResult<()> {
     })
 }
 
-/// Copy an image to the clipboard.
+/// Write an image to the clipboard, replacing what's on it.
 ///
 /// The image data should be in RGBA format.
 ///
 /// # Example
 ///
 /// ```ignore
-/// use rinch::clipboard::{copy_image, ImageData};
+/// use rinch::clipboard::{write_image, ImageData};
 ///
 /// let image = ImageData {
 ///     width: 100,
 ///     height: 100,
 ///     bytes: vec![255; 100 * 100 * 4].into(), // White image
 /// };
-/// copy_image(image).unwrap();
+/// write_image(image).unwrap();
 /// ```
-pub fn copy_image(image: ImageData) -> ClipboardResult<()> {
+pub fn write_image(image: ImageData) -> ClipboardResult<()> {
     with_clipboard(|clipboard| {
         let arboard_image = arboard::ImageData {
             width: image.width,
@@ -158,20 +187,20 @@ pub fn copy_image(image: ImageData) -> ClipboardResult<()> {
     })
 }
 
-/// Paste an image from the clipboard.
+/// Read the image on the clipboard.
 ///
 /// Returns the image data in RGBA format.
 ///
 /// # Example
 ///
 /// ```ignore
-/// use rinch::clipboard::paste_image;
+/// use rinch::clipboard::read_image;
 ///
-/// if let Ok(image) = paste_image() {
+/// if let Ok(image) = read_image() {
 ///     println!("Image: {}x{}", image.width, image.height);
 /// }
 /// ```
-pub fn paste_image() -> ClipboardResult<ImageData<'static>> {
+pub fn read_image() -> ClipboardResult<ImageData<'static>> {
     with_clipboard(|clipboard| {
         let image = clipboard.get_image()?;
         Ok(ImageData {
@@ -182,6 +211,24 @@ pub fn paste_image() -> ClipboardResult<ImageData<'static>> {
     })
 }
 
+/// Copy an image to the clipboard. The same as [`write_image`].
+pub fn copy_image(image: ImageData) -> ClipboardResult<()> {
+    write_image(image)
+}
+
+/// Paste an image from the clipboard. The same as [`read_image`].
+pub fn paste_image() -> ClipboardResult<ImageData<'static>> {
+    read_image()
+}
+
+/// What's on the clipboard, for `onpaste` handlers.
+pub(crate) fn paste_event() -> ClipboardEvent {
+    ClipboardEvent {
+        text: read_text().ok(),
+        image: read_image().ok().map(ClipboardImage::from),
+    }
+}
+
 /// Check if the clipboard contains an image.
 ///
 /// # Example
@@ -194,7 +241,7 @@ pub fn paste_image() -> ClipboardResult<ImageData<'static>> {
 /// }
 /// ```
 pub fn has_image() -> bool {
-    paste_image().is_ok()
+    read_image().is_ok()
 }
 
 /// Image data for clipboard operations.
@@ -229,3 +276,23 @@ impl<'a> ImageData<'a> {
         }
     }
 }
+
+impl From<ImageData<'_>> for ClipboardImage {
+    fn from(image: ImageData<'_>) -> Self {
+        ClipboardImage {
+            width: image.width,
+            height: image.height,
+            rgba: image.bytes.into(),
+        }
+    }
+}
+
+impl From<ClipboardImage> for ImageData<'static> {
+    fn from(image: ClipboardImage) -> Self {
+        ImageData {
+            width: image.width,
+            height: image.height,
+            bytes: std::borrow::Cow::Owned(image.rgba.to_vec()),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Masks that format what's typed into an input.
+
+/// A format for input text, such as a phone number or an amount of money.
+///
+/// [`apply`](Mask::apply) reformats the text after each edit and says
+/// where the caret belongs, so typing, pasting and deleting in the middle
+/// keep the caret after the same character the user left it after.
+///
+/// ```ignore
+/// use rinch::components::Mask;
+///
+/// let phone = Mask::phone();
+/// let masked = phone.apply("5551234", 7);
+/// assert_eq!(masked.text, "(555) 123-4");
+/// assert_eq!(masked.caret, 11);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Pattern(Vec<Slot>),
+    Currency { symbol: String, decimals: usize },
+}
+
+/// One character of a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Digit,
+    Letter,
+    Alphanumeric,
+    Hex,
+    Literal(char),
+}
+
+impl Slot {
+    fn accepts(self, ch: char) -> bool {
+        match self {
+            Slot::Digit => ch.is_ascii_digit(),
+            Slot::Letter => ch.is_alphabetic(),
+            Slot::Alphanumeric => ch.is_alphanumeric(),
+            Slot::Hex => ch.is_ascii_hexdigit(),
+            Slot::Literal(_) => false,
+        }
+    }
+}
+
+/// Text formatted by a [`Mask`], with the caret's new place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Masked {
+    pub text: String,
+    /// The caret, in characters from the start of `text`.
+    pub caret: usize,
+}
+
+impl Mask {
+    /// A mask from a pattern: `9` takes a digit, `a` a letter, `*` a letter
+    /// or digit and `h` a hex digit. Anything else is shown as it is, and
+    /// `\` shows the character after it as it is.
+    pub fn pattern(pattern: &str) -> Self {
+        let mut slots = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(ch) = chars.next() {
+            slots.push(match ch {
+                '9' => Slot::Digit,
+                'a' => Slot::Letter,
+                '*' => Slot::Alphanumeric,
+                'h' => Slot::Hex,
+                '\\' => match chars.next() {
+                    Some(ch) => Slot::Literal(ch),
+                    None => break,
+                },
+                ch => Slot::Literal(ch),
+            });
+        }
+        Self {
+            kind: Kind::Pattern(slots),
+        }
+    }
+
+    /// A North American phone number: `(555) 123-4567`.
+    pub fn phone() -> Self {
+        Self::pattern("(999) 999-9999")
+    }
+
+    /// An ISO 8601 date: `2024-03-15`.
+    pub fn date() -> Self {
+        Self::pattern("9999-99-99")
+    }
+
+    /// A CSS hex color: `#1e90ff`.
+    pub fn hex_color() -> Self {
+        Self::pattern("#hhhhhh")
+    }
+
+    /// An amount of money: `symbol`, then digits grouped in thousands with
+    /// commas, and up to `decimals` digits after a point. With no decimals,
+    /// anything typed after a point is dropped.
+    pub fn currency(symbol: &str, decimals: usize) -> Self {
+        Self {
+            kind: Kind::Currency {
+                symbol: symbol.to_string(),
+                decimals,
+            },
+        }
+    }
+
+    /// Format `text`, as edited, with the caret `caret` characters in.
+    /// Characters the mask has no place for are dropped.
+    pub fn apply(&self, text: &str, caret: usize) -> Masked {
+        let chars: Vec<char> = text.chars().collect();
+        match &self.kind {
+            Kind::Pattern(slots) => apply_pattern(slots, &chars, caret),
+            Kind::Currency { symbol, decimals } => apply_currency(symbol, *decimals, &chars, caret),
+        }
+    }
+
+    /// The characters typed into `text`, without what the mask adds, such
+    /// as `5551234567` for `(555) 123-4567`.
+    pub fn unmask(&self, text: &str) -> String {
+        let masked = self.apply(text, 0).text;
+        match &self.kind {
+            Kind::Pattern(slots) => masked
+                .chars()
+                .zip(slots)
+                .filter(|(_, slot)| !matches!(slot, Slot::Literal(_)))
+                .map(|(ch, _)| ch)
+                .collect(),
+            Kind::Currency { symbol, .. } => masked
+                .strip_prefix(symbol.as_str())
+                .unwrap_or(&masked)
+                .chars()
+                .filter(|ch| ch.is_ascii_digit() || *ch == '.')
+                .collect(),
+        }
+    }
+
+    /// Whether `text` fills the whole pattern. Currency is complete once
+    /// it has a digit.
+    pub fn is_complete(&self, text: &str) -> bool {
+        let masked = self.apply(text, 0).text;
+        match &self.kind {
+            Kind::Pattern(slots) => masked.chars().count() == slots.len(),
+            Kind::Currency { .. } => masked.chars().any(|ch| ch.is_ascii_digit()),
+        }
+    }
+}
+
+fn apply_pattern(slots: &[Slot], chars: &[char], caret: usize) -> Masked {
+    let mut text = String::new();
+    let mut len = 0;
+    // Where the text ends after its last typed character
+    let mut filled = 0;
+    let mut new_caret = 0;
+    let mut next = 0;
+    'slots: for &slot in slots {
+        if next >= chars.len() {
+            break;
+        }
+        if let Slot::Literal(literal) = slot {
+            text.push(literal);
+            len += 1;
+            // Typing the literal itself just steps over it
+            if chars[next] == literal {
+                next += 1;
+                if next <= caret {
+                    new_caret = len;
+                }
+            }
+            continue;
+        }
+        while let Some(&ch) = chars.get(next) {
+            next += 1;
+            if slot.accepts(ch) {
+                text.push(ch);
+                len += 1;
+                filled = len;
+                if next <= caret {
+                    new_caret = len;
+                }
+                continue 'slots;
+            }
+        }
+    }
+    Masked {
+        text: text.chars().take(filled).collect(),
+        caret: new_caret.min(filled),
+    }
+}
+
+fn apply_currency(symbol: &str, decimals: usize, chars: &[char], caret: usize) -> Masked {
+    // Kept characters, and whether each was before the caret
+    let mut whole = Vec::new();
+    let mut fraction = Vec::new();
+    let mut point = None;
+    // The symbol isn't part of the amount, even if it has a point or digits
+    let symbol_len = symbol.chars().count();
+    let start = if chars.iter().copied().take(symbol_len).eq(symbol.chars()) {
+        symbol_len
+    } else {
+        0
+    };
+    for (index, &ch) in chars.iter().enumerate().skip(start) {
+        let before = index < caret;
+        if ch.is_ascii_digit() {
+            match point {
+                None => whole.push((ch, before)),
+                Some(_) if fraction.len() < decimals => fraction.push((ch, before)),
+                Some(_) => {}
+            }
+        } else if ch == '.' && point.is_none() {
+            point = Some(before);
+        }
+    }
+    // Without decimals, the point only ends the whole amount
+    if decimals == 0 {
+        point = None;
+    }
+    while whole.len() > 1 && whole[0].0 == '0' {
+        whole.remove(0);
+    }
+    if whole.is_empty() && point.is_none() {
+        return Masked {
+            text: String::new(),
+            caret: 0,
+        };
+    }
+    if whole.is_empty() {
+        whole.push(('0', false));
+    }
+
+    let mut text = symbol.to_string();
+    let mut len = symbol.chars().count();
+    let mut new_caret = len;
+    for (index, &(ch, before)) in whole.iter().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            text.push(',');
+            len += 1;
+        }
+        text.push(ch);
+        len += 1;
+        if before {
+            new_caret = len;
+        }
+    }
+    if let Some(before) = point {
+        text.push('.');
+        len += 1;
+        if before {
+            new_caret = len;
+        }
+        for &(ch, before) in &fraction {
+            text.push(ch);
+            len += 1;
+            if before {
+                new_caret = len;
+            }
+        }
+    }
+    Masked {
+        text,
+        caret: new_caret,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(mask: &Mask, text: &str, caret: usize) -> (String, usize) {
+        let masked = mask.apply(text, caret);
+        (masked.text, masked.caret)
+    }
+
+    #[test]
+    fn patterns_keep_the_caret_after_the_same_character() {
+        let phone = Mask::phone();
+        assert_eq!(apply(&phone, "555", 3), ("(555".into(), 4));
+        assert_eq!(apply(&phone, "5551", 4), ("(555) 1".into(), 7));
+        // Typing a digit in the middle pushes the rest along
+        assert_eq!(apply(&phone, "(5955) 123-4", 3), ("(595) 512-34".into(), 3));
+        // Deleting a digit pulls it back
+        assert_eq!(apply(&phone, "(55) 123-4", 3), ("(551) 234".into(), 3));
+        // Pasted text with its own punctuation, and letters, are cleaned up
+        assert_eq!(
+            apply(&phone, "555.123.4567x", 13),
+            ("(555) 123-4567".into(), 14)
+        );
+        assert_eq!(phone.unmask("(555) 123-4567"), "5551234567");
+        assert!(phone.is_complete("5551234567"));
+        assert!(!phone.is_complete("555123"));
+
+        let color = Mask::hex_color();
+        assert_eq!(apply(&color, "1E90FG", 6), ("#1E90F".into(), 6));
+        assert_eq!(apply(&color, "#1e", 3), ("#1e".into(), 3));
+        assert_eq!(
+            apply(&Mask::date(), "20240315", 5),
+            ("2024-03-15".into(), 6)
+        );
+        assert_eq!(apply(&Mask::pattern(r"\9-99"), "12", 2), ("9-12".into(), 4));
+    }
+
+    #[test]
+    fn currency_groups_thousands() {
+        let dollars = Mask::currency("$", 2);
+        assert_eq!(apply(&dollars, "1234", 4), ("$1,234".into(), 6));
+        assert_eq!(apply(&dollars, "$1,2345", 7), ("$12,345".into(), 7));
+        // Caret after the 2, before the 3
+        assert_eq!(apply(&dollars, "$12,345", 3), ("$12,345".into(), 3));
+        assert_eq!(apply(&dollars, "0012.345", 8), ("$12.34".into(), 6));
+        assert_eq!(apply(&dollars, ".5", 2), ("$0.5".into(), 4));
+        assert_eq!(apply(&dollars, "$", 1), (String::new(), 0));
+        assert_eq!(dollars.unmask("$1,234.50"), "1234.50");
+        // Digits after the point are dropped when there are no decimals
+        let euros = Mask::currency("€", 0);
+        assert_eq!(apply(&euros, "12.5", 4), ("€12".into(), 3));
+        assert_eq!(euros.unmask("€1,234.99"), "1234");
+        // A symbol with a point or digits isn't read as part of the amount
+        let kronor = Mask::currency("kr.", 2);
+        assert_eq!(apply(&kronor, "kr.12", 5), ("kr.12".into(), 5));
+        assert_eq!(kronor.unmask("kr.1,234.50"), "1234.50");
+        assert_eq!(Mask::currency("R$1", 2).unmask("R$15.00"), "5.00");
+    }
+}
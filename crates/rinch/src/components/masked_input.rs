@@ -0,0 +1,151 @@
+//! Inputs that format what's typed with a [`Mask`].
+
+use rinch_core::element::Element;
+use rinch_core::events::{html_escape_string, register_input_handler};
+use rinch_core::node_ref::NODE_REF_ATTR;
+use rinch_core::{untracked, use_node_ref, use_signal, NodeRef, Signal};
+
+use super::mask::Mask;
+
+/// A masked input's text, formatted by its mask, and a ref to the input.
+///
+/// Each edit is formatted as it's typed, and the caret is put back after
+/// the character the user typed, deleted up to or pasted before, although
+/// formatting rebuilds the input.
+///
+/// ```ignore
+/// let phone = use_masked_field(Mask::phone(), "");
+///
+/// rsx! {
+///     MaskedInput { field: phone.clone(), placeholder: "(555) 123-4567" }
+///     button { disabled: !phone.is_complete(), onclick: move || call(phone.value()), "Call" }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MaskedField {
+    mask: Mask,
+    text: Signal<String>,
+    input: NodeRef,
+}
+
+impl MaskedField {
+    /// A field formatting its text with `mask`, holding it in `text`, for
+    /// the input `input` refers to.
+    pub fn new(mask: Mask, text: Signal<String>, input: NodeRef) -> Self {
+        Self { mask, text, input }
+    }
+
+    /// The formatted text, such as `(555) 123-4567`.
+    pub fn text(&self) -> String {
+        self.text.get()
+    }
+
+    /// What was typed, without what the mask adds, such as `5551234567`.
+    pub fn value(&self) -> String {
+        self.mask.unmask(&self.text.get())
+    }
+
+    /// Whether the text fills the mask.
+    pub fn is_complete(&self) -> bool {
+        self.mask.is_complete(&self.text.get())
+    }
+
+    /// Replace the text, formatting it.
+    pub fn set(&self, text: &str) {
+        self.text.set(self.mask.apply(text, 0).text);
+    }
+
+    /// The input's ref.
+    pub fn node_ref(&self) -> NodeRef {
+        self.input.clone()
+    }
+
+    /// Take the input's text after an edit: format it, and put the caret
+    /// after the edited characters.
+    pub fn edited(&self, typed: &str) {
+        let before = untracked(|| self.text.get());
+        let masked = self.mask.apply(typed, edit_end(&before, typed));
+        self.input.set_caret(masked.caret);
+        self.text.set(masked.text);
+    }
+}
+
+/// A masked field kept across renders, starting with `initial` formatted.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_masked_field(mask: Mask, initial: &str) -> MaskedField {
+    let text = use_signal(|| mask.apply(initial, 0).text);
+    MaskedField::new(mask, text, use_node_ref())
+}
+
+/// The markup for a masked input: an `input` with `class` and
+/// `placeholder`, showing `field`'s text and formatting each edit. This is
+/// what the `MaskedInput` component expands to.
+pub fn masked_input(field: &MaskedField, class: &str, placeholder: &str) -> Element {
+    let edited = field.clone();
+    let handler = register_input_handler(Box::new(move |typed: String| edited.edited(&typed)));
+    Element::Html(format!(
+        "<input class=\"{}\" placeholder=\"{}\" value=\"{}\" {NODE_REF_ATTR}=\"{}\" data-oninput=\"{}\">",
+        html_escape_string(class),
+        html_escape_string(placeholder),
+        html_escape_string(&field.text()),
+        field.input.id(),
+        handler,
+    ))
+}
+
+/// Where the caret is after editing `before` into `after`: at the end of
+/// what changed, in characters.
+fn edit_end(before: &str, after: &str) -> usize {
+    let before: Vec<char> = before.chars().collect();
+    let after: Vec<char> = after.chars().collect();
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    after.len() - suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::node_ref::{take_focus_requests, FocusRequest};
+
+    #[test]
+    fn the_caret_follows_the_edit() {
+        assert_eq!(edit_end("(555) 12", "(555) 123"), 9);
+        // Typed in the middle
+        assert_eq!(edit_end("(555) 123-4", "(5559) 123-4"), 5);
+        // Deleted in the middle
+        assert_eq!(edit_end("(555) 123-4", "(55) 123-4"), 3);
+        // Pasted over everything
+        assert_eq!(edit_end("12", "5551234"), 7);
+    }
+
+    #[test]
+    fn edits_are_formatted_and_keep_the_caret() {
+        let input = NodeRef::new();
+        let field = MaskedField::new(
+            Mask::phone(),
+            Signal::new("(555) 123".into()),
+            input.clone(),
+        );
+        take_focus_requests();
+
+        // A digit typed after the area code moves the rest along
+        field.edited("(5551) 123");
+        assert_eq!(field.text(), "(555) 112-3");
+        assert_eq!(field.value(), "5551123");
+        assert_eq!(
+            take_focus_requests(),
+            [(input.id(), FocusRequest::Caret(7))]
+        );
+    }
+}
@@ -0,0 +1,19 @@
+//! Building blocks shared by widgets.
+//!
+//! [`SelectionModel`] keeps which items of a list, grid or tree are
+//! selected, with the mouse and keyboard rules users expect from file
+//! managers and tables, so every list in an app selects the same way.
+//! [`Mask`] formats what's typed into an input, such as a phone number or
+//! an amount of money, keeping the caret in place, and [`MaskedField`]
+//! does so for an input as it's edited. [`Virtualizer`] works out which
+//! rows of a long list are scrolled into view, so only those are rendered.
+
+mod mask;
+mod masked_input;
+mod selection;
+mod virtual_list;
+
+pub use mask::{Mask, Masked};
+pub use masked_input::{masked_input, use_masked_field, MaskedField};
+pub use selection::{use_selection, SelectModifiers, SelectionMode, SelectionModel};
+pub use virtual_list::{use_virtualizer, virtual_list, RowHeight, VirtualRow, Virtualizer};
@@ -0,0 +1,350 @@
+//! Selection in lists, grids and trees.
+
+use rinch_core::{untracked, use_ref, Signal};
+
+/// How many items a [`SelectionModel`] can select at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    /// At most one item.
+    Single,
+    /// Any number of items, with Ctrl+click and Shift+click.
+    #[default]
+    Multiple,
+}
+
+/// The modifier keys held for a click or arrow key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SelectModifiers {
+    /// Ctrl (Cmd on macOS): add or remove one item, keeping the rest.
+    pub toggle: bool,
+    /// Shift: select the items between the anchor and this one.
+    pub range: bool,
+}
+
+impl SelectModifiers {
+    /// No modifiers: select just the item.
+    pub const NONE: Self = Self {
+        toggle: false,
+        range: false,
+    };
+    /// Ctrl or Cmd held.
+    pub const TOGGLE: Self = Self {
+        toggle: true,
+        range: false,
+    };
+    /// Shift held.
+    pub const RANGE: Self = Self {
+        toggle: false,
+        range: true,
+    };
+}
+
+#[derive(Clone, PartialEq)]
+struct Selection<K> {
+    /// Selected keys, in the order they were selected.
+    keys: Vec<K>,
+    /// Where Shift ranges start: the last item clicked without Shift.
+    anchor: Option<K>,
+    /// The item the keyboard moves from: the last item clicked or moved to.
+    lead: Option<K>,
+}
+
+/// Which items of a list, grid or tree are selected, with the usual mouse
+/// and keyboard rules: click selects one item, Ctrl+click toggles one,
+/// Shift+click selects the range from the anchor, and the arrow keys move
+/// from the last item touched.
+///
+/// Items are identified by a key, such as a row ID, so the selection
+/// survives sorting and filtering. Methods that need the items' order take
+/// the keys as they're shown. The getters are reactive, like a signal's.
+///
+/// ```ignore
+/// use rinch::components::{use_selection, SelectModifiers, SelectionMode};
+///
+/// fn file_list(files: Vec<String>) -> Element {
+///     let selection = use_selection::<String>(SelectionMode::Multiple);
+///     rsx! {
+///         for name in files.clone() {
+///             div {
+///                 class: if selection.is_selected(&name) { "row selected" } else { "row" },
+///                 onclick: {
+///                     let (selection, files, name) = (selection.clone(), files.clone(), name.clone());
+///                     move || selection.click(name.clone(), SelectModifiers::NONE, &files)
+///                 },
+///                 {name}
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub struct SelectionModel<K> {
+    mode: SelectionMode,
+    state: Signal<Selection<K>>,
+}
+
+impl<K> Clone for SelectionModel<K> {
+    fn clone(&self) -> Self {
+        Self {
+            mode: self.mode,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<K: Clone + PartialEq + 'static> SelectionModel<K> {
+    /// An empty selection.
+    pub fn new(mode: SelectionMode) -> Self {
+        Self {
+            mode,
+            state: Signal::new(Selection {
+                keys: Vec::new(),
+                anchor: None,
+                lead: None,
+            }),
+        }
+    }
+
+    /// How many items can be selected at once.
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    /// The selected keys, in the order they were selected.
+    pub fn selected(&self) -> Vec<K> {
+        self.state.with(|state| state.keys.clone())
+    }
+
+    /// Whether the item with this key is selected.
+    pub fn is_selected(&self, key: &K) -> bool {
+        self.state.with(|state| state.keys.contains(key))
+    }
+
+    /// How many items are selected.
+    pub fn len(&self) -> usize {
+        self.state.with(|state| state.keys.len())
+    }
+
+    /// Whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Where Shift ranges start.
+    pub fn anchor(&self) -> Option<K> {
+        self.state.with(|state| state.anchor.clone())
+    }
+
+    /// The item the arrow keys move from, to show as focused.
+    pub fn lead(&self) -> Option<K> {
+        self.state.with(|state| state.lead.clone())
+    }
+
+    /// Handle a click on the item with this key. `items` are the keys in
+    /// the order they're shown, for Shift ranges.
+    pub fn click(&self, key: K, modifiers: SelectModifiers, items: &[K]) {
+        self.update(|state| {
+            if modifiers.range
+                && self.mode == SelectionMode::Multiple
+                && let Some(range) = state
+                    .anchor
+                    .as_ref()
+                    .and_then(|anchor| range(items, anchor, &key))
+            {
+                if !modifiers.toggle {
+                    state.keys.clear();
+                }
+                for item in range {
+                    if !state.keys.contains(item) {
+                        state.keys.push(item.clone());
+                    }
+                }
+                state.lead = Some(key);
+                return;
+            }
+            if modifiers.toggle {
+                if let Some(index) = state.keys.iter().position(|selected| *selected == key) {
+                    state.keys.remove(index);
+                } else if self.mode == SelectionMode::Single {
+                    state.keys = vec![key.clone()];
+                } else {
+                    state.keys.push(key.clone());
+                }
+            } else {
+                state.keys = vec![key.clone()];
+            }
+            state.anchor = Some(key.clone());
+            state.lead = Some(key);
+        });
+    }
+
+    /// Handle an arrow key, moving `offset` items from the lead in `items`.
+    /// Offsets past either end stop there, so `isize::MIN` and `isize::MAX`
+    /// serve for Home and End.
+    ///
+    /// Shift extends the range from the anchor, Ctrl moves the lead
+    /// without changing the selection, and no modifier selects the item
+    /// moved to. With no lead, the first item is moved to.
+    pub fn move_by(&self, offset: isize, modifiers: SelectModifiers, items: &[K]) {
+        let lead = untracked(|| self.lead());
+        let index = match lead.and_then(|lead| items.iter().position(|item| *item == lead)) {
+            Some(index) => index.saturating_add_signed(offset).min(items.len() - 1),
+            None if items.is_empty() => return,
+            None => 0,
+        };
+        let key = items[index].clone();
+        if modifiers.toggle && !modifiers.range {
+            self.update(|state| state.lead = Some(key));
+        } else {
+            self.click(key, modifiers, items);
+        }
+    }
+
+    /// Select just the item with this key.
+    pub fn select(&self, key: K) {
+        self.click(key, SelectModifiers::NONE, &[]);
+    }
+
+    /// Add or remove the item with this key.
+    pub fn toggle(&self, key: K) {
+        self.click(key, SelectModifiers::TOGGLE, &[]);
+    }
+
+    /// Select every item, when several can be. The anchor and lead stay.
+    pub fn select_all(&self, items: &[K]) {
+        if self.mode == SelectionMode::Multiple {
+            self.update(|state| state.keys = items.to_vec());
+        }
+    }
+
+    /// Select nothing.
+    pub fn clear(&self) {
+        self.update(|state| {
+            state.keys.clear();
+            state.anchor = None;
+            state.lead = None;
+        });
+    }
+
+    /// Forget items that are no longer shown, such as after a delete or a
+    /// filter change.
+    pub fn retain(&self, items: &[K]) {
+        self.update(|state| {
+            state.keys.retain(|key| items.contains(key));
+            if state
+                .anchor
+                .as_ref()
+                .is_some_and(|anchor| !items.contains(anchor))
+            {
+                state.anchor = None;
+            }
+            if state
+                .lead
+                .as_ref()
+                .is_some_and(|lead| !items.contains(lead))
+            {
+                state.lead = None;
+            }
+        });
+    }
+
+    /// Change the selection, notifying subscribers only if it changed.
+    fn update(&self, change: impl FnOnce(&mut Selection<K>)) {
+        let mut next = untracked(|| self.state.get());
+        change(&mut next);
+        if untracked(|| self.state.with(|state| *state != next)) {
+            self.state.set(next);
+        }
+    }
+}
+
+/// The items from `from` to `to` in `items`, in either direction, if both
+/// are there.
+fn range<'a, K: PartialEq>(items: &'a [K], from: &K, to: &K) -> Option<&'a [K]> {
+    let from = items.iter().position(|item| item == from)?;
+    let to = items.iter().position(|item| item == to)?;
+    Some(&items[from.min(to)..=from.max(to)])
+}
+
+/// A selection kept across renders.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_selection<K: Clone + PartialEq + 'static>(mode: SelectionMode) -> SelectionModel<K> {
+    use_ref(|| SelectionModel::new(mode)).get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_follow_the_usual_modifier_rules() {
+        let items = [1, 2, 3, 4, 5];
+        let selection = SelectionModel::new(SelectionMode::Multiple);
+
+        selection.click(2, SelectModifiers::NONE, &items);
+        selection.click(4, SelectModifiers::RANGE, &items);
+        assert_eq!(selection.selected(), [2, 3, 4]);
+        assert_eq!(selection.anchor(), Some(2));
+
+        // Shift from the same anchor replaces the range
+        selection.click(1, SelectModifiers::RANGE, &items);
+        assert_eq!(selection.selected(), [1, 2]);
+
+        selection.click(5, SelectModifiers::TOGGLE, &items);
+        selection.click(1, SelectModifiers::TOGGLE, &items);
+        assert_eq!(selection.selected(), [2, 5]);
+        assert_eq!(selection.anchor(), Some(1));
+
+        // Ctrl+Shift adds the range to what's selected
+        let both = SelectModifiers {
+            toggle: true,
+            range: true,
+        };
+        selection.click(3, both, &items);
+        assert_eq!(selection.selected(), [2, 5, 1, 3]);
+
+        selection.select_all(&items);
+        assert_eq!(selection.len(), 5);
+        selection.retain(&[2, 4]);
+        assert_eq!(selection.selected(), [2, 4]);
+        assert_eq!(selection.anchor(), None);
+    }
+
+    #[test]
+    fn arrow_keys_move_from_the_lead() {
+        let items = ["a", "b", "c", "d"];
+        let selection = SelectionModel::new(SelectionMode::Multiple);
+
+        selection.move_by(1, SelectModifiers::NONE, &items);
+        assert_eq!(selection.selected(), ["a"]);
+        selection.move_by(1, SelectModifiers::RANGE, &items);
+        selection.move_by(1, SelectModifiers::RANGE, &items);
+        assert_eq!(selection.selected(), ["a", "b", "c"]);
+        assert_eq!(selection.lead(), Some("c"));
+
+        selection.move_by(-1, SelectModifiers::TOGGLE, &items);
+        assert_eq!(selection.lead(), Some("b"));
+        assert_eq!(selection.len(), 3);
+
+        selection.move_by(isize::MAX, SelectModifiers::NONE, &items);
+        assert_eq!(selection.selected(), ["d"]);
+        selection.move_by(isize::MIN, SelectModifiers::NONE, &items);
+        assert_eq!(selection.selected(), ["a"]);
+    }
+
+    #[test]
+    fn single_mode_keeps_one_item() {
+        let items = [1, 2, 3];
+        let selection = SelectionModel::new(SelectionMode::Single);
+
+        selection.click(1, SelectModifiers::NONE, &items);
+        selection.click(3, SelectModifiers::RANGE, &items);
+        assert_eq!(selection.selected(), [3]);
+        selection.toggle(2);
+        assert_eq!(selection.selected(), [2]);
+        selection.toggle(2);
+        assert!(selection.is_empty());
+        selection.select_all(&items);
+        assert!(selection.is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Lists that render only the rows in view.
+
+use std::ops::Range;
+use std::rc::Rc;
+
+use rinch_core::element::{children_to_html, Element};
+use rinch_core::events::html_escape_string;
+use rinch_core::node_ref::NODE_REF_ATTR;
+use rinch_core::{untracked, use_node_ref, NodeRef, ScrollAlignment};
+
+/// Rows rendered past each edge of the visible area, so a short scroll
+/// doesn't show blank space before the re-render.
+const DEFAULT_OVERSCAN: usize = 3;
+
+/// The height assumed for the list's visible area before its first layout.
+const INITIAL_VIEWPORT: f64 = 1000.0;
+
+/// How tall a [`Virtualizer`]'s rows are.
+#[derive(Clone)]
+pub enum RowHeight {
+    /// Every row is this tall, in logical pixels.
+    Fixed(f64),
+    /// Each row's height, by index.
+    Variable(Rc<dyn Fn(usize) -> f64>),
+}
+
+impl RowHeight {
+    /// Rows as tall as `height` says.
+    pub fn variable(height: impl Fn(usize) -> f64 + 'static) -> Self {
+        Self::Variable(Rc::new(height))
+    }
+}
+
+/// A row to render: its index, and where it sits in the list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualRow {
+    /// The row's index.
+    pub index: usize,
+    /// The row's top edge, from the top of the list's content.
+    pub start: f64,
+    /// The row's height.
+    pub height: f64,
+}
+
+/// Where rows are, worked out once per render.
+#[derive(Clone)]
+enum Rows {
+    Fixed(f64),
+    /// Where each row starts, and where the last one ends.
+    Variable(Rc<[f64]>),
+}
+
+/// Which rows of a long list are in view, for rendering only those.
+///
+/// A list of 10,000 rows builds 10,000 elements, and laying them out gets
+/// slow. A virtualizer watches the scroll container the list is in and
+/// works out which rows are visible; render those, in a block as tall as
+/// every row together, and the scrollbar behaves as if they were all
+/// there. Use [`virtual_list`] (or the `VirtualList` component) for the
+/// markup, or build it from [`visible_rows`](Self::visible_rows).
+///
+/// The getters that depend on scrolling are reactive, so the component
+/// re-renders with the new rows as the list scrolls.
+///
+/// ```ignore
+/// use rinch::components::{use_virtualizer, RowHeight};
+///
+/// fn log_view(lines: Vec<String>) -> Element {
+///     let list = use_virtualizer(lines.len(), RowHeight::Fixed(24.0));
+///     rsx! {
+///         VirtualList {
+///             virtualizer: list,
+///             style: "height: 400px",
+///             row: move |i| rsx! { div { class: "line", {lines[i].clone()} } },
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Virtualizer {
+    container: NodeRef,
+    count: usize,
+    rows: Rows,
+    overscan: usize,
+}
+
+impl Virtualizer {
+    /// A virtualizer for `count` rows in the scroll container `container`.
+    pub fn new(container: NodeRef, count: usize, row_height: RowHeight) -> Self {
+        let rows = match row_height {
+            RowHeight::Fixed(height) => Rows::Fixed(height.max(0.0)),
+            RowHeight::Variable(height) => {
+                let mut offsets = Vec::with_capacity(count + 1);
+                let mut end = 0.0;
+                offsets.push(end);
+                for index in 0..count {
+                    end += height(index).max(0.0);
+                    offsets.push(end);
+                }
+                Rows::Variable(offsets.into())
+            }
+        };
+        Self {
+            container,
+            count,
+            rows,
+            overscan: DEFAULT_OVERSCAN,
+        }
+    }
+
+    /// Render `rows` extra rows past each edge of the visible area (3 by
+    /// default).
+    pub fn overscan(mut self, rows: usize) -> Self {
+        self.overscan = rows;
+        self
+    }
+
+    /// The scroll container's ref, for its `node_ref` prop.
+    pub fn container(&self) -> &NodeRef {
+        &self.container
+    }
+
+    /// How many rows the list has.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The height of every row together.
+    pub fn total_height(&self) -> f64 {
+        self.start(self.count)
+    }
+
+    /// Where the row at `index` is, if there is one.
+    pub fn row(&self, index: usize) -> Option<VirtualRow> {
+        (index < self.count).then(|| VirtualRow {
+            index,
+            start: self.start(index),
+            height: self.start(index + 1) - self.start(index),
+        })
+    }
+
+    /// How far the list is scrolled, in logical pixels. Reactive.
+    pub fn scroll_offset(&self) -> f64 {
+        self.container.scroll_offset().1
+    }
+
+    /// The indexes of the rows to render: those in view, and the overscan
+    /// around them. Reactive.
+    pub fn range(&self) -> Range<usize> {
+        let scroll = self.container.scroll_state();
+        let viewport = if scroll.client_height > 0.0 {
+            scroll.client_height
+        } else {
+            INITIAL_VIEWPORT
+        };
+        self.range_in(scroll.scroll_y, viewport)
+    }
+
+    /// The rows to render, in order. Reactive.
+    pub fn visible_rows(&self) -> Vec<VirtualRow> {
+        self.range().filter_map(|index| self.row(index)).collect()
+    }
+
+    /// Scroll the list so the row at `index` is in view, aligned as
+    /// `align` says. Like [`NodeRef::scroll_to`], this takes effect after
+    /// the current event has been handled.
+    pub fn scroll_to_index(&self, index: usize, align: ScrollAlignment, animated: bool) {
+        let Some(row) = self.row(index.min(self.count.saturating_sub(1))) else {
+            return;
+        };
+        let scroll = untracked(|| self.container.scroll_state());
+        let y = scroll_target(row, align, scroll.scroll_y, scroll.client_height);
+        self.container.scroll_to(scroll.scroll_x, y, animated);
+    }
+
+    /// Where the row at `index` starts, or the list ends for `count`.
+    fn start(&self, index: usize) -> f64 {
+        match &self.rows {
+            Rows::Fixed(height) => height * index as f64,
+            Rows::Variable(offsets) => offsets[index.min(self.count)],
+        }
+    }
+
+    /// The row at `y`, or the last row past the end.
+    fn index_at(&self, y: f64) -> usize {
+        let index = match &self.rows {
+            Rows::Fixed(height) if *height > 0.0 => (y.max(0.0) / height) as usize,
+            Rows::Fixed(_) => 0,
+            Rows::Variable(offsets) => offsets
+                .partition_point(|&start| start <= y)
+                .saturating_sub(1),
+        };
+        index.min(self.count.saturating_sub(1))
+    }
+
+    /// The rows to render when `viewport` pixels are visible from `top`.
+    fn range_in(&self, top: f64, viewport: f64) -> Range<usize> {
+        if self.count == 0 {
+            return 0..0;
+        }
+        let first = self.index_at(top);
+        let last = self.index_at(top + viewport);
+        first.saturating_sub(self.overscan)..(last + 1 + self.overscan).min(self.count)
+    }
+}
+
+/// The scroll offset that shows `row` aligned as `align` says, in a
+/// `viewport` pixels tall visible area now scrolled to `top`.
+fn scroll_target(row: VirtualRow, align: ScrollAlignment, top: f64, viewport: f64) -> f64 {
+    let end = row.start + row.height;
+    let y = match align {
+        ScrollAlignment::Start => row.start,
+        ScrollAlignment::Center => row.start + (row.height - viewport) / 2.0,
+        ScrollAlignment::End => end - viewport,
+        ScrollAlignment::Nearest if row.start < top => row.start,
+        ScrollAlignment::Nearest if end > top + viewport => end - viewport,
+        ScrollAlignment::Nearest => top,
+    };
+    y.max(0.0)
+}
+
+/// A virtualizer for `count` rows, with a ref for the scroll container
+/// kept across renders.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_virtualizer(count: usize, row_height: RowHeight) -> Virtualizer {
+    Virtualizer::new(use_node_ref(), count, row_height)
+}
+
+/// The markup for a virtualized list: a scroll container with `class` and
+/// `style`, holding the rows in view, each rendered by `row` and placed
+/// where it belongs. This is what the `VirtualList` component expands to.
+///
+/// Give the container a height, or a parent that limits it, so it scrolls.
+pub fn virtual_list(
+    virtualizer: &Virtualizer,
+    class: &str,
+    style: &str,
+    row: impl Fn(usize) -> Element,
+) -> Element {
+    let mut html = format!(
+        "<div class=\"{}\" style=\"overflow-y: auto; {}\" {NODE_REF_ATTR}=\"{}\">",
+        html_escape_string(class),
+        html_escape_string(style),
+        virtualizer.container().id(),
+    );
+    html.push_str(&format!(
+        "<div style=\"position: relative; height: {}px\">",
+        virtualizer.total_height()
+    ));
+    for item in virtualizer.visible_rows() {
+        html.push_str(&format!(
+            "<div data-index=\"{}\" style=\"position: absolute; top: {}px; left: 0; right: 0; height: {}px\">",
+            item.index, item.start, item.height
+        ));
+        html.push_str(&children_to_html(&[row(item.index)]));
+        html.push_str("</div>");
+    }
+    html.push_str("</div></div>");
+    Element::Html(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::node_ref::update_node_ref_scroll;
+    use rinch_core::ScrollEvent;
+
+    #[test]
+    fn only_rows_in_view_are_rendered() {
+        let list = Virtualizer::new(NodeRef::new(), 10_000, RowHeight::Fixed(20.0)).overscan(2);
+        assert_eq!(list.total_height(), 200_000.0);
+        assert_eq!(list.range_in(0.0, 100.0), 0..8);
+        assert_eq!(list.range_in(1_010.0, 100.0), 48..58);
+        assert_eq!(list.range_in(199_950.0, 100.0), 9_995..10_000);
+
+        let scroll = ScrollEvent {
+            scroll_y: 400.0,
+            client_height: 60.0,
+            ..Default::default()
+        };
+        update_node_ref_scroll(list.container().id(), scroll);
+        assert_eq!(list.range(), 18..26);
+        assert_eq!(list.scroll_offset(), 400.0);
+
+        let empty = Virtualizer::new(NodeRef::new(), 0, RowHeight::Fixed(20.0));
+        assert_eq!(empty.range_in(0.0, 100.0), 0..0);
+    }
+
+    #[test]
+    fn variable_rows_are_placed_by_their_heights() {
+        let heights = [10.0, 30.0, 20.0, 40.0];
+        let list = Virtualizer::new(
+            NodeRef::new(),
+            heights.len(),
+            RowHeight::variable(move |i| heights[i]),
+        )
+        .overscan(0);
+        assert_eq!(list.total_height(), 100.0);
+        assert_eq!(
+            list.row(2),
+            Some(VirtualRow {
+                index: 2,
+                start: 40.0,
+                height: 20.0,
+            })
+        );
+        assert_eq!(list.row(4), None);
+        assert_eq!(list.range_in(15.0, 30.0), 1..3);
+        assert_eq!(list.range_in(95.0, 30.0), 3..4);
+    }
+
+    #[test]
+    fn rows_scroll_into_alignment() {
+        let row = VirtualRow {
+            index: 10,
+            start: 200.0,
+            height: 20.0,
+        };
+        assert_eq!(
+            scroll_target(row, ScrollAlignment::Start, 0.0, 100.0),
+            200.0
+        );
+        assert_eq!(
+            scroll_target(row, ScrollAlignment::Center, 0.0, 100.0),
+            160.0
+        );
+        assert_eq!(scroll_target(row, ScrollAlignment::End, 0.0, 100.0), 120.0);
+        // Nearest scrolls as little as it can, and not at all for a row in view
+        assert_eq!(
+            scroll_target(row, ScrollAlignment::Nearest, 0.0, 100.0),
+            120.0
+        );
+        assert_eq!(
+            scroll_target(row, ScrollAlignment::Nearest, 300.0, 100.0),
+            200.0
+        );
+        assert_eq!(
+            scroll_target(row, ScrollAlignment::Nearest, 150.0, 100.0),
+            150.0
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Frame timing traces.
+//!
+//! The work behind each frame is instrumented with `tracing` spans at debug
+//! level:
+//!
+//! | Span | What it times |
+//! |------|---------------|
+//! | `rerender` | A re-render, around the spans below for each window |
+//! | `reactive_flush` | Re-running the app function after signals changed |
+//! | `update_content` | Turning a window's new HTML into its document |
+//! | `build_document` | Parsing the HTML and building a new document |
+//! | `frame` | Redrawing a window |
+//! | `style_layout` | Blitz's style and layout pass (one pass in blitz) |
+//! | `render` | Rendering the scene on the GPU and presenting it |
+//! | `scene` | Encoding the scene, inside `render` |
+//!
+//! Window spans carry the window's `window` id and its document's `nodes`.
+//! Any `tracing` subscriber sees them. To find where a janky frame's time
+//! went, [`start_trace`] writes them to a file in the Chrome trace format,
+//! for chrome://tracing or <https://ui.perfetto.dev>. Setting
+//! `RINCH_TRACE=path` traces a whole run.
+//!
+//! # Example
+//!
+//! ```ignore
+//! MenuItem {
+//!     label: "Record Trace",
+//!     onclick: || rinch::debug::start_trace("rinch-trace.json").unwrap(),
+//! }
+//! MenuItem {
+//!     label: "Stop Trace",
+//!     onclick: || rinch::debug::stop_trace().unwrap(),
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::fmt::{Debug, Write as _};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use blitz_dom::BaseDocument;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The trace being written, if any.
+static TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+/// Whether a trace is being written, checked before recording spans.
+static TRACING: AtomicBool = AtomicBool::new(false);
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// This thread's ID in traces.
+    static THREAD: Cell<u64> = Cell::new(NEXT_THREAD.fetch_add(1, Ordering::Relaxed));
+}
+
+struct Trace {
+    out: BufWriter<File>,
+    start: Instant,
+    events: usize,
+}
+
+/// Start writing frame spans to `path` as a Chrome trace, replacing any
+/// trace already being written.
+///
+/// The file is valid to open while the trace runs; [`stop_trace`] finishes
+/// it. Traces need rinch's `tracing` subscriber, which `run` installs
+/// unless the app set its own; add [`trace_layer`] to that one instead.
+pub fn start_trace(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(b"[\n")?;
+    let trace = Trace {
+        out,
+        start: Instant::now(),
+        events: 0,
+    };
+    if let Some(old) = lock_trace().replace(trace) {
+        finish(old)?;
+    }
+    TRACING.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Finish the trace being written, if any.
+pub fn stop_trace() -> io::Result<()> {
+    TRACING.store(false, Ordering::Relaxed);
+    match lock_trace().take() {
+        Some(trace) => finish(trace),
+        None => Ok(()),
+    }
+}
+
+fn finish(mut trace: Trace) -> io::Result<()> {
+    trace.out.write_all(b"\n]\n")?;
+    trace.out.flush()
+}
+
+fn lock_trace() -> std::sync::MutexGuard<'static, Option<Trace>> {
+    TRACE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The layer that records spans for [`start_trace`], for apps that install
+/// their own `tracing` subscriber.
+///
+/// It only looks at rinch's spans, and only while a trace is running.
+pub fn trace_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let filter = filter_fn(|metadata| {
+        TRACING.load(Ordering::Relaxed)
+            && metadata.is_span()
+            && metadata.target().starts_with("rinch")
+    })
+    .with_max_level_hint(Level::DEBUG);
+    TraceLayer.with_filter(filter)
+}
+
+/// Records closed spans in the running trace; see [`trace_layer`].
+#[derive(Debug)]
+pub struct TraceLayer;
+
+/// When a span was first entered.
+struct Entered(Instant);
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut args = Args::default();
+            attrs.record(&mut args);
+            span.extensions_mut().insert(args);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(args) = span.extensions_mut().get_mut::<Args>()
+        {
+            values.record(args);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if extensions.get_mut::<Entered>().is_none() {
+                extensions.insert(Entered(Instant::now()));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let end = Instant::now();
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let (Some(Entered(entered)), Some(args)) = (extensions.get(), extensions.get::<Args>())
+        else {
+            return;
+        };
+        let mut trace = lock_trace();
+        let Some(trace) = trace.as_mut() else {
+            return;
+        };
+        // Spans entered before the trace started are left out
+        let Some(start) = entered.checked_duration_since(trace.start) else {
+            return;
+        };
+        let event = event_json(
+            span.name(),
+            THREAD.with(Cell::get),
+            start.as_secs_f64() * 1e6,
+            (end - *entered).as_secs_f64() * 1e6,
+            args,
+        );
+        let separator = if trace.events == 0 { "" } else { ",\n" };
+        trace.events += 1;
+        let mut written = write!(trace.out, "{separator}{event}");
+        // Flush whole frames, so the file stays useful if the app dies
+        if span.parent().is_none() && written.is_ok() {
+            written = trace.out.flush();
+        }
+        if let Err(e) = written {
+            tracing::warn!("Stopped writing the trace: {e}");
+            TRACING.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A span's fields, as JSON values.
+#[derive(Debug, Default)]
+struct Args(Vec<(&'static str, String)>);
+
+impl Args {
+    fn set(&mut self, field: &Field, json: String) {
+        match self.0.iter_mut().find(|(name, _)| *name == field.name()) {
+            Some((_, value)) => *value = json,
+            None => self.0.push((field.name(), json)),
+        }
+    }
+}
+
+impl Visit for Args {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(field, value.to_string());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, value.to_string());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if value.is_finite() {
+            self.set(field, value.to_string());
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, value.to_string());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, json_string(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.set(field, json_string(&format!("{value:?}")));
+    }
+}
+
+/// A Chrome trace "complete" event, with times in microseconds.
+fn event_json(name: &str, thread: u64, start: f64, duration: f64, args: &Args) -> String {
+    let mut json = format!(
+        r#"{{"name":{},"cat":"rinch","ph":"X","pid":1,"tid":{thread},"ts":{start:.3},"dur":{duration:.3},"args":{{"#,
+        json_string(name)
+    );
+    for (i, (name, value)) in args.0.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(json, "{separator}{}:{value}", json_string(name));
+    }
+    json.push_str("}}");
+    json
+}
+
+/// `s` as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c < ' ' => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// How many nodes a document has, for spans' `nodes` field.
+pub(crate) fn node_count(doc: &BaseDocument) -> usize {
+    let mut count = 0;
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop().and_then(|id| doc.get_node(id)) {
+        count += 1;
+        stack.extend(node.children.iter().copied());
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(json_string(r#"say "hi"\"#), r#""say \"hi\"\\""#);
+        assert_eq!(json_string("a\nb\u{1}"), r#""a\nb\u0001""#);
+    }
+
+    #[test]
+    fn spans_become_complete_events() {
+        let args = Args(vec![
+            ("window", json_string("WindowId(1)")),
+            ("nodes", "42".into()),
+        ]);
+        assert_eq!(
+            event_json("frame", 2, 1500.0, 16.25, &args),
+            r#"{"name":"frame","cat":"rinch","ph":"X","pid":1,"tid":2,"ts":1500.000,"dur":16.250,"args":{"window":"WindowId(1)","nodes":42}}"#
+        );
+    }
+}
@@ -2,6 +2,14 @@
 //!
 //! This module provides cross-platform file dialog support using the `rfd` crate.
 //!
+//! Every dialog can be shown two ways. The blocking methods (`pick_file`,
+//! `save`, `pick`, ...) wait for the user to answer, and the event loop
+//! with them, so the app doesn't redraw while the dialog is open. Their
+//! `_async` counterparts return a future instead: await it in a
+//! [`spawn_local`](crate::prelude::spawn_local) task and the app keeps
+//! running. Either way, the dialog is modal to the current window (the one
+//! handling the event, or else the focused one).
+//!
 //! # Example
 //!
 //! ```ignore
@@ -29,14 +37,104 @@
 //! if let Some(path) = pick_folder().pick() {
 //!     println!("Folder: {}", path.display());
 //! }
+//!
+//! // Open a file without blocking the event loop
+//! let opened = use_signal(|| None::<PathBuf>);
+//! let on_open = move || {
+//!     let opened = opened.clone();
+//!     spawn_local(async move {
+//!         if let Some(path) = open_file().pick_file_async().await {
+//!             opened.set(Some(path));
+//!         }
+//!     });
+//! };
 //! ```
 
-use rfd::{FileDialog, MessageDialog, MessageButtons};
+use rfd::{
+    AsyncFileDialog, AsyncMessageDialog, FileDialog, FileHandle, MessageButtons, MessageDialog,
+    MessageDialogResult,
+};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use winit::window::Window;
+
+use crate::windows::current_native_window;
 
 // Re-export MessageLevel for convenience
 pub use rfd::MessageLevel;
 
+/// What a file dialog was set up with, to build either kind of rfd dialog.
+#[derive(Default)]
+struct FileOptions {
+    title: Option<String>,
+    directory: Option<PathBuf>,
+    file_name: Option<String>,
+    filters: Vec<(String, Vec<String>)>,
+}
+
+impl FileOptions {
+    fn add_filter(&mut self, name: impl Into<String>, extensions: &[&str]) {
+        let extensions = extensions.iter().map(|e| e.to_string()).collect();
+        self.filters.push((name.into(), extensions));
+    }
+
+    /// A blocking dialog, modal to the current window.
+    fn blocking(self) -> FileDialog {
+        let mut dialog = FileDialog::new();
+        if let Some(window) = current_native_window() {
+            dialog = dialog.set_parent(&*window);
+        }
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(directory) = self.directory {
+            dialog = dialog.set_directory(directory);
+        }
+        if let Some(file_name) = self.file_name {
+            dialog = dialog.set_file_name(file_name);
+        }
+        for (name, extensions) in &self.filters {
+            dialog = dialog.add_filter(name, extensions.as_slice());
+        }
+        dialog
+    }
+
+    /// An async dialog, modal to the current window, and that window, kept
+    /// alive until the dialog closes.
+    fn asynchronous(self) -> (AsyncFileDialog, Option<Arc<Window>>) {
+        let mut dialog = AsyncFileDialog::new();
+        let parent = current_native_window();
+        if let Some(window) = &parent {
+            dialog = dialog.set_parent(&**window);
+        }
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(directory) = self.directory {
+            dialog = dialog.set_directory(directory);
+        }
+        if let Some(file_name) = self.file_name {
+            dialog = dialog.set_file_name(file_name);
+        }
+        for (name, extensions) in &self.filters {
+            dialog = dialog.add_filter(name, extensions.as_slice());
+        }
+        (dialog, parent)
+    }
+}
+
+/// Await an async dialog's answer, holding on to its parent window.
+async fn answer<T>(dialog: impl Future<Output = T>, parent: Option<Arc<Window>>) -> T {
+    let answer = dialog.await;
+    drop(parent);
+    answer
+}
+
+fn path_of(file: FileHandle) -> PathBuf {
+    file.path().to_path_buf()
+}
+
 /// Builder for opening files.
 ///
 /// # Example
@@ -51,43 +149,61 @@ pub use rfd::MessageLevel;
 ///     .pick_file();
 /// ```
 pub struct OpenFileDialog {
-    dialog: FileDialog,
+    options: FileOptions,
 }
 
 impl OpenFileDialog {
     /// Create a new open file dialog.
     pub fn new() -> Self {
         Self {
-            dialog: FileDialog::new(),
+            options: FileOptions::default(),
         }
     }
 
     /// Set the dialog title.
     pub fn set_title(mut self, title: impl Into<String>) -> Self {
-        self.dialog = self.dialog.set_title(title);
+        self.options.title = Some(title.into());
         self
     }
 
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
-        self.dialog = self.dialog.set_directory(path);
+        self.options.directory = Some(path.as_ref().to_path_buf());
         self
     }
 
     /// Add a file filter (e.g., "Images", &["png", "jpg"]).
     pub fn add_filter(mut self, name: impl Into<String>, extensions: &[&str]) -> Self {
-        self.dialog = self.dialog.add_filter(name, extensions);
+        self.options.add_filter(name, extensions);
         self
     }
 
     /// Show the dialog and pick a single file.
     pub fn pick_file(self) -> Option<PathBuf> {
-        self.dialog.pick_file()
+        self.options.blocking().pick_file()
     }
 
     /// Show the dialog and pick multiple files.
     pub fn pick_files(self) -> Option<Vec<PathBuf>> {
-        self.dialog.pick_files()
+        self.options.blocking().pick_files()
+    }
+
+    /// Show the dialog without blocking, resolving to the picked file.
+    pub fn pick_file_async(self) -> impl Future<Output = Option<PathBuf>> {
+        let (dialog, parent) = self.options.asynchronous();
+        let picked = answer(dialog.pick_file(), parent);
+        async move { picked.await.map(path_of) }
+    }
+
+    /// Show the dialog without blocking, resolving to the picked files.
+    pub fn pick_files_async(self) -> impl Future<Output = Option<Vec<PathBuf>>> {
+        let (dialog, parent) = self.options.asynchronous();
+        let picked = answer(dialog.pick_files(), parent);
+        async move {
+            picked
+                .await
+                .map(|files| files.into_iter().map(path_of).collect())
+        }
     }
 }
 
@@ -111,44 +227,51 @@ impl Default for OpenFileDialog {
 ///     .save();
 /// ```
 pub struct SaveFileDialog {
-    dialog: FileDialog,
+    options: FileOptions,
 }
 
 impl SaveFileDialog {
     /// Create a new save file dialog.
     pub fn new() -> Self {
         Self {
-            dialog: FileDialog::new(),
+            options: FileOptions::default(),
         }
     }
 
     /// Set the dialog title.
     pub fn set_title(mut self, title: impl Into<String>) -> Self {
-        self.dialog = self.dialog.set_title(title);
+        self.options.title = Some(title.into());
         self
     }
 
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
-        self.dialog = self.dialog.set_directory(path);
+        self.options.directory = Some(path.as_ref().to_path_buf());
         self
     }
 
     /// Set the default file name.
     pub fn set_file_name(mut self, name: impl Into<String>) -> Self {
-        self.dialog = self.dialog.set_file_name(name);
+        self.options.file_name = Some(name.into());
         self
     }
 
     /// Add a file filter (e.g., "Text Files", &["txt"]).
     pub fn add_filter(mut self, name: impl Into<String>, extensions: &[&str]) -> Self {
-        self.dialog = self.dialog.add_filter(name, extensions);
+        self.options.add_filter(name, extensions);
         self
     }
 
     /// Show the dialog and get the save path.
     pub fn save(self) -> Option<PathBuf> {
-        self.dialog.save_file()
+        self.options.blocking().save_file()
+    }
+
+    /// Show the dialog without blocking, resolving to the save path.
+    pub fn save_async(self) -> impl Future<Output = Option<PathBuf>> {
+        let (dialog, parent) = self.options.asynchronous();
+        let picked = answer(dialog.save_file(), parent);
+        async move { picked.await.map(path_of) }
     }
 }
 
@@ -171,37 +294,55 @@ impl Default for SaveFileDialog {
 ///     .pick();
 /// ```
 pub struct FolderDialog {
-    dialog: FileDialog,
+    options: FileOptions,
 }
 
 impl FolderDialog {
     /// Create a new folder picker dialog.
     pub fn new() -> Self {
         Self {
-            dialog: FileDialog::new(),
+            options: FileOptions::default(),
         }
     }
 
     /// Set the dialog title.
     pub fn set_title(mut self, title: impl Into<String>) -> Self {
-        self.dialog = self.dialog.set_title(title);
+        self.options.title = Some(title.into());
         self
     }
 
     /// Set the starting directory.
     pub fn set_directory(mut self, path: impl AsRef<Path>) -> Self {
-        self.dialog = self.dialog.set_directory(path);
+        self.options.directory = Some(path.as_ref().to_path_buf());
         self
     }
 
     /// Show the dialog and pick a folder.
     pub fn pick(self) -> Option<PathBuf> {
-        self.dialog.pick_folder()
+        self.options.blocking().pick_folder()
     }
 
     /// Show the dialog and pick multiple folders.
     pub fn pick_multiple(self) -> Option<Vec<PathBuf>> {
-        self.dialog.pick_folders()
+        self.options.blocking().pick_folders()
+    }
+
+    /// Show the dialog without blocking, resolving to the picked folder.
+    pub fn pick_async(self) -> impl Future<Output = Option<PathBuf>> {
+        let (dialog, parent) = self.options.asynchronous();
+        let picked = answer(dialog.pick_folder(), parent);
+        async move { picked.await.map(path_of) }
+    }
+
+    /// Show the dialog without blocking, resolving to the picked folders.
+    pub fn pick_multiple_async(self) -> impl Future<Output = Option<Vec<PathBuf>>> {
+        let (dialog, parent) = self.options.asynchronous();
+        let picked = answer(dialog.pick_folders(), parent);
+        async move {
+            picked
+                .await
+                .map(|folders| folders.into_iter().map(path_of).collect())
+        }
     }
 }
 
@@ -230,48 +371,97 @@ impl Default for FolderDialog {
 ///     .confirm();
 /// ```
 pub struct MessageDialogBuilder {
-    dialog: MessageDialog,
+    message: String,
+    title: Option<String>,
+    level: MessageLevel,
 }
 
 impl MessageDialogBuilder {
     /// Create a new message dialog with the given message.
     pub fn new(message: impl Into<String>) -> Self {
         Self {
-            dialog: MessageDialog::new().set_description(message),
+            message: message.into(),
+            title: None,
+            level: MessageLevel::Info,
         }
     }
 
     /// Set the dialog title.
     pub fn set_title(mut self, title: impl Into<String>) -> Self {
-        self.dialog = self.dialog.set_title(title);
+        self.title = Some(title.into());
         self
     }
 
     /// Set the message level (Info, Warning, Error).
     pub fn set_level(mut self, level: MessageLevel) -> Self {
-        self.dialog = self.dialog.set_level(level);
+        self.level = level;
         self
     }
 
     /// Show an OK button only.
     pub fn show(self) {
-        self.dialog.set_buttons(MessageButtons::Ok).show();
+        self.blocking(MessageButtons::Ok).show();
     }
 
     /// Show OK/Cancel buttons and return whether OK was clicked.
     pub fn confirm(self) -> bool {
-        self.dialog
-            .set_buttons(MessageButtons::OkCancel)
-            .show()
-            == rfd::MessageDialogResult::Ok
+        self.blocking(MessageButtons::OkCancel).show() == MessageDialogResult::Ok
     }
 
     /// Show Yes/No buttons and return whether Yes was clicked.
     pub fn ask(self) -> bool {
-        self.dialog
-            .set_buttons(MessageButtons::YesNo)
-            .show()
-            == rfd::MessageDialogResult::Yes
+        self.blocking(MessageButtons::YesNo).show() == MessageDialogResult::Yes
+    }
+
+    /// Like [`show`](Self::show), without blocking.
+    pub fn show_async(self) -> impl Future<Output = ()> {
+        let shown = self.asynchronous(MessageButtons::Ok);
+        async move {
+            shown.await;
+        }
+    }
+
+    /// Like [`confirm`](Self::confirm), without blocking.
+    pub fn confirm_async(self) -> impl Future<Output = bool> {
+        let shown = self.asynchronous(MessageButtons::OkCancel);
+        async move { shown.await == MessageDialogResult::Ok }
+    }
+
+    /// Like [`ask`](Self::ask), without blocking.
+    pub fn ask_async(self) -> impl Future<Output = bool> {
+        let shown = self.asynchronous(MessageButtons::YesNo);
+        async move { shown.await == MessageDialogResult::Yes }
+    }
+
+    /// A blocking dialog with `buttons`, modal to the current window.
+    fn blocking(self, buttons: MessageButtons) -> MessageDialog {
+        let mut dialog = MessageDialog::new()
+            .set_description(self.message)
+            .set_level(self.level)
+            .set_buttons(buttons);
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(window) = current_native_window() {
+            dialog = dialog.set_parent(&*window);
+        }
+        dialog
+    }
+
+    /// Show an async dialog with `buttons`, modal to the current window.
+    fn asynchronous(self, buttons: MessageButtons) -> impl Future<Output = MessageDialogResult> {
+        let mut dialog = AsyncMessageDialog::new()
+            .set_description(self.message)
+            .set_level(self.level)
+            .set_buttons(buttons);
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        let parent = current_native_window();
+        if let Some(window) = &parent {
+            dialog = dialog.set_parent(&**window);
+        }
+        answer(dialog.show(), parent)
     }
 }
 
@@ -326,6 +516,30 @@ pub fn pick_folder() -> FolderDialog {
     FolderDialog::new()
 }
 
+/// Show an open file dialog without blocking, resolving to the picked
+/// file. Shorthand for `open_file().pick_file_async()`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::dialogs::open_file_dialog;
+///
+/// spawn_local(async move {
+///     if let Some(path) = open_file_dialog().await {
+///         contents.set(std::fs::read_to_string(path).unwrap_or_default());
+///     }
+/// });
+/// ```
+pub fn open_file_dialog() -> impl Future<Output = Option<PathBuf>> {
+    open_file().pick_file_async()
+}
+
+/// Show a save file dialog without blocking, resolving to the save path.
+/// Shorthand for `save_file().save_async()`.
+pub fn save_file_dialog() -> impl Future<Output = Option<PathBuf>> {
+    save_file().save_async()
+}
+
 /// Create a message dialog builder.
 ///
 /// # Example
@@ -0,0 +1,401 @@
+//! Loading data over HTTP.
+//!
+//! [`use_fetch`] GETs a URL in the background and re-renders the app as the
+//! request goes from loading to a response or an error:
+//!
+//! ```ignore
+//! use rinch::fetch::use_fetch;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Issue {
+//!     title: String,
+//! }
+//!
+//! fn issues() -> Element {
+//!     let page = use_signal(|| 1);
+//!     let issues = use_fetch(|| format!("https://example.com/issues?page={}", page.get()));
+//!     let status = match issues.json::<Vec<Issue>>() {
+//!         None => "Loading...".to_string(),
+//!         Some(Ok(issues)) => format!("{} issues", issues.len()),
+//!         Some(Err(e)) => format!("Couldn't load issues: {e}"),
+//!     };
+//!     rsx! {
+//!         p { {status} }
+//!         button { onclick: move || page.update(|page| *page += 1), "Next page" }
+//!     }
+//! }
+//! ```
+//!
+//! - The URL is rebuilt every render; when it changes (here, when `page`
+//!   does), the request for the old one is cancelled and the new one sent.
+//!   The last result stays until the new one arrives, so lists don't flash
+//!   empty; [`Fetch::loading`] says whether one is on its way.
+//! - Responses outside 200-299 are errors ([`FetchError::Status`]), with the
+//!   response kept for its body.
+//! - A request still running when its component goes away is cancelled.
+//!
+//! Requests run on a background tokio runtime and their results are
+//! handed to the event loop, so the app never blocks on the network.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+use std::sync::{Mutex, OnceLock};
+
+use rinch_core::{mark_pending, untracked, use_ref, Signal};
+use serde::de::DeserializeOwned;
+use tokio::task::AbortHandle;
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::RinchEvent;
+
+/// An HTTP response, read in full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// The URL requested.
+    pub url: String,
+    /// The status code, such as 200.
+    pub status: u16,
+    /// The headers, in the order received.
+    pub headers: Vec<(String, String)>,
+    /// The body.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// The value of the header `name` (compared case-insensitively).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body as text, with invalid UTF-8 replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// The body decoded from JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, FetchError> {
+        serde_json::from_slice(&self.body).map_err(|e| FetchError::Decode(e.to_string()))
+    }
+}
+
+/// Why a fetch failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The request couldn't be made or its response read, such as when the
+    /// server can't be reached.
+    Request(String),
+    /// The server answered with a status outside 200-299.
+    Status(Response),
+    /// The body couldn't be decoded.
+    Decode(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "request failed: {e}"),
+            Self::Status(response) => {
+                write!(f, "{} returned HTTP {}", response.url, response.status)
+            }
+            Self::Decode(e) => write!(f, "couldn't decode the response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+type FetchResult = Result<Response, FetchError>;
+
+thread_local! {
+    /// Requests in flight, by ID.
+    static PENDING: RefCell<HashMap<u64, Weak<FetchState>>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Finished requests waiting for the event loop.
+static FINISHED: Mutex<Vec<(u64, FetchResult)>> = Mutex::new(Vec::new());
+
+/// A request made by [`use_fetch`]. Cheap to clone; clones share it.
+#[derive(Clone)]
+pub struct Fetch {
+    state: Rc<FetchState>,
+}
+
+impl Fetch {
+    /// Whether a request is on its way.
+    pub fn loading(&self) -> bool {
+        self.state.loading.get()
+    }
+
+    /// The latest result, or `None` before the first one arrives.
+    pub fn result(&self) -> Option<FetchResult> {
+        self.state.result.get()
+    }
+
+    /// The latest response, if the latest request succeeded.
+    pub fn data(&self) -> Option<Response> {
+        self.result()?.ok()
+    }
+
+    /// Why the latest request failed, if it did.
+    pub fn error(&self) -> Option<FetchError> {
+        self.result()?.err()
+    }
+
+    /// The latest response as text, if the latest request succeeded.
+    pub fn text(&self) -> Option<String> {
+        self.data().map(|response| response.text())
+    }
+
+    /// The latest result decoded from JSON, or `None` before the first one
+    /// arrives. Failed requests and bodies that don't decode are both
+    /// errors.
+    pub fn json<T: DeserializeOwned>(&self) -> Option<Result<T, FetchError>> {
+        Some(self.result()?.and_then(|response| response.json()))
+    }
+
+    /// Request the URL again, such as for a "Retry" button, cancelling a
+    /// request on its way.
+    pub fn refetch(&self) {
+        let url = self.state.url.borrow().clone();
+        if let Some(url) = url {
+            self.state.send(url);
+        }
+    }
+}
+
+/// GET the URL `url` returns, re-rendering the app when the response
+/// arrives. `url` is called every render, and a new URL is fetched in place
+/// of the old one; return `None` (it may return an `Option<String>`) to not
+/// fetch anything yet, such as while a search box is empty. While a
+/// request is in flight, the `Suspense` it's rendered in shows its fallback.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_fetch<U: Into<Option<String>>>(url: impl FnOnce() -> U) -> Fetch {
+    let state = use_ref(|| Rc::new(FetchState::new())).get();
+    let url = url().into();
+    if *state.url.borrow() != url {
+        state.url.replace(url.clone());
+        match url {
+            Some(url) => state.send(url),
+            None => state.cancel(),
+        }
+    }
+    let fetch = Fetch { state };
+    if fetch.loading() {
+        mark_pending();
+    }
+    fetch
+}
+
+/// One [`use_fetch`]'s state.
+struct FetchState {
+    /// The URL being fetched, or last fetched.
+    url: RefCell<Option<String>>,
+    /// The request in flight, with the task to abort to cancel it.
+    request: RefCell<Option<(u64, Option<AbortHandle>)>>,
+    loading: Signal<bool>,
+    result: Signal<Option<FetchResult>>,
+}
+
+impl FetchState {
+    fn new() -> Self {
+        Self {
+            url: RefCell::new(None),
+            request: RefCell::new(None),
+            loading: Signal::new(false),
+            result: Signal::new(None),
+        }
+    }
+
+    /// Start a request for `url` in place of any in flight.
+    fn send(self: &Rc<Self>, url: String) {
+        let id = self.begin();
+        let task = match crate::windows::event_proxy() {
+            Some(proxy) => Some(spawn(id, url, proxy)),
+            None => {
+                finish(id, Err(FetchError::Request("the app isn't running".into())));
+                None
+            }
+        };
+        if let Some((current, handle)) = &mut *self.request.borrow_mut()
+            && *current == id
+        {
+            *handle = task;
+        }
+    }
+
+    /// Note a new request, cancelling any in flight, and return its ID.
+    fn begin(self: &Rc<Self>) -> u64 {
+        self.cancel();
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        PENDING.with(|pending| pending.borrow_mut().insert(id, Rc::downgrade(self)));
+        self.request.replace(Some((id, None)));
+        if !untracked(|| self.loading.get()) {
+            self.loading.set(true);
+        }
+        id
+    }
+
+    /// Cancel the request in flight, if any.
+    fn cancel(&self) {
+        self.abort();
+        if untracked(|| self.loading.get()) {
+            self.loading.set(false);
+        }
+    }
+
+    /// Stop the request in flight, if any, and forget it.
+    fn abort(&self) {
+        if let Some((id, task)) = self.request.take() {
+            if let Some(task) = task {
+                task.abort();
+            }
+            let _ = PENDING.try_with(|pending| pending.borrow_mut().remove(&id));
+        }
+    }
+}
+
+impl Drop for FetchState {
+    fn drop(&mut self) {
+        // Not `cancel`: nothing reads `loading` any more
+        self.abort();
+    }
+}
+
+/// Record the result of request `id`, if it's still wanted.
+fn finish(id: u64, result: FetchResult) {
+    let Some(state) = PENDING.with(|pending| pending.borrow_mut().remove(&id)) else {
+        return;
+    };
+    let Some(state) = state.upgrade() else {
+        return;
+    };
+    state.request.take();
+    state.result.set(Some(result));
+    state.loading.set(false);
+}
+
+/// Hand finished requests to their [`use_fetch`]s (called by the runtime on
+/// [`RinchEvent::FetchFinished`]).
+pub(crate) fn deliver() {
+    let finished = std::mem::take(&mut *FINISHED.lock().unwrap_or_else(|e| e.into_inner()));
+    for (id, result) in finished {
+        finish(id, result);
+    }
+}
+
+/// Run request `id` on the network runtime, waking the event loop when it's
+/// done.
+fn spawn(id: u64, url: String, proxy: EventLoopProxy<RinchEvent>) -> AbortHandle {
+    crate::net::runtime()
+        .spawn(async move {
+            let result = get(&url).await;
+            FINISHED
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((id, result));
+            let _ = proxy.send_event(RinchEvent::FetchFinished);
+        })
+        .abort_handle()
+}
+
+async fn get(url: &str) -> FetchResult {
+    let request_failed = |e: reqwest::Error| FetchError::Request(e.to_string());
+    let response = client().get(url).send().await.map_err(request_failed)?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    let body = response.bytes().await.map_err(request_failed)?.to_vec();
+    let response = Response {
+        url: url.to_string(),
+        status,
+        headers,
+        body,
+    };
+    if (200..300).contains(&status) {
+        Ok(response)
+    } else {
+        Err(FetchError::Status(response))
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> Response {
+        Response {
+            url: "https://example.com/items".into(),
+            status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn responses_decode() {
+        let ok = response(200, "[1, 2, 3]");
+        assert_eq!(ok.header("content-type"), Some("application/json"));
+        assert_eq!(ok.text(), "[1, 2, 3]");
+        assert_eq!(ok.json::<Vec<u32>>(), Ok(vec![1, 2, 3]));
+        assert!(matches!(ok.json::<String>(), Err(FetchError::Decode(_))));
+        assert_eq!(
+            FetchError::Status(response(404, "")).to_string(),
+            "https://example.com/items returned HTTP 404"
+        );
+    }
+
+    #[test]
+    fn only_the_latest_request_lands() {
+        let state = Rc::new(FetchState::new());
+        let fetch = Fetch {
+            state: state.clone(),
+        };
+        let first = state.begin();
+        assert!(fetch.loading());
+        let second = state.begin();
+
+        // The first request was cancelled; its result is dropped
+        finish(first, Ok(response(200, "[1]")));
+        assert!(fetch.loading());
+        assert_eq!(fetch.result(), None);
+
+        finish(second, Ok(response(200, "[2]")));
+        assert!(!fetch.loading());
+        assert_eq!(fetch.json::<Vec<u32>>(), Some(Ok(vec![2])));
+
+        // Results outlive the requests after them until those finish
+        let third = state.begin();
+        assert_eq!(fetch.json::<Vec<u32>>(), Some(Ok(vec![2])));
+        finish(third, Err(FetchError::Status(response(500, ""))));
+        assert!(matches!(fetch.error(), Some(FetchError::Status(_))));
+        assert!(matches!(
+            fetch.json::<Vec<u32>>(),
+            Some(Err(FetchError::Status(_)))
+        ));
+
+        // Dropping the state forgets its request
+        let fourth = state.begin();
+        drop((fetch, state));
+        assert!(PENDING.with(|pending| !pending.borrow().contains_key(&fourth)));
+    }
+}
@@ -0,0 +1,483 @@
+//! Custom font loading.
+//!
+//! Fonts can be registered from embedded bytes or files, or declared with
+//! `@font-face` in a stylesheet. Registered faces are added to every window's
+//! font collection, so they can be used by family name in `font-family` lists
+//! alongside system fonts, with the usual weight/style matching and fallback.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::fonts::{self, FontFace, FaceStyle};
+//!
+//! fn main() {
+//!     // Family, weight, and style are read from the font file
+//!     fonts::register(include_bytes!("../assets/Inter-Regular.ttf").to_vec());
+//!
+//!     // Or override them
+//!     fonts::register_face(
+//!         FontFace::new(include_bytes!("../assets/Brand-Italic.otf").to_vec())
+//!             .family("Brand")
+//!             .style(FaceStyle::Italic),
+//!     );
+//!
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! Local files referenced by `@font-face` are loaded automatically:
+//!
+//! ```css
+//! @font-face {
+//!     font-family: "Brand";
+//!     src: url("assets/Brand-Bold.otf") format("opentype");
+//!     font-weight: bold;
+//! }
+//! body { font-family: "Brand", "Segoe UI", sans-serif; }
+//! ```
+//!
+//! Color emoji are drawn from the platform emoji font (Segoe UI Emoji, Apple
+//! Color Emoji, or Noto Color Emoji) whenever the requested fonts lack a glyph.
+//! Apps that bundle their own emoji font can register it and pass its family to
+//! [`set_emoji_family`].
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parley::fontique::{
+    Blob, FallbackKey, FontInfoOverride, FontStyle, FontWeight, FontWidth, Script,
+};
+use parley::FontContext;
+
+use crate::shell::cascade::{
+    find_outside_quotes, matching_brace, parse_declarations, strip_comments,
+};
+
+/// Style of a registered font face.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FaceStyle {
+    #[default]
+    Normal,
+    Italic,
+    /// Oblique with an optional slant angle in degrees.
+    Oblique(Option<f32>),
+}
+
+/// A font face to add to every window's font collection.
+#[derive(Debug, Clone)]
+pub struct FontFace {
+    data: Arc<Vec<u8>>,
+    family: Option<String>,
+    weight: Option<f32>,
+    width: Option<f32>,
+    style: Option<FaceStyle>,
+}
+
+impl FontFace {
+    /// Create a face from font file bytes (TTF, OTF, or a collection).
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        Self::from_shared(Arc::new(data.into()))
+    }
+
+    /// Load a face from a font file or an asset URL (see [`crate::assets`]).
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        read_font_file(path.as_ref()).map(Self::from_shared)
+    }
+
+    fn from_shared(data: Arc<Vec<u8>>) -> Self {
+        Self {
+            data,
+            family: None,
+            weight: None,
+            width: None,
+            style: None,
+        }
+    }
+
+    /// Register the face under this family name instead of its own.
+    pub fn family(mut self, family: impl Into<String>) -> Self {
+        self.family = Some(family.into());
+        self
+    }
+
+    /// Override the face's weight (100-900).
+    ///
+    /// Leave this unset for variable fonts: their `wght` axis already covers a
+    /// range of weights, and pinning one would hide the others from matching.
+    pub fn weight(mut self, weight: f32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Override the face's width as a percentage of normal (50-200).
+    pub fn width(mut self, percentage: f32) -> Self {
+        self.width = Some(percentage);
+        self
+    }
+
+    /// Override the face's style.
+    pub fn style(mut self, style: FaceStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+/// Color emoji families, in order of preference.
+const EMOJI_FAMILIES: &[&str] = &[
+    "Segoe UI Emoji",
+    "Apple Color Emoji",
+    "Noto Color Emoji",
+    "Twemoji Mozilla",
+];
+
+/// Scripts whose fallback chains end in the emoji font. Emoji take the script
+/// of the surrounding text, so the common scripts need it too.
+const EMOJI_FALLBACK_SCRIPTS: &[[u8; 4]] = &[
+    *b"Zsye", *b"Zyyy", *b"Zinh", *b"Latn", *b"Cyrl", *b"Grek", *b"Arab", *b"Hebr", *b"Deva",
+    *b"Thai", *b"Hani", *b"Hira", *b"Kana", *b"Hang",
+];
+
+thread_local! {
+    static REGISTERED_FONTS: RefCell<Vec<FontFace>> = const { RefCell::new(Vec::new()) };
+    static FONT_FILES: RefCell<HashMap<PathBuf, Arc<Vec<u8>>>> = RefCell::new(HashMap::new());
+    static EMOJI_FAMILY: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Bumped whenever a font is registered, the emoji family changes or font
+    /// files are read again, so cached font contexts are rebuilt.
+    static FONTS_GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+fn fonts_changed() {
+    FONTS_GENERATION.with(|generation| generation.set(generation.get() + 1));
+}
+
+/// Register a font from its file bytes.
+///
+/// Windows created afterwards (and documents rebuilt on re-render) can use
+/// the font by the family name stored in the file.
+pub fn register(data: impl Into<Vec<u8>>) {
+    register_face(FontFace::new(data));
+}
+
+/// Register a font file.
+pub fn register_file(path: impl AsRef<Path>) -> io::Result<()> {
+    register_face(FontFace::from_file(path)?);
+    Ok(())
+}
+
+/// Register a font face with optional family/weight/style overrides.
+pub fn register_face(face: FontFace) {
+    REGISTERED_FONTS.with(|fonts| fonts.borrow_mut().push(face));
+    fonts_changed();
+}
+
+/// Use `family` for emoji ahead of the platform emoji fonts.
+///
+/// The family must be installed or registered with [`register_face`].
+pub fn set_emoji_family(family: impl Into<String>) {
+    EMOJI_FAMILY.with(|f| *f.borrow_mut() = Some(family.into()));
+    fonts_changed();
+}
+
+/// A window's font context, kept across document rebuilds. Building one
+/// loads the system fonts, so it's only built again when the `@font-face`
+/// rules in the window's stylesheets or the registered fonts change.
+#[derive(Default)]
+pub(crate) struct FontCache {
+    /// The context, with the `@font-face` rules and fonts generation it was
+    /// built for.
+    built: Option<((Vec<String>, u64), FontContext)>,
+}
+
+impl FontCache {
+    /// The font context for a document with `stylesheets`.
+    pub(crate) fn font_context(&mut self, stylesheets: &[String]) -> FontContext {
+        let built_for = (
+            stylesheets
+                .iter()
+                .flat_map(|css| font_face_rules(css))
+                .collect::<Vec<_>>(),
+            FONTS_GENERATION.with(Cell::get),
+        );
+        match &self.built {
+            Some((built, ctx)) if *built == built_for => ctx.clone(),
+            _ => {
+                let ctx = build_font_context(&built_for.0);
+                self.built = Some((built_for, ctx.clone()));
+                ctx
+            }
+        }
+    }
+}
+
+/// Build a font context containing system fonts, registered fonts, and the
+/// `@font-face` rules in `stylesheets`.
+pub(crate) fn font_context(stylesheets: &[String]) -> FontContext {
+    let rules: Vec<_> = stylesheets
+        .iter()
+        .flat_map(|css| font_face_rules(css))
+        .collect();
+    build_font_context(&rules)
+}
+
+/// Build a font context from the bodies of `@font-face` rules.
+fn build_font_context(rules: &[String]) -> FontContext {
+    let mut ctx = FontContext::new();
+
+    let registered = REGISTERED_FONTS.with(|fonts| fonts.borrow().clone());
+    let declared = rules.iter().filter_map(|rule| font_face_in_rule(rule));
+
+    for face in registered.into_iter().chain(declared) {
+        let info = FontInfoOverride {
+            family_name: face.family.as_deref(),
+            weight: face.weight.map(FontWeight::new),
+            width: face.width.map(FontWidth::from_percentage),
+            style: face.style.map(|style| match style {
+                FaceStyle::Normal => FontStyle::Normal,
+                FaceStyle::Italic => FontStyle::Italic,
+                FaceStyle::Oblique(angle) => FontStyle::Oblique(angle),
+            }),
+            ..Default::default()
+        };
+        let added = ctx
+            .collection
+            .register_fonts(Blob::new(face.data.clone()), Some(info));
+        if added.is_empty() {
+            tracing::warn!("Font data for {:?} could not be parsed", face.family);
+        }
+    }
+
+    add_emoji_fallbacks(&mut ctx);
+    ctx
+}
+
+/// Append the available color emoji families to the fallback chains.
+fn add_emoji_fallbacks(ctx: &mut FontContext) {
+    let preferred = EMOJI_FAMILY.with(|f| f.borrow().clone());
+    let families: Vec<_> = preferred
+        .iter()
+        .map(String::as_str)
+        .chain(EMOJI_FAMILIES.iter().copied())
+        .filter_map(|name| ctx.collection.family_id(name))
+        .collect();
+
+    if families.is_empty() {
+        tracing::debug!("No color emoji font found");
+        return;
+    }
+
+    for script in EMOJI_FALLBACK_SCRIPTS {
+        let key = FallbackKey::new(Script::from_bytes(*script), None);
+        ctx.collection
+            .append_fallbacks(key, families.iter().copied());
+    }
+}
+
+/// Read a font file or asset, caching its contents so document rebuilds don't
+/// hit the disk.
+fn read_font_file(path: &Path) -> io::Result<Arc<Vec<u8>>> {
+    if let Some(data) = FONT_FILES.with(|files| files.borrow().get(path).cloned()) {
+        return Ok(data);
+    }
+    let data = Arc::new(match path.to_str() {
+        Some(url) => crate::assets::read(url)?.into_owned(),
+        None => std::fs::read(path)?,
+    });
+    FONT_FILES.with(|files| files.borrow_mut().insert(path.to_path_buf(), data.clone()));
+    Ok(data)
+}
+
+/// Drop the cached font files, so changed files are read again (for hot
+/// reload).
+pub(crate) fn forget_font_files() {
+    FONT_FILES.with(|files| files.borrow_mut().clear());
+    fonts_changed();
+}
+
+/// The declarations of each top-level `@font-face` rule in a stylesheet.
+fn font_face_rules(css: &str) -> Vec<String> {
+    let css = strip_comments(css);
+    let lower = css.to_ascii_lowercase();
+    let mut rules = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(offset) = lower[cursor..].find("@font-face") {
+        let start = cursor + offset;
+        let Some(open) = find_outside_quotes(&css[start..], &['{']).map(|i| start + i) else {
+            break;
+        };
+        let Some(close) = matching_brace(&css, open) else {
+            break;
+        };
+        cursor = close + 1;
+        rules.push(css[open + 1..close].trim().to_string());
+    }
+
+    rules
+}
+
+/// The face an `@font-face` rule's declarations describe, if its `src`
+/// points at a readable local file.
+fn font_face_in_rule(rule: &str) -> Option<FontFace> {
+    let mut family = None;
+    let mut data = None;
+    let mut weight = None;
+    let mut width = None;
+    let mut style = None;
+    for decl in parse_declarations(rule) {
+        match decl.property.as_str() {
+            "font-family" => family = Some(unquote(&decl.value).to_string()),
+            "src" => data = load_src(&decl.value),
+            "font-weight" => weight = parse_weight(&decl.value),
+            "font-stretch" => width = parse_width(&decl.value),
+            "font-style" => style = parse_style(&decl.value),
+            _ => {}
+        }
+    }
+
+    Some(FontFace {
+        data: data?,
+        family,
+        weight,
+        width,
+        style,
+    })
+}
+
+/// Load the first local `url(...)` in a `src` descriptor that can be read.
+///
+/// Remote and `local()` sources are skipped; remote fonts aren't fetched and
+/// `local()` fonts are already in the system collection.
+fn load_src(value: &str) -> Option<Arc<Vec<u8>>> {
+    let mut rest = value;
+    while let Some(start) = rest.to_ascii_lowercase().find("url(") {
+        let args = &rest[start + 4..];
+        let end = find_outside_quotes(args, &[')'])?;
+        let url = unquote(args[..end].trim());
+        rest = &args[end + 1..];
+
+        let lower = url.to_ascii_lowercase();
+        if lower.starts_with("http://")
+            || lower.starts_with("https://")
+            || lower.starts_with("data:")
+        {
+            continue;
+        }
+        let path = url.strip_prefix("file://").unwrap_or(url);
+        match read_font_file(Path::new(path)) {
+            Ok(data) => return Some(data),
+            Err(e) => tracing::warn!("Failed to read font {:?}: {}", path, e),
+        }
+    }
+    None
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+// Ranges like `font-weight: 100 900` describe a variable font. They return
+// `None` so the font keeps its own default instance for matching, and the
+// requested weight is applied through its variation axes.
+
+fn parse_weight(value: &str) -> Option<f32> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "normal" => Some(400.0),
+        "bold" => Some(700.0),
+        other => other.parse().ok(),
+    }
+}
+
+fn parse_width(value: &str) -> Option<f32> {
+    let value = value.trim().to_ascii_lowercase();
+    let percentage = match value.as_str() {
+        "ultra-condensed" => 50.0,
+        "extra-condensed" => 62.5,
+        "condensed" => 75.0,
+        "semi-condensed" => 87.5,
+        "normal" => 100.0,
+        "semi-expanded" => 112.5,
+        "expanded" => 125.0,
+        "extra-expanded" => 150.0,
+        "ultra-expanded" => 200.0,
+        other => other.strip_suffix('%')?.parse().ok()?,
+    };
+    Some(percentage)
+}
+
+fn parse_style(value: &str) -> Option<FaceStyle> {
+    let value = value.trim().to_ascii_lowercase();
+    let mut parts = value.split_whitespace();
+    match parts.next()? {
+        "normal" => Some(FaceStyle::Normal),
+        "italic" => Some(FaceStyle::Italic),
+        "oblique" => {
+            let angles: Vec<_> = parts.collect();
+            match angles.as_slice() {
+                [] => Some(FaceStyle::Oblique(None)),
+                [angle] => Some(FaceStyle::Oblique(Some(
+                    angle.strip_suffix("deg")?.parse().ok()?,
+                ))),
+                _ => Some(FaceStyle::Oblique(None)),
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn font_face_rules_load_local_files() {
+        let dir = std::env::temp_dir().join("rinch-font-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Brand.ttf");
+        std::fs::write(&path, b"font bytes").unwrap();
+        let path = path.to_string_lossy().replace('\\', "/");
+
+        let css = format!(
+            r#"/* @font-face {{ font-family: Commented; src: url(x.ttf); }} */
+            @font-face {{
+                font-family: "Brand";
+                src: url("https://example.com/Brand.woff2"), local(Brand), url('{}') format("truetype");
+                font-weight: bold;
+                font-style: italic;
+            }}
+            @font-face {{ font-family: Missing; src: url(missing.ttf); }}
+            body {{ font-family: Brand, sans-serif; }}"#,
+            path
+        );
+
+        let faces: Vec<_> = font_face_rules(&css)
+            .iter()
+            .filter_map(|rule| font_face_in_rule(rule))
+            .collect();
+        assert_eq!(faces.len(), 1);
+        assert_eq!(faces[0].family.as_deref(), Some("Brand"));
+        assert_eq!(faces[0].weight, Some(700.0));
+        assert_eq!(faces[0].style, Some(FaceStyle::Italic));
+        assert_eq!(faces[0].data.as_slice(), b"font bytes");
+    }
+
+    #[test]
+    fn weight_and_style_descriptors() {
+        assert_eq!(parse_weight("300"), Some(300.0));
+        assert_eq!(parse_weight("100 900"), None);
+        assert_eq!(
+            parse_style("oblique 10deg"),
+            Some(FaceStyle::Oblique(Some(10.0)))
+        );
+        assert_eq!(
+            parse_style("oblique 0deg 12deg"),
+            Some(FaceStyle::Oblique(None))
+        );
+        assert_eq!(parse_style("sideways"), None);
+        assert_eq!(parse_width("condensed"), Some(75.0));
+        assert_eq!(parse_width("80%"), Some(80.0));
+        assert_eq!(parse_width("75% 125%"), None);
+    }
+}
@@ -0,0 +1,626 @@
+//! Form state and validation.
+//!
+//! [`use_form`] keeps a form's fields, whether each was changed or visited,
+//! and what's wrong with them, so a settings dialog or a login form doesn't
+//! wire a handful of signals per field by hand:
+//!
+//! ```ignore
+//! use rinch::form::use_form;
+//!
+//! fn login() -> Element {
+//!     let form = use_form();
+//!     let email = form
+//!         .field("email", String::new)
+//!         .required("Enter your email")
+//!         .validate(|email| match email.contains('@') {
+//!             true => Ok(()),
+//!             false => Err("That isn't an email address".into()),
+//!         });
+//!     let submit = {
+//!         let form = form.clone();
+//!         move || {
+//!             if form.submit().is_ok() {
+//!                 sign_in(form.value::<String>("email"));
+//!             }
+//!         }
+//!     };
+//!     rsx! {
+//!         input { value: {email.value()}, aria_invalid: {email.invalid()} }
+//!         p { class: "error", {email.error().unwrap_or_default()} }
+//!         button { onclick: submit, "Sign in" }
+//!     }
+//! }
+//! ```
+//!
+//! - Fields are registered by name on every render. The first render's
+//!   initial value is kept; [`use_form`] drops the validators when the
+//!   component renders, so each render's can use that render's values.
+//! - A field is dirty while its value differs from its initial value, and
+//!   touched once the app calls [`Field::touch`], such as when focus leaves
+//!   its input.
+//! - Errors are worked out from the current values whenever they're read,
+//!   so they're reactive and never stale. When they're shown follows the
+//!   form's [`ValidateOn`]; after a submit, all of them are.
+//! - Async validators return `None` while their answer is on its way, such
+//!   as from a [`use_fetch`](crate::fetch) the component started. A form
+//!   with one pending can't be submitted.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::str::FromStr;
+
+use rinch_core::{untracked, use_ref, Signal};
+
+/// When a field's errors are shown, before the form is first submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidateOn {
+    /// As soon as the field is changed.
+    Change,
+    /// Once the field has been touched.
+    #[default]
+    Blur,
+    /// Only after a submit.
+    Submit,
+}
+
+/// A field's error, as reported by [`Form::submit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// The field's name.
+    pub field: String,
+    /// What's wrong with its value.
+    pub message: String,
+}
+
+/// Why a form couldn't be submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitError {
+    /// Some fields have errors, in the order the fields were registered.
+    Invalid(Vec<FieldError>),
+    /// An async validator hasn't answered yet.
+    Pending,
+}
+
+/// What a field's validators make of its value.
+enum Check {
+    Valid,
+    Invalid(String),
+    Pending,
+}
+
+type Validator<T> = Box<dyn Fn(&T) -> Option<Result<(), String>>>;
+
+/// One field's state.
+struct FieldState<T> {
+    name: String,
+    initial: Signal<T>,
+    value: Signal<T>,
+    touched: Signal<bool>,
+    validators: RefCell<Vec<Validator<T>>>,
+    /// Weak, as the form holds its fields.
+    form: Weak<FormState>,
+}
+
+/// A field of any type, as the form sees it.
+trait AnyField {
+    fn name(&self) -> &str;
+    fn as_any(self: Rc<Self>) -> Rc<dyn Any>;
+    fn is_dirty(&self) -> bool;
+    fn check(&self) -> Check;
+    fn touch(&self);
+    fn clear_validators(&self);
+    fn reset(&self);
+    fn mark_clean(&self);
+}
+
+impl<T: Clone + PartialEq + 'static> AnyField for FieldState<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn as_any(self: Rc<Self>) -> Rc<dyn Any> {
+        self
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.value
+            .with(|value| self.initial.with(|initial| value != initial))
+    }
+
+    fn check(&self) -> Check {
+        let validators = self.validators.borrow();
+        let mut check = Check::Valid;
+        self.value.with(|value| {
+            for validator in validators.iter() {
+                match validator(value) {
+                    Some(Ok(())) => {}
+                    Some(Err(message)) => {
+                        check = Check::Invalid(message);
+                        return;
+                    }
+                    None => check = Check::Pending,
+                }
+            }
+        });
+        check
+    }
+
+    fn touch(&self) {
+        if !untracked(|| self.touched.get()) {
+            self.touched.set(true);
+        }
+    }
+
+    fn clear_validators(&self) {
+        self.validators.borrow_mut().clear();
+    }
+
+    fn reset(&self) {
+        let initial = untracked(|| self.initial.get());
+        if untracked(|| self.value.with(|value| *value != initial)) {
+            self.value.set(initial);
+        }
+        if untracked(|| self.touched.get()) {
+            self.touched.set(false);
+        }
+    }
+
+    fn mark_clean(&self) {
+        if self.is_dirty() {
+            self.initial.set(untracked(|| self.value.get()));
+        }
+        if untracked(|| self.touched.get()) {
+            self.touched.set(false);
+        }
+    }
+}
+
+/// The state behind a [`Form`].
+struct FormState {
+    fields: RefCell<Vec<Rc<dyn AnyField>>>,
+    validate_on: Cell<ValidateOn>,
+    /// Whether a submit was tried since the last reset, showing every error.
+    submitted: Signal<bool>,
+}
+
+/// A form's fields and their validation. See the [module docs](self).
+#[derive(Clone)]
+pub struct Form {
+    state: Rc<FormState>,
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Form {
+    /// A form with no fields, showing errors once fields are touched.
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(FormState {
+                fields: RefCell::new(Vec::new()),
+                validate_on: Cell::new(ValidateOn::default()),
+                submitted: Signal::new(false),
+            }),
+        }
+    }
+
+    /// Set when fields' errors are shown.
+    pub fn validate_on(&self, when: ValidateOn) -> &Self {
+        self.state.validate_on.set(when);
+        self
+    }
+
+    /// The field called `name`, registering it with the value `initial`
+    /// returns if it's new. Validators added to it are kept until
+    /// [`clear_validators`](Self::clear_validators), so looking a field up
+    /// again in the same render keeps them.
+    ///
+    /// # Panics
+    ///
+    /// If the field was registered with another type.
+    pub fn field<T: Clone + PartialEq + 'static>(
+        &self,
+        name: &str,
+        initial: impl FnOnce() -> T,
+    ) -> Field<T> {
+        if let Some(field) = self.find(name) {
+            let state = field
+                .as_any()
+                .downcast::<FieldState<T>>()
+                .unwrap_or_else(|_| {
+                    panic!("form field `{name}` was registered as another type");
+                });
+            return Field { state };
+        }
+        let initial = initial();
+        let state = Rc::new(FieldState {
+            name: name.to_string(),
+            initial: Signal::new(initial.clone()),
+            value: Signal::new(initial),
+            touched: Signal::new(false),
+            validators: RefCell::new(Vec::new()),
+            form: Rc::downgrade(&self.state),
+        });
+        self.state.fields.borrow_mut().push(state.clone());
+        Field { state }
+    }
+
+    /// Stop tracking the field called `name`, such as when it's hidden, so
+    /// it no longer holds up submitting.
+    pub fn remove(&self, name: &str) {
+        self.state
+            .fields
+            .borrow_mut()
+            .retain(|field| field.name() != name);
+    }
+
+    /// The value of the field called `name`.
+    ///
+    /// # Panics
+    ///
+    /// If there's no such field, or it has another type.
+    pub fn value<T: Clone + PartialEq + 'static>(&self, name: &str) -> T {
+        let field = self
+            .find(name)
+            .unwrap_or_else(|| panic!("no form field `{name}`"));
+        let state = field
+            .as_any()
+            .downcast::<FieldState<T>>()
+            .unwrap_or_else(|_| {
+                panic!("form field `{name}` was registered as another type");
+            });
+        state.value.get()
+    }
+
+    /// Whether any field differs from its initial value.
+    pub fn is_dirty(&self) -> bool {
+        self.fields().iter().any(|field| field.is_dirty())
+    }
+
+    /// Whether every field's validators accept its value.
+    pub fn is_valid(&self) -> bool {
+        self.fields()
+            .iter()
+            .all(|field| matches!(field.check(), Check::Valid))
+    }
+
+    /// Whether an async validator hasn't answered yet.
+    pub fn is_pending(&self) -> bool {
+        self.fields()
+            .iter()
+            .any(|field| matches!(field.check(), Check::Pending))
+    }
+
+    /// Whether a submit was tried since the form was last reset.
+    pub fn is_submitted(&self) -> bool {
+        self.state.submitted.get()
+    }
+
+    /// Every field's error, shown or not, in the order the fields were
+    /// registered.
+    pub fn errors(&self) -> Vec<FieldError> {
+        self.fields()
+            .iter()
+            .filter_map(|field| match field.check() {
+                Check::Invalid(message) => Some(FieldError {
+                    field: field.name().to_string(),
+                    message,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Try to submit the form: every field is touched and its errors shown.
+    /// Returns `Ok` if the form can be sent.
+    pub fn submit(&self) -> Result<(), SubmitError> {
+        let fields = self.fields();
+        for field in &fields {
+            field.touch();
+        }
+        if !untracked(|| self.state.submitted.get()) {
+            self.state.submitted.set(true);
+        }
+        let errors = untracked(|| self.errors());
+        if !errors.is_empty() {
+            return Err(SubmitError::Invalid(errors));
+        }
+        if untracked(|| self.is_pending()) {
+            return Err(SubmitError::Pending);
+        }
+        Ok(())
+    }
+
+    /// Put every field back to its initial value, untouched, with no
+    /// errors shown.
+    pub fn reset(&self) {
+        for field in self.fields() {
+            field.reset();
+        }
+        self.clear_submitted();
+    }
+
+    /// Make the current values the initial ones, such as once they're
+    /// saved, so the form is no longer dirty.
+    pub fn mark_clean(&self) {
+        for field in self.fields() {
+            field.mark_clean();
+        }
+        self.clear_submitted();
+    }
+
+    /// Drop every field's validators, for the next render to add its own.
+    /// [`use_form`] does this each time the component renders.
+    pub fn clear_validators(&self) {
+        for field in self.fields() {
+            field.clear_validators();
+        }
+    }
+
+    fn clear_submitted(&self) {
+        if untracked(|| self.state.submitted.get()) {
+            self.state.submitted.set(false);
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<Rc<dyn AnyField>> {
+        self.state
+            .fields
+            .borrow()
+            .iter()
+            .find(|field| field.name() == name)
+            .cloned()
+    }
+
+    /// The fields, copied out so validators can use the form.
+    fn fields(&self) -> Vec<Rc<dyn AnyField>> {
+        self.state.fields.borrow().clone()
+    }
+}
+
+/// A field of a [`Form`], holding a `T`. Its getters are reactive, like a
+/// signal's.
+pub struct Field<T> {
+    state: Rc<FieldState<T>>,
+}
+
+impl<T> Clone for Field<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> Field<T> {
+    /// Add a validator, returning what's wrong with the value if anything
+    /// is. Validators run in the order they're added, and the first error
+    /// is the field's.
+    pub fn validate(self, validator: impl Fn(&T) -> Result<(), String> + 'static) -> Self {
+        self.validate_async(move |value| Some(validator(value)))
+    }
+
+    /// Add a validator whose answer may not be ready: it returns `None`
+    /// until it is. It's asked again whenever the form is read, so it can
+    /// read a signal the answer arrives in.
+    pub fn validate_async(
+        self,
+        validator: impl Fn(&T) -> Option<Result<(), String>> + 'static,
+    ) -> Self {
+        self.state.validators.borrow_mut().push(Box::new(validator));
+        self
+    }
+
+    /// The field's name.
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    /// The current value.
+    pub fn value(&self) -> T {
+        self.state.value.get()
+    }
+
+    /// Set the value, such as from the field's input.
+    pub fn set(&self, value: T) {
+        self.state.value.set(value);
+    }
+
+    /// Mark the field as visited, such as when focus leaves its input.
+    pub fn touch(&self) {
+        self.state.touch();
+    }
+
+    /// Whether the field was touched since the form was last reset.
+    pub fn is_touched(&self) -> bool {
+        self.state.touched.get()
+    }
+
+    /// Whether the value differs from the initial value.
+    pub fn is_dirty(&self) -> bool {
+        self.state.is_dirty()
+    }
+
+    /// Whether an async validator hasn't answered yet.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state.check(), Check::Pending)
+    }
+
+    /// The error to show with the field, if it has one and the form's
+    /// [`ValidateOn`] says to show it yet. A field kept after its form is
+    /// dropped shows none.
+    pub fn error(&self) -> Option<String> {
+        let shown = self.state.form.upgrade().is_some_and(|form| {
+            form.submitted.get()
+                || match form.validate_on.get() {
+                    ValidateOn::Change => self.is_dirty() || self.is_touched(),
+                    ValidateOn::Blur => self.is_touched(),
+                    ValidateOn::Submit => false,
+                }
+        });
+        match self.state.check() {
+            Check::Invalid(message) if shown => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Whether an error is shown, for `aria_invalid`.
+    pub fn invalid(&self) -> bool {
+        self.error().is_some()
+    }
+}
+
+impl Field<String> {
+    /// Add a validator rejecting an empty or blank value with `message`.
+    pub fn required(self, message: impl Into<String>) -> Self {
+        let message = message.into();
+        self.validate(move |value| match value.trim().is_empty() {
+            true => Err(message.clone()),
+            false => Ok(()),
+        })
+    }
+
+    /// Add a validator rejecting values that don't parse as an `N`, with
+    /// `message`. Blank values are left to [`required`](Self::required).
+    pub fn parses<N: FromStr>(self, message: impl Into<String>) -> Self {
+        let message = message.into();
+        self.validate(move |value| {
+            let value = value.trim();
+            match value.is_empty() || value.parse::<N>().is_ok() {
+                true => Ok(()),
+                false => Err(message.clone()),
+            }
+        })
+    }
+}
+
+/// A form kept across renders. See the [module docs](self).
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_form() -> Form {
+    let form = use_ref(Form::new).get();
+    form.clear_validators();
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_field(form: &Form) -> Field<String> {
+        form.field("name", String::new).required("Enter a name")
+    }
+
+    #[test]
+    fn errors_show_once_touched_and_after_submitting() {
+        let form = Form::new();
+        let name = name_field(&form);
+        let port = form
+            .field("port", || "8080".to_string())
+            .parses::<u16>("Enter a port number");
+
+        assert!(!form.is_valid());
+        assert_eq!(name.error(), None);
+        name.touch();
+        assert_eq!(name.error().as_deref(), Some("Enter a name"));
+        assert!(name.invalid());
+
+        port.set("http".into());
+        assert!(form.is_dirty());
+        assert_eq!(port.error(), None);
+        assert_eq!(
+            form.submit(),
+            Err(SubmitError::Invalid(vec![
+                FieldError {
+                    field: "name".into(),
+                    message: "Enter a name".into(),
+                },
+                FieldError {
+                    field: "port".into(),
+                    message: "Enter a port number".into(),
+                },
+            ]))
+        );
+        assert!(port.invalid());
+
+        // The next render registers the fields again, keeping their values
+        form.clear_validators();
+        let name = name_field(&form);
+        name.set("rinch".into());
+        let port = form.field("port", String::new);
+        assert_eq!(port.value(), "http");
+        assert_eq!(form.submit(), Ok(()));
+
+        form.reset();
+        assert_eq!(port.value(), "8080");
+        assert!(!form.is_dirty() && !form.is_submitted() && !name.is_touched());
+
+        name.set("saved".into());
+        form.mark_clean();
+        assert!(!form.is_dirty());
+        form.reset();
+        assert_eq!(name.value(), "saved");
+    }
+
+    #[test]
+    fn looking_a_field_up_again_keeps_its_validators() {
+        let form = Form::new();
+        let name = name_field(&form);
+        name.touch();
+        let again = form.field("name", String::new);
+        assert_eq!(again.error().as_deref(), Some("Enter a name"));
+        assert!(!form.is_valid());
+
+        form.clear_validators();
+        assert!(form.is_valid());
+    }
+
+    #[test]
+    fn pending_validators_hold_up_submitting() {
+        let form = Form::new();
+        form.validate_on(ValidateOn::Change);
+        let taken = Signal::new(None::<bool>);
+        let user = {
+            let taken = taken.clone();
+            form.field("user", String::new).validate_async(move |_| {
+                taken.get().map(|taken| {
+                    if taken {
+                        Err("That name is taken".into())
+                    } else {
+                        Ok(())
+                    }
+                })
+            })
+        };
+
+        user.set("joe".into());
+        assert!(user.is_pending());
+        assert_eq!(form.submit(), Err(SubmitError::Pending));
+        taken.set(Some(true));
+        assert_eq!(user.error().as_deref(), Some("That name is taken"));
+        taken.set(Some(false));
+        assert_eq!(form.submit(), Ok(()));
+
+        form.remove("user");
+        assert!(form.is_valid());
+    }
+
+    #[test]
+    fn forms_and_fields_are_freed_when_dropped() {
+        let form = Form::new();
+        let name = name_field(&form);
+        name.touch();
+        let state = Rc::downgrade(&form.state);
+        drop(form);
+        assert!(state.upgrade().is_none());
+        assert_eq!(name.error(), None);
+
+        let field = Rc::downgrade(&name.state);
+        drop(name);
+        assert!(field.upgrade().is_none());
+    }
+}
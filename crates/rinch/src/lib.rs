@@ -33,7 +33,22 @@
 //! | [`use_signal`] | Reactive state that triggers re-renders |
 //! | [`use_state`] | Simple state with `(value, setter)` tuple |
 //! | [`use_ref`] | Mutable reference (doesn't trigger re-renders) |
-//! | [`use_effect`] | Side effects when dependencies change |
+//! | [`use_node_ref`] | Reference to a rendered element (scroll offset) |
+//! | [`use_infinite_scroll`] | Load more as a scroll container nears its end |
+//! | [`use_element_size`] | An element's own size, updated after layout |
+//! | [`use_visibility`] | Whether, and how much of, an element is visible |
+//! | [`use_scroll_progress`] | How far a scroll container is scrolled, from 0 to 1 |
+//! | [`use_spring`] | A value that springs towards a target |
+//! | [`use_animated`] | A value that tweens along an easing curve |
+//! | [`use_animation_frame`] | A callback run every animation frame |
+//! | [`use_transition_group`] | A list whose items animate in and out |
+//! | [`use_keyed_list`] | A list whose items keep their own signal and scope |
+//! | [`use_debounced`] | A value that catches up once it stops changing |
+//! | [`use_lazy`] | A subtree built the first time it's shown (`Lazy` in `rsx!`) |
+//! | [`use_error_boundary`] | A fallback in place of content that panicked (`ErrorBoundary` in `rsx!`) |
+//! | [`use_store`] | State whose fields are tracked one by one |
+//! | [`use_effect`] | Side effects run after the render, with cleanups |
+//! | [`use_effect_with`] | Side effects when dependencies change |
 //! | [`use_effect_cleanup`] | Effects with cleanup functions |
 //! | [`use_mount`] | One-time effect on first render |
 //! | [`use_memo`] | Memoized expensive computations |
@@ -82,53 +97,158 @@
 //! [`use_signal`]: prelude::use_signal
 //! [`use_state`]: prelude::use_state
 //! [`use_ref`]: prelude::use_ref
+//! [`use_node_ref`]: prelude::use_node_ref
+//! [`use_infinite_scroll`]: prelude::use_infinite_scroll
+//! [`use_element_size`]: prelude::use_element_size
+//! [`use_visibility`]: prelude::use_visibility
+//! [`use_scroll_progress`]: prelude::use_scroll_progress
+//! [`use_spring`]: prelude::use_spring
+//! [`use_animated`]: prelude::use_animated
+//! [`use_animation_frame`]: prelude::use_animation_frame
+//! [`use_transition_group`]: prelude::use_transition_group
+//! [`use_debounced`]: prelude::use_debounced
+//! [`use_lazy`]: prelude::use_lazy
+//! [`use_error_boundary`]: prelude::use_error_boundary
+//! [`use_store`]: prelude::use_store
+//! [`use_keyed_list`]: prelude::use_keyed_list
 //! [`use_effect`]: prelude::use_effect
+//! [`use_effect_with`]: prelude::use_effect_with
 //! [`use_effect_cleanup`]: prelude::use_effect_cleanup
 //! [`use_mount`]: prelude::use_mount
 //! [`use_memo`]: prelude::use_memo
 //! [`use_callback`]: prelude::use_callback
 
 pub mod app;
+pub mod components;
+pub mod form;
+pub mod plugin;
+pub mod window;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod assets;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod debug;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fonts;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod menu;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod process;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod search;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod shell;
-pub mod window;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod svg;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod windows;
 
-#[cfg(feature = "file-dialogs")]
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod web;
+
+#[cfg(all(feature = "file-dialogs", not(target_arch = "wasm32")))]
 pub mod dialogs;
 
-#[cfg(feature = "clipboard")]
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
 pub mod clipboard;
 
-#[cfg(feature = "system-tray")]
+#[cfg(all(feature = "system-tray", not(target_arch = "wasm32")))]
 pub mod tray;
 
+#[cfg(all(feature = "storage", not(target_arch = "wasm32")))]
+pub mod storage;
+
+#[cfg(all(feature = "settings", not(target_arch = "wasm32")))]
+pub mod settings;
+
+#[cfg(all(feature = "fetch", not(target_arch = "wasm32")))]
+pub mod fetch;
+
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+pub mod websocket;
+
+#[cfg(all(feature = "print", not(target_arch = "wasm32")))]
+pub mod print;
+
+#[cfg(all(
+    any(feature = "fetch", feature = "websocket"),
+    not(target_arch = "wasm32")
+))]
+mod net;
+
 pub mod prelude {
     //! Common imports for rinch applications.
-    pub use crate::shell::run;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::shell::{run, run_single_instance};
+    #[cfg(all(feature = "web", target_arch = "wasm32"))]
+    pub use crate::web::run;
     pub use rinch_core::element::*;
     pub use rinch_core::{batch, derived, untracked, Effect, Memo, Scope, Signal};
     // Hooks for ergonomic state management
     pub use rinch_core::{
-        create_context, use_callback, use_context, use_derived, use_effect, use_effect_cleanup,
-        use_memo, use_mount, use_ref, use_signal, use_state, RefHandle,
+        create_context, on_cleanup, request_remeasure, use_animated, use_animated_then,
+        use_animation_frame, use_callback, use_context, use_debounced, use_deep_links, use_derived,
+        use_effect, use_effect_cleanup, use_effect_with, use_element_size, use_error_boundary,
+        use_hotkey, use_idle, use_infinite_scroll, use_keyed_list, use_lazy, use_memo, use_mount,
+        use_node_ref, use_ref, use_resource, use_scroll_progress, use_signal, use_spring,
+        use_state, use_store, use_transition_group, use_visibility, ClipboardEvent, ClipboardImage,
+        DragEvent, KeyboardEvent, KeyedItem, Margins, NodeRef, RefHandle, ScrollAlignment,
+        ScrollBehavior, ScrollEvent, ScrollOptions, SpringConfig, Visibility,
+    };
+    // Stores, and `#[derive(Store)]`
+    pub use rinch_core::Store;
+    pub use rinch_macros::Store;
+    // Portals
+    pub use rinch_core::create_portal;
+    // Suspense and error boundaries
+    pub use rinch_core::{mark_pending, suspense, throw_error, CaughtError};
+    // Timers
+    pub use rinch_core::{clear_timeout, set_timeout, TimerId};
+    // Async
+    pub use rinch_core::{spawn_local, Resource, ResourceState, TaskHandle};
+    // Animation
+    pub use rinch_core::{
+        override_reduced_motion, prefers_reduced_motion, request_animation_frame,
+        request_paint_frame, with_full_motion, Animatable, AnimatedStyle, Color, Easing, FrameTime,
+        TransitionItem, TransitionPhase,
     };
     pub use rinch_macros::rsx;
+    // Bundled files
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::asset;
+    // Translations
+    pub use rinch_core::t;
     // Window control functions
+    #[cfg(not(target_arch = "wasm32"))]
     pub use crate::windows::{
-        close_current_window, minimize_current_window, toggle_maximize_current_window,
+        close_current_window, current_window_content_scale, hide_main_window,
+        minimize_current_window, quit, set_hide_on_close, show_main_window, toggle_main_window,
+        toggle_maximize_current_window, zoom_current_window, Zoom,
     };
+    // Lifecycle events
+    pub use rinch_core::{on_before_quit, on_resume, on_session_ending, on_suspend};
+    // Power and user activity
+    pub use rinch_core::PowerStatus;
 }
 
 // Re-export core types at crate root
 pub use rinch_core::element::{
     AppMenuProps, Children, Element, MenuItemProps, MenuProps, WindowProps,
 };
+pub use rinch_core::{a11y, clock, i18n, lifecycle};
 pub use rinch_core::{batch, derived, untracked, Effect, Memo, Scope, Signal};
 pub use rinch_macros::rsx;
-pub use shell::run;
-#[cfg(feature = "hot-reload")]
-pub use shell::run_with_hot_reload;
+#[cfg(not(target_arch = "wasm32"))]
+pub use shell::{run, run_single_instance};
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+pub use shell::{run_with_hot_reload, run_with_hot_reload_config};
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub use web::run;
+#[cfg(not(target_arch = "wasm32"))]
+pub use windows::quit;
 
 pub use rinch_core as core;
+#[cfg(not(target_arch = "wasm32"))]
 pub use rinch_renderer as renderer;
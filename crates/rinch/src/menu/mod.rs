@@ -4,7 +4,8 @@ use muda::{
     accelerator::Accelerator, Menu, MenuEvent, MenuEventReceiver, MenuItem, PredefinedMenuItem,
     Submenu,
 };
-use rinch_core::element::{Element, MenuItemCallback, MenuItemProps};
+use rinch_core::element::{Element, MenuItemCallback, MenuItemProps, Shortcut};
+use rinch_core::hotkey::{hotkeys, HotkeyBinding};
 use std::collections::HashMap;
 use std::str::FromStr;
 use winit::keyboard::KeyCode;
@@ -19,15 +20,77 @@ pub struct MenuManager {
     callbacks: Vec<MenuCallback>,
     /// Keyboard shortcuts mapped to menu item IDs for manual matching.
     shortcuts: Vec<(ParsedShortcut, muda::MenuId)>,
+    /// Items with physical shortcuts, relabelled as the layout is learned.
+    physical_items: Vec<(ParsedShortcut, MenuItem)>,
+    /// The character each key has typed on the user's layout.
+    layout: HashMap<KeyCode, char>,
 }
 
 /// A parsed keyboard shortcut for matching against keyboard events.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedShortcut {
     pub ctrl_or_cmd: bool,
     pub alt: bool,
     pub shift: bool,
+    /// The key's place, as on a US keyboard.
     pub key: KeyCode,
+    /// The character the key types, matched on the user's layout; `None`
+    /// for named keys, digits and physical shortcuts, which match `key`.
+    pub character: Option<char>,
+}
+
+impl ParsedShortcut {
+    /// Whether a key press triggers this shortcut.
+    pub fn matches(&self, press: &KeyPress) -> bool {
+        if (self.ctrl_or_cmd, self.alt, self.shift) != (press.ctrl_or_cmd, press.alt, press.shift) {
+            return false;
+        }
+        match (self.character, press.character) {
+            // Latin layouts match by character, wherever the key is
+            (Some(expected), Some(typed)) if typed.is_ascii() => {
+                typed.to_ascii_lowercase() == expected
+            }
+            // Other layouts match by place
+            _ => self.key == press.key,
+        }
+    }
+}
+
+/// A key press, for matching against shortcuts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub ctrl_or_cmd: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// The key's place.
+    pub key: KeyCode,
+    /// The character the key types on the user's layout, ignoring
+    /// modifiers, if it types one.
+    pub character: Option<char>,
+}
+
+impl KeyPress {
+    /// Whether the key types `letter` (or, on layouts without Latin
+    /// letters, is where `letter` is on a US keyboard).
+    pub fn is_letter(&self, letter: char) -> bool {
+        match self.character.filter(char::is_ascii) {
+            Some(typed) => typed.to_ascii_lowercase() == letter,
+            None => us_character(self.key) == Some(letter),
+        }
+    }
+}
+
+impl From<&ParsedShortcut> for KeyPress {
+    /// The press of a shortcut's keys on a US keyboard.
+    fn from(shortcut: &ParsedShortcut) -> Self {
+        Self {
+            ctrl_or_cmd: shortcut.ctrl_or_cmd,
+            alt: shortcut.alt,
+            shift: shortcut.shift,
+            key: shortcut.key,
+            character: shortcut.character.or_else(|| us_character(shortcut.key)),
+        }
+    }
 }
 
 /// Stores menu item information and callback.
@@ -44,6 +107,8 @@ impl MenuManager {
             item_callbacks: HashMap::new(),
             callbacks: Vec::new(),
             shortcuts: Vec::new(),
+            physical_items: Vec::new(),
+            layout: HashMap::new(),
         }
     }
 
@@ -102,11 +167,17 @@ impl MenuManager {
 
     /// Build a MenuItem from MenuItemProps.
     fn build_menu_item(&mut self, props: &MenuItemProps) -> MenuItem {
-        // Parse accelerator from shortcut string
-        let accelerator = props
-            .shortcut
-            .as_ref()
-            .and_then(|s| parse_shortcut(s));
+        let parsed = props.shortcut.as_ref().and_then(parse_menu_shortcut);
+
+        // Parse accelerator from shortcut string. Physical shortcuts show
+        // the key the layout has in their place.
+        let accelerator = match (&props.shortcut, &parsed) {
+            (Some(shortcut), Some(parsed)) if shortcut.physical => {
+                accelerator(parsed, self.layout.get(&parsed.key).copied())
+            }
+            (Some(shortcut), _) => parse_shortcut(&shortcut.keys),
+            (None, _) => None,
+        };
 
         let item = MenuItem::new(&props.label, props.enabled, accelerator);
 
@@ -119,10 +190,15 @@ impl MenuManager {
         self.item_callbacks.insert(item.id().clone(), callback_idx);
 
         // Store keyboard shortcut for manual matching
-        if let Some(shortcut_str) = &props.shortcut {
-            if let Some(parsed) = parse_shortcut_for_matching(shortcut_str) {
-                self.shortcuts.push((parsed, item.id().clone()));
+        if let Some(parsed) = parsed {
+            if props
+                .shortcut
+                .as_ref()
+                .is_some_and(|shortcut| shortcut.physical)
+            {
+                self.physical_items.push((parsed.clone(), item.clone()));
             }
+            self.shortcuts.push((parsed, item.id().clone()));
         }
 
         item
@@ -201,26 +277,26 @@ impl MenuManager {
     ///
     /// Returns the menu ID if a match is found, allowing the caller to
     /// trigger the appropriate menu event.
-    pub fn match_shortcut(
-        &self,
-        ctrl: bool,
-        meta: bool,
-        alt: bool,
-        shift: bool,
-        key: KeyCode,
-    ) -> Option<muda::MenuId> {
-        let ctrl_or_cmd = ctrl || meta;
-
-        for (shortcut, menu_id) in &self.shortcuts {
-            if shortcut.ctrl_or_cmd == ctrl_or_cmd
-                && shortcut.alt == alt
-                && shortcut.shift == shift
-                && shortcut.key == key
-            {
-                return Some(menu_id.clone());
+    pub fn match_shortcut(&self, press: &KeyPress) -> Option<muda::MenuId> {
+        self.shortcuts
+            .iter()
+            .find(|(shortcut, _)| shortcut.matches(press))
+            .map(|(_, menu_id)| menu_id.clone())
+    }
+
+    /// Remember what a key typed, relabelling physical shortcuts on it.
+    pub fn learn_layout(&mut self, press: &KeyPress) {
+        let Some(character) = press.character.filter(|c| !c.is_control()) else {
+            return;
+        };
+        if self.layout.insert(press.key, character) == Some(character) {
+            return;
+        }
+        for (shortcut, item) in &self.physical_items {
+            if shortcut.key == press.key {
+                let _ = item.set_accelerator(accelerator(shortcut, Some(character)));
             }
         }
-        None
     }
 }
 
@@ -242,8 +318,88 @@ fn parse_shortcut(shortcut: &str) -> Option<Accelerator> {
     Accelerator::from_str(&normalized).ok()
 }
 
+/// The accelerator muda shows for a shortcut, labelled with the character
+/// the layout types in its place if known.
+fn accelerator(shortcut: &ParsedShortcut, label: Option<char>) -> Option<Accelerator> {
+    let mut keys = String::new();
+    if shortcut.ctrl_or_cmd {
+        keys.push_str("CmdOrCtrl+");
+    }
+    if shortcut.alt {
+        keys.push_str("Alt+");
+    }
+    if shortcut.shift {
+        keys.push_str("Shift+");
+    }
+    match label.or(shortcut.character) {
+        Some(c) if c.is_ascii_alphanumeric() => keys.push(c.to_ascii_uppercase()),
+        _ => keys.push_str(&format!("{:?}", shortcut.key)),
+    }
+    Accelerator::from_str(&keys).ok()
+}
+
+/// Parse a menu item's shortcut for keyboard event matching.
+pub(crate) fn parse_menu_shortcut(shortcut: &Shortcut) -> Option<ParsedShortcut> {
+    let mut parsed = parse_shortcut_for_matching(&shortcut.keys)?;
+    if shortcut.physical {
+        parsed.character = None;
+    }
+    Some(parsed)
+}
+
+/// Whether a key typed text with AltGr, which Windows reports as Ctrl+Alt.
+/// The `use_hotkey` hotkeys registered by `root` (`None` for the app) that
+/// a key press triggers. Those without Ctrl, Cmd or Alt don't fire while
+/// `typing` in a text field.
+pub(crate) fn matching_hotkeys(
+    press: &KeyPress,
+    root: Option<u64>,
+    typing: bool,
+) -> Vec<HotkeyBinding> {
+    if typing && !press.ctrl_or_cmd && !press.alt {
+        return Vec::new();
+    }
+    hotkeys()
+        .into_iter()
+        .filter(|hotkey| hotkey.root == root)
+        .filter(|hotkey| {
+            parse_shortcut_for_matching(&hotkey.shortcut).is_some_and(|s| s.matches(press))
+        })
+        .collect()
+}
+
+/// AltGr combinations type characters rather than trigger shortcuts.
+pub(crate) fn typed_with_alt_graph(ctrl: bool, alt: bool, text: Option<&str>) -> bool {
+    ctrl && alt && text.is_some_and(|text| text.chars().any(|c| !c.is_control()))
+}
+
+/// The character a key types on a US keyboard.
+fn us_character(key: KeyCode) -> Option<char> {
+    let name = format!("{key:?}");
+    if let Some(letter) = name.strip_prefix("Key") {
+        return letter.chars().next().map(|c| c.to_ascii_lowercase());
+    }
+    if let Some(digit) = name.strip_prefix("Digit") {
+        return digit.chars().next();
+    }
+    Some(match key {
+        KeyCode::Equal => '=',
+        KeyCode::Minus => '-',
+        KeyCode::Comma => ',',
+        KeyCode::Period => '.',
+        KeyCode::Slash => '/',
+        KeyCode::Backslash => '\\',
+        KeyCode::Semicolon => ';',
+        KeyCode::Quote => '\'',
+        KeyCode::Backquote => '`',
+        KeyCode::BracketLeft => '[',
+        KeyCode::BracketRight => ']',
+        _ => return None,
+    })
+}
+
 /// Parse a shortcut string into a ParsedShortcut for keyboard event matching.
-fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
+pub(crate) fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
     let parts: Vec<&str> = shortcut.split('+').collect();
     if parts.is_empty() {
         return None;
@@ -264,7 +420,14 @@ fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
         }
     }
 
-    let key = match key_str.to_uppercase().as_str() {
+    // US key names, such as `KeyZ` and `Digit1`, name the same keys
+    let upper = key_str.to_uppercase();
+    let name = upper
+        .strip_prefix("KEY")
+        .or_else(|| upper.strip_prefix("DIGIT"))
+        .filter(|rest| rest.len() == 1)
+        .unwrap_or(&upper);
+    let key = match name {
         "A" => KeyCode::KeyA,
         "B" => KeyCode::KeyB,
         "C" => KeyCode::KeyC,
@@ -303,6 +466,15 @@ fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
         "9" => KeyCode::Digit9,
         "=" | "EQUAL" | "PLUS" => KeyCode::Equal,
         "-" | "MINUS" => KeyCode::Minus,
+        "," | "COMMA" => KeyCode::Comma,
+        "." | "PERIOD" => KeyCode::Period,
+        "/" | "SLASH" => KeyCode::Slash,
+        "\\" | "BACKSLASH" => KeyCode::Backslash,
+        ";" | "SEMICOLON" => KeyCode::Semicolon,
+        "'" | "QUOTE" => KeyCode::Quote,
+        "`" | "BACKQUOTE" => KeyCode::Backquote,
+        "[" | "BRACKETLEFT" => KeyCode::BracketLeft,
+        "]" | "BRACKETRIGHT" => KeyCode::BracketRight,
         "F1" => KeyCode::F1,
         "F2" => KeyCode::F2,
         "F3" => KeyCode::F3,
@@ -332,10 +504,97 @@ fn parse_shortcut_for_matching(shortcut: &str) -> Option<ParsedShortcut> {
         _ => return None,
     };
 
+    // Digits match by place: AZERTY types them with Shift
+    let character = us_character(key).filter(|c| !c.is_ascii_digit());
+
     Some(ParsedShortcut {
         ctrl_or_cmd,
         alt,
         shift,
         key,
+        character,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A press of `key` that types `character`, with Ctrl.
+    fn ctrl(key: KeyCode, character: Option<char>) -> KeyPress {
+        KeyPress {
+            ctrl_or_cmd: true,
+            alt: false,
+            shift: false,
+            key,
+            character,
+        }
+    }
+
+    #[test]
+    fn letters_match_by_character_on_latin_layouts() {
+        let undo = parse_shortcut_for_matching("Cmd+Z").unwrap();
+        let close = parse_shortcut_for_matching("Cmd+W").unwrap();
+        // AZERTY types z where US has w
+        assert!(undo.matches(&ctrl(KeyCode::KeyW, Some('z'))));
+        assert!(!close.matches(&ctrl(KeyCode::KeyW, Some('z'))));
+        assert!(!undo.matches(&ctrl(KeyCode::KeyZ, Some('w'))));
+        // Cyrillic layouts match by place
+        assert!(undo.matches(&ctrl(KeyCode::KeyZ, Some('я'))));
+        assert!(!undo.matches(&ctrl(KeyCode::KeyW, Some('ц'))));
+        // Digits match by place, whatever AZERTY types there
+        let one = parse_shortcut_for_matching("Ctrl+1").unwrap();
+        assert!(one.matches(&ctrl(KeyCode::Digit1, Some('&'))));
+        let shifted = KeyPress {
+            shift: true,
+            ..ctrl(KeyCode::Digit1, Some('&'))
+        };
+        assert!(!one.matches(&shifted));
+    }
+
+    #[test]
+    fn physical_shortcuts_match_by_place() {
+        let shortcut = parse_menu_shortcut(&Shortcut::physical("Ctrl+KeyZ")).unwrap();
+        assert!(shortcut.matches(&ctrl(KeyCode::KeyZ, Some('w'))));
+        assert!(!shortcut.matches(&ctrl(KeyCode::KeyW, Some('z'))));
+        let press = KeyPress::from(&parse_shortcut_for_matching("Ctrl+Shift+;").unwrap());
+        assert_eq!(
+            (press.key, press.character),
+            (KeyCode::Semicolon, Some(';'))
+        );
+        assert!(press.shift);
+    }
+
+    #[test]
+    fn alt_graph_text_is_not_a_shortcut() {
+        assert!(typed_with_alt_graph(true, true, Some("@")));
+        assert!(!typed_with_alt_graph(true, true, None));
+        assert!(!typed_with_alt_graph(false, true, Some("@")));
+        assert!(ctrl(KeyCode::KeyW, Some('z')).is_letter('z'));
+        assert!(ctrl(KeyCode::KeyC, Some('с')).is_letter('c'));
+    }
+
+    #[test]
+    fn hotkeys_match_their_roots_presses() {
+        use rinch_core::hooks::{begin_render, end_render, render_root, use_hotkey};
+
+        begin_render();
+        use_hotkey("Ctrl+K", || {});
+        use_hotkey("Escape", || {});
+        end_render();
+        render_root(7, || use_hotkey("Ctrl+K", || {}));
+
+        let k = ctrl(KeyCode::KeyK, Some('k'));
+        let escape = KeyPress {
+            ctrl_or_cmd: false,
+            ..ctrl(KeyCode::Escape, None)
+        };
+        assert_eq!(matching_hotkeys(&k, None, false).len(), 1);
+        assert_eq!(matching_hotkeys(&k, Some(7), false).len(), 1);
+        assert!(matching_hotkeys(&k, Some(8), false).is_empty());
+        assert_eq!(matching_hotkeys(&escape, None, false).len(), 1);
+        // Plain keys are left to text fields
+        assert!(matching_hotkeys(&escape, None, true).is_empty());
+        assert_eq!(matching_hotkeys(&k, None, true).len(), 1);
+    }
+}
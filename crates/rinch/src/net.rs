@@ -0,0 +1,18 @@
+//! The background runtime for network features.
+
+use std::sync::OnceLock;
+
+/// The tokio runtime [`fetch`](crate::fetch) requests and
+/// [`websocket`](crate::websocket) connections run on, started when first
+/// needed.
+pub(crate) fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("rinch-net")
+            .enable_all()
+            .build()
+            .expect("couldn't start the network runtime")
+    })
+}
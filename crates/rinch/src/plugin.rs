@@ -0,0 +1,573 @@
+//! Plugins that extend an app without changing it.
+//!
+//! A plugin implements [`RinchPlugin`] and, when the app starts, registers
+//! what it contributes: components for the slots the app leaves open,
+//! stylesheets, commands, menus and background services. The app lists its
+//! plugins with [`App::with_plugin`](crate::app::App::with_plugin):
+//!
+//! ```ignore
+//! use rinch::app::App;
+//! use rinch::plugin::{PluginContext, RinchPlugin};
+//!
+//! struct GitPlugin;
+//!
+//! impl RinchPlugin for GitPlugin {
+//!     fn name(&self) -> &str {
+//!         "git"
+//!     }
+//!
+//!     fn register(&self, plugin: &mut PluginContext) {
+//!         plugin.component("sidebar", || rsx! { div { class: "git-status", "main, 2 changes" } });
+//!         plugin.stylesheet(".git-status { color: #7f849c; }");
+//!         plugin.command("git.pull", "Pull", || pull());
+//!         plugin.menu(|| rsx! {
+//!             Menu { label: "Git",
+//!                 MenuItem { label: "Pull", onclick: || { rinch::plugin::run_command("git.pull"); } }
+//!             }
+//!         });
+//!     }
+//! }
+//!
+//! fn main() {
+//!     App::new().with_plugin(GitPlugin).run(app);
+//! }
+//! ```
+//!
+//! The app places a slot's components with `PluginSlot { name: "sidebar" }`
+//! in `rsx!`. Plugins' menus are added to the app's `AppMenu`, into the
+//! menu with the same label if there is one.
+//!
+//! With the `dynamic-plugins` feature, plugins can also be loaded from
+//! shared libraries with [`load_plugin`]. A library links its own copy of
+//! rinch, with its own hooks, signals and event handlers, so what it
+//! registered with that copy would never reach the app. Loaded plugins
+//! implement [`DynamicPlugin`] instead, and contribute only stylesheets,
+//! commands and menu items that run commands.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rinch_core::element::Element;
+#[cfg(feature = "dynamic-plugins")]
+use rinch_core::element::{MenuItemCallback, MenuItemProps, MenuProps};
+
+/// A plugin: something that contributes to an app when it starts.
+pub trait RinchPlugin {
+    /// The plugin's name, for logs and [`plugins`].
+    fn name(&self) -> &str;
+
+    /// Register the plugin's contributions. Called once, before the app's
+    /// first render.
+    fn register(&self, plugin: &mut PluginContext);
+}
+
+/// A command a plugin registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    /// The command's ID, such as `git.pull`.
+    pub id: String,
+    /// What to show for it, such as in a command palette.
+    pub label: String,
+    /// The plugin that registered it.
+    pub plugin: String,
+}
+
+struct Command {
+    info: CommandInfo,
+    run: Rc<dyn Fn()>,
+}
+
+/// Everything plugins contributed.
+#[derive(Default)]
+struct Registry {
+    plugins: Vec<String>,
+    components: Vec<(String, Rc<dyn Fn() -> Element>)>,
+    commands: Vec<Command>,
+    menus: Vec<Rc<dyn Fn() -> Element>>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+/// What a plugin registers its contributions with. See
+/// [`RinchPlugin::register`].
+pub struct PluginContext {
+    plugin: String,
+    services: Vec<Box<dyn FnOnce()>>,
+}
+
+impl PluginContext {
+    /// Render `render` wherever the app puts `PluginSlot { name: slot }`,
+    /// after the components registered for it before. It can call hooks.
+    pub fn component(&mut self, slot: &str, render: impl Fn() -> Element + 'static) {
+        REGISTRY.with(|registry| {
+            registry
+                .borrow_mut()
+                .components
+                .push((slot.to_string(), Rc::new(render)));
+        });
+    }
+
+    /// Add a stylesheet to every window, at user-agent origin, so the
+    /// app's own styles win over it.
+    pub fn stylesheet(&mut self, css: impl Into<String>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::shell::add_user_agent_stylesheet(css);
+        #[cfg(target_arch = "wasm32")]
+        let _ = css;
+    }
+
+    /// Register a command the app can run with [`run_command`] and list
+    /// with [`commands`]. A later command with the same ID replaces it.
+    pub fn command(&mut self, id: &str, label: &str, run: impl Fn() + 'static) {
+        let command = Command {
+            info: CommandInfo {
+                id: id.to_string(),
+                label: label.to_string(),
+                plugin: self.plugin.clone(),
+            },
+            run: Rc::new(run),
+        };
+        REGISTRY.with(|registry| {
+            let commands = &mut registry.borrow_mut().commands;
+            commands.retain(|known| known.info.id != id);
+            commands.push(command);
+        });
+    }
+
+    /// Add the `Menu`s `render` returns to the app's `AppMenu` every
+    /// render. Items go into the app's menu of the same label, after a
+    /// separator, or into a new menu after the app's.
+    pub fn menu(&mut self, render: impl Fn() -> Element + 'static) {
+        REGISTRY.with(|registry| registry.borrow_mut().menus.push(Rc::new(render)));
+    }
+
+    /// Start a background service once the app is running, such as a
+    /// thread that watches files and sends results to the UI.
+    pub fn service(&mut self, start: impl FnOnce() + 'static) {
+        self.services.push(Box::new(start));
+    }
+}
+
+/// Register `plugin`'s contributions, returning the services to start.
+pub(crate) fn register(plugin: &dyn RinchPlugin) -> Vec<Box<dyn FnOnce()>> {
+    let mut context = PluginContext {
+        plugin: plugin.name().to_string(),
+        services: Vec::new(),
+    };
+    plugin.register(&mut context);
+    tracing::info!("Registered plugin {}", context.plugin);
+    REGISTRY.with(|registry| registry.borrow_mut().plugins.push(context.plugin));
+    context.services
+}
+
+/// Start services after the event loop starts, so they can reach it.
+pub(crate) fn start_services(services: Vec<Box<dyn FnOnce()>>) {
+    for start in services {
+        rinch_core::set_timeout(Duration::ZERO, start);
+    }
+}
+
+/// The names of the plugins registered, in order.
+pub fn plugins() -> Vec<String> {
+    REGISTRY.with(|registry| registry.borrow().plugins.clone())
+}
+
+/// The commands plugins registered, in order.
+pub fn commands() -> Vec<CommandInfo> {
+    REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .commands
+            .iter()
+            .map(|command| command.info.clone())
+            .collect()
+    })
+}
+
+/// Run the command with this ID. Returns `false` if no plugin registered
+/// one.
+pub fn run_command(id: &str) -> bool {
+    let run = REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .commands
+            .iter()
+            .find(|command| command.info.id == id)
+            .map(|command| command.run.clone())
+    });
+    match run {
+        Some(run) => {
+            run();
+            true
+        }
+        None => {
+            tracing::warn!("No plugin command {id}");
+            false
+        }
+    }
+}
+
+/// Render the components plugins registered for `slot`. `PluginSlot` in
+/// `rsx!` expands to it.
+pub fn render_slot(slot: &str) -> Element {
+    let components: Vec<_> = REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .components
+            .iter()
+            .filter(|(name, _)| name == slot)
+            .map(|(_, render)| render.clone())
+            .collect()
+    });
+    Element::Fragment(components.iter().map(|render| render()).collect())
+}
+
+/// Add plugins' menus to the first `AppMenu` at the top of the app's
+/// element tree. An app without an `AppMenu` gets none.
+pub(crate) fn merge_menus(root: Element) -> Element {
+    let renders: Vec<_> = REGISTRY.with(|registry| registry.borrow().menus.clone());
+    if renders.is_empty() {
+        return root;
+    }
+    let mut menus = Vec::new();
+    for render in renders {
+        flatten(render(), &mut menus);
+    }
+    add_menus(root, &mut menus)
+}
+
+/// Move `menus` into the first `AppMenu` in `element`.
+fn add_menus(element: Element, menus: &mut Vec<Element>) -> Element {
+    match element {
+        Element::AppMenu(props, mut children) => {
+            for menu in menus.drain(..) {
+                let Element::Menu(props, items) = menu else {
+                    continue;
+                };
+                let existing = children.iter_mut().find_map(|child| match child {
+                    Element::Menu(existing, existing_items) if existing.label == props.label => {
+                        Some(existing_items)
+                    }
+                    _ => None,
+                });
+                match existing {
+                    Some(existing_items) => {
+                        existing_items.push(Element::MenuSeparator);
+                        existing_items.extend(items);
+                    }
+                    None => children.push(Element::Menu(props, items)),
+                }
+            }
+            Element::AppMenu(props, children)
+        }
+        Element::Fragment(children) => Element::Fragment(
+            children
+                .into_iter()
+                .map(|child| add_menus(child, menus))
+                .collect(),
+        ),
+        element => element,
+    }
+}
+
+/// Collect the elements in `element`, looking through fragments.
+fn flatten(element: Element, out: &mut Vec<Element>) {
+    match element {
+        Element::Fragment(children) => {
+            for child in children {
+                flatten(child, out);
+            }
+        }
+        element => out.push(element),
+    }
+}
+
+/// A plugin loaded from a shared library with [`load_plugin`].
+///
+/// It can't render components, start services or call rinch: the library's
+/// copy of rinch isn't the app's. It registers through
+/// [`DynamicPluginContext`], whose calls go to the app's copy.
+#[cfg(feature = "dynamic-plugins")]
+pub trait DynamicPlugin {
+    /// The plugin's name, for logs and [`plugins`].
+    fn name(&self) -> &str;
+
+    /// Register the plugin's contributions. Called once, before the app's
+    /// first render.
+    fn register(&self, plugin: &mut dyn DynamicPluginContext);
+}
+
+/// What a [`DynamicPlugin`] registers its contributions with.
+#[cfg(feature = "dynamic-plugins")]
+pub trait DynamicPluginContext {
+    /// Add a stylesheet to every window, as [`PluginContext::stylesheet`]
+    /// does.
+    fn stylesheet(&mut self, css: &str);
+
+    /// Register a command, as [`PluginContext::command`] does. `run` is
+    /// the library's code, so it mustn't use signals or call rinch.
+    fn command(&mut self, id: &str, label: &str, run: Box<dyn Fn()>);
+
+    /// Add an item labelled `label` that runs `command` to the app's menu
+    /// labelled `menu`, or to a new menu after the app's.
+    fn menu_item(&mut self, menu: &str, label: &str, command: &str);
+}
+
+/// A [`DynamicPlugin`] from a library, registered as a plugin of the app.
+#[cfg(feature = "dynamic-plugins")]
+struct Loaded(Box<dyn DynamicPlugin>);
+
+#[cfg(feature = "dynamic-plugins")]
+impl RinchPlugin for Loaded {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn register(&self, plugin: &mut PluginContext) {
+        let mut registrar = Registrar {
+            context: plugin,
+            items: Vec::new(),
+        };
+        self.0.register(&mut registrar);
+
+        // One menu per label, so its items aren't split by separators
+        let mut menus: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        for (menu, label, command) in registrar.items {
+            match menus.iter_mut().find(|(known, _)| *known == menu) {
+                Some((_, items)) => items.push((label, command)),
+                None => menus.push((menu, vec![(label, command)])),
+            }
+        }
+        if menus.is_empty() {
+            return;
+        }
+        plugin.menu(move || {
+            Element::Fragment(
+                menus
+                    .iter()
+                    .map(|(label, items)| {
+                        Element::Menu(
+                            MenuProps {
+                                label: label.clone(),
+                            },
+                            items
+                                .iter()
+                                .map(|(label, command)| command_item(label, command))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            )
+        });
+    }
+}
+
+/// Registers a [`DynamicPlugin`]'s contributions in the app's copy of
+/// rinch.
+#[cfg(feature = "dynamic-plugins")]
+struct Registrar<'a> {
+    context: &'a mut PluginContext,
+    /// Menu items, as `(menu, label, command)`.
+    items: Vec<(String, String, String)>,
+}
+
+#[cfg(feature = "dynamic-plugins")]
+impl DynamicPluginContext for Registrar<'_> {
+    fn stylesheet(&mut self, css: &str) {
+        self.context.stylesheet(css);
+    }
+
+    fn command(&mut self, id: &str, label: &str, run: Box<dyn Fn()>) {
+        self.context.command(id, label, run);
+    }
+
+    fn menu_item(&mut self, menu: &str, label: &str, command: &str) {
+        self.items
+            .push((menu.to_string(), label.to_string(), command.to_string()));
+    }
+}
+
+/// A menu item running a plugin command.
+#[cfg(feature = "dynamic-plugins")]
+fn command_item(label: &str, command: &str) -> Element {
+    let command = command.to_string();
+    Element::MenuItem(MenuItemProps {
+        label: label.to_string(),
+        onclick: Some(MenuItemCallback::new(move || {
+            run_command(&command);
+        })),
+        ..Default::default()
+    })
+}
+
+/// Load a plugin from a shared library, keeping the library loaded for the
+/// rest of the process. The library exports a [`DynamicPlugin`] with
+/// [`export_plugin!`](crate::export_plugin).
+///
+/// # Safety
+///
+/// Loading a library runs its initialisers. The library must be built by
+/// the same compiler, with the same version of rinch, as the app: plugins
+/// are passed as Rust trait objects, which have no stable ABI.
+#[cfg(all(feature = "dynamic-plugins", not(target_arch = "wasm32")))]
+pub unsafe fn load_plugin(
+    path: impl AsRef<std::ffi::OsStr>,
+) -> Result<Box<dyn RinchPlugin>, libloading::Error> {
+    // SAFETY: the caller vouches for the library
+    let library = unsafe { libloading::Library::new(path)? };
+    // SAFETY: `export_plugin!` defines the symbol with this signature
+    let plugin = unsafe {
+        let create: libloading::Symbol<fn() -> Box<dyn DynamicPlugin>> =
+            library.get(b"rinch_plugin")?;
+        create()
+    };
+    // The plugin's code lives in the library
+    std::mem::forget(library);
+    Ok(Box::new(Loaded(plugin)))
+}
+
+/// Export a [`DynamicPlugin`] from a shared library for [`load_plugin`] to
+/// find.
+///
+/// ```ignore
+/// rinch::export_plugin!(GitPlugin);
+/// ```
+#[cfg(feature = "dynamic-plugins")]
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:expr) => {
+        #[unsafe(no_mangle)]
+        pub fn rinch_plugin() -> ::std::boxed::Box<dyn $crate::plugin::DynamicPlugin> {
+            ::std::boxed::Box::new($plugin)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::element::{AppMenuProps, MenuItemProps, MenuProps};
+
+    fn menu(label: &str, items: &[&str]) -> Element {
+        Element::Menu(
+            MenuProps {
+                label: label.into(),
+            },
+            items
+                .iter()
+                .map(|label| {
+                    Element::MenuItem(MenuItemProps {
+                        label: label.to_string(),
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn labels(element: &Element) -> Vec<String> {
+        match element {
+            Element::Menu(props, items) => std::iter::once(props.label.clone())
+                .chain(items.iter().map(|item| match item {
+                    Element::MenuItem(props) => props.label.clone(),
+                    Element::MenuSeparator => "-".into(),
+                    _ => "?".into(),
+                }))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    struct Tools;
+
+    impl RinchPlugin for Tools {
+        fn name(&self) -> &str {
+            "tools"
+        }
+
+        fn register(&self, plugin: &mut PluginContext) {
+            plugin.component("sidebar", || Element::Html("<p>tools</p>".into()));
+            plugin.command("tools.run", "Run", || {});
+            plugin.menu(|| {
+                Element::Fragment(vec![menu("File", &["Export"]), menu("Tools", &["Run"])])
+            });
+            plugin.service(|| {});
+        }
+    }
+
+    #[cfg(feature = "dynamic-plugins")]
+    struct Formatter;
+
+    #[cfg(feature = "dynamic-plugins")]
+    impl DynamicPlugin for Formatter {
+        fn name(&self) -> &str {
+            "formatter"
+        }
+
+        fn register(&self, plugin: &mut dyn DynamicPluginContext) {
+            plugin.command("format.file", "Format File", Box::new(|| {}));
+            plugin.menu_item("Edit", "Format File", "format.file");
+            plugin.menu_item("Format", "Format File", "format.file");
+            plugin.menu_item("Format", "Settings", "format.settings");
+        }
+    }
+
+    #[cfg(feature = "dynamic-plugins")]
+    #[test]
+    fn loaded_plugins_add_commands_and_menu_items() {
+        register(&Loaded(Box::new(Formatter)));
+        assert_eq!(plugins(), ["formatter"]);
+        assert_eq!(commands()[0].id, "format.file");
+
+        let root = Element::AppMenu(AppMenuProps::default(), vec![menu("Edit", &["Undo"])]);
+        let Element::AppMenu(_, menus) = merge_menus(root) else {
+            panic!("the app menu stays");
+        };
+        let menus: Vec<_> = menus.iter().map(labels).collect();
+        assert_eq!(
+            menus,
+            [
+                vec!["Edit", "Undo", "-", "Format File"],
+                vec!["Format", "Format File", "Settings"]
+            ]
+        );
+    }
+
+    #[test]
+    fn plugins_contribute_to_slots_commands_and_menus() {
+        let services = register(&Tools);
+        assert_eq!(services.len(), 1);
+        assert_eq!(plugins(), ["tools"]);
+        assert_eq!(commands()[0].plugin, "tools");
+        assert!(run_command("tools.run"));
+        assert!(!run_command("tools.missing"));
+
+        let Element::Fragment(sidebar) = render_slot("sidebar") else {
+            panic!("slots render as fragments");
+        };
+        assert_eq!(sidebar.len(), 1);
+
+        let root = Element::Fragment(vec![Element::AppMenu(
+            AppMenuProps::default(),
+            vec![menu("File", &["Open"]), menu("Help", &["About"])],
+        )]);
+        let Element::Fragment(root) = merge_menus(root) else {
+            panic!("the root stays a fragment");
+        };
+        let Element::AppMenu(_, menus) = &root[0] else {
+            panic!("the app menu stays");
+        };
+        let menus: Vec<_> = menus.iter().map(labels).collect();
+        assert_eq!(
+            menus,
+            [
+                vec!["File", "Open", "-", "Export"],
+                vec!["Help", "About"],
+                vec!["Tools", "Run"]
+            ]
+        );
+    }
+}
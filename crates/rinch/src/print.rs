@@ -0,0 +1,581 @@
+//! Printing and PDF export.
+//!
+//! Enable with the `print` feature. [`export_pdf`] lays out the element a
+//! [`NodeRef`] is attached to on pages of the chosen size, rasterizes them
+//! with Vello on the CPU and writes them to a PDF; [`print_element`] does
+//! the same and sends the PDF to the printer.
+//!
+//! The element is laid out again at the page's width with the window's
+//! styles, so it can look different from on screen where its layout
+//! depends on the window's size. Pages break between boxes where one fits,
+//! so table rows and list items aren't cut in half, and a header (with
+//! `{page}` and `{pages}` filled in) can repeat at the top of every page.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::print::{export_pdf, PrintOptions};
+//!
+//! let invoice = use_node_ref();
+//! let save = {
+//!     let invoice = invoice.clone();
+//!     move || {
+//!         let options = PrintOptions {
+//!             header: Some("<p>Invoice 1024 - page {page} of {pages}</p>".into()),
+//!             ..Default::default()
+//!         };
+//!         if let Err(e) = export_pdf(&invoice, &options, "invoice.pdf") {
+//!             eprintln!("Couldn't export the invoice: {e}");
+//!         }
+//!     }
+//! };
+//! rsx! {
+//!     div { node_ref: invoice, class: "invoice", /* ... */ }
+//!     button { onclick: save, "Save as PDF" }
+//! }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use anyrender::{render_to_buffer, PaintScene};
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use blitz_dom::{BaseDocument, Document};
+use blitz_paint::paint_scene;
+use peniko::kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+use rinch_core::NodeRef;
+
+use crate::shell::relayout::{layout, window_with_ref};
+use crate::shell::scroll::{border_box_origin, ScrollWatch, Scroller};
+use crate::shell::stylesheets::split_styles;
+use crate::shell::text_effects::TextEffects;
+
+/// CSS pixels per point: CSS pixels are 1/96 inch, points 1/72.
+const PX_PER_PT: f64 = 96.0 / 72.0;
+
+/// Height headers are laid out in before they're measured, in CSS pixels.
+const HEADER_LAYOUT_HEIGHT: f64 = 1000.0;
+
+/// A paper size.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageSize {
+    /// 210 × 297 mm.
+    #[default]
+    A4,
+    /// 8.5 × 11 inches.
+    Letter,
+    /// A size in points (1/72 inch).
+    Custom { width: f64, height: f64 },
+}
+
+impl PageSize {
+    /// Width and height in points.
+    pub fn points(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+/// Page margins, in points (1/72 inch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageMargins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl PageMargins {
+    /// The same margin on every side.
+    pub fn all(margin: f64) -> Self {
+        Self {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+}
+
+impl Default for PageMargins {
+    /// Half an inch.
+    fn default() -> Self {
+        Self::all(36.0)
+    }
+}
+
+/// How to lay out printed pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintOptions {
+    pub page_size: PageSize,
+    pub margins: PageMargins,
+    /// HTML repeated at the top of every page, inside the margins, styled
+    /// by the window's stylesheets. `{page}` and `{pages}` are replaced by
+    /// the page number and count.
+    pub header: Option<String>,
+    /// Device pixels per CSS pixel the pages are rasterized at. 2.0, the
+    /// default, is 192 dpi.
+    pub scale: f64,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::default(),
+            margins: PageMargins::default(),
+            header: None,
+            scale: 2.0,
+        }
+    }
+}
+
+/// Why printing failed.
+#[derive(Debug)]
+pub enum PrintError {
+    /// The node ref isn't attached to an element in any window.
+    NotRendered,
+    /// The margins leave no room on the page for the content.
+    NoRoom,
+    /// The PDF couldn't be written.
+    Io(io::Error),
+    /// The printer command failed.
+    Printer(String),
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintError::NotRendered => write!(f, "the element isn't rendered in any window"),
+            PrintError::NoRoom => write!(f, "the margins leave no room for the content"),
+            PrintError::Io(e) => write!(f, "{e}"),
+            PrintError::Printer(e) => write!(f, "couldn't print: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrintError {}
+
+impl From<io::Error> for PrintError {
+    fn from(e: io::Error) -> Self {
+        PrintError::Io(e)
+    }
+}
+
+/// Write the element `node_ref` is attached to as a PDF at `path`,
+/// returning how many pages it took.
+pub fn export_pdf(
+    node_ref: &NodeRef,
+    options: &PrintOptions,
+    path: impl AsRef<Path>,
+) -> Result<usize, PrintError> {
+    let pages = render_pages(node_ref.id(), options)?;
+    std::fs::write(path, write_pdf(&pages, options.page_size.points()))?;
+    Ok(pages.len())
+}
+
+/// Print the element `node_ref` is attached to on the default printer.
+///
+/// The pages go to the printer as a PDF, through `lp` on macOS and Linux
+/// (CUPS), and through the PDF viewer's Print command on Windows, which
+/// may show its print dialog.
+pub fn print_element(node_ref: &NodeRef, options: &PrintOptions) -> Result<(), PrintError> {
+    let path = std::env::temp_dir().join(format!(
+        "rinch-print-{}-{}.pdf",
+        std::process::id(),
+        node_ref.id()
+    ));
+    export_pdf(node_ref, options, &path)?;
+    send_to_printer(&path)
+}
+
+/// A rasterized page, RGBA.
+struct Page {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Lay out and rasterize the pages for the element with `node_ref`.
+fn render_pages(node_ref: u64, options: &PrintOptions) -> Result<Vec<Page>, PrintError> {
+    let (props, html) = window_with_ref(node_ref).ok_or(PrintError::NotRendered)?;
+    let header = options
+        .header
+        .as_ref()
+        .map(|header| header_html(&html, header));
+
+    let (page_width, page_height) = options.page_size.points();
+    let margins = options.margins;
+    let content_width = (page_width - margins.left - margins.right) * PX_PER_PT;
+    let content_height = (page_height - margins.top - margins.bottom) * PX_PER_PT;
+    let scale = options.scale;
+
+    // The header's height comes off every page
+    let header_height = match &header {
+        Some(header) => {
+            let (doc, _) = layout(&props, header, content_width, HEADER_LAYOUT_HEIGHT, scale);
+            let root = doc.inner().root_element().id;
+            doc.inner()
+                .get_node(root)
+                .map_or(0.0, |node| node.final_layout.size.height as f64)
+        }
+        None => 0.0,
+    };
+    let body_height = content_height - header_height;
+    if content_width < 1.0 || body_height < 1.0 {
+        return Err(PrintError::NoRoom);
+    }
+
+    let (doc, text_effects) = layout(&props, &html, content_width, body_height, scale);
+    let (node_id, (left, top), (width, height), candidates) = {
+        let inner = doc.inner();
+        let node_id = ScrollWatch::new(&inner)
+            .node_for_ref(node_ref)
+            .ok_or(PrintError::NotRendered)?;
+        let size = inner.get_node(node_id).map_or((0.0, 0.0), |node| {
+            (
+                node.final_layout.size.width as f64,
+                node.final_layout.size.height as f64,
+            )
+        });
+        (
+            node_id,
+            border_box_origin(&inner, node_id),
+            size,
+            box_tops(&inner, node_id),
+        )
+    };
+    tracing::debug!("Printing node {node_id}: {width}x{height} at ({left}, {top})");
+
+    let breaks = page_breaks(top, top + height, body_height, &candidates);
+    let device = |length: f64| (length * scale).round() as u32;
+    let mut pages = Vec::with_capacity(breaks.len());
+    for (index, &(start, end)) in breaks.iter().enumerate() {
+        let mut page = Page {
+            width: device(page_width * PX_PER_PT),
+            height: device(page_height * PX_PER_PT),
+            pixels: vec![
+                255;
+                (device(page_width * PX_PER_PT) * device(page_height * PX_PER_PT) * 4)
+                    as usize
+            ],
+        };
+        let origin = (
+            device(margins.left * PX_PER_PT),
+            device(margins.top * PX_PER_PT),
+        );
+
+        if let Some(header) = &header {
+            let header = header
+                .replace("{page}", &(index + 1).to_string())
+                .replace("{pages}", &breaks.len().to_string());
+            let (header_doc, header_effects) =
+                layout(&props, &header, content_width, header_height, scale);
+            let pixels = rasterize(
+                &*header_doc,
+                &header_effects,
+                device(content_width),
+                device(header_height),
+            );
+            page.blit(
+                &pixels,
+                device(content_width),
+                device(header_height),
+                origin,
+            );
+        }
+
+        Scroller::Viewport.set_offset(&mut doc.inner_mut(), (left, start));
+        let pixels = rasterize(
+            &*doc,
+            &text_effects,
+            device(content_width),
+            device(body_height),
+        );
+        // Only the element, not what's beside or after it
+        let clip = (
+            device(width.min(content_width)),
+            device(end - start).min(device(body_height)),
+        );
+        page.blit_clipped(
+            &pixels,
+            device(content_width),
+            clip,
+            (origin.0, origin.1 + device(header_height)),
+        );
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// A header with the window's stylesheets.
+fn header_html(window_html: &str, header: &str) -> String {
+    let mut html = String::new();
+    for css in split_styles(window_html).1 {
+        html.push_str("<style>");
+        html.push_str(&css);
+        html.push_str("</style>");
+    }
+    html.push_str(header);
+    html
+}
+
+/// Paint a document on white, `width` by `height` device pixels.
+fn rasterize(doc: &dyn Document, text_effects: &TextEffects, width: u32, height: u32) -> Vec<u8> {
+    let inner = doc.inner();
+    let scale = inner.viewport().scale_f64();
+    render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| {
+            let page = Rect::new(0.0, 0.0, width as f64, height as f64);
+            scene.fill(Fill::NonZero, Affine::IDENTITY, Color::WHITE, None, &page);
+            paint_scene(scene, &inner, scale, width, height);
+            text_effects.paint(scene, &inner, scale);
+        },
+        width,
+        height,
+    )
+}
+
+/// Where the boxes inside an element start, for page breaks.
+fn box_tops(doc: &BaseDocument, node_id: usize) -> Vec<f64> {
+    let mut tops = Vec::new();
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = doc.get_node(id) else {
+            continue;
+        };
+        if id != node_id && node.element_data().is_some() && node.final_layout.size.height > 0.0 {
+            tops.push(border_box_origin(doc, id).1);
+        }
+        stack.extend(node.children.iter());
+    }
+    tops
+}
+
+/// Split `top..bottom` into pages at most `page_height` tall, breaking at
+/// the last box that starts on a page, unless that would leave the page
+/// less than half full.
+fn page_breaks(top: f64, bottom: f64, page_height: f64, box_tops: &[f64]) -> Vec<(f64, f64)> {
+    let mut pages = Vec::new();
+    let mut start = top;
+    while bottom - start > 0.5 {
+        let limit = start + page_height;
+        if limit >= bottom {
+            pages.push((start, bottom));
+            break;
+        }
+        let end = box_tops
+            .iter()
+            .copied()
+            .filter(|&box_top| box_top > start + page_height / 2.0 && box_top <= limit)
+            .reduce(f64::max)
+            .unwrap_or(limit);
+        pages.push((start, end));
+        start = end;
+    }
+    if pages.is_empty() {
+        pages.push((top, top));
+    }
+    pages
+}
+
+impl Page {
+    /// Copy a `width`-pixel-wide RGBA image onto the page at `at`.
+    fn blit(&mut self, pixels: &[u8], width: u32, height: u32, at: (u32, u32)) {
+        self.blit_clipped(pixels, width, (width, height), at);
+    }
+
+    /// Copy the top-left `clip` of a `width`-pixel-wide RGBA image onto
+    /// the page at `at`.
+    fn blit_clipped(&mut self, pixels: &[u8], width: u32, clip: (u32, u32), (x, y): (u32, u32)) {
+        let columns = clip.0.min(width).min(self.width.saturating_sub(x)) as usize;
+        let rows = clip.1.min(self.height.saturating_sub(y));
+        for row in 0..rows {
+            let from = (row * width) as usize * 4;
+            let Some(source) = pixels.get(from..from + columns * 4) else {
+                break;
+            };
+            let to = (((y + row) * self.width + x) * 4) as usize;
+            self.pixels[to..to + columns * 4].copy_from_slice(source);
+        }
+    }
+}
+
+/// A PDF with one full-page image per page.
+fn write_pdf(pages: &[Page], (page_width, page_height): (f64, f64)) -> Vec<u8> {
+    let mut pdf = Vec::new();
+    let mut offsets = Vec::new();
+    pdf.extend_from_slice(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n");
+
+    // Objects: 1 catalog, 2 page tree, then a page, its contents and its
+    // image for each page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 3 + index * 3).collect();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{id} 0 R")).collect();
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .as_bytes(),
+    );
+    for (page, id) in pages.iter().zip(&page_ids) {
+        object(
+            &mut pdf,
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {page_width} {page_height}] \
+                 /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                id + 2,
+                id + 1
+            )
+            .as_bytes(),
+        );
+        let contents = format!("q {page_width} 0 0 {page_height} 0 0 cm /Im0 Do Q");
+        object(
+            &mut pdf,
+            format!(
+                "<< /Length {} >>\nstream\n{contents}\nendstream",
+                contents.len()
+            )
+            .as_bytes(),
+        );
+        let rgb: Vec<u8> = page
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        let data = miniz_oxide::deflate::compress_to_vec_zlib(&rgb, 6);
+        let mut image = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+            page.width,
+            page.height,
+            data.len()
+        )
+        .into_bytes();
+        image.extend_from_slice(&data);
+        image.extend_from_slice(b"\nendstream");
+        object(&mut pdf, &image);
+    }
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).as_bytes(),
+    );
+    for offset in &offsets {
+        pdf.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            offsets.len() + 1
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// Send a PDF to the default printer.
+fn send_to_printer(path: &Path) -> Result<(), PrintError> {
+    #[cfg(windows)]
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command"])
+        .arg(format!(
+            "Start-Process -FilePath '{}' -Verb Print",
+            path.display().to_string().replace('\'', "''")
+        ))
+        .output();
+    #[cfg(not(windows))]
+    let output = Command::new("lp").arg(path).output();
+
+    match output {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(PrintError::Printer(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+        Err(e) => Err(PrintError::Printer(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_break_before_boxes_that_would_be_cut() {
+        // Rows 40px tall from 100, on 100px pages: the fourth row starts
+        // at 220, so the first page ends there rather than at 200
+        let rows: Vec<f64> = (0..10).map(|row| 100.0 + row as f64 * 40.0).collect();
+        assert_eq!(
+            page_breaks(100.0, 500.0, 130.0, &rows),
+            vec![
+                (100.0, 220.0),
+                (220.0, 340.0),
+                (340.0, 460.0),
+                (460.0, 500.0)
+            ]
+        );
+        // With nothing to break at, pages are cut at their height
+        assert_eq!(
+            page_breaks(0.0, 250.0, 100.0, &[]),
+            vec![(0.0, 100.0), (100.0, 200.0), (200.0, 250.0)]
+        );
+        // A box starting early on a page isn't worth breaking at
+        assert_eq!(
+            page_breaks(0.0, 150.0, 100.0, &[10.0]),
+            vec![(0.0, 100.0), (100.0, 150.0)]
+        );
+    }
+
+    #[test]
+    fn pdfs_have_a_page_per_image() {
+        let page = |color: u8| Page {
+            width: 2,
+            height: 2,
+            pixels: vec![color; 16],
+        };
+        let pdf = write_pdf(&[page(0), page(255)], PageSize::Letter.points());
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Kids [3 0 R 6 0 R] /Count 2"));
+        assert!(text.contains("/MediaBox [0 0 612 792]"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+
+        // The cross-reference table points at each object
+        let xref = text.find("xref\n").unwrap();
+        let entries: Vec<usize> = text[xref..]
+            .lines()
+            .skip(3)
+            .take(8)
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        for (index, offset) in entries.iter().enumerate() {
+            assert!(pdf[*offset..].starts_with(format!("{} 0 obj", index + 1).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn clipped_copies_stay_inside_the_page() {
+        let mut page = Page {
+            width: 3,
+            height: 2,
+            pixels: vec![255; 24],
+        };
+        page.blit_clipped(&[0; 16], 2, (2, 2), (2, 1));
+        let black: Vec<bool> = page.pixels.chunks(4).map(|pixel| pixel[0] == 0).collect();
+        assert_eq!(black, [false, false, false, false, false, true]);
+    }
+}
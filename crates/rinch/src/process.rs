@@ -0,0 +1,412 @@
+//! Running child processes.
+//!
+//! [`use_process`] runs a command and streams its output into the app, for
+//! panels wrapping command-line tools:
+//!
+//! ```ignore
+//! use std::process::Command;
+//! use rinch::process::{use_process, ProcessStatus};
+//!
+//! fn build_log() -> Element {
+//!     let mut command = Command::new("cargo");
+//!     command.arg("build").current_dir("/path/to/project");
+//!     let build = use_process(command);
+//!     let status = match build.status() {
+//!         ProcessStatus::Running => "Building...".to_string(),
+//!         ProcessStatus::Exited(status) if status.success() => "Done".to_string(),
+//!         ProcessStatus::Exited(status) => format!("Failed ({status})"),
+//!         ProcessStatus::Failed(e) => format!("Couldn't run cargo: {e}"),
+//!     };
+//!     let rebuild = {
+//!         let build = build.clone();
+//!         move || build.restart()
+//!     };
+//!     rsx! {
+//!         p { {status} }
+//!         pre { {build.stderr().join("\n")} }
+//!         button { onclick: rebuild, "Rebuild" }
+//!     }
+//! }
+//! ```
+//!
+//! - The command is run on the first render; later renders' commands are
+//!   ignored. [`Process::restart`] runs it again.
+//! - Output is read line by line on background threads and handed to the
+//!   UI thread, re-rendering the app as lines arrive. stdin is closed.
+//! - The process is killed when its component goes away.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use rinch_core::{untracked, use_ref, Signal};
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::RinchEvent;
+
+/// How often an exited process's status is checked for, once its output
+/// ends.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Which output a line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A line of a process's output, without its line ending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputLine {
+    pub stream: Stream,
+    pub text: String,
+}
+
+/// Where a process stands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessStatus {
+    Running,
+    /// It exited, or was killed.
+    Exited(ExitStatus),
+    /// It couldn't be started, such as when the program doesn't exist.
+    Failed(String),
+}
+
+/// What a process's threads report to the UI thread.
+#[derive(Debug)]
+enum ProcessEvent {
+    Line(OutputLine),
+    Exited(ExitStatus),
+}
+
+thread_local! {
+    /// Running processes, by ID.
+    static PROCESSES: RefCell<HashMap<u64, Weak<ProcessState>>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Process events waiting for the event loop.
+static EVENTS: Mutex<Vec<(u64, ProcessEvent)>> = Mutex::new(Vec::new());
+
+/// A process run by [`use_process`]. Cheap to clone; clones share it.
+#[derive(Clone)]
+pub struct Process {
+    state: Rc<ProcessState>,
+}
+
+impl Process {
+    /// Where the process stands. Reactive, like the other getters.
+    pub fn status(&self) -> ProcessStatus {
+        self.state.status.get()
+    }
+
+    /// Whether the process is still running.
+    pub fn is_running(&self) -> bool {
+        self.status() == ProcessStatus::Running
+    }
+
+    /// The exit code, once the process has exited with one.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self.status() {
+            ProcessStatus::Exited(status) => status.code(),
+            _ => None,
+        }
+    }
+
+    /// All output lines so far, stdout and stderr interleaved as they
+    /// arrived.
+    pub fn lines(&self) -> Vec<OutputLine> {
+        self.state.lines.get()
+    }
+
+    /// The stdout lines so far.
+    pub fn stdout(&self) -> Vec<String> {
+        self.stream(Stream::Stdout)
+    }
+
+    /// The stderr lines so far.
+    pub fn stderr(&self) -> Vec<String> {
+        self.stream(Stream::Stderr)
+    }
+
+    fn stream(&self, stream: Stream) -> Vec<String> {
+        self.state.lines.with(|lines| {
+            lines
+                .iter()
+                .filter(|line| line.stream == stream)
+                .map(|line| line.text.clone())
+                .collect()
+        })
+    }
+
+    /// Kill the process, if it's running. Its status becomes `Exited` once
+    /// it's gone.
+    pub fn kill(&self) {
+        self.state.kill();
+    }
+
+    /// Kill the process if it's running and run the command again, with
+    /// the output cleared.
+    pub fn restart(&self) {
+        self.state.start(crate::windows::event_proxy());
+    }
+}
+
+/// Run `command` with its output piped to the app, killing it when the
+/// calling component goes away. Only the first render's command is run.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_process(command: Command) -> Process {
+    let mut command = Some(command);
+    let state = use_ref(|| {
+        let state = Rc::new(ProcessState::new(command.take().expect("taken once")));
+        state.start(crate::windows::event_proxy());
+        state
+    })
+    .get();
+    Process { state }
+}
+
+/// One [`use_process`]'s state.
+struct ProcessState {
+    command: RefCell<Command>,
+    /// The running process's ID and handle.
+    child: RefCell<Option<(u64, Arc<Mutex<Child>>)>>,
+    status: Signal<ProcessStatus>,
+    lines: Signal<Vec<OutputLine>>,
+}
+
+impl ProcessState {
+    fn new(command: Command) -> Self {
+        Self {
+            command: RefCell::new(command),
+            child: RefCell::new(None),
+            status: Signal::new(ProcessStatus::Running),
+            lines: Signal::new(Vec::new()),
+        }
+    }
+
+    /// Run the command, killing the process already running.
+    fn start(self: &Rc<Self>, proxy: Option<EventLoopProxy<RinchEvent>>) {
+        self.abandon();
+        if untracked(|| self.lines.with(|lines| !lines.is_empty())) {
+            self.lines.set(Vec::new());
+        }
+        let spawned = self
+            .command
+            .borrow_mut()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                self.set_status(ProcessStatus::Failed(e.to_string()));
+                return;
+            }
+        };
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        PROCESSES.with(|processes| processes.borrow_mut().insert(id, Rc::downgrade(self)));
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|out| read_lines(id, out, Stream::Stdout, &proxy)),
+            child
+                .stderr
+                .take()
+                .map(|err| read_lines(id, err, Stream::Stderr, &proxy)),
+        ];
+        let child = Arc::new(Mutex::new(child));
+        wait_for_exit(id, child.clone(), readers.into_iter().flatten(), proxy);
+        self.child.replace(Some((id, child)));
+        self.set_status(ProcessStatus::Running);
+    }
+
+    /// Kill the running process, if any.
+    fn kill(&self) {
+        if let Some((_, child)) = &*self.child.borrow() {
+            let _ = child.lock().unwrap_or_else(|e| e.into_inner()).kill();
+        }
+    }
+
+    /// Kill the running process, if any, and forget it: its remaining
+    /// output and exit are ignored.
+    fn abandon(&self) {
+        self.kill();
+        if let Some((id, _)) = self.child.take() {
+            let _ = PROCESSES.try_with(|processes| processes.borrow_mut().remove(&id));
+        }
+    }
+
+    fn set_status(&self, status: ProcessStatus) {
+        if untracked(|| self.status.get()) != status {
+            self.status.set(status);
+        }
+    }
+
+    fn apply(&self, event: ProcessEvent) {
+        match event {
+            ProcessEvent::Line(line) => self.lines.update(|lines| lines.push(line)),
+            ProcessEvent::Exited(status) => {
+                self.child.take();
+                self.set_status(ProcessStatus::Exited(status));
+            }
+        }
+    }
+}
+
+impl Drop for ProcessState {
+    fn drop(&mut self) {
+        self.abandon();
+    }
+}
+
+/// Hand process output and exits to their [`use_process`]es (called by the
+/// runtime on [`RinchEvent::ProcessOutput`]).
+pub(crate) fn deliver() {
+    let events = std::mem::take(&mut *EVENTS.lock().unwrap_or_else(|e| e.into_inner()));
+    for (id, event) in events {
+        let state = PROCESSES.with(|processes| {
+            let mut processes = processes.borrow_mut();
+            let state = processes.get(&id).and_then(Weak::upgrade);
+            if matches!(event, ProcessEvent::Exited(_)) {
+                processes.remove(&id);
+            }
+            state
+        });
+        if let Some(state) = state {
+            state.apply(event);
+        }
+    }
+}
+
+/// Queue `event` for the UI thread, waking it if nothing else is queued.
+fn emit(id: u64, event: ProcessEvent, proxy: &Option<EventLoopProxy<RinchEvent>>) {
+    let mut events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    // A queue with events in it has woken the loop already
+    let wake = events.is_empty();
+    events.push((id, event));
+    drop(events);
+    if wake && let Some(proxy) = proxy {
+        let _ = proxy.send_event(RinchEvent::ProcessOutput);
+    }
+}
+
+/// Read `output` line by line on a background thread.
+fn read_lines(
+    id: u64,
+    output: impl Read + Send + 'static,
+    stream: Stream,
+    proxy: &Option<EventLoopProxy<RinchEvent>>,
+) -> JoinHandle<()> {
+    let proxy = proxy.clone();
+    std::thread::spawn(move || {
+        let mut output = BufReader::new(output);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match output.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&line);
+                    let text = text.trim_end_matches(['\n', '\r']).to_string();
+                    emit(id, ProcessEvent::Line(OutputLine { stream, text }), &proxy);
+                }
+            }
+        }
+    })
+}
+
+/// Report the exit of `child` once its output has been read.
+fn wait_for_exit(
+    id: u64,
+    child: Arc<Mutex<Child>>,
+    readers: impl Iterator<Item = JoinHandle<()>> + Send + 'static,
+    proxy: Option<EventLoopProxy<RinchEvent>>,
+) {
+    std::thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        // Not `wait`, which would hold the lock `kill` needs
+        loop {
+            let status = child.lock().unwrap_or_else(|e| e.into_inner()).try_wait();
+            match status {
+                Ok(Some(status)) => {
+                    emit(id, ProcessEvent::Exited(status), &proxy);
+                    return;
+                }
+                Ok(None) => std::thread::sleep(EXIT_POLL_INTERVAL),
+                Err(e) => {
+                    tracing::warn!("Couldn't wait for a child process: {e}");
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Deliver events until `done`, failing after a few seconds.
+    fn deliver_until(done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+            deliver();
+        }
+    }
+
+    fn sh(script: &str) -> Process {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        let state = Rc::new(ProcessState::new(command));
+        state.start(None);
+        Process { state }
+    }
+
+    #[test]
+    fn output_and_exit_status_are_streamed() {
+        let process = sh("echo one; echo oops >&2; printf 'two\\r\\n'; exit 3");
+        assert!(process.is_running());
+        deliver_until(|| !process.is_running());
+        assert_eq!(process.exit_code(), Some(3));
+        assert_eq!(process.stdout(), ["one", "two"]);
+        assert_eq!(process.stderr(), ["oops"]);
+        assert_eq!(process.lines().len(), 3);
+
+        process.restart();
+        assert!(process.is_running());
+        assert!(process.lines().is_empty());
+        deliver_until(|| !process.is_running());
+        assert_eq!(process.stdout(), ["one", "two"]);
+    }
+
+    #[test]
+    fn processes_can_be_killed() {
+        let process = sh("echo started; exec sleep 30");
+        deliver_until(|| !process.stdout().is_empty());
+        process.kill();
+        deliver_until(|| !process.is_running());
+        assert!(matches!(process.status(), ProcessStatus::Exited(_)));
+        // Killed by a signal, so there's no code
+        assert_eq!(process.exit_code(), None);
+
+        let missing = Process {
+            state: Rc::new(ProcessState::new(Command::new("/nonexistent/program"))),
+        };
+        missing.restart();
+        assert!(matches!(missing.status(), ProcessStatus::Failed(_)));
+    }
+}
@@ -0,0 +1,262 @@
+//! Text search in window content.
+//!
+//! [`find_in_window`] searches the laid-out text of a window and reports each
+//! match with its on-screen rectangles. Searches run in the runtime, which owns
+//! the documents: the first call for a query starts the search and returns no
+//! matches, and the app re-renders once results are ready. Later calls return
+//! the cached results, refreshed in the background when the window's content
+//! changes.
+//!
+//! Every window also has a built-in find bar: press Ctrl+F (Cmd+F on macOS) to
+//! open it, type to highlight matches, Enter/Shift+Enter (or F3/Shift+F3) to
+//! move between them, and Escape to close it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::search::find_in_window;
+//!
+//! let matches = find_in_window(log_window, "error");
+//! rsx! { span { {format!("{} errors", matches.len())} } }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use blitz_dom::BaseDocument;
+
+use crate::shell::runtime::RinchEvent;
+use crate::shell::selection::{inline_roots, range_rects};
+use crate::shell::text_rewrite::{strip_breaks, BREAK_OPPORTUNITY, SOFT_HYPHEN};
+use crate::windows::{send_event, WindowHandle};
+
+/// A rectangle in window coordinates (logical pixels).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A single occurrence of the query in a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// The matched text as it appears in the window.
+    pub text: String,
+    /// The match's rectangles, one per line it spans.
+    pub rects: Vec<MatchRect>,
+}
+
+struct CachedSearch {
+    matches: Vec<SearchMatch>,
+    /// The window's content changed since the search ran.
+    stale: bool,
+}
+
+thread_local! {
+    /// Searches waiting for the runtime.
+    static SEARCH_REQUESTS: RefCell<Vec<(WindowHandle, String)>> = const { RefCell::new(Vec::new()) };
+    /// Latest results per window and query.
+    static SEARCH_RESULTS: RefCell<HashMap<(WindowHandle, String), CachedSearch>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Find all case-insensitive occurrences of `query` in a window's text.
+///
+/// Returns the latest known results, so the first call for a query returns
+/// no matches: it asks the runtime to search, and the app re-renders once
+/// the results are in, when this returns them. Call it while rendering, so
+/// the re-render picks them up. See the [module docs](self).
+pub fn find_in_window(handle: WindowHandle, query: &str) -> Vec<SearchMatch> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let key = (handle, query.to_string());
+    let (matches, needs_search) = SEARCH_RESULTS.with(|results| {
+        match results.borrow_mut().get_mut(&key) {
+            Some(cached) => {
+                let stale = cached.stale;
+                // Only queue one refresh per change
+                cached.stale = false;
+                (cached.matches.clone(), stale)
+            }
+            None => (Vec::new(), true),
+        }
+    });
+
+    if needs_search {
+        let already_queued = SEARCH_REQUESTS.with(|r| r.borrow().contains(&key));
+        if !already_queued {
+            SEARCH_REQUESTS.with(|r| r.borrow_mut().push(key));
+            send_event(RinchEvent::ProcessSearchRequests);
+        }
+    }
+
+    matches
+}
+
+/// Take all pending searches (called by runtime).
+pub(crate) fn take_search_requests() -> Vec<(WindowHandle, String)> {
+    SEARCH_REQUESTS.with(|r| r.borrow_mut().drain(..).collect())
+}
+
+/// Store search results (called by runtime).
+///
+/// Returns `true` if they differ from the previous results, in which case the
+/// app should re-render.
+pub(crate) fn set_search_results(
+    handle: WindowHandle,
+    query: String,
+    matches: Vec<SearchMatch>,
+) -> bool {
+    SEARCH_RESULTS.with(|results| {
+        let mut results = results.borrow_mut();
+        let changed = results
+            .get(&(handle, query.clone()))
+            .is_none_or(|cached| cached.matches != matches);
+        results.insert(
+            (handle, query),
+            CachedSearch {
+                matches,
+                stale: false,
+            },
+        );
+        changed
+    })
+}
+
+/// Mark a window's cached results as out of date (called by runtime when its
+/// content changes).
+pub(crate) fn invalidate_search_results(handle: WindowHandle) {
+    SEARCH_RESULTS.with(|results| {
+        for ((h, _), cached) in results.borrow_mut().iter_mut() {
+            if *h == handle {
+                cached.stale = true;
+            }
+        }
+    });
+}
+
+/// Drop a closed window's cached results.
+pub(crate) fn remove_search_results(handle: WindowHandle) {
+    SEARCH_RESULTS.with(|results| results.borrow_mut().retain(|(h, _), _| *h != handle));
+}
+
+/// Search a laid-out document (called by runtime).
+pub(crate) fn find_matches(doc: &BaseDocument, query: &str) -> Vec<SearchMatch> {
+    text_matches(doc, query)
+        .into_iter()
+        .filter_map(|(node_id, range)| {
+            let text = &doc
+                .get_node(node_id)?
+                .element_data()?
+                .inline_layout_data
+                .as_ref()?
+                .text;
+            let rects = range_rects(doc, node_id, range.start, range.end)
+                .into_iter()
+                .map(|rect| MatchRect {
+                    x: rect.x0 as f32,
+                    y: rect.y0 as f32,
+                    width: rect.width() as f32,
+                    height: rect.height() as f32,
+                })
+                .collect();
+            Some(SearchMatch {
+                text: strip_breaks(text.get(range)?),
+                rects,
+            })
+        })
+        .collect()
+}
+
+/// Occurrences of `query` as `(inline root, byte range)`, in document order.
+pub(crate) fn text_matches(doc: &BaseDocument, query: &str) -> Vec<(usize, Range<usize>)> {
+    inline_roots(doc)
+        .into_iter()
+        .flat_map(|node_id| {
+            let ranges = doc
+                .get_node(node_id)
+                .and_then(|node| node.element_data())
+                .and_then(|el| el.inline_layout_data.as_ref())
+                .map(|text| match_ranges(&text.text, query))
+                .unwrap_or_default();
+            ranges.into_iter().map(move |range| (node_id, range))
+        })
+        .collect()
+}
+
+/// Byte ranges of the case-insensitive occurrences of `query` in `text`.
+pub(crate) fn match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercasing can change byte lengths, so fold char by char and keep a map
+    // back to the original offsets
+    let mut folded = String::with_capacity(text.len());
+    // Where each folded byte's original char starts and ends
+    let mut offsets = Vec::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        // Ignore break opportunities inserted for line wrapping
+        if c == BREAK_OPPORTUNITY || c == SOFT_HYPHEN {
+            continue;
+        }
+        for lower in c.to_lowercase() {
+            for _ in 0..lower.len_utf8() {
+                offsets.push(i..i + c.len_utf8());
+            }
+            folded.push(lower);
+        }
+    }
+    let query = query.to_lowercase();
+
+    let mut ranges = Vec::new();
+    let mut cursor = 0;
+    while let Some(found) = folded[cursor..].find(&query) {
+        let start = cursor + found;
+        let end = start + query.len();
+        // Map the end through the char the last matched byte folded from, as
+        // one char can fold to several, like `İ` to `i̇`
+        ranges.push(offsets[start].start..offsets[end - 1].end);
+        cursor = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_are_case_insensitive_and_non_overlapping() {
+        assert_eq!(
+            match_ranges("Error: error ERROR", "error"),
+            vec![0..5, 7..12, 13..18]
+        );
+        assert_eq!(match_ranges("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(match_ranges("abc", ""), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn ranges_map_to_original_offsets() {
+        let text = "Ärger über Öl";
+        let ranges = match_ranges(text, "über");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(&text[ranges[0].clone()], "über");
+
+        let ranges = match_ranges(text, "öl");
+        assert_eq!(&text[ranges[0].clone()], "Öl");
+
+        // `İ` lowercases to `i` and a combining dot
+        assert_eq!(match_ranges("İzmir", "i"), vec![0..2, 4..5]);
+        assert_eq!(match_ranges("İzmir", "zm"), vec![2..4]);
+
+        let text = "path/\u{200B}to/\u{200B}file";
+        let ranges = match_ranges(text, "to/file");
+        assert_eq!(strip_breaks(&text[ranges[0].clone()]), "to/file");
+    }
+}
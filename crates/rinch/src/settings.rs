@@ -0,0 +1,495 @@
+//! Typed app settings.
+//!
+//! An app describes its settings as a struct and implements [`Settings`]
+//! for it; [`define`] loads them from a file in the app's config directory
+//! (next to [`storage`](crate::storage)'s) and returns a handle that reads
+//! them reactively and writes changes back.
+//!
+//! ```ignore
+//! use rinch::settings::{self, Settings};
+//!
+//! #[derive(Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+//! #[serde(default)]
+//! struct AppSettings {
+//!     theme: String,
+//!     font_size: f32,
+//! }
+//!
+//! impl Settings for AppSettings {}
+//!
+//! fn app() -> Element {
+//!     let settings = settings::define::<AppSettings>();
+//!     let font_size = settings.field(|s| s.font_size);
+//!     // ...
+//!     settings.update(|s| s.theme = "dark".into());
+//! }
+//! ```
+//!
+//! - The file is TOML, or JSON if [`Settings::FILE`] ends in `.json`, with a
+//!   top-level `version` key for [`Settings::migrate`].
+//! - Changes are written shortly after they're made, through a temporary
+//!   file, and when the app exits.
+//! - Edits made to the file while the app runs, such as in a text editor,
+//!   are picked up within a couple of seconds. An edit that doesn't parse is
+//!   ignored (with a warning) until it's fixed.
+//! - A file that doesn't parse at startup is set aside as `<file>.corrupt`
+//!   and the app starts from the defaults.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use rinch_core::clock::{clear_timeout, set_timeout, TimerId};
+use rinch_core::{untracked, use_ref, Signal};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::RinchEvent;
+use crate::storage::{app_config_dir, write_atomically, SAVE_DELAY};
+
+/// How often the watcher checks settings files for outside edits.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An app's settings: a struct loaded from and saved to a file.
+///
+/// Mark the struct `#[serde(default)]`, so a file missing some settings
+/// (written by an older version of the app, or by hand) still loads.
+pub trait Settings: Serialize + DeserializeOwned + Default + Clone + PartialEq + 'static {
+    /// The file's name in the app's config directory.
+    const FILE: &'static str = "settings.toml";
+
+    /// The version of the settings' layout, written to the file. Raise it
+    /// when settings are renamed or restructured, and handle the old layout
+    /// in [`migrate`](Self::migrate).
+    const VERSION: u32 = 1;
+
+    /// Bring a document written at `version` up to `version + 1`. Called
+    /// once for each version between the file's and [`VERSION`](Self::VERSION),
+    /// on the document as JSON (whatever the file's format), before it's
+    /// deserialized. Files without a version are version 1.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// const VERSION: u32 = 2;
+    ///
+    /// fn migrate(version: u32, document: &mut serde_json::Value) {
+    ///     // Version 2 renamed `dark_mode: bool` to `theme: String`
+    ///     if version == 1 && let Some(settings) = document.as_object_mut() {
+    ///         let dark = settings.remove("dark_mode").and_then(|v| v.as_bool());
+    ///         let theme = if dark == Some(true) { "dark" } else { "light" };
+    ///         settings.insert("theme".into(), theme.into());
+    ///     }
+    /// }
+    /// ```
+    fn migrate(version: u32, document: &mut Value) {
+        let _ = (version, document);
+    }
+}
+
+thread_local! {
+    /// Each settings type's state, by type.
+    static DEFINED: RefCell<HashMap<TypeId, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+    /// Reload and flush functions for each defined settings type.
+    static RELOADERS: RefCell<Vec<Rc<dyn Reload>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Settings files the watcher checks, with when they last changed.
+static WATCHED: Mutex<Vec<(PathBuf, Option<SystemTime>)>> = Mutex::new(Vec::new());
+
+/// Load `S` (the first time; later calls share its state) and return a
+/// handle to it.
+pub fn define<S: Settings>() -> SettingsHandle<S> {
+    let existing = DEFINED.with(|defined| defined.borrow().get(&TypeId::of::<S>()).cloned());
+    let state = existing.and_then(|state| state.downcast::<State<S>>().ok());
+    let state = state.unwrap_or_else(|| {
+        let state = Rc::new(State::<S>::load(
+            app_config_dir().map(|dir| dir.join(S::FILE)),
+        ));
+        DEFINED.with(|defined| {
+            defined
+                .borrow_mut()
+                .insert(TypeId::of::<S>(), state.clone() as Rc<dyn Any>)
+        });
+        RELOADERS.with(|reloaders| reloaders.borrow_mut().push(state.clone()));
+        if let Some(path) = &state.path {
+            let mut watched = WATCHED.lock().unwrap_or_else(|e| e.into_inner());
+            watched.push((path.clone(), modified(path)));
+        }
+        state
+    });
+    SettingsHandle { state }
+}
+
+/// A handle to an app's settings, from [`define`].
+pub struct SettingsHandle<S> {
+    state: Rc<State<S>>,
+}
+
+impl<S> Clone for SettingsHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S: Settings> SettingsHandle<S> {
+    /// The settings. Reactive: reading them during render re-renders the
+    /// app when any of them changes; see [`field`](Self::field) to follow
+    /// just one.
+    pub fn get(&self) -> S {
+        self.state.value.get()
+    }
+
+    /// One setting, such as `settings.field(|s| s.font_size)`. Reactive,
+    /// but only to changes in what `select` returns, so effects and memos
+    /// that read it don't re-run when other settings change.
+    ///
+    /// This is a hook: call it at the top level of a component.
+    pub fn field<R: Clone + PartialEq + 'static>(&self, select: impl Fn(&S) -> R + 'static) -> R {
+        let state = self.state.clone();
+        let field = use_ref(move || {
+            let value = untracked(|| state.value.with(&select));
+            let field: Rc<Field<S, R>> = Rc::new(Field {
+                signal: Signal::new(value),
+                select: Box::new(select),
+            });
+            let watcher: Rc<dyn FieldWatch<S>> = field.clone();
+            state.fields.borrow_mut().push(Rc::downgrade(&watcher));
+            field
+        })
+        .get();
+        field.signal.get()
+    }
+
+    /// Replace the settings.
+    pub fn set(&self, settings: S) {
+        if self.state.replace(settings) {
+            self.state.schedule_save();
+        }
+    }
+
+    /// Change the settings in place.
+    pub fn update(&self, f: impl FnOnce(&mut S)) {
+        let mut settings = untracked(|| self.get());
+        f(&mut settings);
+        self.set(settings);
+    }
+
+    /// Write pending changes now.
+    pub fn flush(&self) -> io::Result<()> {
+        self.state.flush()
+    }
+}
+
+/// One settings type's state.
+struct State<S> {
+    path: Option<PathBuf>,
+    value: Signal<S>,
+    fields: RefCell<Vec<Weak<dyn FieldWatch<S>>>>,
+    save: RefCell<Option<TimerId>>,
+}
+
+impl<S: Settings> State<S> {
+    fn load(path: Option<PathBuf>) -> Self {
+        let value = path.as_deref().and_then(load_or_set_aside::<S>);
+        Self {
+            path,
+            value: Signal::new(value.unwrap_or_default()),
+            fields: RefCell::new(Vec::new()),
+            save: RefCell::new(None),
+        }
+    }
+
+    /// Set the settings, updating the fields that changed. Returns whether
+    /// anything changed.
+    fn replace(&self, settings: S) -> bool {
+        if untracked(|| self.value.with(|current| *current == settings)) {
+            return false;
+        }
+        self.value.set(settings);
+        self.fields
+            .borrow_mut()
+            .retain(|field| field.strong_count() > 0);
+        let fields = self.fields.borrow().clone();
+        let settings = untracked(|| self.value.get());
+        for field in fields.iter().filter_map(Weak::upgrade) {
+            field.changed(&settings);
+        }
+        true
+    }
+
+    fn schedule_save(&self) {
+        if self.save.borrow().is_some() {
+            return;
+        }
+        let type_id = TypeId::of::<S>();
+        let timer = set_timeout(SAVE_DELAY, move || {
+            let state = DEFINED.with(|defined| defined.borrow().get(&type_id).cloned());
+            if let Some(state) = state.and_then(|state| state.downcast::<State<S>>().ok())
+                && let Err(e) = state.flush()
+            {
+                tracing::warn!("Couldn't save settings: {e}");
+            }
+        });
+        *self.save.borrow_mut() = Some(timer);
+    }
+}
+
+/// Reloading and flushing, for every settings type.
+trait Reload {
+    /// Read the file again if it changed.
+    fn reload(&self);
+    fn flush(&self) -> io::Result<()>;
+}
+
+impl<S: Settings> Reload for State<S> {
+    fn reload(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match read::<S>(path) {
+            Ok(Some(settings)) => {
+                self.replace(settings);
+            }
+            // Deleted: keep the settings, which the next change writes back
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Ignoring unreadable settings in {}: {e}", path.display()),
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let Some(timer) = self.save.borrow_mut().take() else {
+            return Ok(());
+        };
+        clear_timeout(timer);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = untracked(|| self.value.with(encode::<S>))?;
+        write_atomically(path, contents.as_bytes())?;
+        // Don't reload our own write
+        let mut watched = WATCHED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, last)) = watched.iter_mut().find(|(watched, _)| watched == path) {
+            *last = modified(path);
+        }
+        Ok(())
+    }
+}
+
+/// A followed setting.
+struct Field<S, R> {
+    signal: Signal<R>,
+    select: Box<dyn Fn(&S) -> R>,
+}
+
+trait FieldWatch<S> {
+    fn changed(&self, settings: &S);
+}
+
+impl<S, R: Clone + PartialEq> FieldWatch<S> for Field<S, R> {
+    fn changed(&self, settings: &S) {
+        let value = (self.select)(settings);
+        if untracked(|| self.signal.with(|current| *current != value)) {
+            self.signal.set(value);
+        }
+    }
+}
+
+/// Read settings files changed by something else (called by the runtime).
+pub(crate) fn reload_changed() {
+    let reloaders = RELOADERS.with(|reloaders| reloaders.borrow().clone());
+    for reloader in reloaders {
+        reloader.reload();
+    }
+}
+
+/// Write every settings type's pending changes (called by the runtime).
+pub(crate) fn flush_all() -> io::Result<()> {
+    let reloaders = RELOADERS.with(|reloaders| reloaders.borrow().clone());
+    reloaders.iter().try_for_each(|reloader| reloader.flush())
+}
+
+/// Check settings files for outside edits every [`WATCH_INTERVAL`] on a
+/// background thread, sending `SettingsFileChanged` when one changes.
+pub(crate) fn watch(proxy: EventLoopProxy<RinchEvent>) {
+    let spawned = std::thread::Builder::new()
+        .name("rinch-settings".into())
+        .spawn(move || loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let mut changed = false;
+            for (path, last) in WATCHED.lock().unwrap_or_else(|e| e.into_inner()).iter_mut() {
+                let now = modified(path);
+                if now != *last {
+                    *last = now;
+                    changed = true;
+                }
+            }
+            if changed && proxy.send_event(RinchEvent::SettingsFileChanged).is_err() {
+                return;
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Couldn't watch settings files: {e}");
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Whether `S` is stored as JSON rather than TOML.
+fn is_json<S: Settings>() -> bool {
+    S::FILE.ends_with(".json")
+}
+
+/// Read `S` from `path`: `None` if there's no file.
+fn read<S: Settings>(path: &Path) -> io::Result<Option<S>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut document: Value = if is_json::<S>() {
+        serde_json::from_str(&contents).map_err(io::Error::other)?
+    } else {
+        toml::from_str(&contents).map_err(io::Error::other)?
+    };
+    migrate::<S>(&mut document);
+    serde_json::from_value(document)
+        .map(Some)
+        .map_err(io::Error::other)
+}
+
+/// Read `S` from `path` at startup, moving the file aside if it doesn't
+/// parse, so it isn't overwritten.
+fn load_or_set_aside<S: Settings>(path: &Path) -> Option<S> {
+    match read::<S>(path) {
+        Ok(settings) => settings,
+        Err(e) => {
+            let mut aside = path.as_os_str().to_owned();
+            aside.push(".corrupt");
+            tracing::warn!(
+                "Settings in {} are unreadable ({e}); moving them to {}",
+                path.display(),
+                Path::new(&aside).display()
+            );
+            if let Err(e) = std::fs::rename(path, &aside) {
+                tracing::warn!("Couldn't move unreadable settings aside: {e}");
+            }
+            None
+        }
+    }
+}
+
+/// Bring `document` up to `S::VERSION`, removing its `version` key.
+fn migrate<S: Settings>(document: &mut Value) {
+    let version = document
+        .as_object_mut()
+        .and_then(|settings| settings.remove("version"))
+        .and_then(|version| version.as_u64())
+        .map_or(1, |version| version as u32);
+    if version > S::VERSION {
+        tracing::warn!(
+            "Settings are version {version}, newer than this app's {}",
+            S::VERSION
+        );
+    }
+    for version in version..S::VERSION {
+        S::migrate(version, document);
+    }
+}
+
+/// The file contents for `settings`.
+fn encode<S: Settings>(settings: &S) -> io::Result<String> {
+    let mut document = serde_json::to_value(settings).map_err(io::Error::other)?;
+    if let Some(fields) = document.as_object_mut() {
+        fields.insert("version".into(), S::VERSION.into());
+    }
+    if is_json::<S>() {
+        return serde_json::to_string_pretty(&document).map_err(io::Error::other);
+    }
+    // TOML has no null: unset options are left out
+    strip_nulls(&mut document);
+    toml::to_string_pretty(&document).map_err(io::Error::other)
+}
+
+/// Remove null object members, at any depth.
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, value| !value.is_null());
+            fields.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Settings that stay a JSON document, to test the file handling
+    /// without a derive.
+    #[derive(Clone, PartialEq, Default)]
+    struct Document(Value);
+
+    impl Serialize for Document {
+        fn serialize<Se: serde::Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+            self.0.serialize(serializer)
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Document {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Value::deserialize(deserializer).map(Document)
+        }
+    }
+
+    impl Settings for Document {
+        const FILE: &'static str = "settings.json";
+        const VERSION: u32 = 3;
+
+        fn migrate(version: u32, document: &mut Value) {
+            let steps = document["steps"].as_array_mut().unwrap();
+            steps.push(version.into());
+        }
+    }
+
+    #[test]
+    fn documents_are_migrated_from_their_version() {
+        let mut old = json!({ "steps": [] });
+        migrate::<Document>(&mut old);
+        assert_eq!(old, json!({ "steps": [1, 2] }));
+
+        let mut current = json!({ "version": 3, "steps": [] });
+        migrate::<Document>(&mut current);
+        assert_eq!(current, json!({ "steps": [] }));
+    }
+
+    #[test]
+    fn encoding_records_the_version() {
+        let settings = Document(json!({ "theme": "dark", "recent": null }));
+        let encoded: Value = serde_json::from_str(&encode(&settings).unwrap()).unwrap();
+        assert_eq!(
+            encoded,
+            json!({ "theme": "dark", "recent": null, "version": 3 })
+        );
+
+        let mut nested = json!({ "a": null, "b": { "c": null, "d": [{ "e": null }] } });
+        strip_nulls(&mut nested);
+        assert_eq!(nested, json!({ "b": { "d": [{}] } }));
+    }
+}
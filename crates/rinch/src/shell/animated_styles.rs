@@ -0,0 +1,90 @@
+//! Animated styles set through node refs.
+//!
+//! `NodeRef::set_animated_style` changes how an element is painted without
+//! re-rendering. Translations are applied by shifting the element's layout
+//! location, which the painter and hit testing read directly, so a change
+//! costs a repaint but no restyle or layout. Like sticky elements, rinch
+//! remembers where it put each element so it can tell whether layout has
+//! since placed it afresh or its own shift needs undoing.
+//!
+//! Styles bound to scrolling with `NodeRef::bind_scroll` are computed here
+//! too, from the source container's scroll state at each paint, so they
+//! follow every scrolled pixel without a reactive update.
+
+use std::collections::HashMap;
+
+use blitz_dom::BaseDocument;
+use rinch_core::node_ref::{scroll_bindings, AnimatedStyle};
+
+use super::scroll::{ScrollWatch, Scroller};
+
+/// Animated styles for a window's refs.
+#[derive(Debug, Default)]
+pub(crate) struct AnimatedStyles {
+    /// Styles by ref ID.
+    styles: HashMap<u64, AnimatedStyle>,
+    /// Nodes rinch has shifted: the location it set, and the shift.
+    placed: HashMap<usize, ((f32, f32), (f64, f64))>,
+}
+
+impl AnimatedStyles {
+    /// Replace the styles (every ref's, including other windows').
+    pub(crate) fn set(&mut self, styles: &HashMap<u64, AnimatedStyle>) {
+        self.styles = styles.clone();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.styles.is_empty() && self.placed.is_empty()
+    }
+
+    /// Forget shifted nodes, after the document has been rebuilt.
+    pub(crate) fn clear_placed(&mut self) {
+        self.placed.clear();
+    }
+
+    /// Shift styled elements to match their styles (or their scroll
+    /// bindings), and move elements whose style was cleared back.
+    pub(crate) fn apply(&mut self, doc: &mut BaseDocument, watch: &ScrollWatch) {
+        let mut shifts: HashMap<usize, (f64, f64)> = self
+            .placed
+            .keys()
+            .map(|&node_id| (node_id, (0.0, 0.0)))
+            .collect();
+        for (&node_ref, style) in &self.styles {
+            if let Some(node_id) = watch.node_for_ref(node_ref) {
+                shifts.insert(node_id, (style.translate_x, style.translate_y));
+            }
+        }
+        for (node_ref, (source, style)) in scroll_bindings() {
+            let (Some(node_id), Some(source)) =
+                (watch.node_for_ref(node_ref), watch.node_for_ref(source))
+            else {
+                continue;
+            };
+            let style = style(Scroller::for_node(doc, source).state(doc));
+            shifts.insert(node_id, (style.translate_x, style.translate_y));
+        }
+
+        for (node_id, shift) in shifts {
+            let Some(node) = doc.get_node_mut(node_id) else {
+                self.placed.remove(&node_id);
+                continue;
+            };
+            let location = &mut node.final_layout.location;
+
+            // Undo the last shift, unless layout has placed the element afresh
+            let applied = match self.placed.get(&node_id) {
+                Some(&(placed, applied)) if placed == (location.x, location.y) => applied,
+                _ => (0.0, 0.0),
+            };
+            location.x += (shift.0 - applied.0) as f32;
+            location.y += (shift.1 - applied.1) as f32;
+            if shift == (0.0, 0.0) {
+                self.placed.remove(&node_id);
+            } else {
+                self.placed
+                    .insert(node_id, ((location.x, location.y), shift));
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Starting the app when the user logs in.
+//!
+//! For tray-style apps' "Start with system" setting:
+//!
+//! ```ignore
+//! let start_with_system = use_signal(|| autostart::is_enabled().unwrap_or(false));
+//! let toggle = move || {
+//!     let enable = !start_with_system.get();
+//!     let result = if enable { autostart::enable() } else { autostart::disable() };
+//!     match result {
+//!         Ok(()) => start_with_system.set(enable),
+//!         Err(e) => eprintln!("Couldn't change the login item: {e}"),
+//!     }
+//! };
+//! ```
+//!
+//! The running executable is registered, under its file name:
+//!
+//! - **Linux**: a desktop entry in `~/.config/autostart` (XDG autostart).
+//! - **Windows**: a value under
+//!   `HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Run`.
+//! - **macOS**: a launch agent in `~/Library/LaunchAgents`, loaded at the
+//!   next login.
+
+use std::io;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Start the running executable when the user logs in. Enabling again
+/// updates the registration to the current executable, such as after the
+/// app moved.
+pub fn enable() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    platform::enable(&app_name(&exe), &exe.to_string_lossy())
+}
+
+/// Stop starting the app at login. Disabling when it isn't enabled does
+/// nothing.
+pub fn disable() -> io::Result<()> {
+    platform::disable(&app_name(&std::env::current_exe()?))
+}
+
+/// Whether the app starts at login.
+pub fn is_enabled() -> io::Result<bool> {
+    platform::is_enabled(&app_name(&std::env::current_exe()?))
+}
+
+/// The name the app is registered under: its executable's file name.
+fn app_name(exe: &std::path::Path) -> String {
+    exe.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "rinch".into())
+}
+
+/// Remove a file, if it's there.
+#[cfg(unix)]
+fn remove_if_present(path: &std::path::Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// `$HOME`, or a `NotFound` error.
+#[cfg(unix)]
+fn home_dir() -> io::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::io;
+    use std::path::PathBuf;
+
+    pub(super) fn enable(name: &str, exe: &str) -> io::Result<()> {
+        let path = entry_path(name)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, super::desktop_entry(name, exe))
+    }
+
+    pub(super) fn disable(name: &str) -> io::Result<()> {
+        super::remove_if_present(&entry_path(name)?)
+    }
+
+    pub(super) fn is_enabled(name: &str) -> io::Result<bool> {
+        match std::fs::read_to_string(entry_path(name)?) {
+            // Desktop environments hide entries the user turns off
+            Ok(entry) => Ok(!entry.lines().any(|line| {
+                matches!(
+                    line.trim(),
+                    "Hidden=true" | "X-GNOME-Autostart-enabled=false"
+                )
+            })),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn entry_path(name: &str) -> io::Result<PathBuf> {
+        let config = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => PathBuf::from(config),
+            None => super::home_dir()?.join(".config"),
+        };
+        Ok(config.join("autostart").join(format!("{name}.desktop")))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::io;
+    use std::path::PathBuf;
+
+    pub(super) fn enable(name: &str, exe: &str) -> io::Result<()> {
+        let path = agent_path(name)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, super::launch_agent(&super::agent_label(name), exe))
+    }
+
+    pub(super) fn disable(name: &str) -> io::Result<()> {
+        super::remove_if_present(&agent_path(name)?)
+    }
+
+    pub(super) fn is_enabled(name: &str) -> io::Result<bool> {
+        Ok(agent_path(name)?.exists())
+    }
+
+    fn agent_path(name: &str) -> io::Result<PathBuf> {
+        Ok(super::home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", super::agent_label(name))))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::io;
+
+    use super::reg;
+
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+    pub(super) fn enable(name: &str, exe: &str) -> io::Result<()> {
+        let output = reg(&[
+            "add",
+            RUN_KEY,
+            "/v",
+            name,
+            "/d",
+            &format!("\"{exe}\""),
+            "/f",
+        ])?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "reg add failed ({})",
+                output.status
+            )));
+        }
+        Ok(())
+    }
+
+    pub(super) fn disable(name: &str) -> io::Result<()> {
+        if is_enabled(name)? {
+            let output = reg(&["delete", RUN_KEY, "/v", name, "/f"])?;
+            if !output.status.success() {
+                return Err(io::Error::other(format!(
+                    "reg delete failed ({})",
+                    output.status
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn is_enabled(name: &str) -> io::Result<bool> {
+        // `reg query` fails when the value isn't there
+        Ok(reg(&["query", RUN_KEY, "/v", name])?.status.success())
+    }
+}
+
+/// Run `reg` with `args`, without flashing a console window.
+#[cfg(windows)]
+pub(crate) fn reg(args: &[&str]) -> io::Result<std::process::Output> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "autostart isn't supported on this platform",
+        )
+    }
+
+    pub(super) fn enable(_name: &str, _exe: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn disable(_name: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn is_enabled(_name: &str) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// An XDG autostart desktop entry running `exe`.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn desktop_entry(name: &str, exe: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={name}\n\
+         Exec={}\n\
+         X-GNOME-Autostart-enabled=true\n",
+        super::deep_link::desktop_exec_quote(exe),
+    )
+}
+
+/// The launch agent label for the app, such as `rinch.autostart.notes`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn agent_label(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("rinch.autostart.{name}")
+}
+
+/// A launch agent property list running `exe` at login.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent(label: &str, exe: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \t<key>Label</key>\n\
+         \t<string>{}</string>\n\
+         \t<key>ProgramArguments</key>\n\
+         \t<array>\n\
+         \t\t<string>{}</string>\n\
+         \t</array>\n\
+         \t<key>RunAtLoad</key>\n\
+         \t<true/>\n\
+         </dict>\n\
+         </plist>\n",
+        xml_escape(label),
+        xml_escape(exe),
+    )
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_entries_run_the_executable() {
+        let entry = desktop_entry("notes", "/opt/My Notes/notes");
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nExec=\"/opt/My Notes/notes\"\n"));
+    }
+
+    #[test]
+    fn launch_agents_run_the_executable_at_load() {
+        let label = agent_label("my notes");
+        assert_eq!(label, "rinch.autostart.my_notes");
+        let agent = launch_agent(&label, "/Applications/A&B.app/Contents/MacOS/ab");
+        assert!(agent.contains("<string>rinch.autostart.my_notes</string>"));
+        assert!(agent.contains("<string>/Applications/A&amp;B.app/Contents/MacOS/ab</string>"));
+        assert!(agent.contains("<key>RunAtLoad</key>\n\t<true/>"));
+    }
+}
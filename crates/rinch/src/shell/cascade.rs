@@ -0,0 +1,1166 @@
+//! Cascade inspection for DevTools.
+//!
+//! Re-runs a simplified cascade over the document's author stylesheets to
+//! explain where an element's styles come from: which rules matched, in what
+//! order they apply, which declarations lost, and the winning value for each
+//! property. `!important` and `@layer` ordering follow the CSS cascade.
+//! Selector support covers type, `*`, id, class, attribute, `:root`,
+//! `:hover` and `:focus` selectors joined by descendant or child combinators.
+//! Rules using other pseudo-classes (`:active`, `:nth-child`...) or sibling
+//! combinators are treated as not matching. `@media` conditions are checked
+//! against the window's size and theme, and rules in ones that don't match
+//! are listed as inactive; `@supports` and `@container` are assumed to match.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Specificity as `(ids, classes, types)`.
+pub type Specificity = (u32, u32, u32);
+
+/// Where a matched rule was declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleSource {
+    /// A `<style>` element, by document order. `href` is set for stylesheets
+    /// inlined from a local `<link>`.
+    Stylesheet { index: usize, href: Option<String> },
+    /// The element's `style` attribute.
+    Inline,
+}
+
+impl fmt::Display for StyleSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleSource::Stylesheet {
+                href: Some(href), ..
+            } => write!(f, "{}", href),
+            StyleSource::Stylesheet { index, href: None } => write!(f, "<style> #{}", index + 1),
+            StyleSource::Inline => write!(f, "style attribute"),
+        }
+    }
+}
+
+/// What `@media` conditions are checked against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MediaContext {
+    /// The viewport's width in CSS pixels.
+    pub width: f32,
+    /// The viewport's height in CSS pixels.
+    pub height: f32,
+    /// Whether the window's theme is dark, for `prefers-color-scheme`.
+    pub dark: bool,
+}
+
+/// The parts of an element that selectors can match against.
+#[derive(Debug, Clone, Default)]
+pub struct MatchElement {
+    /// Lowercase tag name.
+    pub tag: String,
+    /// The `id` attribute.
+    pub id: Option<String>,
+    /// The `class` attribute, split on whitespace.
+    pub classes: Vec<String>,
+    /// All attributes as `(name, value)`.
+    pub attrs: Vec<(String, String)>,
+    /// Whether the pointer is over the element or something inside it.
+    pub hovered: bool,
+    /// Whether the element has keyboard focus.
+    pub focused: bool,
+}
+
+/// A single `property: value` declaration within a matched rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedDeclaration {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+    /// `true` if another declaration for the same property wins the cascade.
+    pub overridden: bool,
+}
+
+/// A rule that matched an element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedRule {
+    /// The rule's full selector list, or `element.style` for inline styles.
+    pub selector: String,
+    /// Enclosing `@media`/`@supports`/`@container` preludes, outermost first.
+    pub conditions: Vec<String>,
+    /// The cascade layer the rule belongs to, like `base` or `lib.reset`.
+    pub layer: Option<String>,
+    /// Specificity of the most specific matching selector in the list.
+    pub specificity: Specificity,
+    /// `false` if an enclosing `@media` doesn't match, so the rule doesn't
+    /// apply and all its declarations are overridden.
+    pub active: bool,
+    pub source: StyleSource,
+    pub declarations: Vec<MatchedDeclaration>,
+}
+
+/// Find every rule matching `chain[0]`, in cascade order (winning rules first).
+///
+/// `chain` is the element followed by its ancestors, nearest first.
+/// `stylesheets` are in document order.
+pub fn matched_rules(
+    stylesheets: &[(StyleSource, String)],
+    inline_style: Option<&str>,
+    chain: &[MatchElement],
+    media: &MediaContext,
+) -> Vec<MatchedRule> {
+    if chain.is_empty() {
+        return Vec::new();
+    }
+
+    // Layer order is shared across stylesheets, so parse everything first
+    let mut layers = Vec::new();
+    let parsed: Vec<_> = stylesheets
+        .iter()
+        .map(|(source, css)| (source, parse_rules(css, &mut layers)))
+        .collect();
+
+    let mut rules = Vec::new();
+    for (source, sheet) in parsed {
+        for rule in sheet {
+            let specificity = rule
+                .selector
+                .split(',')
+                .filter_map(|s| match_selector(s.trim(), chain))
+                .max();
+            if let Some(specificity) = specificity {
+                let rank = layer_rank(rule.layer.as_deref(), &layers);
+                let active = rule.conditions.iter().all(|c| media_matches(c, media));
+                let rule = MatchedRule {
+                    selector: rule.selector,
+                    conditions: rule.conditions,
+                    layer: rule.layer,
+                    specificity,
+                    active,
+                    source: source.clone(),
+                    declarations: parse_declarations(&rule.body),
+                };
+                rules.push((rule, rank));
+            }
+        }
+    }
+
+    if let Some(style) = inline_style {
+        let declarations = parse_declarations(style);
+        if !declarations.is_empty() {
+            let rule = MatchedRule {
+                selector: "element.style".into(),
+                conditions: Vec::new(),
+                layer: None,
+                specificity: (0, 0, 0),
+                active: true,
+                source: StyleSource::Inline,
+                declarations,
+            };
+            rules.push((rule, layer_rank(None, &layers)));
+        }
+    }
+
+    // Stable sort keeps source order for equal keys; reversing afterwards puts
+    // later rules first, as they win ties.
+    rules.sort_by(|(a, a_rank), (b, b_rank)| {
+        is_inline(a)
+            .cmp(&is_inline(b))
+            .then_with(|| a_rank.cmp(b_rank))
+            .then(a.specificity.cmp(&b.specificity))
+    });
+    rules.reverse();
+    mark_overridden(&mut rules);
+    rules.into_iter().map(|(rule, _)| rule).collect()
+}
+
+/// The winning value for each property, sorted by property name.
+pub fn computed_values(rules: &[MatchedRule]) -> Vec<(String, String)> {
+    let mut values: Vec<(String, String)> = rules
+        .iter()
+        .flat_map(|rule| &rule.declarations)
+        .filter(|decl| !decl.overridden)
+        .map(|decl| (decl.property.clone(), decl.value.clone()))
+        .collect();
+    values.sort();
+    values
+}
+
+fn is_inline(rule: &MatchedRule) -> bool {
+    rule.source == StyleSource::Inline
+}
+
+/// Sort key for a rule's cascade layer.
+///
+/// Each level of the layer path maps to the position its name was first
+/// declared in. Rules directly inside a layer rank above its sub-layers, and
+/// unlayered rules rank above every layer.
+fn layer_rank(layer: Option<&str>, layers: &[String]) -> Vec<usize> {
+    let mut rank = Vec::new();
+    if let Some(layer) = layer {
+        let mut prefix = String::new();
+        for part in layer.split('.') {
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(part);
+            rank.push(
+                layers
+                    .iter()
+                    .position(|l| *l == prefix)
+                    .unwrap_or(usize::MAX),
+            );
+        }
+    }
+    rank.push(usize::MAX);
+    rank
+}
+
+/// Mark every declaration that loses to another one for the same property,
+/// and those of inactive rules.
+///
+/// `rules` must already be in normal cascade order. Important declarations
+/// beat normal ones, and for them layer order is reversed: earlier layers win,
+/// and layered styles beat unlayered ones. Within a rule, later declarations
+/// win over earlier ones.
+fn mark_overridden(rules: &mut [(MatchedRule, Vec<usize>)]) {
+    let mut winners: Vec<(String, (usize, usize))> = Vec::new();
+
+    let beats = |rules: &[(MatchedRule, Vec<usize>)], a: (usize, usize), b: (usize, usize)| {
+        let (rule_a, rank_a) = &rules[a.0];
+        let (rule_b, rank_b) = &rules[b.0];
+        let important = rule_a.declarations[a.1].important;
+        important
+            .cmp(&rule_b.declarations[b.1].important)
+            .then_with(|| is_inline(rule_a).cmp(&is_inline(rule_b)))
+            .then_with(|| {
+                if important {
+                    rank_b.cmp(rank_a)
+                } else {
+                    rank_a.cmp(rank_b)
+                }
+            })
+            .then(rule_a.specificity.cmp(&rule_b.specificity))
+            // Lower indices come later in source order
+            .then(b.0.cmp(&a.0))
+            .then(a.1.cmp(&b.1))
+            == Ordering::Greater
+    };
+
+    for i in 0..rules.len() {
+        if !rules[i].0.active {
+            continue;
+        }
+        for j in 0..rules[i].0.declarations.len() {
+            let property = &rules[i].0.declarations[j].property;
+            match winners.iter().position(|(p, _)| p == property) {
+                Some(w) if beats(rules, (i, j), winners[w].1) => winners[w].1 = (i, j),
+                Some(_) => {}
+                None => winners.push((property.clone(), (i, j))),
+            }
+        }
+    }
+
+    for (i, (rule, _)) in rules.iter_mut().enumerate() {
+        for (j, decl) in rule.declarations.iter_mut().enumerate() {
+            decl.overridden = !winners.iter().any(|(_, w)| *w == (i, j));
+        }
+    }
+}
+
+struct ParsedRule {
+    selector: String,
+    conditions: Vec<String>,
+    layer: Option<String>,
+    body: String,
+}
+
+/// Split a stylesheet into style rules, descending into conditional at-rules
+/// and cascade layers. Layer names are appended to `layers` in the order they
+/// are first declared.
+fn parse_rules(css: &str, layers: &mut Vec<String>) -> Vec<ParsedRule> {
+    let css = strip_comments(css);
+    let mut rules = Vec::new();
+    collect_rules(&css, &mut Vec::new(), None, layers, &mut rules);
+    rules
+}
+
+fn collect_rules(
+    css: &str,
+    conditions: &mut Vec<String>,
+    layer: Option<&str>,
+    layers: &mut Vec<String>,
+    out: &mut Vec<ParsedRule>,
+) {
+    let declare_layer = |name: &str, layers: &mut Vec<String>| {
+        let full = match layer {
+            Some(parent) => format!("{}.{}", parent, name),
+            None => name.to_string(),
+        };
+        if !layers.contains(&full) {
+            layers.push(full.clone());
+        }
+        full
+    };
+
+    let mut rest = css;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        let Some(open) = find_outside_quotes(rest, &['{', ';']) else {
+            break;
+        };
+        if rest.as_bytes()[open] == b';' {
+            // Statement at-rules: `@layer a, b;` fixes layer order up front
+            let statement = rest[..open].trim();
+            if let Some(names) = at_rule_prelude(statement, "layer") {
+                for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    declare_layer(name, layers);
+                }
+            }
+            rest = &rest[open + 1..];
+            continue;
+        }
+
+        let prelude = rest[..open].trim().to_string();
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        let body = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        if let Some(name) = at_rule_prelude(&prelude, "layer") {
+            let name = if name.is_empty() {
+                // Anonymous layers can't be re-entered, so each gets a unique name
+                format!("<anonymous {}>", layers.len() + 1)
+            } else {
+                name.to_string()
+            };
+            let full = declare_layer(&name, layers);
+            collect_rules(body, conditions, Some(&full), layers, out);
+            continue;
+        }
+
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            let name = at_rule
+                .split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .unwrap_or("")
+                .to_ascii_lowercase();
+            if matches!(name.as_str(), "media" | "supports" | "container") {
+                conditions.push(prelude.clone());
+                collect_rules(body, conditions, layer, layers, out);
+                conditions.pop();
+            }
+            continue;
+        }
+
+        out.push(ParsedRule {
+            selector: prelude,
+            conditions: conditions.clone(),
+            layer: layer.map(String::from),
+            body: body.to_string(),
+        });
+    }
+}
+
+/// Whether a rule's condition holds. Only `@media` is checked: `@supports`
+/// and `@container` conditions are assumed to hold.
+fn media_matches(condition: &str, media: &MediaContext) -> bool {
+    let Some(queries) = at_rule_prelude(condition, "media") else {
+        return true;
+    };
+    queries
+        .to_ascii_lowercase()
+        .split(',')
+        .any(|query| media_query_matches(query.trim(), media))
+}
+
+/// Whether one query of a media query list matches, like
+/// `screen and (min-width: 600px)`.
+fn media_query_matches(query: &str, media: &MediaContext) -> bool {
+    let (negated, query) = match query.strip_prefix("not ") {
+        Some(query) => (true, query),
+        None => (false, query.strip_prefix("only ").unwrap_or(query)),
+    };
+    let matches = query.split(" and ").map(str::trim).all(|part| {
+        match part.strip_prefix('(').and_then(|p| p.strip_suffix(')')) {
+            Some(feature) => media_feature_matches(feature.trim(), media),
+            None => matches!(part, "" | "all" | "screen"),
+        }
+    });
+    matches != negated
+}
+
+/// Whether a media feature matches, like `min-width: 600px` or
+/// `400px < width <= 800px`. Features that can't be checked match.
+fn media_feature_matches(feature: &str, media: &MediaContext) -> bool {
+    if let Some((name, value)) = feature.split_once(':') {
+        let value = value.trim();
+        let length = || media_length(value);
+        return match name.trim() {
+            "width" => length() == Some(media.width),
+            "min-width" => length().is_some_and(|min| media.width >= min),
+            "max-width" => length().is_some_and(|max| media.width <= max),
+            "height" => length() == Some(media.height),
+            "min-height" => length().is_some_and(|min| media.height >= min),
+            "max-height" => length().is_some_and(|max| media.height <= max),
+            "orientation" => (value == "portrait") == (media.height >= media.width),
+            "prefers-color-scheme" => (value == "dark") == media.dark,
+            "prefers-reduced-motion" => value == "no-preference",
+            _ => true,
+        };
+    }
+
+    // Range syntax: operands with comparisons between them
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    let mut rest = feature;
+    while let Some(at) = rest.find(['<', '>', '=']) {
+        operands.push(rest[..at].trim());
+        let len = if rest[at + 1..].starts_with('=') {
+            2
+        } else {
+            1
+        };
+        operators.push(&rest[at..at + len]);
+        rest = &rest[at + len..];
+    }
+    operands.push(rest.trim());
+    let value = |operand: &str| match operand {
+        "width" => Some(media.width),
+        "height" => Some(media.height),
+        _ => media_length(operand),
+    };
+    operators.iter().enumerate().all(|(i, &operator)| {
+        match (value(operands[i]), value(operands[i + 1])) {
+            (Some(a), Some(b)) => match operator {
+                "<" => a < b,
+                "<=" => a <= b,
+                ">" => a > b,
+                ">=" => a >= b,
+                _ => a == b,
+            },
+            _ => true,
+        }
+    })
+}
+
+/// A length in a media query, in CSS pixels. `em` and `rem` are 16px.
+fn media_length(value: &str) -> Option<f32> {
+    if let Some(px) = value.strip_suffix("px") {
+        return px.trim().parse().ok();
+    }
+    if let Some(em) = value
+        .strip_suffix("rem")
+        .or_else(|| value.strip_suffix("em"))
+    {
+        return em.trim().parse::<f32>().ok().map(|em| em * 16.0);
+    }
+    value.parse().ok().filter(|&value: &f32| value == 0.0)
+}
+
+/// If `prelude` is the at-rule `name`, return the rest of the prelude.
+fn at_rule_prelude<'a>(prelude: &'a str, name: &str) -> Option<&'a str> {
+    let rest = prelude.strip_prefix('@')?;
+    if rest.len() < name.len() || !rest[..name.len()].eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let rest = &rest[name.len()..];
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+pub(crate) fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return out,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Byte index of the first of `targets` that isn't inside a string.
+pub(crate) fn find_outside_quotes(s: &str, targets: &[char]) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if targets.contains(&c) => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Byte index of the `}` closing the `{` at `open`.
+pub(crate) fn matching_brace(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s[open..].char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(open + i);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Parse a declaration block (without braces).
+pub(crate) fn parse_declarations(block: &str) -> Vec<MatchedDeclaration> {
+    let mut declarations = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    let mut push = |decl: &str| {
+        let Some((property, value)) = decl.split_once(':') else {
+            return;
+        };
+        let property = property.trim().to_ascii_lowercase();
+        let mut value = value.trim();
+        if property.is_empty() || value.is_empty() {
+            return;
+        }
+
+        let mut important = false;
+        if let Some(bang) = value.rfind('!')
+            && value[bang + 1..].trim().eq_ignore_ascii_case("important")
+        {
+            important = true;
+            value = value[..bang].trim_end();
+        }
+
+        declarations.push(MatchedDeclaration {
+            property,
+            value: value.to_string(),
+            important,
+            overridden: false,
+        });
+    };
+
+    for (i, c) in block.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ';' if depth == 0 => {
+                    push(&block[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            },
+        }
+    }
+    push(&block[start..]);
+
+    declarations
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Default)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attrs: Vec<AttrSelector>,
+    root: bool,
+    hover: bool,
+    focus: bool,
+    /// Contains a selector we can't evaluate, so it never matches.
+    unsupported: bool,
+}
+
+#[derive(Debug)]
+struct AttrSelector {
+    name: String,
+    op: Option<(String, String)>,
+}
+
+/// Whether a selector list, such as `"button.save, #cancel"`, matches the
+/// first element of `chain`.
+pub(crate) fn matches_selector_list(selectors: &str, chain: &[MatchElement]) -> bool {
+    !chain.is_empty()
+        && selectors
+            .split(',')
+            .any(|s| match_selector(s.trim(), chain).is_some())
+}
+
+/// Match a single complex selector, returning its specificity if it matches.
+fn match_selector(selector: &str, chain: &[MatchElement]) -> Option<Specificity> {
+    let parts = parse_selector(selector)?;
+    let specificity = parts.iter().fold((0, 0, 0), |(a, b, c), (compound, _)| {
+        (
+            a + compound.ids.len() as u32,
+            b + (compound.classes.len()
+                + compound.attrs.len()
+                + compound.root as usize
+                + compound.hover as usize
+                + compound.focus as usize) as u32,
+            c + compound.tag.is_some() as u32,
+        )
+    });
+
+    let (last, rest) = parts.split_last()?;
+    if !matches_compound(&last.0, &chain[0]) {
+        return None;
+    }
+    matches_ancestors(rest, &chain[1..]).then_some(specificity)
+}
+
+/// Match the remaining compounds (right to left) against ancestors.
+///
+/// Each entry's combinator relates it to the compound on its right.
+fn matches_ancestors(parts: &[(Compound, Combinator)], ancestors: &[MatchElement]) -> bool {
+    let Some(((compound, combinator), rest)) = parts.split_last() else {
+        return true;
+    };
+    match combinator {
+        Combinator::Child => ancestors.first().is_some_and(|el| {
+            matches_compound(compound, el) && matches_ancestors(rest, &ancestors[1..])
+        }),
+        Combinator::Descendant => (0..ancestors.len()).any(|i| {
+            matches_compound(compound, &ancestors[i])
+                && matches_ancestors(rest, &ancestors[i + 1..])
+        }),
+    }
+}
+
+fn matches_compound(compound: &Compound, el: &MatchElement) -> bool {
+    if compound.unsupported {
+        return false;
+    }
+    if compound
+        .tag
+        .as_ref()
+        .is_some_and(|t| !t.eq_ignore_ascii_case(&el.tag))
+    {
+        return false;
+    }
+    if compound.root && el.tag != "html" {
+        return false;
+    }
+    if (compound.hover && !el.hovered) || (compound.focus && !el.focused) {
+        return false;
+    }
+    if compound.ids.iter().any(|id| el.id.as_ref() != Some(id)) {
+        return false;
+    }
+    if compound.classes.iter().any(|c| !el.classes.contains(c)) {
+        return false;
+    }
+    compound.attrs.iter().all(|sel| {
+        let Some((_, value)) = el
+            .attrs
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(&sel.name))
+        else {
+            return false;
+        };
+        match &sel.op {
+            None => true,
+            Some((op, expected)) => match op.as_str() {
+                "=" => value == expected,
+                "~=" => value.split_whitespace().any(|v| v == expected),
+                "|=" => value == expected || value.starts_with(&format!("{}-", expected)),
+                "^=" => !expected.is_empty() && value.starts_with(expected.as_str()),
+                "$=" => !expected.is_empty() && value.ends_with(expected.as_str()),
+                "*=" => !expected.is_empty() && value.contains(expected.as_str()),
+                _ => false,
+            },
+        }
+    })
+}
+
+/// Parse a complex selector into compounds, each paired with the combinator
+/// linking it to the next compound.
+fn parse_selector(selector: &str) -> Option<Vec<(Compound, Combinator)>> {
+    let mut parts = Vec::new();
+    let mut current = Compound::default();
+    let mut has_current = false;
+    let mut pending = None;
+    let mut chars = selector.chars().peekable();
+
+    let ident = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let mut s = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                s.push(c);
+                chars.next();
+            } else if c == '\\' {
+                chars.next();
+                if let Some(escaped) = chars.next() {
+                    s.push(escaped);
+                }
+            } else {
+                break;
+            }
+        }
+        s
+    };
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' | '>' | '+' | '~' => {
+                chars.next();
+                if c.is_whitespace() {
+                    pending.get_or_insert(Combinator::Descendant);
+                } else if c == '>' {
+                    pending = Some(Combinator::Child);
+                } else {
+                    // Sibling combinators
+                    current.unsupported = true;
+                    pending = Some(Combinator::Descendant);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(combinator) = pending.take()
+            && has_current
+        {
+            parts.push((std::mem::take(&mut current), combinator));
+        }
+        has_current = true;
+
+        match c {
+            '*' => {
+                chars.next();
+            }
+            '#' => {
+                chars.next();
+                current.ids.push(ident(&mut chars));
+            }
+            '.' => {
+                chars.next();
+                current.classes.push(ident(&mut chars));
+            }
+            '[' => {
+                chars.next();
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                current.attrs.push(parse_attr_selector(&inner));
+            }
+            ':' => {
+                chars.next();
+                let element = chars.peek() == Some(&':');
+                if element {
+                    chars.next();
+                }
+                let name = ident(&mut chars).to_ascii_lowercase();
+                if chars.peek() == Some(&'(') {
+                    let mut depth = 0;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                if element {
+                    current.unsupported = true;
+                } else if name == "root" {
+                    current.root = true;
+                } else if name == "hover" {
+                    current.hover = true;
+                } else if name == "focus" {
+                    current.focus = true;
+                } else {
+                    current.unsupported = true;
+                }
+            }
+            c if c.is_alphanumeric() || c == '-' || c == '_' => {
+                current.tag = Some(ident(&mut chars).to_ascii_lowercase());
+            }
+            _ => return None,
+        }
+    }
+
+    if !has_current {
+        return None;
+    }
+    // The last compound's combinator is never read
+    parts.push((current, Combinator::Descendant));
+    Some(parts)
+}
+
+fn parse_attr_selector(inner: &str) -> AttrSelector {
+    for op in ["~=", "|=", "^=", "$=", "*=", "="] {
+        if let Some((name, value)) = inner.split_once(op) {
+            let value = value.trim();
+            // Drop a trailing case-sensitivity flag like ` i`
+            let value = value
+                .strip_suffix(" i")
+                .or_else(|| value.strip_suffix(" s"))
+                .unwrap_or(value)
+                .trim();
+            let value = value.trim_matches(|c| c == '"' || c == '\'');
+            return AttrSelector {
+                name: name.trim().to_string(),
+                op: Some((op.to_string(), value.to_string())),
+            };
+        }
+    }
+    AttrSelector {
+        name: inner.trim().to_string(),
+        op: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rinch_core::element::Element;
+
+    use super::*;
+    use crate::testing::TestApp;
+
+    fn element(tag: &str, id: Option<&str>, classes: &[&str]) -> MatchElement {
+        let mut attrs = Vec::new();
+        if let Some(id) = id {
+            attrs.push(("id".into(), id.into()));
+        }
+        if !classes.is_empty() {
+            attrs.push(("class".into(), classes.join(" ")));
+        }
+        MatchElement {
+            tag: tag.into(),
+            id: id.map(Into::into),
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+            attrs,
+            ..Default::default()
+        }
+    }
+
+    const MEDIA: MediaContext = MediaContext {
+        width: 800.0,
+        height: 600.0,
+        dark: false,
+    };
+
+    fn sheet(css: &str) -> Vec<(StyleSource, String)> {
+        vec![(
+            StyleSource::Stylesheet {
+                index: 0,
+                href: None,
+            },
+            css.into(),
+        )]
+    }
+
+    #[test]
+    fn selector_matching_and_specificity() {
+        let chain = [
+            element("button", Some("save"), &["btn", "primary"]),
+            element("div", None, &["toolbar"]),
+            element("body", None, &[]),
+            element("html", None, &[]),
+        ];
+
+        assert_eq!(match_selector("button", &chain), Some((0, 0, 1)));
+        assert_eq!(match_selector(".btn.primary", &chain), Some((0, 2, 0)));
+        assert_eq!(match_selector("#save", &chain), Some((1, 0, 0)));
+        assert_eq!(match_selector(".toolbar > button", &chain), Some((0, 1, 1)));
+        assert_eq!(match_selector("body .btn", &chain), Some((0, 1, 1)));
+        assert_eq!(
+            match_selector(":root button[id=save]", &chain),
+            Some((0, 2, 1))
+        );
+        assert_eq!(match_selector("body > button", &chain), None);
+        assert_eq!(match_selector(".btn:hover", &chain), None);
+        assert_eq!(match_selector("span + button", &chain), None);
+        assert_eq!(match_selector("*", &chain), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn selector_lists_match_any_selector() {
+        let chain = [
+            element("button", Some("save"), &["btn"]),
+            element("div", None, &["toolbar"]),
+        ];
+        assert!(matches_selector_list(".toolbar > button", &chain));
+        assert!(matches_selector_list("a, #save", &chain));
+        assert!(!matches_selector_list("a, .menu button", &chain));
+        assert!(!matches_selector_list("button", &[]));
+    }
+
+    #[test]
+    fn hover_and_focus_follow_element_state() {
+        let mut chain = [
+            element("button", None, &["btn"]),
+            element("div", None, &["card"]),
+        ];
+        chain[1].hovered = true;
+        assert_eq!(match_selector(".btn:hover", &chain), None);
+        assert_eq!(match_selector(".card:hover .btn", &chain), Some((0, 3, 0)));
+
+        chain[0].hovered = true;
+        chain[0].focused = true;
+        assert_eq!(match_selector(".btn:hover:focus", &chain), Some((0, 3, 0)));
+        assert_eq!(match_selector(".btn::before", &chain), None);
+    }
+
+    #[test]
+    fn later_and_more_specific_rules_win() {
+        let chain = [element("p", None, &["note"])];
+        let rules = matched_rules(
+            &sheet(
+                "p { padding: 4px; color: red; }
+                 .note { padding: 8px; }
+                 p { color: blue; }",
+            ),
+            None,
+            &chain,
+            &MEDIA,
+        );
+
+        let selectors: Vec<_> = rules.iter().map(|r| r.selector.as_str()).collect();
+        assert_eq!(selectors, vec![".note", "p", "p"]);
+        assert!(!rules[0].declarations[0].overridden);
+        assert!(
+            !rules[1].declarations[0].overridden,
+            "second `p` color wins"
+        );
+        assert!(
+            rules[2].declarations[0].overridden,
+            "padding loses to .note"
+        );
+        assert!(
+            rules[2].declarations[1].overridden,
+            "color loses to later p"
+        );
+
+        assert_eq!(
+            computed_values(&rules),
+            vec![
+                ("color".to_string(), "blue".to_string()),
+                ("padding".to_string(), "8px".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn important_and_inline_styles() {
+        let chain = [element("div", Some("main"), &[])];
+        let rules = matched_rules(
+            &sheet("div { margin: 0 !important; } #main { margin: 4px; color: red; }"),
+            Some("margin: 10px; color: green"),
+            &chain,
+            &MEDIA,
+        );
+
+        assert_eq!(rules[0].source, StyleSource::Inline);
+        assert_eq!(
+            computed_values(&rules),
+            vec![
+                ("color".to_string(), "green".to_string()),
+                ("margin".to_string(), "0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn conditional_rules_and_comments() {
+        let chain = [element("a", None, &[])];
+        let rules = matched_rules(
+            &sheet(
+                "/* a { color: gray } */
+                 @import url('x.css');
+                 @media (min-width: 600px) { a { color: red; } }
+                 @font-face { font-family: X; src: url(data:font/woff;base64,AA==); }
+                 a { background: url(data:image/png;base64,AA==); }",
+            ),
+            None,
+            &chain,
+            &MEDIA,
+        );
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].conditions, vec!["@media (min-width: 600px)"]);
+        assert!(rules[0].active);
+        assert_eq!(
+            rules[0].declarations[0].value,
+            "url(data:image/png;base64,AA==)"
+        );
+    }
+
+    #[test]
+    fn rules_in_media_that_dont_match_are_inactive() {
+        let chain = [element("p", None, &[])];
+        let rules = matched_rules(
+            &sheet(
+                "p { color: black; }
+                 @media (max-width: 600px) { p { color: red; } }
+                 @media screen and (min-width: 40em) and (prefers-color-scheme: light) {
+                     p { margin: 0; }
+                 }
+                 @media print, (400px < width <= 800px) { p { padding: 0; } }
+                 @media not all and (orientation: landscape) { p { gap: 0; } }
+                 @supports (display: grid) { p { display: grid; } }",
+            ),
+            None,
+            &chain,
+            &MEDIA,
+        );
+
+        let active: Vec<_> = rules
+            .iter()
+            .map(|r| (r.declarations[0].property.as_str(), r.active))
+            .collect();
+        assert_eq!(
+            active,
+            vec![
+                ("display", true),
+                ("gap", false),
+                ("padding", true),
+                ("margin", true),
+                ("color", false),
+                ("color", true),
+            ]
+        );
+        assert!(rules[1].declarations[0].overridden);
+        assert_eq!(
+            computed_values(&rules),
+            vec![
+                ("color".to_string(), "black".to_string()),
+                ("display".to_string(), "grid".to_string()),
+                ("margin".to_string(), "0".to_string()),
+                ("padding".to_string(), "0".to_string()),
+            ]
+        );
+
+        let narrow_dark = MediaContext {
+            width: 500.0,
+            dark: true,
+            ..MEDIA
+        };
+        let rules = matched_rules(
+            &sheet(
+                "@media (max-width: 600px) { p { color: red; } }
+                 @media (prefers-color-scheme: light) { p { margin: 0; } }",
+            ),
+            None,
+            &chain,
+            &narrow_dark,
+        );
+        let active: Vec<_> = rules.iter().map(|r| r.active).collect();
+        assert_eq!(active, vec![false, true]);
+    }
+
+    #[test]
+    fn layers_order_normal_and_important_declarations() {
+        let chain = [element("button", None, &["btn"])];
+        let rules = matched_rules(
+            &sheet(
+                "@layer reset, library;
+                 .btn { color: black; }
+                 @layer library {
+                     .btn.btn { color: blue; padding: 4px !important; }
+                 }
+                 @layer reset {
+                     button { color: red; padding: 0 !important; margin: 0; }
+                 }
+                 @layer library.theme { button { margin: 2px; } }",
+            ),
+            None,
+            &chain,
+            &MEDIA,
+        );
+
+        // Unlayered beats layered; later layers beat earlier ones
+        let layers: Vec<_> = rules.iter().map(|r| r.layer.as_deref()).collect();
+        assert_eq!(
+            layers,
+            vec![None, Some("library"), Some("library.theme"), Some("reset")]
+        );
+
+        // Important declarations reverse the layer order
+        assert_eq!(
+            computed_values(&rules),
+            vec![
+                ("color".to_string(), "black".to_string()),
+                ("margin".to_string(), "2px".to_string()),
+                ("padding".to_string(), "0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_follows_layer_and_important_order() {
+        const CSS: &str = "@layer reset, components;
+            @layer components { .box.box.box { width: 50px; height: 10px; padding: 0 !important; } }
+            @layer reset { div { padding: 7px !important; } }
+            div { width: 80px; padding: 0; }";
+        let app = TestApp::mount(
+            || Element::Html(format!(r#"<style>{CSS}</style><div class="box"></div>"#)),
+            200,
+            100,
+        );
+
+        // Unlayered `width` beats the more specific layered one; the
+        // earliest layer's `!important` padding beats the rest
+        let rect = app.rect(app.find_by_class("box").unwrap()).unwrap();
+        assert_eq!((rect.width, rect.height), (94.0, 24.0));
+
+        let rules = matched_rules(&sheet(CSS), None, &[element("div", None, &["box"])], &MEDIA);
+        assert_eq!(
+            computed_values(&rules),
+            vec![
+                ("height".to_string(), "10px".to_string()),
+                ("padding".to_string(), "7px".to_string()),
+                ("width".to_string(), "80px".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn source_labels() {
+        let linked = StyleSource::Stylesheet {
+            index: 0,
+            href: Some("app.css".into()),
+        };
+        assert_eq!(linked.to_string(), "app.css");
+        assert_eq!(
+            StyleSource::Stylesheet {
+                index: 1,
+                href: None
+            }
+            .to_string(),
+            "<style> #2"
+        );
+        assert_eq!(StyleSource::Inline.to_string(), "style attribute");
+    }
+}
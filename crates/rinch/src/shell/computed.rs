@@ -0,0 +1,294 @@
+//! Elements' computed styles.
+//!
+//! The features rinch adds on top of the style engine, such as transitions,
+//! sticky positioning, styled scrollbars and text rewrites, read the values
+//! Stylo computed for each element, so they see the same styles layout and
+//! painting do: user-agent rules, media queries, `var()`, inheritance and
+//! every selector the engine matches.
+//!
+//! The engine doesn't compute a few properties those features need, like
+//! `hyphens`, `user-select` and `overscroll-behavior`, and the document
+//! painter's decoration lines are turned off with `text-decoration-line`
+//! (see `text_effects`). Declarations of these are copied into `--rinch-*`
+//! custom properties, which the engine does compute: stylesheets get a
+//! generated copy of their rules with just those declarations
+//! ([`mirrored_rules`]), and `style` attributes get them added before the
+//! document is first styled ([`mirror_inline_styles`]). [`computed_value`]
+//! reads them back under the property's own name. Custom properties always
+//! inherit, so a user-agent stylesheet ([`MIRROR_RESET_CSS`]) resets those
+//! of properties that don't.
+
+use blitz_dom::BaseDocument;
+use style::properties::{ComputedValues, PropertyDeclarationId, PropertyId};
+use style::Atom;
+
+use super::cascade::{matching_brace, parse_declarations, strip_comments};
+
+/// Properties the style engine doesn't compute, and whether they inherit.
+const MIRRORED: &[(&str, bool)] = &[
+    ("hyphens", true),
+    ("-webkit-hyphens", true),
+    // `none` keeps a subtree unselectable unless something in it sets it
+    // back, so it's treated as inherited
+    ("user-select", true),
+    ("-webkit-user-select", true),
+    ("scrollbar-color", true),
+    ("scrollbar-width", false),
+    ("overscroll-behavior", false),
+    ("overscroll-behavior-x", false),
+    ("overscroll-behavior-y", false),
+    ("overscroll-behavior-inline", false),
+    ("overscroll-behavior-block", false),
+    ("text-decoration", false),
+    ("text-decoration-line", false),
+    ("text-decoration-style", false),
+    ("text-decoration-color", false),
+    ("text-decoration-thickness", false),
+    ("text-underline-offset", true),
+];
+
+/// User-agent stylesheet resetting the custom properties of mirrored
+/// properties that don't inherit, on every element.
+pub(crate) const MIRROR_RESET_CSS: &str = "* {
+    --rinch-scrollbar-width: initial;
+    --rinch-overscroll-behavior: initial;
+    --rinch-overscroll-behavior-x: initial;
+    --rinch-overscroll-behavior-y: initial;
+    --rinch-overscroll-behavior-inline: initial;
+    --rinch-overscroll-behavior-block: initial;
+    --rinch-text-decoration: initial;
+    --rinch-text-decoration-line: initial;
+    --rinch-text-decoration-style: initial;
+    --rinch-text-decoration-color: initial;
+    --rinch-text-decoration-thickness: initial;
+}";
+
+/// At-rules whose blocks hold style rules.
+const GROUPING_RULES: &[&str] = &["media", "supports", "layer", "container", "scope"];
+
+/// The custom property a mirrored property is copied into.
+fn mirror_name(property: &str) -> Option<String> {
+    MIRRORED
+        .iter()
+        .any(|(name, _)| *name == property)
+        .then(|| format!("--rinch-{property}"))
+}
+
+/// A node's computed value of `property` (a longhand or custom property),
+/// as CSS text. Text nodes have their parent's styles. `None` if the node
+/// isn't styled, or the property is unset or unknown.
+pub(crate) fn computed_value(doc: &BaseDocument, node_id: usize, property: &str) -> Option<String> {
+    with_styles(doc, node_id, |styles| value_of(styles, property)).flatten()
+}
+
+/// A node's computed values of `properties`, as `(property, value)` pairs,
+/// leaving out those without one.
+pub(crate) fn computed_values(
+    doc: &BaseDocument,
+    node_id: usize,
+    properties: &[&str],
+) -> Vec<(String, String)> {
+    with_styles(doc, node_id, |styles| {
+        properties
+            .iter()
+            .filter_map(|&property| Some((property.to_string(), value_of(styles, property)?)))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// A node's computed values of the properties in `declared`, with
+/// shorthands expanded to their longhands, sorted by property. DevTools
+/// lists these for the properties an element's rules set.
+pub(crate) fn declared_computed_values<'a>(
+    doc: &BaseDocument,
+    node_id: usize,
+    declared: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, String)> {
+    let mut properties = Vec::new();
+    for property in declared {
+        if property.starts_with("--") || mirror_name(property).is_some() {
+            properties.push(property);
+            continue;
+        }
+        match PropertyId::parse_enabled_for_all_content(property).map(|id| id.as_shorthand()) {
+            Ok(Ok(shorthand)) => properties.extend(shorthand.longhands().map(|l| l.name())),
+            Ok(Err(_)) => properties.push(property),
+            Err(_) => {}
+        }
+    }
+    properties.sort_unstable();
+    properties.dedup();
+    computed_values(doc, node_id, &properties)
+}
+
+/// An element's own value of `property` in its `style` attribute.
+pub(crate) fn inline_value(doc: &BaseDocument, node_id: usize, property: &str) -> Option<String> {
+    let element = doc.get_node(node_id)?.element_data()?;
+    element
+        .attrs()
+        .iter()
+        .filter(|attr| attr.name.local.as_ref() == "style")
+        .flat_map(|attr| parse_declarations(&attr.value))
+        .filter(|declaration| declaration.property == property)
+        .last()
+        .map(|declaration| declaration.value)
+}
+
+/// Run `f` with the primary styles of a node, or of its element if it's text.
+fn with_styles<R>(
+    doc: &BaseDocument,
+    node_id: usize,
+    f: impl FnOnce(&ComputedValues) -> R,
+) -> Option<R> {
+    let mut node = doc.get_node(node_id)?;
+    while node.element_data().is_none() {
+        node = doc.get_node(node.parent?)?;
+    }
+    let styles = node.primary_styles()?;
+    Some(f(&styles))
+}
+
+fn value_of(styles: &ComputedValues, property: &str) -> Option<String> {
+    let mirror = mirror_name(property);
+    let property = mirror.as_deref().unwrap_or(property);
+    let value = match property.strip_prefix("--") {
+        Some(name) => {
+            styles.computed_value_to_string(PropertyDeclarationId::Custom(&Atom::from(name)))
+        }
+        None => {
+            let longhand = PropertyId::parse_enabled_for_all_content(property)
+                .ok()?
+                .longhand_id()?;
+            styles.computed_value_to_string(PropertyDeclarationId::Longhand(longhand))
+        }
+    };
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// A stylesheet's rules cut down to their declarations of mirrored
+/// properties, copied into the custom properties.
+///
+/// `.code { hyphens: auto }` becomes `.code { --rinch-hyphens: auto; }`.
+pub(crate) fn mirrored_rules(css: &str) -> String {
+    let mut rules = String::new();
+    collect_mirrored_rules(&strip_comments(css), &mut rules);
+    rules
+}
+
+fn collect_mirrored_rules(css: &str, out: &mut String) {
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        // Skip statements like `@import ...;` before the rule
+        let prelude = rest[..open].rsplit(';').next().unwrap_or_default().trim();
+        let body = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            // Copying `@keyframes` or `@font-face` would replace the
+            // originals
+            let name = at_rule
+                .split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if GROUPING_RULES.contains(&name.as_str()) {
+                let mut inner = String::new();
+                collect_mirrored_rules(body, &mut inner);
+                if !inner.is_empty() {
+                    out.push_str(&format!("{} {{\n{}}}\n", prelude, inner));
+                }
+            }
+            continue;
+        }
+        let declarations: String = parse_declarations(body)
+            .iter()
+            .filter_map(|d| {
+                let important = if d.important { " !important" } else { "" };
+                Some(format!(
+                    "{}: {}{}; ",
+                    mirror_name(&d.property)?,
+                    d.value,
+                    important
+                ))
+            })
+            .collect();
+        if !declarations.is_empty() {
+            out.push_str(&format!("{} {{ {}}}\n", prelude, declarations));
+        }
+    }
+}
+
+/// Copy mirrored properties in `style` attributes into their custom
+/// properties. Call before the document is first styled.
+pub(crate) fn mirror_inline_styles(doc: &mut BaseDocument) {
+    let mut mirrored = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter());
+        let Some(style) = node.element_data().and_then(|el| {
+            el.attrs()
+                .iter()
+                .find(|a| a.name.local.as_ref() == "style")
+                .map(|a| a.value.to_string())
+        }) else {
+            continue;
+        };
+        for declaration in parse_declarations(&style) {
+            if let Some(property) = mirror_name(&declaration.property) {
+                mirrored.push((node_id, property, declaration.value));
+            }
+        }
+    }
+
+    if mirrored.is_empty() {
+        return;
+    }
+    let mut mutator = doc.mutate();
+    for (node_id, property, value) in mirrored {
+        mutator.set_style_property(node_id, &property, &value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrors_properties_the_engine_does_not_compute() {
+        let css = r#"
+            /* long words */
+            .code, pre { hyphens: auto; color: red; }
+            .list { overscroll-behavior: contain !important; }
+            @media (max-width: 600px) {
+                .card { text-decoration: underline wavy; user-select: none; }
+            }
+            @keyframes fade { from { hyphens: none; } }
+            p { margin: 0; }
+        "#;
+        let rules = mirrored_rules(css);
+        assert!(rules.contains(".code, pre { --rinch-hyphens: auto; }"));
+        assert!(rules.contains(".list { --rinch-overscroll-behavior: contain !important; }"));
+        assert!(rules.contains(
+            "@media (max-width: 600px) {\n.card { --rinch-text-decoration: underline wavy; --rinch-user-select: none; }"
+        ));
+        assert!(!rules.contains("fade"));
+        assert!(!rules.contains("color"));
+        assert!(!rules.contains("margin"));
+    }
+
+    #[test]
+    fn properties_that_do_not_inherit_are_reset() {
+        for (property, inherited) in MIRRORED {
+            let reset = format!("--rinch-{property}: initial;");
+            assert_eq!(MIRROR_RESET_CSS.contains(&reset), !inherited, "{property}");
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! The panic boundary.
+//!
+//! A panic in app code (a render, an event handler, a menu callback, a
+//! timer) is caught instead of ending the app. The window it happened in
+//! shows a crash panel with the panic message, where it happened and a
+//! backtrace, and a "Reload window" button that renders the app into it
+//! again; other windows keep working. A panic while rendering shows the
+//! panel in every window, since they're all rendered together. A panic
+//! inside an `ErrorBoundary` is caught by the boundary instead.
+//!
+//! The panel is plain HTML with `rinch-crash-*` classes, styled by
+//! [`CRASH_PANEL_CSS`]. Apps can restyle it to match:
+//!
+//! ```ignore
+//! use rinch::shell::crash::set_crash_panel_styles;
+//!
+//! fn main() {
+//!     set_crash_panel_styles(".rinch-crash { background: #1e1e2e; color: #cdd6f4; }");
+//!     rinch::run(app);
+//! }
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use rinch_core::events::html_escape_string;
+
+/// The crash panel's default styles.
+pub const CRASH_PANEL_CSS: &str = r#"
+.rinch-crash {
+    box-sizing: border-box;
+    min-height: 100vh;
+    padding: 32px;
+    font-family: system-ui, sans-serif;
+    background: #fff8f6;
+    color: #3b1d1a;
+}
+.rinch-crash-title { margin: 0 0 12px; font-size: 20px; }
+.rinch-crash-message { margin: 0 0 4px; font-size: 15px; font-weight: 600; }
+.rinch-crash-location { margin: 0 0 16px; font-size: 13px; opacity: 0.75; }
+.rinch-crash-backtrace {
+    max-height: 50vh;
+    overflow: auto;
+    margin: 0 0 16px;
+    padding: 12px;
+    font-size: 12px;
+    background: #f3e4e1;
+    border-radius: 4px;
+}
+.rinch-crash-reload {
+    padding: 6px 14px;
+    font-size: 14px;
+    border: 1px solid #b3261e;
+    border-radius: 4px;
+    background: #b3261e;
+    color: white;
+}
+"#;
+
+/// Attribute naming the crash panel action a button takes.
+pub(crate) const CRASH_ACTION_ATTR: &str = "data-rinch-crash";
+
+thread_local! {
+    /// The last panic on this thread, recorded by the panic hook.
+    static LAST_PANIC: RefCell<Option<Crash>> = const { RefCell::new(None) };
+    /// Styles replacing [`CRASH_PANEL_CSS`].
+    static PANEL_STYLES: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// A caught panic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crash {
+    /// The panic message.
+    pub message: String,
+    /// Where it panicked, as `file:line:column`.
+    pub location: Option<String>,
+    /// The backtrace, empty if none was captured.
+    pub backtrace: String,
+}
+
+impl Crash {
+    fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "the app panicked".into());
+        Self {
+            message,
+            location: None,
+            backtrace: String::new(),
+        }
+    }
+}
+
+/// Style the crash panel with `css` instead of [`CRASH_PANEL_CSS`]. Call it
+/// before `run`, or from the app: it applies to panels shown after it.
+pub fn set_crash_panel_styles(css: impl Into<String>) {
+    PANEL_STYLES.with(|styles| *styles.borrow_mut() = Some(css.into()));
+}
+
+/// Record panics' locations and backtraces for the crash panel, as well as
+/// reporting them as before (called once by the runtime).
+pub(crate) fn install_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let mut crash = Crash::from_payload(info.payload());
+            crash.location = info.location().map(|location| location.to_string());
+            crash.backtrace = std::backtrace::Backtrace::force_capture().to_string();
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(crash));
+            previous(info);
+        }));
+    });
+}
+
+/// Run app code, catching a panic from it.
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, Crash> {
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        rinch_core::reset_after_panic();
+        let crash = LAST_PANIC.with(|last| last.borrow_mut().take());
+        crash.unwrap_or_else(|| Crash::from_payload(&*payload))
+    })
+}
+
+/// The crash panel's HTML for `crash`.
+pub(crate) fn crash_panel_html(crash: &Crash) -> String {
+    let styles = PANEL_STYLES
+        .with(|styles| styles.borrow().clone())
+        .unwrap_or_else(|| CRASH_PANEL_CSS.to_string());
+    let location = crash
+        .location
+        .as_ref()
+        .map(|location| {
+            format!(
+                r#"<p class="rinch-crash-location">at {}</p>"#,
+                html_escape_string(location)
+            )
+        })
+        .unwrap_or_default();
+    let backtrace = if crash.backtrace.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#"<pre class="rinch-crash-backtrace">{}</pre>"#,
+            html_escape_string(&crash.backtrace)
+        )
+    };
+    format!(
+        r#"<style>{styles}</style><div class="rinch-crash" role="alert"><h1 class="rinch-crash-title">Something went wrong</h1><p class="rinch-crash-message">{}</p>{location}{backtrace}<button class="rinch-crash-reload" {CRASH_ACTION_ATTR}="reload">Reload window</button></div>"#,
+        html_escape_string(&crash.message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_are_caught_with_their_location() {
+        install_hook();
+        assert_eq!(catch(|| 1), Ok(1));
+        let crash = catch::<()>(|| panic!("no <rows> left")).unwrap_err();
+        assert_eq!(crash.message, "no <rows> left");
+        assert!(crash.location.unwrap().starts_with(file!()));
+
+        let crash = Crash {
+            message: "no <rows> left".into(),
+            location: None,
+            backtrace: String::new(),
+        };
+        let html = crash_panel_html(&crash);
+        assert!(html.contains("no &lt;rows&gt; left"));
+        assert!(html.contains(r#"data-rinch-crash="reload""#));
+        assert!(!html.contains("rinch-crash-backtrace\""));
+
+        set_crash_panel_styles(".rinch-crash { color: red; }");
+        assert!(crash_panel_html(&crash).starts_with("<style>.rinch-crash { color: red; }</style>"));
+    }
+}
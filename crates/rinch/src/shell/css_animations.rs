@@ -0,0 +1,313 @@
+//! CSS animations across re-renders.
+//!
+//! The style engine runs `@keyframes` animations, but starts an element's
+//! animations when the element is created, and a re-render builds a new
+//! document. Left alone, every re-render would restart every spinner and
+//! skeleton shimmer, and replay entrance animations that had finished.
+//!
+//! So rinch remembers when each animated element's animations started, by
+//! its place in the tree. When a rebuilt document has the same element at
+//! that place, it gets a negative `animation-delay` (in its inline style) of
+//! the time already played before it's first styled, and carries on from
+//! where the old element was. If its computed `animation-name`s turn out
+//! different, say because a class was switched, it gets its own delays back
+//! and its animations start over.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+
+use super::computed::{computed_value, computed_values, inline_value};
+use super::scroll::{node_at_path, node_path};
+use super::text_effects::split_outside_parens;
+use super::transitions::{parse_time, TimingFunction};
+
+/// Keywords in the `animation` shorthand that aren't names.
+const KEYWORDS: &[&str] = &[
+    "infinite",
+    "normal",
+    "reverse",
+    "alternate",
+    "alternate-reverse",
+    "none",
+    "forwards",
+    "backwards",
+    "both",
+    "running",
+    "paused",
+];
+
+/// An element's animations: their names, and the delays its styles give
+/// them.
+#[derive(Debug, Clone, PartialEq)]
+struct Animations {
+    names: Vec<String>,
+    delays: Vec<Duration>,
+}
+
+impl Animations {
+    /// The animations `animation` and its longhands declare, if any.
+    fn declared(values: &[(String, String)]) -> Option<Self> {
+        let value = |property: &str| {
+            values
+                .iter()
+                .rev()
+                .find(|(p, _)| p == property)
+                .map(|(_, v)| v.as_str())
+        };
+        let list = |property: &str| value(property).map(|v| split_outside_parens(v, ','));
+
+        let mut names = Vec::new();
+        let mut delays = Vec::new();
+        for item in list("animation").unwrap_or_default() {
+            let (mut name, mut duration, mut delay) = (None, None, None);
+            for token in split_outside_parens(item, ' ') {
+                if let Some(time) = parse_time(token) {
+                    if duration.is_none() {
+                        duration = Some(time);
+                    } else {
+                        delay = Some(time);
+                    }
+                } else if TimingFunction::parse(token).is_none()
+                    && token.parse::<f64>().is_err()
+                    && !KEYWORDS.contains(&token)
+                {
+                    name = Some(token);
+                }
+            }
+            names.push(name.unwrap_or("none").to_string());
+            delays.push(delay.unwrap_or_default());
+        }
+        if let Some(list) = list("animation-name") {
+            names = list.into_iter().map(str::to_string).collect();
+        }
+        if let Some(list) = list("animation-delay") {
+            delays = list
+                .into_iter()
+                .map(|v| parse_time(v).unwrap_or_default())
+                .collect();
+        }
+
+        names
+            .iter()
+            .any(|name| name != "none")
+            .then_some(Self { names, delays })
+    }
+
+    /// The `animation-delay` that carries the animations on after
+    /// `elapsed`.
+    fn carried_on(&self, elapsed: Duration) -> String {
+        (0..self.names.len())
+            .map(|i| {
+                let delay = match self.delays.len() {
+                    0 => Duration::ZERO,
+                    len => self.delays[i % len],
+                };
+                let seconds = delay.as_secs_f64() - elapsed.as_secs_f64();
+                format!("{:.3}s", seconds)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// An animated element, and when its animations started.
+#[derive(Debug)]
+struct Animated {
+    tag: String,
+    animations: Animations,
+    started: Instant,
+}
+
+/// An element of a rebuilt document at the place of an animated one in the
+/// old document, given a delay carrying the old animations on.
+#[derive(Debug)]
+struct Carried {
+    node_id: usize,
+    path: Vec<usize>,
+    previous: Animated,
+    /// Its own inline `animation-delay`, if it had one.
+    inline_delay: Option<String>,
+}
+
+/// CSS animations in a window's document.
+#[derive(Debug, Default)]
+pub(crate) struct CssAnimations {
+    /// Animated elements, by tree path.
+    elements: HashMap<Vec<usize>, Animated>,
+    /// Elements of a rebuilt document given carried-on delays, until it's
+    /// styled.
+    carried: Vec<Carried>,
+    /// Elements whose animations are held off to start them over, with
+    /// their paths and own inline `animation-name`s.
+    restarting: Vec<(usize, Vec<usize>, Option<String>)>,
+}
+
+impl CssAnimations {
+    /// Carry on the animations of the elements of a freshly built document
+    /// that are where animated elements of the old one were. Call before
+    /// the document is first styled, then call [`Self::styled`].
+    pub(crate) fn carry_on(&mut self, doc: &mut BaseDocument, now: Instant) {
+        self.carried.clear();
+        self.restarting.clear();
+        for (path, previous) in std::mem::take(&mut self.elements) {
+            let Some(node_id) = node_at_path(doc, &path) else {
+                continue;
+            };
+            let Some(data) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+                continue;
+            };
+            if data.name.local.as_ref() != previous.tag {
+                continue;
+            }
+            let inline_delay = inline_value(doc, node_id, "animation-delay");
+            self.carried.push(Carried {
+                node_id,
+                path,
+                previous,
+                inline_delay,
+            });
+        }
+
+        let mut mutator = doc.mutate();
+        for carried in &self.carried {
+            let previous = &carried.previous;
+            let delay = previous.animations.carried_on(now - previous.started);
+            mutator.set_style_property(carried.node_id, "animation-delay", &delay);
+        }
+    }
+
+    /// Find the animated elements once the document is styled. Elements
+    /// that were carried on but now run other animations get their own
+    /// delays back and are held off. Returns whether any were, in which
+    /// case restyle the document and call [`Self::restarted`].
+    pub(crate) fn styled(&mut self, doc: &mut BaseDocument, now: Instant) -> bool {
+        let mut carried: HashMap<usize, Carried> = std::mem::take(&mut self.carried)
+            .into_iter()
+            .map(|carried| (carried.node_id, carried))
+            .collect();
+
+        let mut restored = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            let Some(data) = node.element_data() else {
+                continue;
+            };
+            let animations = Animations::declared(&computed_values(
+                doc,
+                node_id,
+                &["animation-name", "animation-delay"],
+            ));
+            if let Some(carried) = carried.remove(&node_id) {
+                if let Some(animations) = &animations
+                    && animations.names == carried.previous.animations.names
+                {
+                    self.elements.insert(carried.path, carried.previous);
+                    continue;
+                }
+                restored.push((node_id, carried.inline_delay));
+                if animations.is_some() {
+                    let name = inline_value(doc, node_id, "animation-name");
+                    self.restarting.push((node_id, carried.path.clone(), name));
+                }
+            }
+            if let Some(animations) = animations {
+                self.elements.insert(
+                    node_path(doc, node_id),
+                    Animated {
+                        tag: data.name.local.to_string(),
+                        animations,
+                        started: now,
+                    },
+                );
+            }
+        }
+
+        let mut mutator = doc.mutate();
+        for (node_id, delay) in restored {
+            match delay {
+                Some(delay) => mutator.set_style_property(node_id, "animation-delay", &delay),
+                None => mutator.remove_style_property(node_id, "animation-delay"),
+            }
+        }
+        for (node_id, ..) in &self.restarting {
+            mutator.set_style_property(*node_id, "animation-name", "none");
+        }
+        !self.restarting.is_empty()
+    }
+
+    /// Start the held-off animations over, once the document was restyled
+    /// with them held off and their own delays back. Restyle it again
+    /// afterwards.
+    pub(crate) fn restarted(&mut self, doc: &mut BaseDocument) {
+        let restarting = std::mem::take(&mut self.restarting);
+        for (node_id, path, _) in &restarting {
+            // The delays were read while the carried-on ones were in place
+            if let Some(animated) = self.elements.get_mut(path) {
+                let delays = computed_value(doc, *node_id, "animation-delay").unwrap_or_default();
+                animated.animations.delays = split_outside_parens(&delays, ',')
+                    .into_iter()
+                    .map(|v| parse_time(v).unwrap_or_default())
+                    .collect();
+            }
+        }
+
+        let mut mutator = doc.mutate();
+        for (node_id, _, name) in restarting {
+            match name {
+                Some(name) => mutator.set_style_property(node_id, "animation-name", &name),
+                None => mutator.remove_style_property(node_id, "animation-name"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn animations_are_parsed() {
+        let animations = Animations::declared(&values(&[(
+            "animation",
+            "spin 1s linear infinite, pulse 2s ease-in-out 500ms 3 alternate",
+        )]))
+        .unwrap();
+        assert_eq!(animations.names, ["spin", "pulse"]);
+        assert_eq!(
+            animations.delays,
+            [Duration::ZERO, Duration::from_millis(500)]
+        );
+
+        let animations = Animations::declared(&values(&[
+            ("animation", "spin 1s infinite"),
+            ("animation-name", "shimmer"),
+        ]))
+        .unwrap();
+        assert_eq!(animations.names, ["shimmer"]);
+
+        assert_eq!(
+            Animations::declared(&values(&[("animation", "none")])),
+            None
+        );
+        assert_eq!(Animations::declared(&values(&[("color", "red")])), None);
+    }
+
+    #[test]
+    fn delays_carry_animations_on() {
+        let animations = Animations {
+            names: vec!["spin".into(), "pulse".into()],
+            delays: vec![Duration::ZERO, Duration::from_millis(500)],
+        };
+        assert_eq!(
+            animations.carried_on(Duration::from_millis(1250)),
+            "-1.250s, -0.750s"
+        );
+    }
+}
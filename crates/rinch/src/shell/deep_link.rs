@@ -0,0 +1,217 @@
+//! Custom URL schemes.
+//!
+//! [`register_protocol`] makes the OS open `myapp://...` URLs with the
+//! running executable, which receives the URL as its first argument. The
+//! runtime picks links in registered schemes out of its own arguments at
+//! startup and out of arguments forwarded by later launches (see
+//! `run_single_instance`), and hands them to the app through
+//! `use_deep_links`.
+
+use std::io;
+use std::sync::Mutex;
+
+/// Schemes registered by this process, lowercase.
+static SCHEMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Register the running executable as the handler for `scheme` URLs.
+///
+/// Call it from `main`, before `run`, so a link the app is launched with is
+/// recognized. Registering again (on every start, say) is harmless and
+/// keeps the registration pointing at the current executable.
+///
+/// - **Linux**: writes a `.desktop` file for the scheme to
+///   `~/.local/share/applications` and makes it the default handler with
+///   `xdg-mime`.
+/// - **Windows**: writes `HKEY_CURRENT_USER\Software\Classes\<scheme>`.
+/// - **macOS**: schemes can only be declared in the app bundle's
+///   `Info.plist` (`CFBundleURLTypes`), so this returns an `Unsupported`
+///   error, after recording the scheme for this process.
+///
+/// Without `run_single_instance`, each link the OS opens starts a new
+/// instance of the app.
+///
+/// # Example
+///
+/// ```ignore
+/// fn main() {
+///     if let Err(e) = rinch::shell::register_protocol("myapp") {
+///         eprintln!("Couldn't register myapp:// links: {e}");
+///     }
+///     rinch::run_single_instance(app, |_| {});
+/// }
+/// ```
+pub fn register_protocol(scheme: &str) -> io::Result<()> {
+    if !is_valid_scheme(scheme) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{scheme}` isn't a valid URL scheme"),
+        ));
+    }
+    let scheme = scheme.to_ascii_lowercase();
+    {
+        let mut schemes = SCHEMES.lock().unwrap_or_else(|e| e.into_inner());
+        if !schemes.contains(&scheme) {
+            schemes.push(scheme.clone());
+        }
+    }
+    register_with_os(&scheme)
+}
+
+/// The arguments that are links in a registered scheme.
+pub(crate) fn deep_links_in(args: &[String]) -> Vec<String> {
+    let schemes = SCHEMES.lock().unwrap_or_else(|e| e.into_inner());
+    args.iter()
+        .filter(|arg| {
+            arg.split_once(':')
+                .is_some_and(|(scheme, _)| schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `scheme` is a URL scheme: a letter followed by letters, digits,
+/// `+`, `-` and `.` (RFC 3986).
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn register_with_os(scheme: &str) -> io::Result<()> {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    let exe = std::env::current_exe()?;
+    let name = exe
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "rinch".into());
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    let applications = data_home.join("applications");
+    std::fs::create_dir_all(&applications)?;
+
+    let file_name = format!("{name}-{scheme}-handler.desktop");
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={name}\n\
+         Exec={} %u\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/{scheme};\n",
+        desktop_exec_quote(&exe.to_string_lossy()),
+    );
+    std::fs::write(applications.join(&file_name), entry)?;
+
+    let status = Command::new("xdg-mime")
+        .args(["default", &file_name, &format!("x-scheme-handler/{scheme}")])
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("xdg-mime failed ({status})")));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn register_with_os(scheme: &str) -> io::Result<()> {
+    use super::autostart::reg;
+
+    let exe = std::env::current_exe()?;
+    let key = format!(r"HKCU\Software\Classes\{scheme}");
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let entries: [&[&str]; 3] = [
+        &["/ve", "/d", &format!("URL:{scheme} Protocol")],
+        &["/v", "URL Protocol", "/d", ""],
+        &["/ve", "/d", &command],
+    ];
+    for (index, entry) in entries.iter().enumerate() {
+        let key = if index == 2 {
+            format!(r"{key}\shell\open\command")
+        } else {
+            key.clone()
+        };
+        let args: Vec<&str> = ["add", key.as_str()]
+            .into_iter()
+            .chain(entry.iter().copied())
+            .chain(["/f"])
+            .collect();
+        let status = reg(&args)?.status;
+        if !status.success() {
+            return Err(io::Error::other(format!("reg add {key} failed ({status})")));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+fn register_with_os(scheme: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("declare the `{scheme}` scheme in the app bundle's Info.plist (CFBundleURLTypes)"),
+    ))
+}
+
+/// Quote a path for a desktop entry's `Exec` key.
+///
+/// The quoted argument's backslashes are then escaped again, as the key's
+/// value is a string, which has escapes of its own.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+pub(crate) fn desktop_exec_quote(path: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in path.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    let mut value = String::with_capacity(quoted.len());
+    for c in quoted.chars() {
+        match c {
+            '\\' => value.push_str(r"\\"),
+            '\n' => value.push_str(r"\n"),
+            '\t' => value.push_str(r"\t"),
+            '\r' => value.push_str(r"\r"),
+            // `%` introduces field codes, so literal ones are doubled
+            '%' => value.push_str("%%"),
+            c => value.push(c),
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemes_are_validated_and_matched() {
+        assert!(is_valid_scheme("my-app+x.2"));
+        assert!(!is_valid_scheme("2app"));
+        assert!(!is_valid_scheme("my app"));
+        assert!(register_protocol("not valid").is_err());
+
+        SCHEMES.lock().unwrap().push("rinchtest".into());
+        let args = [
+            "--verbose".to_string(),
+            "RinchTest://auth?code=1".to_string(),
+            "other://x".to_string(),
+        ];
+        assert_eq!(deep_links_in(&args), ["RinchTest://auth?code=1"]);
+    }
+
+    #[test]
+    fn exec_paths_are_quoted() {
+        assert_eq!(
+            desktop_exec_quote("/opt/My App/$bin%"),
+            r#""/opt/My App/\\$bin%%""#
+        );
+        // A literal backslash is escaped for the command line, then both
+        // backslashes for the string
+        assert_eq!(desktop_exec_quote(r"/opt/a\b"), r#""/opt/a\\\\b""#);
+        assert_eq!(desktop_exec_quote("/opt/a\nb"), r#""/opt/a\nb""#);
+    }
+}
@@ -3,6 +3,17 @@
 //! Provides a developer tools panel for inspecting the UI tree,
 //! viewing element styles, and debugging hook state.
 
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::kurbo::{Affine, Rect, Stroke};
+use peniko::{Color, Fill};
+
+use super::scroll::border_box_origin;
+
+/// Fill and outline of the element selected in the DevTools window.
+const HIGHLIGHT_FILL: Color = Color::from_rgba8(0x4a, 0x90, 0xd9, 0x40);
+const HIGHLIGHT_OUTLINE: Color = Color::from_rgba8(0x4a, 0x90, 0xd9, 0xff);
+
 /// The currently active panel in the devtools.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DevToolsPanel {
@@ -69,4 +80,24 @@ impl DevToolsState {
     pub fn set_panel(&mut self, panel: DevToolsPanel) {
         self.active_panel = panel;
     }
+
+    /// Highlight the selected node's border box.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        let Some(node) = self.selected_node.and_then(|id| doc.get_node(id)) else {
+            return;
+        };
+        let (x, y) = border_box_origin(doc, node.id);
+        let size = node.final_layout.size;
+        let rect = Rect::new(x, y, x + size.width as f64, y + size.height as f64);
+        let transform = Affine::scale(scale);
+        scene.fill(Fill::NonZero, transform, HIGHLIGHT_FILL, None, &rect);
+        let outline = Stroke::new(1.0 / scale);
+        scene.stroke(
+            &outline,
+            transform,
+            HIGHLIGHT_OUTLINE,
+            None,
+            &rect.inset(-0.5 / scale),
+        );
+    }
 }
@@ -2,8 +2,12 @@
 //!
 //! Generates HTML for the devtools panel overlay.
 
+use std::time::{Duration, Instant};
+
 use super::devtools::{DevToolsPanel, DevToolsState};
+use rinch_core::events::html_escape_string;
 use rinch_core::hooks::get_hooks_debug_info;
+use rinch_core::reactive::{ReactiveGraph, ReactiveNode, ReactiveNodeKind};
 
 /// Render the devtools overlay as HTML.
 ///
@@ -192,8 +196,12 @@ fn render_hooks_panel() -> String {
                 ">
                     <div style="color: #569cd6;">#{} {}</div>
                     <div style="color: #808080; font-size: 11px;">{}</div>
+                    <div style="color: #b5cea8;">{}</div>
                 </div>"#,
-                i, info.hook_type, info.value_type
+                i,
+                info.hook_type,
+                html_escape_string(info.value_type),
+                html_escape_string(info.value.as_deref().unwrap_or_default())
             )
         })
         .collect();
@@ -208,6 +216,158 @@ fn render_hooks_panel() -> String {
     )
 }
 
+/// Render the reactive graph for the DevTools window: signals, memos and
+/// effects in columns, each listing the nodes it reads (`←`) and the nodes
+/// that read it (`→`). Nodes that look leaked are listed first and marked.
+pub fn render_reactive_graph(graph: &ReactiveGraph, now: Instant) -> String {
+    if graph.nodes.is_empty() {
+        return r#"<p style="color: #808080;">No signals or effects.</p>"#.to_string();
+    }
+
+    let suspicious = graph
+        .nodes
+        .iter()
+        .filter(|node| node.warning().is_some())
+        .count();
+    let count = |kind| graph.nodes.iter().filter(|node| node.kind == kind).count();
+    let summary = format!(
+        r#"<div class="graph-summary">{} signals, {} memos, {} effects, {} subscriptions{}</div>"#,
+        count(ReactiveNodeKind::Signal),
+        count(ReactiveNodeKind::Memo),
+        count(ReactiveNodeKind::Effect),
+        graph.edges.len(),
+        if suspicious > 0 {
+            format!(r#", <span class="graph-warning">{suspicious} suspicious</span>"#)
+        } else {
+            String::new()
+        }
+    );
+
+    let column = |kind, title| {
+        let mut nodes: Vec<_> = graph
+            .nodes
+            .iter()
+            .filter(|node| node.kind == kind)
+            .collect();
+        nodes.sort_by_key(|node| node.warning().is_none());
+        let cards: String = nodes
+            .into_iter()
+            .map(|node| render_graph_node(graph, node, now))
+            .collect();
+        format!(r#"<div class="graph-column"><div class="layout-title">{title}</div>{cards}</div>"#)
+    };
+
+    format!(
+        r#"{summary}<div class="graph">{}{}{}</div>"#,
+        column(ReactiveNodeKind::Signal, "Signals"),
+        column(ReactiveNodeKind::Memo, "Memos"),
+        column(ReactiveNodeKind::Effect, "Effects"),
+    )
+}
+
+/// Render one node of the reactive graph.
+fn render_graph_node(graph: &ReactiveGraph, node: &ReactiveNode, now: Instant) -> String {
+    let ids = |ids: Vec<usize>| {
+        ids.iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let sources: Vec<_> = graph.sources(node.id).collect();
+    let subscribers: Vec<_> = graph.subscribers(node.id).collect();
+    let mut edges = String::new();
+    if !sources.is_empty() {
+        edges.push_str(&format!(
+            r#"<div class="graph-edges">← {}</div>"#,
+            ids(sources)
+        ));
+    }
+    if !subscribers.is_empty() {
+        edges.push_str(&format!(
+            r#"<div class="graph-edges">→ {}</div>"#,
+            ids(subscribers)
+        ));
+    }
+
+    let label = match node.kind {
+        ReactiveNodeKind::Effect => effect_name(node.label).to_string(),
+        _ => short_type_name(node.label),
+    };
+    let last_update = match node.last_update {
+        Some(at) => ago(now.saturating_duration_since(at)),
+        None => "never".to_string(),
+    };
+    let warning = node
+        .warning()
+        .map(|warning| {
+            format!(
+                r#"<div class="graph-warning">⚠ {}</div>"#,
+                html_escape_string(&warning)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<div class="graph-node{}">
+            <div><span class="graph-id">#{}</span> <span class="graph-label">{}</span></div>
+            <div class="graph-stats">{} updates, last {}</div>
+            {}{}
+        </div>"#,
+        if warning.is_empty() {
+            ""
+        } else {
+            " suspicious"
+        },
+        node.id,
+        html_escape_string(&label),
+        node.updates,
+        last_update,
+        edges,
+        warning
+    )
+}
+
+/// The function that created an effect, from its closure's type name:
+/// `app::counter::{{closure}}` is `app::counter`.
+fn effect_name(closure: &str) -> &str {
+    let mut name = closure;
+    while let Some(outer) = name.strip_suffix("::{{closure}}") {
+        name = outer;
+    }
+    name
+}
+
+/// A type name without module paths: `alloc::vec::Vec<alloc::string::String>`
+/// is `Vec<String>`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    // Where the current path began
+    let mut start = 0;
+    let mut rest = name;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("::") {
+            short.truncate(start);
+            rest = after;
+            continue;
+        }
+        short.push(c);
+        if !(c.is_alphanumeric() || c == '_') {
+            start = short.len();
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    short
+}
+
+/// How long ago something happened, roughly.
+fn ago(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0 => "just now".to_string(),
+        seconds @ 1..60 => format!("{seconds}s ago"),
+        seconds => format!("{}m ago", seconds / 60),
+    }
+}
+
 /// CSS styles for the devtools overlay.
 /// These can be included in the document head for proper styling.
 pub fn devtools_styles() -> &'static str {
@@ -227,3 +387,27 @@ pub fn devtools_styles() -> &'static str {
     }
     "#
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_shortened() {
+        assert_eq!(
+            short_type_name("alloc::vec::Vec<alloc::string::String>"),
+            "Vec<String>"
+        );
+        assert_eq!(
+            short_type_name("core::option::Option<(i32, &str)>"),
+            "Option<(i32, &str)>"
+        );
+        assert_eq!(
+            effect_name("app::counter::{{closure}}::{{closure}}"),
+            "app::counter"
+        );
+        assert_eq!(ago(Duration::from_millis(300)), "just now");
+        assert_eq!(ago(Duration::from_secs(12)), "12s ago");
+        assert_eq!(ago(Duration::from_secs(150)), "2m ago");
+    }
+}
@@ -0,0 +1,169 @@
+//! Dragging elements with `ondragstart`, `ondrag` and `ondragend`.
+//!
+//! Pressing the mouse on an element with drag handlers (or inside one)
+//! arms a drag. Once the pointer has moved further than
+//! [`DRAG_THRESHOLD`], `ondragstart` runs, then `ondrag` on every move
+//! until the button is released, which runs `ondragend`. A press that's
+//! released before the pointer moves that far is a click, as before.
+//!
+//! Elements with drag handlers don't drag the window, even if they're
+//! `draggable` or inside a `data-drag-window` title bar.
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::{DragEvent, EventHandlerId};
+
+/// How far the pointer moves, in logical pixels, before a press becomes a
+/// drag.
+pub const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Attribute with an element's `ondragstart` handler ID.
+const DRAG_START_ATTR: &str = "data-ondragstart";
+/// Attribute with an element's `ondrag` handler ID.
+const DRAG_ATTR: &str = "data-ondrag";
+/// Attribute with an element's `ondragend` handler ID.
+const DRAG_END_ATTR: &str = "data-ondragend";
+
+/// An element's drag handlers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DragHandlers {
+    start: Option<EventHandlerId>,
+    drag: Option<EventHandlerId>,
+    end: Option<EventHandlerId>,
+}
+
+impl DragHandlers {
+    /// The handlers of `node_id`, or of the nearest element above it with
+    /// any.
+    pub(crate) fn find(doc: &BaseDocument, node_id: usize) -> Option<Self> {
+        let mut current = Some(node_id);
+        while let Some(node) = current.and_then(|id| doc.get_node(id)) {
+            current = node.parent;
+            let Some(data) = node.element_data() else {
+                continue;
+            };
+            let handler = |name: &str| {
+                data.attrs()
+                    .iter()
+                    .find(|attr| attr.name.local.as_ref() == name)
+                    .and_then(|attr| attr.value.parse().ok())
+                    .map(EventHandlerId)
+            };
+            let handlers = Self {
+                start: handler(DRAG_START_ATTR),
+                drag: handler(DRAG_ATTR),
+                end: handler(DRAG_END_ATTR),
+            };
+            if handlers != Self::default() {
+                return Some(handlers);
+            }
+        }
+        None
+    }
+}
+
+/// A press on an element with drag handlers, and the drag it may become.
+#[derive(Debug, Clone)]
+pub(crate) struct ElementDrag {
+    handlers: DragHandlers,
+    start: (f32, f32),
+    started: bool,
+}
+
+impl ElementDrag {
+    /// A press at `start`, in document coordinates.
+    pub(crate) fn new(handlers: DragHandlers, start: (f32, f32)) -> Self {
+        Self {
+            handlers,
+            start,
+            started: false,
+        }
+    }
+
+    /// Whether the pointer has moved far enough to be dragging. The release
+    /// that ends a drag isn't a click.
+    pub(crate) fn started(&self) -> bool {
+        self.started
+    }
+
+    /// The handlers to run now the pointer has moved to `pos`.
+    pub(crate) fn moved(&mut self, pos: (f32, f32)) -> Vec<(EventHandlerId, DragEvent)> {
+        let mut calls = Vec::new();
+        if !self.started {
+            let (dx, dy) = (pos.0 - self.start.0, pos.1 - self.start.1);
+            if dx.hypot(dy) < DRAG_THRESHOLD {
+                return calls;
+            }
+            self.started = true;
+            calls.extend(self.handlers.start.map(|id| (id, self.event(self.start))));
+        }
+        calls.extend(self.handlers.drag.map(|id| (id, self.event(pos))));
+        calls
+    }
+
+    /// The handlers to run now the button is released at `pos`: none if the
+    /// press never became a drag.
+    pub(crate) fn released(self, pos: (f32, f32)) -> Vec<(EventHandlerId, DragEvent)> {
+        if !self.started {
+            return Vec::new();
+        }
+        self.handlers
+            .end
+            .map(|id| (id, self.event(pos)))
+            .into_iter()
+            .collect()
+    }
+
+    fn event(&self, (x, y): (f32, f32)) -> DragEvent {
+        DragEvent {
+            x: x as f64,
+            y: y as f64,
+            start_x: self.start.0 as f64,
+            start_y: self.start.1 as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handlers() -> DragHandlers {
+        DragHandlers {
+            start: Some(EventHandlerId(1)),
+            drag: Some(EventHandlerId(2)),
+            end: Some(EventHandlerId(3)),
+        }
+    }
+
+    fn ids(calls: &[(EventHandlerId, DragEvent)]) -> Vec<usize> {
+        calls.iter().map(|(id, _)| id.0).collect()
+    }
+
+    #[test]
+    fn drags_start_past_the_threshold() {
+        let mut drag = ElementDrag::new(handlers(), (10.0, 10.0));
+        assert!(drag.moved((12.0, 11.0)).is_empty());
+        assert!(!drag.started());
+
+        let calls = drag.moved((20.0, 10.0));
+        assert_eq!(ids(&calls), [1, 2]);
+        assert_eq!(calls[0].1.delta(), (0.0, 0.0));
+        assert_eq!(calls[1].1.delta(), (10.0, 0.0));
+        assert!(drag.started());
+
+        let calls = drag.moved((25.0, 30.0));
+        assert_eq!(ids(&calls), [2]);
+        assert_eq!((calls[0].1.x, calls[0].1.y), (25.0, 30.0));
+
+        let calls = drag.released((26.0, 30.0));
+        assert_eq!(ids(&calls), [3]);
+        assert_eq!(calls[0].1.delta(), (16.0, 20.0));
+    }
+
+    #[test]
+    fn presses_that_stay_put_are_clicks() {
+        let mut drag = ElementDrag::new(handlers(), (10.0, 10.0));
+        assert!(drag.moved((11.0, 11.0)).is_empty());
+        assert!(drag.released((11.0, 11.0)).is_empty());
+    }
+}
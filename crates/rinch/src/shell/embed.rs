@@ -0,0 +1,104 @@
+//! Running a rinch app inside another application's window.
+//!
+//! [`attach_to_parent`] renders the app into a child of a window the host
+//! owns, such as a plugin's editor area in a DAW or a panel in a native app.
+//! The host keeps its own event loop and message pump, and calls
+//! [`EmbeddedApp::pump`] from it, such as from a timer or its idle handler.
+//!
+//! ```ignore
+//! use rinch::shell::attach_to_parent;
+//!
+//! // `parent` is the host's window, as a `RawWindowHandle`
+//! let mut ui = unsafe { attach_to_parent(parent, app) };
+//!
+//! // In the host's timer callback
+//! ui.pump();
+//!
+//! // When the host resizes the area
+//! ui.set_size(width, height);
+//! ```
+//!
+//! - Only the app's first window goes into the host's window: it's placed
+//!   at the top left, without decorations. DevTools and other windows the
+//!   app opens are windows of their own.
+//! - Each call to `pump` handles the events waiting and returns without
+//!   blocking, so timers and animations run as often as the host pumps.
+//! - Embedding is supported on Windows, macOS and Linux (X11).
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use rinch_core::element::Element;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use winit::raw_window_handle::RawWindowHandle;
+
+use super::runtime::{start, RinchEvent, Runtime};
+
+thread_local! {
+    /// The host window the next window created goes into.
+    static PARENT: Cell<Option<RawWindowHandle>> = const { Cell::new(None) };
+}
+
+/// The host window for the window being created, if the app is embedded
+/// and this is its first window.
+pub(crate) fn take_parent() -> Option<RawWindowHandle> {
+    PARENT.with(Cell::take)
+}
+
+/// A rinch app running inside a host's window. Dropping it closes the app's
+/// windows.
+pub struct EmbeddedApp {
+    event_loop: EventLoop<RinchEvent>,
+    runtime: Runtime,
+    running: bool,
+}
+
+impl EmbeddedApp {
+    /// Handle the events waiting, such as input, timers and redraws,
+    /// without blocking. Returns `false` once the app has quit.
+    pub fn pump(&mut self) -> bool {
+        if self.running {
+            let status = self
+                .event_loop
+                .pump_app_events(Some(Duration::ZERO), &mut self.runtime);
+            self.running = matches!(status, PumpStatus::Continue);
+            if !self.running {
+                let _ = crate::debug::stop_trace();
+            }
+        }
+        self.running
+    }
+
+    /// Resize the app to fill `width` by `height` physical pixels of the
+    /// host's window.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        if let Some(window) = self.runtime.main_window() {
+            let _ = window
+                .window
+                .request_inner_size(PhysicalSize::new(width, height));
+        }
+    }
+}
+
+/// Start `app` inside the host window `parent`, for the host to drive with
+/// [`EmbeddedApp::pump`]. See the [module docs](self).
+///
+/// # Safety
+///
+/// `parent` must be a valid window handle, and the window must outlive the
+/// returned `EmbeddedApp`. Call this on the thread the host's window
+/// belongs to, and only once per process: rinch keeps one event loop.
+pub unsafe fn attach_to_parent<F>(parent: RawWindowHandle, app: F) -> EmbeddedApp
+where
+    F: Fn() -> Element + 'static,
+{
+    PARENT.with(|cell| cell.set(Some(parent)));
+    let (event_loop, runtime) = start(app, EventLoop::with_user_event(), None, None);
+    EmbeddedApp {
+        event_loop,
+        runtime,
+        running: true,
+    }
+}
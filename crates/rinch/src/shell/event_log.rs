@@ -0,0 +1,342 @@
+//! Event log for the DevTools window.
+//!
+//! While DevTools is open, the runtime records each click and scroll it
+//! dispatches: the element that received it, the path the event bubbled
+//! along to find a handler, which handler ran and how long it took. Clicks
+//! that found no handler are recorded too, with the elements they passed
+//! through, so a missing `onclick` shows up as an unhandled click on the
+//! element that was actually hit.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::{html_escape_string, EventHandlerId};
+
+/// How many events the log keeps.
+const CAPACITY: usize = 200;
+
+/// Handlers taking at least this long count as slow: they miss a frame.
+const SLOW_HANDLER: Duration = Duration::from_millis(16);
+
+/// What kind of event was dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A left click, dispatched to the nearest `onclick`.
+    Click,
+    /// A watched element scrolled, dispatched to its `onscroll`.
+    Scroll,
+}
+
+/// What happened to an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// No element on the path had a handler.
+    NoHandler,
+    /// The path led to a handler that is waiting to run.
+    Pending(EventHandlerId),
+    /// The handler ran, taking this long.
+    Handled(EventHandlerId, Duration),
+    /// The handler wasn't registered any more, usually because the element
+    /// came from an older render.
+    Missing(EventHandlerId),
+}
+
+/// A dispatched event.
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    /// When the event happened.
+    pub time: Instant,
+    /// What kind of event it was.
+    pub kind: EventKind,
+    /// The elements the event passed through, from the target outwards,
+    /// ending at the element with the handler (or the root if none had one).
+    pub path: Vec<String>,
+    /// What happened to it.
+    pub outcome: EventOutcome,
+}
+
+/// Which events the log shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventFilter {
+    /// Every event.
+    #[default]
+    All,
+    /// Clicks only.
+    Clicks,
+    /// Scrolls only.
+    Scrolls,
+    /// Events that no handler received.
+    Unhandled,
+    /// Events whose handler missed a frame.
+    Slow,
+}
+
+impl EventFilter {
+    const ALL: [(EventFilter, &'static str, &'static str); 5] = [
+        (EventFilter::All, "all", "All"),
+        (EventFilter::Clicks, "clicks", "Clicks"),
+        (EventFilter::Scrolls, "scrolls", "Scrolls"),
+        (EventFilter::Unhandled, "unhandled", "Unhandled"),
+        (EventFilter::Slow, "slow", "Slow"),
+    ];
+
+    /// The filter named by a `data-devtools-filter` attribute value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, key, _)| *key == name)
+            .map(|(filter, _, _)| *filter)
+    }
+
+    fn matches(self, record: &EventRecord) -> bool {
+        match self {
+            EventFilter::All => true,
+            EventFilter::Clicks => record.kind == EventKind::Click,
+            EventFilter::Scrolls => record.kind == EventKind::Scroll,
+            EventFilter::Unhandled => matches!(
+                record.outcome,
+                EventOutcome::NoHandler | EventOutcome::Missing(_)
+            ),
+            EventFilter::Slow => matches!(
+                record.outcome,
+                EventOutcome::Handled(_, duration) if duration >= SLOW_HANDLER
+            ),
+        }
+    }
+}
+
+/// The most recent dispatched events.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    records: VecDeque<EventRecord>,
+    /// Which events are shown.
+    pub filter: EventFilter,
+    /// Bumped on every change, so the DevTools window knows to refresh.
+    version: u64,
+}
+
+impl EventLog {
+    /// Record an event, dropping the oldest if the log is full.
+    pub fn record(&mut self, kind: EventKind, path: Vec<String>, handler: Option<EventHandlerId>) {
+        if self.records.len() == CAPACITY {
+            self.records.pop_front();
+        }
+        self.records.push_back(EventRecord {
+            time: Instant::now(),
+            kind,
+            path,
+            outcome: handler.map_or(EventOutcome::NoHandler, EventOutcome::Pending),
+        });
+        self.version += 1;
+    }
+
+    /// Note that `handler` ran for its most recent event, taking `duration`,
+    /// or that it wasn't found.
+    pub fn finish(&mut self, handler: EventHandlerId, called: bool, duration: Duration) {
+        let pending = self
+            .records
+            .iter_mut()
+            .rev()
+            .find(|record| record.outcome == EventOutcome::Pending(handler));
+        if let Some(record) = pending {
+            record.outcome = if called {
+                EventOutcome::Handled(handler, duration)
+            } else {
+                EventOutcome::Missing(handler)
+            };
+            self.version += 1;
+        }
+    }
+
+    /// Forget every event.
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.version += 1;
+    }
+
+    /// A number that changes whenever the log does.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The events the filter shows, newest first.
+    pub fn visible(&self) -> impl Iterator<Item = &EventRecord> {
+        self.records
+            .iter()
+            .rev()
+            .filter(|record| self.filter.matches(record))
+    }
+
+    /// HTML for the DevTools window's event log section.
+    pub fn html(&self, now: Instant) -> String {
+        let filters: String = EventFilter::ALL
+            .iter()
+            .map(|(filter, key, label)| {
+                format!(
+                    r#"<span class="event-filter{}" data-devtools-filter="{key}">{label}</span>"#,
+                    if *filter == self.filter {
+                        " active"
+                    } else {
+                        ""
+                    },
+                )
+            })
+            .collect();
+        let records: String = self
+            .visible()
+            .map(|record| record_html(record, now))
+            .collect();
+        let records = if records.is_empty() {
+            r#"<p style="color: #808080;">No events yet. Click or scroll in the inspected window.</p>"#
+                .to_string()
+        } else {
+            records
+        };
+        format!(
+            r#"<div class="event-filters">{filters}<span class="event-filter" data-devtools-filter="clear">Clear</span></div>{records}"#
+        )
+    }
+}
+
+/// HTML for one event.
+fn record_html(record: &EventRecord, now: Instant) -> String {
+    let kind = match record.kind {
+        EventKind::Click => "click",
+        EventKind::Scroll => "scroll",
+    };
+    let (outcome, class) = match record.outcome {
+        EventOutcome::NoHandler => (
+            match record.kind {
+                EventKind::Click => "no onclick on the target or its ancestors".to_string(),
+                EventKind::Scroll => "no onscroll".to_string(),
+            },
+            " unhandled",
+        ),
+        EventOutcome::Pending(handler) => (format!("handler {handler} pending"), ""),
+        EventOutcome::Handled(handler, duration) => (
+            format!(
+                "handler {handler} took {:.1}ms",
+                duration.as_secs_f64() * 1000.0
+            ),
+            if duration >= SLOW_HANDLER {
+                " slow"
+            } else {
+                ""
+            },
+        ),
+        EventOutcome::Missing(handler) => (
+            format!("handler {handler} isn't registered (stale element?)"),
+            " unhandled",
+        ),
+    };
+    let path: Vec<_> = record
+        .path
+        .iter()
+        .map(|element| html_escape_string(element))
+        .collect();
+    format!(
+        r#"<div class="event{class}">
+            <div><span class="event-kind">{kind}</span> <span class="event-time">{:.1}s ago</span></div>
+            <div class="event-path">{}</div>
+            <div class="event-outcome">{outcome}</div>
+        </div>"#,
+        now.saturating_duration_since(record.time).as_secs_f64(),
+        path.join(" → "),
+    )
+}
+
+/// The elements from `node_id` outwards to the first one with `attr`, or
+/// to the root if none has it, described as `tag#id.class`.
+pub(crate) fn event_path(doc: &BaseDocument, node_id: usize, attr: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = Some(node_id);
+    while let Some(node) = current.and_then(|id| doc.get_node(id)) {
+        current = node.parent;
+        let Some(element) = node.element_data() else {
+            continue;
+        };
+        let value = |name: &str| {
+            element
+                .attrs()
+                .iter()
+                .find(|a| a.name.local.as_ref() == name)
+                .map(|a| a.value.to_string())
+        };
+        let mut description = element.name.local.to_string();
+        if let Some(id) = value("id") {
+            description.push('#');
+            description.push_str(&id);
+        }
+        for class in value("class").iter().flat_map(|c| c.split_whitespace()) {
+            description.push('.');
+            description.push_str(class);
+        }
+        path.push(description);
+        if value(attr).is_some() {
+            break;
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_finish_their_latest_event() {
+        let mut log = EventLog::default();
+        let handler = EventHandlerId(7);
+        log.record(EventKind::Click, vec!["button".into()], Some(handler));
+        log.record(EventKind::Click, vec!["div".into()], None);
+        log.record(EventKind::Click, vec!["button".into()], Some(handler));
+        log.finish(handler, true, Duration::from_millis(30));
+
+        let outcomes: Vec<_> = log.visible().map(|record| record.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                EventOutcome::Handled(handler, Duration::from_millis(30)),
+                EventOutcome::NoHandler,
+                EventOutcome::Pending(handler),
+            ]
+        );
+
+        log.finish(handler, false, Duration::ZERO);
+        assert_eq!(log.records[0].outcome, EventOutcome::Missing(handler));
+    }
+
+    #[test]
+    fn filters_pick_events() {
+        let mut log = EventLog::default();
+        log.record(EventKind::Click, Vec::new(), None);
+        log.record(EventKind::Scroll, Vec::new(), Some(EventHandlerId(1)));
+        log.finish(EventHandlerId(1), true, Duration::from_millis(40));
+        log.record(EventKind::Click, Vec::new(), Some(EventHandlerId(2)));
+        log.finish(EventHandlerId(2), true, Duration::from_millis(1));
+
+        let count = |log: &mut EventLog, filter| {
+            log.filter = filter;
+            log.visible().count()
+        };
+        assert_eq!(count(&mut log, EventFilter::All), 3);
+        assert_eq!(count(&mut log, EventFilter::Clicks), 2);
+        assert_eq!(count(&mut log, EventFilter::Scrolls), 1);
+        assert_eq!(count(&mut log, EventFilter::Unhandled), 1);
+        assert_eq!(count(&mut log, EventFilter::Slow), 1);
+        assert_eq!(EventFilter::from_name("slow"), Some(EventFilter::Slow));
+        assert_eq!(EventFilter::from_name("clear"), None);
+    }
+
+    #[test]
+    fn the_log_keeps_recent_events() {
+        let mut log = EventLog::default();
+        for _ in 0..CAPACITY + 5 {
+            log.record(EventKind::Click, Vec::new(), None);
+        }
+        assert_eq!(log.visible().count(), CAPACITY);
+        log.clear();
+        assert_eq!(log.visible().count(), 0);
+    }
+}
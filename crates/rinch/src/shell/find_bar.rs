@@ -0,0 +1,173 @@
+//! Built-in find bar.
+//!
+//! Ctrl+F (Cmd+F on macOS) opens a small window over the top-right corner of
+//! the window being searched. Typing in it highlights every match in the
+//! target window, Enter/F3 moves to the next match and Shift+Enter/Shift+F3 to
+//! the previous one, scrolling it into view. Escape closes the bar.
+
+use std::ops::Range;
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+use rinch_core::events::html_escape_string;
+
+use super::selection::range_rects;
+use crate::search::text_matches;
+
+/// Highlight color for matches.
+const MATCH_COLOR: Color = Color::from_rgba8(0xff, 0xd7, 0x00, 0x66);
+/// Highlight color for the current match.
+const CURRENT_MATCH_COLOR: Color = Color::from_rgba8(0xff, 0x8c, 0x00, 0xaa);
+
+/// Width of the find bar window.
+pub(crate) const FIND_BAR_WIDTH: u32 = 320;
+/// Height of the find bar window.
+pub(crate) const FIND_BAR_HEIGHT: u32 = 40;
+
+/// Find bar state for a searched window.
+#[derive(Debug, Default)]
+pub struct FindState {
+    /// The text being searched for.
+    pub query: String,
+    /// Matches as `(inline root, byte range)`, in document order.
+    matches: Vec<(usize, Range<usize>)>,
+    /// Index of the current match.
+    current: usize,
+    /// The document changed, so matches must be found again.
+    dirty: bool,
+}
+
+impl FindState {
+    /// Whether a search is active.
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    /// Number of matches.
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Replace the query and search again, starting from the first match.
+    pub(crate) fn set_query(&mut self, doc: &BaseDocument, query: String) {
+        self.query = query;
+        self.current = 0;
+        self.search(doc);
+    }
+
+    /// Stop searching and remove highlights.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Mark matches as out of date after the document changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.dirty = self.is_active();
+    }
+
+    /// Search again if the document changed. Must run after layout.
+    pub(crate) fn refresh(&mut self, doc: &BaseDocument) {
+        if self.dirty {
+            self.search(doc);
+        }
+    }
+
+    /// Move to the next (or previous) match, wrapping around.
+    pub(crate) fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+    }
+
+    /// Window-relative rectangles of the current match.
+    pub(crate) fn current_rects(&self, doc: &BaseDocument) -> Vec<Rect> {
+        self.matches
+            .get(self.current)
+            .map(|(node_id, range)| range_rects(doc, *node_id, range.start, range.end))
+            .unwrap_or_default()
+    }
+
+    /// Paint match highlights.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        for (i, (node_id, range)) in self.matches.iter().enumerate() {
+            let color = if i == self.current {
+                CURRENT_MATCH_COLOR
+            } else {
+                MATCH_COLOR
+            };
+            for rect in range_rects(doc, *node_id, range.start, range.end) {
+                scene.fill(Fill::NonZero, Affine::scale(scale), color, None, &rect);
+            }
+        }
+    }
+
+    fn search(&mut self, doc: &BaseDocument) {
+        self.matches = text_matches(doc, &self.query);
+        self.current = self.current.min(self.matches.len().saturating_sub(1));
+        self.dirty = false;
+    }
+
+    /// HTML for the find bar window.
+    pub(crate) fn html(&self) -> String {
+        let status = if !self.is_active() {
+            String::new()
+        } else if self.matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            format!("{} of {}", self.current + 1, self.matches.len())
+        };
+        let query = if self.is_active() {
+            html_escape_string(&self.query)
+        } else {
+            r#"<span class="placeholder">Find in window</span>"#.to_string()
+        };
+
+        format!(
+            r#"<style>
+    body {{
+        margin: 0;
+        font-family: system-ui, -apple-system, sans-serif;
+        font-size: 13px;
+        background: #2d2d2d;
+        color: #e0e0e0;
+    }}
+    .find-bar {{
+        display: flex;
+        align-items: center;
+        height: {height}px;
+        padding: 0 10px;
+        box-sizing: border-box;
+        border: 1px solid #555;
+    }}
+    .query {{
+        flex: 1;
+        padding: 4px 6px;
+        background: #1e1e1e;
+        border: 1px solid #007acc;
+        border-radius: 3px;
+        white-space: pre;
+        overflow: hidden;
+    }}
+    .placeholder {{ color: #808080; }}
+    .status {{
+        margin-left: 10px;
+        color: #a0a0a0;
+        white-space: nowrap;
+    }}
+</style>
+<div class="find-bar">
+    <div class="query">{query}</div>
+    <div class="status">{status}</div>
+</div>"#,
+            height = FIND_BAR_HEIGHT,
+        )
+    }
+}
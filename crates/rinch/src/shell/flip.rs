@@ -0,0 +1,275 @@
+//! Move animations across re-renders.
+//!
+//! Two kinds of element glide to their new place when a re-render moves
+//! them, rather than jumping:
+//!
+//! - Children with a `key` in a container with `animate_moves`, such as the
+//!   rows of a sorted list. Their positions are relative to the container,
+//!   so a list that moves as a whole (or a list nested in a moving item)
+//!   doesn't animate its children.
+//! - Elements with a `transition_id`, such as a thumbnail that becomes the
+//!   header image of a detail view. The old and new elements can be in
+//!   entirely different parts of the page; the new one starts at the old
+//!   one's position and size.
+//!
+//! Before the document is rebuilt, rinch records where these elements are;
+//! after layout, any that moved are shifted back to where they were, and
+//! the shift eases away over [`MOVE_DURATION`] (the "FLIP" technique: first,
+//! last, invert, play).
+//!
+//! When the user prefers reduced motion, elements jump to their new place
+//! unless they have `motion: "full"`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+use rinch_core::{prefers_reduced_motion, untracked};
+
+use super::scroll::{border_box_origin, ease_out_cubic, node_path};
+
+/// How long a moved element takes to reach its new place.
+pub(crate) const MOVE_DURATION: Duration = Duration::from_millis(250);
+
+/// Attribute marking a container whose keyed children animate moves.
+const ANIMATE_MOVES_ATTR: &str = "data-animate-moves";
+
+/// Attribute holding a list item's key.
+const KEY_ATTR: &str = "data-key";
+
+/// Attribute holding a shared element's transition ID.
+const TRANSITION_ID_ATTR: &str = "data-transition-id";
+
+/// Attribute that keeps an element animating when the user prefers reduced
+/// motion, with the value `full`.
+const MOTION_ATTR: &str = "data-motion";
+
+/// What identifies an element across rebuilds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MoveId {
+    /// A list container's tree path, and the item's key.
+    Keyed(Vec<usize>, String),
+    /// A shared element's transition ID.
+    Shared(String),
+}
+
+/// Where an element is: its position (window-relative for shared elements,
+/// container-relative for list items) and border-box size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placement {
+    position: (f64, f64),
+    size: (f32, f32),
+}
+
+fn attr(doc: &BaseDocument, node_id: usize, name: &str) -> Option<String> {
+    doc.get_node(node_id)?
+        .element_data()?
+        .attrs()
+        .iter()
+        .find(|a| a.name.local.as_ref() == name)
+        .map(|a| a.value.to_string())
+}
+
+/// Elements that animate moves, with their node and placement.
+fn movable_elements(doc: &BaseDocument) -> Vec<(MoveId, usize, Placement)> {
+    let placement = |node_id: usize, origin: (f64, f64)| {
+        let (x, y) = border_box_origin(doc, node_id);
+        let size = doc
+            .get_node(node_id)
+            .map(|node| (node.final_layout.size.width, node.final_layout.size.height))
+            .unwrap_or_default();
+        Placement {
+            position: (x - origin.0, y - origin.1),
+            size,
+        }
+    };
+
+    let mut elements = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter().rev());
+
+        if let Some(id) = attr(doc, node_id, TRANSITION_ID_ATTR) {
+            elements.push((MoveId::Shared(id), node_id, placement(node_id, (0.0, 0.0))));
+        }
+        if attr(doc, node_id, ANIMATE_MOVES_ATTR).is_none_or(|value| value == "false") {
+            continue;
+        }
+        let path = node_path(doc, node_id);
+        let origin = border_box_origin(doc, node_id);
+        for &child in &node.children {
+            if let Some(key) = attr(doc, child, KEY_ATTR) {
+                let id = MoveId::Keyed(path.clone(), key);
+                elements.push((id, child, placement(child, origin)));
+            }
+        }
+    }
+    elements
+}
+
+/// Where movable elements were before a rebuild.
+#[derive(Debug, Default)]
+pub(crate) struct MoveSnapshot {
+    placements: HashMap<MoveId, Placement>,
+}
+
+impl MoveSnapshot {
+    /// Record a document's movable elements, as they appear now (including
+    /// any move still animating).
+    pub(crate) fn take(doc: &BaseDocument) -> Self {
+        Self {
+            placements: movable_elements(doc)
+                .into_iter()
+                .map(|(id, _, placement)| (id, placement))
+                .collect(),
+        }
+    }
+}
+
+/// How much of a move is left, `elapsed` into it, from 1 down to 0.
+fn remaining(elapsed: Duration) -> f64 {
+    let progress = (elapsed.as_secs_f64() / MOVE_DURATION.as_secs_f64()).min(1.0);
+    1.0 - ease_out_cubic(progress)
+}
+
+/// How far a moved element is still shifted from its new place, `elapsed`
+/// into its move.
+fn remaining_shift(offset: (f64, f64), elapsed: Duration) -> (f64, f64) {
+    let remaining = remaining(elapsed);
+    (offset.0 * remaining, offset.1 * remaining)
+}
+
+/// An element's size `elapsed` into a move from `from` to `to`.
+fn size_at(from: (f32, f32), to: (f32, f32), elapsed: Duration) -> (f32, f32) {
+    let remaining = remaining(elapsed) as f32;
+    (
+        to.0 + (from.0 - to.0) * remaining,
+        to.1 + (from.1 - to.1) * remaining,
+    )
+}
+
+/// An element gliding to its new place.
+#[derive(Debug)]
+struct Move {
+    node_id: usize,
+    /// Where the element was, relative to where it is now.
+    offset: (f64, f64),
+    /// The size it had and the size layout gave it, if they differ.
+    size: Option<((f32, f32), (f32, f32))>,
+    start: Instant,
+    /// The location and size rinch set, and how far that location was from
+    /// where layout put the element.
+    placed: Option<((f32, f32), (f32, f32), (f64, f64))>,
+}
+
+/// Elements gliding to new places after a re-render.
+#[derive(Debug, Default)]
+pub(crate) struct MoveAnimations {
+    moves: Vec<Move>,
+}
+
+impl MoveAnimations {
+    /// Animate the elements of a freshly laid out document that moved since
+    /// `before` was taken.
+    pub(crate) fn start(&mut self, doc: &BaseDocument, before: &MoveSnapshot, now: Instant) {
+        self.moves.clear();
+        let reduced = untracked(prefers_reduced_motion);
+        for (id, node_id, placement) in movable_elements(doc) {
+            let Some(old) = before.placements.get(&id) else {
+                continue;
+            };
+            if reduced && attr(doc, node_id, MOTION_ATTR).is_none_or(|value| value != "full") {
+                continue;
+            }
+            let offset = (
+                old.position.0 - placement.position.0,
+                old.position.1 - placement.position.1,
+            );
+            let moved = offset.0.abs() >= 0.5 || offset.1.abs() >= 0.5;
+            // Only shared elements change size as they move
+            let resized = matches!(id, MoveId::Shared(_))
+                && ((old.size.0 - placement.size.0).abs() >= 0.5
+                    || (old.size.1 - placement.size.1).abs() >= 0.5);
+            if moved || resized {
+                self.moves.push(Move {
+                    node_id,
+                    offset,
+                    size: resized.then_some((old.size, placement.size)),
+                    start: now,
+                    placed: None,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        !self.moves.is_empty()
+    }
+
+    /// Shift moving elements to where they should be at `now`, dropping
+    /// those that have arrived.
+    pub(crate) fn tick(&mut self, doc: &mut BaseDocument, now: Instant) {
+        self.moves.retain_mut(|item| {
+            let Some(node) = doc.get_node_mut(item.node_id) else {
+                return false;
+            };
+            let layout = &mut node.final_layout;
+
+            // Undo the last shift, unless layout has placed the element afresh
+            let applied = match item.placed {
+                Some((location, size, shift))
+                    if location == (layout.location.x, layout.location.y)
+                        && size == (layout.size.width, layout.size.height) =>
+                {
+                    shift
+                }
+                _ => {
+                    if let Some((_, to)) = &mut item.size {
+                        *to = (layout.size.width, layout.size.height);
+                    }
+                    (0.0, 0.0)
+                }
+            };
+            let elapsed = now.saturating_duration_since(item.start);
+            let shift = remaining_shift(item.offset, elapsed);
+            layout.location.x += (shift.0 - applied.0) as f32;
+            layout.location.y += (shift.1 - applied.1) as f32;
+            if let Some((from, to)) = item.size {
+                (layout.size.width, layout.size.height) = size_at(from, to, elapsed);
+            }
+            item.placed = Some((
+                (layout.location.x, layout.location.y),
+                (layout.size.width, layout.size.height),
+                shift,
+            ));
+            elapsed < MOVE_DURATION
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moved_items_ease_into_place() {
+        let offset = (0.0, -120.0);
+        assert_eq!(remaining_shift(offset, Duration::ZERO), offset);
+        let halfway = remaining_shift(offset, MOVE_DURATION / 2);
+        // Ease-out covers most of the distance early
+        assert_eq!(halfway, (0.0, -15.0));
+        assert_eq!(remaining_shift(offset, MOVE_DURATION), (0.0, 0.0));
+        assert_eq!(remaining_shift(offset, MOVE_DURATION * 3), (0.0, 0.0));
+    }
+
+    #[test]
+    fn shared_elements_resize_as_they_move() {
+        let (from, to) = ((80.0, 60.0), (400.0, 300.0));
+        assert_eq!(size_at(from, to, Duration::ZERO), from);
+        assert_eq!(size_at(from, to, MOVE_DURATION / 2), (360.0, 270.0));
+        assert_eq!(size_at(from, to, MOVE_DURATION), to);
+    }
+}
@@ -0,0 +1,502 @@
+//! Keyboard focus and the focus ring.
+//!
+//! Tab and Shift+Tab move focus through a window's focusable elements:
+//! links, form controls, `contenteditable` elements and anything with a
+//! `tabindex` of 0 or more (positive ones first, in order). Focus moved
+//! with the keyboard is *visible*. Focus given by a click isn't, except on
+//! text fields, and becomes visible once a key is pressed, as in browsers.
+//!
+//! Blitz matches `:focus-visible` wherever `:focus` matches, so only
+//! visible focus is given to the document; an element focused by a click
+//! (a button, say) doesn't match `:focus` either, as in Safari.
+//!
+//! While focus is visible, rinch paints a ring around the focused element
+//! over the scene, unless the app styles its focus: an `outline` on the
+//! element, or an `outline` or `box-shadow` in a `:focus` or
+//! `:focus-visible` rule for it. The ring follows the element's corner
+//! radius, and apps can theme it with inherited custom properties:
+//! `--rinch-focus-ring-color`, `--rinch-focus-ring-width` and
+//! `--rinch-focus-ring-offset`.
+
+use anyrender::PaintScene;
+use blitz_dom::{BaseDocument, Node};
+use peniko::kurbo::{Affine, Rect, RoundedRect, Stroke};
+use peniko::Color;
+
+use super::cascade::{matching_brace, parse_declarations, strip_comments, MatchedDeclaration};
+use super::computed::computed_value;
+use super::scroll::{border_box_origin, node_at_path, node_path};
+use super::scrollbars::parse_px;
+use super::text_effects::split_outside_parens;
+
+/// Custom property set on elements whose rules style their focus.
+const STYLED_PROPERTY: &str = "--rinch-focus-styled";
+
+/// User-agent stylesheet keeping [`STYLED_PROPERTY`] from inheriting.
+pub(crate) const FOCUS_RESET_CSS: &str = "* { --rinch-focus-styled: initial; }";
+
+/// Ring color when `--rinch-focus-ring-color` isn't set.
+const DEFAULT_COLOR: Color = Color::from_rgb8(0x1a, 0x73, 0xe8);
+/// Ring thickness when `--rinch-focus-ring-width` isn't set.
+const DEFAULT_WIDTH: f64 = 2.0;
+/// Gap between the element's border box and the ring when
+/// `--rinch-focus-ring-offset` isn't set.
+const DEFAULT_OFFSET: f64 = 2.0;
+
+/// Input types that aren't text fields.
+const NON_TEXT_INPUTS: [&str; 9] = [
+    "button", "checkbox", "color", "file", "image", "radio", "range", "reset", "submit",
+];
+
+/// How rinch paints the ring around an element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct FocusRing {
+    pub color: Color,
+    pub width: f64,
+    pub offset: f64,
+    /// The element's own corner radius.
+    pub radius: f64,
+}
+
+/// A window's focused element.
+#[derive(Debug, Default)]
+pub(crate) struct Focus {
+    node: Option<usize>,
+    /// Child-index path of the focused element, to find it again in a
+    /// rebuilt document.
+    path: Vec<usize>,
+    visible: bool,
+}
+
+impl Focus {
+    /// The focused element.
+    pub(crate) fn node(&self) -> Option<usize> {
+        self.node
+    }
+
+    /// Whether focus is visible (matches `:focus-visible`).
+    pub(crate) fn is_visible(&self) -> bool {
+        self.node.is_some() && self.visible
+    }
+
+    /// Move focus to the next element in tab order (the previous one if
+    /// `backward`), wrapping around, and make it visible. Returns `false` if
+    /// nothing is focusable.
+    pub(crate) fn tab(&mut self, doc: &mut BaseDocument, backward: bool) -> bool {
+        let order = tab_order(&tab_indices(doc));
+        let Some(next) = step(&order, self.node, backward) else {
+            return false;
+        };
+        self.set(doc, Some(next), true);
+        true
+    }
+
+    /// Focus the focusable element at or above `hit`, after a pointer press
+    /// on it; pressing anything else clears focus. Only text fields get
+    /// visible focus this way.
+    pub(crate) fn pointer_down(&mut self, doc: &mut BaseDocument, hit: Option<usize>) {
+        let target = hit.and_then(|node_id| focusable_ancestor(doc, node_id));
+        let visible = target.is_some_and(|node_id| is_text_field(doc, node_id));
+        self.set(doc, target, visible);
+    }
+
+    /// A key other than Tab was pressed: focus given by a click becomes
+    /// visible. Returns `true` if it wasn't already.
+    pub(crate) fn key_pressed(&mut self, doc: &mut BaseDocument) -> bool {
+        if self.node.is_none() || self.visible {
+            return false;
+        }
+        self.set(doc, self.node, true);
+        true
+    }
+
+    /// Focus `node_id` for the app, if it's focusable. Focus shows if it
+    /// already did, or on a text field. Returns `false` if it isn't
+    /// focusable.
+    pub(crate) fn focus(&mut self, doc: &mut BaseDocument, node_id: usize) -> bool {
+        if focusable(doc, node_id).is_none() {
+            return false;
+        }
+        let visible = self.visible || is_text_field(doc, node_id);
+        self.set(doc, Some(node_id), visible);
+        true
+    }
+
+    /// Take focus from `node_id`, if it has it.
+    pub(crate) fn blur(&mut self, doc: &mut BaseDocument, node_id: usize) {
+        if self.node == Some(node_id) {
+            self.set(doc, None, false);
+        }
+    }
+
+    /// Focus the same element in a rebuilt document. Must run before the
+    /// document is resolved, so its styles see the focus.
+    pub(crate) fn restore(&mut self, doc: &mut BaseDocument) {
+        if self.node.is_none() {
+            return;
+        }
+        let node =
+            node_at_path(doc, &self.path).filter(|&node_id| focusable(doc, node_id).is_some());
+        let visible = self.visible;
+        // The old document's focus went with it
+        self.node = None;
+        self.set(doc, node, visible);
+    }
+
+    fn set(&mut self, doc: &mut BaseDocument, node: Option<usize>, visible: bool) {
+        self.node = node;
+        self.visible = visible;
+        self.path = node
+            .map(|node_id| node_path(doc, node_id))
+            .unwrap_or_default();
+        match node.filter(|_| visible) {
+            Some(node_id) => doc.set_focus_to(node_id),
+            None => doc.clear_focus(),
+        }
+    }
+
+    /// Paint the focus ring, while focus is visible.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        let Some(node_id) = self.node.filter(|_| self.visible) else {
+            return;
+        };
+        // Read once the document is styled with the focus
+        let Some(ring) = ring_style(doc, node_id) else {
+            return;
+        };
+        let Some(node) = doc.get_node(node_id) else {
+            return;
+        };
+        let size = node.final_layout.size;
+        if size.width <= 0.0 && size.height <= 0.0 {
+            return;
+        }
+        let (x, y) = border_box_origin(doc, node_id);
+        // Stroke along the middle of the ring
+        let outset = ring.offset + ring.width / 2.0;
+        let rect =
+            Rect::new(x, y, x + size.width as f64, y + size.height as f64).inflate(outset, outset);
+        let radius = if ring.radius > 0.0 {
+            ring.radius + outset
+        } else {
+            0.0
+        };
+        scene.stroke(
+            &Stroke::new(ring.width),
+            Affine::scale(scale),
+            ring.color,
+            None,
+            &RoundedRect::from_rect(rect, radius),
+        );
+    }
+}
+
+/// The ring to paint around `node_id`, or `None` if its focus is styled.
+fn ring_style(doc: &BaseDocument, node_id: usize) -> Option<FocusRing> {
+    if computed_value(doc, node_id, STYLED_PROPERTY).is_some() || inline_outline(doc, node_id) {
+        return None;
+    }
+
+    let custom = |property: &str| computed_value(doc, node_id, property);
+    let color = custom("--rinch-focus-ring-color")
+        .and_then(|value| peniko::color::parse_color(value.trim()).ok())
+        .map(|color| color.to_alpha_color())
+        .unwrap_or(DEFAULT_COLOR);
+    let width = custom("--rinch-focus-ring-width")
+        .and_then(|value| parse_px(&value))
+        .unwrap_or(DEFAULT_WIDTH);
+    let offset = custom("--rinch-focus-ring-offset")
+        .and_then(|value| parse_px(&value))
+        .unwrap_or(DEFAULT_OFFSET);
+    let radius = custom("border-top-left-radius")
+        .and_then(|value| {
+            split_outside_parens(&value, ' ')
+                .first()
+                .copied()
+                .and_then(parse_px)
+        })
+        .unwrap_or(0.0);
+    Some(FocusRing {
+        color,
+        width: width.max(0.0),
+        offset,
+        radius,
+    })
+}
+
+/// Whether an element's `style` attribute sets an `outline`.
+fn inline_outline(doc: &BaseDocument, node_id: usize) -> bool {
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return false;
+    };
+    element
+        .attrs()
+        .iter()
+        .filter(|attr| attr.name.local.as_ref() == "style")
+        .flat_map(|attr| parse_declarations(&attr.value))
+        .any(|declaration| declaration.property.starts_with("outline"))
+}
+
+/// Whether a rule's declarations style the focus indicator of the elements
+/// it matches: an `outline`, or a `box-shadow` in a `:focus` or
+/// `:focus-visible` rule.
+fn styles_focus(declarations: &[MatchedDeclaration], focus_rule: bool) -> bool {
+    declarations.iter().any(|declaration| {
+        declaration.property.starts_with("outline")
+            || (focus_rule && declaration.property == "box-shadow")
+    })
+}
+
+/// Copies of a stylesheet's rules that style focus, setting
+/// [`STYLED_PROPERTY`] on the elements they match.
+///
+/// `.btn:focus-visible { outline: none }` becomes
+/// `.btn:focus-visible { --rinch-focus-styled: 1; }`.
+pub(crate) fn focus_rules(css: &str) -> String {
+    let mut rules = String::new();
+    collect_focus_rules(&strip_comments(css), &mut rules);
+    rules
+}
+
+fn collect_focus_rules(css: &str, out: &mut String) {
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        // Skip statements like `@import ...;` before the rule
+        let prelude = rest[..open].rsplit(';').next().unwrap_or_default().trim();
+        let body = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        if prelude.starts_with('@') {
+            if prelude.starts_with("@keyframes") || prelude.starts_with("@font-face") {
+                continue;
+            }
+            let mut inner = String::new();
+            collect_focus_rules(body, &mut inner);
+            if !inner.is_empty() {
+                out.push_str(&format!("{} {{\n{}}}\n", prelude, inner));
+            }
+            continue;
+        }
+        let declarations = parse_declarations(body);
+        let selectors: Vec<&str> = prelude
+            .split(',')
+            .map(str::trim)
+            .filter(|selector| styles_focus(&declarations, strip_focus(selector).is_some()))
+            .collect();
+        if !selectors.is_empty() {
+            out.push_str(&format!(
+                "{} {{ {STYLED_PROPERTY}: 1; }}\n",
+                selectors.join(", ")
+            ));
+        }
+    }
+}
+
+/// `sel:focus-visible` or `sel:focus` without the pseudo-class, if the
+/// selector's last compound has it.
+fn strip_focus(selector: &str) -> Option<String> {
+    let start = selector
+        .rfind(|c: char| c.is_whitespace() || c == '>')
+        .map_or(0, |i| i + 1);
+    let (ancestors, compound) = selector.split_at(start);
+    let mut stripped = compound.to_string();
+    for pseudo in [":focus-visible", ":focus"] {
+        while let Some(index) = find_pseudo(&stripped, pseudo) {
+            stripped.replace_range(index..index + pseudo.len(), "");
+        }
+    }
+    if stripped == compound {
+        return None;
+    }
+    let stripped = if stripped.is_empty() { "*" } else { &stripped };
+    Some(format!("{ancestors}{stripped}"))
+}
+
+/// Where `pseudo` appears in `compound` as a whole pseudo-class (so
+/// `:focus` doesn't find `:focus-within`).
+fn find_pseudo(compound: &str, pseudo: &str) -> Option<usize> {
+    compound
+        .match_indices(pseudo)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            !compound[index + pseudo.len()..]
+                .starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// The `tabindex` of each focusable element, in tree order, leaving out
+/// those inside `hidden` elements or ones with an inline `display: none`.
+fn tab_indices(doc: &BaseDocument) -> Vec<(usize, i32)> {
+    let mut elements = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if is_hidden(node) {
+            continue;
+        }
+        if let Some(tab_index) = focusable(doc, node_id) {
+            elements.push((node_id, tab_index));
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    elements
+}
+
+fn is_hidden(node: &Node) -> bool {
+    node.element_data().is_some_and(|element| {
+        element
+            .attrs()
+            .iter()
+            .any(|attr| match attr.name.local.as_ref() {
+                "hidden" => true,
+                "style" => attr
+                    .value
+                    .split(';')
+                    .filter_map(|declaration| declaration.split_once(':'))
+                    .any(|(property, value)| {
+                        property.trim() == "display" && value.trim() == "none"
+                    }),
+                _ => false,
+            })
+    })
+}
+
+/// The tab order of elements given in tree order with their `tabindex`:
+/// positive ones ascending, then zeros. Negative ones aren't reachable
+/// with Tab.
+fn tab_order(elements: &[(usize, i32)]) -> Vec<usize> {
+    let mut positive: Vec<_> = elements.iter().filter(|(_, index)| *index > 0).collect();
+    // Stable, so equal indices keep tree order
+    positive.sort_by_key(|(_, index)| *index);
+    positive
+        .into_iter()
+        .chain(elements.iter().filter(|(_, index)| *index == 0))
+        .map(|(node_id, _)| *node_id)
+        .collect()
+}
+
+/// The element after `current` in `order`, wrapping around; the first (or
+/// last, going backward) if `current` isn't in it.
+fn step(order: &[usize], current: Option<usize>, backward: bool) -> Option<usize> {
+    let position = current.and_then(|current| order.iter().position(|&id| id == current));
+    let index = match (position, backward) {
+        (Some(i), false) => (i + 1) % order.len(),
+        (Some(i), true) => (i + order.len() - 1) % order.len(),
+        (None, false) => 0,
+        (None, true) => order.len().checked_sub(1)?,
+    };
+    order.get(index).copied()
+}
+
+/// The nearest focusable element at or above `node_id`.
+fn focusable_ancestor(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if focusable(doc, id).is_some() {
+            return Some(id);
+        }
+        current = doc.get_node(id)?.parent;
+    }
+    None
+}
+
+/// An element's `tabindex` if it can take focus: its attribute, else 0 for
+/// elements focusable by default.
+fn focusable(doc: &BaseDocument, node_id: usize) -> Option<i32> {
+    let element = doc.get_node(node_id)?.element_data()?;
+    let attr = |name: &str| {
+        element
+            .attrs()
+            .iter()
+            .find(|attr| attr.name.local.as_ref() == name)
+            .map(|attr| attr.value.as_str())
+    };
+    let tag = element.name.local.as_ref();
+    let natively = match tag {
+        "a" | "area" => attr("href").is_some(),
+        "button" | "select" | "textarea" | "summary" => true,
+        "input" => attr("type") != Some("hidden"),
+        _ => attr("contenteditable").is_some_and(|value| value != "false"),
+    };
+    let disabled =
+        matches!(tag, "button" | "input" | "select" | "textarea") && attr("disabled").is_some();
+    if disabled {
+        return None;
+    }
+    match attr("tabindex").and_then(|value| value.trim().parse().ok()) {
+        Some(tab_index) => Some(tab_index),
+        None => natively.then_some(0),
+    }
+}
+
+/// Whether an element takes typed text, so clicking it shows focus.
+fn is_text_field(doc: &BaseDocument, node_id: usize) -> bool {
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return false;
+    };
+    let attr = |name: &str| {
+        element
+            .attrs()
+            .iter()
+            .find(|attr| attr.name.local.as_ref() == name)
+            .map(|attr| attr.value.as_str())
+    };
+    match element.name.local.as_ref() {
+        "textarea" => true,
+        "input" => attr("type").is_none_or(|kind| !NON_TEXT_INPUTS.contains(&kind)),
+        _ => attr("contenteditable").is_some_and(|value| value != "false"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_order_puts_positive_indices_first() {
+        let elements = [(1, 0), (2, 2), (3, -1), (4, 1), (5, 0), (6, 2)];
+        let order = tab_order(&elements);
+        assert_eq!(order, [4, 2, 6, 1, 5]);
+        assert_eq!(step(&order, None, false), Some(4));
+        assert_eq!(step(&order, None, true), Some(5));
+        assert_eq!(step(&order, Some(5), false), Some(4));
+        assert_eq!(step(&order, Some(4), true), Some(5));
+        assert_eq!(step(&order, Some(3), false), Some(4));
+        assert_eq!(step(&[], None, false), None);
+    }
+
+    #[test]
+    fn rewrites_focus_rules() {
+        let css = r#"
+            .btn:focus-visible, a:hover { outline: none !important; }
+            @media (min-width: 100px) { form > input:focus { box-shadow: 0 0 2px blue; } }
+            .menu:focus-within { color: red; }
+        "#;
+        let rules = focus_rules(css);
+        assert!(rules.starts_with(".btn:focus-visible, a:hover { --rinch-focus-styled: 1; }"));
+        assert!(rules.contains(
+            "@media (min-width: 100px) {\nform > input:focus { --rinch-focus-styled: 1; }"
+        ));
+        assert!(!rules.contains(".menu"));
+        assert_eq!(strip_focus(":focus"), Some("*".into()));
+    }
+
+    #[test]
+    fn outlines_and_focus_shadows_count_as_styled() {
+        assert!(styles_focus(&parse_declarations("outline: none"), false));
+        assert!(styles_focus(
+            &parse_declarations("box-shadow: 0 0 0 2px red"),
+            true
+        ));
+        assert!(!styles_focus(
+            &parse_declarations("box-shadow: 0 1px 2px gray"),
+            false
+        ));
+        assert!(!styles_focus(&parse_declarations("color: red"), true));
+    }
+}
@@ -2,11 +2,23 @@
 //!
 //! When enabled with the `hot-reload` feature, this module provides file watching
 //! capabilities that trigger UI re-renders when source files change.
+//!
+//! Changes that only touch stylesheets take a fast path: the new styles are
+//! applied to the live documents without rebuilding them, so component state,
+//! signals, and scroll positions survive iterative styling.
+//!
+//! The files of assets named with `asset!` are watched too, wherever they
+//! are. A change to one rebuilds every window's document so images, fonts
+//! and animations are read again.
+//!
+//! A change to Rust source rebuilds the app in the background and restarts
+//! it, handing its signals, windows and scroll positions to the new
+//! process (see [`hot_restart`](super::hot_restart)).
 
 use notify::{
     event::ModifyKind, Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
 use std::time::{Duration, Instant};
 use winit::event_loop::EventLoopProxy;
@@ -22,6 +34,13 @@ pub struct HotReloadConfig {
     pub extensions: Vec<String>,
     /// Debounce duration to prevent multiple rapid reloads.
     pub debounce: Duration,
+    /// Whether a change to Rust source rebuilds and restarts the app,
+    /// keeping its state. Without it, the running code just re-renders.
+    pub restart: bool,
+    /// The command that rebuilds the app, such as
+    /// `["cargo", "build", "--features", "dev"]`. By default, cargo builds
+    /// the running binary or example again in the same profile.
+    pub build_command: Option<Vec<String>>,
 }
 
 impl Default for HotReloadConfig {
@@ -42,6 +61,8 @@ impl Default for HotReloadConfig {
             },
             extensions: vec!["rs".into(), "css".into(), "html".into()],
             debounce: Duration::from_millis(100),
+            restart: true,
+            build_command: None,
         }
     }
 }
@@ -66,15 +87,29 @@ impl HotReloadConfig {
         self.debounce = debounce;
         self
     }
+
+    /// Set whether Rust source changes rebuild and restart the app.
+    pub fn with_restart(mut self, restart: bool) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Set the command that rebuilds the app.
+    pub fn with_build_command(mut self, command: Vec<String>) -> Self {
+        self.build_command = Some(command);
+        self
+    }
 }
 
 /// Hot reloader that watches files and triggers UI re-renders.
 pub struct HotReloader {
-    _watcher: RecommendedWatcher,
+    watcher: RecommendedWatcher,
     receiver: Receiver<Result<Event, notify::Error>>,
     config: HotReloadConfig,
     last_reload: Instant,
     proxy: EventLoopProxy<RinchEvent>,
+    /// Directories watched for asset files.
+    asset_dirs: Vec<PathBuf>,
 }
 
 impl HotReloader {
@@ -103,11 +138,12 @@ impl HotReloader {
         }
 
         Ok(Self {
-            _watcher: watcher,
+            watcher,
             receiver: rx,
             config,
             last_reload: Instant::now(),
             proxy,
+            asset_dirs: Vec::new(),
         })
     }
 
@@ -115,17 +151,17 @@ impl HotReloader {
     ///
     /// Call this periodically (e.g., in about_to_wait).
     pub fn poll(&mut self) {
+        self.watch_assets();
+        let mut pending: Option<ReloadKind> = None;
+
         while let Ok(result) = self.receiver.try_recv() {
             match result {
                 Ok(event) => {
-                    if self.should_reload(&event) {
-                        // Check debounce
-                        let now = Instant::now();
-                        if now.duration_since(self.last_reload) >= self.config.debounce {
-                            self.last_reload = now;
-                            tracing::info!("Hot reload: file changed, triggering re-render");
-                            let _ = self.proxy.send_event(RinchEvent::ReRender);
-                        }
+                    if let Some(kind) = self.classify(&event) {
+                        pending = Some(match pending {
+                            Some(existing) => existing.merge(kind),
+                            None => kind,
+                        });
                     }
                 }
                 Err(e) => {
@@ -133,28 +169,125 @@ impl HotReloader {
                 }
             }
         }
+
+        let Some(kind) = pending else {
+            return;
+        };
+
+        // Check debounce
+        let now = Instant::now();
+        if now.duration_since(self.last_reload) < self.config.debounce {
+            return;
+        }
+        self.last_reload = now;
+
+        match kind {
+            ReloadKind::Styles => {
+                tracing::info!("Hot reload: stylesheet changed, applying styles in place");
+                let _ = self.proxy.send_event(RinchEvent::ReloadStyles);
+            }
+            ReloadKind::Full => {
+                tracing::info!("Hot reload: file changed, triggering re-render");
+                let _ = self.proxy.send_event(RinchEvent::ReRender);
+            }
+            ReloadKind::Assets => {
+                tracing::info!("Hot reload: asset changed, rebuilding documents");
+                let _ = self.proxy.send_event(RinchEvent::ReloadAssets);
+            }
+            ReloadKind::Code => {
+                // Stylesheets saved along with the code apply while it builds
+                let _ = self.proxy.send_event(RinchEvent::ReRender);
+                if self.config.restart {
+                    let command = self.config.build_command.clone();
+                    super::hot_restart::rebuild(self.proxy.clone(), command);
+                }
+            }
+        }
+    }
+
+    /// Watch the directories of assets named since the last poll. Editors
+    /// often save by replacing a file, which would end a watch on the file
+    /// itself.
+    fn watch_assets(&mut self) {
+        for file in crate::assets::asset_files() {
+            let Some(dir) = file.parent() else {
+                continue;
+            };
+            if self.asset_dirs.iter().any(|watched| watched == dir) {
+                continue;
+            }
+            if let Err(e) = self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                tracing::warn!("Hot reload: can't watch assets in {:?}: {:?}", dir, e);
+            }
+            self.asset_dirs.push(dir.to_path_buf());
+        }
     }
 
-    /// Check if an event should trigger a reload.
-    fn should_reload(&self, event: &Event) -> bool {
+    /// Decide what kind of reload an event requires, if any.
+    fn classify(&self, event: &Event) -> Option<ReloadKind> {
         // Only reload on data modifications
         if !matches!(
             event.kind,
             EventKind::Modify(ModifyKind::Data(_)) | EventKind::Create(_)
         ) {
-            return false;
+            return None;
         }
 
         // Check if any of the changed files have watched extensions
+        let mut kind = None;
         for path in &event.paths {
             if let Some(ext) = path.extension() {
                 let ext_str = ext.to_string_lossy().to_lowercase();
-                if self.config.extensions.iter().any(|e| e == &ext_str) {
-                    return true;
+                let is_asset = crate::assets::is_asset_file(path);
+                if is_asset || self.config.extensions.iter().any(|e| e == &ext_str) {
+                    let path_kind = if is_stylesheet(path) {
+                        ReloadKind::Styles
+                    } else if ext_str == "rs" {
+                        ReloadKind::Code
+                    } else if is_asset {
+                        ReloadKind::Assets
+                    } else {
+                        ReloadKind::Full
+                    };
+                    kind = Some(match kind {
+                        Some(existing) => path_kind.merge(existing),
+                        None => path_kind,
+                    });
                 }
             }
         }
 
-        false
+        kind
     }
 }
+
+/// How much of the UI a file change invalidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReloadKind {
+    /// Only stylesheets changed - styles can be swapped in place.
+    Styles,
+    /// Anything else changed - re-run the app function.
+    Full,
+    /// An asset other than a stylesheet changed - rebuild the documents
+    /// and re-run the app function.
+    Assets,
+    /// Rust source changed - rebuild the app and restart it.
+    Code,
+}
+
+impl ReloadKind {
+    fn merge(self, other: ReloadKind) -> ReloadKind {
+        match (self, other) {
+            (ReloadKind::Styles, ReloadKind::Styles) => ReloadKind::Styles,
+            (ReloadKind::Code, _) | (_, ReloadKind::Code) => ReloadKind::Code,
+            (ReloadKind::Assets, _) | (_, ReloadKind::Assets) => ReloadKind::Assets,
+            _ => ReloadKind::Full,
+        }
+    }
+}
+
+/// Check if a path is a CSS stylesheet.
+fn is_stylesheet(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("css"))
+}
@@ -0,0 +1,276 @@
+//! Restarting the app with rebuilt code, keeping its state.
+//!
+//! When Rust source changes, hot reload rebuilds the app with cargo on a
+//! background thread. Once the executable has changed, the runtime saves
+//! the app's hook values (see [`rinch_core::hot_state`]) and its windows'
+//! places and scroll positions, quits, and starts the new executable with
+//! [`STATE_VAR`] naming the file it saved them to. The new process reads
+//! the file back before its first render.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use rinch_core::hot_state::{HotValue, SavedHook};
+use winit::event_loop::EventLoopProxy;
+
+use super::runtime::RinchEvent;
+use super::scroll::ScrollSnapshot;
+
+/// Environment variable naming the file a restarted app reads its state
+/// from.
+pub const STATE_VAR: &str = "RINCH_HOT_STATE";
+
+/// Whether a rebuild is running, and whether another was asked for
+/// meanwhile.
+static BUILD: Mutex<(bool, bool)> = Mutex::new((false, false));
+
+/// A window as the app left it when it restarted.
+#[derive(Debug, Clone)]
+pub(crate) struct SavedWindow {
+    pub title: String,
+    /// Outer position, in physical pixels.
+    pub position: Option<(i32, i32)>,
+    /// Inner size, in physical pixels.
+    pub size: (u32, u32),
+    pub maximized: bool,
+    pub content_scale: f32,
+    pub scroll: ScrollSnapshot,
+}
+
+impl HotValue for SavedWindow {
+    fn to_hot(&self) -> String {
+        (
+            self.title.clone(),
+            (
+                self.position,
+                self.size,
+                (self.maximized, self.content_scale),
+            ),
+            self.scroll.clone(),
+        )
+            .to_hot()
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        let (title, (position, size, (maximized, content_scale)), scroll) =
+            HotValue::from_hot(text)?;
+        Some(Self {
+            title,
+            position,
+            size,
+            maximized,
+            content_scale,
+            scroll,
+        })
+    }
+}
+
+/// What the app hands over to the process that replaces it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SavedState {
+    pub hooks: Vec<SavedHook>,
+    pub windows: Vec<SavedWindow>,
+}
+
+impl HotValue for SavedState {
+    fn to_hot(&self) -> String {
+        (self.hooks.clone(), self.windows.clone()).to_hot()
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        let (hooks, windows) = HotValue::from_hot(text)?;
+        Some(Self { hooks, windows })
+    }
+}
+
+impl SavedState {
+    /// The state the process this one replaced saved, if it was started by
+    /// a hot restart.
+    pub(crate) fn take() -> Option<Self> {
+        let path = std::env::var_os(STATE_VAR)?;
+        let text = std::fs::read_to_string(&path).ok();
+        let _ = std::fs::remove_file(&path);
+        let state = text.as_deref().and_then(Self::from_hot);
+        if state.is_none() {
+            tracing::warn!("Hot reload: couldn't read the state saved before restarting");
+        }
+        state
+    }
+
+    /// Take the saved window with this title, so each saved window is
+    /// given to one new window.
+    pub(crate) fn take_window(&mut self, title: &str) -> Option<SavedWindow> {
+        let index = self
+            .windows
+            .iter()
+            .position(|window| window.title == title)?;
+        Some(self.windows.remove(index))
+    }
+
+    /// Save the state to a file and start `exe` to pick it up, with this
+    /// process's arguments.
+    pub(crate) fn relaunch(&self, exe: &Path) -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("rinch-hot-state-{}", std::process::id()));
+        std::fs::write(&path, self.to_hot())?;
+        Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .env(STATE_VAR, &path)
+            .spawn()?;
+        Ok(())
+    }
+}
+
+/// Rebuild the app on a background thread, with `command` or else cargo,
+/// and send `RinchEvent::HotRestart` if that changed the executable. A
+/// rebuild asked for while one runs follows it.
+pub(crate) fn rebuild(proxy: EventLoopProxy<RinchEvent>, command: Option<Vec<String>>) {
+    {
+        let mut state = BUILD.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0 {
+            state.1 = true;
+            return;
+        }
+        *state = (true, false);
+    }
+    std::thread::spawn(move || {
+        let mut changed = false;
+        loop {
+            changed |= build(command.as_deref());
+            let mut state = BUILD.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.1 {
+                state.0 = false;
+                break;
+            }
+            state.1 = false;
+        }
+        if changed {
+            let _ = proxy.send_event(RinchEvent::HotRestart);
+        }
+    });
+}
+
+/// Run one build. Returns whether the executable changed.
+fn build(command: Option<&[String]>) -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let mut process = match command {
+        Some([program, args @ ..]) => {
+            let mut process = Command::new(program);
+            process.args(args);
+            process
+        }
+        _ => {
+            let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+            let mut process = Command::new(cargo);
+            process.args(cargo_args(&exe));
+            process
+        }
+    };
+    let before = modified(&exe);
+
+    // Windows won't replace a running executable, but it will rename one
+    #[cfg(target_os = "windows")]
+    let moved = exe.with_extension("exe.old");
+    #[cfg(target_os = "windows")]
+    {
+        let _ = std::fs::remove_file(&moved);
+        let _ = std::fs::rename(&exe, &moved);
+    }
+
+    tracing::info!("Hot reload: rebuilding with {:?}", process);
+    let built = match process.status() {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            tracing::warn!("Hot reload: build failed ({status}), keeping the running app");
+            false
+        }
+        Err(e) => {
+            tracing::warn!("Hot reload: couldn't run the build: {e}");
+            false
+        }
+    };
+
+    #[cfg(target_os = "windows")]
+    if !exe.exists() {
+        let _ = std::fs::rename(&moved, &exe);
+    }
+
+    built && modified(&exe) != before
+}
+
+/// The arguments to `cargo` that build `exe` again: the binary or example
+/// of that name, in the profile whose directory it's in.
+fn cargo_args(exe: &Path) -> Vec<OsString> {
+    let mut args = vec![OsString::from("build")];
+    let name = exe.file_stem().unwrap_or_default().to_os_string();
+    let mut dir = exe.parent();
+    if dir
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == "examples")
+    {
+        args.extend([OsString::from("--example"), name]);
+        dir = dir.and_then(Path::parent);
+    } else {
+        args.extend([OsString::from("--bin"), name]);
+    }
+    match dir.and_then(Path::file_name).and_then(|dir| dir.to_str()) {
+        None | Some("debug") => {}
+        Some("release") => args.push("--release".into()),
+        Some(profile) => args.extend(["--profile".into(), profile.into()]),
+    }
+    args
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_builds_the_running_binary_again() {
+        let args = |exe: &str| {
+            cargo_args(Path::new(exe))
+                .into_iter()
+                .map(|arg| arg.into_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            args("/app/target/debug/editor"),
+            ["build", "--bin", "editor"]
+        );
+        assert_eq!(
+            args("/app/target/release/examples/demo"),
+            ["build", "--example", "demo", "--release"]
+        );
+        assert_eq!(
+            args("/app/target/dev-fast/editor"),
+            ["build", "--bin", "editor", "--profile", "dev-fast"]
+        );
+    }
+
+    #[test]
+    fn windows_keep_their_zoom() {
+        let window = SavedWindow {
+            title: "Editor".into(),
+            position: Some((40, 60)),
+            size: (1024, 768),
+            maximized: false,
+            content_scale: 1.25,
+            scroll: ScrollSnapshot::default(),
+        };
+        let restored = SavedWindow::from_hot(&window.to_hot()).unwrap();
+        assert_eq!(restored.title, "Editor");
+        assert_eq!(restored.size, (1024, 768));
+        assert_eq!(restored.content_scale, 1.25);
+    }
+}
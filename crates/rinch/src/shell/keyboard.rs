@@ -0,0 +1,213 @@
+//! Keyboard and focus events for elements.
+//!
+//! Key presses go to the focused element, or to `body` when nothing has
+//! focus, and bubble up: `onkeydown`, `onkeyup` and `onkeypress` handlers
+//! on it and on every element above it run, innermost first. `onkeypress`
+//! only runs for keys that type text without Ctrl, Cmd or Alt.
+//!
+//! When focus moves, `onblur` runs on the element losing it, then `onfocus`
+//! on the element gaining it. Neither bubbles.
+//!
+//! Ctrl+C, Ctrl+X and Ctrl+V (Cmd on macOS) also bubble `oncopy`, `oncut`
+//! and `onpaste` up from the same element, with the selected text, or the
+//! clipboard's contents, in the event.
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::{EventHandlerId, KeyboardEvent};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+
+use crate::menu::KeyPress;
+
+/// Attribute with an element's `onkeydown` handler ID.
+pub(crate) const KEY_DOWN_ATTR: &str = "data-onkeydown";
+/// Attribute with an element's `onkeyup` handler ID.
+pub(crate) const KEY_UP_ATTR: &str = "data-onkeyup";
+/// Attribute with an element's `onkeypress` handler ID.
+pub(crate) const KEY_PRESS_ATTR: &str = "data-onkeypress";
+/// Attribute with an element's `onfocus` handler ID.
+pub(crate) const FOCUS_ATTR: &str = "data-onfocus";
+/// Attribute with an element's `onblur` handler ID.
+pub(crate) const BLUR_ATTR: &str = "data-onblur";
+/// Attribute with an element's `oncopy` handler ID.
+pub(crate) const COPY_ATTR: &str = "data-oncopy";
+/// Attribute with an element's `oncut` handler ID.
+pub(crate) const CUT_ATTR: &str = "data-oncut";
+/// Attribute with an element's `onpaste` handler ID.
+pub(crate) const PASTE_ATTR: &str = "data-onpaste";
+
+/// The element key events go to: the focused one, or `body`.
+pub(crate) fn key_target(doc: &BaseDocument, focused: Option<usize>) -> Option<usize> {
+    focused.or_else(|| {
+        let root = doc.root_element();
+        root.children.iter().copied().find(|&child| {
+            doc.get_node(child)
+                .and_then(|node| node.element_data())
+                .is_some_and(|element| element.name.local.as_ref() == "body")
+        })
+    })
+}
+
+/// The handlers in `attr` on `node_id` and the elements above it,
+/// innermost first.
+pub(crate) fn bubbling_handlers(
+    doc: &BaseDocument,
+    node_id: usize,
+    attr: &str,
+) -> Vec<EventHandlerId> {
+    let mut handlers = Vec::new();
+    let mut current = Some(node_id);
+    while let Some(node) = current.and_then(|id| doc.get_node(id)) {
+        current = node.parent;
+        handlers.extend(handler(doc, node.id, attr));
+    }
+    handlers
+}
+
+/// The handler in `attr` on `node_id` alone.
+pub(crate) fn handler(doc: &BaseDocument, node_id: usize, attr: &str) -> Option<EventHandlerId> {
+    doc.get_node(node_id)?
+        .element_data()?
+        .attrs()
+        .iter()
+        .find(|a| a.name.local.as_ref() == attr)
+        .and_then(|a| a.value.parse().ok())
+        .map(EventHandlerId)
+}
+
+/// The event for a winit key event.
+pub(crate) fn keyboard_event(
+    event: &winit::event::KeyEvent,
+    modifiers: ModifiersState,
+) -> KeyboardEvent {
+    KeyboardEvent {
+        key: key_name(&event.logical_key),
+        code: code_name(event.physical_key),
+        ctrl: modifiers.control_key(),
+        alt: modifiers.alt_key(),
+        shift: modifiers.shift_key(),
+        meta: modifiers.super_key(),
+        repeat: event.repeat,
+    }
+}
+
+/// The event for a key press parsed from a shortcut, typed on a US
+/// keyboard (for tests).
+pub(crate) fn press_event(press: &KeyPress) -> KeyboardEvent {
+    let key = match press.character {
+        Some(c) if press.shift => c.to_uppercase().to_string(),
+        Some(c) => c.to_string(),
+        None => code_name(PhysicalKey::Code(press.key)),
+    };
+    KeyboardEvent {
+        key,
+        code: code_name(PhysicalKey::Code(press.key)),
+        ctrl: press.ctrl_or_cmd,
+        alt: press.alt,
+        shift: press.shift,
+        meta: false,
+        repeat: false,
+    }
+}
+
+/// The clipboard event attribute a key press fires, if it's Ctrl/Cmd+C, X
+/// or V.
+pub(crate) fn clipboard_attr(press: &KeyPress) -> Option<&'static str> {
+    if !press.ctrl_or_cmd || press.alt || press.shift {
+        return None;
+    }
+    ['c', 'x', 'v']
+        .into_iter()
+        .zip([COPY_ATTR, CUT_ATTR, PASTE_ATTR])
+        .find_map(|(letter, attr)| press.is_letter(letter).then_some(attr))
+}
+
+/// Whether a key event types text, so `onkeypress` runs for it.
+pub(crate) fn types_text(event: &KeyboardEvent) -> bool {
+    let mut chars = event.key.chars();
+    let single = chars.next().filter(|_| chars.next().is_none());
+    !event.ctrl_or_cmd() && !event.alt && single.is_some_and(|c| !c.is_control())
+}
+
+/// A key's web name: the text it types, or its named key.
+fn key_name(key: &Key) -> String {
+    match key {
+        Key::Character(text) => text.to_string(),
+        Key::Named(NamedKey::Space) => " ".to_string(),
+        // winit's names are the web's
+        Key::Named(named) => format!("{named:?}"),
+        _ => "Unidentified".to_string(),
+    }
+}
+
+/// A key's web code: where it is on the keyboard.
+fn code_name(key: PhysicalKey) -> String {
+    match key {
+        // The web calls the Windows and Cmd keys "Meta"
+        PhysicalKey::Code(KeyCode::SuperLeft) => "MetaLeft".to_string(),
+        PhysicalKey::Code(KeyCode::SuperRight) => "MetaRight".to_string(),
+        // winit's names are the web's
+        PhysicalKey::Code(code) => format!("{code:?}"),
+        PhysicalKey::Unidentified(_) => "Unidentified".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_have_web_names() {
+        assert_eq!(key_name(&Key::Character("a".into())), "a");
+        assert_eq!(key_name(&Key::Named(NamedKey::ArrowLeft)), "ArrowLeft");
+        assert_eq!(key_name(&Key::Named(NamedKey::Space)), " ");
+        assert_eq!(code_name(PhysicalKey::Code(KeyCode::KeyA)), "KeyA");
+        assert_eq!(code_name(PhysicalKey::Code(KeyCode::Digit1)), "Digit1");
+        assert_eq!(code_name(PhysicalKey::Code(KeyCode::SuperLeft)), "MetaLeft");
+    }
+
+    #[test]
+    fn only_keys_that_type_text_are_presses() {
+        let typed = |key: &str, ctrl: bool| KeyboardEvent {
+            key: key.into(),
+            ctrl,
+            ..Default::default()
+        };
+        assert!(types_text(&typed("a", false)));
+        assert!(types_text(&typed(" ", false)));
+        assert!(!types_text(&typed("a", true)));
+        assert!(!types_text(&typed("Enter", false)));
+    }
+
+    #[test]
+    fn ctrl_c_x_and_v_are_clipboard_events() {
+        let press = |ctrl_or_cmd: bool, shift: bool, key: KeyCode, character: char| KeyPress {
+            ctrl_or_cmd,
+            alt: false,
+            shift,
+            key,
+            character: Some(character),
+        };
+        assert_eq!(
+            clipboard_attr(&press(true, false, KeyCode::KeyC, 'c')),
+            Some(COPY_ATTR)
+        );
+        assert_eq!(
+            clipboard_attr(&press(true, false, KeyCode::KeyX, 'x')),
+            Some(CUT_ATTR)
+        );
+        assert_eq!(
+            clipboard_attr(&press(true, false, KeyCode::KeyV, 'v')),
+            Some(PASTE_ATTR)
+        );
+        assert_eq!(
+            clipboard_attr(&press(false, false, KeyCode::KeyV, 'v')),
+            None
+        );
+        assert_eq!(clipboard_attr(&press(true, true, KeyCode::KeyV, 'v')), None);
+        // Where the letter is typed, not where it is on a US keyboard
+        assert_eq!(
+            clipboard_attr(&press(true, false, KeyCode::Period, 'v')),
+            Some(PASTE_ATTR)
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Live regions: elements whose text is announced when it changes.
+//!
+//! An element is a live region if it has `aria-live="polite"` or
+//! `"assertive"`, or, without `aria-live`, `role="status"` or `"log"`
+//! (polite) or `role="alert"` (assertive). When a re-render changes a
+//! region's text, the whole new text is announced. Regions are matched
+//! between documents by their position in the tree; a region that wasn't
+//! there before isn't announced, except alerts, which are announced as
+//! soon as they appear with text. Regions inside regions are part of the
+//! outer one.
+
+use std::collections::BTreeMap;
+
+use blitz_dom::BaseDocument;
+use rinch_core::a11y::{announce, Politeness};
+
+/// A live region's politeness, whether it's an alert, and its text.
+type Region = (Politeness, bool, String);
+
+/// The live regions of a window's document, keyed by their position in the
+/// tree (child indices among elements from the root).
+#[derive(Debug, Default)]
+pub(crate) struct LiveRegions(BTreeMap<Vec<usize>, Region>);
+
+impl LiveRegions {
+    /// Record a document's live regions.
+    pub(crate) fn new(doc: &BaseDocument) -> Self {
+        let mut regions = BTreeMap::new();
+        let mut stack = vec![(0, Vec::new())];
+        while let Some((node_id, path)) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            if let Some(element) = node.element_data() {
+                let attr = |name: &str| {
+                    element
+                        .attrs()
+                        .iter()
+                        .find(|attr| attr.name.local.as_ref() == name)
+                        .map(|attr| attr.value.as_str())
+                };
+                let (live, role) = (attr("aria-live"), attr("role"));
+                if let Some(politeness) = politeness(live, role) {
+                    let text = node.text_content();
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    regions.insert(path, (politeness, role == Some("alert"), text));
+                    continue;
+                }
+            }
+            let children = node.children.iter().copied().filter(|&id| {
+                doc.get_node(id)
+                    .is_some_and(|child| child.element_data().is_some())
+            });
+            for (index, child) in children.enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                stack.push((child, child_path));
+            }
+        }
+        Self(regions)
+    }
+
+    /// Announce the regions `doc` changed, and remember its regions.
+    pub(crate) fn update(&mut self, doc: &BaseDocument) {
+        let after = Self::new(doc);
+        for (message, politeness) in self.changes(&after) {
+            announce(message, politeness);
+        }
+        *self = after;
+    }
+
+    /// What to announce for the regions in `after`, in document order.
+    fn changes(&self, after: &Self) -> Vec<(String, Politeness)> {
+        after
+            .0
+            .iter()
+            .filter(|(path, (_, alert, text))| {
+                !text.is_empty()
+                    && match self.0.get(*path) {
+                        Some((_, _, before)) => before != text,
+                        None => *alert,
+                    }
+            })
+            .map(|(_, (politeness, _, text))| (text.clone(), *politeness))
+            .collect()
+    }
+}
+
+/// A region's politeness from its `aria-live` and `role`, or `None` if it
+/// isn't a live region.
+fn politeness(live: Option<&str>, role: Option<&str>) -> Option<Politeness> {
+    match (live, role) {
+        (Some(live), _) => Politeness::from_aria_live(live),
+        (None, Some("alert")) => Some(Politeness::Assertive),
+        (None, Some("status" | "log")) => Some(Politeness::Polite),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(regions: &[(&[usize], bool, &str)]) -> LiveRegions {
+        LiveRegions(
+            regions
+                .iter()
+                .map(|&(path, alert, text)| {
+                    let politeness = if alert {
+                        Politeness::Assertive
+                    } else {
+                        Politeness::Polite
+                    };
+                    (path.to_vec(), (politeness, alert, text.to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn changed_text_and_new_alerts_are_announced() {
+        let before = regions(&[(&[0], false, "Ready"), (&[1], false, "")]);
+        let after = regions(&[
+            (&[0], false, "Saved"),
+            (&[1], false, ""),
+            (&[2], false, "New status"),
+            (&[3], true, "Disk full"),
+        ]);
+        assert_eq!(
+            before.changes(&after),
+            [
+                ("Saved".to_string(), Politeness::Polite),
+                ("Disk full".to_string(), Politeness::Assertive)
+            ]
+        );
+        assert!(after.changes(&after).is_empty());
+    }
+
+    #[test]
+    fn aria_live_overrides_role() {
+        assert_eq!(politeness(None, Some("alert")), Some(Politeness::Assertive));
+        assert_eq!(politeness(None, Some("status")), Some(Politeness::Polite));
+        assert_eq!(politeness(Some("off"), Some("alert")), None);
+        assert_eq!(
+            politeness(Some("assertive"), Some("status")),
+            Some(Politeness::Assertive)
+        );
+        assert_eq!(politeness(None, Some("button")), None);
+    }
+}
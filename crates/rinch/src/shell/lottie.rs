@@ -0,0 +1,1080 @@
+//! Lottie animation playback, for `LottiePlayer` elements (`lottie` feature).
+//!
+//! `LottiePlayer { src: "intro.json" }` renders as an empty `div` carrying
+//! the player's settings in `data-lottie-*` attributes. After layout, rinch
+//! loads the Bodymovin JSON each player points at and paints the current
+//! frame over the player's content box, scaled to fit, as vector paths.
+//!
+//! The shape subset that icon and illustration exports use is supported:
+//! shape and null layers with parenting, groups, paths, rectangles, ellipses,
+//! fills and strokes, with keyframes (hold and bezier-eased) on any property.
+//! Masks, mattes, gradients, trim paths, images, text and precomps are not
+//! drawn. Animations are painted after the page, clipped to the part of the
+//! player that shows: scroll containers around it cut it off, players under
+//! `display: none`, `visibility: hidden` or `opacity: 0` aren't painted, and
+//! positioned elements with a background stacked above the player (by
+//! `z-index`, then document order) cover it.
+//!
+//! Players pause when the user prefers reduced motion, unless they have
+//! `motion: "full"`.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::kurbo::{Affine, BezPath, Ellipse, Point, Rect, Shape, Stroke};
+use peniko::{Color, Fill, Mix};
+use rinch_core::node_ref::Margins;
+use rinch_core::{prefers_reduced_motion, untracked};
+use serde_json::Value;
+
+use super::computed::computed_values;
+use super::scroll::{border_box_origin, node_path};
+use super::selection::content_origin;
+use super::visibility::{clip_bounds, intersect, Bounds};
+
+/// Attribute holding a player's JSON file.
+const SRC_ATTR: &str = "data-lottie-src";
+/// Attribute saying whether a player is playing (`false` pauses it).
+const PLAYING_ATTR: &str = "data-lottie-playing";
+/// Attribute saying whether a player loops (`false` stops on the last frame).
+const LOOP_ATTR: &str = "data-lottie-loop";
+/// Attribute that keeps a player going under reduced motion, as `full`.
+const MOTION_ATTR: &str = "data-motion";
+
+/// Tolerance for turning ellipses and rounded rectangles into paths.
+const PATH_TOLERANCE: f64 = 0.1;
+
+/// A property's value, as a list of numbers: a scalar, a point, a color, or
+/// a path's vertices and tangents.
+type Components = Vec<f64>;
+
+/// A keyframe of an animated property.
+#[derive(Debug, Clone, PartialEq)]
+struct Keyframe {
+    /// The frame it's at.
+    time: f64,
+    value: Components,
+    /// Whether the value holds until the next keyframe.
+    hold: bool,
+    /// The bezier handles easing towards the next keyframe.
+    easing: Option<(Point, Point)>,
+}
+
+/// A property that may be animated.
+#[derive(Debug, Clone, PartialEq)]
+enum Property {
+    Static(Components),
+    Animated(Vec<Keyframe>),
+    /// A position whose x and y are animated separately.
+    Split(Box<Property>, Box<Property>),
+}
+
+/// The first number of a value that may be a number or a list of them, as
+/// bezier handles and split positions are.
+fn first_number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_array()?.first()?.as_f64())
+}
+
+/// A point from `[x, y]`, or `{ "x": .., "y": .. }` as easing handles are.
+fn point(value: &Value) -> Option<Point> {
+    if let Some(values) = value.as_array() {
+        return Some(Point::new(
+            values.first()?.as_f64()?,
+            values.get(1)?.as_f64()?,
+        ));
+    }
+    Some(Point::new(
+        first_number(value.get("x")?)?,
+        first_number(value.get("y")?)?,
+    ))
+}
+
+/// A path's vertices, in- and out-tangents, flattened; and whether it's
+/// closed.
+fn path_components(value: &Value) -> Option<(Components, bool)> {
+    // Keyframed paths wrap the path in a list
+    let value = match value.as_array() {
+        Some(values) => values.first()?,
+        None => value,
+    };
+    let mut components = Vec::new();
+    for key in ["v", "i", "o"] {
+        for vertex in value.get(key)?.as_array()? {
+            let vertex = point(vertex)?;
+            components.extend([vertex.x, vertex.y]);
+        }
+    }
+    let closed = value.get("c").and_then(Value::as_bool).unwrap_or(false);
+    Some((components, closed))
+}
+
+fn components(value: &Value) -> Option<Components> {
+    if let Some(number) = value.as_f64() {
+        return Some(vec![number]);
+    }
+    let values = value.as_array()?;
+    if values.first().is_some_and(Value::is_object) {
+        return path_components(value).map(|(components, _)| components);
+    }
+    values.iter().map(Value::as_f64).collect()
+}
+
+impl Property {
+    /// Parse a property object (`{ "a": 0, "k": .. }`).
+    fn parse(value: &Value) -> Option<Self> {
+        if value.get("s").and_then(Value::as_bool) == Some(true) {
+            return Some(Self::Split(
+                Box::new(Self::parse(value.get("x")?)?),
+                Box::new(Self::parse(value.get("y")?)?),
+            ));
+        }
+        let k = value.get("k")?;
+        let keyframes = k
+            .as_array()
+            .filter(|frames| frames.first().is_some_and(|frame| frame.get("t").is_some()));
+        let Some(frames) = keyframes else {
+            return components(k).map(Self::Static);
+        };
+
+        let mut keyframes: Vec<Keyframe> = Vec::with_capacity(frames.len());
+        for (index, frame) in frames.iter().enumerate() {
+            let value = match frame.get("s") {
+                Some(start) => components(start)?,
+                // Older exports end on a keyframe without a value, taking
+                // the end value of the one before
+                None => {
+                    let end = index
+                        .checked_sub(1)
+                        .and_then(|previous| frames[previous].get("e"))
+                        .and_then(components);
+                    match end.or_else(|| keyframes.last().map(|key| key.value.clone())) {
+                        Some(value) => value,
+                        None => continue,
+                    }
+                }
+            };
+            let easing = frame
+                .get("o")
+                .and_then(point)
+                .zip(frame.get("i").and_then(point));
+            keyframes.push(Keyframe {
+                time: frame.get("t")?.as_f64()?,
+                value,
+                hold: frame.get("h").and_then(Value::as_f64) == Some(1.0),
+                easing,
+            });
+        }
+        Some(Self::Animated(keyframes))
+    }
+
+    /// The value at `frame`.
+    fn at(&self, frame: f64) -> Components {
+        match self {
+            Self::Static(value) => value.clone(),
+            Self::Split(x, y) => vec![
+                x.at(frame).first().copied().unwrap_or(0.0),
+                y.at(frame).first().copied().unwrap_or(0.0),
+            ],
+            Self::Animated(keyframes) => {
+                let Some(next) = keyframes.iter().position(|key| key.time > frame) else {
+                    return keyframes
+                        .last()
+                        .map(|key| key.value.clone())
+                        .unwrap_or_default();
+                };
+                if next == 0 {
+                    return keyframes[0].value.clone();
+                }
+                let (from, to) = (&keyframes[next - 1], &keyframes[next]);
+                if from.hold || from.value.len() != to.value.len() {
+                    return from.value.clone();
+                }
+                let mut t = (frame - from.time) / (to.time - from.time);
+                if let Some((out, into)) = from.easing {
+                    t = cubic_bezier(out, into, t);
+                }
+                from.value
+                    .iter()
+                    .zip(&to.value)
+                    .map(|(a, b)| a + (b - a) * t)
+                    .collect()
+            }
+        }
+    }
+
+    /// The first component at `frame`, or `default`.
+    fn scalar(&self, frame: f64, default: f64) -> f64 {
+        self.at(frame).first().copied().unwrap_or(default)
+    }
+
+    /// The first two components at `frame`, or `default`.
+    fn pair(&self, frame: f64, default: (f64, f64)) -> (f64, f64) {
+        match self.at(frame)[..] {
+            [x, y, ..] => (x, y),
+            [x] => (x, x),
+            [] => default,
+        }
+    }
+}
+
+/// The eased progress of a CSS-style cubic bezier with handles `p1` and `p2`
+/// at progress `x`.
+fn cubic_bezier(p1: Point, p2: Point, x: f64) -> f64 {
+    if x <= 0.0 || x >= 1.0 {
+        return x.clamp(0.0, 1.0);
+    }
+    let bezier = |t: f64, a: f64, b: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+    };
+    // x(t) only increases, so bisect for the t that gives `x`
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..32 {
+        let mid = (low + high) / 2.0;
+        if bezier(mid, p1.x, p2.x) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    bezier((low + high) / 2.0, p1.y, p2.y)
+}
+
+/// A layer's or group's transform.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Transform {
+    anchor: Option<Property>,
+    position: Option<Property>,
+    scale: Option<Property>,
+    rotation: Option<Property>,
+    opacity: Option<Property>,
+}
+
+impl Transform {
+    fn parse(value: &Value) -> Self {
+        let property = |key: &str| value.get(key).and_then(Property::parse);
+        Self {
+            anchor: property("a"),
+            position: property("p"),
+            scale: property("s"),
+            rotation: property("r"),
+            opacity: property("o"),
+        }
+    }
+
+    /// The transform at `frame`, and its opacity from 0 to 1.
+    fn at(&self, frame: f64) -> (Affine, f64) {
+        let pair = |property: &Option<Property>, default| {
+            property
+                .as_ref()
+                .map_or(default, |property| property.pair(frame, default))
+        };
+        let scalar = |property: &Option<Property>, default| {
+            property
+                .as_ref()
+                .map_or(default, |property| property.scalar(frame, default))
+        };
+        let anchor = pair(&self.anchor, (0.0, 0.0));
+        let position = pair(&self.position, (0.0, 0.0));
+        let scale = pair(&self.scale, (100.0, 100.0));
+        let rotation = scalar(&self.rotation, 0.0);
+        let affine = Affine::translate(position)
+            * Affine::rotate(rotation.to_radians())
+            * Affine::scale_non_uniform(scale.0 / 100.0, scale.1 / 100.0)
+            * Affine::translate((-anchor.0, -anchor.1));
+        (affine, scalar(&self.opacity, 100.0) / 100.0)
+    }
+}
+
+/// An item of a shape layer or group.
+#[derive(Debug, Clone, PartialEq)]
+enum ShapeItem {
+    Group(Vec<ShapeItem>),
+    Path {
+        path: Property,
+        closed: bool,
+    },
+    Rect {
+        position: Property,
+        size: Property,
+        roundness: Option<Property>,
+    },
+    Ellipse {
+        position: Property,
+        size: Property,
+    },
+    Fill {
+        color: Property,
+        opacity: Option<Property>,
+    },
+    Stroke {
+        color: Property,
+        opacity: Option<Property>,
+        width: Property,
+    },
+    Transform(Transform),
+}
+
+impl ShapeItem {
+    /// Parse a shape item; unsupported ones and hidden ones are `None`.
+    fn parse(value: &Value) -> Option<Self> {
+        if value.get("hd").and_then(Value::as_bool) == Some(true) {
+            return None;
+        }
+        let property = |key: &str| value.get(key).and_then(Property::parse);
+        Some(match value.get("ty")?.as_str()? {
+            "gr" => Self::Group(parse_shapes(value.get("it")?)),
+            "sh" => {
+                let shape = value.get("ks")?;
+                let closed = shape
+                    .get("k")
+                    .and_then(|k| match k.as_array() {
+                        // Keyframed: the first keyframe's start value
+                        Some(frames) if frames.first()?.get("t").is_some() => {
+                            path_components(frames.first()?.get("s")?)
+                        }
+                        _ => path_components(k),
+                    })
+                    .is_some_and(|(_, closed)| closed);
+                Self::Path {
+                    path: Property::parse(shape)?,
+                    closed,
+                }
+            }
+            "rc" => Self::Rect {
+                position: property("p")?,
+                size: property("s")?,
+                roundness: property("r"),
+            },
+            "el" => Self::Ellipse {
+                position: property("p")?,
+                size: property("s")?,
+            },
+            "fl" => Self::Fill {
+                color: property("c")?,
+                opacity: property("o"),
+            },
+            "st" => Self::Stroke {
+                color: property("c")?,
+                opacity: property("o"),
+                width: property("w")?,
+            },
+            "tr" => Self::Transform(Transform::parse(value)),
+            _ => return None,
+        })
+    }
+
+    /// The item's outline at `frame`, if it's a shape.
+    fn path(&self, frame: f64) -> Option<BezPath> {
+        match self {
+            Self::Path { path, closed } => Some(bez_path(&path.at(frame), *closed)),
+            Self::Rect {
+                position,
+                size,
+                roundness,
+            } => {
+                let (x, y) = position.pair(frame, (0.0, 0.0));
+                let (width, height) = size.pair(frame, (0.0, 0.0));
+                let radius = roundness.as_ref().map_or(0.0, |r| r.scalar(frame, 0.0));
+                let rect = Rect::from_center_size((x, y), (width, height));
+                Some(rect.to_rounded_rect(radius).to_path(PATH_TOLERANCE))
+            }
+            Self::Ellipse { position, size } => {
+                let (x, y) = position.pair(frame, (0.0, 0.0));
+                let (width, height) = size.pair(frame, (0.0, 0.0));
+                let ellipse = Ellipse::new((x, y), (width / 2.0, height / 2.0), 0.0);
+                Some(ellipse.to_path(PATH_TOLERANCE))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse_shapes(value: &Value) -> Vec<ShapeItem> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(ShapeItem::parse).collect())
+        .unwrap_or_default()
+}
+
+/// A path from flattened vertices and tangents (see [`path_components`]).
+fn bez_path(components: &[f64], closed: bool) -> BezPath {
+    let count = components.len() / 6;
+    let point = |list: usize, index: usize| {
+        let offset = (list * count + index) * 2;
+        Point::new(components[offset], components[offset + 1])
+    };
+    let vertex = |index| point(0, index);
+    let in_tangent = |index| vertex(index) + point(1, index).to_vec2();
+    let out_tangent = |index| vertex(index) + point(2, index).to_vec2();
+
+    let mut path = BezPath::new();
+    if count == 0 {
+        return path;
+    }
+    path.move_to(vertex(0));
+    for index in 1..count {
+        path.curve_to(out_tangent(index - 1), in_tangent(index), vertex(index));
+    }
+    if closed {
+        path.curve_to(out_tangent(count - 1), in_tangent(0), vertex(0));
+        path.close_path();
+    }
+    path
+}
+
+/// A color property's color at `frame`, with `opacity` applied.
+fn color_at(color: &Property, opacity: &Option<Property>, frame: f64, alpha: f64) -> Color {
+    let rgba = color.at(frame);
+    let channel = |index: usize, default: f64| rgba.get(index).copied().unwrap_or(default) as f32;
+    let opacity = opacity.as_ref().map_or(100.0, |o| o.scalar(frame, 100.0)) / 100.0;
+    Color::new([
+        channel(0, 0.0),
+        channel(1, 0.0),
+        channel(2, 0.0),
+        channel(3, 1.0) * (opacity * alpha) as f32,
+    ])
+}
+
+/// Paint a group's items, top item last.
+fn paint_group(
+    scene: &mut impl PaintScene,
+    items: &[ShapeItem],
+    transform: Affine,
+    alpha: f64,
+    frame: f64,
+) {
+    let (transform, alpha) = match items.iter().find_map(|item| match item {
+        ShapeItem::Transform(own) => Some(own.at(frame)),
+        _ => None,
+    }) {
+        Some((own, opacity)) => (transform * own, alpha * opacity),
+        None => (transform, alpha),
+    };
+
+    // Fills and strokes apply to the shapes listed before them, including
+    // those of nested groups
+    let paths_before = |end: usize| -> Vec<BezPath> {
+        let mut paths = Vec::new();
+        for item in &items[..end] {
+            if let ShapeItem::Group(inner) = item {
+                collect_paths(inner, Affine::IDENTITY, frame, &mut paths);
+            } else if let Some(path) = item.path(frame) {
+                paths.push(path);
+            }
+        }
+        paths
+    };
+    for (index, item) in items.iter().enumerate().rev() {
+        match item {
+            ShapeItem::Group(inner) => paint_group(scene, inner, transform, alpha, frame),
+            ShapeItem::Fill { color, opacity } => {
+                let color = color_at(color, opacity, frame, alpha);
+                for path in paths_before(index) {
+                    scene.fill(Fill::NonZero, transform, color, None, &path);
+                }
+            }
+            ShapeItem::Stroke {
+                color,
+                opacity,
+                width,
+            } => {
+                let color = color_at(color, opacity, frame, alpha);
+                let stroke = Stroke::new(width.scalar(frame, 1.0));
+                for path in paths_before(index) {
+                    scene.stroke(&stroke, transform, color, None, &path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A group's shapes, in its parent's coordinates.
+fn collect_paths(items: &[ShapeItem], transform: Affine, frame: f64, paths: &mut Vec<BezPath>) {
+    let transform = match items.iter().find_map(|item| match item {
+        ShapeItem::Transform(own) => Some(own.at(frame).0),
+        _ => None,
+    }) {
+        Some(own) => transform * own,
+        None => transform,
+    };
+    for item in items {
+        if let ShapeItem::Group(inner) = item {
+            collect_paths(inner, transform, frame, paths);
+        } else if let Some(mut path) = item.path(frame) {
+            path.apply_affine(transform);
+            paths.push(path);
+        }
+    }
+}
+
+/// A layer of a composition.
+#[derive(Debug, Clone, PartialEq)]
+struct Layer {
+    index: Option<i64>,
+    parent: Option<i64>,
+    in_point: f64,
+    out_point: f64,
+    transform: Transform,
+    /// Empty for layers that only move their children (null layers) and
+    /// those rinch can't draw.
+    shapes: Vec<ShapeItem>,
+}
+
+/// A parsed Lottie animation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Composition {
+    width: f64,
+    height: f64,
+    frame_rate: f64,
+    in_point: f64,
+    out_point: f64,
+    layers: Vec<Layer>,
+}
+
+impl Composition {
+    /// Parse Bodymovin JSON, or `None` if it isn't a Lottie animation.
+    pub(crate) fn parse(json: &str) -> Option<Self> {
+        let root: Value = serde_json::from_str(json).ok()?;
+        let number = |value: &Value, key: &str| value.get(key).and_then(Value::as_f64);
+        let layers = root
+            .get("layers")?
+            .as_array()?
+            .iter()
+            .map(|layer| Layer {
+                index: layer.get("ind").and_then(Value::as_i64),
+                parent: layer.get("parent").and_then(Value::as_i64),
+                in_point: number(layer, "ip").unwrap_or(f64::MIN),
+                out_point: number(layer, "op").unwrap_or(f64::MAX),
+                transform: layer.get("ks").map(Transform::parse).unwrap_or_default(),
+                shapes: match layer.get("ty").and_then(Value::as_i64) {
+                    Some(4) => layer.get("shapes").map(parse_shapes).unwrap_or_default(),
+                    _ => Vec::new(),
+                },
+            })
+            .collect();
+        Some(Self {
+            width: number(&root, "w")?,
+            height: number(&root, "h")?,
+            frame_rate: number(&root, "fr").filter(|rate| *rate > 0.0)?,
+            in_point: number(&root, "ip").unwrap_or(0.0),
+            out_point: number(&root, "op")?,
+            layers,
+        })
+    }
+
+    /// How long one run of the animation lasts.
+    fn duration(&self) -> Duration {
+        Duration::from_secs_f64(((self.out_point - self.in_point) / self.frame_rate).max(0.0))
+    }
+
+    /// The frame shown `position` into playback.
+    fn frame_at(&self, position: Duration, looping: bool) -> f64 {
+        let length = self.out_point - self.in_point;
+        let frames = position.as_secs_f64() * self.frame_rate;
+        if length <= 0.0 {
+            self.in_point
+        } else if looping {
+            self.in_point + frames % length
+        } else {
+            // Stop on the last frame, which is just before the out point
+            self.in_point + frames.min(length - 1.0).max(0.0)
+        }
+    }
+
+    /// A layer's transform, including its parents'.
+    fn layer_transform(&self, layer: &Layer, frame: f64) -> Affine {
+        let mut transform = layer.transform.at(frame).0;
+        let mut parent = layer.parent;
+        // Bounded, in case of a parenting cycle
+        for _ in 0..self.layers.len() {
+            let Some(parent_layer) = parent
+                .and_then(|index| self.layers.iter().find(|layer| layer.index == Some(index)))
+            else {
+                break;
+            };
+            transform = parent_layer.transform.at(frame).0 * transform;
+            parent = parent_layer.parent;
+        }
+        transform
+    }
+
+    /// Paint `frame`, with the composition's `(0, 0)`-`(w, h)` mapped by
+    /// `transform`.
+    fn paint(&self, scene: &mut impl PaintScene, frame: f64, transform: Affine) {
+        for layer in self.layers.iter().rev() {
+            if layer.shapes.is_empty() || frame < layer.in_point || frame >= layer.out_point {
+                continue;
+            }
+            let (_, opacity) = layer.transform.at(frame);
+            let transform = transform * self.layer_transform(layer, frame);
+            paint_group(scene, &layer.shapes, transform, opacity, frame);
+        }
+    }
+}
+
+/// Scale and center content of `size` to fit in a box of `bounds`.
+fn fit(size: (f64, f64), bounds: (f64, f64)) -> Affine {
+    if size.0 <= 0.0 || size.1 <= 0.0 {
+        return Affine::IDENTITY;
+    }
+    let scale = (bounds.0 / size.0).min(bounds.1 / size.1);
+    Affine::translate((
+        (bounds.0 - size.0 * scale) / 2.0,
+        (bounds.1 - size.1 * scale) / 2.0,
+    )) * Affine::scale(scale)
+}
+
+/// A player element and how far into its animation it is.
+#[derive(Debug)]
+struct Player {
+    node_id: usize,
+    src: String,
+    playing: bool,
+    looping: bool,
+    /// How far playback has got.
+    position: Duration,
+    /// When playback last advanced, while playing.
+    last_tick: Option<Instant>,
+}
+
+/// A window's Lottie players.
+#[derive(Debug, Default)]
+pub(crate) struct LottiePlayers {
+    /// Loaded animations by source; `None` if the file couldn't be loaded.
+    animations: HashMap<String, Option<Rc<Composition>>>,
+    /// Players by tree position, so playback carries over rebuilds.
+    players: HashMap<Vec<usize>, Player>,
+}
+
+impl LottiePlayers {
+    /// Drop the loaded animations, so the next `find` reads them again.
+    pub(crate) fn forget_animations(&mut self) {
+        self.animations.clear();
+    }
+
+    /// Find the players in a freshly built document, keeping the playback
+    /// of those already playing the same animation.
+    pub(crate) fn find(&mut self, doc: &BaseDocument) {
+        let reduced = untracked(prefers_reduced_motion);
+        let mut previous = std::mem::take(&mut self.players);
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            let Some(element) = node.element_data() else {
+                continue;
+            };
+            let attr = |name: &str| {
+                element
+                    .attrs()
+                    .iter()
+                    .find(|a| a.name.local.as_ref() == name)
+                    .map(|a| a.value.to_string())
+            };
+            let Some(src) = attr(SRC_ATTR) else {
+                continue;
+            };
+            let full_motion = attr(MOTION_ATTR).as_deref() == Some("full");
+            let path = node_path(doc, node_id);
+            let (position, last_tick) = previous
+                .remove(&path)
+                .filter(|player| player.src == src)
+                .map_or((Duration::ZERO, None), |player| {
+                    (player.position, player.last_tick)
+                });
+            let playing =
+                attr(PLAYING_ATTR).as_deref() != Some("false") && (!reduced || full_motion);
+            self.animations
+                .entry(src.clone())
+                .or_insert_with(|| load(&src));
+            self.players.insert(
+                path,
+                Player {
+                    node_id,
+                    src,
+                    playing,
+                    looping: attr(LOOP_ATTR).as_deref() != Some("false"),
+                    position,
+                    last_tick: last_tick.filter(|_| playing),
+                },
+            );
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Advance playing animations to `now`. Returns whether any are still
+    /// playing.
+    pub(crate) fn tick(&mut self, now: Instant) -> bool {
+        let mut active = false;
+        for player in self.players.values_mut() {
+            if !player.playing {
+                continue;
+            }
+            let Some(Some(animation)) = self.animations.get(&player.src) else {
+                continue;
+            };
+            if let Some(last) = player.last_tick {
+                player.position += now.saturating_duration_since(last);
+            }
+            player.last_tick = Some(now);
+            if player.looping || player.position < animation.duration() {
+                active = true;
+            } else {
+                player.last_tick = None;
+            }
+        }
+        active
+    }
+
+    /// Paint each player's current frame over its content box.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        for player in self.players.values() {
+            let Some(Some(animation)) = self.animations.get(&player.src) else {
+                continue;
+            };
+            let Some(node) = doc.get_node(player.node_id) else {
+                continue;
+            };
+            let layout = &node.final_layout;
+            let content = (
+                (layout.size.width
+                    - layout.border.left
+                    - layout.border.right
+                    - layout.padding.left
+                    - layout.padding.right) as f64,
+                (layout.size.height
+                    - layout.border.top
+                    - layout.border.bottom
+                    - layout.padding.top
+                    - layout.padding.bottom) as f64,
+            );
+            let origin = content_origin(doc, player.node_id);
+            let shown = (
+                origin.0,
+                origin.1,
+                origin.0 + content.0,
+                origin.1 + content.1,
+            );
+            let visible = visible_parts(doc, player.node_id, shown);
+            if visible.is_empty() {
+                continue;
+            }
+            let mut clip = BezPath::new();
+            for (left, top, right, bottom) in visible {
+                clip.extend(Rect::new(left, top, right, bottom).path_elements(0.1));
+            }
+
+            let transform = Affine::scale(scale)
+                * Affine::translate(origin)
+                * fit((animation.width, animation.height), content);
+            let frame = animation.frame_at(player.position, player.looping);
+            scene.push_layer(Mix::Normal, 1.0, Affine::scale(scale), &clip);
+            animation.paint(scene, frame, transform);
+            scene.pop_layer();
+        }
+    }
+}
+
+/// The parts of a player's `area` that show, as rectangles that don't
+/// overlap: within the scroll containers around it, and not under a
+/// positioned element stacked above it. Empty if the player is hidden.
+fn visible_parts(doc: &BaseDocument, node_id: usize, area: Bounds) -> Vec<Bounds> {
+    // Hidden by itself or an ancestor
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let values = computed_values(doc, id, &["display", "visibility", "opacity"]);
+        let hidden = values.iter().any(|(property, value)| {
+            matches!(
+                (property.as_str(), value.as_str()),
+                ("display", "none") | ("visibility", "hidden" | "collapse") | ("opacity", "0")
+            )
+        });
+        if hidden {
+            return Vec::new();
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+
+    let Some(area) =
+        clip_bounds(doc, node_id, Margins::default()).and_then(|clip| intersect(area, clip))
+    else {
+        return Vec::new();
+    };
+    covering(doc, node_id)
+        .into_iter()
+        .fold(vec![area], subtract)
+}
+
+/// The boxes of positioned elements with a background painted above a
+/// player: stacked higher, or as high and later in the document. An
+/// element's stack level is the `z-index` of the nearest positioned element
+/// around it (or itself) that has one.
+fn covering(doc: &BaseDocument, player: usize) -> Vec<Bounds> {
+    let stack_level = |node_id: usize| {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let values = computed_values(doc, id, &["position", "z-index"]);
+            let value = |property: &str| {
+                values
+                    .iter()
+                    .find(|(name, _)| name == property)
+                    .map(|(_, value)| value.as_str())
+            };
+            if value("position").is_some_and(|position| position != "static")
+                && let Some(z_index) = value("z-index").and_then(|z| z.parse::<i32>().ok())
+            {
+                return z_index;
+            }
+            current = doc.get_node(id).and_then(|node| node.parent);
+        }
+        0
+    };
+    let contains = |ancestor: usize, mut node_id: usize| loop {
+        if node_id == ancestor {
+            return true;
+        }
+        match doc.get_node(node_id).and_then(|node| node.parent) {
+            Some(parent) => node_id = parent,
+            None => return false,
+        }
+    };
+    let level = stack_level(player);
+
+    let mut covers = Vec::new();
+    let mut after = false;
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter().rev());
+        if node_id == player {
+            after = true;
+        }
+        if node.element_data().is_none() || contains(node_id, player) || contains(player, node_id) {
+            continue;
+        }
+        let values = computed_values(
+            doc,
+            node_id,
+            &[
+                "position",
+                "background-color",
+                "background-image",
+                "visibility",
+            ],
+        );
+        let value = |property: &str| {
+            values
+                .iter()
+                .find(|(name, _)| name == property)
+                .map(|(_, value)| value.as_str())
+        };
+        let painted = !matches!(
+            value("background-color"),
+            None | Some("transparent" | "rgba(0, 0, 0, 0)")
+        ) || !matches!(value("background-image"), None | Some("none"));
+        if !painted
+            || matches!(value("position"), None | Some("static"))
+            || value("visibility") != Some("visible")
+        {
+            continue;
+        }
+        let z = stack_level(node_id);
+        if z > level || (z == level && after) {
+            let (x, y) = border_box_origin(doc, node_id);
+            let size = node.final_layout.size;
+            let bounds = (x, y, x + size.width as f64, y + size.height as f64);
+            if let Some(shown) = clip_bounds(doc, node_id, Margins::default())
+                .and_then(|clip| intersect(bounds, clip))
+            {
+                covers.push(shown);
+            }
+        }
+    }
+    covers
+}
+
+/// `parts` without what `cover` covers, still as rectangles that don't
+/// overlap.
+fn subtract(parts: Vec<Bounds>, cover: Bounds) -> Vec<Bounds> {
+    let mut left = Vec::new();
+    for part in parts {
+        let Some(hole) = intersect(part, cover) else {
+            left.push(part);
+            continue;
+        };
+        let pieces = [
+            // Above, below, then beside the hole
+            (part.0, part.1, part.2, hole.1),
+            (part.0, hole.3, part.2, part.3),
+            (part.0, hole.1, hole.0, hole.3),
+            (hole.2, hole.1, part.2, hole.3),
+        ];
+        left.extend(
+            pieces
+                .into_iter()
+                .filter(|piece| piece.0 < piece.2 && piece.1 < piece.3),
+        );
+    }
+    left
+}
+
+/// Load an animation from a JSON file.
+fn load(src: &str) -> Option<Rc<Composition>> {
+    let json = crate::assets::read_to_string(src)
+        .map_err(|error| tracing::warn!("Couldn't read Lottie animation {src}: {error}"))
+        .ok()?;
+    let composition = Composition::parse(&json);
+    if composition.is_none() {
+        tracing::warn!("{src} isn't a Lottie animation");
+    }
+    composition.map(Rc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 100x100, 30fps, one-second animation of a square that slides right
+    /// and fades, in a group with a fill.
+    const SQUARE: &str = r#"{
+        "w": 100, "h": 100, "fr": 30, "ip": 0, "op": 30,
+        "layers": [{
+            "ty": 4, "ind": 1, "ip": 0, "op": 30,
+            "ks": {
+                "p": { "a": 1, "k": [
+                    { "t": 0, "s": [0, 50], "o": { "x": [0], "y": [0] }, "i": { "x": [1], "y": [1] } },
+                    { "t": 30, "s": [100, 50] }
+                ] },
+                "o": { "a": 0, "k": 50 }
+            },
+            "shapes": [{
+                "ty": "gr",
+                "it": [
+                    { "ty": "rc", "p": { "a": 0, "k": [0, 0] }, "s": { "a": 0, "k": [20, 20] }, "r": { "a": 0, "k": 0 } },
+                    { "ty": "fl", "c": { "a": 0, "k": [1, 0, 0, 1] }, "o": { "a": 0, "k": 100 } },
+                    { "ty": "tr", "p": { "a": 0, "k": [0, 0] } }
+                ]
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn parses_shape_layers() {
+        let composition = Composition::parse(SQUARE).unwrap();
+        assert_eq!((composition.width, composition.height), (100.0, 100.0));
+        assert_eq!(composition.duration(), Duration::from_secs(1));
+        let layer = &composition.layers[0];
+        let ShapeItem::Group(items) = &layer.shapes[0] else {
+            panic!("expected a group");
+        };
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], ShapeItem::Fill { .. }));
+        assert_eq!(layer.transform.at(0.0).1, 0.5);
+        assert!(Composition::parse(r#"{ "layers": [] }"#).is_none());
+    }
+
+    #[test]
+    fn keyframes_interpolate_with_easing_and_holds() {
+        let composition = Composition::parse(SQUARE).unwrap();
+        let position = composition.layers[0].transform.position.as_ref().unwrap();
+        assert_eq!(position.pair(0.0, (0.0, 0.0)), (0.0, 50.0));
+        // Linear handles
+        assert!((position.pair(15.0, (0.0, 0.0)).0 - 50.0).abs() < 1e-6);
+        assert_eq!(position.pair(40.0, (0.0, 0.0)), (100.0, 50.0));
+
+        let held = Property::Animated(vec![
+            Keyframe {
+                time: 0.0,
+                value: vec![1.0],
+                hold: true,
+                easing: None,
+            },
+            Keyframe {
+                time: 10.0,
+                value: vec![2.0],
+                hold: false,
+                easing: None,
+            },
+        ]);
+        assert_eq!(held.scalar(9.0, 0.0), 1.0);
+        assert_eq!(held.scalar(10.0, 0.0), 2.0);
+
+        // Ease-in-out is slow at the ends and fast in the middle
+        let ease = |x| cubic_bezier(Point::new(0.42, 0.0), Point::new(0.58, 1.0), x);
+        assert!((ease(0.5) - 0.5).abs() < 1e-6);
+        assert!(ease(0.1) < 0.1);
+    }
+
+    #[test]
+    fn playback_loops_or_stops_on_the_last_frame() {
+        let composition = Composition::parse(SQUARE).unwrap();
+        let half = Duration::from_millis(500);
+        assert_eq!(composition.frame_at(half, true), 15.0);
+        assert_eq!(composition.frame_at(half * 3, true), 15.0);
+        assert_eq!(composition.frame_at(half * 3, false), 29.0);
+    }
+
+    #[test]
+    fn builds_paths_from_vertices() {
+        let value: Value = serde_json::from_str(
+            r#"{ "c": true, "v": [[0, 0], [10, 0], [10, 10]],
+                 "i": [[0, 0], [0, 0], [0, 0]], "o": [[0, 0], [0, 0], [0, 0]] }"#,
+        )
+        .unwrap();
+        let (components, closed) = path_components(&value).unwrap();
+        assert!(closed);
+        let path = bez_path(&components, closed);
+        assert_eq!(path.bounding_box(), Rect::new(0.0, 0.0, 10.0, 10.0));
+        // Move, two curves, the closing curve, and close
+        assert_eq!(path.elements().len(), 5);
+    }
+
+    #[test]
+    fn covered_parts_are_left_out() {
+        let player = (0.0, 0.0, 100.0, 100.0);
+        // A bar across the middle leaves the top and bottom
+        assert_eq!(
+            subtract(vec![player], (-10.0, 40.0, 110.0, 60.0)),
+            vec![(0.0, 0.0, 100.0, 40.0), (0.0, 60.0, 100.0, 100.0)]
+        );
+        // A corner leaves an L, in two rectangles
+        assert_eq!(
+            subtract(vec![player], (50.0, 50.0, 200.0, 200.0)),
+            vec![(0.0, 0.0, 100.0, 50.0), (0.0, 50.0, 50.0, 100.0)]
+        );
+        // Covers that overlap each other
+        let parts = subtract(
+            subtract(vec![player], (0.0, 0.0, 60.0, 100.0)),
+            (40.0, 0.0, 100.0, 100.0),
+        );
+        assert!(parts.is_empty());
+        assert_eq!(
+            subtract(vec![player], (200.0, 0.0, 300.0, 100.0)),
+            vec![player]
+        );
+    }
+
+    #[test]
+    fn animations_fit_their_box() {
+        let transform = fit((100.0, 50.0), (200.0, 200.0));
+        assert_eq!(transform * Point::new(0.0, 0.0), Point::new(0.0, 50.0));
+        assert_eq!(
+            transform * Point::new(100.0, 50.0),
+            Point::new(200.0, 150.0)
+        );
+    }
+}
@@ -1,18 +1,71 @@
 //! Shell module - window management and event loop.
 
+pub mod animated_styles;
+pub mod autostart;
+pub mod cascade;
+pub mod computed;
+pub mod crash;
+pub mod css_animations;
+pub mod deep_link;
 pub mod devtools;
 pub mod devtools_overlay;
+pub mod drag;
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+pub mod embed;
+pub mod event_log;
+pub mod find_bar;
+pub mod flip;
+pub mod focus;
 #[cfg(feature = "hot-reload")]
 pub mod hot_reload;
+#[cfg(feature = "hot-reload")]
+pub mod hot_restart;
+pub mod keyboard;
+pub mod live_regions;
+#[cfg(feature = "lottie")]
+pub mod lottie;
+pub mod motion;
+pub mod paint_flash;
+pub mod power;
+pub mod relayout;
+pub mod remeasure;
+pub mod rsx_source;
+pub mod rtl;
 pub mod runtime;
+pub mod scroll;
+pub mod scrollbars;
+pub mod selection;
+pub mod single_instance;
+pub mod sticky;
+pub mod stylesheets;
+#[cfg(test)]
+mod test_util;
+pub mod text_effects;
+pub mod text_input;
+pub mod text_rewrite;
+pub mod touch;
+pub mod transitions;
 pub mod transparent_renderer;
+pub mod visibility;
+pub mod wheel;
 pub mod window_manager;
+pub mod zoom;
 
+pub use crash::set_crash_panel_styles;
+pub use deep_link::register_protocol;
 pub use devtools::{DevToolsPanel, DevToolsState};
 pub use devtools_overlay::render_overlay;
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+pub use embed::{attach_to_parent, EmbeddedApp};
 #[cfg(feature = "hot-reload")]
 pub use hot_reload::{HotReloadConfig, HotReloader};
-pub use runtime::{run, RinchEvent, Runtime};
+pub use power::{idle_time, power};
+#[cfg(target_os = "android")]
+pub use runtime::run_android;
+pub use runtime::{run, run_single_instance, RinchEvent, Runtime};
 #[cfg(feature = "hot-reload")]
-pub use runtime::run_with_hot_reload;
+pub use runtime::{run_with_hot_reload, run_with_hot_reload_config};
+pub use stylesheets::{add_user_agent_stylesheet, set_user_agent_styles, UserAgentStyles};
 pub use window_manager::{ManagedWindow, WindowManager};
+#[cfg(target_os = "android")]
+pub use winit::platform::android::activity::AndroidApp;
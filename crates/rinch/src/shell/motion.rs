@@ -0,0 +1,97 @@
+//! The OS reduced-motion setting.
+//!
+//! winit doesn't report it, so rinch asks the platform's settings tool: the
+//! GNOME `enable-animations` key on Linux and the BSDs, Accessibility's
+//! "Reduce motion" on macOS, and "Animation effects" (which turns off window
+//! animations) on Windows. The runtime checks at startup and whenever a
+//! window gains focus, since the user changes the setting in another app.
+//! `RINCH_REDUCED_MOTION=1` (or `0`) overrides the check, for testing.
+
+use std::process::Command;
+
+/// Whether the OS asks apps to reduce motion. `false` if it can't be found
+/// out.
+pub(crate) fn system_prefers_reduced_motion() -> bool {
+    if let Ok(value) = std::env::var("RINCH_REDUCED_MOTION") {
+        return !matches!(value.trim(), "" | "0" | "false");
+    }
+    query_system()
+}
+
+/// Run a command and return its output, if it succeeds.
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // Don't flash a console window
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn query_system() -> bool {
+    output(
+        "defaults",
+        &["read", "com.apple.universalaccess", "reduceMotion"],
+    )
+    .is_some_and(|value| value.trim() == "1")
+}
+
+#[cfg(windows)]
+fn query_system() -> bool {
+    output(
+        "reg",
+        &[
+            "query",
+            r"HKCU\Control Panel\Desktop\WindowMetrics",
+            "/v",
+            "MinAnimate",
+        ],
+    )
+    .is_some_and(|value| reg_value(&value) == Some("0"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn query_system() -> bool {
+    output(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "enable-animations"],
+    )
+    .is_some_and(|value| value.trim() == "false")
+}
+
+#[cfg(not(any(unix, windows)))]
+fn query_system() -> bool {
+    false
+}
+
+/// The data of the value in `reg query` output, such as `0` from
+/// `    MinAnimate    REG_SZ    0`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn reg_value(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.split_once("REG_SZ"))
+        .map(|(_, value)| value.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_reg_query_output() {
+        let output = "\r\nHKEY_CURRENT_USER\\Control Panel\\Desktop\\WindowMetrics\r\n    \
+                      MinAnimate    REG_SZ    0\r\n\r\n";
+        assert_eq!(reg_value(output), Some("0"));
+        assert_eq!(reg_value("ERROR: not found"), None);
+    }
+}
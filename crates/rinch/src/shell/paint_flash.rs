@@ -0,0 +1,303 @@
+//! Re-render highlighting ("paint flashing") for rinch applications.
+//!
+//! A signal change re-runs the app function and, if the HTML differs,
+//! rebuilds the whole document; there are no per-element effects, and the
+//! scene is re-encoded in full every frame. What over-invalidation looks
+//! like here is a re-render that changes little or nothing. With paint
+//! flashing on (Alt+R), every re-render flashes the window's edge, the
+//! elements whose tag, attributes or text actually changed flash inside it,
+//! and a badge in the top right counts re-renders (orange), re-renders that
+//! changed nothing (grey) and the elements the last one changed (green).
+//!
+//! Elements are matched between renders by their position in the tree, so
+//! inserting an element also flashes the siblings after it.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::kurbo::{Affine, Rect, RoundedRect, Stroke, Vec2};
+use peniko::{Color, Fill};
+
+use super::scroll::border_box_origin;
+
+/// How long a flash takes to fade out.
+const FLASH_DURATION: Duration = Duration::from_millis(600);
+
+/// Window edge flashed on every re-render.
+const RENDER_COLOR: Color = Color::from_rgba8(0xff, 0x98, 0x00, 0xff);
+/// Elements a re-render changed.
+const CHANGED_COLOR: Color = Color::from_rgba8(0x4c, 0xaf, 0x50, 0xff);
+/// Re-renders that changed nothing.
+const WASTED_COLOR: Color = Color::from_rgba8(0x9e, 0x9e, 0x9e, 0xff);
+/// Counter badge background.
+const BADGE_COLOR: Color = Color::from_rgba8(0x1e, 0x1e, 0x1e, 0xd0);
+
+/// Digit cell size and segment thickness, in logical pixels.
+const DIGIT_WIDTH: f64 = 6.0;
+const DIGIT_HEIGHT: f64 = 10.0;
+const SEGMENT: f64 = 1.5;
+
+/// The elements of a document, keyed by their position in the tree (child
+/// indices among elements from the root), with their node ID and a hash of
+/// their tag, attributes and own text.
+pub(crate) struct Snapshot(HashMap<Vec<usize>, (usize, u64)>);
+
+impl Snapshot {
+    /// Record a document's elements.
+    pub(crate) fn take(doc: &BaseDocument) -> Self {
+        let mut elements = HashMap::new();
+        let mut stack = vec![(0, Vec::new())];
+        while let Some((node_id, path)) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            if let Some(element) = node.element_data() {
+                let mut hasher = DefaultHasher::new();
+                element.name.local.as_ref().hash(&mut hasher);
+                for attr in element.attrs() {
+                    attr.name.local.as_ref().hash(&mut hasher);
+                    attr.value.hash(&mut hasher);
+                }
+                for child in node.children.iter().filter_map(|&id| doc.get_node(id)) {
+                    if let Some(text) = child.text_data() {
+                        text.content.hash(&mut hasher);
+                    }
+                }
+                elements.insert(path.clone(), (node_id, hasher.finish()));
+            }
+            let children = node.children.iter().copied().filter(|&id| {
+                doc.get_node(id)
+                    .is_some_and(|child| child.element_data().is_some())
+            });
+            for (index, child) in children.enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                stack.push((child, child_path));
+            }
+        }
+        Self(elements)
+    }
+
+    /// Node IDs, in `after`, of the elements that are new or different.
+    fn changed(&self, after: &Snapshot) -> Vec<usize> {
+        let mut changed: Vec<_> = after
+            .0
+            .iter()
+            .filter(|(path, (_, hash))| self.0.get(*path).is_none_or(|(_, before)| before != hash))
+            .map(|(_, (node_id, _))| *node_id)
+            .collect();
+        changed.sort_unstable();
+        changed
+    }
+}
+
+/// Paint flashing state for a window.
+#[derive(Debug, Default)]
+pub(crate) struct PaintFlash {
+    /// Whether re-renders are highlighted.
+    pub enabled: bool,
+    /// When the window last re-rendered.
+    rendered: Option<Instant>,
+    /// Elements the last re-render changed.
+    changed: Vec<usize>,
+    /// Re-renders since paint flashing was turned on.
+    renders: usize,
+    /// Re-renders that changed nothing.
+    wasted: usize,
+}
+
+impl PaintFlash {
+    /// Turn paint flashing on or off, resetting the counters.
+    pub(crate) fn toggle(&mut self) {
+        *self = Self {
+            enabled: !self.enabled,
+            ..Self::default()
+        };
+    }
+
+    /// Snapshot `doc` before a re-render, if paint flashing is on.
+    pub(crate) fn before_render(&self, doc: &BaseDocument) -> Option<Snapshot> {
+        self.enabled.then(|| Snapshot::take(doc))
+    }
+
+    /// Note a re-render, comparing the document with its snapshot from
+    /// before.
+    pub(crate) fn rendered(&mut self, before: Option<Snapshot>, doc: &BaseDocument) {
+        let Some(before) = before else {
+            return;
+        };
+        self.changed = before.changed(&Snapshot::take(doc));
+        self.rendered = Some(Instant::now());
+        self.renders += 1;
+        if self.changed.is_empty() {
+            self.wasted += 1;
+        }
+    }
+
+    /// Forget node IDs from a document that was rebuilt without a
+    /// re-render.
+    pub(crate) fn clear(&mut self) {
+        self.changed.clear();
+    }
+
+    /// Whether a flash is still fading, needing more frames.
+    pub(crate) fn is_active(&self, now: Instant) -> bool {
+        self.enabled && self.rendered.is_some_and(|at| now - at < FLASH_DURATION)
+    }
+
+    /// Paint the flashes and the counter badge.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        if !self.enabled {
+            return;
+        }
+        let (width, height) = doc.viewport().window_size;
+        let (width, height) = (width as f64 / scale, height as f64 / scale);
+        let transform = Affine::scale(scale);
+
+        let fade = self
+            .rendered
+            .map(|at| 1.0 - at.elapsed().as_secs_f64() / FLASH_DURATION.as_secs_f64())
+            .unwrap_or(0.0);
+        if fade > 0.0 {
+            let alpha = fade as f32;
+            let edge = Stroke::new(4.0);
+            scene.stroke(
+                &edge,
+                transform,
+                RENDER_COLOR.multiply_alpha(alpha),
+                None,
+                &Rect::new(0.0, 0.0, width, height).inset(-2.0),
+            );
+            let outline = Stroke::new(1.0 / scale);
+            for node in self.changed.iter().filter_map(|&id| doc.get_node(id)) {
+                let (x, y) = border_box_origin(doc, node.id);
+                let size = node.final_layout.size;
+                let rect = Rect::new(x, y, x + size.width as f64, y + size.height as f64);
+                scene.fill(
+                    Fill::NonZero,
+                    transform,
+                    CHANGED_COLOR.multiply_alpha(alpha * 0.25),
+                    None,
+                    &rect,
+                );
+                scene.stroke(
+                    &outline,
+                    transform,
+                    CHANGED_COLOR.multiply_alpha(alpha),
+                    None,
+                    &rect.inset(-0.5 / scale),
+                );
+            }
+        }
+
+        self.paint_badge(scene, transform, width);
+    }
+
+    /// Paint the counters in the top right corner.
+    fn paint_badge(&self, scene: &mut impl PaintScene, transform: Affine, width: f64) {
+        let counters = [
+            (RENDER_COLOR, self.renders),
+            (WASTED_COLOR, self.wasted),
+            (CHANGED_COLOR, self.changed.len()),
+        ];
+        let counter_width = |count: usize| {
+            DIGIT_HEIGHT + 3.0 + count.to_string().len() as f64 * (DIGIT_WIDTH + 2.0)
+        };
+        let badge_width = counters
+            .iter()
+            .map(|&(_, count)| counter_width(count))
+            .sum::<f64>()
+            + 6.0 * 2.0
+            + 8.0;
+        let badge = Rect::new(
+            width - badge_width - 8.0,
+            8.0,
+            width - 8.0,
+            8.0 + DIGIT_HEIGHT + 8.0,
+        );
+        scene.fill(
+            Fill::NonZero,
+            transform,
+            BADGE_COLOR,
+            None,
+            &RoundedRect::from_rect(badge, 4.0),
+        );
+
+        let mut x = badge.x0 + 4.0;
+        let y = badge.y0 + 4.0;
+        for (color, count) in counters {
+            let swatch = Rect::new(x, y, x + DIGIT_HEIGHT, y + DIGIT_HEIGHT);
+            scene.fill(Fill::NonZero, transform, color, None, &swatch);
+            let mut digit_x = x + DIGIT_HEIGHT + 3.0;
+            for digit in count.to_string().bytes().map(|b| b - b'0') {
+                for segment in digit_segments(digit, digit_x, y) {
+                    scene.fill(Fill::NonZero, transform, Color::WHITE, None, &segment);
+                }
+                digit_x += DIGIT_WIDTH + 2.0;
+            }
+            x += counter_width(count) + 6.0;
+        }
+    }
+}
+
+/// The lit segments of a seven-segment `digit` with its top left at
+/// `(x, y)`.
+fn digit_segments(digit: u8, x: f64, y: f64) -> impl Iterator<Item = Rect> {
+    // Segments a to g: top, top right, bottom right, bottom, bottom left,
+    // top left, middle
+    const LIT: [u8; 10] = [
+        0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+        0b1111111, 0b1101111,
+    ];
+    let (w, h, t) = (DIGIT_WIDTH, DIGIT_HEIGHT, SEGMENT);
+    let mid = (h - t) / 2.0;
+    let segments = [
+        Rect::new(0.0, 0.0, w, t),
+        Rect::new(w - t, 0.0, w, mid + t),
+        Rect::new(w - t, mid, w, h),
+        Rect::new(0.0, h - t, w, h),
+        Rect::new(0.0, mid, t, h),
+        Rect::new(0.0, 0.0, t, mid + t),
+        Rect::new(0.0, mid, w, mid + t),
+    ];
+    let lit = LIT.get(digit as usize).copied().unwrap_or(0);
+    segments
+        .into_iter()
+        .enumerate()
+        .filter(move |(i, _)| lit & (1 << i) != 0)
+        .map(move |(_, rect)| rect + Vec2::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(elements: &[(&[usize], usize, u64)]) -> Snapshot {
+        Snapshot(
+            elements
+                .iter()
+                .map(|&(path, node_id, hash)| (path.to_vec(), (node_id, hash)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn only_changed_elements_flash() {
+        let before = snapshot(&[(&[], 1, 10), (&[0], 2, 20), (&[1], 3, 30)]);
+        let after = snapshot(&[(&[], 7, 10), (&[0], 8, 21), (&[1], 9, 30), (&[2], 10, 40)]);
+        assert_eq!(before.changed(&after), [8, 10]);
+        assert!(after.changed(&after).is_empty());
+    }
+
+    #[test]
+    fn digits_light_their_segments() {
+        assert_eq!(digit_segments(8, 0.0, 0.0).count(), 7);
+        assert_eq!(digit_segments(1, 0.0, 0.0).count(), 2);
+        let one: Vec<_> = digit_segments(1, 10.0, 20.0).collect();
+        assert!(one.iter().all(|rect| rect.x1 == 10.0 + DIGIT_WIDTH));
+        assert_eq!(one[0].y0, 20.0);
+    }
+}
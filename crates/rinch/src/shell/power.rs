@@ -0,0 +1,213 @@
+//! The OS power source.
+//!
+//! winit doesn't report it, so a background thread polls the OS every
+//! [`POLL_INTERVAL`]: `/sys/class/power_supply` on Linux,
+//! `GetSystemPowerStatus` on Windows and `pmset -g batt` on macOS. Changes
+//! reach the app through `rinch_core::power`.
+
+use std::time::Duration;
+
+use rinch_core::power::PowerStatus;
+use winit::event_loop::EventLoopProxy;
+
+use super::runtime::RinchEvent;
+
+pub use rinch_core::power::{idle_time, power};
+
+/// How often the power source is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Check the power source now and every [`POLL_INTERVAL`] on a background
+/// thread, sending changes to the event loop.
+pub(crate) fn watch(proxy: EventLoopProxy<RinchEvent>) {
+    let spawned = std::thread::Builder::new()
+        .name("rinch-power".into())
+        .spawn(move || {
+            let mut last = None;
+            loop {
+                let status = query_system();
+                if last != Some(status) {
+                    last = Some(status);
+                    if proxy.send_event(RinchEvent::PowerChanged(status)).is_err() {
+                        return;
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Couldn't watch the power source: {e}");
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn query_system() -> PowerStatus {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return PowerStatus::default();
+    };
+    let supplies: Vec<Supply> = entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let read = |name: &str| {
+                std::fs::read_to_string(path.join(name))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+            Supply {
+                kind: read("type"),
+                online: read("online") == "1",
+                capacity: read("capacity").parse().ok(),
+                // Mice and keyboards report their own batteries
+                peripheral: read("scope") == "Device",
+            }
+        })
+        .collect();
+    linux_status(&supplies)
+}
+
+/// A power supply under `/sys/class/power_supply`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct Supply {
+    /// `Battery`, `Mains`, `USB`...
+    kind: String,
+    online: bool,
+    /// Percent charged, for batteries.
+    capacity: Option<f32>,
+    peripheral: bool,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn linux_status(supplies: &[Supply]) -> PowerStatus {
+    let supplies = supplies.iter().filter(|supply| !supply.peripheral);
+    let levels: Vec<f32> = supplies
+        .clone()
+        .filter(|supply| supply.kind == "Battery")
+        .filter_map(|supply| supply.capacity)
+        .collect();
+    let mut chargers = supplies
+        .filter(|supply| matches!(supply.kind.as_str(), "Mains" | "USB" | "USB_C" | "USB_PD"))
+        .peekable();
+    let battery_level = (!levels.is_empty())
+        .then(|| (levels.iter().sum::<f32>() / levels.len() as f32 / 100.0).clamp(0.0, 1.0));
+    let on_ac_power = if chargers.peek().is_some() {
+        chargers.any(|supply| supply.online)
+    } else {
+        battery_level.is_none()
+    };
+    PowerStatus {
+        battery_level,
+        on_ac_power,
+    }
+}
+
+#[cfg(windows)]
+fn query_system() -> PowerStatus {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    // SAFETY: the struct matches SYSTEM_POWER_STATUS, which the call fills in
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerStatus::default();
+    }
+    // 128 is "no system battery", 255 "unknown"
+    let has_battery = status.battery_flag & 128 == 0 && status.battery_flag != 255;
+    PowerStatus {
+        battery_level: (has_battery && status.battery_life_percent <= 100)
+            .then(|| f32::from(status.battery_life_percent) / 100.0),
+        on_ac_power: status.ac_line_status != 0,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn query_system() -> PowerStatus {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| pmset_status(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// The status from `pmset -g batt` output, such as
+/// `Now drawing from 'Battery Power'` and
+/// ` -InternalBattery-0 (id=123) 87%; discharging; 4:12 remaining`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn pmset_status(output: &str) -> PowerStatus {
+    let battery_level = output
+        .lines()
+        .find(|line| line.contains("InternalBattery"))
+        .and_then(|line| line.split_whitespace().find(|word| word.contains('%')))
+        .and_then(|word| word.split('%').next()?.parse::<f32>().ok())
+        .map(|percent| (percent / 100.0).clamp(0.0, 1.0));
+    PowerStatus {
+        battery_level,
+        on_ac_power: !output.contains("'Battery Power'"),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn query_system() -> PowerStatus {
+    PowerStatus::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_linux_power_supplies() {
+        let supply = |kind: &str, online, capacity, peripheral| Supply {
+            kind: kind.into(),
+            online,
+            capacity,
+            peripheral,
+        };
+        let laptop = [
+            supply("Mains", false, None, false),
+            supply("Battery", false, Some(80.0), false),
+            supply("Battery", false, Some(60.0), false),
+            supply("Battery", false, Some(5.0), true),
+        ];
+        assert_eq!(
+            linux_status(&laptop),
+            PowerStatus {
+                battery_level: Some(0.7),
+                on_ac_power: false
+            }
+        );
+        assert_eq!(linux_status(&[]), PowerStatus::default());
+    }
+
+    #[test]
+    fn reads_pmset_output() {
+        let output = "Now drawing from 'Battery Power'\n \
+                      -InternalBattery-0 (id=4653155)\t87%; discharging; 4:12 remaining present: true\n";
+        assert_eq!(
+            pmset_status(output),
+            PowerStatus {
+                battery_level: Some(0.87),
+                on_ac_power: false
+            }
+        );
+        assert_eq!(
+            pmset_status("Now drawing from 'AC Power'\n"),
+            PowerStatus::default()
+        );
+    }
+}
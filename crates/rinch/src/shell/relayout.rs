@@ -0,0 +1,92 @@
+//! Laying windows' content out again, off screen.
+//!
+//! Printing and SVG export work from a node ref, which may be in any
+//! window, and lay its window's content out at a size of their own. The
+//! window manager keeps each window's latest props and HTML here for them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use blitz_dom::{Document, DocumentConfig};
+use blitz_traits::shell::{ColorScheme, Viewport};
+use rinch_core::element::WindowProps;
+use rinch_core::node_ref::NODE_REF_ATTR;
+use winit::window::WindowId;
+
+use super::css_animations::CssAnimations;
+use super::rtl::MirroredRows;
+use super::stylesheets::{inline_local_stylesheets, split_styles, user_agent_stylesheets};
+use super::text_effects::TextEffects;
+use super::window_manager::build_document;
+
+use crate::fonts::font_context;
+
+thread_local! {
+    /// Each window's latest props and HTML.
+    static WINDOWS: RefCell<HashMap<WindowId, (WindowProps, String)>> = RefCell::new(HashMap::new());
+}
+
+/// Keep a window's latest content.
+pub(crate) fn record_window(window_id: WindowId, props: &WindowProps, html: &str) {
+    WINDOWS.with(|windows| {
+        windows
+            .borrow_mut()
+            .insert(window_id, (props.clone(), html.to_string()));
+    });
+}
+
+/// Forget a closed window.
+pub(crate) fn forget_window(window_id: WindowId) {
+    WINDOWS.with(|windows| windows.borrow_mut().remove(&window_id));
+}
+
+/// The props and HTML, with local stylesheets inlined, of the window
+/// showing the element with `node_ref`.
+pub(crate) fn window_with_ref(node_ref: u64) -> Option<(WindowProps, String)> {
+    let marker = format!("{NODE_REF_ATTR}=\"{node_ref}\"");
+    let (props, html) = WINDOWS.with(|windows| {
+        windows
+            .borrow()
+            .values()
+            .find(|(_, html)| html.contains(&marker))
+            .cloned()
+    })?;
+    Some((props, inline_local_stylesheets(&html)))
+}
+
+/// Lay out `html` with a window's styles in a viewport `width` by `height`
+/// CSS pixels, rendered at `scale` device pixels per CSS pixel.
+pub(crate) fn layout(
+    props: &WindowProps,
+    html: &str,
+    width: f64,
+    height: f64,
+    scale: f64,
+) -> (Box<dyn Document>, TextEffects) {
+    let viewport = Viewport::new(
+        (width * scale).round() as u32,
+        (height * scale).round() as u32,
+        scale as f32,
+        ColorScheme::Light,
+    );
+    let config = DocumentConfig {
+        viewport: Some(viewport),
+        ua_stylesheets: Some(user_agent_stylesheets(
+            blitz_dom::DEFAULT_CSS,
+            props.reset,
+            props.rtl,
+        )),
+        font_ctx: Some(font_context(&split_styles(html).1)),
+        ..Default::default()
+    };
+    // Nothing laid out here is scrolled by hand, so no scrollbars
+    let (doc, text_effects, ..) = build_document(
+        html,
+        config,
+        false,
+        0.0,
+        &mut CssAnimations::default(),
+        &mut MirroredRows::default(),
+    );
+    (doc, text_effects)
+}
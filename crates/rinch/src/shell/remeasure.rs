@@ -0,0 +1,144 @@
+//! Measuring revealed content again.
+//!
+//! Layout caches each box's measurements, and content laid out while
+//! `display: none` or `visibility: hidden` hid it can keep stale ones, such
+//! as zero-height text, once it's shown. A re-render builds a fresh
+//! document, but `:hover`, `:focus` and in-place stylesheet changes restyle
+//! the live one, so a hover menu or a focus popover could come out empty.
+//!
+//! So when one of those changes shows an element its styles hid, rinch
+//! clears the cached measurements of the element, everything inside it and
+//! the elements around it, and the next layout measures them afresh. Apps
+//! can ask for the same with `request_remeasure`.
+
+use std::collections::HashSet;
+
+use blitz_dom::BaseDocument;
+
+use super::computed::computed_values;
+
+/// The elements whose styles hide them.
+#[derive(Debug, Default)]
+pub(crate) struct Revealed {
+    hidden: HashSet<usize>,
+}
+
+impl Revealed {
+    /// Note which elements of a freshly built document are hidden. Call
+    /// after styling it.
+    pub(crate) fn rebuilt(&mut self, doc: &BaseDocument) {
+        self.hidden = hidden_elements(doc);
+    }
+
+    /// Remeasure the elements that were hidden and now aren't, after the
+    /// hovered or focused element or the stylesheets changed and the
+    /// document was restyled. Returns whether any were; they're measured
+    /// at the next layout.
+    pub(crate) fn restyled(&mut self, doc: &mut BaseDocument) -> bool {
+        let shown: Vec<usize> = self
+            .hidden
+            .iter()
+            .copied()
+            .filter(|&node_id| hides(&display_values(doc, node_id)) == Some(false))
+            .collect();
+        self.hidden = hidden_elements(doc);
+        for &node_id in &shown {
+            remeasure(doc, node_id);
+        }
+        !shown.is_empty()
+    }
+}
+
+/// The elements hidden by their own `display` or `visibility`.
+fn hidden_elements(doc: &BaseDocument) -> HashSet<usize> {
+    let mut hidden = HashSet::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter());
+        if node.element_data().is_some() && hides(&display_values(doc, node_id)) == Some(true) {
+            hidden.insert(node_id);
+        }
+    }
+    hidden
+}
+
+fn display_values(doc: &BaseDocument, node_id: usize) -> Vec<(String, String)> {
+    computed_values(doc, node_id, &["display", "visibility"])
+}
+
+/// Whether an element's values hide it, if they include `display` or
+/// `visibility`.
+fn hides(values: &[(String, String)]) -> Option<bool> {
+    let value = |property: &str| {
+        values
+            .iter()
+            .rev()
+            .find(|(p, _)| p == property)
+            .map(|(_, v)| v.trim())
+    };
+    let display = value("display");
+    let visibility = value("visibility");
+    if display.is_none() && visibility.is_none() {
+        return None;
+    }
+    Some(display == Some("none") || matches!(visibility, Some("hidden" | "collapse")))
+}
+
+/// Clear the cached measurements of `node_id`, everything inside it and the
+/// elements around it, so the next layout measures them again.
+pub(crate) fn remeasure(doc: &mut BaseDocument, node_id: usize) {
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = doc.get_node_mut(id) else {
+            continue;
+        };
+        node.cache.clear();
+        stack.extend(node.children.iter());
+        // Anonymous blocks wrapping inline content aren't children
+        stack.extend(node.layout_children.borrow().iter().flatten());
+    }
+
+    // Sizes of the boxes around it depend on its size
+    let mut current = doc.get_node(node_id).and_then(|node| node.parent);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node_mut(id) else {
+            break;
+        };
+        node.cache.clear();
+        current = node.parent;
+        let anonymous: Vec<usize> = node
+            .layout_children
+            .borrow()
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|child| !node.children.contains(child))
+            .collect();
+        for child in anonymous {
+            if let Some(child) = doc.get_node_mut(child) {
+                child.cache.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn display_none_and_visibility_hidden_hide() {
+        assert_eq!(hides(&values(&[("display", "none")])), Some(true));
+        assert_eq!(hides(&values(&[("visibility", "hidden")])), Some(true));
+        assert_eq!(
+            hides(&values(&[("display", "none"), ("display", "block")])),
+            Some(false)
+        );
+        assert_eq!(hides(&values(&[("visibility", "visible")])), Some(false));
+        assert_eq!(hides(&values(&[("color", "red")])), None);
+    }
+}
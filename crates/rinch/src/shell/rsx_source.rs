@@ -0,0 +1,123 @@
+//! Finding the `rsx!` code that made an element.
+//!
+//! In debug builds `rsx!` marks each element with `data-rsx-src`, its
+//! `file:line:column`, and the elements at the top of each `rsx!` with
+//! `data-rsx-block`, the location of the `rsx!` call. The nearest block
+//! above an element is the function that rendered it, as close to an
+//! owning component as rsx has.
+
+use std::process::Command;
+
+use blitz_dom::BaseDocument;
+
+/// Attribute with an element's location in its `rsx!`.
+const SOURCE_ATTR: &str = "data-rsx-src";
+/// Attribute with the location of the `rsx!` an element is at the top of.
+const BLOCK_ATTR: &str = "data-rsx-block";
+
+/// Environment variable naming the command that opens a source location,
+/// such as `code -g` or `zed`. The location is added as its last argument.
+const EDITOR_VAR: &str = "RINCH_EDITOR";
+
+/// Where in the source an element came from, as `file:line:column`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsxSource {
+    /// The element itself, or the nearest element above it from `rsx!`.
+    pub element: String,
+    /// The `rsx!` call that rendered it, if known.
+    pub block: Option<String>,
+}
+
+impl std::fmt::Display for RsxSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.element)?;
+        if let Some(block) = &self.block {
+            write!(f, " (in rsx! at {block})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The source of `node_id`, or of the nearest element above it with one.
+/// `None` for release builds, or HTML that didn't come from `rsx!`.
+pub(crate) fn find_source(doc: &BaseDocument, node_id: usize) -> Option<RsxSource> {
+    let mut element = None;
+    let mut current = Some(node_id);
+    while let Some(node) = current.and_then(|id| doc.get_node(id)) {
+        current = node.parent;
+        let Some(data) = node.element_data() else {
+            continue;
+        };
+        let value = |name: &str| {
+            data.attrs()
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == name)
+                .map(|attr| attr.value.to_string())
+        };
+        if element.is_none() {
+            element = value(SOURCE_ATTR);
+        }
+        if element.is_some()
+            && let Some(block) = value(BLOCK_ATTR)
+        {
+            return Some(RsxSource {
+                element: element?,
+                block: Some(block),
+            });
+        }
+    }
+    element.map(|element| RsxSource {
+        element,
+        block: None,
+    })
+}
+
+/// Log where `source` is, and open it with the `RINCH_EDITOR` command if
+/// one is set.
+pub(crate) fn jump_to_source(source: &RsxSource) {
+    tracing::info!("Element from {source}");
+    let Some(editor) = std::env::var_os(EDITOR_VAR) else {
+        return;
+    };
+    let Some(mut command) = editor_command(&editor.to_string_lossy(), &source.element) else {
+        return;
+    };
+    if let Err(e) = command.spawn() {
+        tracing::warn!("Couldn't open {} with {EDITOR_VAR}: {e}", source.element);
+    }
+}
+
+/// The command opening `location` with `editor`, a program and its
+/// leading arguments separated by spaces.
+fn editor_command(editor: &str, location: &str) -> Option<Command> {
+    let mut parts = editor.split_whitespace();
+    let mut command = Command::new(parts.next()?);
+    command.args(parts).arg(location);
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editors_get_the_location_last() {
+        let command = editor_command("code -g", "src/main.rs:12:9").unwrap();
+        assert_eq!(command.get_program(), "code");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["-g", "src/main.rs:12:9"]);
+        assert!(editor_command("  ", "src/main.rs:1:1").is_none());
+    }
+
+    #[test]
+    fn sources_name_their_block() {
+        let source = RsxSource {
+            element: "src/main.rs:12:9".into(),
+            block: Some("src/main.rs:10:5".into()),
+        };
+        assert_eq!(
+            source.to_string(),
+            "src/main.rs:12:9 (in rsx! at src/main.rs:10:5)"
+        );
+    }
+}
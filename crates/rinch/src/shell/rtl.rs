@@ -0,0 +1,148 @@
+//! Right-to-left layout the style engine doesn't do itself.
+//!
+//! Flex rows run along the inline axis, so in right-to-left content they
+//! start on the right, but the layout engine always starts them on the
+//! left. After a document is styled, rinch flips the `flex-direction` of
+//! right-to-left flex rows in their inline style, `row` to `row-reverse`
+//! and back, so their first item is on the right and
+//! `justify-content: flex-start` packs them to the right, as in browsers.
+//! [`MirroredRows`] remembers the `style` attribute's own value, and puts it
+//! back before the document is restyled for a hover or focus change or new
+//! stylesheets, so rows are flipped again from the new styles.
+//!
+//! An element's direction is its computed `direction`, which follows `dir`
+//! attributes, the app's rules and the window's `rtl`.
+
+use blitz_dom::BaseDocument;
+
+use super::computed::{computed_values, inline_value};
+
+/// The flex rows flipped in a document, with each one's own
+/// `flex-direction` from its `style` attribute.
+#[derive(Default)]
+pub(crate) struct MirroredRows {
+    rows: Vec<(usize, Option<String>)>,
+}
+
+impl MirroredRows {
+    /// Flip the flex rows in right-to-left content, once the document is
+    /// styled, replacing any rows of a document it replaced. Returns
+    /// whether any were flipped.
+    pub(crate) fn mirror(&mut self, doc: &mut BaseDocument) -> bool {
+        let mut flips = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter());
+            if node.element_data().is_none() {
+                continue;
+            }
+            let values = computed_values(doc, node_id, &["direction", "display", "flex-direction"]);
+            let value = |property: &str| {
+                values
+                    .iter()
+                    .find(|(name, _)| name == property)
+                    .map(|(_, value)| value.as_str())
+            };
+            if value("direction") == Some("rtl")
+                && let Some(flipped) = mirrored(value("display"), value("flex-direction"))
+            {
+                flips.push((node_id, flipped));
+            }
+        }
+
+        self.rows = flips
+            .iter()
+            .map(|&(node_id, _)| (node_id, inline_value(doc, node_id, "flex-direction")))
+            .collect();
+        let mut mutator = doc.mutate();
+        for &(node_id, direction) in &flips {
+            mutator.set_style_property(node_id, "flex-direction", direction);
+        }
+        !flips.is_empty()
+    }
+
+    /// Put back the rows' own `flex-direction`, before the document is
+    /// restyled.
+    pub(crate) fn unmirror(&mut self, doc: &mut BaseDocument) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let mut mutator = doc.mutate();
+        for (node_id, inline) in self.rows.drain(..) {
+            match inline {
+                Some(value) => mutator.set_style_property(node_id, "flex-direction", &value),
+                None => mutator.remove_style_property(node_id, "flex-direction"),
+            }
+        }
+    }
+}
+
+/// The `flex-direction` mirroring an element's layout, if it's a flex
+/// container laid out in a row.
+fn mirrored(display: Option<&str>, flex_direction: Option<&str>) -> Option<&'static str> {
+    if !matches!(display, Some("flex" | "inline-flex")) {
+        return None;
+    }
+    match flex_direction.unwrap_or("row") {
+        "row" => Some("row-reverse"),
+        "row-reverse" => Some("row"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blitz_dom::{Document, DocumentConfig};
+
+    use super::*;
+    use crate::shell::computed::computed_value;
+    use crate::shell::css_animations::CssAnimations;
+    use crate::shell::window_manager::build_document;
+
+    #[test]
+    fn rows_get_their_own_direction_back_before_restyling() {
+        let mut rows = MirroredRows::default();
+        let (doc, ..) = build_document(
+            r#"<div dir="rtl">
+                <div style="display: flex; flex-direction: row; gap: 4px"></div>
+                <div style="display: flex"></div>
+            </div>"#,
+            DocumentConfig::default(),
+            false,
+            0.0,
+            &mut CssAnimations::default(),
+            &mut rows,
+        );
+        let mut inner = doc.inner_mut();
+        let ids: Vec<_> = rows.rows.iter().map(|&(node_id, _)| node_id).collect();
+        let directions = |doc: &BaseDocument| -> Vec<_> {
+            ids.iter()
+                .map(|&id| computed_value(doc, id, "flex-direction").unwrap())
+                .collect()
+        };
+        assert_eq!(directions(&inner), ["row-reverse", "row-reverse"]);
+
+        rows.unmirror(&mut inner);
+        inner.resolve(0.0);
+        assert_eq!(directions(&inner), ["row", "row"]);
+        assert!(rows.mirror(&mut inner));
+        inner.resolve(0.0);
+        assert_eq!(directions(&inner), ["row-reverse", "row-reverse"]);
+    }
+
+    #[test]
+    fn only_flex_rows_are_mirrored() {
+        assert_eq!(mirrored(Some("flex"), None), Some("row-reverse"));
+        assert_eq!(
+            mirrored(Some("inline-flex"), Some("row-reverse")),
+            Some("row")
+        );
+        assert_eq!(mirrored(Some("flex"), Some("row")), Some("row-reverse"));
+        assert_eq!(mirrored(Some("flex"), Some("column")), None);
+        assert_eq!(mirrored(Some("block"), None), None);
+        assert_eq!(mirrored(None, Some("row")), None);
+    }
+}
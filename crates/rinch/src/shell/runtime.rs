@@ -2,18 +2,63 @@
 
 use crate::menu::MenuManager;
 use muda::MenuEvent;
-use rinch_core::element::{Element, WindowProps};
-use rinch_core::events::{clear_handlers, dispatch_event, EventHandlerId};
-use rinch_core::hooks::{begin_render, clear_hooks, end_render};
+use rinch_core::a11y::take_announcements;
+use rinch_core::animation::{
+    animation_frame_requested, prefers_reduced_motion, run_animation_frame,
+    set_system_reduced_motion, FRAME_INTERVAL,
+};
+use rinch_core::clock::{self, next_timer, run_timers};
+use rinch_core::deep_link::push_deep_link;
+use rinch_core::element::{children_to_html, Element, WindowProps};
+use rinch_core::events::{
+    clear_handlers, dispatch_clipboard_event, dispatch_drag_event, dispatch_event,
+    dispatch_input_event, dispatch_key_event, dispatch_scroll_event, ClipboardEvent, DragEvent,
+    EventHandlerId, KeyboardEvent, ScrollEvent,
+};
+use rinch_core::hooks::{
+    begin_render, clear_hooks, drop_root, end_render, render_root, run_effects,
+};
+use rinch_core::i18n::take_missing_translations;
+use rinch_core::lifecycle::{run_before_quit, run_resume, run_session_ending, run_suspend};
+use rinch_core::node_ref::{
+    take_animated_styles, take_focus_requests, take_remeasure_requests, take_scroll_requests,
+    update_node_ref_rect, update_node_ref_scroll, update_node_ref_size, update_node_ref_visibility,
+    LayoutRect, Visibility,
+};
+use rinch_core::power::{record_user_input, set_power_status, PowerStatus};
+use rinch_core::reactive::{reactive_graph, reactive_graph_version, untracked};
+use rinch_core::task::{poll_tasks, set_wake_handler};
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
+use winit::event_loop::{
+    ActiveEventLoop, ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy,
+};
 use winit::window::WindowId;
 
+use super::cascade::MatchedRule;
+use super::crash::{catch, crash_panel_html, Crash, CRASH_ACTION_ATTR};
+use super::devtools_overlay::render_reactive_graph;
+use super::drag::ElementDrag;
+use super::event_log::{event_path, EventFilter, EventKind, EventLog};
+use super::find_bar::{FIND_BAR_HEIGHT, FIND_BAR_WIDTH};
+use super::rsx_source::{find_source, jump_to_source};
+use super::touch::TouchInput;
 use super::window_manager::WindowManager;
 
+/// Attribute carrying the node ID of each element in the DevTools DOM tree.
+const DEVTOOLS_NODE_ATTR: &str = "data-devtools-node";
+/// Attribute naming the event log filter a DevTools button picks.
+const DEVTOOLS_FILTER_ATTR: &str = "data-devtools-filter";
+
+/// How often the DevTools window refreshes while the reactive graph changes.
+const DEVTOOLS_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Events used internally by rinch.
 #[derive(Debug, Clone)]
 pub enum RinchEvent {
@@ -23,19 +68,65 @@ pub enum RinchEvent {
     MenuEvent(muda::MenuId),
     /// Request a re-render of all windows.
     ReRender,
+    /// Stylesheets changed on disk - apply them without rebuilding documents.
+    ReloadStyles,
+    /// Asset files changed on disk - rebuild documents to read them again.
+    ReloadAssets,
+    /// The app was rebuilt - restart it with the new code, keeping its state.
+    HotRestart,
     /// An element was clicked (with handler ID and source window).
     ElementClicked { handler_id: EventHandlerId, window_id: WindowId },
+    /// A key was pressed or released (with the key handlers to call, in
+    /// order).
+    KeyInput {
+        window_id: WindowId,
+        calls: Vec<(EventHandlerId, KeyboardEvent)>,
+    },
+    /// Text was copied, cut or pasted (with the `oncopy`, `oncut` or
+    /// `onpaste` handlers to call).
+    ClipboardInput {
+        window_id: WindowId,
+        handlers: Vec<EventHandlerId>,
+        event: ClipboardEvent,
+    },
+    /// Focus moved (with the `onblur` and `onfocus` handlers to call).
+    FocusChanged {
+        window_id: WindowId,
+        handlers: Vec<EventHandlerId>,
+    },
+    /// A text field's value was edited or committed (with the handlers to
+    /// call with it).
+    FieldInput {
+        window_id: WindowId,
+        handlers: Vec<EventHandlerId>,
+        value: String,
+    },
+    /// A watched element scrolled, or was laid out for the first time (no handler).
+    ElementScrolled {
+        window_id: WindowId,
+        handler_id: Option<EventHandlerId>,
+        node_ref: Option<u64>,
+        event: ScrollEvent,
+    },
+    /// An element with a node ref was laid out at a new content-box size.
+    ElementResized { node_ref: u64, size: (f64, f64) },
+    /// An element whose node ref's rect the app reads moved or resized.
+    ElementMoved { node_ref: u64, rect: LayoutRect },
+    /// A node ref's watched visibility changed.
+    ElementVisibility {
+        node_ref: u64,
+        visibility: Visibility,
+    },
     /// Toggle the DevTools window.
     ToggleDevTools { source_window: WindowId },
     /// Update DevTools with hovered element info.
     UpdateDevToolsHover { element_info: Option<HoveredElementInfo> },
     /// A keyboard shortcut was pressed - check against menu shortcuts.
     KeyboardShortcut {
-        ctrl: bool,
-        meta: bool,
-        alt: bool,
-        shift: bool,
-        key: winit::keyboard::KeyCode,
+        window_id: WindowId,
+        press: crate::menu::KeyPress,
+        /// Whether a text field has focus.
+        typing: bool,
     },
     /// Process pending window requests (open/close).
     ProcessWindowRequests,
@@ -45,6 +136,39 @@ pub enum RinchEvent {
     ToggleMaximizeWindow { window_id: WindowId },
     /// Close a window (from window controls).
     CloseWindowControl { window_id: WindowId },
+    /// Process pending text searches.
+    ProcessSearchRequests,
+    /// Toggle the find bar for a window.
+    ToggleFindBar { source_window: WindowId },
+    /// Change a window's content scale.
+    ZoomWindow {
+        window_id: WindowId,
+        zoom: crate::windows::Zoom,
+    },
+    /// Another instance of a single-instance app was started, with these
+    /// arguments.
+    SecondInstance { args: Vec<String> },
+    /// A later launch was given a link in a registered URL scheme.
+    DeepLink(String),
+    /// Quit the app, if its before-quit handlers allow.
+    Quit,
+    /// Show (`Some(true)`), hide (`Some(false)`) or toggle (`None`) the main
+    /// window.
+    SetMainWindowVisible(Option<bool>),
+    /// A tray icon was clicked or one of its menu items chosen.
+    TrayEvent,
+    /// The computer's power source changed.
+    PowerChanged(PowerStatus),
+    /// A settings file was changed by something other than the app.
+    SettingsFileChanged,
+    /// A `use_fetch` request finished.
+    FetchFinished,
+    /// A `use_websocket` connection opened, dropped or received a message.
+    WebSocketEvent,
+    /// A `use_process` child wrote output or exited.
+    ProcessOutput,
+    /// A `spawn_local` task was woken.
+    PollTasks,
 }
 
 /// Information about a hovered element for DevTools display.
@@ -60,6 +184,13 @@ pub struct HoveredElementInfo {
     pub styles: Vec<(String, String)>,
     /// Layout information.
     pub layout: ElementLayout,
+    /// Rules matching the element, in cascade order (winning rules first).
+    pub matched_rules: Vec<MatchedRule>,
+    /// The computed value of each property the matched rules set, with
+    /// shorthands expanded, from the style engine.
+    pub computed_styles: Vec<(String, String)>,
+    /// Where in the app's `rsx!` the element was made (debug builds).
+    pub source: Option<super::rsx_source::RsxSource>,
 }
 
 /// Layout information for an element.
@@ -114,6 +245,11 @@ impl RenderContext {
         }
     }
 
+    /// Whether a re-render has been requested but hasn't happened yet.
+    fn render_pending(&self) -> bool {
+        self.inner.borrow().needs_render
+    }
+
     fn clear_render_flag(&self) {
         self.inner.borrow_mut().needs_render = false;
     }
@@ -147,16 +283,62 @@ pub struct Runtime {
     render_context: RenderContext,
     #[cfg(feature = "hot-reload")]
     hot_reloader: Option<super::hot_reload::HotReloader>,
+    /// State to hand to the rebuilt app once the event loop exits.
+    #[cfg(feature = "hot-reload")]
+    hot_restart: Option<super::hot_restart::SavedState>,
+    /// Windows as the process this one replaced left them, for the windows
+    /// the app declares again.
+    #[cfg(feature = "hot-reload")]
+    restored: super::hot_restart::SavedState,
     /// The DevTools window ID, if open.
     devtools_window: Option<WindowId>,
     /// The window being inspected by DevTools.
     devtools_target: Option<WindowId>,
     /// Current hovered element info for DevTools display.
     hovered_element: Option<HoveredElementInfo>,
+    /// The reactive graph and event log versions the DevTools window last
+    /// showed.
+    devtools_version: (u64, u64),
+    /// When the DevTools window was last refreshed for graph changes.
+    devtools_refreshed: Option<Instant>,
+    /// Events dispatched while DevTools is open.
+    event_log: EventLog,
+    /// The find bar window ID, if open.
+    find_bar_window: Option<WindowId>,
+    /// The window being searched by the find bar.
+    find_target: Option<WindowId>,
     /// Mapping from WindowHandle to winit WindowId for programmatic window management.
     window_handles: std::collections::HashMap<crate::windows::WindowHandle, WindowId>,
     /// Reverse mapping from winit WindowId to WindowHandle.
     window_ids_to_handles: std::collections::HashMap<WindowId, crate::windows::WindowHandle>,
+    /// Windows opened with a component, which re-render with the app.
+    component_windows: std::collections::HashMap<
+        WindowId,
+        (
+            crate::windows::WindowHandle,
+            crate::windows::WindowComponent,
+        ),
+    >,
+    /// When the last animation frame ran.
+    last_animation_frame: Option<Instant>,
+    /// Whether windows were last built for reduced motion.
+    reduced_motion: bool,
+    /// The first window created, focused when another instance starts.
+    main_window: Option<WindowId>,
+    /// Called with the arguments of later instances of a single-instance
+    /// app.
+    on_second_instance: Option<Box<dyn Fn(Vec<String>)>>,
+    /// Whether the OS has suspended the app.
+    suspended: bool,
+    /// Whether the app is quitting with its before-quit handlers' consent.
+    quitting: bool,
+    /// Windows showing the crash panel, with the panic each shows.
+    crashes: std::collections::HashMap<WindowId, Crash>,
+    /// Whether the crash panels are from a panic while rendering, which
+    /// every window shows.
+    render_crashed: bool,
+    /// The finger standing in for the mouse.
+    touch: TouchInput,
 }
 
 impl Runtime {
@@ -179,11 +361,74 @@ impl Runtime {
             render_context,
             #[cfg(feature = "hot-reload")]
             hot_reloader: None,
+            #[cfg(feature = "hot-reload")]
+            hot_restart: None,
+            #[cfg(feature = "hot-reload")]
+            restored: Default::default(),
             devtools_window: None,
             devtools_target: None,
             hovered_element: None,
+            devtools_version: (0, 0),
+            devtools_refreshed: None,
+            event_log: EventLog::default(),
+            find_bar_window: None,
+            find_target: None,
             window_handles: std::collections::HashMap::new(),
             window_ids_to_handles: std::collections::HashMap::new(),
+            component_windows: std::collections::HashMap::new(),
+            last_animation_frame: None,
+            reduced_motion: false,
+            main_window: None,
+            on_second_instance: None,
+            suspended: false,
+            quitting: false,
+            crashes: std::collections::HashMap::new(),
+            render_crashed: false,
+            touch: TouchInput::default(),
+        }
+    }
+
+    /// Rebuild windows, with or without the reduced-motion stylesheet, when
+    /// the user's preference (or the app's override) changes.
+    fn update_reduced_motion(&mut self) {
+        let reduced = untracked(prefers_reduced_motion);
+        if reduced != self.reduced_motion {
+            self.reduced_motion = reduced;
+            self.window_manager.rebuild_all();
+            // For apps that read the preference themselves
+            self.render_context.request_render();
+        }
+    }
+
+    /// Run animation frame callbacks when a frame is due, and wake up for
+    /// the next one while animations want more.
+    fn tick_animations(&mut self, event_loop: &ActiveEventLoop) {
+        if !animation_frame_requested() {
+            self.last_animation_frame = None;
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
+        let now = clock::now();
+        let due = self
+            .last_animation_frame
+            .map_or(now, |last| last + FRAME_INTERVAL);
+        if now >= due {
+            self.last_animation_frame = Some(now);
+            let changed = catch(|| run_animation_frame(now)).unwrap_or_else(|crash| {
+                self.show_crash_in_current_window(crash);
+                true
+            });
+            if changed {
+                self.render_context.request_render();
+            }
+        }
+
+        if animation_frame_requested() {
+            let next = self.last_animation_frame.unwrap_or(now) + FRAME_INTERVAL;
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next));
+        } else {
+            event_loop.set_control_flow(ControlFlow::Wait);
         }
     }
 
@@ -207,6 +452,35 @@ impl Runtime {
         }
     }
 
+    /// Save the app's state and quit, for `run_internal` to start the
+    /// rebuilt app with it. DevTools, the find bar and windows opened with
+    /// `open_window` aren't carried over.
+    #[cfg(feature = "hot-reload")]
+    fn hot_restart(&mut self, event_loop: &ActiveEventLoop) {
+        let windows = self
+            .window_manager
+            .windows_iter()
+            .filter(|(id, _)| {
+                Some(**id) != self.devtools_window
+                    && Some(**id) != self.find_bar_window
+                    && !self.window_ids_to_handles.contains_key(*id)
+            })
+            .map(|(_, window)| window.save_for_restart())
+            .collect();
+        tracing::info!("Hot reload: restarting with the rebuilt app");
+        self.quitting = true;
+        self.hot_restart = Some(super::hot_restart::SavedState {
+            hooks: rinch_core::hooks::save_hooks(),
+            windows,
+        });
+        event_loop.exit();
+    }
+
+    /// The app's first window.
+    pub(crate) fn main_window(&self) -> Option<&super::window_manager::ManagedWindow> {
+        self.main_window.and_then(|id| self.window_manager.get(id))
+    }
+
     /// Store the app function for re-rendering.
     fn set_app_fn<F: Fn() -> Element + 'static>(&mut self, app: F) {
         self.app_fn = Some(Box::new(app));
@@ -252,6 +526,13 @@ impl Runtime {
             ) {
                 Ok(id) => {
                     tracing::info!("Created window {:?}: {}", id, pending.props.title);
+                    self.main_window.get_or_insert(id);
+                    #[cfg(feature = "hot-reload")]
+                    if let Some(saved) = self.restored.take_window(&pending.props.title)
+                        && let Some(window) = self.window_manager.get_mut(id)
+                    {
+                        window.restore_after_restart(&saved);
+                    }
                 }
                 Err(e) => {
                     tracing::error!("Failed to create window: {}", e);
@@ -299,7 +580,12 @@ impl Runtime {
     fn poll_menu_events(&mut self) {
         // Poll for menu events
         while let Ok(event) = MenuEvent::receiver().try_recv() {
-            if self.menu_manager.handle_event(&event) {
+            let handled =
+                catch(|| self.menu_manager.handle_event(&event)).unwrap_or_else(|crash| {
+                    self.show_crash_in_current_window(crash);
+                    true
+                });
+            if handled {
                 // Callback was invoked - request re-render in case state changed
                 self.render_context.request_render();
             }
@@ -342,9 +628,18 @@ impl Runtime {
                             // Resume the window to start rendering
                             if let Some(window) = self.window_manager.get_mut(window_id) {
                                 window.resume();
+                                if let Some(state) = &open_req.restore {
+                                    window.restore_state(state);
+                                }
                                 // Set initial window state
                                 Self::update_window_state_for_handle(open_req.handle, window);
                             }
+                            // Its component renders with the app
+                            if let Some(component) = open_req.component {
+                                self.component_windows
+                                    .insert(window_id, (open_req.handle, component));
+                                self.render_context.request_render();
+                            }
                         }
                         Err(e) => {
                             tracing::error!("Failed to open window: {:?}", e);
@@ -360,6 +655,7 @@ impl Runtime {
                         );
                         self.window_ids_to_handles.remove(&window_id);
                         crate::windows::remove_window_state(close_req.handle);
+                        self.unmount_component_window(window_id);
                         self.window_manager.close_window(window_id);
                     } else {
                         tracing::warn!(
@@ -368,10 +664,41 @@ impl Runtime {
                         );
                     }
                 }
+                WindowRequest::Visibility(visibility_req) => {
+                    let window = self
+                        .window_handles
+                        .get(&visibility_req.handle)
+                        .and_then(|&window_id| self.window_manager.get(window_id));
+                    if let Some(window) = window {
+                        show_window(window, visibility_req.visible);
+                    } else {
+                        tracing::warn!(
+                            "Attempted to show or hide unknown window handle {:?}",
+                            visibility_req.handle
+                        );
+                    }
+                }
+                WindowRequest::Zoom(zoom_req) => {
+                    if let Some(&window_id) = self.window_handles.get(&zoom_req.handle) {
+                        self.zoom_window(window_id, zoom_req.zoom);
+                    } else {
+                        tracing::warn!(
+                            "Attempted to zoom unknown window handle {:?}",
+                            zoom_req.handle
+                        );
+                    }
+                }
             }
         }
     }
 
+    /// Drop the hooks of a window opened with a component, as it closes.
+    fn unmount_component_window(&mut self, window_id: WindowId) {
+        if let Some((handle, _)) = self.component_windows.remove(&window_id) {
+            drop_root(handle.id());
+        }
+    }
+
     /// Update the window state for a given handle.
     fn update_window_state_for_handle(
         handle: crate::windows::WindowHandle,
@@ -390,6 +717,7 @@ impl Runtime {
             height: size.height,
             maximized,
             minimized,
+            zoom: managed_window.content_scale(),
         };
 
         crate::windows::update_window_state(handle, state);
@@ -402,13 +730,32 @@ impl Runtime {
             return;
         };
 
+        let _span = tracing::debug_span!("rerender").entered();
+
         // Clear old event handlers
         clear_handlers();
 
         // Re-run the app function to get new element tree
-        begin_render();
-        let root = app_fn();
-        end_render();
+        let root = tracing::debug_span!("reactive_flush").in_scope(|| {
+            catch(|| {
+                begin_render();
+                let root = app_fn();
+                end_render();
+                root
+            })
+        });
+        let root = match root {
+            Ok(root) => root,
+            Err(crash) => {
+                // Every window is rendered by the app function
+                for window_id in self.app_window_ids() {
+                    self.show_crash(window_id, crash.clone());
+                }
+                self.render_crashed = true;
+                self.render_context.clear_render_flag();
+                return;
+            }
+        };
 
         // Extract HTML for each window
         let mut window_contents: Vec<(WindowProps, String)> = Vec::new();
@@ -431,16 +778,174 @@ impl Runtime {
         extract_windows(root, &mut window_contents);
 
         // Update each window's content
-        // For now, we assume windows are in the same order
-        let window_ids: Vec<WindowId> = self.window_manager.window_ids();
+        // For now, we assume windows are in the same order. Windows opened
+        // with `open_window` aren't the app's.
+        let window_ids: Vec<WindowId> = self
+            .app_window_ids()
+            .into_iter()
+            .filter(|id| !self.window_ids_to_handles.contains_key(id))
+            .collect();
 
         for (id, (_props, html)) in window_ids.iter().zip(window_contents.iter()) {
+            // Crashed windows keep their crash panel until reloaded
+            if self.crashes.contains_key(id) {
+                continue;
+            }
             if let Some(window) = self.window_manager.get_mut(*id) {
                 window.update_content(html.clone());
             }
         }
 
+        self.render_component_windows();
+
         self.render_context.clear_render_flag();
+
+        // Elements scrolled to or focused may have just been rendered
+        self.process_scroll_requests();
+        self.process_focus_requests();
+        self.process_remeasure_requests();
+
+        self.run_committed_effects();
+    }
+
+    /// Run the `use_effect` effects waiting for the content just put in the
+    /// windows, re-rendering if any ran.
+    fn run_committed_effects(&mut self) {
+        match catch(run_effects) {
+            Ok(true) => self.render_context.request_render(),
+            Ok(false) => {}
+            Err(crash) => self.show_crash_in_current_window(crash),
+        }
+    }
+
+    /// Re-render the windows opened with a component, each as a root of its
+    /// own.
+    fn render_component_windows(&mut self) {
+        let windows: Vec<_> = self
+            .component_windows
+            .iter()
+            .map(|(&id, (handle, component))| (id, *handle, component.clone()))
+            .collect();
+        for (window_id, handle, component) in windows {
+            if self.crashes.contains_key(&window_id) {
+                continue;
+            }
+            crate::windows::set_current_window_id(Some(window_id));
+            let html =
+                catch(|| render_root(handle.id(), || children_to_html(&[component.render()])));
+            crate::windows::set_current_window_id(None);
+            match html {
+                Ok(html) => {
+                    if let Some(window) = self.window_manager.get_mut(window_id) {
+                        window.update_content(html);
+                    }
+                }
+                Err(crash) => self.show_crash(window_id, crash),
+            }
+        }
+    }
+
+    /// Carry out scroll calls made through node refs.
+    fn process_scroll_requests(&mut self) {
+        for (node_ref, request) in take_scroll_requests() {
+            let scrolled = self.window_manager.window_ids().into_iter().any(|id| {
+                self.window_manager
+                    .get_mut(id)
+                    .is_some_and(|window| window.scroll_node_ref(node_ref, request))
+            });
+            if !scrolled {
+                tracing::debug!(
+                    "Node ref {} isn't attached to an element, not scrolling",
+                    node_ref
+                );
+            }
+        }
+    }
+
+    /// Carry out focus calls made through node refs.
+    fn process_focus_requests(&mut self) {
+        for (node_ref, request) in take_focus_requests() {
+            let found = self.window_manager.window_ids().into_iter().any(|id| {
+                self.window_manager
+                    .get_mut(id)
+                    .is_some_and(|window| window.focus_node_ref(node_ref, request))
+            });
+            if !found {
+                tracing::debug!(
+                    "Node ref {} isn't attached to an element, not focusing",
+                    node_ref
+                );
+            }
+        }
+    }
+
+    /// Carry out remeasure calls made through node refs.
+    fn process_remeasure_requests(&mut self) {
+        for node_ref in take_remeasure_requests() {
+            let found = self.window_manager.window_ids().into_iter().any(|id| {
+                self.window_manager
+                    .get_mut(id)
+                    .is_some_and(|window| window.remeasure_node_ref(node_ref))
+            });
+            if !found {
+                tracing::debug!(
+                    "Node ref {} isn't attached to an element, not remeasuring",
+                    node_ref
+                );
+            }
+        }
+    }
+
+    /// The app's windows, rather than DevTools or the find bar.
+    fn app_window_ids(&self) -> Vec<WindowId> {
+        self.window_manager
+            .window_ids()
+            .into_iter()
+            .filter(|&id| Some(id) != self.devtools_window && Some(id) != self.find_bar_window)
+            .collect()
+    }
+
+    /// Replace a window's content with the crash panel for a panic caught
+    /// from app code.
+    fn show_crash(&mut self, window_id: WindowId, crash: Crash) {
+        tracing::error!(
+            "Caught a panic in window {:?}: {}; showing the crash panel",
+            window_id,
+            crash.message
+        );
+        if let Some(window) = self.window_manager.get_mut(window_id) {
+            window.update_content(crash_panel_html(&crash));
+        }
+        self.crashes.insert(window_id, crash);
+    }
+
+    /// Show a panic caught outside any window's event in the window the
+    /// user is in.
+    fn show_crash_in_current_window(&mut self, crash: Crash) {
+        let window_id = crate::windows::get_current_window_id()
+            .filter(|id| self.window_manager.get(*id).is_some())
+            .or(self.main_window);
+        match window_id {
+            Some(window_id) => self.show_crash(window_id, crash),
+            None => tracing::error!(
+                "Caught a panic with no window to show it: {}",
+                crash.message
+            ),
+        }
+    }
+
+    /// Render the app into a crashed window again, from its crash panel's
+    /// "Reload window" button.
+    fn reload_window(&mut self, window_id: WindowId) {
+        if self.crashes.remove(&window_id).is_none() {
+            return;
+        }
+        // A render panic is the same panic in every window
+        if self.render_crashed {
+            self.crashes.clear();
+            self.render_crashed = false;
+        }
+        self.render_context.request_render();
     }
 
     /// Handle a click event by dispatching to the registered handler.
@@ -450,7 +955,13 @@ impl Runtime {
         // Track the current window so event handlers can call window control functions
         crate::windows::set_current_window_id(Some(window_id));
 
-        if dispatch_event(handler_id) {
+        let start = Instant::now();
+        let called = catch(|| dispatch_event(handler_id)).unwrap_or_else(|crash| {
+            self.show_crash(window_id, crash);
+            true
+        });
+        self.event_log.finish(handler_id, called, start.elapsed());
+        if called {
             // Handler was called - request re-render in case state changed
             self.render_context.request_render();
         }
@@ -459,6 +970,145 @@ impl Runtime {
         crate::windows::set_current_window_id(None);
     }
 
+    /// Run drag handlers, in order, for a pointer move or release.
+    fn handle_element_drag(
+        &mut self,
+        window_id: WindowId,
+        calls: Vec<(EventHandlerId, DragEvent)>,
+    ) {
+        crate::windows::set_current_window_id(Some(window_id));
+        let mut called = false;
+        for (handler_id, event) in calls {
+            called |= catch(|| dispatch_drag_event(handler_id, event)).unwrap_or_else(|crash| {
+                self.show_crash(window_id, crash);
+                true
+            });
+        }
+        if called {
+            self.render_context.request_render();
+        }
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Run key handlers, innermost element first.
+    fn handle_key_input(
+        &mut self,
+        window_id: WindowId,
+        calls: Vec<(EventHandlerId, KeyboardEvent)>,
+    ) {
+        crate::windows::set_current_window_id(Some(window_id));
+        let mut called = false;
+        for (handler_id, event) in calls {
+            called |= catch(|| dispatch_key_event(handler_id, event)).unwrap_or_else(|crash| {
+                self.show_crash(window_id, crash);
+                true
+            });
+        }
+        if called {
+            self.render_context.request_render();
+        }
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Run `oncopy`, `oncut` or `onpaste` handlers, innermost first.
+    fn handle_clipboard_input(
+        &mut self,
+        window_id: WindowId,
+        handlers: Vec<EventHandlerId>,
+        event: ClipboardEvent,
+    ) {
+        crate::windows::set_current_window_id(Some(window_id));
+        let mut called = false;
+        for handler_id in handlers {
+            let handled = catch(|| dispatch_clipboard_event(handler_id, event.clone()));
+            called |= handled.unwrap_or_else(|crash| {
+                self.show_crash(window_id, crash);
+                true
+            });
+        }
+        if called {
+            self.render_context.request_render();
+        }
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Run `onblur` and `onfocus` handlers after focus moved.
+    fn handle_focus_change(&mut self, window_id: WindowId, handlers: Vec<EventHandlerId>) {
+        crate::windows::set_current_window_id(Some(window_id));
+        let mut called = false;
+        for handler_id in handlers {
+            called |= catch(|| dispatch_event(handler_id)).unwrap_or_else(|crash| {
+                self.show_crash(window_id, crash);
+                true
+            });
+        }
+        if called {
+            self.render_context.request_render();
+        }
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Call a text field's `bind`, `oninput` or `onchange` handlers with its
+    /// value.
+    fn handle_field_input(
+        &mut self,
+        window_id: WindowId,
+        handlers: Vec<EventHandlerId>,
+        value: String,
+    ) {
+        crate::windows::set_current_window_id(Some(window_id));
+        let mut called = false;
+        for handler_id in handlers {
+            let value = value.clone();
+            called |= catch(|| dispatch_input_event(handler_id, value)).unwrap_or_else(|crash| {
+                self.show_crash(window_id, crash);
+                true
+            });
+        }
+        if called {
+            self.render_context.request_render();
+        }
+        crate::windows::set_current_window_id(None);
+    }
+
+    /// Handle a scroll event by updating the element's ref and calling its handler.
+    fn handle_element_scroll(
+        &mut self,
+        window_id: WindowId,
+        handler_id: Option<EventHandlerId>,
+        node_ref: Option<u64>,
+        event: ScrollEvent,
+    ) {
+        let mut changed = node_ref.is_some_and(|id| update_node_ref_scroll(id, event));
+
+        if let Some(handler_id) = handler_id {
+            // While DevTools is open, log the scroll
+            if self.devtools_window.is_some()
+                && let Some(window) = self.window_manager.get(window_id)
+                && let Some(node_id) = window.scroll_watch.node_for_handler(handler_id)
+            {
+                let path = event_path(&window.doc.inner(), node_id, "data-onscroll");
+                self.event_log
+                    .record(EventKind::Scroll, path, Some(handler_id));
+            }
+
+            crate::windows::set_current_window_id(Some(window_id));
+            let start = Instant::now();
+            let called =
+                catch(|| dispatch_scroll_event(handler_id, event)).unwrap_or_else(|crash| {
+                    self.show_crash(window_id, crash);
+                    true
+                });
+            self.event_log.finish(handler_id, called, start.elapsed());
+            changed |= called;
+            crate::windows::set_current_window_id(None);
+        }
+
+        if changed {
+            self.render_context.request_render();
+        }
+    }
+
     /// Toggle the DevTools window.
     fn toggle_devtools(&mut self, event_loop: &ActiveEventLoop, source_window: WindowId) {
         // If DevTools is already open, close it
@@ -467,7 +1117,7 @@ impl Runtime {
             if let Some(mut window) = self.window_manager.close_window(devtools_id) {
                 window.suspend();
             }
-            self.devtools_target = None;
+            self.release_devtools_target();
             return;
         }
 
@@ -487,6 +1137,12 @@ impl Runtime {
             transparent: false,
             always_on_top: true,
             visible: true,
+            reset: false,
+            rtl: false,
+            antialiasing: Default::default(),
+            overlay_scrollbars: false,
+            overscroll_bounce: false,
+            content_scale: 1.0,
         };
 
         let proxy = self.proxy.clone().expect("Proxy should be set");
@@ -503,6 +1159,242 @@ impl Runtime {
         }
     }
 
+    /// Change a window's content scale, re-rendering so the app can show
+    /// the new level.
+    fn zoom_window(&mut self, window_id: WindowId, zoom: crate::windows::Zoom) {
+        if let Some(window) = self.window_manager.get_mut(window_id)
+            && window.zoom(zoom)
+        {
+            if let Some(&handle) = self.window_ids_to_handles.get(&window_id) {
+                Self::update_window_state_for_handle(handle, window);
+            }
+            self.render_context.request_render();
+        }
+    }
+
+    /// Close a window from its window controls, quitting after the last.
+    fn close_window_control(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId) {
+        if self.find_bar_window == Some(window_id) || self.find_target == Some(window_id) {
+            self.close_find_bar();
+        }
+
+        // Clean up window state tracking if this is a programmatically opened window
+        if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
+            self.window_handles.remove(&handle);
+            crate::windows::remove_window_state(handle);
+            crate::search::remove_search_results(handle);
+        }
+        self.unmount_component_window(window_id);
+
+        self.window_manager.close_window(window_id);
+
+        if !self.window_manager.has_windows() {
+            event_loop.exit();
+        }
+    }
+
+    /// Ask the app's before-quit handlers whether it may quit, re-rendering
+    /// since they may have changed state (to show a prompt, say).
+    fn confirm_quit(&mut self) -> bool {
+        let allowed = run_before_quit();
+        self.render_context.request_render();
+        if allowed {
+            self.quitting = true;
+        } else {
+            tracing::info!("Quit cancelled by the app");
+        }
+        allowed
+    }
+
+    /// Whether closing `window_id` would quit the app, being its last
+    /// window.
+    fn is_last_window(&self, window_id: WindowId) -> bool {
+        self.window_manager.window_ids() == [window_id]
+    }
+
+    /// Show, hide or toggle the main window (or the first window, if the
+    /// main one has closed), returning it.
+    fn set_main_window_visible(&mut self, visible: Option<bool>) -> Option<WindowId> {
+        let main_window = self
+            .main_window
+            .filter(|&id| self.window_manager.get(id).is_some())
+            .or_else(|| self.window_manager.window_ids().into_iter().next());
+        if let Some(window) = main_window.and_then(|id| self.window_manager.get(id)) {
+            let visible = visible.unwrap_or_else(|| window.window.is_visible() != Some(true));
+            show_window(window, visible);
+        }
+        main_window
+    }
+
+    /// Bring the main window forward and pass another instance's arguments
+    /// to the app.
+    fn handle_second_instance(&mut self, args: Vec<String>) {
+        tracing::debug!("Another instance started with {:?}", args);
+        let main_window = self.set_main_window_visible(Some(true));
+        if let Some(on_second_instance) = &self.on_second_instance {
+            crate::windows::set_current_window_id(main_window);
+            on_second_instance(args);
+            crate::windows::set_current_window_id(None);
+            self.render_context.request_render();
+        }
+    }
+
+    /// Toggle the find bar for a window.
+    fn toggle_find_bar(&mut self, event_loop: &ActiveEventLoop, source_window: WindowId) {
+        // If the find bar is already open, close it
+        if self.find_bar_window.is_some() {
+            self.close_find_bar();
+            return;
+        }
+
+        let Some(target) = self.window_manager.get(source_window) else {
+            return;
+        };
+
+        // Place the bar over the target's top-right corner
+        let scale = target.window.scale_factor();
+        let position = target
+            .window
+            .inner_position()
+            .unwrap_or_default()
+            .to_logical::<i32>(scale);
+        let size = target.window.inner_size().to_logical::<i32>(scale);
+        let x = position.x + size.width - FIND_BAR_WIDTH as i32 - 16;
+        let y = position.y + 8;
+
+        let html = target.find.html();
+        let props = WindowProps {
+            title: "Find".into(),
+            width: FIND_BAR_WIDTH,
+            height: FIND_BAR_HEIGHT,
+            x: Some(x),
+            y: Some(y),
+            borderless: true,
+            resizable: false,
+            transparent: false,
+            always_on_top: true,
+            visible: true,
+            reset: false,
+            rtl: false,
+            antialiasing: Default::default(),
+            overlay_scrollbars: false,
+            overscroll_bounce: false,
+            content_scale: 1.0,
+        };
+
+        let proxy = self.proxy.clone().expect("Proxy should be set");
+        match self
+            .window_manager
+            .create_window(event_loop, proxy, props, html)
+        {
+            Ok(window_id) => {
+                self.find_bar_window = Some(window_id);
+                self.find_target = Some(source_window);
+                if let Some(window) = self.window_manager.get_mut(window_id) {
+                    window.resume();
+                    window.window.focus_window();
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to create find bar window: {:?}", e);
+            }
+        }
+    }
+
+    /// Close the find bar and clear its highlights.
+    fn close_find_bar(&mut self) {
+        if let Some(find_bar_id) = self.find_bar_window.take()
+            && let Some(mut window) = self.window_manager.close_window(find_bar_id)
+        {
+            window.suspend();
+        }
+        if let Some(target_id) = self.find_target.take()
+            && let Some(target) = self.window_manager.get_mut(target_id)
+        {
+            target.find.clear();
+            target.request_redraw();
+            target.window.focus_window();
+        }
+    }
+
+    /// Handle a key press in the find bar window.
+    fn handle_find_bar_key(&mut self, window_id: WindowId, event: &winit::event::KeyEvent) {
+        use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
+
+        let Some(target_id) = self.find_target else {
+            return;
+        };
+        let modifiers = self
+            .window_manager
+            .get(window_id)
+            .map(|w| w.keyboard_modifiers.state())
+            .unwrap_or_default();
+        let shift = modifiers.shift_key();
+        let find_shortcut = (modifiers.control_key() || modifiers.super_key())
+            && event.physical_key == PhysicalKey::Code(KeyCode::KeyF);
+        if find_shortcut || event.logical_key == Key::Named(NamedKey::Escape) {
+            self.close_find_bar();
+            return;
+        }
+
+        let Some(target) = self.window_manager.get_mut(target_id) else {
+            return;
+        };
+
+        let mut query = target.find.query.clone();
+        match &event.logical_key {
+            Key::Named(NamedKey::Enter) | Key::Named(NamedKey::F3) => {
+                target.find.step(!shift);
+                target.scroll_to_current_match();
+                target.request_redraw();
+            }
+            Key::Named(NamedKey::Backspace) => {
+                query.pop();
+            }
+            _ => {
+                if let Some(text) = &event.text {
+                    query.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+
+        if query != target.find.query {
+            target.find.set_query(&target.doc.inner(), query);
+            target.scroll_to_current_match();
+            target.request_redraw();
+        }
+
+        let html = target.find.html();
+        if let Some(find_bar) = self
+            .find_bar_window
+            .and_then(|id| self.window_manager.get_mut(id))
+        {
+            find_bar.update_content(html);
+        }
+    }
+
+    /// Run pending `find_in_window` searches.
+    fn process_search_requests(&mut self) {
+        use crate::search::{find_matches, set_search_results, take_search_requests};
+
+        let mut changed = false;
+        for (handle, query) in take_search_requests() {
+            let matches = self
+                .window_handles
+                .get(&handle)
+                .and_then(|id| self.window_manager.get(*id))
+                .map(|window| find_matches(&window.doc.inner(), &query))
+                .unwrap_or_default();
+            changed |= set_search_results(handle, query, matches);
+        }
+
+        // Only re-render when results changed, or searching on every render
+        // would loop
+        if changed {
+            self.render_context.request_render();
+        }
+    }
+
     /// Generate an HTML representation of the DOM tree for the target window.
     fn generate_dom_tree_html(&self) -> String {
         let Some(target_id) = self.devtools_target else {
@@ -514,15 +1406,18 @@ impl Runtime {
         };
 
         let inner = window.doc.inner();
+        let selected = window.devtools.selected_node;
         let mut html = String::new();
 
-        // Start from the root and walk the tree
+        // Start from the root and walk the tree. Elements carry their node ID
+        // so clicking one selects it.
         fn render_node(
             inner: &blitz_dom::BaseDocument,
             node_id: usize,
             depth: usize,
             html: &mut String,
             max_depth: usize,
+            selected: Option<usize>,
         ) {
             if depth > max_depth {
                 return;
@@ -556,14 +1451,19 @@ impl Runtime {
                 }
 
                 html.push_str(&format!(
-                    r#"{}<div class="tree-node"><span class="tag">&lt;{}&gt;</span>{}{}</div>"#,
-                    indent, tag, id_str, class_str
+                    r#"{}<div class="tree-node{}" data-devtools-node="{}"><span class="tag">&lt;{}&gt;</span>{}{}</div>"#,
+                    indent,
+                    if selected == Some(node_id) { " selected" } else { "" },
+                    node_id,
+                    tag,
+                    id_str,
+                    class_str
                 ));
                 html.push('\n');
 
                 // Recurse into children
                 for &child_id in &node.children {
-                    render_node(inner, child_id, depth + 1, html, max_depth);
+                    render_node(inner, child_id, depth + 1, html, max_depth, selected);
                 }
             } else if node.is_text_node() {
                 let text = node.text_content();
@@ -579,7 +1479,7 @@ impl Runtime {
             } else {
                 // Other node types - just recurse
                 for &child_id in &node.children {
-                    render_node(inner, child_id, depth + 1, html, max_depth);
+                    render_node(inner, child_id, depth + 1, html, max_depth, selected);
                 }
             }
         }
@@ -595,7 +1495,7 @@ impl Runtime {
         // Get root node and render
         if let Some(root) = inner.get_node(0) {
             for &child_id in &root.children {
-                render_node(&inner, child_id, 0, &mut html, 10);
+                render_node(&inner, child_id, 0, &mut html, 10, selected);
             }
         }
 
@@ -606,28 +1506,142 @@ impl Runtime {
         }
     }
 
-    /// Generate HTML content for the DevTools window.
-    fn generate_devtools_html(&self) -> String {
-        use rinch_core::get_hooks_debug_info;
+    /// Stop inspecting the DevTools target, removing its highlight.
+    fn release_devtools_target(&mut self) {
+        if let Some(target) = self
+            .devtools_target
+            .take()
+            .and_then(|id| self.window_manager.get_mut(id))
+        {
+            target.devtools.clear_selection();
+            target.request_redraw();
+        }
+    }
 
-        let hooks_info = get_hooks_debug_info();
-        let hooks_html: String = if hooks_info.is_empty() {
-            r#"<p style="color: #808080;">No hooks registered.</p>"#.to_string()
+    /// The node a left click in `window_id` selects for DevTools: an element
+    /// in the DevTools DOM tree, or any element of the inspected window in
+    /// inspect mode (Alt+I).
+    fn devtools_click(&self, window_id: WindowId) -> Option<usize> {
+        let window = self.window_manager.get(window_id)?;
+        if self.devtools_window == Some(window_id) {
+            window.attribute_at_mouse(DEVTOOLS_NODE_ATTR)?.parse().ok()
+        } else if self.devtools_target == Some(window_id) && window.devtools.inspect_mode {
+            window.node_at_mouse()
         } else {
-            hooks_info
-                .iter()
-                .enumerate()
-                .map(|(i, info)| {
-                    format!(
-                        r#"<div class="hook-item">
-                            <span class="hook-index">#{}</span>
-                            <span class="hook-type">{}</span>
-                            <span class="hook-value-type">{}</span>
-                        </div>"#,
-                        i, info.hook_type, info.value_type
-                    )
-                })
-                .collect()
+            None
+        }
+    }
+
+    /// Select a node of the inspected window: highlight it there and mark it
+    /// in the DevTools DOM tree.
+    fn select_devtools_node(&mut self, node_id: usize) {
+        let Some(target) = self
+            .devtools_target
+            .and_then(|id| self.window_manager.get_mut(id))
+        else {
+            return;
+        };
+        target.devtools.select_node(node_id);
+        target.request_redraw();
+        self.update_devtools();
+    }
+
+    /// Regenerate the DevTools window's content.
+    fn update_devtools(&mut self) {
+        if let Some(devtools_id) = self.devtools_window {
+            let html = self.generate_devtools_html();
+            if let Some(window) = self.window_manager.get_mut(devtools_id) {
+                window.update_content(html);
+            }
+        }
+    }
+
+    /// Show the event log entries `name` picks, or clear the log for
+    /// `"clear"`.
+    fn set_event_filter(&mut self, name: &str) {
+        if name == "clear" {
+            self.event_log.clear();
+        } else if let Some(filter) = EventFilter::from_name(name) {
+            self.event_log.filter = filter;
+        }
+        self.update_devtools();
+    }
+
+    /// Fire due timers, and wake up for the next one unless something else
+    /// wakes the loop first.
+    fn tick_timers(&mut self, event_loop: &ActiveEventLoop) {
+        let fired = catch(|| run_timers(clock::now())).unwrap_or_else(|crash| {
+            self.show_crash_in_current_window(crash);
+            true
+        });
+        if fired {
+            self.render_context.request_render();
+        }
+        if let Some(next) = next_timer()
+            && !matches!(event_loop.control_flow(), ControlFlow::WaitUntil(due) if due <= next)
+        {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next));
+        }
+    }
+
+    /// Refresh the DevTools window when the reactive graph or event log
+    /// changes, at most every `DEVTOOLS_REFRESH_INTERVAL`.
+    fn refresh_devtools(&mut self, event_loop: &ActiveEventLoop) {
+        let version = (reactive_graph_version(), self.event_log.version());
+        if self.devtools_window.is_none() || version == self.devtools_version {
+            return;
+        }
+
+        let now = Instant::now();
+        let due = self
+            .devtools_refreshed
+            .map_or(now, |last| last + DEVTOOLS_REFRESH_INTERVAL);
+        if now < due {
+            // Come back when it's due, unless an animation frame comes first
+            if !matches!(event_loop.control_flow(), ControlFlow::WaitUntil(next) if next <= due) {
+                event_loop.set_control_flow(ControlFlow::WaitUntil(due));
+            }
+            return;
+        }
+
+        self.devtools_refreshed = Some(now);
+        self.devtools_version = version;
+        self.update_devtools();
+    }
+
+    /// Generate HTML content for the DevTools window.
+    fn generate_devtools_html(&self) -> String {
+        use rinch_core::events::html_escape_string;
+        use rinch_core::get_hooks_debug_info;
+
+        let hooks_info = get_hooks_debug_info();
+        let hooks_html: String = if hooks_info.is_empty() {
+            r#"<p style="color: #808080;">No hooks registered.</p>"#.to_string()
+        } else {
+            hooks_info
+                .iter()
+                .enumerate()
+                .map(|(i, info)| {
+                    let value = info
+                        .value
+                        .as_deref()
+                        .map(html_escape_string)
+                        .map(|value| format!(r#"<span class="hook-value">{value}</span>"#))
+                        .unwrap_or_default();
+                    format!(
+                        r#"<div class="hook-item">
+                            <span class="hook-index">#{}</span>
+                            <span class="hook-type">{}</span>
+                            <span class="hook-value-type">{}</span>
+                            {}
+                        </div>"#,
+                        i,
+                        info.hook_type,
+                        html_escape_string(info.value_type),
+                        value
+                    )
+                })
+                .collect()
         };
 
         // Generate element info section
@@ -635,6 +1649,10 @@ impl Runtime {
             Some(info) => {
                 let id_str = info.id.as_deref().unwrap_or("-");
                 let classes_str = info.classes.as_deref().unwrap_or("-");
+                let source_str = info.source.as_ref().map_or_else(
+                    || "-".to_string(),
+                    |source| html_escape_string(&source.to_string()),
+                );
 
                 // Generate styles HTML
                 let styles_html: String = if info.styles.is_empty() {
@@ -652,18 +1670,104 @@ impl Runtime {
                         .collect();
                     format!(
                         r#"<div class="element-styles">
-                            <div class="layout-title">Computed Styles</div>
+                            <div class="layout-title">Box Model</div>
                             {}
                         </div>"#,
                         style_items
                     )
                 };
 
+                let computed_html = if info.computed_styles.is_empty() {
+                    String::new()
+                } else {
+                    let items: String = info
+                        .computed_styles
+                        .iter()
+                        .map(|(name, value)| {
+                            format!(
+                                r#"<div class="style-item"><span class="style-name">{}</span>: <span class="style-value">{}</span></div>"#,
+                                html_escape_string(name),
+                                html_escape_string(value)
+                            )
+                        })
+                        .collect();
+                    format!(
+                        r#"<div class="element-styles">
+                            <div class="layout-title">Computed Styles</div>
+                            {}
+                        </div>"#,
+                        items
+                    )
+                };
+
+                let rules_html = if info.matched_rules.is_empty() {
+                    String::new()
+                } else {
+                    let rules: String = info
+                        .matched_rules
+                        .iter()
+                        .map(|rule| {
+                            let conditions: String = rule
+                                .conditions
+                                .iter()
+                                .map(|c| {
+                                    format!(
+                                        r#"<div class="rule-condition">{}</div>"#,
+                                        html_escape_string(c)
+                                    )
+                                })
+                                .collect();
+                            let declarations: String = rule
+                                .declarations
+                                .iter()
+                                .map(|decl| {
+                                    format!(
+                                        r#"<div class="style-item{}"><span class="style-name">{}</span>: <span class="style-value">{}{}</span></div>"#,
+                                        if decl.overridden { " overridden" } else { "" },
+                                        html_escape_string(&decl.property),
+                                        html_escape_string(&decl.value),
+                                        if decl.important { " !important" } else { "" }
+                                    )
+                                })
+                                .collect();
+                            let layer = rule
+                                .layer
+                                .as_ref()
+                                .map(|l| format!(r#"<div class="rule-condition">@layer {}</div>"#, html_escape_string(l)))
+                                .unwrap_or_default();
+                            let (a, b, c) = rule.specificity;
+                            format!(
+                                r#"<div class="rule{}">
+                                    <div class="rule-header"><span class="rule-selector">{}</span><span class="rule-source">{} ({},{},{})</span></div>
+                                    {}{}{}
+                                </div>"#,
+                                if rule.active { "" } else { " inactive" },
+                                html_escape_string(&rule.selector),
+                                html_escape_string(&rule.source.to_string()),
+                                a,
+                                b,
+                                c,
+                                layer,
+                                conditions,
+                                declarations
+                            )
+                        })
+                        .collect();
+                    format!(
+                        r#"<div class="element-styles">
+                            <div class="layout-title">Matched Rules</div>
+                            {}
+                        </div>"#,
+                        rules
+                    )
+                };
+
                 format!(
                     r#"<div class="element-info">
                         <div class="element-tag">&lt;{}&gt;</div>
                         <div class="element-attr"><span class="attr-name">id:</span> <span class="attr-value">{}</span></div>
                         <div class="element-attr"><span class="attr-name">class:</span> <span class="attr-value">{}</span></div>
+                        <div class="element-attr"><span class="attr-name">source:</span> <span class="attr-value">{}</span></div>
                         <div class="element-layout">
                             <div class="layout-title">Layout</div>
                             <div class="layout-grid">
@@ -673,19 +1777,22 @@ impl Runtime {
                                 <div>h: {:.0}</div>
                             </div>
                         </div>
-                        {}
+                        {}{}{}
                     </div>"#,
                     info.tag_name,
                     id_str,
                     classes_str,
+                    source_str,
                     info.layout.x,
                     info.layout.y,
                     info.layout.width,
                     info.layout.height,
-                    styles_html
+                    styles_html,
+                    computed_html,
+                    rules_html
                 )
             }
-            None => r#"<p style="color: #808080;">Enable inspect mode (Alt+I) and hover over elements.</p>"#.to_string(),
+            None => r#"<p style="color: #808080;">Enable inspect mode (Alt+I) and hover over elements, or click one to select it.</p>"#.to_string(),
         };
 
         format!(
@@ -758,6 +1865,11 @@ impl Runtime {
             color: #ce9178;
             font-size: 11px;
         }}
+        .hook-value {{
+            color: #b5cea8;
+            margin-left: auto;
+            overflow-wrap: anywhere;
+        }}
         .info {{
             color: #808080;
             font-size: 11px;
@@ -832,6 +1944,32 @@ impl Runtime {
         .style-value {{
             color: #ce9178;
         }}
+        .style-item.overridden {{
+            text-decoration: line-through;
+            opacity: 0.5;
+        }}
+        .rule {{
+            margin-bottom: 8px;
+        }}
+        .rule.inactive {{
+            opacity: 0.5;
+        }}
+        .rule-header {{
+            display: flex;
+            justify-content: space-between;
+            gap: 8px;
+        }}
+        .rule-selector {{
+            color: #d7ba7d;
+        }}
+        .rule-source {{
+            color: #808080;
+            font-size: 11px;
+        }}
+        .rule-condition {{
+            color: #c586c0;
+            font-size: 11px;
+        }}
         .dom-tree {{
             background: #252526;
             padding: 8px;
@@ -847,6 +1985,9 @@ impl Runtime {
         .tree-node .tag {{
             color: #569cd6;
         }}
+        .tree-node.selected {{
+            background: #264f78;
+        }}
         .tree-node .attr-id {{
             color: #9cdcfe;
         }}
@@ -858,6 +1999,95 @@ impl Runtime {
             font-style: italic;
             padding: 2px 0;
         }}
+        .graph-summary {{
+            color: #808080;
+            margin-bottom: 8px;
+        }}
+        .graph {{
+            display: flex;
+            gap: 8px;
+            align-items: flex-start;
+        }}
+        .graph-column {{
+            flex: 1;
+            min-width: 0;
+        }}
+        .graph-node {{
+            background: #2d2d2d;
+            padding: 6px 8px;
+            margin-bottom: 4px;
+            border-radius: 4px;
+            border-left: 2px solid #3c3c3c;
+            font-size: 11px;
+        }}
+        .graph-node.suspicious {{
+            border-left-color: #f48771;
+        }}
+        .graph-id {{
+            color: #808080;
+        }}
+        .graph-label {{
+            color: #569cd6;
+            overflow-wrap: anywhere;
+        }}
+        .graph-stats {{
+            color: #b5cea8;
+        }}
+        .graph-edges {{
+            color: #9cdcfe;
+        }}
+        .graph-warning {{
+            color: #f48771;
+        }}
+        .event-filters {{
+            display: flex;
+            gap: 4px;
+            margin-bottom: 8px;
+        }}
+        .event-filter {{
+            padding: 2px 8px;
+            border-radius: 3px;
+            background: #3c3c3c;
+            cursor: pointer;
+        }}
+        .event-filter.active {{
+            background: #007acc;
+            color: #ffffff;
+        }}
+        .event {{
+            background: #2d2d2d;
+            padding: 6px 8px;
+            margin-bottom: 4px;
+            border-radius: 4px;
+            border-left: 2px solid #3c3c3c;
+            font-size: 11px;
+        }}
+        .event.unhandled {{
+            border-left-color: #808080;
+        }}
+        .event.slow {{
+            border-left-color: #f48771;
+        }}
+        .event-kind {{
+            color: #569cd6;
+            font-weight: bold;
+        }}
+        .event-time {{
+            color: #808080;
+        }}
+        .event-path {{
+            color: #9cdcfe;
+            overflow-wrap: anywhere;
+        }}
+        .event-outcome {{
+            color: #b5cea8;
+        }}
+        .event.unhandled .event-outcome {{
+            color: #808080;
+        }}
+        .event.slow .event-outcome {{
+            color: #f48771;
+        }}
     </style>
 </head>
 <body>
@@ -879,6 +2109,14 @@ impl Runtime {
             <div class="section-title">Registered Hooks ({} total)</div>
             {}
         </div>
+        <div class="section">
+            <div class="section-title">Reactive Graph</div>
+            {}
+        </div>
+        <div class="section">
+            <div class="section-title">Event Log</div>
+            {}
+        </div>
         <div class="section">
             <div class="section-title">Keyboard Shortcuts</div>
             <div class="shortcuts">
@@ -894,6 +2132,10 @@ impl Runtime {
                     <span class="shortcut">Alt+I</span>
                     <span class="shortcut-desc">Toggle inspect mode</span>
                 </div>
+                <div class="shortcut-row">
+                    <span class="shortcut">Alt+R</span>
+                    <span class="shortcut-desc">Toggle paint flashing</span>
+                </div>
                 <div class="shortcut-row">
                     <span class="shortcut">Alt+T</span>
                     <span class="shortcut-desc">Print Taffy tree</span>
@@ -911,44 +2153,107 @@ impl Runtime {
             self.generate_dom_tree_html(),
             element_html,
             hooks_info.len(),
-            hooks_html
+            hooks_html,
+            render_reactive_graph(&reactive_graph(), Instant::now()),
+            self.event_log.html(Instant::now())
         )
     }
 }
 
 impl ApplicationHandler<RinchEvent> for Runtime {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // Windows are built with the reduced-motion styles from the start
+        set_system_reduced_motion(super::motion::system_prefers_reduced_motion());
+        self.reduced_motion = untracked(prefers_reduced_motion);
+
         // Create any pending windows
         self.create_pending_windows(event_loop);
 
         // Initialize menus after windows are created
         self.initialize_menus();
 
+        // The first render is in its windows
+        self.run_committed_effects();
+
         // Resume existing windows (activates rendering)
         self.window_manager.resume_all();
+
+        if std::mem::take(&mut self.suspended) {
+            run_resume();
+            self.render_context.request_render();
+        }
     }
 
     fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
         self.window_manager.suspend_all();
+        self.suspended = true;
+        run_suspend();
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Terminated from outside (the macOS Quit item or a logout, say)
+        // rather than through a close the app agreed to
+        if !self.quitting {
+            run_session_ending();
+        }
+
+        #[cfg(feature = "storage")]
+        if let Err(e) = crate::storage::flush() {
+            tracing::warn!("Couldn't save storage: {e}");
+        }
+        #[cfg(feature = "settings")]
+        if let Err(e) = crate::settings::flush_all() {
+            tracing::warn!("Couldn't save settings: {e}");
+        }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        // Touches stand in for the mouse: taps click and drags pan
+        if let WindowEvent::Touch(touch) = event {
+            let scale_factor = self
+                .window_manager
+                .get(window_id)
+                .map_or(1.0, |window| window.window.scale_factor());
+            for event in self.touch.events(touch, scale_factor) {
+                self.window_event(event_loop, window_id, event);
+            }
+            return;
+        }
+
         // Handle close request at runtime level
         if matches!(event, WindowEvent::CloseRequested) {
             tracing::info!("Window {:?} close requested", window_id);
 
+            if self.is_last_window(window_id) && crate::windows::hides_on_close() {
+                if let Some(window) = self.window_manager.get(window_id) {
+                    show_window(window, false);
+                }
+                return;
+            }
+            if self.is_last_window(window_id) && !self.confirm_quit() {
+                return;
+            }
+
             // Check if this is the DevTools window being closed
             if self.devtools_window == Some(window_id) {
                 self.devtools_window = None;
-                self.devtools_target = None;
+                self.release_devtools_target();
+            }
+
+            // Closing the find bar or the window it searches closes the bar
+            if self.find_bar_window == Some(window_id) || self.find_target == Some(window_id) {
+                self.close_find_bar();
             }
 
             // Clean up window state tracking if this is a programmatically opened window
             if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
                 self.window_handles.remove(&handle);
                 crate::windows::remove_window_state(handle);
+                crate::search::remove_search_results(handle);
             }
+            self.unmount_component_window(window_id);
 
+            self.crashes.remove(&window_id);
             self.window_manager.close_window(window_id);
 
             if !self.window_manager.has_windows() {
@@ -965,10 +2270,99 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                         Self::update_window_state_for_handle(handle, window);
                     }
                 }
+                // Layout or scrolling may have moved text since the last search
+                WindowEvent::RedrawRequested => {
+                    crate::search::invalidate_search_results(handle);
+                }
                 _ => {}
             }
         }
 
+        // The find bar window takes its keyboard input here, not in its document
+        if self.find_bar_window == Some(window_id)
+            && let WindowEvent::KeyboardInput { event, .. } = &event
+        {
+            if event.state.is_pressed() {
+                self.handle_find_bar_key(window_id, event);
+            }
+            return;
+        }
+
+        // F3 steps through matches from the searched window too
+        if self.find_target == Some(window_id)
+            && let WindowEvent::KeyboardInput { event, .. } = &event
+            && event.state.is_pressed()
+            && event.logical_key == winit::keyboard::Key::Named(winit::keyboard::NamedKey::F3)
+        {
+            self.handle_find_bar_key(window_id, event);
+            return;
+        }
+
+        // For use_idle
+        if matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. }
+        ) && record_user_input()
+        {
+            self.render_context.request_render();
+        }
+
+        // The user may have changed the setting in another app
+        if matches!(event, WindowEvent::Focused(true)) {
+            set_system_reduced_motion(super::motion::system_prefers_reduced_motion());
+        }
+        // Menu callbacks and zoom_current_window() act on the focused window
+        match event {
+            WindowEvent::Focused(true) => crate::windows::set_focused_window_id(Some(window_id)),
+            WindowEvent::Focused(false) => crate::windows::unset_focused_window_id(window_id),
+            _ => {}
+        }
+
+        // Clicks in the DevTools tree, or in the inspected window in inspect
+        // mode, select elements instead of activating them
+        let left_click = matches!(
+            event,
+            WindowEvent::MouseInput {
+                state: winit::event::ElementState::Released,
+                button: winit::event::MouseButton::Left,
+                ..
+            }
+        );
+        let devtools_selection = left_click.then(|| self.devtools_click(window_id)).flatten();
+        if let Some(node_id) = devtools_selection {
+            self.select_devtools_node(node_id);
+            // An element picked in the inspected window jumps to its rsx!
+            if self.devtools_target == Some(window_id)
+                && let Some(window) = self.window_manager.get(window_id)
+                && let Some(source) = find_source(&window.doc.inner(), node_id)
+            {
+                jump_to_source(&source);
+            }
+        }
+        if left_click
+            && self.devtools_window == Some(window_id)
+            && let Some(filter) = self
+                .window_manager
+                .get(window_id)
+                .and_then(|window| window.attribute_at_mouse(DEVTOOLS_FILTER_ATTR))
+        {
+            self.set_event_filter(&filter);
+        }
+
+        if left_click
+            && self.crashes.contains_key(&window_id)
+            && self
+                .window_manager
+                .get(window_id)
+                .and_then(|window| window.attribute_at_mouse(CRASH_ACTION_ATTR))
+                .is_some_and(|action| action == "reload")
+        {
+            self.reload_window(window_id);
+        }
+
         // Forward other events to the window
         if let Some(window) = self.window_manager.get_mut(window_id) {
             // Check for mouse down events that might trigger window dragging
@@ -978,8 +2372,10 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 ..
             } = &event
             {
-                // Check if we should start window dragging
-                if window.should_drag_window() {
+                // Elements with drag handlers are dragged themselves
+                if let Some(handlers) = window.drag_handlers_at_mouse() {
+                    window.element_drag = Some(ElementDrag::new(handlers, window.mouse_pos));
+                } else if window.should_drag_window() {
                     window.start_drag();
                     return; // Don't process further - drag takes over
                 }
@@ -992,15 +2388,53 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 ..
             } = &event
             {
-                // Check if we clicked on an element with a handler
-                if let Some(handler_id) = window.get_clicked_handler() {
-                    if let Some(proxy) = &self.proxy {
+                // Check if we clicked on an element with a handler. A release
+                // that ends a text selection drag or an element drag isn't a
+                // click.
+                let element_dragged = window
+                    .element_drag
+                    .as_ref()
+                    .is_some_and(ElementDrag::started);
+                if !window.selection.dragged() && !element_dragged && devtools_selection.is_none() {
+                    let handler = window.get_clicked_handler();
+
+                    // While DevTools is open, log clicks in the app's windows
+                    if self.devtools_window.is_some_and(|id| id != window_id)
+                        && let Some(node_id) = window.node_at_mouse()
+                    {
+                        let path = event_path(&window.doc.inner(), node_id, "data-rid");
+                        self.event_log.record(EventKind::Click, path, handler);
+                    }
+
+                    if let Some(handler_id) = handler
+                        && let Some(proxy) = &self.proxy
+                    {
                         let _ = proxy.send_event(RinchEvent::ElementClicked { handler_id, window_id });
                     }
                 }
             }
 
+            let moved = matches!(event, WindowEvent::CursorMoved { .. });
+            let released = left_click;
             window.handle_event(event);
+
+            // Moves and the release run the drag handlers, once the window
+            // has the new pointer position
+            let pos = window.mouse_pos;
+            let drag_calls = if moved && let Some(drag) = &mut window.element_drag {
+                let calls = drag.moved(pos);
+                if drag.started() {
+                    window.selection.clear();
+                }
+                calls
+            } else if released && let Some(drag) = window.element_drag.take() {
+                drag.released(pos)
+            } else {
+                Vec::new()
+            };
+            if !drag_calls.is_empty() {
+                self.handle_element_drag(window_id, drag_calls);
+            }
         }
     }
 
@@ -1014,7 +2448,12 @@ impl ApplicationHandler<RinchEvent> for Runtime {
             RinchEvent::MenuEvent(id) => {
                 // Find the menu item and trigger its callback
                 let event = muda::MenuEvent { id };
-                if self.menu_manager.handle_event(&event) {
+                let handled =
+                    catch(|| self.menu_manager.handle_event(&event)).unwrap_or_else(|crash| {
+                        self.show_crash_in_current_window(crash);
+                        true
+                    });
+                if handled {
                     // Callback was invoked - request re-render
                     self.render_context.request_render();
                 }
@@ -1023,38 +2462,142 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 tracing::debug!("Re-rendering...");
                 self.re_render();
             }
+            RinchEvent::ReloadStyles => {
+                tracing::debug!("Reloading stylesheets...");
+                for window_id in self.window_manager.window_ids() {
+                    if let Some(window) = self.window_manager.get_mut(window_id) {
+                        window.reload_stylesheets();
+                    }
+                }
+            }
+            RinchEvent::HotRestart => {
+                #[cfg(feature = "hot-reload")]
+                self.hot_restart(event_loop);
+            }
+            RinchEvent::ReloadAssets => {
+                tracing::debug!("Reloading assets...");
+                crate::fonts::forget_font_files();
+                for window_id in self.window_manager.window_ids() {
+                    if let Some(window) = self.window_manager.get_mut(window_id) {
+                        window.reload_assets();
+                    }
+                }
+                self.re_render();
+            }
             RinchEvent::ElementClicked { handler_id, window_id } => {
                 self.handle_element_click(handler_id, window_id);
             }
+            RinchEvent::KeyInput { window_id, calls } => {
+                self.handle_key_input(window_id, calls);
+            }
+            RinchEvent::ClipboardInput {
+                window_id,
+                handlers,
+                event,
+            } => {
+                self.handle_clipboard_input(window_id, handlers, event);
+            }
+            RinchEvent::FocusChanged {
+                window_id,
+                handlers,
+            } => {
+                self.handle_focus_change(window_id, handlers);
+            }
+            RinchEvent::FieldInput {
+                window_id,
+                handlers,
+                value,
+            } => {
+                self.handle_field_input(window_id, handlers, value);
+            }
+            RinchEvent::ElementScrolled {
+                window_id,
+                handler_id,
+                node_ref,
+                event,
+            } => {
+                self.handle_element_scroll(window_id, handler_id, node_ref, event);
+            }
+            RinchEvent::ElementResized { node_ref, size } => {
+                if update_node_ref_size(node_ref, size) {
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::ElementMoved { node_ref, rect } => {
+                if update_node_ref_rect(node_ref, rect) {
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::ElementVisibility {
+                node_ref,
+                visibility,
+            } => {
+                if update_node_ref_visibility(node_ref, visibility) {
+                    self.render_context.request_render();
+                }
+            }
             RinchEvent::ToggleDevTools { source_window } => {
                 self.toggle_devtools(event_loop, source_window);
             }
             RinchEvent::UpdateDevToolsHover { element_info } => {
                 self.hovered_element = element_info;
-                // Update DevTools window content
-                if let Some(devtools_id) = self.devtools_window {
-                    let html = self.generate_devtools_html();
-                    if let Some(window) = self.window_manager.get_mut(devtools_id) {
-                        window.update_content(html);
-                    }
-                }
+                self.update_devtools();
             }
             RinchEvent::KeyboardShortcut {
-                ctrl,
-                meta,
-                alt,
-                shift,
-                key,
+                window_id,
+                press,
+                typing,
             } => {
+                self.menu_manager.learn_layout(&press);
                 // Check if keyboard shortcut matches a menu item
-                if let Some(menu_id) = self.menu_manager.match_shortcut(ctrl, meta, alt, shift, key)
-                {
+                let mut handled = false;
+                if let Some(menu_id) = self.menu_manager.match_shortcut(&press) {
                     let event = muda::MenuEvent { id: menu_id };
-                    if self.menu_manager.handle_event(&event) {
+                    crate::windows::set_current_window_id(Some(window_id));
+                    handled =
+                        catch(|| self.menu_manager.handle_event(&event)).unwrap_or_else(|crash| {
+                            self.show_crash(window_id, crash);
+                            true
+                        });
+                    crate::windows::set_current_window_id(None);
+                    if handled {
                         // Callback was invoked - request re-render
                         self.render_context.request_render();
                     }
                 }
+                // Then the window's `use_hotkey` hotkeys
+                if !handled {
+                    let root = self
+                        .component_windows
+                        .get(&window_id)
+                        .map(|(handle, _)| handle.id());
+                    let hotkeys = crate::menu::matching_hotkeys(&press, root, typing);
+                    crate::windows::set_current_window_id(Some(window_id));
+                    for hotkey in &hotkeys {
+                        if let Err(crash) = catch(|| hotkey.invoke()) {
+                            self.show_crash(window_id, crash);
+                        }
+                    }
+                    crate::windows::set_current_window_id(None);
+                    if !hotkeys.is_empty() {
+                        handled = true;
+                        self.render_context.request_render();
+                    }
+                }
+                // Built-in zoom shortcuts, unless a menu item or hotkey took the key
+                if !handled && press.ctrl_or_cmd && !press.alt {
+                    use crate::windows::Zoom;
+                    use winit::keyboard::KeyCode;
+                    let zoom = match press.key {
+                        KeyCode::Equal | KeyCode::NumpadAdd => Some(Zoom::In),
+                        KeyCode::Minus | KeyCode::NumpadSubtract => Some(Zoom::Out),
+                        KeyCode::Digit0 | KeyCode::Numpad0 => Some(Zoom::Reset),
+                        _ => None,
+                    };
+                    if let Some(zoom) = zoom {
+                        self.zoom_window(window_id, zoom);
+                    }
+                }
             }
             RinchEvent::ProcessWindowRequests => {
                 self.process_window_requests(event_loop);
@@ -1071,22 +2614,103 @@ impl ApplicationHandler<RinchEvent> for Runtime {
                 }
             }
             RinchEvent::CloseWindowControl { window_id } => {
-                // Clean up window state tracking if this is a programmatically opened window
-                if let Some(handle) = self.window_ids_to_handles.remove(&window_id) {
-                    self.window_handles.remove(&handle);
-                    crate::windows::remove_window_state(handle);
+                // Closing the last window quits, unless the app cancels or
+                // keeps running with it hidden
+                if self.is_last_window(window_id) && crate::windows::hides_on_close() {
+                    if let Some(window) = self.window_manager.get(window_id) {
+                        show_window(window, false);
+                    }
+                } else if !self.is_last_window(window_id) || self.confirm_quit() {
+                    self.close_window_control(event_loop, window_id);
                 }
-
-                self.window_manager.close_window(window_id);
-
-                if !self.window_manager.has_windows() {
+            }
+            RinchEvent::ProcessSearchRequests => {
+                self.process_search_requests();
+            }
+            RinchEvent::ToggleFindBar { source_window } => {
+                self.toggle_find_bar(event_loop, source_window);
+            }
+            RinchEvent::ZoomWindow { window_id, zoom } => {
+                self.zoom_window(window_id, zoom);
+            }
+            RinchEvent::SecondInstance { args } => {
+                self.handle_second_instance(args);
+            }
+            RinchEvent::PowerChanged(status) => {
+                set_power_status(status);
+                self.render_context.request_render();
+            }
+            RinchEvent::SettingsFileChanged => {
+                #[cfg(feature = "settings")]
+                {
+                    crate::settings::reload_changed();
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::FetchFinished => {
+                #[cfg(feature = "fetch")]
+                {
+                    crate::fetch::deliver();
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::WebSocketEvent => {
+                #[cfg(feature = "websocket")]
+                {
+                    crate::websocket::deliver();
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::ProcessOutput => {
+                crate::process::deliver();
+                self.render_context.request_render();
+            }
+            RinchEvent::PollTasks => {
+                let ran = catch(poll_tasks).unwrap_or_else(|crash| {
+                    self.show_crash_in_current_window(crash);
+                    true
+                });
+                if ran {
+                    self.render_context.request_render();
+                }
+            }
+            RinchEvent::Quit => {
+                if self.confirm_quit() {
                     event_loop.exit();
                 }
             }
+            RinchEvent::SetMainWindowVisible(visible) => {
+                self.set_main_window_visible(visible);
+            }
+            RinchEvent::TrayEvent => {
+                #[cfg(feature = "system-tray")]
+                {
+                    let ran = catch(crate::tray::deliver).unwrap_or_else(|crash| {
+                        self.show_crash_in_current_window(crash);
+                        true
+                    });
+                    if ran {
+                        self.render_context.request_render();
+                    }
+                }
+            }
+            RinchEvent::DeepLink(url) => {
+                tracing::debug!("Deep link: {}", url);
+                push_deep_link(url);
+                self.render_context.request_render();
+            }
+        }
+
+        // Handlers may have asked to scroll or focus something. If they also
+        // changed state, wait for the re-render so the target is up to date.
+        if !self.render_context.render_pending() {
+            self.process_scroll_requests();
+            self.process_focus_requests();
+            self.process_remeasure_requests();
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         // Poll menu events
         self.poll_menu_events();
 
@@ -1095,24 +2719,62 @@ impl ApplicationHandler<RinchEvent> for Runtime {
         if let Some(reloader) = &mut self.hot_reloader {
             reloader.poll();
         }
+
+        self.update_reduced_motion();
+        self.tick_animations(event_loop);
+        self.tick_timers(event_loop);
+        self.refresh_devtools(event_loop);
+        deliver_announcements();
+        report_missing_translations();
+
+        // Repaint for animated styles set by handlers or paint frames
+        if let Some(styles) = take_animated_styles() {
+            self.window_manager.set_animated_styles(&styles);
+        }
     }
 }
 
-/// Convert element children to an HTML string for blitz.
-fn children_to_html(children: &[Element]) -> String {
-    let mut html = String::new();
-    for child in children {
-        match child {
-            Element::Html(content) => {
-                html.push_str(content);
-            }
-            Element::Fragment(kids) => {
-                html.push_str(&children_to_html(kids));
-            }
-            _ => {}
+/// Show a window and bring it to the front, or hide it.
+fn show_window(window: &super::window_manager::ManagedWindow, visible: bool) {
+    window.window.set_visible(visible);
+    if visible {
+        window.window.set_minimized(false);
+        window.window.focus_window();
+    }
+}
+
+/// Pass on screen reader announcements. There's no accessibility tree to
+/// hand them to yet, so they're only logged.
+fn deliver_announcements() {
+    for announcement in take_announcements() {
+        tracing::debug!(
+            target: "rinch::a11y",
+            politeness = ?announcement.politeness,
+            "{}",
+            announcement.message
+        );
+    }
+}
+
+/// Warn about messages the current locale doesn't translate.
+fn report_missing_translations() {
+    for missing in take_missing_translations() {
+        match missing.fallback {
+            Some(fallback) => tracing::warn!(
+                target: "rinch::i18n",
+                "`{}` isn't translated into {}; using {}",
+                missing.id,
+                missing.locale,
+                fallback
+            ),
+            None => tracing::warn!(
+                target: "rinch::i18n",
+                "`{}` isn't translated into {} or the fallback locale",
+                missing.id,
+                missing.locale
+            ),
         }
     }
-    html
 }
 
 /// Run the application with the given root element.
@@ -1120,13 +2782,64 @@ pub fn run<F>(app: F)
 where
     F: Fn() -> Element + 'static,
 {
-    run_internal(app, false);
+    run_internal(app, EventLoop::with_user_event(), None, None);
+}
+
+/// Run the application unless an instance of it is already running.
+///
+/// A second launch (a file double-clicked in a file manager, say) forwards
+/// its command-line arguments to the running instance and exits; the
+/// running instance brings its main window forward and calls
+/// `on_second_instance` with them. Relative paths to existing files are
+/// made absolute first. The handler isn't called for this instance's own
+/// arguments.
+///
+/// Instances are told apart by executable name and user. If the channel
+/// between them can't be set up, the app runs normally.
+///
+/// # Example
+///
+/// ```ignore
+/// fn main() {
+///     rinch::run_single_instance(app, |args| {
+///         for path in args.iter().filter(|arg| !arg.starts_with('-')) {
+///             open_document(path);
+///         }
+///     });
+/// }
+/// ```
+pub fn run_single_instance<F, H>(app: F, on_second_instance: H)
+where
+    F: Fn() -> Element + 'static,
+    H: Fn(Vec<String>) + 'static,
+{
+    use super::single_instance::{claim, forwarded_args, instance_id, Claim};
+
+    init_tracing();
+    match claim(&instance_id(), &forwarded_args()) {
+        Ok(Claim::Primary(listener)) => {
+            run_internal(
+                app,
+                EventLoop::with_user_event(),
+                None,
+                Some((listener, Box::new(on_second_instance))),
+            );
+        }
+        Ok(Claim::Forwarded) => {
+            tracing::info!("Passed the arguments to the running instance");
+        }
+        Err(e) => {
+            tracing::warn!("Couldn't check for a running instance, starting anyway: {e}");
+            run_internal(app, EventLoop::with_user_event(), None, None);
+        }
+    }
 }
 
 /// Run the application with hot reloading enabled.
 ///
-/// When files in the `src` directory change, the UI will automatically re-render.
-/// This is useful during development to see changes without restarting.
+/// When a stylesheet changes, it's applied in place. When Rust source
+/// changes, the app is rebuilt and restarted with the new code, keeping its
+/// signals, windows and scroll positions.
 ///
 /// # Example
 ///
@@ -1140,19 +2853,138 @@ pub fn run_with_hot_reload<F>(app: F)
 where
     F: Fn() -> Element + 'static,
 {
-    run_internal(app, true);
+    run_with_hot_reload_config(super::hot_reload::HotReloadConfig::default(), app);
 }
 
-fn run_internal<F>(app: F, #[allow(unused)] enable_hot_reload: bool)
+/// Run the application with hot reloading configured by `config`, such as
+/// to watch other paths or rebuild with another command.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::shell::HotReloadConfig;
+///
+/// fn main() {
+///     let config = HotReloadConfig::default()
+///         .with_build_command(vec!["cargo".into(), "build".into(), "--features".into(), "dev".into()]);
+///     rinch::run_with_hot_reload_config(config, app);
+/// }
+/// ```
+#[cfg(feature = "hot-reload")]
+pub fn run_with_hot_reload_config<F>(config: super::hot_reload::HotReloadConfig, app: F)
+where
+    F: Fn() -> Element + 'static,
+{
+    run_internal(app, EventLoop::with_user_event(), Some(config), None);
+}
+
+/// Run the application as an Android app, from the `android_main` the
+/// activity starts.
+///
+/// Windows fill the screen. The app is suspended while it's in the
+/// background: its windows stop rendering and give up their surfaces, and
+/// `on_suspend` and `on_resume` handlers run.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::shell::AndroidApp;
+///
+/// #[unsafe(no_mangle)]
+/// fn android_main(android_app: AndroidApp) {
+///     rinch::shell::run_android(android_app, app);
+/// }
+/// ```
+#[cfg(target_os = "android")]
+pub fn run_android<F>(android_app: winit::platform::android::activity::AndroidApp, app: F)
 where
     F: Fn() -> Element + 'static,
 {
-    // Initialize tracing
-    let _ = tracing_subscriber::fmt::try_init();
+    use winit::platform::android::EventLoopBuilderExtAndroid;
+
+    let mut event_loop = EventLoop::with_user_event();
+    event_loop.with_android_app(android_app);
+    run_internal(app, event_loop, None, None);
+}
+
+/// Hot reload settings, when the feature is enabled.
+#[cfg(feature = "hot-reload")]
+type HotReload = super::hot_reload::HotReloadConfig;
+#[cfg(not(feature = "hot-reload"))]
+type HotReload = ();
+
+/// The first instance's listener and the app's handler for later instances.
+type SingleInstance = (
+    super::single_instance::InstanceListener,
+    Box<dyn Fn(Vec<String>)>,
+);
+
+/// Initialize tracing, with rinch's layer for frame traces.
+fn init_tracing() {
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
+        .with(crate::debug::trace_layer())
+        .try_init();
+}
+
+fn run_internal<F>(
+    app: F,
+    event_loop: EventLoopBuilder<RinchEvent>,
+    hot_reload: Option<HotReload>,
+    single_instance: Option<SingleInstance>,
+) where
+    F: Fn() -> Element + 'static,
+{
+    let (event_loop, mut runtime) = start(app, event_loop, hot_reload, single_instance);
+    event_loop.run_app(&mut runtime).expect("Event loop error");
+    let _ = crate::debug::stop_trace();
+
+    // Close the windows, then start the rebuilt app
+    #[cfg(feature = "hot-reload")]
+    if let Some(state) = runtime.hot_restart.take() {
+        drop(runtime);
+        if let Err(e) = std::env::current_exe().and_then(|exe| state.relaunch(&exe)) {
+            tracing::error!("Hot reload: couldn't start the rebuilt app: {e}");
+        }
+    }
+}
+
+/// Render the app for the first time and build the event loop to run it.
+pub(crate) fn start<F>(
+    app: F,
+    mut event_loop: EventLoopBuilder<RinchEvent>,
+    #[allow(unused)] hot_reload: Option<HotReload>,
+    single_instance: Option<SingleInstance>,
+) -> (EventLoop<RinchEvent>, Runtime)
+where
+    F: Fn() -> Element + 'static,
+{
+    init_tracing();
+    if let Some(path) = std::env::var_os("RINCH_TRACE")
+        && let Err(e) = crate::debug::start_trace(&path)
+    {
+        tracing::warn!("Couldn't start a trace at {}: {e}", path.to_string_lossy());
+    }
 
     // Clear any stale state from previous runs
     clear_handlers();
     clear_hooks();
+    super::crash::install_hook();
+
+    // Pick up the state of the app this rebuild replaced
+    #[cfg(feature = "hot-reload")]
+    let restored = hot_reload
+        .as_ref()
+        .and_then(|_| super::hot_restart::SavedState::take())
+        .unwrap_or_default();
+    #[cfg(feature = "hot-reload")]
+    rinch_core::hooks::restore_hooks(restored.hooks.clone());
+
+    // Links the app was launched with are there for the first render
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for url in super::deep_link::deep_links_in(&args) {
+        push_deep_link(url);
+    }
 
     // Build the initial element tree
     begin_render();
@@ -1163,25 +2995,48 @@ where
     let mut runtime = Runtime::new();
     runtime.set_app_fn(app);
     runtime.process_element(root);
+    #[cfg(feature = "hot-reload")]
+    {
+        runtime.restored = restored;
+    }
 
     // Create event loop
-    let event_loop = EventLoop::<RinchEvent>::with_user_event()
-        .build()
-        .expect("Failed to create event loop");
+    let event_loop = event_loop.build().expect("Failed to create event loop");
 
     let proxy = event_loop.create_proxy();
     runtime.proxy = Some(proxy.clone());
     runtime.render_context.set_proxy(proxy.clone());
 
+    // Listen for later instances
+    if let Some((listener, on_second_instance)) = single_instance {
+        listener.listen(proxy.clone());
+        runtime.on_second_instance = Some(on_second_instance);
+    }
+
+    super::power::watch(proxy.clone());
+    #[cfg(feature = "settings")]
+    crate::settings::watch(proxy.clone());
+
+    // Wake the loop to poll `spawn_local` tasks
+    let task_proxy = Mutex::new(proxy.clone());
+    set_wake_handler(move || {
+        let proxy = task_proxy.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = proxy.send_event(RinchEvent::PollTasks);
+    });
+
+    // Wake the loop for tray icon clicks and menu items
+    #[cfg(feature = "system-tray")]
+    crate::tray::set_event_proxy(proxy.clone());
+
     // Set proxy for window management API
     crate::windows::set_event_proxy(proxy);
 
     // Enable hot reload if requested
     #[cfg(feature = "hot-reload")]
-    if enable_hot_reload {
-        runtime.enable_hot_reload(super::hot_reload::HotReloadConfig::default());
+    if let Some(config) = hot_reload {
+        runtime.enable_hot_reload(config);
     }
 
     event_loop.set_control_flow(ControlFlow::Wait);
-    event_loop.run_app(&mut runtime).expect("Event loop error");
+    (event_loop, runtime)
 }
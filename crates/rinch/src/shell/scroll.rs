@@ -0,0 +1,697 @@
+//! Scroll tracking.
+//!
+//! Elements with an `onscroll` handler or a `node_ref` are watched: after each
+//! layout their scroll state (and, for refs, their size, rect and watched
+//! visibility) is compared with the last one seen, and changes are reported to the runtime, which runs the
+//! handler and updates the ref.
+//!
+//! Documents are rebuilt when the app's HTML changes, which would reset every
+//! scroll position, so positions are carried over from the old document to
+//! the new one.
+//!
+//! Programmatic scrolling (`NodeRef::scroll_into_view` and `scroll_to`) is
+//! computed here too, and smooth scrolls are animated by [`ScrollAnimations`]
+//! on each redraw.
+
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::{EventHandlerId, ScrollEvent};
+use rinch_core::hot_state::HotValue;
+use rinch_core::node_ref::{
+    measures_rect, visibility_query, LayoutRect, ScrollAlignment, ScrollOptions, Visibility,
+    NODE_REF_ATTR,
+};
+
+use super::visibility::element_visibility;
+
+/// Attribute holding an element's `onscroll` handler ID.
+const ONSCROLL_ATTR: &str = "data-onscroll";
+
+/// How long a smooth scroll takes.
+const SMOOTH_SCROLL_DURATION: Duration = Duration::from_millis(300);
+
+/// A watched element's scroll state changed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ScrollChange {
+    /// The element's `onscroll` handler, unless this is the first report.
+    pub handler_id: Option<EventHandlerId>,
+    /// The element's node ref.
+    pub node_ref: Option<u64>,
+    /// The new scroll state.
+    pub event: ScrollEvent,
+}
+
+#[derive(Debug)]
+struct Watched {
+    node_id: usize,
+    handler_id: Option<EventHandlerId>,
+    node_ref: Option<u64>,
+    /// Last state reported, or `None` before the first layout.
+    last: Option<ScrollEvent>,
+    /// Last content-box size reported to the ref.
+    last_size: Option<(f64, f64)>,
+    /// Last border box reported to the ref.
+    last_rect: Option<LayoutRect>,
+    /// Last visibility reported to the ref.
+    last_visibility: Option<Visibility>,
+}
+
+/// Elements whose scrolling the app is listening to.
+#[derive(Debug, Default)]
+pub(crate) struct ScrollWatch {
+    watched: Vec<Watched>,
+}
+
+impl ScrollWatch {
+    /// Find the watched elements in a freshly built document.
+    pub(crate) fn new(doc: &BaseDocument) -> Self {
+        let mut watched = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            if let Some(el) = node.element_data() {
+                let attr = |name: &str| {
+                    el.attrs()
+                        .iter()
+                        .find(|a| a.name.local.as_ref() == name)
+                        .and_then(|a| a.value.parse().ok())
+                };
+                let handler_id = attr(ONSCROLL_ATTR).map(EventHandlerId);
+                let node_ref = attr(NODE_REF_ATTR).map(|id: usize| id as u64);
+                if handler_id.is_some() || node_ref.is_some() {
+                    watched.push(Watched {
+                        node_id,
+                        handler_id,
+                        node_ref,
+                        last: None,
+                        last_size: None,
+                        last_rect: None,
+                        last_visibility: None,
+                    });
+                }
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        Self { watched }
+    }
+
+    /// The element whose `onscroll` is `handler_id`.
+    pub(crate) fn node_for_handler(&self, handler_id: EventHandlerId) -> Option<usize> {
+        self.watched
+            .iter()
+            .find(|watched| watched.handler_id == Some(handler_id))
+            .map(|watched| watched.node_id)
+    }
+
+    /// The element a node ref is attached to.
+    pub(crate) fn node_for_ref(&self, node_ref: u64) -> Option<usize> {
+        self.watched
+            .iter()
+            .find(|watched| watched.node_ref == Some(node_ref))
+            .map(|watched| watched.node_id)
+    }
+
+    /// Collect watched elements whose scroll state changed. Must run after layout.
+    ///
+    /// The first report for an element only updates its ref: the element
+    /// appeared rather than scrolled, so its handler isn't called.
+    pub(crate) fn changes(&mut self, doc: &BaseDocument) -> Vec<ScrollChange> {
+        let mut changes = Vec::new();
+        for watched in &mut self.watched {
+            let event = scroll_state(doc, watched.node_id);
+            if watched.last == Some(event) {
+                continue;
+            }
+            let first = watched.last.is_none();
+            watched.last = Some(event);
+            if first && watched.node_ref.is_none() {
+                continue;
+            }
+            changes.push(ScrollChange {
+                handler_id: if first { None } else { watched.handler_id },
+                node_ref: watched.node_ref,
+                event,
+            });
+        }
+        changes
+    }
+
+    /// Collect node refs whose element's content-box size changed, as
+    /// `(ref ID, size)`. Must run after layout.
+    pub(crate) fn resizes(&mut self, doc: &BaseDocument) -> Vec<(u64, (f64, f64))> {
+        let mut resizes = Vec::new();
+        for watched in &mut self.watched {
+            let (Some(node_ref), Some(node)) = (watched.node_ref, doc.get_node(watched.node_id))
+            else {
+                continue;
+            };
+            let layout = &node.final_layout;
+            let size = (
+                (layout.size.width
+                    - layout.padding.left
+                    - layout.padding.right
+                    - layout.border.left
+                    - layout.border.right)
+                    .max(0.0) as f64,
+                (layout.size.height
+                    - layout.padding.top
+                    - layout.padding.bottom
+                    - layout.border.top
+                    - layout.border.bottom)
+                    .max(0.0) as f64,
+            );
+            if watched.last_size != Some(size) {
+                watched.last_size = Some(size);
+                resizes.push((node_ref, size));
+            }
+        }
+        resizes
+    }
+
+    /// Collect node refs whose element moved or resized in the window, as
+    /// `(ref ID, border box)`, for refs the app has read the rect of. Must
+    /// run after layout.
+    pub(crate) fn moves(&mut self, doc: &BaseDocument) -> Vec<(u64, LayoutRect)> {
+        let mut moves = Vec::new();
+        for watched in &mut self.watched {
+            let (Some(node_ref), Some(node)) = (watched.node_ref, doc.get_node(watched.node_id))
+            else {
+                continue;
+            };
+            if !measures_rect(node_ref) {
+                continue;
+            }
+            let (x, y) = border_box_origin(doc, watched.node_id);
+            let rect = LayoutRect {
+                x,
+                y,
+                width: node.final_layout.size.width as f64,
+                height: node.final_layout.size.height as f64,
+            };
+            if watched.last_rect != Some(rect) {
+                watched.last_rect = Some(rect);
+                moves.push((node_ref, rect));
+            }
+        }
+        moves
+    }
+
+    /// Collect node refs whose watched visibility changed, as
+    /// `(ref ID, visibility)`. Must run after layout.
+    pub(crate) fn visibility_changes(&mut self, doc: &BaseDocument) -> Vec<(u64, Visibility)> {
+        let mut changes = Vec::new();
+        for index in 0..self.watched.len() {
+            let Some(node_ref) = self.watched[index].node_ref else {
+                continue;
+            };
+            let Some(query) = visibility_query(node_ref) else {
+                continue;
+            };
+            let root = match query.root {
+                Some(root_ref) => match self.node_for_ref(root_ref) {
+                    Some(root) => Some(root),
+                    // The root is in another window
+                    None => continue,
+                },
+                None => None,
+            };
+            let watched = &mut self.watched[index];
+            let visibility = element_visibility(doc, watched.node_id, root, query.margins);
+            if watched.last_visibility != Some(visibility) {
+                watched.last_visibility = Some(visibility);
+                changes.push((node_ref, visibility));
+            }
+        }
+        changes
+    }
+}
+
+/// Something that scrolls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Scroller {
+    /// The window's viewport.
+    Viewport,
+    /// A scroll container element.
+    Node(usize),
+}
+
+impl Scroller {
+    /// The scroller for an element: the viewport for `html` and `body`.
+    pub(crate) fn for_node(doc: &BaseDocument, node_id: usize) -> Self {
+        if scrolls_viewport(doc, node_id) {
+            Scroller::Viewport
+        } else {
+            Scroller::Node(node_id)
+        }
+    }
+
+    /// Current scroll state.
+    pub(crate) fn state(self, doc: &BaseDocument) -> ScrollEvent {
+        match self {
+            Scroller::Viewport => scroll_state(doc, doc.root_element().id),
+            Scroller::Node(node_id) => scroll_state(doc, node_id),
+        }
+    }
+
+    /// Window-relative origin of the scroller's visible area (inside its
+    /// borders).
+    pub(crate) fn client_origin(self, doc: &BaseDocument) -> (f64, f64) {
+        match self {
+            Scroller::Viewport => (0.0, 0.0),
+            Scroller::Node(node_id) => {
+                let (x, y) = border_box_origin(doc, node_id);
+                let (left, top) = doc
+                    .get_node(node_id)
+                    .map(|n| (n.final_layout.border.left, n.final_layout.border.top))
+                    .unwrap_or_default();
+                (x + left as f64, y + top as f64)
+            }
+        }
+    }
+
+    /// Scroll to an offset, limited to how far the scroller can scroll.
+    pub(crate) fn scroll_to(self, doc: &mut BaseDocument, (x, y): (f64, f64)) {
+        let state = self.state(doc);
+        let x = x.clamp(0.0, state.max_scroll_x());
+        let y = y.clamp(0.0, state.max_scroll_y());
+        self.set_offset(doc, (x, y));
+    }
+
+    /// Set the scroll offset as is, even past the scroller's edges.
+    pub(crate) fn set_offset(self, doc: &mut BaseDocument, (x, y): (f64, f64)) {
+        match self {
+            Scroller::Viewport => {
+                let mut viewport = doc.viewport_scroll();
+                viewport.x = x;
+                viewport.y = y;
+                doc.set_viewport_scroll(viewport);
+            }
+            Scroller::Node(node_id) => {
+                if let Some(node) = doc.get_node_mut(node_id) {
+                    node.scroll_offset.x = x;
+                    node.scroll_offset.y = y;
+                }
+            }
+        }
+    }
+
+    /// Where to find the scroller again after the document is rebuilt.
+    fn path(self, doc: &BaseDocument) -> Option<Vec<usize>> {
+        match self {
+            Scroller::Viewport => None,
+            Scroller::Node(node_id) => Some(node_path(doc, node_id)),
+        }
+    }
+
+    fn from_path(doc: &BaseDocument, path: &Option<Vec<usize>>) -> Option<Self> {
+        match path {
+            None => Some(Scroller::Viewport),
+            Some(path) => node_at_path(doc, path).map(Scroller::Node),
+        }
+    }
+
+    /// Whether a window-relative point is over the scroller, including its
+    /// scrollbars.
+    fn contains(self, doc: &BaseDocument, (x, y): (f64, f64)) -> bool {
+        match self {
+            Scroller::Viewport => true,
+            Scroller::Node(node_id) => {
+                let Some(node) = doc.get_node(node_id) else {
+                    return false;
+                };
+                let (left, top) = border_box_origin(doc, node_id);
+                let size = node.final_layout.size;
+                (left..left + size.width as f64).contains(&x)
+                    && (top..top + size.height as f64).contains(&y)
+            }
+        }
+    }
+}
+
+/// Whether scrolling a node scrolls the viewport (`<html>` and `<body>`).
+pub(crate) fn scrolls_viewport(doc: &BaseDocument, node_id: usize) -> bool {
+    doc.get_node(node_id)
+        .and_then(|node| node.element_data())
+        .is_some_and(|el| matches!(el.name.local.as_ref(), "html" | "body"))
+}
+
+/// Whether a node scrolls its own content (`overflow` other than `visible`).
+pub(crate) fn is_scroll_container(doc: &BaseDocument, node_id: usize) -> bool {
+    !scrolls_viewport(doc, node_id)
+        && doc.get_node(node_id).is_some_and(|node| {
+            node.style.overflow.x.is_scroll_container()
+                || node.style.overflow.y.is_scroll_container()
+        })
+}
+
+/// Window-relative position of a node's border box, after scrolling.
+pub(crate) fn border_box_origin(doc: &BaseDocument, node_id: usize) -> (f64, f64) {
+    let Some(node) = doc.get_node(node_id) else {
+        return (0.0, 0.0);
+    };
+    let position = node.absolute_position(0.0, 0.0);
+    let (mut x, mut y) = (position.x as f64, position.y as f64);
+
+    // Layout positions ignore scrolling, which happens at paint time
+    let mut current = node.parent;
+    while let Some(id) = current {
+        let Some(ancestor) = doc.get_node(id) else {
+            break;
+        };
+        x -= ancestor.scroll_offset.x;
+        y -= ancestor.scroll_offset.y;
+        current = ancestor.parent;
+    }
+    let viewport_scroll = doc.viewport_scroll();
+    (x - viewport_scroll.x, y - viewport_scroll.y)
+}
+
+/// The offset that brings an element into view along one axis.
+///
+/// `start` and `len` are the element's position within the scrolled content
+/// and its size, `view` is the size of the visible area and `offset` the
+/// current scroll offset.
+fn aligned_offset(offset: f64, view: f64, start: f64, len: f64, alignment: ScrollAlignment) -> f64 {
+    let end = start + len;
+    match alignment {
+        ScrollAlignment::Start => start,
+        ScrollAlignment::Center => start + len / 2.0 - view / 2.0,
+        ScrollAlignment::End => end - view,
+        ScrollAlignment::Nearest => {
+            if start < offset || len > view {
+                start
+            } else if end > offset + view {
+                end - view
+            } else {
+                offset
+            }
+        }
+    }
+}
+
+/// Offsets that bring a node into view, for each scroller that has to move,
+/// innermost first.
+pub(crate) fn scroll_into_view_offsets(
+    doc: &BaseDocument,
+    node_id: usize,
+    options: ScrollOptions,
+) -> Vec<(Scroller, (f64, f64))> {
+    let Some(node) = doc.get_node(node_id) else {
+        return Vec::new();
+    };
+    let (mut x, mut y) = border_box_origin(doc, node_id);
+    let width = node.final_layout.size.width as f64;
+    let height = node.final_layout.size.height as f64;
+
+    let mut scrollers = Vec::new();
+    let mut current = node.parent;
+    while let Some(id) = current {
+        if is_scroll_container(doc, id) {
+            scrollers.push(Scroller::Node(id));
+        }
+        current = doc.get_node(id).and_then(|ancestor| ancestor.parent);
+    }
+    scrollers.push(Scroller::Viewport);
+
+    let mut offsets = Vec::new();
+    for scroller in scrollers {
+        let (view_x, view_y) = scroller.client_origin(doc);
+        let state = scroller.state(doc);
+        let new_x = aligned_offset(
+            state.scroll_x,
+            state.client_width,
+            x - view_x + state.scroll_x,
+            width,
+            options.inline,
+        )
+        .clamp(0.0, state.max_scroll_x());
+        let new_y = aligned_offset(
+            state.scroll_y,
+            state.client_height,
+            y - view_y + state.scroll_y,
+            height,
+            options.block,
+        )
+        .clamp(0.0, state.max_scroll_y());
+
+        // The element moves with the content, which outer scrollers see
+        x -= new_x - state.scroll_x;
+        y -= new_y - state.scroll_y;
+        if (new_x, new_y) != (state.scroll_x, state.scroll_y) {
+            offsets.push((scroller, (new_x, new_y)));
+        }
+    }
+    offsets
+}
+
+/// A smooth scroll in progress.
+#[derive(Debug)]
+struct ScrollAnimation {
+    /// Tree position of the scroller (`None` for the viewport), so the
+    /// animation survives document rebuilds.
+    scroller: Option<Vec<usize>>,
+    from: (f64, f64),
+    to: (f64, f64),
+    start: Instant,
+}
+
+/// Smooth scrolls in progress in a window.
+#[derive(Debug, Default)]
+pub(crate) struct ScrollAnimations {
+    active: Vec<ScrollAnimation>,
+}
+
+impl ScrollAnimations {
+    /// Whether any scroll is animating.
+    pub(crate) fn is_active(&self) -> bool {
+        !self.active.is_empty()
+    }
+
+    /// Start animating a scroller to an offset, replacing any animation it
+    /// already has.
+    pub(crate) fn start(&mut self, doc: &BaseDocument, scroller: Scroller, to: (f64, f64)) {
+        let path = scroller.path(doc);
+        self.active.retain(|animation| animation.scroller != path);
+        let state = scroller.state(doc);
+        self.active.push(ScrollAnimation {
+            scroller: path,
+            from: (state.scroll_x, state.scroll_y),
+            to,
+            start: Instant::now(),
+        });
+    }
+
+    /// Stop animating scrollers under a window-relative point, because the
+    /// user is scrolling them (with the wheel or by grabbing a scrollbar).
+    pub(crate) fn cancel_at(&mut self, doc: &BaseDocument, point: (f64, f64)) {
+        self.active.retain(|animation| {
+            Scroller::from_path(doc, &animation.scroller)
+                .is_some_and(|scroller| !scroller.contains(doc, point))
+        });
+    }
+
+    /// Move animated scrollers to where they should be now, dropping
+    /// finished animations.
+    pub(crate) fn tick(&mut self, doc: &mut BaseDocument) {
+        let now = Instant::now();
+        self.active.retain(|animation| {
+            let Some(scroller) = Scroller::from_path(doc, &animation.scroller) else {
+                return false;
+            };
+            let t = (now.duration_since(animation.start).as_secs_f64()
+                / SMOOTH_SCROLL_DURATION.as_secs_f64())
+            .min(1.0);
+            let eased = ease_out_cubic(t);
+            let (from_x, from_y) = animation.from;
+            let (to_x, to_y) = animation.to;
+            scroller.scroll_to(
+                doc,
+                (
+                    from_x + (to_x - from_x) * eased,
+                    from_y + (to_y - from_y) * eased,
+                ),
+            );
+            t < 1.0
+        });
+    }
+}
+
+/// Ease-out cubic: fast at first, settling gently.
+pub(crate) fn ease_out_cubic(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// An element's current scroll state, in logical pixels.
+pub(crate) fn scroll_state(doc: &BaseDocument, node_id: usize) -> ScrollEvent {
+    let Some(node) = doc.get_node(node_id) else {
+        return ScrollEvent::default();
+    };
+    let layout = &node.final_layout;
+
+    if scrolls_viewport(doc, node_id) {
+        let (width, height) = doc.viewport().window_size;
+        let scale = doc.viewport().scale_f64();
+        let scroll = doc.viewport_scroll();
+        let root = doc.root_element();
+        let root_layout = &root.final_layout;
+        return ScrollEvent {
+            scroll_x: scroll.x,
+            scroll_y: scroll.y,
+            content_width: root_layout.content_size.width.max(root_layout.size.width) as f64,
+            content_height: root_layout.content_size.height.max(root_layout.size.height) as f64,
+            client_width: width as f64 / scale,
+            client_height: height as f64 / scale,
+        };
+    }
+
+    let client_width = layout.size.width - layout.border.left - layout.border.right;
+    let client_height = layout.size.height - layout.border.top - layout.border.bottom;
+    ScrollEvent {
+        scroll_x: node.scroll_offset.x,
+        scroll_y: node.scroll_offset.y,
+        content_width: layout.content_size.width.max(client_width) as f64,
+        content_height: layout.content_size.height.max(client_height) as f64,
+        client_width: client_width as f64,
+        client_height: client_height as f64,
+    }
+}
+
+/// Scroll positions of a document, to carry over to its replacement.
+///
+/// Scrolled elements are identified by their position in the tree, so a
+/// container keeps its offset as long as the structure above it is unchanged.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ScrollSnapshot {
+    viewport: (f64, f64),
+    /// Child-index path from the root and offset of each scrolled element.
+    nodes: Vec<(Vec<usize>, (f64, f64))>,
+}
+
+impl ScrollSnapshot {
+    /// Record a document's scroll positions.
+    pub(crate) fn take(doc: &BaseDocument) -> Self {
+        let viewport = doc.viewport_scroll();
+        let mut nodes = Vec::new();
+        let mut stack = vec![(0, Vec::new())];
+        while let Some((node_id, path)) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            let offset = (node.scroll_offset.x, node.scroll_offset.y);
+            if offset != (0.0, 0.0) {
+                nodes.push((path.clone(), offset));
+            }
+            for (index, child) in node.children.iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                stack.push((*child, child_path));
+            }
+        }
+        Self {
+            viewport: (viewport.x, viewport.y),
+            nodes,
+        }
+    }
+
+    /// Apply the recorded positions to a new document. Must run after layout,
+    /// so offsets can be limited to how far each element can now scroll.
+    pub(crate) fn restore(&self, doc: &mut BaseDocument) {
+        for (path, &(x, y)) in &self.nodes {
+            let Some(node_id) = node_at_path(doc, path) else {
+                continue;
+            };
+            let state = scroll_state(doc, node_id);
+            if let Some(node) = doc.get_node_mut(node_id) {
+                node.scroll_offset.x = x.clamp(0.0, state.max_scroll_x());
+                node.scroll_offset.y = y.clamp(0.0, state.max_scroll_y());
+            }
+        }
+
+        let root = doc.root_element().id;
+        let state = scroll_state(doc, root);
+        let mut viewport = doc.viewport_scroll();
+        viewport.x = self.viewport.0.clamp(0.0, state.max_scroll_x());
+        viewport.y = self.viewport.1.clamp(0.0, state.max_scroll_y());
+        doc.set_viewport_scroll(viewport);
+    }
+}
+
+impl HotValue for ScrollSnapshot {
+    fn to_hot(&self) -> String {
+        (self.viewport, self.nodes.clone()).to_hot()
+    }
+
+    fn from_hot(text: &str) -> Option<Self> {
+        let (viewport, nodes) = HotValue::from_hot(text)?;
+        Some(Self { viewport, nodes })
+    }
+}
+
+/// Child-index path from the root to a node.
+pub(crate) fn node_path(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current = node_id;
+    while let Some(parent_id) = doc.get_node(current).and_then(|node| node.parent) {
+        let Some(index) = doc
+            .get_node(parent_id)
+            .and_then(|parent| parent.children.iter().position(|&child| child == current))
+        else {
+            break;
+        };
+        path.push(index);
+        current = parent_id;
+    }
+    path.reverse();
+    path
+}
+
+/// Follow a child-index path from the root.
+pub(crate) fn node_at_path(doc: &BaseDocument, path: &[usize]) -> Option<usize> {
+    path.iter().try_fold(0, |node_id, &index| {
+        doc.get_node(node_id)?.children.get(index).copied()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_scrolls_as_little_as_possible() {
+        let nearest = ScrollAlignment::Nearest;
+        // Already visible
+        assert_eq!(aligned_offset(100.0, 200.0, 150.0, 20.0, nearest), 100.0);
+        // Above the visible area: align its top
+        assert_eq!(aligned_offset(100.0, 200.0, 40.0, 20.0, nearest), 40.0);
+        // Below: align its bottom
+        assert_eq!(aligned_offset(100.0, 200.0, 400.0, 20.0, nearest), 220.0);
+        // Taller than the visible area: align its top
+        assert_eq!(aligned_offset(100.0, 200.0, 150.0, 500.0, nearest), 150.0);
+    }
+
+    #[test]
+    fn explicit_alignments() {
+        assert_eq!(
+            aligned_offset(0.0, 200.0, 400.0, 20.0, ScrollAlignment::Start),
+            400.0
+        );
+        assert_eq!(
+            aligned_offset(0.0, 200.0, 400.0, 20.0, ScrollAlignment::Center),
+            310.0
+        );
+        assert_eq!(
+            aligned_offset(0.0, 200.0, 400.0, 20.0, ScrollAlignment::End),
+            220.0
+        );
+    }
+
+    #[test]
+    fn easing_starts_and_ends_in_place() {
+        assert_eq!(ease_out_cubic(0.0), 0.0);
+        assert_eq!(ease_out_cubic(1.0), 1.0);
+        assert!(ease_out_cubic(0.5) > 0.5);
+    }
+}
@@ -0,0 +1,557 @@
+//! Styled and overlay scrollbars.
+//!
+//! When a window's CSS styles scrollbars, or the window asks for overlay
+//! scrollbars, rinch paints scroll containers' scrollbars itself, over the
+//! scene. Supported styling:
+//!
+//! - `scrollbar-color: <thumb> <track>` and `scrollbar-width: auto | thin | none`
+//! - `::-webkit-scrollbar { width; height; display: none }`
+//! - `::-webkit-scrollbar-thumb { background; border-radius }`
+//! - `::-webkit-scrollbar-track { background }`
+//!
+//! The standard properties win over `::-webkit-scrollbar` rules when both
+//! are set, as in browsers. The style engine doesn't match scrollbar
+//! pseudo-elements, so a generated stylesheet copies their rules onto their
+//! element, as `--rinch-webkit-scrollbar-*` custom properties, and the
+//! styles are read from elements' computed values.
+//!
+//! Overlay scrollbars take no layout space: a generated stylesheet sets
+//! `scrollbar-width: none` everywhere, and thin bars are drawn over the
+//! content while it scrolls, fading out once it stops.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use peniko::kurbo::{Affine, Rect, RoundedRect};
+use peniko::{Color, Fill};
+use rinch_core::events::ScrollEvent;
+
+use super::cascade::{matching_brace, parse_declarations, strip_comments};
+use super::computed::{computed_value, computed_values};
+use super::scroll::{border_box_origin, scrolls_viewport, Scroller};
+use super::text_effects::split_outside_parens;
+
+/// Prefix of the properties scrollbar pseudo-element rules are rewritten to.
+const PROPERTY_PREFIX: &str = "--rinch-webkit-scrollbar";
+
+/// The properties a scroller's scrollbar styles are read from.
+const SCROLLBAR_PROPERTIES: &[&str] = &[
+    "overflow-x",
+    "overflow-y",
+    "scrollbar-width",
+    "--rinch-webkit-scrollbar-width",
+    "--rinch-webkit-scrollbar-height",
+    "--rinch-webkit-scrollbar-display",
+    "--rinch-webkit-scrollbar-thumb-background-color",
+    "--rinch-webkit-scrollbar-thumb-background",
+    "--rinch-webkit-scrollbar-track-background-color",
+    "--rinch-webkit-scrollbar-track-background",
+    "--rinch-webkit-scrollbar-thumb-border-radius",
+];
+
+/// User-agent stylesheet keeping the rewritten scrollbar properties, which
+/// are custom properties, from inheriting.
+pub(crate) const WEBKIT_SCROLLBAR_RESET_CSS: &str = "* {
+    --rinch-webkit-scrollbar-width: initial;
+    --rinch-webkit-scrollbar-height: initial;
+    --rinch-webkit-scrollbar-display: initial;
+    --rinch-webkit-scrollbar-thumb-background-color: initial;
+    --rinch-webkit-scrollbar-thumb-background: initial;
+    --rinch-webkit-scrollbar-track-background-color: initial;
+    --rinch-webkit-scrollbar-track-background: initial;
+    --rinch-webkit-scrollbar-thumb-border-radius: initial;
+}";
+
+/// Generated stylesheet that frees the layout space of scrollbars, for
+/// overlay scrollbars.
+pub(crate) const OVERLAY_SCROLLBAR_CSS: &str = "* { scrollbar-width: none !important; }";
+
+/// Thickness of `scrollbar-width: auto` scrollbars.
+const AUTO_WIDTH: f64 = 12.0;
+/// Thickness of `scrollbar-width: thin` scrollbars.
+const THIN_WIDTH: f64 = 6.0;
+/// Thickness of overlay scrollbars, including their inset.
+const OVERLAY_WIDTH: f64 = 10.0;
+/// Gap between an overlay thumb and the scroller's edge.
+const OVERLAY_INSET: f64 = 2.0;
+/// Shortest a thumb gets, so it stays easy to see and grab.
+const MIN_THUMB_LENGTH: f64 = 20.0;
+
+/// Thumb color when none is set.
+const DEFAULT_THUMB_COLOR: Color = Color::from_rgba8(0x80, 0x80, 0x80, 0x99);
+
+/// How long overlay scrollbars stay fully visible after scrolling stops.
+const OVERLAY_VISIBLE: Duration = Duration::from_millis(800);
+/// How long overlay scrollbars take to fade out.
+const OVERLAY_FADE: Duration = Duration::from_millis(300);
+
+/// An element's scrollbar styling.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ScrollbarStyle {
+    /// Thickness of the vertical scrollbar.
+    pub width: Option<f64>,
+    /// Thickness of the horizontal scrollbar.
+    pub height: Option<f64>,
+    /// Scrollbars are hidden (`scrollbar-width: none`).
+    pub hidden: bool,
+    /// Thumb color.
+    pub thumb: Option<Color>,
+    /// Track color; the track isn't painted without one.
+    pub track: Option<Color>,
+    /// Thumb corner radius; rounded ends by default.
+    pub thumb_radius: Option<f64>,
+}
+
+impl ScrollbarStyle {
+    /// Resolve an element's style from its own declarations and its
+    /// (inherited) `scrollbar-color`.
+    fn from_values(values: &[(String, String)], scrollbar_color: Option<&str>) -> Self {
+        let value = |property: &str| {
+            values
+                .iter()
+                .find(|(name, _)| name == property)
+                .map(|(_, value)| value.as_str())
+        };
+        let webkit =
+            |part: &str, property: &str| value(&format!("{PROPERTY_PREFIX}{part}-{property}"));
+
+        let mut style = Self {
+            width: webkit("", "width").and_then(parse_px),
+            height: webkit("", "height").and_then(parse_px),
+            hidden: webkit("", "display") == Some("none"),
+            thumb: webkit("-thumb", "background-color")
+                .or(webkit("-thumb", "background"))
+                .and_then(parse_background),
+            track: webkit("-track", "background-color")
+                .or(webkit("-track", "background"))
+                .and_then(parse_background),
+            thumb_radius: webkit("-thumb", "border-radius").and_then(parse_px),
+        };
+
+        match value("scrollbar-width") {
+            Some("none") => style.hidden = true,
+            Some("thin") => {
+                style.width = Some(THIN_WIDTH);
+                style.height = Some(THIN_WIDTH);
+            }
+            _ => {}
+        }
+        if let Some(colors) = scrollbar_color.filter(|c| *c != "auto") {
+            let colors = split_outside_parens(colors, ' ');
+            if let [thumb, track] = colors[..] {
+                style.thumb = parse_background(thumb);
+                style.track = parse_background(track);
+            }
+        }
+        style
+    }
+}
+
+/// Rewrite a stylesheet's `::-webkit-scrollbar`, `-thumb` and `-track` rules
+/// into rules on the scrolling element, with prefixed properties.
+///
+/// `.list::-webkit-scrollbar-thumb { background: #555 }` becomes
+/// `.list { --rinch-webkit-scrollbar-thumb-background: #555; }`.
+pub(crate) fn scrollbar_rules(css: &str) -> String {
+    let mut rules = String::new();
+    collect_scrollbar_rules(&strip_comments(css), &mut rules);
+    rules
+}
+
+fn collect_scrollbar_rules(css: &str, out: &mut String) {
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        // Skip statements like `@import ...;` before the rule
+        let prelude = rest[..open].rsplit(';').next().unwrap_or_default().trim();
+        let body = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        if prelude.starts_with('@') {
+            let mut inner = String::new();
+            collect_scrollbar_rules(body, &mut inner);
+            if !inner.is_empty() {
+                out.push_str(&format!("{} {{\n{}}}\n", prelude, inner));
+            }
+            continue;
+        }
+        for selector in prelude.split(',') {
+            let Some((element, part)) = split_scrollbar_selector(selector.trim()) else {
+                continue;
+            };
+            let declarations: String = parse_declarations(body)
+                .iter()
+                .map(|d| {
+                    let important = if d.important { " !important" } else { "" };
+                    format!(
+                        "{PROPERTY_PREFIX}{part}-{}: {}{}; ",
+                        d.property, d.value, important
+                    )
+                })
+                .collect();
+            out.push_str(&format!("{} {{ {}}}\n", element, declarations));
+        }
+    }
+}
+
+/// Split `sel::-webkit-scrollbar-thumb` into `("sel", "-thumb")`. Parts other
+/// than the bar, thumb and track aren't supported.
+fn split_scrollbar_selector(selector: &str) -> Option<(String, &'static str)> {
+    const PSEUDO: &str = "::-webkit-scrollbar";
+    let index = selector.find(PSEUDO)?;
+    let part = match &selector[index + PSEUDO.len()..] {
+        "" => "",
+        "-thumb" => "-thumb",
+        "-track" => "-track",
+        _ => return None,
+    };
+    let element = selector[..index].trim();
+    let element = if element.is_empty() { "*" } else { element };
+    Some((element.to_string(), part))
+}
+
+/// A pixel length (`px` or unitless).
+pub(crate) fn parse_px(value: &str) -> Option<f64> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The color of a `background` value, which may include other layers.
+fn parse_background(value: &str) -> Option<Color> {
+    std::iter::once(value)
+        .chain(split_outside_parens(value, ' '))
+        .find_map(|token| peniko::color::parse_color(token).ok())
+        .map(|color| color.to_alpha_color())
+}
+
+/// Whether an element's own `overflow` lets it scroll (`auto` or `scroll`).
+fn has_scrollbars(values: &[(String, String)]) -> bool {
+    values
+        .iter()
+        .filter(|(name, _)| matches!(name.as_str(), "overflow" | "overflow-x" | "overflow-y"))
+        .any(|(_, value)| {
+            value
+                .split_whitespace()
+                .any(|v| v == "auto" || v == "scroll")
+        })
+}
+
+/// Opacity of an overlay scrollbar that has been idle for `idle`.
+fn overlay_opacity(idle: Duration) -> f32 {
+    if idle <= OVERLAY_VISIBLE {
+        return 1.0;
+    }
+    let fading = (idle - OVERLAY_VISIBLE).as_secs_f32() / OVERLAY_FADE.as_secs_f32();
+    (1.0 - fading).max(0.0)
+}
+
+/// Track and thumb rectangles of a scroller's scrollbars, vertical first.
+///
+/// `bounds` is the scroller's visible box in window coordinates, `width` and
+/// `height` the thickness of the vertical and horizontal bars.
+fn scrollbar_rects(
+    bounds: Rect,
+    state: &ScrollEvent,
+    width: f64,
+    height: f64,
+) -> Vec<(Rect, Rect)> {
+    let vertical = state.content_height > state.client_height + 0.5;
+    let horizontal = state.content_width > state.client_width + 0.5;
+    let mut rects = Vec::new();
+
+    let thumb = |track_length: f64, client: f64, content: f64, offset: f64, max: f64| {
+        let length = (track_length * client / content)
+            .max(MIN_THUMB_LENGTH)
+            .min(track_length);
+        let position = if max > 0.0 {
+            offset / max * (track_length - length)
+        } else {
+            0.0
+        };
+        (position, length)
+    };
+
+    if vertical {
+        let bottom = if horizontal {
+            bounds.y1 - height
+        } else {
+            bounds.y1
+        };
+        let track = Rect::new(bounds.x1 - width, bounds.y0, bounds.x1, bottom);
+        let (position, length) = thumb(
+            track.height(),
+            state.client_height,
+            state.content_height,
+            state.scroll_y,
+            state.max_scroll_y(),
+        );
+        let thumb = Rect::new(
+            track.x0,
+            track.y0 + position,
+            track.x1,
+            track.y0 + position + length,
+        );
+        rects.push((track, thumb));
+    }
+    if horizontal {
+        let right = if vertical {
+            bounds.x1 - width
+        } else {
+            bounds.x1
+        };
+        let track = Rect::new(bounds.x0, bounds.y1 - height, right, bounds.y1);
+        let (position, length) = thumb(
+            track.width(),
+            state.client_width,
+            state.content_width,
+            state.scroll_x,
+            state.max_scroll_x(),
+        );
+        let thumb = Rect::new(
+            track.x0 + position,
+            track.y0,
+            track.x0 + position + length,
+            track.y1,
+        );
+        rects.push((track, thumb));
+    }
+    rects
+}
+
+/// A scroller's visible box (inside its borders) in window coordinates.
+fn scroller_bounds(doc: &BaseDocument, scroller: Scroller, state: &ScrollEvent) -> Rect {
+    match scroller {
+        Scroller::Viewport => Rect::new(0.0, 0.0, state.client_width, state.client_height),
+        Scroller::Node(node_id) => {
+            let Some(node) = doc.get_node(node_id) else {
+                return Rect::ZERO;
+            };
+            let (x, y) = border_box_origin(doc, node_id);
+            let layout = &node.final_layout;
+            Rect::new(
+                x + layout.border.left as f64,
+                y + layout.border.top as f64,
+                x + (layout.size.width - layout.border.right) as f64,
+                y + (layout.size.height - layout.border.bottom) as f64,
+            )
+        }
+    }
+}
+
+/// Scrollbars rinch paints for a window's document.
+#[derive(Debug, Default)]
+pub struct Scrollbars {
+    overlay: bool,
+    /// Scrollers whose bars rinch paints, with their styles. Empty unless the
+    /// document styles scrollbars or the window uses overlay scrollbars.
+    styles: Vec<(Scroller, ScrollbarStyle)>,
+    /// Each overlay scroller's last seen offset, and when it last changed.
+    activity: HashMap<Scroller, ((f64, f64), Option<Instant>)>,
+}
+
+impl Scrollbars {
+    /// Resolve scrollbar styles for a freshly built and styled document.
+    pub(crate) fn new(doc: &BaseDocument, overlay: bool) -> Self {
+        let mut scrollbars = Self {
+            overlay,
+            ..Self::default()
+        };
+        let mut viewport_style = None;
+
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            if node.element_data().is_none() {
+                continue;
+            }
+
+            let values = computed_values(doc, node_id, SCROLLBAR_PROPERTIES);
+            let color = computed_value(doc, node_id, "scrollbar-color");
+            let style = ScrollbarStyle::from_values(&values, color.as_deref());
+            // Unstyled scrollbars are left to the document painter, unless
+            // they're overlay scrollbars
+            let paint = overlay || style != ScrollbarStyle::default();
+            if scrolls_viewport(doc, node_id) {
+                // `body` styles win over `html` styles
+                if paint {
+                    viewport_style = Some(style);
+                }
+            } else if paint && has_scrollbars(&values) {
+                scrollbars.styles.push((Scroller::Node(node_id), style));
+            }
+        }
+        if let Some(style) = viewport_style {
+            scrollbars.styles.push((Scroller::Viewport, style));
+        }
+        scrollbars
+    }
+
+    /// Paint scrollbars over the scene.
+    ///
+    /// Returns `true` while overlay scrollbars are showing, as they need more
+    /// frames to fade out.
+    pub(crate) fn paint(
+        &mut self,
+        scene: &mut impl PaintScene,
+        doc: &BaseDocument,
+        scale: f64,
+    ) -> bool {
+        let now = Instant::now();
+        let mut showing = false;
+
+        for (scroller, style) in &self.styles {
+            let state = scroller.state(doc);
+            let opacity = if self.overlay {
+                let offset = (state.scroll_x, state.scroll_y);
+                let activity = self.activity.entry(*scroller).or_insert((offset, None));
+                if activity.0 != offset {
+                    *activity = (offset, Some(now));
+                }
+                match activity.1 {
+                    Some(scrolled) => overlay_opacity(now.duration_since(scrolled)),
+                    None => 0.0,
+                }
+            } else {
+                1.0
+            };
+            if style.hidden || opacity <= 0.0 {
+                continue;
+            }
+            showing |= self.overlay;
+
+            let (default_width, inset) = if self.overlay {
+                (OVERLAY_WIDTH, OVERLAY_INSET)
+            } else {
+                (AUTO_WIDTH, 0.0)
+            };
+            let width = style.width.unwrap_or(default_width);
+            let height = style.height.or(style.width).unwrap_or(default_width);
+            let bounds = scroller_bounds(doc, *scroller, &state);
+            let transform = Affine::scale(scale);
+            let thumb_color = style
+                .thumb
+                .unwrap_or(DEFAULT_THUMB_COLOR)
+                .multiply_alpha(opacity);
+
+            for (track, thumb) in scrollbar_rects(bounds, &state, width, height) {
+                if let Some(track_color) = style.track {
+                    scene.fill(
+                        Fill::NonZero,
+                        transform,
+                        track_color.multiply_alpha(opacity),
+                        None,
+                        &track,
+                    );
+                }
+                let thumb = thumb.inset(-inset);
+                let radius = style
+                    .thumb_radius
+                    .unwrap_or(thumb.width().min(thumb.height()) / 2.0);
+                let thumb = RoundedRect::from_rect(thumb, radius);
+                scene.fill(Fill::NonZero, transform, thumb_color, None, &thumb);
+            }
+        }
+        showing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn rewrites_webkit_scrollbar_rules() {
+        let css = r#"
+            /* dark scrollbars */
+            .list::-webkit-scrollbar { width: 8px; }
+            .list::-webkit-scrollbar-thumb, ::-webkit-scrollbar-thumb { background: #555 !important; }
+            .list::-webkit-scrollbar-button { display: none; }
+            p { color: red; }
+            @media (min-width: 600px) {
+                .wide::-webkit-scrollbar-track { background-color: #111; }
+            }
+        "#;
+        let rules = scrollbar_rules(css);
+        assert!(rules.contains(".list { --rinch-webkit-scrollbar-width: 8px; }"));
+        assert!(
+            rules.contains(".list { --rinch-webkit-scrollbar-thumb-background: #555 !important; }")
+        );
+        assert!(rules.contains("* { --rinch-webkit-scrollbar-thumb-background: #555 !important; }"));
+        assert!(rules.contains(
+            "@media (min-width: 600px) {\n.wide { --rinch-webkit-scrollbar-track-background-color: #111; }"
+        ));
+        assert!(!rules.contains("button"));
+        assert!(!rules.contains("color: red"));
+    }
+
+    #[test]
+    fn standard_properties_win_over_webkit_rules() {
+        let own = values(&[
+            ("--rinch-webkit-scrollbar-width", "14px"),
+            ("--rinch-webkit-scrollbar-thumb-background", "red"),
+            ("--rinch-webkit-scrollbar-thumb-border-radius", "0"),
+            ("scrollbar-width", "thin"),
+        ]);
+        let style = ScrollbarStyle::from_values(&own, Some("rgb(0, 0, 255) #000"));
+        assert_eq!(style.width, Some(THIN_WIDTH));
+        assert_eq!(style.thumb, Some(Color::from_rgb8(0, 0, 255)));
+        assert_eq!(style.track, Some(Color::BLACK));
+        assert_eq!(style.thumb_radius, Some(0.0));
+
+        let webkit_only = ScrollbarStyle::from_values(&own[..3], Some("auto"));
+        assert_eq!(webkit_only.width, Some(14.0));
+        assert_eq!(webkit_only.thumb, Some(Color::from_rgb8(255, 0, 0)));
+
+        let hidden = values(&[("scrollbar-width", "none")]);
+        assert!(ScrollbarStyle::from_values(&hidden, None).hidden);
+    }
+
+    #[test]
+    fn only_auto_and_scroll_overflow_get_scrollbars() {
+        assert!(has_scrollbars(&values(&[("overflow-y", "auto")])));
+        assert!(has_scrollbars(&values(&[("overflow", "hidden scroll")])));
+        assert!(!has_scrollbars(&values(&[("overflow", "hidden")])));
+        assert!(!has_scrollbars(&values(&[("color", "red")])));
+    }
+
+    #[test]
+    fn thumb_reflects_scroll_position() {
+        let state = ScrollEvent {
+            scroll_y: 300.0,
+            content_height: 800.0,
+            client_height: 200.0,
+            content_width: 100.0,
+            client_width: 100.0,
+            ..Default::default()
+        };
+        let rects = scrollbar_rects(Rect::new(0.0, 0.0, 100.0, 200.0), &state, 10.0, 10.0);
+        assert_eq!(rects.len(), 1);
+        let (track, thumb) = rects[0];
+        assert_eq!(track, Rect::new(90.0, 0.0, 100.0, 200.0));
+        // A quarter of the content is visible, and we're halfway down
+        assert_eq!(thumb.height(), 50.0);
+        assert_eq!(thumb.y0, 75.0);
+    }
+
+    #[test]
+    fn overlay_scrollbars_fade_after_idling() {
+        assert_eq!(overlay_opacity(Duration::ZERO), 1.0);
+        assert_eq!(overlay_opacity(OVERLAY_VISIBLE), 1.0);
+        let halfway = overlay_opacity(OVERLAY_VISIBLE + OVERLAY_FADE / 2);
+        assert!((halfway - 0.5).abs() < 0.01);
+        assert_eq!(overlay_opacity(OVERLAY_VISIBLE + OVERLAY_FADE * 2), 0.0);
+    }
+}
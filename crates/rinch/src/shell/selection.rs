@@ -0,0 +1,353 @@
+//! Text selection for rendered content.
+//!
+//! Click-drag over text selects it, across elements, in document order. The
+//! selection is painted as a highlight over the scene and copied with Ctrl+C
+//! (Cmd+C on macOS) when the `clipboard` feature is enabled. Text inside
+//! elements styled `user-select: none` is skipped.
+
+use anyrender::PaintScene;
+use blitz_dom::BaseDocument;
+use parley::{Affinity, Cursor, Selection};
+use peniko::kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+
+use super::cascade::MatchElement;
+use super::computed::computed_value;
+use super::text_rewrite::strip_breaks;
+
+/// Highlight color for selected text.
+const SELECTION_COLOR: Color = Color::from_rgba8(0x33, 0x90, 0xff, 0x66);
+
+/// A position in the text of an inline formatting root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextPoint {
+    /// The node whose inline layout contains the text.
+    pub node_id: usize,
+    /// Byte index into the node's laid-out text.
+    pub index: usize,
+}
+
+/// The current text selection in a window.
+#[derive(Debug, Default)]
+pub struct TextSelection {
+    anchor: Option<TextPoint>,
+    focus: Option<TextPoint>,
+    /// Inline roots in document order, and whether their text can be selected.
+    roots: Vec<(usize, bool)>,
+    /// A selection drag is in progress.
+    dragging: bool,
+    /// The pointer moved while dragging, so the release isn't a click.
+    moved: bool,
+}
+
+impl TextSelection {
+    /// Start selecting at a point, replacing any previous selection.
+    pub(crate) fn begin(&mut self, doc: &BaseDocument, x: f32, y: f32) {
+        self.roots = selectable_roots(doc);
+        let point = text_point_at(doc, x, y).filter(|p| self.is_selectable(p.node_id));
+        self.anchor = point;
+        self.focus = point;
+        self.dragging = point.is_some();
+        self.moved = false;
+    }
+
+    /// Extend the selection to a point while dragging.
+    ///
+    /// Returns `true` if the selection changed.
+    pub(crate) fn extend(&mut self, doc: &BaseDocument, x: f32, y: f32) -> bool {
+        if !self.dragging {
+            return false;
+        }
+        let Some(point) = text_point_at(doc, x, y) else {
+            return false;
+        };
+        if self.focus == Some(point) || !self.roots.iter().any(|(id, _)| *id == point.node_id) {
+            return false;
+        }
+        self.focus = Some(point);
+        self.moved = true;
+        true
+    }
+
+    /// Finish a selection drag.
+    pub(crate) fn end(&mut self) {
+        self.dragging = false;
+    }
+
+    /// Whether the last press turned into a selection drag.
+    pub fn dragged(&self) -> bool {
+        self.moved
+    }
+
+    /// Clear the selection.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Whether any text is selected.
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.focus
+    }
+
+    /// The selected text, with a newline between blocks.
+    pub fn text(&self, doc: &BaseDocument) -> Option<String> {
+        let pieces: Vec<String> = self
+            .ranges(doc)
+            .into_iter()
+            .filter_map(|(node_id, start, end)| {
+                let text = &doc
+                    .get_node(node_id)?
+                    .element_data()?
+                    .inline_layout_data
+                    .as_ref()?
+                    .text;
+                text.get(start..end).map(strip_breaks)
+            })
+            .collect();
+        (!pieces.is_empty()).then(|| pieces.join("\n"))
+    }
+
+    /// Paint the selection highlight.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        for (node_id, start, end) in self.ranges(doc) {
+            for rect in range_rects(doc, node_id, start, end) {
+                scene.fill(
+                    Fill::NonZero,
+                    Affine::scale(scale),
+                    SELECTION_COLOR,
+                    None,
+                    &rect,
+                );
+            }
+        }
+    }
+
+    fn is_selectable(&self, node_id: usize) -> bool {
+        self.roots
+            .iter()
+            .any(|&(id, selectable)| id == node_id && selectable)
+    }
+
+    /// Selected byte ranges as `(inline root, start, end)`, in document order.
+    fn ranges(&self, doc: &BaseDocument) -> Vec<(usize, usize, usize)> {
+        let (Some(anchor), Some(focus)) = (self.anchor, self.focus) else {
+            return Vec::new();
+        };
+        if anchor == focus {
+            return Vec::new();
+        }
+
+        let position = |p: &TextPoint| self.roots.iter().position(|(id, _)| *id == p.node_id);
+        let (Some(a), Some(f)) = (position(&anchor), position(&focus)) else {
+            return Vec::new();
+        };
+        let (start, end) = if (a, anchor.index) <= (f, focus.index) {
+            ((a, anchor.index), (f, focus.index))
+        } else {
+            ((f, focus.index), (a, anchor.index))
+        };
+
+        let mut ranges = Vec::new();
+        for (i, &(node_id, selectable)) in
+            self.roots.iter().enumerate().take(end.0 + 1).skip(start.0)
+        {
+            if !selectable {
+                continue;
+            }
+            let Some(len) = doc
+                .get_node(node_id)
+                .and_then(|n| n.element_data())
+                .and_then(|el| el.inline_layout_data.as_ref())
+                .map(|text| text.text.len())
+            else {
+                continue;
+            };
+            let from = if i == start.0 { start.1 } else { 0 };
+            let to = if i == end.0 { end.1 } else { len };
+            if from < to {
+                ranges.push((node_id, from, to.min(len)));
+            }
+        }
+        ranges
+    }
+}
+
+/// Window-relative rectangles covering a byte range of an inline root's text,
+/// one per line.
+pub(crate) fn range_rects(
+    doc: &BaseDocument,
+    node_id: usize,
+    start: usize,
+    end: usize,
+) -> Vec<Rect> {
+    let Some(text) = doc
+        .get_node(node_id)
+        .and_then(|node| node.element_data())
+        .and_then(|el| el.inline_layout_data.as_ref())
+    else {
+        return Vec::new();
+    };
+
+    let (origin_x, origin_y) = content_origin(doc, node_id);
+    let selection = Selection::new(
+        Cursor::from_byte_index(&text.layout, start, Affinity::Downstream),
+        Cursor::from_byte_index(&text.layout, end, Affinity::Upstream),
+    );
+    selection
+        .geometry(&text.layout)
+        .into_iter()
+        .map(|(rect, _)| {
+            Rect::new(
+                origin_x + rect.x0,
+                origin_y + rect.y0,
+                origin_x + rect.x1,
+                origin_y + rect.y1,
+            )
+        })
+        .collect()
+}
+
+/// Find the text position under a window-relative point.
+fn text_point_at(doc: &BaseDocument, x: f32, y: f32) -> Option<TextPoint> {
+    let hit = doc.hit(x, y)?;
+
+    // The hit node may be a text node or inline element; its inline root owns the layout
+    let mut current = Some(hit.node_id);
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if let Some(text) = node
+            .element_data()
+            .and_then(|el| el.inline_layout_data.as_ref())
+        {
+            let (origin_x, origin_y) = content_origin(doc, id);
+            let cursor = Cursor::from_point(&text.layout, x - origin_x as f32, y - origin_y as f32);
+            return Some(TextPoint {
+                node_id: id,
+                index: cursor.index(),
+            });
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// Window-relative position of a node's content box, where its text is laid
+/// out, after scrolling.
+pub(crate) fn content_origin(doc: &BaseDocument, node_id: usize) -> (f64, f64) {
+    let Some(node) = doc.get_node(node_id) else {
+        return (0.0, 0.0);
+    };
+    let position = node.absolute_position(0.0, 0.0);
+    let layout = &node.final_layout;
+    let mut x = (position.x + layout.border.left + layout.padding.left) as f64;
+    let mut y = (position.y + layout.border.top + layout.padding.top) as f64;
+
+    // Layout positions ignore scrolling, which happens at paint time
+    let mut current = node.parent;
+    while let Some(id) = current {
+        let Some(ancestor) = doc.get_node(id) else {
+            break;
+        };
+        x -= ancestor.scroll_offset.x;
+        y -= ancestor.scroll_offset.y;
+        current = ancestor.parent;
+    }
+    let viewport_scroll = doc.viewport_scroll();
+    (x - viewport_scroll.x, y - viewport_scroll.y)
+}
+
+/// Inline formatting roots (elements that own laid-out text), in document order.
+pub(crate) fn inline_roots(doc: &BaseDocument) -> Vec<usize> {
+    let mut roots = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if node
+            .element_data()
+            .is_some_and(|el| el.inline_layout_data.is_some())
+        {
+            roots.push(node_id);
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    roots
+}
+
+/// Collect inline roots in document order, marking those inside
+/// `user-select: none`.
+fn selectable_roots(doc: &BaseDocument) -> Vec<(usize, bool)> {
+    inline_roots(doc)
+        .into_iter()
+        .map(|node_id| {
+            let selectable = ["user-select", "-webkit-user-select"]
+                .iter()
+                .find_map(|property| computed_value(doc, node_id, property))
+                .is_none_or(|value| value != "none");
+            (node_id, selectable)
+        })
+        .collect()
+}
+
+/// The hovered and focused elements, which `:hover` and `:focus` rules
+/// match. Elements restyle when they change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StyleState {
+    pub hovered: Option<usize>,
+    pub focused: Option<usize>,
+}
+
+/// Build the selector-matching chain for a node: the node's element followed
+/// by its ancestors.
+pub(crate) fn match_chain(doc: &BaseDocument, node_id: usize) -> Vec<MatchElement> {
+    match_chain_in(doc, node_id, StyleState::default())
+}
+
+/// Build the selector-matching chain for a node in `state`.
+pub(crate) fn match_chain_in(
+    doc: &BaseDocument,
+    node_id: usize,
+    state: StyleState,
+) -> Vec<MatchElement> {
+    // An element is hovered when the pointer is over it or its descendants
+    let mut hovered = Vec::new();
+    let mut current = state.hovered;
+    while let Some(node) = current.and_then(|id| doc.get_node(id)) {
+        hovered.push(node.id);
+        current = node.parent;
+    }
+
+    let mut chain = Vec::new();
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node(id) else {
+            break;
+        };
+        if let Some(el) = node.element_data() {
+            let attrs: Vec<(String, String)> = el
+                .attrs()
+                .iter()
+                .map(|a| (a.name.local.to_string(), a.value.to_string()))
+                .collect();
+            let attr = |name: &str| {
+                attrs
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.clone())
+            };
+            chain.push(MatchElement {
+                tag: el.name.local.to_string(),
+                id: attr("id"),
+                classes: attr("class")
+                    .map(|c| c.split_whitespace().map(String::from).collect())
+                    .unwrap_or_default(),
+                attrs,
+                hovered: hovered.contains(&id),
+                focused: state.focused == Some(id),
+            });
+        }
+        current = node.parent;
+    }
+    chain
+}
@@ -0,0 +1,514 @@
+//! Single-instance applications.
+//!
+//! The first instance of an app started with `run_single_instance` listens
+//! on a per-user local endpoint named after its executable: a Unix domain
+//! socket in `$XDG_RUNTIME_DIR` (or a directory of the user's own, only
+//! they can open, in the temp directory), or on Windows a loopback TCP port
+//! written, with a random token, to a file in the temp directory. Later
+//! instances connect, send their command-line arguments and exit once the
+//! first instance acknowledges them. On Unix, both ends check the other is
+//! the same user.
+//!
+//! Messages are a little-endian `u32` length followed by the arguments,
+//! NUL-separated; the reply is `ok`. An endpoint nothing answers on is left
+//! over from an instance that crashed, and is replaced.
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use winit::event_loop::EventLoopProxy;
+
+use super::deep_link::deep_links_in;
+use super::runtime::RinchEvent;
+
+/// How long either side waits on the other before giving up.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest message accepted, in bytes.
+const MAX_MESSAGE: usize = 1 << 20;
+
+/// Attempts at becoming the first instance or reaching it, when another
+/// instance is starting at the same time.
+const ATTEMPTS: usize = 5;
+
+/// The outcome of starting up.
+pub(crate) enum Claim {
+    /// This is the first instance.
+    Primary(InstanceListener),
+    /// The first instance received this one's arguments.
+    Forwarded,
+}
+
+/// Become the first instance of `id`, or forward `args` to it.
+pub(crate) fn claim(id: &str, args: &[String]) -> io::Result<Claim> {
+    let mut last_error = None;
+    for attempt in 0..ATTEMPTS {
+        match InstanceListener::bind(id) {
+            Ok(listener) => return Ok(Claim::Primary(listener)),
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+            Err(_) => {}
+        }
+        match forward(id, args) {
+            Ok(()) => return Ok(Claim::Forwarded),
+            // Left over from an instance that exited without cleaning up
+            Err(e) if is_stale(&e) => remove_stale_endpoint(id)?,
+            // Another instance is still starting up
+            Err(e) => {
+                last_error = Some(e);
+                std::thread::sleep(Duration::from_millis(50 << attempt));
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::other("couldn't claim the instance")))
+}
+
+/// The id of the running app: its executable's name and the user, so
+/// different users each get their own first instance.
+pub(crate) fn instance_id() -> String {
+    let exe = std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "rinch".into());
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    sanitize(&format!("{exe}-{user}"))
+}
+
+/// This process's arguments, with relative paths to existing files made
+/// absolute so the first instance, with its own working directory, can
+/// open them.
+pub(crate) fn forwarded_args() -> Vec<String> {
+    let cwd = std::env::current_dir().ok();
+    std::env::args_os()
+        .skip(1)
+        .map(|arg| {
+            let path = Path::new(&arg);
+            match &cwd {
+                Some(cwd) if path.is_relative() && path.exists() => {
+                    cwd.join(path).to_string_lossy().into_owned()
+                }
+                _ => arg.to_string_lossy().into_owned(),
+            }
+        })
+        .collect()
+}
+
+/// The first instance's end of the channel.
+pub(crate) struct InstanceListener {
+    #[cfg(unix)]
+    listener: std::os::unix::net::UnixListener,
+    #[cfg(windows)]
+    listener: std::net::TcpListener,
+    #[cfg(windows)]
+    token: String,
+}
+
+impl InstanceListener {
+    /// Listen as the first instance of `id`; `AlreadyExists` if another
+    /// instance's endpoint is there.
+    #[cfg(unix)]
+    fn bind(id: &str) -> io::Result<Self> {
+        use std::os::unix::net::UnixListener;
+        match UnixListener::bind(endpoint_path(id)?) {
+            Ok(listener) => Ok(Self { listener }),
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                Err(io::Error::new(io::ErrorKind::AlreadyExists, e))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(windows)]
+    fn bind(id: &str) -> io::Result<Self> {
+        use std::hash::{BuildHasher, Hasher};
+        use std::net::{Ipv4Addr, TcpListener};
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(endpoint_path(id)?)?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let token = format!(
+            "{:016x}",
+            std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish()
+        );
+        write!(file, "{} {}", listener.local_addr()?.port(), token)?;
+        Ok(Self { listener, token })
+    }
+
+    /// Receive later instances' arguments on a background thread, sending
+    /// each set to the event loop.
+    pub(crate) fn listen(self, proxy: EventLoopProxy<RinchEvent>) {
+        let spawned = std::thread::Builder::new()
+            .name("rinch-single-instance".into())
+            .spawn(move || {
+                for stream in self.listener.incoming() {
+                    let received = stream.and_then(|stream| self.receive(stream));
+                    match received {
+                        Ok(args) => {
+                            let links = deep_links_in(&args);
+                            let events = std::iter::once(RinchEvent::SecondInstance { args })
+                                .chain(links.into_iter().map(RinchEvent::DeepLink));
+                            for event in events {
+                                if proxy.send_event(event).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            tracing::debug!("Ignoring a connection from another instance: {e}")
+                        }
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!("Couldn't listen for other instances: {e}");
+        }
+    }
+
+    #[cfg(unix)]
+    fn receive(&self, mut stream: std::os::unix::net::UnixStream) -> io::Result<Vec<String>> {
+        check_peer(&stream)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let args = read_message(&mut stream)?;
+        stream.write_all(b"ok")?;
+        Ok(args)
+    }
+
+    #[cfg(windows)]
+    fn receive(&self, mut stream: std::net::TcpStream) -> io::Result<Vec<String>> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut args = read_message(&mut stream)?;
+        // The token proves the sender could read this user's temp directory,
+        // so anyone else is refused before being told they got through
+        if args.is_empty() || args.remove(0) != self.token {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "wrong token",
+            ));
+        }
+        stream.write_all(b"ok")?;
+        Ok(args)
+    }
+}
+
+/// Send `args` to the first instance of `id` and wait for it to take them.
+#[cfg(unix)]
+fn forward(id: &str, args: &[String]) -> io::Result<()> {
+    let stream = connect(id)?;
+    check_peer(&stream)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    send(stream, args)
+}
+
+#[cfg(windows)]
+fn forward(id: &str, args: &[String]) -> io::Result<()> {
+    let (stream, token) = connect(id)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut message = vec![token];
+    message.extend_from_slice(args);
+    send(stream, &message)
+}
+
+/// Connect to the first instance of `id`.
+#[cfg(unix)]
+fn connect(id: &str) -> io::Result<std::os::unix::net::UnixStream> {
+    std::os::unix::net::UnixStream::connect(endpoint_path(id)?)
+}
+
+/// Connect to the first instance of `id`, returning its token too.
+#[cfg(windows)]
+fn connect(id: &str) -> io::Result<(std::net::TcpStream, String)> {
+    use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+    let contents = std::fs::read_to_string(endpoint_path(id)?)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "incomplete instance file");
+    let (port, token) = contents.split_once(' ').ok_or_else(invalid)?;
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    Ok((stream, token.to_string()))
+}
+
+/// Write a message and wait for the acknowledgement.
+fn send(mut stream: impl Read + Write, args: &[String]) -> io::Result<()> {
+    let message = encode(args);
+    stream.write_all(&(message.len() as u32).to_le_bytes())?;
+    stream.write_all(&message)?;
+    stream.flush()?;
+    let mut ack = [0; 2];
+    stream.read_exact(&mut ack)?;
+    if &ack != b"ok" {
+        // Something else is listening where the first instance was
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "unexpected reply",
+        ));
+    }
+    Ok(())
+}
+
+/// Read a message, for the caller to acknowledge once it accepts it.
+fn read_message(stream: &mut impl Read) -> io::Result<Vec<String>> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message too long",
+        ));
+    }
+    let mut message = vec![0; len];
+    stream.read_exact(&mut message)?;
+    decode(&message)
+}
+
+fn encode(args: &[String]) -> Vec<u8> {
+    args.join("\0").into_bytes()
+}
+
+fn decode(message: &[u8]) -> io::Result<Vec<String>> {
+    let message =
+        std::str::from_utf8(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if message.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(message.split('\0').map(str::to_string).collect())
+}
+
+/// Whether a failed connection means no instance is listening any more.
+fn is_stale(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::NotFound
+    )
+}
+
+/// Remove an endpoint found stale, unless another instance replaced it
+/// since: it's only removed if nothing answers on it when it's checked
+/// again, and it's still the file that was checked.
+fn remove_stale_endpoint(id: &str) -> io::Result<()> {
+    let path = endpoint_path(id)?;
+    let Some(found) = identity(&path) else {
+        return Ok(());
+    };
+    match connect(id) {
+        Err(e) if is_stale(&e) => {}
+        // Another instance is listening there now
+        _ => return Ok(()),
+    }
+    if identity(&path) != Some(found) {
+        return Ok(());
+    }
+    remove_endpoint(id)
+}
+
+/// What tells an endpoint apart from one bound in its place: the socket's
+/// inode and when it was made.
+#[cfg(unix)]
+fn identity(path: &Path) -> Option<(u64, u64, i64, i64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    Some((
+        metadata.dev(),
+        metadata.ino(),
+        metadata.ctime(),
+        metadata.ctime_nsec(),
+    ))
+}
+
+/// What tells an endpoint apart from one written in its place: each
+/// instance writes its own port and random token.
+#[cfg(windows)]
+fn identity(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+fn remove_endpoint(id: &str) -> io::Result<()> {
+    match std::fs::remove_file(endpoint_path(id)?) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Where the first instance's socket (or, on Windows, port file) lives.
+#[cfg(unix)]
+fn endpoint_path(id: &str) -> io::Result<PathBuf> {
+    Ok(endpoint_dir()?.join(format!("{id}.sock")))
+}
+
+#[cfg(windows)]
+fn endpoint_path(id: &str) -> io::Result<PathBuf> {
+    // The temp directory is in the user's profile
+    Ok(std::env::temp_dir().join(format!("{id}.instance")))
+}
+
+/// `$XDG_RUNTIME_DIR`, or else a directory of the user's own in the temp
+/// directory, which anyone can write to. Either way only the user may open
+/// it, so nobody else can take the socket's name first.
+#[cfg(unix)]
+fn endpoint_dir() -> io::Result<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from)
+        && is_private(&dir)
+    {
+        return Ok(dir);
+    }
+    // SAFETY: getuid can't fail
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("rinch-{uid}"));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    // Someone else may have made it first
+    if !is_private(&dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} belongs to another user", dir.display()),
+        ));
+    }
+    Ok(dir)
+}
+
+/// Whether `dir` is a directory only this user can open.
+#[cfg(unix)]
+fn is_private(dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    // SAFETY: getuid can't fail
+    let uid = unsafe { libc::getuid() };
+    std::fs::symlink_metadata(dir).is_ok_and(|metadata| {
+        metadata.is_dir() && metadata.uid() == uid && metadata.mode() & 0o077 == 0
+    })
+}
+
+/// Refuse a connection from, or to, another user's process.
+#[cfg(unix)]
+fn check_peer(stream: &std::os::unix::net::UnixStream) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+    let fd = stream.as_raw_fd();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let uid = {
+        let mut cred = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: SO_PEERCRED fills in a ucred of the length given
+        let result = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                (&raw mut cred).cast(),
+                &mut len,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        cred.uid
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let uid = {
+        let (mut uid, mut gid) = (0, 0);
+        // SAFETY: getpeereid only writes the two IDs
+        if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        uid
+    };
+    // SAFETY: getuid can't fail
+    if uid != unsafe { libc::getuid() } {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "another user's process",
+        ));
+    }
+    Ok(())
+}
+
+/// Keep ASCII letters, digits, `-` and `_`, replacing anything else with
+/// `_`.
+fn sanitize(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let args = vec![
+            "--open".to_string(),
+            "/tmp/a b.txt".to_string(),
+            String::new(),
+        ];
+        assert_eq!(decode(&encode(&args)).unwrap(), args);
+        assert!(decode(&encode(&[])).unwrap().is_empty());
+        assert_eq!(sanitize("my app-ü"), "my_app-_");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn later_instances_forward_their_arguments() {
+        let id = sanitize(&format!("rinch-test-{}", std::process::id()));
+        let Ok(Claim::Primary(first)) = claim(&id, &[]) else {
+            panic!("the first instance should listen");
+        };
+        let second = std::thread::spawn({
+            let id = id.clone();
+            move || matches!(claim(&id, &["notes.txt".to_string()]), Ok(Claim::Forwarded))
+        });
+        let (stream, _) = first.listener.accept().unwrap();
+        assert_eq!(first.receive(stream).unwrap(), ["notes.txt"]);
+        assert!(second.join().unwrap());
+
+        // A socket nobody listens on any more is replaced
+        drop(first);
+        assert!(matches!(claim(&id, &[]), Ok(Claim::Primary(_))));
+        remove_endpoint(&id).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn endpoints_an_instance_listens_on_are_kept() {
+        let id = sanitize(&format!("rinch-test-live-{}", std::process::id()));
+        let Ok(Claim::Primary(first)) = claim(&id, &[]) else {
+            panic!("the first instance should listen");
+        };
+        remove_stale_endpoint(&id).unwrap();
+        assert!(endpoint_path(&id).unwrap().exists());
+
+        drop(first);
+        remove_stale_endpoint(&id).unwrap();
+        assert!(!endpoint_path(&id).unwrap().exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sockets_live_where_only_the_user_can_reach_them() {
+        let dir = endpoint_dir().unwrap();
+        assert!(is_private(&dir));
+    }
+}
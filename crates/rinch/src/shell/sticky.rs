@@ -0,0 +1,339 @@
+//! Sticky positioning.
+//!
+//! The layout engine treats `position: sticky` like `relative`, so rinch
+//! moves sticky elements itself after layout and whenever their scroller
+//! moves. A sticky element keeps to its position in the flow until scrolling
+//! would carry it past its `top`/`bottom`/`left`/`right` inset from the edge
+//! of the nearest scroll container (or the window); then it stays at the
+//! inset, but never leaves its containing block.
+//!
+//! As in browsers, table parts (`thead`, `tr`, `th`, ...) are contained by
+//! their table rather than their parent, so sticky header cells stay put for
+//! the whole table.
+
+use blitz_dom::BaseDocument;
+
+use super::computed::computed_values;
+use super::scroll::{border_box_origin, is_scroll_container, Scroller};
+use super::scrollbars::parse_px;
+
+/// The properties sticky insets are read from.
+const STICKY_PROPERTIES: &[&str] = &["position", "top", "right", "bottom", "left"];
+
+/// Elements contained by their table rather than their parent.
+const TABLE_PARTS: &[&str] = &["thead", "tbody", "tfoot", "tr", "th", "td", "caption"];
+
+/// Sticky insets, in logical pixels; `None` for `auto`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Insets {
+    top: Option<f64>,
+    right: Option<f64>,
+    bottom: Option<f64>,
+    left: Option<f64>,
+}
+
+impl Insets {
+    /// Insets of a sticky element, or `None` if it isn't sticky.
+    fn from_values(values: &[(String, String)]) -> Option<Self> {
+        let value = |property: &str| {
+            values
+                .iter()
+                .rev()
+                .find(|(p, _)| p == property)
+                .map(|(_, v)| v.as_str())
+        };
+        if value("position").map(str::trim) != Some("sticky") {
+            return None;
+        }
+        Some(Self {
+            top: value("top").and_then(parse_px),
+            right: value("right").and_then(parse_px),
+            bottom: value("bottom").and_then(parse_px),
+            left: value("left").and_then(parse_px),
+        })
+    }
+
+    /// The offset the layout engine already applied, treating the element
+    /// as relatively positioned (`top` wins over `bottom`, `left` over
+    /// `right`).
+    fn relative_offset(&self) -> (f64, f64) {
+        (
+            self.left.or(self.right.map(|right| -right)).unwrap_or(0.0),
+            self.top
+                .or(self.bottom.map(|bottom| -bottom))
+                .unwrap_or(0.0),
+        )
+    }
+}
+
+/// How far to move a sticky element along one axis.
+///
+/// `start` and `len` are the element's position and size where the flow put
+/// it, `view` is the visible area of its scroller, and `limits` is its
+/// containing block's content area, all as window-relative `(start, end)`
+/// ranges.
+fn sticky_shift(
+    start: f64,
+    len: f64,
+    view: (f64, f64),
+    insets: (Option<f64>, Option<f64>),
+    limits: (f64, f64),
+) -> f64 {
+    let end = start + len;
+    let mut shift: f64 = 0.0;
+    if let Some(inset) = insets.1 {
+        shift = shift.min(view.1 - inset - end);
+    }
+    // The start inset wins when both apply
+    if let Some(inset) = insets.0 {
+        shift = shift.max(view.0 + inset - start);
+    }
+    shift
+        .min((limits.1 - end).max(0.0))
+        .max((limits.0 - start).min(0.0))
+}
+
+/// A sticky element, and where rinch last put it.
+#[derive(Debug)]
+struct Sticky {
+    node_id: usize,
+    insets: Insets,
+    containing_block: usize,
+    /// The location rinch set, and how far that was from where layout put
+    /// the element.
+    placed: Option<((f32, f32), (f64, f64))>,
+}
+
+/// Sticky elements in a window's document.
+#[derive(Debug, Default)]
+pub(crate) struct StickyElements {
+    elements: Vec<Sticky>,
+}
+
+impl StickyElements {
+    /// Find sticky elements in a freshly built and styled document.
+    pub(crate) fn new(doc: &BaseDocument) -> Self {
+        let mut sticky = Self::default();
+        // Document order, so containing blocks move before what they contain
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            let Some(el) = node.element_data() else {
+                continue;
+            };
+            let values = computed_values(doc, node_id, STICKY_PROPERTIES);
+            let Some(insets) = Insets::from_values(&values) else {
+                continue;
+            };
+            let Some(parent) = node.parent else {
+                continue;
+            };
+            let containing_block = if TABLE_PARTS.contains(&el.name.local.as_ref()) {
+                table_ancestor(doc, node_id).unwrap_or(parent)
+            } else {
+                parent
+            };
+            sticky.elements.push(Sticky {
+                node_id,
+                insets,
+                containing_block,
+                placed: None,
+            });
+        }
+        sticky
+    }
+
+    /// Move sticky elements to where the current scroll offsets put them.
+    ///
+    /// Call after layout and after scrolling; the window redraws anyway.
+    pub(crate) fn update(&mut self, doc: &mut BaseDocument) {
+        for sticky in &mut self.elements {
+            let Some(node) = doc.get_node(sticky.node_id) else {
+                continue;
+            };
+            let location = node.final_layout.location;
+            let size = node.final_layout.size;
+
+            // Undo the last move, unless layout has placed the element afresh
+            let applied = match sticky.placed {
+                Some((placed, shift)) if placed == (location.x, location.y) => shift,
+                _ => (0.0, 0.0),
+            };
+            let (x, y) = border_box_origin(doc, sticky.node_id);
+            let (relative_x, relative_y) = sticky.insets.relative_offset();
+            let (flow_x, flow_y) = (x - applied.0 - relative_x, y - applied.1 - relative_y);
+
+            let (view_x, view_y) = visible_area(doc, scroller_of(doc, sticky.node_id));
+            let (limit_x, limit_y) = content_area(doc, sticky.containing_block);
+            let shift_x = sticky_shift(
+                flow_x,
+                size.width as f64,
+                view_x,
+                (sticky.insets.left, sticky.insets.right),
+                limit_x,
+            );
+            let shift_y = sticky_shift(
+                flow_y,
+                size.height as f64,
+                view_y,
+                (sticky.insets.top, sticky.insets.bottom),
+                limit_y,
+            );
+
+            // Replace the layout engine's relative offset with the sticky one
+            let (dx, dy) = (
+                shift_x - relative_x - applied.0,
+                shift_y - relative_y - applied.1,
+            );
+            let Some(node) = doc.get_node_mut(sticky.node_id) else {
+                continue;
+            };
+            node.final_layout.location.x += dx as f32;
+            node.final_layout.location.y += dy as f32;
+            let placed = node.final_layout.location;
+            sticky.placed = Some((
+                (placed.x, placed.y),
+                (shift_x - relative_x, shift_y - relative_y),
+            ));
+        }
+    }
+}
+
+/// The nearest `table` ancestor of a node.
+fn table_ancestor(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    let mut current = doc.get_node(node_id)?.parent;
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if node
+            .element_data()
+            .is_some_and(|el| el.name.local.as_ref() == "table")
+        {
+            return Some(id);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+/// The scroller a node scrolls with.
+fn scroller_of(doc: &BaseDocument, node_id: usize) -> Scroller {
+    let mut current = doc.get_node(node_id).and_then(|node| node.parent);
+    while let Some(id) = current {
+        if is_scroll_container(doc, id) {
+            return Scroller::Node(id);
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+    Scroller::Viewport
+}
+
+/// A scroller's visible area, as window-relative `(start, end)` ranges.
+fn visible_area(doc: &BaseDocument, scroller: Scroller) -> ((f64, f64), (f64, f64)) {
+    let state = scroller.state(doc);
+    let (x, y) = scroller.client_origin(doc);
+    ((x, x + state.client_width), (y, y + state.client_height))
+}
+
+/// A node's content box, as window-relative `(start, end)` ranges.
+fn content_area(doc: &BaseDocument, node_id: usize) -> ((f64, f64), (f64, f64)) {
+    let Some(node) = doc.get_node(node_id) else {
+        return ((f64::MIN, f64::MAX), (f64::MIN, f64::MAX));
+    };
+    let (x, y) = border_box_origin(doc, node_id);
+    let layout = &node.final_layout;
+    let left = x + (layout.border.left + layout.padding.left) as f64;
+    let top = y + (layout.border.top + layout.padding.top) as f64;
+    let right = x + (layout.size.width - layout.border.right - layout.padding.right) as f64;
+    let bottom = y + (layout.size.height - layout.border.bottom - layout.padding.bottom) as f64;
+    ((left, right), (top, bottom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn reads_sticky_insets() {
+        assert_eq!(Insets::from_values(&values(&[("top", "0")])), None);
+        let insets = Insets::from_values(&values(&[
+            ("position", "sticky"),
+            ("top", "8px"),
+            ("left", "auto"),
+        ]))
+        .unwrap();
+        assert_eq!(insets.top, Some(8.0));
+        assert_eq!(insets.left, None);
+        assert_eq!(insets.relative_offset(), (0.0, 8.0));
+    }
+
+    #[test]
+    fn sticks_to_the_top_inset() {
+        // Header at 100..130 in a table spanning 100..1000, view 0..400
+        let limits = (100.0, 1000.0);
+        assert_eq!(
+            sticky_shift(100.0, 30.0, (0.0, 400.0), (Some(0.0), None), limits),
+            0.0
+        );
+        // Scrolled 250px: the flow position is -150, so it moves down 150
+        assert_eq!(
+            sticky_shift(
+                -150.0,
+                30.0,
+                (0.0, 400.0),
+                (Some(0.0), None),
+                (-150.0, 750.0)
+            ),
+            150.0
+        );
+        // Near the table's end it's pushed out with it
+        assert_eq!(
+            sticky_shift(
+                -800.0,
+                30.0,
+                (0.0, 400.0),
+                (Some(0.0), None),
+                (-800.0, 100.0)
+            ),
+            800.0
+        );
+        assert_eq!(
+            sticky_shift(-900.0, 30.0, (0.0, 400.0), (Some(0.0), None), (-900.0, 0.0)),
+            870.0
+        );
+    }
+
+    #[test]
+    fn sticks_to_the_bottom_inset() {
+        // A footer below the visible area is pulled up to its bottom edge
+        assert_eq!(
+            sticky_shift(600.0, 40.0, (0.0, 400.0), (None, Some(10.0)), (0.0, 640.0)),
+            -250.0
+        );
+        // But not above its containing block
+        assert_eq!(
+            sticky_shift(
+                600.0,
+                40.0,
+                (0.0, 400.0),
+                (None, Some(10.0)),
+                (500.0, 640.0)
+            ),
+            -100.0
+        );
+        // The start inset wins when both apply
+        assert_eq!(
+            sticky_shift(
+                -20.0,
+                500.0,
+                (0.0, 400.0),
+                (Some(0.0), Some(0.0)),
+                (-1000.0, 1000.0)
+            ),
+            20.0
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Stylesheet helpers for window content.
+//!
+//! Local `<link rel="stylesheet">` tags are inlined as `<style>` blocks so blitz
+//! can apply them without a network provider, and rendered HTML can be compared
+//! to detect updates that only touch style content. Those updates are applied to
+//! the live document instead of rebuilding it, which keeps scroll positions and
+//! other document state intact.
+//!
+//! This module also controls the user-agent stylesheet that every document
+//! starts from. Design systems can swap the built-in element defaults for a
+//! structural reset or their own base styles:
+//!
+//! ```ignore
+//! use rinch::shell::stylesheets::{set_user_agent_styles, UserAgentStyles};
+//!
+//! fn main() {
+//!     set_user_agent_styles(UserAgentStyles::Reset);
+//!     rinch::run(app);
+//! }
+//! ```
+
+use std::cell::RefCell;
+
+use rinch_core::{prefers_reduced_motion, untracked};
+
+use super::computed::{mirrored_rules, MIRROR_RESET_CSS};
+use super::focus::FOCUS_RESET_CSS;
+use super::scrollbars::WEBKIT_SCROLLBAR_RESET_CSS;
+
+/// Minimal user-agent stylesheet used by [`UserAgentStyles::Reset`].
+///
+/// Keeps the structural rules a document needs to render correctly (hidden
+/// metadata elements, block-level flow, table and list display types) but
+/// drops margins, font sizes, borders, and form control styling.
+pub const RESET_CSS: &str = r#"
+head, script, style, title, meta, link, template, [hidden] { display: none; }
+html, body, div, section, article, aside, header, footer, nav, main,
+h1, h2, h3, h4, h5, h6, p, ul, ol, li, pre, blockquote, form,
+fieldset, figure, figcaption, details, summary, dl, dt, dd, hr, address {
+    display: block;
+}
+li { display: list-item; }
+table { display: table; border-collapse: collapse; }
+thead { display: table-header-group; }
+tbody { display: table-row-group; }
+tfoot { display: table-footer-group; }
+tr { display: table-row; }
+td, th { display: table-cell; }
+pre { white-space: pre; }
+b, strong { font-weight: bold; }
+i, em { font-style: italic; }
+button, input, select, textarea { font: inherit; color: inherit; }
+"#;
+
+/// Bidirectional text rules for the `dir` attribute and `<bdi>`/`<bdo>`.
+///
+/// Added after every base stylesheet so `dir="rtl"` works with a custom or
+/// reset base too.
+pub const BIDI_CSS: &str = r#"
+[dir="rtl" i] { direction: rtl; }
+[dir="ltr" i] { direction: ltr; }
+[dir]:not(bdi):not(bdo) { unicode-bidi: isolate; }
+bdi:not([dir]) { unicode-bidi: isolate; }
+bdo, bdo[dir] { unicode-bidi: isolate-override; }
+[dir="auto" i] { unicode-bidi: plaintext; }
+"#;
+
+/// Makes a window's root element right-to-left, for its `rtl` property.
+pub(crate) const RTL_ROOT_CSS: &str = ":root { direction: rtl; }";
+
+/// Rules that cut CSS animations and transitions short, added when the user
+/// prefers reduced motion.
+///
+/// Important user-agent declarations beat any author styles. Elements with
+/// `motion: "full"` (`data-motion="full"`) keep their animations.
+pub const REDUCED_MOTION_CSS: &str = r#"
+:not([data-motion="full"]), :not([data-motion="full"])::before,
+:not([data-motion="full"])::after {
+    animation-duration: 1ms !important;
+    animation-delay: 0s !important;
+    animation-iteration-count: 1 !important;
+    transition-duration: 0s !important;
+    transition-delay: 0s !important;
+}
+"#;
+
+/// The user-agent stylesheet that new documents start from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum UserAgentStyles {
+    /// The built-in blitz defaults (margins, headings, form controls...).
+    #[default]
+    Default,
+    /// The structural reset in [`RESET_CSS`].
+    Reset,
+    /// A custom stylesheet that replaces the defaults entirely.
+    Custom(String),
+}
+
+thread_local! {
+    static UA_STYLES: RefCell<UserAgentStyles> = const { RefCell::new(UserAgentStyles::Default) };
+    static EXTRA_UA_STYLESHEETS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Choose the user-agent stylesheet for all windows.
+///
+/// Call this before [`run`](crate::run); windows created afterwards (and
+/// documents rebuilt on re-render) use the new styles. A window with
+/// `reset: true` always uses [`UserAgentStyles::Reset`].
+pub fn set_user_agent_styles(styles: UserAgentStyles) {
+    UA_STYLES.with(|s| *s.borrow_mut() = styles);
+}
+
+/// Add a stylesheet at user-agent origin, after the base stylesheet.
+///
+/// Rules added here lose to any author styles in the document, which makes
+/// this the right place for design-system defaults.
+pub fn add_user_agent_stylesheet(css: impl Into<String>) {
+    EXTRA_UA_STYLESHEETS.with(|s| s.borrow_mut().push(css.into()));
+}
+
+/// Resolve the user-agent stylesheets for a window.
+///
+/// `default_css` is the engine's built-in stylesheet. `rtl` makes the root
+/// element right-to-left. [`REDUCED_MOTION_CSS`] is added while the user
+/// prefers reduced motion. The first sheet resets the custom properties
+/// rinch's features read (see `computed`), and the user-agent rules for
+/// properties the style engine doesn't compute are copied into them.
+pub(crate) fn user_agent_stylesheets(default_css: &str, reset: bool, rtl: bool) -> Vec<String> {
+    let base = if reset {
+        UserAgentStyles::Reset
+    } else {
+        UA_STYLES.with(|s| s.borrow().clone())
+    };
+
+    let mut sheets = vec![
+        [
+            MIRROR_RESET_CSS,
+            WEBKIT_SCROLLBAR_RESET_CSS,
+            FOCUS_RESET_CSS,
+        ]
+        .join("\n"),
+        match base {
+            UserAgentStyles::Default => default_css.to_string(),
+            UserAgentStyles::Reset => RESET_CSS.to_string(),
+            UserAgentStyles::Custom(css) => css,
+        },
+    ];
+    sheets.push(BIDI_CSS.to_string());
+    if rtl {
+        sheets.push(RTL_ROOT_CSS.to_string());
+    }
+    EXTRA_UA_STYLESHEETS.with(|s| sheets.extend(s.borrow().iter().cloned()));
+    let mirrored: String = sheets[1..].iter().map(|css| mirrored_rules(css)).collect();
+    if !mirrored.is_empty() {
+        sheets.push(mirrored);
+    }
+    if untracked(prefers_reduced_motion) {
+        sheets.push(REDUCED_MOTION_CSS.to_string());
+    }
+    sheets
+}
+
+/// Attribute recording which file an inlined stylesheet came from.
+pub const INLINED_HREF_ATTR: &str = "data-rinch-href";
+
+/// Attribute marking a `<style>` block rinch generated rather than the app.
+pub const GENERATED_STYLE_ATTR: &str = "data-rinch-generated";
+
+/// Append a generated stylesheet to a window's HTML.
+pub(crate) fn append_generated_styles(html: &str, css: &str) -> String {
+    format!("{}<style {}>{}</style>", html, GENERATED_STYLE_ATTR, css)
+}
+
+/// Replace `<link rel="stylesheet" href="...">` tags pointing at local files
+/// with `<style>` blocks containing the file contents. `href`s can be paths
+/// or asset URLs (see [`crate::assets`]).
+///
+/// Remote stylesheets (`http://`, `https://`, `data:`) and files that can't be
+/// read are left untouched.
+pub fn inline_local_stylesheets(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut cursor = 0;
+
+    while let Some(offset) = lower[cursor..].find("<link") {
+        let start = cursor + offset;
+        let Some(tag_len) = lower[start..].find('>') else {
+            break;
+        };
+        let end = start + tag_len + 1;
+        let tag = &html[start..end];
+
+        out.push_str(&html[cursor..start]);
+        match local_stylesheet_href(tag) {
+            Some(href) => match crate::assets::read_to_string(&href) {
+                Ok(css) => {
+                    out.push_str(&format!(
+                        "<style {}=\"{}\">{}</style>",
+                        INLINED_HREF_ATTR, href, css
+                    ));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to read stylesheet {:?}: {}", href, e);
+                    out.push_str(tag);
+                }
+            },
+            None => out.push_str(tag),
+        }
+        cursor = end;
+    }
+
+    out.push_str(&html[cursor..]);
+    out
+}
+
+/// Get the `href` of a `<link>` tag if it references a local stylesheet.
+fn local_stylesheet_href(tag: &str) -> Option<String> {
+    let rel = attr_value(tag, "rel")?;
+    if !rel
+        .split_whitespace()
+        .any(|r| r.eq_ignore_ascii_case("stylesheet"))
+    {
+        return None;
+    }
+
+    let href = attr_value(tag, "href")?;
+    let lower = href.to_ascii_lowercase();
+    if href.is_empty()
+        || lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || lower.starts_with("data:")
+    {
+        return None;
+    }
+    Some(href)
+}
+
+/// Read a quoted attribute value from a single start tag.
+fn attr_value(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+
+    while let Some(offset) = lower[search..].find(name) {
+        let start = search + offset;
+        search = start + name.len();
+
+        // Must be a whole attribute name, not a suffix of another one
+        let preceded_by_space = lower[..start]
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_ascii_whitespace());
+        if !preceded_by_space {
+            continue;
+        }
+
+        let rest = tag[search..].trim_start();
+        let Some(rest) = rest.strip_prefix('=') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let quote = rest.chars().next()?;
+        if quote == '"' || quote == '\'' {
+            let value = &rest[1..];
+            let close = value.find(quote)?;
+            return Some(value[..close].to_string());
+        }
+        let close = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        return Some(rest[..close].to_string());
+    }
+
+    None
+}
+
+/// Split HTML into its markup with `<style>` contents removed, plus the
+/// contents of each `<style>` block in document order.
+pub fn split_styles(html: &str) -> (String, Vec<String>) {
+    let lower = html.to_ascii_lowercase();
+    let mut markup = String::with_capacity(html.len());
+    let mut styles = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(offset) = lower[cursor..].find("<style") {
+        let start = cursor + offset;
+        let Some(open_len) = lower[start..].find('>') else {
+            break;
+        };
+        let content_start = start + open_len + 1;
+        let Some(close_offset) = lower[content_start..].find("</style") else {
+            break;
+        };
+        let content_end = content_start + close_offset;
+
+        markup.push_str(&html[cursor..content_start]);
+        styles.push(html[content_start..content_end].to_string());
+        cursor = content_end;
+    }
+
+    markup.push_str(&html[cursor..]);
+    (markup, styles)
+}
+
+/// Compare two renders of the same window.
+///
+/// Returns the new `<style>` contents if the markup is identical and only
+/// style content changed, or `None` if a full document rebuild is needed
+/// (or nothing changed at all).
+pub fn styles_only_change(old_html: &str, new_html: &str) -> Option<Vec<String>> {
+    if old_html == new_html {
+        return None;
+    }
+
+    let (old_markup, old_styles) = split_styles(old_html);
+    let (new_markup, new_styles) = split_styles(new_html);
+
+    if old_markup == new_markup && old_styles.len() == new_styles.len() {
+        Some(new_styles)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_styles_extracts_contents() {
+        let html = r#"<style>a { color: red; }</style><p>Hi</p><STYLE media="x">b {}</STYLE>"#;
+        let (markup, styles) = split_styles(html);
+        assert_eq!(
+            markup,
+            r#"<style></style><p>Hi</p><STYLE media="x"></STYLE>"#
+        );
+        assert_eq!(styles, vec!["a { color: red; }", "b {}"]);
+    }
+
+    #[test]
+    fn style_only_edit_is_detected() {
+        let old = "<style>p { margin: 0; }</style><p>Text</p>";
+        let new = "<style>p { margin: 4px; }</style><p>Text</p>";
+        assert_eq!(
+            styles_only_change(old, new),
+            Some(vec!["p { margin: 4px; }".to_string()])
+        );
+    }
+
+    #[test]
+    fn markup_edit_requires_rebuild() {
+        let old = "<style>p {}</style><p>Text</p>";
+        let new = "<style>p {}</style><p>Other</p>";
+        assert_eq!(styles_only_change(old, new), None);
+
+        let added = "<style>p {}</style><style>q {}</style><p>Text</p>";
+        assert_eq!(styles_only_change(old, added), None);
+
+        assert_eq!(styles_only_change(old, old), None);
+    }
+
+    #[test]
+    fn remote_links_are_not_inlined() {
+        let html = r#"<link rel="stylesheet" href="https://example.com/a.css"><p>x</p>"#;
+        assert_eq!(inline_local_stylesheets(html), html);
+
+        let icon = r#"<link rel="icon" href="icon.png">"#;
+        assert_eq!(inline_local_stylesheets(icon), icon);
+    }
+
+    #[test]
+    fn local_links_are_inlined() {
+        let dir = std::env::temp_dir().join("rinch-stylesheet-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.css");
+        std::fs::write(&path, "body { color: blue; }").unwrap();
+
+        let href = path.to_string_lossy().replace('\\', "/");
+        let html = format!(r#"<link rel='stylesheet' href="{}" /><p>x</p>"#, href);
+        let inlined = inline_local_stylesheets(&html);
+        assert_eq!(
+            inlined,
+            format!(
+                r#"<style data-rinch-href="{}">body {{ color: blue; }}</style><p>x</p>"#,
+                href
+            )
+        );
+    }
+
+    #[test]
+    fn reset_window_overrides_global_styles() {
+        set_user_agent_styles(UserAgentStyles::Custom("p { color: red; }".into()));
+        assert_eq!(
+            user_agent_stylesheets("DEFAULT", false, false)[1..],
+            ["p { color: red; }", BIDI_CSS]
+        );
+        assert_eq!(
+            user_agent_stylesheets("DEFAULT", true, false)[1..],
+            [RESET_CSS, BIDI_CSS]
+        );
+
+        add_user_agent_stylesheet("button { border: 0; user-select: none; }");
+        set_user_agent_styles(UserAgentStyles::Default);
+        assert_eq!(
+            user_agent_stylesheets("DEFAULT", false, true)[1..],
+            [
+                "DEFAULT",
+                BIDI_CSS,
+                ":root { direction: rtl; }",
+                "button { border: 0; user-select: none; }",
+                "button { --rinch-user-select: none; }\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn reduced_motion_cuts_css_animations() {
+        set_user_agent_styles(UserAgentStyles::Default);
+        rinch_core::animation::set_system_reduced_motion(true);
+        let sheets = user_agent_stylesheets("DEFAULT", false, false);
+        assert_eq!(sheets.last().map(String::as_str), Some(REDUCED_MOTION_CSS));
+        rinch_core::animation::set_system_reduced_motion(false);
+        let sheets = user_agent_stylesheets("DEFAULT", false, false);
+        assert!(!sheets.contains(&REDUCED_MOTION_CSS.to_string()));
+    }
+
+    #[test]
+    fn attr_value_ignores_suffix_matches() {
+        let tag = r#"<link data-href="no" href=yes.css rel=stylesheet>"#;
+        assert_eq!(attr_value(tag, "href").as_deref(), Some("yes.css"));
+        assert_eq!(attr_value(tag, "rel").as_deref(), Some("stylesheet"));
+    }
+}
@@ -0,0 +1,10 @@
+//! Helpers shared by the shell's tests.
+
+/// Computed values, as `computed_values` returns them, from
+/// `(property, value)` pairs.
+pub(crate) fn values(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(property, value)| (property.to_string(), value.to_string()))
+        .collect()
+}
@@ -0,0 +1,646 @@
+//! Text decorations and shadows painted by rinch.
+//!
+//! The document painter draws `text-decoration` lines as solid strokes in the
+//! text color and doesn't draw `text-shadow` at all. When a window's styles
+//! use decoration styles (`wavy`, `dotted`, `dashed`, `double`), colors,
+//! thicknesses, or offsets, a generated stylesheet turns the painter's lines
+//! off and every decoration is painted here instead, from copies of the
+//! app's declarations (see `computed`). Text shadows are painted over the
+//! scene, with the text redrawn on top.
+
+use std::collections::HashMap;
+
+use anyrender::{Glyph, PaintScene};
+use blitz_dom::BaseDocument;
+use parley::PositionedLayoutItem;
+use peniko::kurbo::{Affine, BezPath, Circle, Line, Rect, Stroke};
+use peniko::{Brush, Color, Fill};
+
+use super::computed::{computed_value, computed_values};
+use super::selection::{content_origin, inline_roots};
+
+/// Turns off the document painter's decoration lines (see the module docs).
+pub(crate) const DECORATION_RESET_CSS: &str = "* { text-decoration-line: none !important; }";
+
+/// The properties an element's own decoration is read from, shorthand
+/// first.
+const DECORATION_PROPERTIES: &[&str] = &[
+    "text-decoration",
+    "text-decoration-line",
+    "text-decoration-style",
+    "text-decoration-color",
+    "text-decoration-thickness",
+    "text-underline-offset",
+];
+
+/// Default font size for `rem` lengths.
+const ROOT_FONT_SIZE: f32 = 16.0;
+/// Copies drawn to approximate a blurred shadow.
+const BLUR_SAMPLES: usize = 8;
+
+/// Which decoration lines to draw.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DecorationLines {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+impl DecorationLines {
+    fn any(&self) -> bool {
+        self.underline || self.overline || self.line_through
+    }
+}
+
+/// `text-decoration-style`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum DecorationStyle {
+    #[default]
+    Solid,
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
+
+impl DecorationStyle {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "solid" => Self::Solid,
+            "double" => Self::Double,
+            "dotted" => Self::Dotted,
+            "dashed" => Self::Dashed,
+            "wavy" => Self::Wavy,
+            _ => return None,
+        })
+    }
+}
+
+/// A CSS length, resolved against the text's font size when painting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Length {
+    Px(f32),
+    Em(f32),
+    /// A fraction of the font size, from percentages.
+    Percent(f32),
+}
+
+impl Length {
+    fn parse(value: &str) -> Option<Self> {
+        let number = |suffix: &str| value.strip_suffix(suffix)?.parse::<f32>().ok();
+        if let Some(n) = number("px") {
+            Some(Self::Px(n))
+        } else if let Some(n) = number("rem") {
+            Some(Self::Px(n * ROOT_FONT_SIZE))
+        } else if let Some(n) = number("em") {
+            Some(Self::Em(n))
+        } else if let Some(n) = number("pt") {
+            Some(Self::Px(n * 4.0 / 3.0))
+        } else if let Some(n) = number("%") {
+            Some(Self::Percent(n / 100.0))
+        } else {
+            // Unitless zero
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|n| *n == 0.0)
+                .map(Self::Px)
+        }
+    }
+
+    fn resolve(self, font_size: f32) -> f32 {
+        match self {
+            Self::Px(px) => px,
+            Self::Em(em) => em * font_size,
+            Self::Percent(fraction) => fraction * font_size,
+        }
+    }
+}
+
+/// A resolved `text-decoration`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Decoration {
+    pub lines: DecorationLines,
+    pub style: DecorationStyle,
+    /// Line color as written; `None` for `currentColor`.
+    pub color: Option<String>,
+    /// Line thickness; `None` for the font's own.
+    pub thickness: Option<Length>,
+    /// Extra distance between the underline and the font's underline position.
+    pub underline_offset: Option<Length>,
+}
+
+impl Decoration {
+    /// Apply a `text-decoration` shorthand value.
+    fn apply_shorthand(&mut self, value: &str) {
+        let mut lines = DecorationLines::default();
+        for token in split_outside_parens(value, ' ') {
+            match token {
+                "none" => {}
+                "underline" => lines.underline = true,
+                "overline" => lines.overline = true,
+                "line-through" => lines.line_through = true,
+                "auto" | "from-font" => self.thickness = None,
+                _ => {
+                    if let Some(style) = DecorationStyle::parse(token) {
+                        self.style = style;
+                    } else if let Some(length) = Length::parse(token) {
+                        self.thickness = Some(length);
+                    } else {
+                        self.color = parse_color_value(token);
+                    }
+                }
+            }
+        }
+        self.lines = lines;
+    }
+
+    /// Apply a `text-decoration-*` longhand.
+    fn apply_longhand(&mut self, property: &str, value: &str) {
+        match property {
+            "text-decoration-line" => {
+                let mut shorthand = Decoration::default();
+                shorthand.apply_shorthand(value);
+                self.lines = shorthand.lines;
+            }
+            "text-decoration-style" => {
+                if let Some(style) = DecorationStyle::parse(value) {
+                    self.style = style;
+                }
+            }
+            "text-decoration-color" => self.color = parse_color_value(value),
+            "text-decoration-thickness" => self.thickness = Length::parse(value),
+            "text-underline-offset" => self.underline_offset = Length::parse(value),
+            _ => {}
+        }
+    }
+}
+
+/// One `text-shadow` layer.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextShadow {
+    pub x: Length,
+    pub y: Length,
+    pub blur: Length,
+    /// Shadow color as written; `None` for `currentColor`.
+    pub color: Option<String>,
+}
+
+/// Parse a `text-shadow` value into its layers, topmost first.
+pub(crate) fn parse_text_shadows(value: &str) -> Vec<TextShadow> {
+    if value.trim() == "none" {
+        return Vec::new();
+    }
+    split_outside_parens(value, ',')
+        .into_iter()
+        .filter_map(|layer| {
+            let mut lengths = Vec::new();
+            let mut color = None;
+            for token in split_outside_parens(layer, ' ') {
+                match Length::parse(token) {
+                    Some(length) => lengths.push(length),
+                    None => color = parse_color_value(token),
+                }
+            }
+            let (x, y) = (*lengths.first()?, *lengths.get(1)?);
+            Some(TextShadow {
+                x,
+                y,
+                blur: lengths.get(2).copied().unwrap_or(Length::Px(0.0)),
+                color,
+            })
+        })
+        .collect()
+}
+
+fn parse_color_value(value: &str) -> Option<String> {
+    (value != "currentcolor" && value != "currentColor").then(|| value.to_string())
+}
+
+/// Split on a separator, keeping parenthesized groups like `rgb(0, 0, 0)`
+/// together and dropping empty pieces.
+pub(crate) fn split_outside_parens(value: &str, separator: char) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && (c == separator || (separator == ' ' && c.is_whitespace())) => {
+                pieces.push(value[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    pieces.push(value[start..].trim());
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
+}
+
+/// Whether any CSS in `text` uses decoration features the document painter
+/// can't draw, so rinch must paint decorations itself.
+pub(crate) fn needs_decoration_painting(text: &str) -> bool {
+    ["text-decoration", "text-underline-offset"]
+        .iter()
+        .flat_map(|name| text.match_indices(name))
+        .any(|(i, _)| {
+            let rest = &text[i..];
+            let end = rest.find([';', '}', '"', '\'']).unwrap_or(rest.len());
+            let Some((property, value)) = rest[..end].split_once(':') else {
+                return false;
+            };
+            let value = value.trim().trim_end_matches("!important").trim();
+            match property.trim() {
+                "text-decoration" => {
+                    let mut plain = Decoration::default();
+                    plain.apply_shorthand(value);
+                    plain.style != DecorationStyle::Solid
+                        || plain.color.is_some()
+                        || plain.thickness.is_some()
+                }
+                "text-decoration-line" => false,
+                "text-decoration-style" => value != "solid",
+                "text-decoration-color" => parse_color_value(value).is_some(),
+                "text-decoration-thickness" | "text-underline-offset" => {
+                    !matches!(value, "auto" | "from-font")
+                }
+                _ => false,
+            }
+        })
+}
+
+/// Decorations and shadows resolved for a document's elements.
+#[derive(Debug, Default)]
+pub struct TextEffects {
+    /// Decorations by element, including those propagated from ancestors.
+    /// Only filled in when rinch paints decorations.
+    decorations: HashMap<usize, Decoration>,
+    /// Shadow layers by element.
+    shadows: HashMap<usize, Vec<TextShadow>>,
+}
+
+impl TextEffects {
+    /// Resolve effects for every element of a freshly built and styled
+    /// document.
+    pub(crate) fn new(doc: &BaseDocument, paint_decorations: bool) -> Self {
+        let mut effects = Self::default();
+        let mut stack: Vec<(usize, Option<Decoration>)> = vec![(0, None)];
+        while let Some((node_id, inherited)) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            let mut decoration = inherited;
+
+            if node.element_data().is_some() {
+                if paint_decorations {
+                    // An element's own lines replace its ancestors'
+                    if let Some(own) = own_decoration(doc, node_id) {
+                        decoration = own.lines.any().then_some(own);
+                    }
+                    if let Some(decoration) = &decoration {
+                        effects.decorations.insert(node_id, decoration.clone());
+                    }
+                }
+                if let Some(value) = computed_value(doc, node_id, "text-shadow") {
+                    let layers = parse_text_shadows(&value);
+                    if !layers.is_empty() {
+                        effects.shadows.insert(node_id, layers);
+                    }
+                }
+            }
+
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|&child| (child, decoration.clone())),
+            );
+        }
+        effects
+    }
+
+    /// Paint shadows and decorations over the scene.
+    pub(crate) fn paint(&self, scene: &mut impl PaintScene, doc: &BaseDocument, scale: f64) {
+        if self.decorations.is_empty() && self.shadows.is_empty() {
+            return;
+        }
+
+        for root_id in inline_roots(doc) {
+            let Some(text) = doc
+                .get_node(root_id)
+                .and_then(|node| node.element_data())
+                .and_then(|el| el.inline_layout_data.as_ref())
+            else {
+                continue;
+            };
+            let (origin_x, origin_y) = content_origin(doc, root_id);
+            let transform = Affine::scale(scale) * Affine::translate((origin_x, origin_y));
+
+            for line in text.layout.lines() {
+                for item in line.items() {
+                    let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                        continue;
+                    };
+                    let brush = &glyph_run.style().brush;
+                    let Some(element_id) = element_of(doc, brush.id) else {
+                        continue;
+                    };
+                    let text_color = match &brush.brush {
+                        Brush::Solid(color) => *color,
+                        _ => Color::BLACK,
+                    };
+                    let run = glyph_run.run();
+                    let font_size = run.font_size();
+
+                    if let Some(layers) = self.shadows.get(&element_id) {
+                        let glyphs: Vec<Glyph> = glyph_run
+                            .positioned_glyphs()
+                            .map(|g| Glyph {
+                                id: g.id as _,
+                                x: g.x,
+                                y: g.y,
+                            })
+                            .collect();
+                        let draw = |scene: &mut _, color: Color, dx: f64, dy: f64| {
+                            draw_glyphs(
+                                scene,
+                                &glyph_run,
+                                &glyphs,
+                                color,
+                                transform * Affine::translate((dx, dy)),
+                            );
+                        };
+
+                        // CSS paints the first layer on top
+                        for layer in layers.iter().rev() {
+                            let color = resolve_color(layer.color.as_deref(), text_color);
+                            let dx = layer.x.resolve(font_size) as f64;
+                            let dy = layer.y.resolve(font_size) as f64;
+                            let blur = layer.blur.resolve(font_size) as f64;
+                            if blur <= 0.0 {
+                                draw(scene, color, dx, dy);
+                                continue;
+                            }
+                            // Approximate the blur with faint copies around the offset
+                            let faint = color.multiply_alpha(1.0 / BLUR_SAMPLES as f32 * 2.0);
+                            for i in 0..BLUR_SAMPLES {
+                                let angle = std::f64::consts::TAU * i as f64 / BLUR_SAMPLES as f64;
+                                let radius = blur / 2.0;
+                                draw(
+                                    scene,
+                                    faint,
+                                    dx + radius * angle.cos(),
+                                    dy + radius * angle.sin(),
+                                );
+                            }
+                        }
+                        // Redraw the text over its shadows
+                        draw(scene, text_color, 0.0, 0.0);
+                    }
+
+                    if let Some(decoration) = self.decorations.get(&element_id) {
+                        let metrics = run.metrics();
+                        let color = resolve_color(decoration.color.as_deref(), text_color);
+                        let x0 = glyph_run.offset() as f64;
+                        let x1 = x0 + glyph_run.advance() as f64;
+                        let baseline = glyph_run.baseline();
+
+                        let mut draw_line = |offset: f32, font_thickness: f32| {
+                            let thickness = decoration
+                                .thickness
+                                .map(|t| t.resolve(font_size))
+                                .unwrap_or(font_thickness)
+                                .max(1.0 / scale as f32);
+                            let y = (baseline - offset + thickness / 2.0) as f64;
+                            paint_decoration_line(
+                                scene,
+                                transform,
+                                color,
+                                decoration.style,
+                                x0,
+                                x1,
+                                y,
+                                thickness as f64,
+                            );
+                        };
+
+                        if decoration.lines.underline {
+                            let extra = decoration
+                                .underline_offset
+                                .map(|o| o.resolve(font_size))
+                                .unwrap_or(0.0);
+                            draw_line(metrics.underline_offset - extra, metrics.underline_size);
+                        }
+                        if decoration.lines.overline {
+                            draw_line(metrics.ascent, metrics.underline_size);
+                        }
+                        if decoration.lines.line_through {
+                            draw_line(metrics.strikethrough_offset, metrics.strikethrough_size);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The decoration an element declares itself, if it sets its lines.
+fn own_decoration(doc: &BaseDocument, node_id: usize) -> Option<Decoration> {
+    let values = computed_values(doc, node_id, DECORATION_PROPERTIES);
+    let mut decoration = Decoration::default();
+    let mut sets_lines = false;
+    if let Some((_, value)) = values.iter().find(|(p, _)| p == "text-decoration") {
+        decoration.apply_shorthand(value);
+        sets_lines = true;
+    }
+    for (property, value) in &values {
+        if property.starts_with("text-decoration-") || property == "text-underline-offset" {
+            decoration.apply_longhand(property, value);
+            sets_lines |= property == "text-decoration-line";
+        }
+    }
+    sets_lines.then_some(decoration)
+}
+
+/// The element a glyph run's style belongs to.
+pub(crate) fn element_of(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let node = doc.get_node(id)?;
+        if node.element_data().is_some() {
+            return Some(id);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+fn resolve_color(value: Option<&str>, current_color: Color) -> Color {
+    value
+        .and_then(|value| peniko::color::parse_color(value).ok())
+        .map(|color| color.to_alpha_color())
+        .unwrap_or(current_color)
+}
+
+fn draw_glyphs(
+    scene: &mut impl PaintScene,
+    glyph_run: &parley::GlyphRun<'_, blitz_dom::node::TextBrush>,
+    glyphs: &[Glyph],
+    color: Color,
+    transform: Affine,
+) {
+    let run = glyph_run.run();
+    let glyph_transform = run
+        .synthesis()
+        .skew()
+        .map(|angle| Affine::skew(angle.to_radians().tan() as f64, 0.0));
+    scene.draw_glyphs(
+        run.font(),
+        run.font_size(),
+        true,
+        run.normalized_coords(),
+        Fill::NonZero,
+        color,
+        1.0,
+        transform,
+        glyph_transform,
+        glyphs.iter().copied(),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paint_decoration_line(
+    scene: &mut impl PaintScene,
+    transform: Affine,
+    color: Color,
+    style: DecorationStyle,
+    x0: f64,
+    x1: f64,
+    y: f64,
+    thickness: f64,
+) {
+    let stroke = Stroke::new(thickness);
+    match style {
+        DecorationStyle::Solid => {
+            scene.stroke(
+                &stroke,
+                transform,
+                color,
+                None,
+                &Line::new((x0, y), (x1, y)),
+            );
+        }
+        DecorationStyle::Double => {
+            for y in [y, y + thickness * 2.0] {
+                scene.stroke(
+                    &stroke,
+                    transform,
+                    color,
+                    None,
+                    &Line::new((x0, y), (x1, y)),
+                );
+            }
+        }
+        DecorationStyle::Dotted => {
+            let mut x = x0 + thickness / 2.0;
+            while x < x1 {
+                scene.fill(
+                    Fill::NonZero,
+                    transform,
+                    color,
+                    None,
+                    &Circle::new((x, y), thickness / 2.0),
+                );
+                x += thickness * 2.0;
+            }
+        }
+        DecorationStyle::Dashed => {
+            let dash = thickness * 3.0;
+            let mut x = x0;
+            while x < x1 {
+                let end = (x + dash).min(x1);
+                let rect = Rect::new(x, y - thickness / 2.0, end, y + thickness / 2.0);
+                scene.fill(Fill::NonZero, transform, color, None, &rect);
+                x += dash * 2.0;
+            }
+        }
+        DecorationStyle::Wavy => {
+            scene.stroke(
+                &stroke,
+                transform,
+                color,
+                None,
+                &wavy_path(x0, x1, y, thickness),
+            );
+        }
+    }
+}
+
+/// A wave along `y` from `x0` to `x1`, sized for the line thickness.
+fn wavy_path(x0: f64, x1: f64, y: f64, thickness: f64) -> BezPath {
+    let amplitude = (thickness * 1.5).max(1.0);
+    let half_wave = amplitude * 2.0;
+    let mut path = BezPath::new();
+    path.move_to((x0, y));
+    let mut x = x0;
+    let mut up = true;
+    while x < x1 {
+        let next = (x + half_wave).min(x1);
+        let peak = if up { y - amplitude } else { y + amplitude };
+        path.quad_to(((x + next) / 2.0, peak), (next, y));
+        x = next;
+        up = !up;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decoration_shorthand() {
+        let mut decoration = Decoration::default();
+        decoration.apply_shorthand("underline wavy rgb(255, 0, 0) 2px");
+        assert!(decoration.lines.underline && !decoration.lines.line_through);
+        assert_eq!(decoration.style, DecorationStyle::Wavy);
+        assert_eq!(decoration.color.as_deref(), Some("rgb(255, 0, 0)"));
+        assert_eq!(decoration.thickness, Some(Length::Px(2.0)));
+
+        decoration.apply_longhand("text-decoration-line", "line-through overline");
+        assert!(decoration.lines.line_through && decoration.lines.overline);
+        assert!(!decoration.lines.underline);
+    }
+
+    #[test]
+    fn parses_text_shadows() {
+        let shadows = parse_text_shadows("1px 2px 3px rgba(0, 0, 0, 0.5), 0 0 0.1em white");
+        assert_eq!(shadows.len(), 2);
+        assert_eq!(shadows[0].x, Length::Px(1.0));
+        assert_eq!(shadows[0].blur, Length::Px(3.0));
+        assert_eq!(shadows[0].color.as_deref(), Some("rgba(0, 0, 0, 0.5)"));
+        assert_eq!(shadows[1].blur, Length::Em(0.1));
+        assert!(parse_text_shadows("none").is_empty());
+    }
+
+    #[test]
+    fn detects_decorations_the_painter_cannot_draw() {
+        assert!(!needs_decoration_painting(
+            "a { text-decoration: underline; }"
+        ));
+        assert!(!needs_decoration_painting("a { text-decoration: none }"));
+        assert!(needs_decoration_painting(
+            ".typo { text-decoration: underline wavy red; }"
+        ));
+        assert!(needs_decoration_painting(
+            r#"<span style="text-decoration-style: dotted">"#
+        ));
+        assert!(needs_decoration_painting(
+            "a { text-underline-offset: 3px }"
+        ));
+        assert!(!needs_decoration_painting(
+            "a { text-decoration-style: solid }"
+        ));
+    }
+}
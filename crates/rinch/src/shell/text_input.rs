@@ -0,0 +1,263 @@
+//! Editable text fields.
+//!
+//! Blitz edits `input` and `textarea` elements itself: it keeps their text,
+//! caret and selection, paints them, and composes IME input. While a text
+//! field has focus, rinch forwards key presses and IME events to it, reads
+//! the field's value back, and passes it to the app:
+//!
+//! - `bind` (from `bind: signal`) and `oninput` run with each new value.
+//! - `onchange` runs when the value has changed since the field was focused
+//!   (or last changed) and the user presses Enter in an `input`, or moves
+//!   focus away.
+//!
+//! A re-render normally rebuilds the document, which would put the caret
+//! back at the start. When the only difference in the new HTML is the
+//! focused field's value, now what the user typed, the document is kept
+//! instead (see [`TextField::keeps_document`]). An app that rewrites the
+//! value (say, uppercasing it) gets a rebuild, and the caret moves; it can
+//! put the caret back with `NodeRef::set_caret` (see [`set_caret`]).
+
+use blitz_dom::BaseDocument;
+use rinch_core::events::{html_escape_string, EventHandlerId};
+
+/// Attribute with a field's `bind` handler ID.
+const BIND_ATTR: &str = "data-bind";
+/// Attribute with a field's `oninput` handler ID.
+const INPUT_ATTR: &str = "data-oninput";
+/// Attribute with a field's `onchange` handler ID.
+const CHANGE_ATTR: &str = "data-onchange";
+
+/// A text field's value handlers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct FieldHandlers {
+    bind: Option<EventHandlerId>,
+    input: Option<EventHandlerId>,
+    change: Option<EventHandlerId>,
+}
+
+impl FieldHandlers {
+    /// The handlers on `node_id`.
+    pub(crate) fn of(doc: &BaseDocument, node_id: usize) -> Self {
+        let Some(data) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+            return Self::default();
+        };
+        let handler = |name: &str| {
+            data.attrs()
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == name)
+                .and_then(|attr| attr.value.parse().ok())
+                .map(EventHandlerId)
+        };
+        Self {
+            bind: handler(BIND_ATTR),
+            input: handler(INPUT_ATTR),
+            change: handler(CHANGE_ATTR),
+        }
+    }
+
+    /// The handlers to run with each new value: the binding, then `oninput`.
+    pub(crate) fn on_input(&self) -> Vec<EventHandlerId> {
+        self.bind.into_iter().chain(self.input).collect()
+    }
+
+    /// The handlers to run when the value is committed.
+    pub(crate) fn on_change(&self) -> Vec<EventHandlerId> {
+        self.change.into_iter().collect()
+    }
+}
+
+/// The value blitz holds for a text field, as edited so far.
+pub(crate) fn field_value(doc: &BaseDocument, node_id: usize) -> Option<String> {
+    let input = doc.get_node(node_id)?.element_data()?.text_input_data()?;
+    Some(input.editor.raw_text().to_string())
+}
+
+/// Put a text field's caret before the character at `position`, or at the
+/// end of its text. Returns `false` if blitz doesn't edit the node.
+pub(crate) fn set_caret(doc: &mut BaseDocument, node_id: usize, position: usize) -> bool {
+    let Some(text) = field_value(doc, node_id) else {
+        return false;
+    };
+    let index = text
+        .char_indices()
+        .nth(position)
+        .map_or(text.len(), |(index, _)| index);
+    doc.with_text_input(node_id, |mut driver| driver.move_to_byte(index));
+    true
+}
+
+/// Whether a text field takes more than one line.
+fn is_multiline(doc: &BaseDocument, node_id: usize) -> bool {
+    doc.get_node(node_id)
+        .and_then(|node| node.element_data())
+        .is_some_and(|element| element.name.local.as_ref() == "textarea")
+}
+
+/// The focused text field.
+#[derive(Debug, Clone)]
+pub(crate) struct TextField {
+    node: usize,
+    handlers: FieldHandlers,
+    multiline: bool,
+    /// The value in the HTML the document was built from.
+    rendered: String,
+    /// The value the app was last given.
+    value: String,
+    /// The value when the field was focused or last committed.
+    committed: String,
+}
+
+impl TextField {
+    /// The text field `node_id`, just focused, or `None` if blitz doesn't
+    /// edit it.
+    pub(crate) fn new(doc: &BaseDocument, node_id: usize) -> Option<Self> {
+        let value = field_value(doc, node_id)?;
+        Some(Self {
+            node: node_id,
+            handlers: FieldHandlers::of(doc, node_id),
+            multiline: is_multiline(doc, node_id),
+            rendered: value.clone(),
+            value: value.clone(),
+            committed: value,
+        })
+    }
+
+    /// The field's node.
+    pub(crate) fn node(&self) -> usize {
+        self.node
+    }
+
+    /// The field's handlers.
+    pub(crate) fn handlers(&self) -> FieldHandlers {
+        self.handlers
+    }
+
+    /// Whether Enter adds a line rather than committing the value.
+    pub(crate) fn is_multiline(&self) -> bool {
+        self.multiline
+    }
+
+    /// Find the field again in a rebuilt document, where focus left it.
+    /// Returns false if it's no longer a text field.
+    pub(crate) fn rebuilt(&mut self, doc: &BaseDocument, node_id: usize) -> bool {
+        let Some(value) = field_value(doc, node_id) else {
+            return false;
+        };
+        self.node = node_id;
+        self.handlers = FieldHandlers::of(doc, node_id);
+        self.multiline = is_multiline(doc, node_id);
+        self.rendered = value.clone();
+        self.value = value;
+        true
+    }
+
+    /// Note the field's value after an edit, returning it if it changed.
+    pub(crate) fn edited(&mut self, value: String) -> Option<String> {
+        if value == self.value {
+            return None;
+        }
+        self.value = value.clone();
+        Some(value)
+    }
+
+    /// Commit the value, returning it if it changed since it was last
+    /// committed.
+    pub(crate) fn commit(&mut self) -> Option<String> {
+        if self.value == self.committed {
+            return None;
+        }
+        self.committed = self.value.clone();
+        Some(self.value.clone())
+    }
+
+    /// Whether the document can stay as it is for `new_html`, because it
+    /// differs from `old_html` only in this field now holding what the user
+    /// typed. The field's value is then taken as rendered.
+    pub(crate) fn keeps_document(&mut self, old_html: &str, new_html: &str) -> bool {
+        if self.rendered == self.value {
+            return false;
+        }
+        let old = html_escape_string(&self.rendered);
+        let new = html_escape_string(&self.value);
+        if !replaces_once(old_html, new_html, &old, &new) {
+            return false;
+        }
+        self.rendered = self.value.clone();
+        true
+    }
+}
+
+/// Whether `new_html` is `old_html` with one occurrence of `old` replaced by
+/// `new`.
+fn replaces_once(old_html: &str, new_html: &str, old: &str, new: &str) -> bool {
+    if new_html.len() + old.len() != old_html.len() + new.len() {
+        return false;
+    }
+    let prefix = old_html
+        .bytes()
+        .zip(new_html.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    // The replaced text starts at the first difference, or up to its
+    // length before it if it begins with what follows
+    (prefix.saturating_sub(old.len())..=prefix).any(|start| {
+        old_html
+            .get(start..)
+            .is_some_and(|rest| rest.starts_with(old))
+            && new_html
+                .get(start..)
+                .is_some_and(|rest| rest.starts_with(new))
+            && old_html.get(..start) == new_html.get(..start)
+            && old_html.get(start + old.len()..) == new_html.get(start + new.len()..)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(rendered: &str, value: &str) -> TextField {
+        TextField {
+            node: 1,
+            handlers: FieldHandlers::default(),
+            multiline: false,
+            rendered: rendered.into(),
+            value: value.into(),
+            committed: rendered.into(),
+        }
+    }
+
+    #[test]
+    fn typing_keeps_the_document() {
+        let mut name = field("Ad", "Ada");
+        let old = r#"<input value="Ad" data-bind="0"><p>Hi</p>"#;
+        let new = r#"<input value="Ada" data-bind="0"><p>Hi</p>"#;
+        assert!(name.keeps_document(old, new));
+        // Once rendered, the next change needs the next edit
+        assert!(!name.keeps_document(new, new));
+
+        // Other changes rebuild it
+        let mut name = field("Ad", "Ada");
+        let greeting = r#"<input value="Ada" data-bind="0"><p>Hi Ada</p>"#;
+        assert!(!name.keeps_document(old, greeting));
+        let uppercased = r#"<input value="ADA" data-bind="0"><p>Hi</p>"#;
+        assert!(!name.keeps_document(old, uppercased));
+    }
+
+    #[test]
+    fn typing_into_empty_fields_keeps_the_document() {
+        let mut notes = field("", "a<b");
+        let old = "<textarea data-bind=\"0\"></textarea>";
+        let new = "<textarea data-bind=\"0\">a&lt;b</textarea>";
+        assert!(notes.keeps_document(old, new));
+    }
+
+    #[test]
+    fn changes_are_committed_once() {
+        let mut name = field("Ad", "Ad");
+        assert_eq!(name.edited("Ad".into()), None);
+        assert_eq!(name.edited("Ada".into()), Some("Ada".into()));
+        assert_eq!(name.commit(), Some("Ada".into()));
+        assert_eq!(name.commit(), None);
+    }
+}
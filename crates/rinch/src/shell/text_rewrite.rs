@@ -0,0 +1,419 @@
+//! Text styles applied by rewriting text content.
+//!
+//! After a document is built and styled, text nodes under these properties
+//! are rewritten, and laid out again.
+//!
+//! `text-transform: uppercase | lowercase | capitalize` changes letter case.
+//!
+//! Text layout only breaks lines at spaces and punctuation, so long URLs and
+//! paths overflow narrow containers. Text under these properties gets
+//! invisible break opportunities inserted:
+//!
+//! - `word-break: break-all` breaks between any two characters.
+//! - `overflow-wrap: anywhere | break-word` (and legacy `word-wrap:
+//!   break-word`) breaks long words after URL and path separators, and long
+//!   runs without separators between any two characters.
+//! - `hyphens: auto` inserts soft hyphens at dictionary hyphenation points
+//!   for the text's `lang` (requires the `hyphenation` feature).
+//!
+//! Break opportunities are zero-width spaces; they're removed again from
+//! copied text and ignored by search.
+
+use blitz_dom::{BaseDocument, NodeData};
+
+use super::computed::computed_value;
+
+/// Zero-width space: a line break opportunity with no visible width.
+pub(crate) const BREAK_OPPORTUNITY: char = '\u{200B}';
+/// Soft hyphen: a hyphenation point, shown only when a line breaks there.
+pub(crate) const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// Words this many characters or longer may be broken by `overflow-wrap`.
+const LONG_WORD_CHARS: usize = 20;
+/// Words shorter than this aren't hyphenated.
+const MIN_HYPHENATED_CHARS: usize = 5;
+
+/// Elements whose text isn't laid out as content, and is left alone.
+const SKIPPED_ELEMENTS: &[&str] = &["style", "script", "title", "textarea"];
+
+/// Characters after which `overflow-wrap` prefers to break a long word.
+const WORD_SEPARATORS: &[char] = &['/', '\\', '.', '-', '_', '?', '&', '=', '#', ':', ',', ';'];
+
+/// `text-transform` case changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum TextTransform {
+    #[default]
+    None,
+    Uppercase,
+    Lowercase,
+    /// Uppercase the first letter of each word.
+    Capitalize,
+}
+
+impl TextTransform {
+    fn parse(value: &str) -> Self {
+        // `full-width` and `full-size-kana` may follow the keyword
+        match value.split_whitespace().next() {
+            Some("uppercase") => Self::Uppercase,
+            Some("lowercase") => Self::Lowercase,
+            Some("capitalize") => Self::Capitalize,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Line breaking behavior for a run of text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct WrapStyle {
+    /// `word-break: break-all`.
+    pub break_all: bool,
+    /// `overflow-wrap: anywhere | break-word`.
+    pub break_long_words: bool,
+    /// `hyphens: auto`, with the text's language.
+    pub hyphenate: Option<String>,
+}
+
+impl WrapStyle {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Rewrite the document's text according to its computed case and line
+/// breaking styles. Returns whether any text changed.
+pub(crate) fn apply_text_rewrites(doc: &mut BaseDocument) -> bool {
+    let mut edits = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if node
+            .element_data()
+            .is_some_and(|el| SKIPPED_ELEMENTS.contains(&el.name.local.as_ref()))
+        {
+            continue;
+        }
+        stack.extend(node.children.iter());
+        let NodeData::Text(text) = &node.data else {
+            continue;
+        };
+        let value = |properties: &[&str]| {
+            properties
+                .iter()
+                .find_map(|property| computed_value(doc, node_id, property))
+        };
+
+        let transform = value(&["text-transform"])
+            .map(|value| TextTransform::parse(&value))
+            .unwrap_or_default();
+        let mut style = WrapStyle {
+            break_all: value(&["word-break"]).is_some_and(|value| value == "break-all"),
+            break_long_words: value(&["overflow-wrap", "word-wrap"])
+                .is_some_and(|value| value == "anywhere" || value == "break-word"),
+            hyphenate: None,
+        };
+        if value(&["hyphens", "-webkit-hyphens"]).is_some_and(|value| value == "auto") {
+            style.hyphenate = Some(language(doc, node_id));
+        }
+
+        if transform == TextTransform::None && style.is_default() {
+            continue;
+        }
+        let rewritten = wrap_text(&transform_text(&text.content, transform), &style);
+        if rewritten != text.content {
+            edits.push((node_id, rewritten));
+        }
+    }
+
+    if edits.is_empty() {
+        return false;
+    }
+    let mut mutator = doc.mutate();
+    for (node_id, content) in edits {
+        mutator.set_node_text(node_id, &content);
+    }
+    true
+}
+
+/// The `lang` of a node's nearest ancestor that sets one, defaulting to
+/// English.
+fn language(doc: &BaseDocument, node_id: usize) -> String {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        let Some(node) = doc.get_node(id) else {
+            break;
+        };
+        if let Some(lang) = node
+            .element_data()
+            .and_then(|el| el.attrs().iter().find(|a| a.name.local.as_ref() == "lang"))
+            .filter(|a| !a.value.is_empty())
+        {
+            return lang.value.to_string();
+        }
+        current = node.parent;
+    }
+    "en".to_string()
+}
+
+/// Apply a `text-transform` to a run of text.
+pub(crate) fn transform_text(text: &str, transform: TextTransform) -> String {
+    match transform {
+        TextTransform::None => text.to_string(),
+        TextTransform::Uppercase => text.to_uppercase(),
+        TextTransform::Lowercase => text.to_lowercase(),
+        TextTransform::Capitalize => {
+            let mut out = String::with_capacity(text.len());
+            let mut at_word_start = true;
+            for c in text.chars() {
+                if at_word_start && c.is_alphanumeric() {
+                    out.extend(c.to_uppercase());
+                    at_word_start = false;
+                } else {
+                    out.push(c);
+                    if c.is_whitespace() || matches!(c, '-' | '/' | '(' | '"') {
+                        at_word_start = true;
+                    }
+                }
+            }
+            out
+        }
+    }
+}
+
+/// Insert break opportunities into a run of text.
+pub(crate) fn wrap_text(text: &str, style: &WrapStyle) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            out.push_str(&wrap_word(&word, style));
+            word.clear();
+            out.push(c);
+        } else {
+            word.push(c);
+        }
+    }
+    out.push_str(&wrap_word(&word, style));
+    out
+}
+
+fn wrap_word(word: &str, style: &WrapStyle) -> String {
+    let len = word.chars().count();
+    if len < 2 {
+        return word.to_string();
+    }
+
+    let word = match &style.hyphenate {
+        Some(lang) if !style.break_all && len >= MIN_HYPHENATED_CHARS => hyphenate(word, lang),
+        _ => word.to_string(),
+    };
+
+    if style.break_all {
+        return break_between_chars(&word);
+    }
+    if style.break_long_words && len >= LONG_WORD_CHARS {
+        // Prefer breaking after separators, then split any remaining long run
+        let mut out = String::with_capacity(word.len());
+        let mut run = String::new();
+        let mut chars = word.chars().peekable();
+        while let Some(c) = chars.next() {
+            run.push(c);
+            if WORD_SEPARATORS.contains(&c) && chars.peek().is_some() {
+                out.push_str(&break_long_run(&run));
+                out.push(BREAK_OPPORTUNITY);
+                run.clear();
+            }
+        }
+        out.push_str(&break_long_run(&run));
+        return out;
+    }
+    word
+}
+
+fn break_long_run(run: &str) -> String {
+    if run.chars().count() >= LONG_WORD_CHARS {
+        break_between_chars(run)
+    } else {
+        run.to_string()
+    }
+}
+
+fn break_between_chars(word: &str) -> String {
+    let mut out = String::with_capacity(word.len() * 2);
+    for (i, c) in word.chars().enumerate() {
+        // Don't separate combining marks from their base character
+        if i > 0 && !is_combining(c) && c != SOFT_HYPHEN {
+            out.push(BREAK_OPPORTUNITY);
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}' | '\u{200D}' | '\u{FE0F}')
+}
+
+/// Insert soft hyphens at the word's hyphenation points.
+#[cfg(feature = "hyphenation")]
+fn hyphenate(word: &str, lang: &str) -> String {
+    use hyphenation::{Hyphenator, Language, Load, Standard};
+
+    thread_local! {
+        static DICTIONARIES: std::cell::RefCell<HashMap<Language, Option<Standard>>> =
+            std::cell::RefCell::new(HashMap::new());
+    }
+
+    let Some(language) = hyphenation_language(lang) else {
+        return word.to_string();
+    };
+    DICTIONARIES.with(|dictionaries| {
+        let mut dictionaries = dictionaries.borrow_mut();
+        let dictionary = dictionaries.entry(language).or_insert_with(|| {
+            Standard::from_embedded(language)
+                .map_err(|e| tracing::warn!("No hyphenation dictionary for {}: {}", lang, e))
+                .ok()
+        });
+        let Some(dictionary) = dictionary else {
+            return word.to_string();
+        };
+
+        let breaks = dictionary.hyphenate(word).breaks;
+        let mut out = String::with_capacity(word.len() + breaks.len() * 2);
+        let mut last = 0;
+        for index in breaks {
+            out.push_str(&word[last..index]);
+            out.push(SOFT_HYPHEN);
+            last = index;
+        }
+        out.push_str(&word[last..]);
+        out
+    })
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn hyphenate(word: &str, _lang: &str) -> String {
+    word.to_string()
+}
+
+/// Map a BCP 47 language tag to a hyphenation dictionary.
+#[cfg(feature = "hyphenation")]
+fn hyphenation_language(lang: &str) -> Option<hyphenation::Language> {
+    use hyphenation::Language;
+
+    let lang = lang.to_ascii_lowercase();
+    let primary = lang.split(['-', '_']).next().unwrap_or_default();
+    Some(match (primary, lang.as_str()) {
+        (_, "en-gb") => Language::EnglishGB,
+        ("en", _) => Language::EnglishUS,
+        ("de", _) => Language::German1996,
+        ("fr", _) => Language::French,
+        ("es", _) => Language::Spanish,
+        ("it", _) => Language::Italian,
+        ("pt", _) => Language::Portuguese,
+        ("nl", _) => Language::Dutch,
+        ("sv", _) => Language::Swedish,
+        ("da", _) => Language::Danish,
+        ("nb" | "no", _) => Language::NorwegianBokmal,
+        ("fi", _) => Language::Finnish,
+        ("pl", _) => Language::Polish,
+        ("cs", _) => Language::Czech,
+        ("ru", _) => Language::Russian,
+        ("uk", _) => Language::Ukrainian,
+        ("tr", _) => Language::Turkish,
+        ("hu", _) => Language::Hungarian,
+        _ => return None,
+    })
+}
+
+/// Remove inserted break opportunities and soft hyphens from text.
+pub(crate) fn strip_breaks(text: &str) -> String {
+    text.chars()
+        .filter(|&c| c != BREAK_OPPORTUNITY && c != SOFT_HYPHEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rinch_core::element::Element;
+
+    use super::*;
+    use crate::testing::TestApp;
+
+    #[test]
+    fn text_transform_changes_case() {
+        assert_eq!(
+            transform_text("Explorer", TextTransform::Uppercase),
+            "EXPLORER"
+        );
+        assert_eq!(
+            transform_text("straße", TextTransform::Uppercase),
+            "STRASSE"
+        );
+        assert_eq!(
+            transform_text("OPEN Editors", TextTransform::Lowercase),
+            "open editors"
+        );
+        assert_eq!(
+            transform_text("open editors (two-pane)", TextTransform::Capitalize),
+            "Open Editors (Two-Pane)"
+        );
+        assert_eq!(
+            TextTransform::parse("uppercase full-width"),
+            TextTransform::Uppercase
+        );
+        assert_eq!(TextTransform::parse("none"), TextTransform::None);
+    }
+
+    #[test]
+    fn break_all_breaks_between_characters() {
+        let style = WrapStyle {
+            break_all: true,
+            ..Default::default()
+        };
+        assert_eq!(wrap_text("ab cd", &style), "a\u{200B}b c\u{200B}d");
+        assert_eq!(wrap_text("e\u{301}x", &style), "e\u{301}\u{200B}x");
+    }
+
+    #[test]
+    fn overflow_wrap_breaks_long_words_after_separators() {
+        let style = WrapStyle {
+            break_long_words: true,
+            ..Default::default()
+        };
+        assert_eq!(wrap_text("short words stay", &style), "short words stay");
+
+        let url = "https://example.com/some/path";
+        let wrapped = wrap_text(url, &style);
+        assert!(wrapped.contains("example.\u{200B}com/\u{200B}some/\u{200B}path"));
+        assert_eq!(strip_breaks(&wrapped), url);
+
+        let run = "a".repeat(LONG_WORD_CHARS);
+        assert_eq!(
+            wrap_text(&run, &style).matches(BREAK_OPPORTUNITY).count(),
+            LONG_WORD_CHARS - 1
+        );
+    }
+
+    #[test]
+    fn styles_and_scripts_are_left_alone() {
+        let app = TestApp::mount(
+            || {
+                Element::Html(
+                    r#"<div style="text-transform: uppercase; word-break: break-all">
+                        <style>.note { color: red; }</style>
+                        <p class="note">quiet</p>
+                    </div>"#
+                        .to_string(),
+                )
+            },
+            200,
+            100,
+        );
+        let style = app.find_by_selector("style").unwrap();
+        assert_eq!(app.text(style), ".note { color: red; }");
+        let note = app.find_by_selector(".note").unwrap();
+        assert_eq!(strip_breaks(&app.text(note)), "QUIET");
+    }
+}
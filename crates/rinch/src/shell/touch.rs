@@ -0,0 +1,220 @@
+//! Touch input.
+//!
+//! Touchscreens report fingers rather than a pointer. The first finger down
+//! drives the pointer: lifting it where it went down is a click there, and
+//! dragging it further than [`TAP_SLOP`] pans whatever is under it, the way
+//! a trackpad scrolls, gliding on once the finger lifts. Other fingers are
+//! ignored while it's down.
+//!
+//! The runtime turns each `WindowEvent::Touch` into the mouse and wheel
+//! events these gestures stand for, so everything downstream (hover,
+//! clicks, focus, scroll chaining, momentum, overscroll) works as it does
+//! with a mouse.
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{
+    DeviceId, ElementState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+};
+
+/// How far a finger can move, in logical pixels, and still tap.
+pub const TAP_SLOP: f64 = 10.0;
+
+/// What a finger did, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Gesture {
+    /// The pointer is over this point.
+    Hover(f64, f64),
+    /// A click at this point.
+    Tap(f64, f64),
+    /// Scroll by this much: positive values scroll towards the start, as
+    /// the content follows the finger.
+    Pan { dx: f64, dy: f64, phase: TouchPhase },
+}
+
+/// The finger driving the pointer.
+#[derive(Debug, Clone, Copy)]
+struct Finger {
+    id: u64,
+    start: (f64, f64),
+    last: (f64, f64),
+    panning: bool,
+}
+
+/// Turns touches into pointer gestures.
+#[derive(Debug, Default)]
+pub(crate) struct TouchInput {
+    finger: Option<Finger>,
+}
+
+impl TouchInput {
+    /// The mouse and wheel events a touch stands for.
+    pub(crate) fn events(&mut self, touch: Touch, scale_factor: f64) -> Vec<WindowEvent> {
+        let location = (touch.location.x, touch.location.y);
+        self.gesture(touch.id, touch.phase, location, scale_factor)
+            .into_iter()
+            .flat_map(|gesture| pointer_events(touch.device_id, gesture))
+            .collect()
+    }
+
+    /// The gestures a touch makes, at `location` in physical pixels.
+    pub(crate) fn gesture(
+        &mut self,
+        id: u64,
+        phase: TouchPhase,
+        location: (f64, f64),
+        scale_factor: f64,
+    ) -> Vec<Gesture> {
+        let (x, y) = location;
+        match phase {
+            TouchPhase::Started => {
+                if self.finger.is_some() {
+                    return Vec::new();
+                }
+                self.finger = Some(Finger {
+                    id,
+                    start: location,
+                    last: location,
+                    panning: false,
+                });
+                vec![Gesture::Hover(x, y)]
+            }
+            _ => {
+                let Some(finger) = self.finger.as_mut().filter(|finger| finger.id == id) else {
+                    return Vec::new();
+                };
+                let (dx, dy) = (x - finger.last.0, y - finger.last.1);
+                match phase {
+                    TouchPhase::Moved if finger.panning => {
+                        finger.last = location;
+                        vec![Gesture::Pan { dx, dy, phase }]
+                    }
+                    TouchPhase::Moved => {
+                        let (sx, sy) = (x - finger.start.0, y - finger.start.1);
+                        if sx.hypot(sy) <= TAP_SLOP * scale_factor {
+                            return Vec::new();
+                        }
+                        finger.panning = true;
+                        finger.last = location;
+                        vec![Gesture::Pan {
+                            dx,
+                            dy,
+                            phase: TouchPhase::Started,
+                        }]
+                    }
+                    TouchPhase::Ended => {
+                        let panning = finger.panning;
+                        self.finger = None;
+                        if panning {
+                            vec![Gesture::Pan { dx, dy, phase }]
+                        } else {
+                            vec![Gesture::Hover(x, y), Gesture::Tap(x, y)]
+                        }
+                    }
+                    _ => {
+                        // Cancelled, as when the system takes over the touch
+                        let panning = finger.panning;
+                        self.finger = None;
+                        if panning {
+                            vec![Gesture::Pan {
+                                dx: 0.0,
+                                dy: 0.0,
+                                phase,
+                            }]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The winit events a gesture stands for.
+fn pointer_events(device_id: DeviceId, gesture: Gesture) -> Vec<WindowEvent> {
+    let mouse_input = |state| WindowEvent::MouseInput {
+        device_id,
+        state,
+        button: MouseButton::Left,
+    };
+    match gesture {
+        Gesture::Hover(x, y) => vec![WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(x, y),
+        }],
+        Gesture::Tap(..) => vec![
+            mouse_input(ElementState::Pressed),
+            mouse_input(ElementState::Released),
+        ],
+        Gesture::Pan { dx, dy, phase } => vec![WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(dx, dy)),
+            phase,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifting_where_it_went_down_taps() {
+        let mut input = TouchInput::default();
+        assert_eq!(
+            input.gesture(1, TouchPhase::Started, (100.0, 50.0), 2.0),
+            vec![Gesture::Hover(100.0, 50.0)]
+        );
+        // Within the slop at 2x
+        assert!(input
+            .gesture(1, TouchPhase::Moved, (115.0, 50.0), 2.0)
+            .is_empty());
+        // A second finger is ignored
+        assert!(input
+            .gesture(2, TouchPhase::Started, (300.0, 300.0), 2.0)
+            .is_empty());
+        assert_eq!(
+            input.gesture(1, TouchPhase::Ended, (115.0, 50.0), 2.0),
+            vec![Gesture::Hover(115.0, 50.0), Gesture::Tap(115.0, 50.0)]
+        );
+        assert!(input
+            .gesture(2, TouchPhase::Ended, (300.0, 300.0), 2.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn dragging_pans() {
+        let mut input = TouchInput::default();
+        input.gesture(1, TouchPhase::Started, (100.0, 100.0), 1.0);
+        assert_eq!(
+            input.gesture(1, TouchPhase::Moved, (100.0, 80.0), 1.0),
+            vec![Gesture::Pan {
+                dx: 0.0,
+                dy: -20.0,
+                phase: TouchPhase::Started
+            }]
+        );
+        assert_eq!(
+            input.gesture(1, TouchPhase::Moved, (100.0, 75.0), 1.0),
+            vec![Gesture::Pan {
+                dx: 0.0,
+                dy: -5.0,
+                phase: TouchPhase::Moved
+            }]
+        );
+        assert_eq!(
+            input.gesture(1, TouchPhase::Ended, (100.0, 70.0), 1.0),
+            vec![Gesture::Pan {
+                dx: 0.0,
+                dy: -5.0,
+                phase: TouchPhase::Ended
+            }]
+        );
+
+        // Cancelling a touch that hasn't moved does nothing
+        input.gesture(3, TouchPhase::Started, (10.0, 10.0), 1.0);
+        assert!(input
+            .gesture(3, TouchPhase::Cancelled, (10.0, 10.0), 1.0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,823 @@
+//! CSS transitions.
+//!
+//! The style engine switches an element to its new styles at once, and a
+//! re-render builds a new document, so rinch runs `transition`s itself.
+//! After each rebuild, and whenever the hovered or focused element changes,
+//! it works out the styles of elements with a `transition`, compares them
+//! with the last ones, and eases each changed property from its old value to
+//! the new one, writing the value in between into the element's inline style
+//! every frame until it gets there.
+//!
+//! Elements are matched across rebuilds by their place in the tree. Values
+//! made of the same numbers and units, like lengths, `transform` functions
+//! or shadows, are blended number by number, and colors channel by channel;
+//! `none` transforms and shadows ease to and from their identity. Anything
+//! else, like `display` or a change from `px` to `auto`, switches at once, as
+//! in browsers. Styles are the element's computed ones, so `:hover` and
+//! `:focus` rules transition but `:active` ones don't.
+//!
+//! When the user prefers reduced motion, styles change at once unless the
+//! element has `motion: "full"`.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+use peniko::color::{parse_color, Srgb};
+use rinch_core::{prefers_reduced_motion, untracked, Color};
+
+use super::cascade::parse_declarations;
+use super::computed::computed_values;
+use super::scroll::node_path;
+use super::text_effects::split_outside_parens;
+
+/// Attribute that keeps an element animating when the user prefers reduced
+/// motion, with the value `full`.
+const MOTION_ATTR: &str = "data-motion";
+
+/// The animatable properties `all`, and shorthands like `padding`, cover.
+const ANIMATABLE: &[&str] = &[
+    "opacity",
+    "transform",
+    "translate",
+    "rotate",
+    "scale",
+    "box-shadow",
+    "text-shadow",
+    "color",
+    "background-color",
+    "border-top-color",
+    "border-right-color",
+    "border-bottom-color",
+    "border-left-color",
+    "outline-color",
+    "width",
+    "height",
+    "min-width",
+    "min-height",
+    "max-width",
+    "max-height",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "padding-top",
+    "padding-right",
+    "padding-bottom",
+    "padding-left",
+    "margin-top",
+    "margin-right",
+    "margin-bottom",
+    "margin-left",
+    "border-top-width",
+    "border-right-width",
+    "border-bottom-width",
+    "border-left-width",
+    "border-top-left-radius",
+    "border-top-right-radius",
+    "border-bottom-right-radius",
+    "border-bottom-left-radius",
+    "outline-width",
+    "outline-offset",
+    "font-size",
+    "letter-spacing",
+    "word-spacing",
+    "row-gap",
+    "column-gap",
+];
+
+/// The longhands transitions are read from.
+const TRANSITION_PROPERTIES: &[&str] = &[
+    "transition-property",
+    "transition-duration",
+    "transition-delay",
+    "transition-timing-function",
+];
+
+/// A CSS easing function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TimingFunction {
+    CubicBezier(f64, f64, f64, f64),
+    /// `steps(n)`, jumping at the end of each step, or at the start with
+    /// `true`.
+    Steps(u32, bool),
+}
+
+impl Default for TimingFunction {
+    fn default() -> Self {
+        Self::EASE
+    }
+}
+
+impl TimingFunction {
+    /// `ease`, the default.
+    pub(crate) const EASE: Self = Self::CubicBezier(0.25, 0.1, 0.25, 1.0);
+
+    /// Parse a keyword, `cubic-bezier()` or `steps()`.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        Some(match value.trim() {
+            "ease" => Self::EASE,
+            "linear" => Self::CubicBezier(0.0, 0.0, 1.0, 1.0),
+            "ease-in" => Self::CubicBezier(0.42, 0.0, 1.0, 1.0),
+            "ease-out" => Self::CubicBezier(0.0, 0.0, 0.58, 1.0),
+            "ease-in-out" => Self::CubicBezier(0.42, 0.0, 0.58, 1.0),
+            "step-start" => Self::Steps(1, true),
+            "step-end" => Self::Steps(1, false),
+            other => {
+                let (name, args) = other.strip_suffix(')')?.split_once('(')?;
+                let args: Vec<&str> = args.split(',').map(str::trim).collect();
+                match name.trim() {
+                    "cubic-bezier" => {
+                        let points = args
+                            .iter()
+                            .map(|arg| arg.parse().ok())
+                            .collect::<Option<Vec<f64>>>()?;
+                        let [x1, y1, x2, y2] = points[..] else {
+                            return None;
+                        };
+                        Self::CubicBezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2)
+                    }
+                    "steps" => {
+                        let count = args.first()?.parse().ok().filter(|&n| n > 0)?;
+                        let start = matches!(args.get(1), Some(&("start" | "jump-start")));
+                        Self::Steps(count, start)
+                    }
+                    _ => return None,
+                }
+            }
+        })
+    }
+
+    /// How far along the value is at progress `t` (clamped to 0..=1).
+    pub(crate) fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Steps(count, start) => {
+                let count = count as f64;
+                let step = if start {
+                    (t * count).ceil()
+                } else {
+                    (t * count).floor()
+                };
+                (step / count).min(1.0)
+            }
+            Self::CubicBezier(x1, y1, x2, y2) => {
+                let bezier = |a: f64, b: f64, s: f64| {
+                    3.0 * a * s * (1.0 - s).powi(2) + 3.0 * b * s * s * (1.0 - s) + s.powi(3)
+                };
+                // Find where on the curve x is `t` (x only grows along it)
+                let (mut low, mut high) = (0.0, 1.0);
+                let mut s = t;
+                for _ in 0..40 {
+                    let x = bezier(x1, x2, s);
+                    if (x - t).abs() < 1e-7 {
+                        break;
+                    }
+                    if x < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+                    s = (low + high) / 2.0;
+                }
+                bezier(y1, y2, s)
+            }
+        }
+    }
+}
+
+/// A value in seconds (`0.2s`) or milliseconds (`200ms`).
+pub(crate) fn parse_time(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let seconds = match value.strip_suffix("ms") {
+        Some(ms) => ms.parse::<f64>().ok()? / 1000.0,
+        None => value.strip_suffix('s')?.parse::<f64>().ok()?,
+    };
+    Some(Duration::from_secs_f64(seconds.max(0.0)))
+}
+
+/// A CSS value broken into numbers and the text around them, so values of
+/// the same shape can be blended number by number.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssValue {
+    /// The text around the numbers, one piece more than there are numbers.
+    text: Vec<String>,
+    numbers: Vec<f64>,
+}
+
+impl CssValue {
+    /// Break up a value, writing its colors as `rgba()` so they blend too.
+    pub(crate) fn parse(value: &str) -> Self {
+        let chars: Vec<char> = normalize_colors(value).chars().collect();
+        let mut text = vec![String::new()];
+        let mut numbers = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let end = number_end(&chars, i);
+            let number = (end > i)
+                .then(|| chars[i..end].iter().collect::<String>().parse::<f64>().ok())
+                .flatten();
+            match number {
+                Some(number) => {
+                    numbers.push(number);
+                    text.push(String::new());
+                    i = end;
+                }
+                None => {
+                    text.last_mut().unwrap().push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+        Self { text, numbers }
+    }
+
+    /// Whether this value can be blended with `other`.
+    pub(crate) fn blends_with(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+
+    /// The value `t` of the way to `to`, which must blend with this one.
+    pub(crate) fn blend(&self, to: &Self, t: f64) -> String {
+        let numbers = self
+            .numbers
+            .iter()
+            .zip(&to.numbers)
+            .map(|(from, to)| from + (to - from) * t);
+        self.with_numbers(numbers)
+    }
+
+    /// This value with its numbers replaced.
+    fn with_numbers(&self, numbers: impl IntoIterator<Item = f64>) -> String {
+        let mut value = self.text[0].clone();
+        for (number, text) in numbers.into_iter().zip(&self.text[1..]) {
+            // Three decimals are plenty, and keep values short
+            let number = (number * 1000.0).round() / 1000.0;
+            if number == 0.0 {
+                value.push('0');
+            } else {
+                value.push_str(&number.to_string());
+            }
+            value.push_str(text);
+        }
+        value
+    }
+}
+
+/// Where a number starting at `start` ends, or `start` if none starts there.
+/// Digits inside names, like `rotate3d` or `#f00`, aren't numbers.
+fn number_end(chars: &[char], start: usize) -> usize {
+    let digit = |i: usize| chars.get(i).is_some_and(char::is_ascii_digit);
+    if start > 0 {
+        let before = chars[start - 1];
+        if before.is_alphanumeric() || matches!(before, '_' | '-' | '.' | '#') {
+            return start;
+        }
+    }
+    let mut i = start;
+    if matches!(chars[i], '+' | '-') {
+        i += 1;
+    }
+    if !(digit(i) || (chars.get(i) == Some(&'.') && digit(i + 1))) {
+        return start;
+    }
+    while digit(i) {
+        i += 1;
+    }
+    if chars.get(i) == Some(&'.') && digit(i + 1) {
+        i += 1;
+        while digit(i) {
+            i += 1;
+        }
+    }
+    // An exponent, but not a unit like `em`
+    if matches!(chars.get(i), Some('e' | 'E')) {
+        let sign = matches!(chars.get(i + 1), Some('+' | '-')) as usize;
+        if digit(i + 1 + sign) {
+            i += 1 + sign;
+            while digit(i) {
+                i += 1;
+            }
+        }
+    }
+    i
+}
+
+/// Write the colors in a value as `rgba()`.
+fn normalize_colors(value: &str) -> String {
+    split_outside_parens(value, ',')
+        .iter()
+        .map(|layer| {
+            split_outside_parens(layer, ' ')
+                .iter()
+                .map(|token| match parse_color(token) {
+                    Ok(color) => {
+                        let [r, g, b, a] = color.to_alpha_color::<Srgb>().components;
+                        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+                        Color::rgba(channel(r), channel(g), channel(b), a).to_string()
+                    }
+                    Err(_) => token.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The value `none` stands for when `property` eases to or from `other`:
+/// `other` with its functions or shadows doing nothing.
+fn identity(property: &str, other: &str) -> Option<String> {
+    let zeroed = |value: &str, to: f64| {
+        let value = CssValue::parse(value);
+        let count = value.numbers.len();
+        value.with_numbers(std::iter::repeat_n(to, count))
+    };
+    match property {
+        "transform" => split_outside_parens(other, ' ')
+            .iter()
+            .map(|function| match function.split('(').next() {
+                Some(name) if name.starts_with("scale") => Some(zeroed(function, 1.0)),
+                Some(name) if name.starts_with("matrix") || name == "perspective" => None,
+                _ => Some(zeroed(function, 0.0)),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|functions| functions.join(" ")),
+        "scale" => Some(zeroed(other, 1.0)),
+        "translate" | "rotate" | "box-shadow" => Some(zeroed(other, 0.0)),
+        _ => None,
+    }
+}
+
+/// How one property transitions.
+#[derive(Debug, Clone, PartialEq)]
+struct Transition {
+    /// A property, a shorthand covering several, or `all`.
+    property: String,
+    duration: Duration,
+    delay: Duration,
+    timing: TimingFunction,
+}
+
+impl Transition {
+    fn covers(&self, property: &str) -> bool {
+        self.property == "all"
+            || property
+                .strip_prefix(self.property.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    }
+}
+
+/// The transitions declared by `transition` and its longhands.
+fn declared_transitions(values: &[(String, String)]) -> Vec<Transition> {
+    let value = |property: &str| {
+        values
+            .iter()
+            .rev()
+            .find(|(p, _)| p == property)
+            .map(|(_, v)| v.as_str())
+    };
+    let list = |property: &str| value(property).map(|v| split_outside_parens(v, ','));
+
+    let mut properties = Vec::new();
+    let mut durations = Vec::new();
+    let mut delays = Vec::new();
+    let mut timings = Vec::new();
+    for item in list("transition").unwrap_or_default() {
+        let (mut property, mut duration, mut delay, mut timing) = ("all", None, None, None);
+        for token in split_outside_parens(item, ' ') {
+            if let Some(time) = parse_time(token) {
+                if duration.is_none() {
+                    duration = Some(time);
+                } else {
+                    delay = Some(time);
+                }
+            } else if let Some(function) = TimingFunction::parse(token) {
+                timing = Some(function);
+            } else {
+                property = token;
+            }
+        }
+        properties.push(property);
+        durations.push(duration.unwrap_or_default());
+        delays.push(delay.unwrap_or_default());
+        timings.push(timing.unwrap_or_default());
+    }
+    if let Some(list) = list("transition-property") {
+        properties = list;
+    }
+    if let Some(list) = list("transition-duration") {
+        durations = list
+            .into_iter()
+            .map(|v| parse_time(v).unwrap_or_default())
+            .collect();
+    }
+    if let Some(list) = list("transition-delay") {
+        delays = list
+            .into_iter()
+            .map(|v| parse_time(v).unwrap_or_default())
+            .collect();
+    }
+    if let Some(list) = list("transition-timing-function") {
+        timings = list
+            .into_iter()
+            .map(|v| TimingFunction::parse(v).unwrap_or_default())
+            .collect();
+    }
+
+    // Shorter lists repeat to match the properties
+    fn nth<T: Copy + Default>(list: &[T], i: usize) -> T {
+        list.get(i % list.len().max(1)).copied().unwrap_or_default()
+    }
+    properties
+        .iter()
+        .enumerate()
+        .map(|(i, property)| Transition {
+            property: property.to_string(),
+            duration: nth(&durations, i),
+            delay: nth(&delays, i),
+            timing: nth(&timings, i),
+        })
+        .filter(|transition| transition.property != "none" && !transition.duration.is_zero())
+        .collect()
+}
+
+/// The computed values of the properties an element's transitions cover.
+fn transition_values(
+    doc: &BaseDocument,
+    node_id: usize,
+    transitions: &[Transition],
+) -> HashMap<String, String> {
+    let mut properties: Vec<&str> = ANIMATABLE
+        .iter()
+        .copied()
+        .filter(|property| transitions.iter().any(|t| t.covers(property)))
+        .collect();
+    for transition in transitions {
+        if transition.property != "all" && !properties.contains(&transition.property.as_str()) {
+            properties.push(&transition.property);
+        }
+    }
+    computed_values(doc, node_id, &properties)
+        .into_iter()
+        .collect()
+}
+
+/// A property on its way to a new value.
+#[derive(Debug)]
+struct Running {
+    from: CssValue,
+    to: CssValue,
+    start: Instant,
+    duration: Duration,
+    timing: TimingFunction,
+}
+
+impl Running {
+    fn new(
+        property: &str,
+        from: &str,
+        to: &str,
+        transition: &Transition,
+        now: Instant,
+    ) -> Option<Self> {
+        let (from, to) = match (from.trim(), to.trim()) {
+            (from, to) if from == to => return None,
+            ("none", to) => (identity(property, to)?, to.to_string()),
+            (from, "none") => (from.to_string(), identity(property, from)?),
+            (from, to) => (from.to_string(), to.to_string()),
+        };
+        let (from, to) = (CssValue::parse(&from), CssValue::parse(&to));
+        from.blends_with(&to).then(|| Self {
+            from,
+            to,
+            start: now + transition.delay,
+            duration: transition.duration,
+            timing: transition.timing,
+        })
+    }
+
+    fn value_at(&self, now: Instant) -> String {
+        let elapsed = now.saturating_duration_since(self.start).as_secs_f64();
+        let t = elapsed / self.duration.as_secs_f64();
+        self.from.blend(&self.to, self.timing.apply(t))
+    }
+
+    fn is_done(&self, now: Instant) -> bool {
+        now >= self.start + self.duration
+    }
+}
+
+/// An element with transitions.
+#[derive(Debug)]
+struct Element {
+    node_id: usize,
+    tag: String,
+    transitions: Vec<Transition>,
+    /// The computed values of the properties its transitions cover.
+    values: HashMap<String, String>,
+    /// The values its own `style` attribute sets, put back when a
+    /// transition ends.
+    inline: HashMap<String, String>,
+    full_motion: bool,
+    running: HashMap<String, Running>,
+    /// Properties whose transitions stopped early, to put back.
+    stopped: Vec<String>,
+}
+
+impl Element {
+    /// Transition the properties whose values changed from `old`. Returns
+    /// whether any started.
+    fn changed_from(&mut self, old: &HashMap<String, String>, now: Instant) -> bool {
+        let reduced = untracked(prefers_reduced_motion) && !self.full_motion;
+        let properties: HashSet<String> = old.keys().chain(self.values.keys()).cloned().collect();
+        let mut started = false;
+        for property in properties {
+            let before = old.get(&property).cloned();
+            let after = self.values.get(&property).cloned();
+            if before == after {
+                continue;
+            }
+
+            // An interrupted transition carries on from where it got to
+            let was_running = self.running.remove(&property);
+            let from = match &was_running {
+                Some(running) => Some(running.value_at(now)),
+                None => before,
+            };
+            let transition = self
+                .transitions
+                .iter()
+                .rev()
+                .find(|transition| transition.covers(&property));
+            let running = match (transition, from, after) {
+                (Some(transition), Some(from), Some(to)) if !reduced => {
+                    Running::new(&property, &from, &to, transition, now)
+                }
+                _ => None,
+            };
+            match running {
+                Some(running) => {
+                    self.running.insert(property, running);
+                    started = true;
+                }
+                None if was_running.is_some() => self.stopped.push(property),
+                None => {}
+            }
+        }
+        started
+    }
+}
+
+/// Transitions in a window's document.
+#[derive(Debug, Default)]
+pub(crate) struct Transitions {
+    /// Elements with transitions, by tree path.
+    elements: HashMap<Vec<usize>, Element>,
+}
+
+impl Transitions {
+    /// Whether any transitions are running.
+    pub(crate) fn is_active(&self) -> bool {
+        self.elements
+            .values()
+            .any(|element| !element.running.is_empty() || !element.stopped.is_empty())
+    }
+
+    /// Find the elements with transitions in a freshly built and styled
+    /// document, and start transitions for those whose styles differ from
+    /// the old one's. Returns whether any started or are still running.
+    pub(crate) fn rebuilt(&mut self, doc: &BaseDocument, now: Instant) -> bool {
+        let mut old = std::mem::take(&mut self.elements);
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            let Some(data) = node.element_data() else {
+                continue;
+            };
+            let transitions =
+                declared_transitions(&computed_values(doc, node_id, TRANSITION_PROPERTIES));
+            if transitions.is_empty() {
+                continue;
+            }
+            let attr = |name: &str| {
+                data.attrs()
+                    .iter()
+                    .find(|attr| attr.name.local.as_ref() == name)
+                    .map(|attr| attr.value.to_string())
+            };
+            let mut element = Element {
+                node_id,
+                tag: data.name.local.to_string(),
+                values: transition_values(doc, node_id, &transitions),
+                transitions,
+                inline: attr("style")
+                    .map(|style| {
+                        parse_declarations(&style)
+                            .into_iter()
+                            .map(|declaration| (declaration.property, declaration.value))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                full_motion: attr(MOTION_ATTR).as_deref() == Some("full"),
+                running: HashMap::new(),
+                stopped: Vec::new(),
+            };
+            let path = node_path(doc, node_id);
+            if let Some(previous) = old.remove(&path)
+                && previous.tag == element.tag
+            {
+                element.running = previous.running;
+                element.changed_from(&previous.values, now);
+            }
+            self.elements.insert(path, element);
+        }
+        self.is_active()
+    }
+
+    /// Put back the styles running transitions overrode, so the document's
+    /// computed styles are its rules' again. Call before restyling for
+    /// [`update`](Self::update); the next [`tick`](Self::tick) writes the
+    /// transitions' values back.
+    pub(crate) fn unapply(&self, doc: &mut BaseDocument) {
+        if !self.is_active() {
+            return;
+        }
+        let mut mutator = doc.mutate();
+        for element in self.elements.values() {
+            for property in element.running.keys() {
+                match element.inline.get(property) {
+                    Some(value) => mutator.set_style_property(element.node_id, property, value),
+                    None => mutator.remove_style_property(element.node_id, property),
+                }
+            }
+        }
+    }
+
+    /// Start transitions for styles that changed with the hovered or focused
+    /// element, once the document is restyled. Returns whether any started.
+    pub(crate) fn update(&mut self, doc: &BaseDocument, now: Instant) -> bool {
+        let mut started = false;
+        for element in self.elements.values_mut() {
+            // The new styles' transitions apply, as in browsers
+            let declared = computed_values(doc, element.node_id, TRANSITION_PROPERTIES);
+            element.transitions = declared_transitions(&declared);
+            let values = transition_values(doc, element.node_id, &element.transitions);
+            let old = std::mem::replace(&mut element.values, values);
+            started |= element.changed_from(&old, now);
+        }
+        started
+    }
+
+    /// Write each running transition's value at `now` into its element's
+    /// style, and put back the styles of those that have ended. Call before
+    /// styling and layout.
+    pub(crate) fn tick(&mut self, doc: &mut BaseDocument, now: Instant) {
+        if !self.is_active() {
+            return;
+        }
+        let mut mutator = doc.mutate();
+        for element in self.elements.values_mut() {
+            let mut ended = std::mem::take(&mut element.stopped);
+            for (property, running) in &element.running {
+                if running.is_done(now) {
+                    ended.push(property.clone());
+                } else {
+                    mutator.set_style_property(element.node_id, property, &running.value_at(now));
+                }
+            }
+            for property in ended {
+                element.running.remove(&property);
+                match element.inline.get(&property) {
+                    Some(value) => mutator.set_style_property(element.node_id, &property, value),
+                    None => mutator.remove_style_property(element.node_id, &property),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn transitions_are_parsed() {
+        let transitions = declared_transitions(&values(&[(
+            "transition",
+            "opacity 200ms ease-in, transform .3s cubic-bezier(0, 0, 1, 1) 50ms, color 0s",
+        )]));
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].property, "opacity");
+        assert_eq!(transitions[0].duration, Duration::from_millis(200));
+        assert_eq!(
+            transitions[0].timing,
+            TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0)
+        );
+        assert_eq!(transitions[1].delay, Duration::from_millis(50));
+
+        let transitions = declared_transitions(&values(&[
+            ("transition-property", "background, padding"),
+            ("transition-duration", "150ms"),
+        ]));
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[1].duration, Duration::from_millis(150));
+        assert!(transitions[0].covers("background-color"));
+        assert!(transitions[1].covers("padding-left"));
+        assert!(!transitions[1].covers("margin-left"));
+    }
+
+    #[test]
+    fn easing_follows_the_curve() {
+        let linear = TimingFunction::parse("linear").unwrap();
+        assert!((linear.apply(0.3) - 0.3).abs() < 1e-6);
+        let ease = TimingFunction::EASE;
+        assert_eq!(ease.apply(0.0), 0.0);
+        assert!((ease.apply(1.0) - 1.0).abs() < 1e-6);
+        assert!(ease.apply(0.5) > 0.75);
+        let steps = TimingFunction::parse("steps(4)").unwrap();
+        assert_eq!(steps.apply(0.3), 0.25);
+        assert_eq!(TimingFunction::parse("steps(0)"), None);
+    }
+
+    #[test]
+    fn values_blend_number_by_number() {
+        let from = CssValue::parse("translateX(0px) scale(1)");
+        let to = CssValue::parse("translateX(10px) scale(1.5)");
+        assert!(from.blends_with(&to));
+        assert_eq!(from.blend(&to, 0.5), "translateX(5px) scale(1.25)");
+
+        let from = CssValue::parse("#000");
+        let to = CssValue::parse("rgba(255, 100, 0, 0.5)");
+        assert_eq!(from.blend(&to, 0.5), "rgba(127.5, 50, 0, 0.75)");
+
+        // Different units or functions don't blend
+        assert!(!CssValue::parse("10px").blends_with(&CssValue::parse("50%")));
+        assert!(!CssValue::parse("rotate3d(1, 0, 0, 5deg)").blends_with(&CssValue::parse("none")));
+        assert_eq!(
+            CssValue::parse("rotate3d(1, 0, 0, 5deg)").numbers,
+            [1.0, 0.0, 0.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn none_eases_from_the_identity() {
+        assert_eq!(
+            identity("transform", "translateY(-4px) scale(1.05)").as_deref(),
+            Some("translateY(0px) scale(1)")
+        );
+        assert_eq!(identity("transform", "matrix(1, 0, 0, 1, 0, 0)"), None);
+
+        let now = Instant::now();
+        let transition = Transition {
+            property: "all".into(),
+            duration: Duration::from_millis(100),
+            delay: Duration::ZERO,
+            timing: TimingFunction::parse("linear").unwrap(),
+        };
+        let running = Running::new(
+            "box-shadow",
+            "none",
+            "0 4px 8px rgba(0, 0, 0, 0.4)",
+            &transition,
+            now,
+        )
+        .unwrap();
+        assert_eq!(
+            running.value_at(now + Duration::from_millis(50)),
+            "0 2px 4px rgba(0, 0, 0, 0.2)"
+        );
+        assert!(Running::new("display", "none", "block", &transition, now).is_none());
+    }
+
+    #[test]
+    fn interrupted_transitions_carry_on_from_where_they_got_to() {
+        let now = Instant::now();
+        let mut element = Element {
+            node_id: 1,
+            tag: "div".into(),
+            transitions: declared_transitions(&values(&[("transition", "opacity 100ms linear")])),
+            values: values(&[("opacity", "0")]).into_iter().collect(),
+            inline: HashMap::new(),
+            full_motion: true,
+            running: HashMap::new(),
+            stopped: Vec::new(),
+        };
+        let shown: HashMap<String, String> = values(&[("opacity", "1")]).into_iter().collect();
+        assert!(element.changed_from(&shown, now));
+
+        let later = now + Duration::from_millis(25);
+        assert_eq!(element.running["opacity"].value_at(later), "0.75");
+        let hidden = std::mem::replace(&mut element.values, shown);
+        assert!(element.changed_from(&hidden, later));
+        assert_eq!(element.running["opacity"].value_at(later), "0.75");
+        assert_eq!(
+            element.running["opacity"].value_at(later + Duration::from_millis(100)),
+            "1"
+        );
+    }
+}
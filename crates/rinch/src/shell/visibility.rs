@@ -0,0 +1,137 @@
+//! Element visibility, for `use_visibility`.
+//!
+//! After each layout, refs the app watches with `NodeRef::watch_visibility`
+//! get how much of their element shows through the root's visible area
+//! (grown by the query's margins). With the window as root, scroll
+//! containers around the element clip it too, so an item scrolled out of a
+//! list counts as hidden even while the list itself is on screen.
+
+use blitz_dom::BaseDocument;
+use rinch_core::node_ref::{Margins, Visibility};
+
+use super::scroll::{border_box_origin, is_scroll_container, Scroller};
+
+/// A window-relative rectangle, as `(left, top, right, bottom)`.
+pub(crate) type Bounds = (f64, f64, f64, f64);
+
+pub(crate) fn intersect(a: Bounds, b: Bounds) -> Option<Bounds> {
+    let bounds = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    (bounds.0 <= bounds.2 && bounds.1 <= bounds.3).then_some(bounds)
+}
+
+fn expand(bounds: Bounds, margins: Margins) -> Bounds {
+    (
+        bounds.0 - margins.left,
+        bounds.1 - margins.top,
+        bounds.2 + margins.right,
+        bounds.3 + margins.bottom,
+    )
+}
+
+fn area(bounds: Bounds) -> f64 {
+    (bounds.2 - bounds.0) * (bounds.3 - bounds.1)
+}
+
+/// A scroller's visible area.
+fn client_bounds(doc: &BaseDocument, scroller: Scroller) -> Bounds {
+    let state = scroller.state(doc);
+    let (x, y) = scroller.client_origin(doc);
+    (x, y, x + state.client_width, y + state.client_height)
+}
+
+/// How much of `element` shows through `clip`, with the ratio rounded to the
+/// nearest percent so scrolling doesn't report every pixel.
+fn visibility_within(element: Bounds, clip: Bounds) -> Visibility {
+    let Some(shown) = intersect(element, clip) else {
+        return Visibility::default();
+    };
+    let ratio = if area(element) > 0.0 {
+        (area(shown) / area(element) * 100.0).round() / 100.0
+    } else {
+        1.0
+    };
+    Visibility {
+        visible: true,
+        ratio,
+    }
+}
+
+/// The part of the window a node can show in, grown by `margins`: the
+/// window's visible area, cut down by the scroll containers around the
+/// node. `None` if they leave nothing.
+pub(crate) fn clip_bounds(doc: &BaseDocument, node_id: usize, margins: Margins) -> Option<Bounds> {
+    let mut clip = Some(expand(client_bounds(doc, Scroller::Viewport), margins));
+    let mut current = doc.get_node(node_id)?.parent;
+    while let Some(id) = current {
+        if is_scroll_container(doc, id) {
+            clip = clip.and_then(|clip| intersect(clip, client_bounds(doc, Scroller::Node(id))));
+        }
+        current = doc.get_node(id).and_then(|ancestor| ancestor.parent);
+    }
+    clip
+}
+
+/// How much of a node is visible within `root` (a scroll container), or
+/// within the window and the scroll containers around it.
+pub(crate) fn element_visibility(
+    doc: &BaseDocument,
+    node_id: usize,
+    root: Option<usize>,
+    margins: Margins,
+) -> Visibility {
+    let Some(node) = doc.get_node(node_id) else {
+        return Visibility::default();
+    };
+    let (x, y) = border_box_origin(doc, node_id);
+    let size = node.final_layout.size;
+    let element = (x, y, x + size.width as f64, y + size.height as f64);
+
+    let clip = match root {
+        Some(root) => Some(expand(
+            client_bounds(doc, Scroller::for_node(doc, root)),
+            margins,
+        )),
+        None => clip_bounds(doc, node_id, margins),
+    };
+    clip.map(|clip| visibility_within(element, clip))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_visible_fraction() {
+        let view = (0.0, 0.0, 800.0, 600.0);
+        assert_eq!(
+            visibility_within((0.0, 100.0, 200.0, 200.0), view),
+            Visibility {
+                visible: true,
+                ratio: 1.0
+            }
+        );
+        // A third below the bottom edge
+        let cut = visibility_within((0.0, 500.0, 200.0, 650.0), view);
+        assert!(cut.visible);
+        assert_eq!(cut.ratio, 0.67);
+        assert_eq!(
+            visibility_within((0.0, 700.0, 200.0, 800.0), view),
+            Visibility::default()
+        );
+        // Empty elements are visible when inside
+        assert_eq!(visibility_within((10.0, 10.0, 10.0, 10.0), view).ratio, 1.0);
+    }
+
+    #[test]
+    fn margins_grow_the_area() {
+        let view = expand((0.0, 0.0, 800.0, 600.0), Margins::all(200.0));
+        assert!(visibility_within((0.0, 700.0, 200.0, 800.0), view).visible);
+        let shrunk = expand((0.0, 0.0, 800.0, 600.0), Margins::all(-50.0));
+        assert_eq!(shrunk, (50.0, 50.0, 750.0, 550.0));
+        assert_eq!(
+            intersect((0.0, 0.0, 10.0, 10.0), (20.0, 0.0, 30.0, 10.0)),
+            None
+        );
+    }
+}
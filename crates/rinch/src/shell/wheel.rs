@@ -0,0 +1,563 @@
+//! Wheel and trackpad scrolling.
+//!
+//! rinch routes wheel input itself instead of leaving it to blitz, so that
+//! trackpads pan by exact pixel amounts, flings keep gliding after the
+//! fingers lift, and scrollers can stretch past their edges and bounce back.
+//!
+//! Each gesture latches onto one scroller: the innermost under the pointer
+//! that can move in the gesture's direction. A nested list that reaches its
+//! edge mid-gesture stops there rather than passing the rest of the gesture
+//! on to the page; the next gesture scrolls the page. `overscroll-behavior:
+//! contain` (or `none`) on a scroller keeps gestures that start over it from
+//! ever reaching the scrollers around it.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use blitz_dom::BaseDocument;
+
+use super::computed::computed_values;
+use super::scroll::{ease_out_cubic, is_scroll_container, Scroller};
+
+/// The properties overscroll behaviors are read from, shorthand first.
+const OVERSCROLL_PROPERTIES: &[&str] = &[
+    "overscroll-behavior",
+    "overscroll-behavior-x",
+    "overscroll-behavior-y",
+    "overscroll-behavior-inline",
+    "overscroll-behavior-block",
+];
+
+/// How far one line of a line-based wheel delta scrolls, in logical pixels.
+pub(crate) const LINE_HEIGHT: f64 = 40.0;
+
+/// How much recent trackpad movement counts towards a fling's velocity.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+/// Slowest release, in pixels per second, that starts a fling.
+const MIN_FLING_VELOCITY: f64 = 150.0;
+
+/// Speed, in pixels per second, below which a fling stops.
+const STOP_VELOCITY: f64 = 10.0;
+
+/// Time constant of a fling's exponential slowdown, in seconds.
+const FLING_TIME_CONSTANT: f64 = 0.325;
+
+/// Furthest a scroller stretches past its edge, in logical pixels.
+const MAX_STRETCH: f64 = 120.0;
+
+/// How long a stretch holds without input before it springs back.
+const STRETCH_HOLD: Duration = Duration::from_millis(80);
+
+/// How long a stretch takes to spring back.
+const STRETCH_SETTLE: Duration = Duration::from_millis(300);
+
+/// How long a gesture stays latched to its scroller after its last
+/// movement, for wheels that don't report when gestures start.
+const LATCH_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Scrollers under a window-relative point, innermost first, ending with the
+/// viewport.
+fn scrollers_at(doc: &BaseDocument, (x, y): (f64, f64)) -> Vec<Scroller> {
+    let mut scrollers = Vec::new();
+    let mut current = doc.hit(x as f32, y as f32).map(|hit| hit.node_id);
+    while let Some(id) = current {
+        if is_scroll_container(doc, id) {
+            scrollers.push(Scroller::Node(id));
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+    scrollers.push(Scroller::Viewport);
+    scrollers
+}
+
+/// What a scroller does with scrolling past its edge, along one axis
+/// (`overscroll-behavior`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum OverscrollBehavior {
+    /// Pass it on to the scroller around it, or bounce.
+    #[default]
+    Auto,
+    /// Keep it, bouncing if the window bounces.
+    Contain,
+    /// Keep it, without bouncing.
+    None,
+}
+
+impl OverscrollBehavior {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "auto" => Some(Self::Auto),
+            "contain" => Some(Self::Contain),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+/// A scroller's `(x, y)` overscroll behavior from its own style values.
+///
+/// Logical longhands are treated as horizontal writing: inline is `x` and
+/// block is `y`.
+fn overscroll_behaviors(values: &[(String, String)]) -> (OverscrollBehavior, OverscrollBehavior) {
+    let (mut x, mut y) = Default::default();
+    for (property, value) in values {
+        match property.as_str() {
+            "overscroll-behavior" => {
+                let parsed: Option<Vec<_>> = value
+                    .split_whitespace()
+                    .map(OverscrollBehavior::parse)
+                    .collect();
+                match parsed.as_deref() {
+                    Some([both]) => (x, y) = (*both, *both),
+                    Some([first, second]) => (x, y) = (*first, *second),
+                    _ => {}
+                }
+            }
+            "overscroll-behavior-x" | "overscroll-behavior-inline" => {
+                x = OverscrollBehavior::parse(value).unwrap_or(x);
+            }
+            "overscroll-behavior-y" | "overscroll-behavior-block" => {
+                y = OverscrollBehavior::parse(value).unwrap_or(y);
+            }
+            _ => {}
+        }
+    }
+    (x, y)
+}
+
+/// Whether a scroller can move at all in a wheel delta's direction.
+fn can_scroll(doc: &BaseDocument, scroller: Scroller, (dx, dy): (f64, f64)) -> bool {
+    let state = scroller.state(doc);
+    let can_move = |delta: f64, offset: f64, max: f64| {
+        (delta > 0.0 && offset > 0.0) || (delta < 0.0 && offset < max)
+    };
+    can_move(dx, state.scroll_x, state.max_scroll_x())
+        || can_move(dy, state.scroll_y, state.max_scroll_y())
+}
+
+/// Scroll a scroller by a wheel delta (positive values scroll towards the
+/// start), as far as it goes.
+///
+/// Returns the part of the delta the scroller couldn't take.
+fn scroll_by(doc: &mut BaseDocument, scroller: Scroller, (dx, dy): (f64, f64)) -> (f64, f64) {
+    let state = scroller.state(doc);
+    let (x, y) = (
+        state.scroll_x.clamp(0.0, state.max_scroll_x()),
+        state.scroll_y.clamp(0.0, state.max_scroll_y()),
+    );
+    let new_x = (x - dx).clamp(0.0, state.max_scroll_x());
+    let new_y = (y - dy).clamp(0.0, state.max_scroll_y());
+    if (new_x, new_y) != (state.scroll_x, state.scroll_y) {
+        scroller.set_offset(doc, (new_x, new_y));
+    }
+    (dx - (x - new_x), dy - (y - new_y))
+}
+
+/// Which scroller wheel input moves, for one window's document.
+#[derive(Debug, Default)]
+pub(crate) struct ScrollChaining {
+    /// Overscroll behavior of scrollers that set one, as `(x, y)`.
+    behaviors: HashMap<Scroller, (OverscrollBehavior, OverscrollBehavior)>,
+    /// The scroller the current gesture is latched to, and when it last
+    /// moved.
+    latch: Option<(Scroller, Instant)>,
+}
+
+impl ScrollChaining {
+    /// Resolve overscroll behaviors for a freshly built and styled
+    /// document.
+    pub(crate) fn new(doc: &BaseDocument) -> Self {
+        let mut chaining = Self::default();
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = doc.get_node(node_id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().rev());
+            if node.element_data().is_none() {
+                continue;
+            }
+            // Any element may become a scroller when its styles change. The
+            // root element's behavior is the viewport's.
+            let behaviors =
+                overscroll_behaviors(&computed_values(doc, node_id, OVERSCROLL_PROPERTIES));
+            if behaviors != Default::default() {
+                let scroller = if node_id == doc.root_element().id {
+                    Scroller::Viewport
+                } else {
+                    Scroller::Node(node_id)
+                };
+                chaining.behaviors.insert(scroller, behaviors);
+            }
+        }
+        chaining
+    }
+
+    fn behavior(&self, scroller: Scroller) -> (OverscrollBehavior, OverscrollBehavior) {
+        self.behaviors.get(&scroller).copied().unwrap_or_default()
+    }
+
+    /// Release the latch, because a new gesture started.
+    pub(crate) fn unlatch(&mut self) {
+        self.latch = None;
+    }
+
+    /// Scroll by a wheel delta (positive values scroll towards the start)
+    /// at a window-relative point.
+    ///
+    /// Returns the scroller that took the delta and the part of the delta
+    /// it couldn't take.
+    pub(crate) fn scroll(
+        &mut self,
+        doc: &mut BaseDocument,
+        point: (f64, f64),
+        delta: (f64, f64),
+        now: Instant,
+    ) -> (Scroller, (f64, f64)) {
+        let latched = self
+            .latch
+            .filter(|(_, last)| now.duration_since(*last) < LATCH_TIMEOUT)
+            .map(|(scroller, _)| scroller);
+        let scroller = latched.unwrap_or_else(|| self.target(doc, point, delta));
+        self.latch = Some((scroller, now));
+        (scroller, scroll_by(doc, scroller, delta))
+    }
+
+    /// The scroller a new gesture latches to.
+    fn target(&self, doc: &BaseDocument, point: (f64, f64), delta: (f64, f64)) -> Scroller {
+        // Scrollers the gesture can reach, stopping at one that contains
+        // its overscroll along the gesture's main axis
+        let vertical = delta.1.abs() >= delta.0.abs();
+        let mut chain = Vec::new();
+        for scroller in scrollers_at(doc, point) {
+            chain.push(scroller);
+            let (x, y) = self.behavior(scroller);
+            if (if vertical { y } else { x }) != OverscrollBehavior::Auto {
+                break;
+            }
+        }
+
+        // Failing one that can move, the innermost that scrolls along the
+        // axis at all gets the overscroll
+        let scrolls_along = |scroller: &Scroller| {
+            let state = scroller.state(doc);
+            if vertical {
+                state.max_scroll_y() > 0.0
+            } else {
+                state.max_scroll_x() > 0.0
+            }
+        };
+        chain
+            .iter()
+            .copied()
+            .find(|&scroller| can_scroll(doc, scroller, delta))
+            .or_else(|| chain.iter().copied().find(scrolls_along))
+            .or_else(|| chain.last().copied())
+            .unwrap_or(Scroller::Viewport)
+    }
+
+    /// The part of a scroller's leftover delta it may stretch by.
+    pub(crate) fn overscroll(&self, scroller: Scroller, (dx, dy): (f64, f64)) -> (f64, f64) {
+        let (x, y) = self.behavior(scroller);
+        (
+            if x == OverscrollBehavior::None {
+                0.0
+            } else {
+                dx
+            },
+            if y == OverscrollBehavior::None {
+                0.0
+            } else {
+                dy
+            },
+        )
+    }
+}
+
+/// Velocity of recent trackpad movement, in pixels per second.
+fn release_velocity(samples: &VecDeque<(Instant, (f64, f64))>, now: Instant) -> (f64, f64) {
+    let recent: Vec<_> = samples
+        .iter()
+        .filter(|(time, _)| now.duration_since(*time) <= VELOCITY_WINDOW)
+        .collect();
+    let Some((first, _)) = recent.first() else {
+        return (0.0, 0.0);
+    };
+    // A lone sample still moved over about a frame
+    let span = now.duration_since(*first).as_secs_f64().max(1.0 / 60.0);
+    let (dx, dy) = recent
+        .iter()
+        .fold((0.0, 0.0), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+    (dx / span, dy / span)
+}
+
+/// Deceleration after a trackpad fling.
+///
+/// Feed it each pixel delta of a gesture with [`track`](Self::track) and call
+/// [`release`](Self::release) when the fingers lift; while a fling is active,
+/// [`step`](Self::step) gives the distance to scroll each frame.
+#[derive(Debug, Default)]
+pub(crate) struct Momentum {
+    samples: VecDeque<(Instant, (f64, f64))>,
+    fling: Option<Fling>,
+}
+
+#[derive(Debug)]
+struct Fling {
+    velocity: (f64, f64),
+    last: Instant,
+}
+
+impl Momentum {
+    /// Whether a fling is gliding.
+    pub(crate) fn is_active(&self) -> bool {
+        self.fling.is_some()
+    }
+
+    /// Record movement during a gesture, stopping any fling.
+    pub(crate) fn track(&mut self, now: Instant, delta: (f64, f64)) {
+        self.fling = None;
+        self.samples.push_back((now, delta));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > VELOCITY_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// End a gesture, starting a fling if it was moving fast enough.
+    pub(crate) fn release(&mut self, now: Instant) {
+        let velocity = release_velocity(&self.samples, now);
+        self.samples.clear();
+        if velocity.0.hypot(velocity.1) >= MIN_FLING_VELOCITY {
+            self.fling = Some(Fling {
+                velocity,
+                last: now,
+            });
+        }
+    }
+
+    /// Stop any fling and forget the current gesture.
+    pub(crate) fn stop(&mut self) {
+        self.samples.clear();
+        self.fling = None;
+    }
+
+    /// Distance the fling covers since the last step.
+    pub(crate) fn step(&mut self, now: Instant) -> Option<(f64, f64)> {
+        let fling = self.fling.as_mut()?;
+        let dt = now.duration_since(fling.last).as_secs_f64();
+        let decay = (-dt / FLING_TIME_CONSTANT).exp();
+        // Integral of the decaying velocity over the step
+        let distance = FLING_TIME_CONSTANT * (1.0 - decay);
+        let (vx, vy) = fling.velocity;
+        let delta = (vx * distance, vy * distance);
+        fling.velocity = (vx * decay, vy * decay);
+        fling.last = now;
+        if fling.velocity.0.hypot(fling.velocity.1) < STOP_VELOCITY {
+            self.fling = None;
+        }
+        Some(delta)
+    }
+}
+
+/// Pull a stretch further, with resistance that grows towards
+/// [`MAX_STRETCH`]. Pulling the other way lets go.
+fn rubber_band(stretch: f64, pull: f64) -> f64 {
+    if stretch * pull < 0.0 {
+        return 0.0;
+    }
+    let resistance = 0.5 * (1.0 - stretch.abs() / MAX_STRETCH).max(0.0);
+    (stretch + pull * resistance).clamp(-MAX_STRETCH, MAX_STRETCH)
+}
+
+/// A scroller stretched past its edge.
+#[derive(Debug)]
+struct Stretch {
+    scroller: Scroller,
+    /// Positive when stretched past the start.
+    amount: (f64, f64),
+    last_pull: Instant,
+    release: Option<(Instant, (f64, f64))>,
+}
+
+/// Rubber-banding past scroll edges, for windows with `overscroll_bounce`.
+#[derive(Debug, Default)]
+pub(crate) struct Bounce {
+    stretch: Option<Stretch>,
+}
+
+impl Bounce {
+    /// Whether a scroller is stretched.
+    pub(crate) fn is_active(&self) -> bool {
+        self.stretch.is_some()
+    }
+
+    /// Drop any stretch without animating, because the document changed.
+    pub(crate) fn clear(&mut self) {
+        self.stretch = None;
+    }
+
+    /// Stretch a scroller with the part of a wheel delta it couldn't take.
+    pub(crate) fn pull(
+        &mut self,
+        doc: &mut BaseDocument,
+        scroller: Scroller,
+        leftover: (f64, f64),
+        now: Instant,
+    ) {
+        if leftover == (0.0, 0.0) {
+            return;
+        }
+        let amount = match self.stretch.take() {
+            Some(stretch) if stretch.scroller == scroller => stretch.amount,
+            Some(stretch) => {
+                // Snap the previously stretched scroller back to its edge
+                let state = stretch.scroller.state(doc);
+                stretch
+                    .scroller
+                    .scroll_to(doc, (state.scroll_x, state.scroll_y));
+                (0.0, 0.0)
+            }
+            None => (0.0, 0.0),
+        };
+        let stretch = Stretch {
+            scroller,
+            amount: (
+                rubber_band(amount.0, leftover.0),
+                rubber_band(amount.1, leftover.1),
+            ),
+            last_pull: now,
+            release: None,
+        };
+        stretch.apply(doc);
+        self.stretch = Some(stretch);
+    }
+
+    /// Spring a stretch back once input stops.
+    pub(crate) fn tick(&mut self, doc: &mut BaseDocument, now: Instant) {
+        let Some(stretch) = &mut self.stretch else {
+            return;
+        };
+        if stretch.release.is_none() && now.duration_since(stretch.last_pull) >= STRETCH_HOLD {
+            stretch.release = Some((now, stretch.amount));
+        }
+        let Some((start, from)) = stretch.release else {
+            return;
+        };
+        let t = (now.duration_since(start).as_secs_f64() / STRETCH_SETTLE.as_secs_f64()).min(1.0);
+        let remaining = 1.0 - ease_out_cubic(t);
+        stretch.amount = (from.0 * remaining, from.1 * remaining);
+        stretch.apply(doc);
+        if t >= 1.0 {
+            self.stretch = None;
+        }
+    }
+}
+
+impl Stretch {
+    /// Move the scroller past its edge by the stretch.
+    fn apply(&self, doc: &mut BaseDocument) {
+        let state = self.scroller.state(doc);
+        let x = state.scroll_x.clamp(0.0, state.max_scroll_x());
+        let y = state.scroll_y.clamp(0.0, state.max_scroll_y());
+        self.scroller
+            .set_offset(doc, (x - self.amount.0, y - self.amount.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::test_util::values;
+
+    #[test]
+    fn release_velocity_averages_recent_movement() {
+        let start = Instant::now();
+        let mut samples = VecDeque::new();
+        samples.push_back((start, (0.0, 500.0)));
+        for i in 1..=5 {
+            samples.push_back((start + Duration::from_millis(200 + i * 10), (0.0, -10.0)));
+        }
+        let now = start + Duration::from_millis(250);
+        let (vx, vy) = release_velocity(&samples, now);
+        assert_eq!(vx, 0.0);
+        // The early sample is outside the window; 50px over 40ms
+        assert!((vy + 1250.0).abs() < 1e-6);
+        assert_eq!(release_velocity(&VecDeque::new(), now), (0.0, 0.0));
+    }
+
+    #[test]
+    fn flings_decelerate_and_stop() {
+        let start = Instant::now();
+        let mut momentum = Momentum::default();
+        for i in 0..5 {
+            momentum.track(start + Duration::from_millis(i * 10), (0.0, 20.0));
+        }
+        momentum.release(start + Duration::from_millis(50));
+        assert!(momentum.is_active());
+
+        let mut last = f64::INFINITY;
+        let mut total = 0.0;
+        let mut frame = 1;
+        while let Some((_, dy)) = momentum.step(start + Duration::from_millis(50 + frame * 16)) {
+            assert!(dy > 0.0 && dy < last);
+            last = dy;
+            total += dy;
+            frame += 1;
+        }
+        assert!(!momentum.is_active());
+        // About velocity * time constant: 2000 px/s * 0.325 s
+        assert!((600.0..=660.0).contains(&total), "{total}");
+    }
+
+    #[test]
+    fn slow_releases_dont_fling() {
+        let start = Instant::now();
+        let mut momentum = Momentum::default();
+        momentum.track(start, (0.0, 1.0));
+        momentum.release(start + Duration::from_millis(50));
+        assert!(!momentum.is_active());
+        assert_eq!(momentum.step(start + Duration::from_millis(66)), None);
+    }
+
+    #[test]
+    fn parses_overscroll_behavior() {
+        use OverscrollBehavior::*;
+        assert_eq!(overscroll_behaviors(&[]), (Auto, Auto));
+        assert_eq!(
+            overscroll_behaviors(&values(&[("overscroll-behavior", "contain")])),
+            (Contain, Contain)
+        );
+        assert_eq!(
+            overscroll_behaviors(&values(&[("overscroll-behavior", "auto none")])),
+            (Auto, None)
+        );
+        assert_eq!(
+            overscroll_behaviors(&values(&[
+                ("overscroll-behavior", "none"),
+                ("overscroll-behavior-x", "auto"),
+                ("overscroll-behavior-block", "contain"),
+            ])),
+            (Auto, Contain)
+        );
+        assert_eq!(
+            overscroll_behaviors(&values(&[("overscroll-behavior", "bogus")])),
+            (Auto, Auto)
+        );
+    }
+
+    #[test]
+    fn rubber_band_stiffens_and_lets_go() {
+        let first = rubber_band(0.0, 40.0);
+        assert_eq!(first, 20.0);
+        let second = rubber_band(first, 40.0);
+        assert!(second - first < first);
+        assert_eq!(rubber_band(MAX_STRETCH, 40.0), MAX_STRETCH);
+        assert_eq!(rubber_band(-30.0, -10.0), -30.0 - 10.0 * 0.5 * 0.75);
+        assert_eq!(rubber_band(30.0, -5.0), 0.0);
+    }
+}
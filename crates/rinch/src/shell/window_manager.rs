@@ -5,33 +5,80 @@ use std::sync::Arc;
 use std::task::Waker;
 use std::time::Instant;
 
-use anyrender_vello::VelloWindowRenderer;
 use anyrender::WindowRenderer;
+use anyrender_vello::{VelloRendererOptions, VelloWindowRenderer};
 use peniko::Color;
+use vello::AaConfig;
 
 use super::transparent_renderer::{TransparentRendererOptions, TransparentWindowRenderer};
-use blitz_dom::{Document, DocumentConfig};
+use blitz_dom::{BaseDocument, Document, DocumentConfig};
 use blitz_html::HtmlDocument;
 use blitz_paint::paint_scene;
+use blitz_shell::convert_events::{winit_ime_to_blitz, winit_key_event_to_blitz};
 use blitz_traits::shell::{ColorScheme, Viewport};
 use blitz_traits::events::{
     BlitzMouseButtonEvent, BlitzWheelDelta, BlitzWheelEvent, MouseEventButton, MouseEventButtons,
     UiEvent,
 };
 use futures_util::task::ArcWake;
-use rinch_core::element::WindowProps;
-use rinch_core::events::EventHandlerId;
+use rinch_core::clock;
+use rinch_core::element::{Antialiasing, WindowProps};
+use rinch_core::events::{ClipboardEvent, EventHandlerId};
+use rinch_core::node_ref::{AnimatedStyle, FocusRequest, ScrollBehavior, ScrollRequest};
+use rinch_core::portal::hoist_portals;
 use winit::dpi::{LogicalPosition, LogicalSize};
-use winit::event::{ElementState, Modifiers, MouseButton, WindowEvent};
+use winit::event::{
+    ElementState, Modifiers, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+};
 use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
 use winit::window::{Theme, Window, WindowAttributes, WindowId};
 
 #[cfg(target_os = "windows")]
 use winit::platform::windows::WindowAttributesExtWindows;
 
+use super::animated_styles::AnimatedStyles;
+use super::cascade::{matched_rules, MediaContext, StyleSource};
+use super::computed::{declared_computed_values, mirror_inline_styles, mirrored_rules};
+use super::css_animations::CssAnimations;
 use super::devtools::DevToolsState;
+use super::drag::{DragHandlers, ElementDrag};
+use super::find_bar::FindState;
+use super::flip::{MoveAnimations, MoveSnapshot};
+use super::focus::{focus_rules, Focus};
+use super::keyboard::{
+    bubbling_handlers, clipboard_attr, handler, key_target, keyboard_event, types_text, BLUR_ATTR,
+    FOCUS_ATTR, KEY_DOWN_ATTR, KEY_PRESS_ATTR, KEY_UP_ATTR, PASTE_ATTR,
+};
+use super::live_regions::LiveRegions;
+#[cfg(feature = "lottie")]
+use super::lottie::LottiePlayers;
+use super::paint_flash::{PaintFlash, Snapshot};
+use super::remeasure::{remeasure, Revealed};
+use super::rsx_source::find_source;
+use super::rtl::MirroredRows;
 use super::runtime::{ElementLayout, HoveredElementInfo, RinchEvent};
+use super::scroll::{
+    scroll_into_view_offsets, ScrollAnimations, ScrollSnapshot, ScrollWatch, Scroller,
+};
+use super::scrollbars::{scrollbar_rules, Scrollbars, OVERLAY_SCROLLBAR_CSS};
+use super::selection::{match_chain_in, StyleState, TextSelection};
+use super::sticky::StickyElements;
+use super::stylesheets::{
+    append_generated_styles, inline_local_stylesheets, split_styles, styles_only_change,
+    user_agent_stylesheets, GENERATED_STYLE_ATTR, INLINED_HREF_ATTR,
+};
+use super::text_effects::{needs_decoration_painting, TextEffects, DECORATION_RESET_CSS};
+use super::text_input::{field_value, set_caret, TextField};
+use super::text_rewrite::apply_text_rewrites;
+use super::transitions::Transitions;
+use super::wheel::{Bounce, Momentum, ScrollChaining, LINE_HEIGHT};
+use super::zoom::{clamp_scale, step_scale, WheelZoom};
+
+use crate::debug::node_count;
+use crate::fonts::FontCache;
+use crate::menu::{typed_with_alt_graph, KeyPress};
+use crate::windows::Zoom;
 
 /// Renderer wrapper that supports both standard and transparent rendering.
 pub enum RinchWindowRenderer {
@@ -99,7 +146,7 @@ pub struct ManagedWindow {
     pub keyboard_modifiers: Modifiers,
     /// Mouse button state.
     pub buttons: MouseEventButtons,
-    /// Current mouse position.
+    /// Current mouse position, in document coordinates.
     pub mouse_pos: (f32, f32),
     /// Animation start time.
     pub animation_timer: Option<Instant>,
@@ -107,6 +154,68 @@ pub struct ManagedWindow {
     pub is_visible: bool,
     /// DevTools state for this window.
     pub devtools: DevToolsState,
+    /// Selected text in the document.
+    pub selection: TextSelection,
+    /// A press on an element with drag handlers, until it's released.
+    pub(crate) element_drag: Option<ElementDrag>,
+    /// Find bar search state.
+    pub find: FindState,
+    /// Text decorations and shadows painted over the document.
+    pub text_effects: TextEffects,
+    /// Scrollbars painted over the document.
+    pub scrollbars: Scrollbars,
+    /// The HTML most recently provided by the app, before stylesheet inlining.
+    pub source_html: String,
+    /// The HTML the current document was built from.
+    rendered_html: String,
+    /// Elements with `onscroll` handlers or node refs.
+    pub(crate) scroll_watch: ScrollWatch,
+    /// Smooth scrolls in progress.
+    scroll_animations: ScrollAnimations,
+    /// Which scroller wheel input moves.
+    scroll_chaining: ScrollChaining,
+    /// `position: sticky` elements, which rinch moves as they scroll.
+    sticky: StickyElements,
+    /// Trackpad fling in progress.
+    momentum: Momentum,
+    /// Scroller stretched past its edge, with `overscroll_bounce`.
+    bounce: Bounce,
+    /// Keyed list items and shared elements gliding to where a re-render
+    /// moved them.
+    moves: MoveAnimations,
+    /// Paint-time styles set through node refs.
+    animated_styles: AnimatedStyles,
+    /// CSS transitions in progress.
+    transitions: Transitions,
+    /// When animated elements' CSS animations started.
+    css_animations: CssAnimations,
+    /// Right-to-left flex rows flipped after styling.
+    mirrored_rows: MirroredRows,
+    /// The font context, reused while the `@font-face` rules stay the same.
+    fonts: FontCache,
+    /// Elements styles hide, to remeasure when hover or focus shows them.
+    revealed: Revealed,
+    /// The hovered and focused elements the document was last styled with.
+    style_state: StyleState,
+    /// `LottiePlayer` elements and their playback.
+    #[cfg(feature = "lottie")]
+    lottie: LottiePlayers,
+    /// Re-render highlighting, toggled with Alt+R.
+    paint_flash: PaintFlash,
+    /// Elements whose text changes are announced to screen readers.
+    live_regions: LiveRegions,
+    /// The focused element and its focus ring.
+    focus: Focus,
+    /// The element `onfocus` was last sent for.
+    focused: Option<usize>,
+    /// The focused text field, which typing edits.
+    text_field: Option<TextField>,
+    /// Zoom on top of the display's scale factor (blitz's viewport zoom).
+    content_scale: f32,
+    /// Ctrl+wheel movement towards the next zoom level.
+    wheel_zoom: WheelZoom,
+    /// Whether AltGr is held.
+    alt_graph: bool,
 }
 
 impl ManagedWindow {
@@ -138,6 +247,15 @@ impl ManagedWindow {
             attrs = attrs.with_position(LogicalPosition::new(x, y));
         }
 
+        // An embedded app's first window goes into the host's window
+        #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+        if let Some(parent) = super::embed::take_parent() {
+            // SAFETY: `attach_to_parent`'s caller vouches for the handle
+            attrs = unsafe { attrs.with_parent_window(Some(parent)) }
+                .with_decorations(false)
+                .with_position(LogicalPosition::new(0, 0));
+        }
+
         // On Windows, transparent windows need WS_EX_NOREDIRECTIONBITMAP for true
         // desktop transparency with DirectComposition
         #[cfg(target_os = "windows")]
@@ -164,16 +282,40 @@ impl ManagedWindow {
             Theme::Light => ColorScheme::Light,
             Theme::Dark => ColorScheme::Dark,
         };
-        let viewport = Viewport::new(size.width, size.height, scale, color_scheme);
+        let mut viewport = Viewport::new(size.width, size.height, scale, color_scheme);
+        let content_scale = clamp_scale(props.content_scale).unwrap_or(1.0);
+        viewport.set_zoom(content_scale);
+        crate::windows::update_content_scale(window.id(), content_scale);
 
-        // Create document config
+        let rendered_html = inline_local_stylesheets(&html_content);
+        let mut fonts = FontCache::default();
         let config = DocumentConfig {
             viewport: Some(viewport),
+            ua_stylesheets: Some(user_agent_stylesheets(
+                blitz_dom::DEFAULT_CSS,
+                props.reset,
+                props.rtl,
+            )),
+            font_ctx: Some(fonts.font_context(&split_styles(&rendered_html).1)),
             ..Default::default()
         };
-
-        // Parse HTML into document
-        let doc: Box<dyn Document> = Box::new(HtmlDocument::from_html(&html_content, config));
+        let mut css_animations = CssAnimations::default();
+        let mut mirrored_rows = MirroredRows::default();
+        let (doc, text_effects, scrollbars, scroll_chaining, sticky) = build_document(
+            &rendered_html,
+            config,
+            props.overlay_scrollbars,
+            0.0,
+            &mut css_animations,
+            &mut mirrored_rows,
+        );
+        let mut transitions = Transitions::default();
+        let mut revealed = Revealed::default();
+        {
+            let inner = doc.inner();
+            transitions.rebuilt(&inner, Instant::now());
+            revealed.rebuilt(&inner);
+        }
 
         // Set the document title from HTML if present
         {
@@ -185,20 +327,33 @@ impl ManagedWindow {
         }
 
         // Create renderer - use transparent renderer for transparent windows on Windows
+        let antialiasing_method = aa_config(props.antialiasing);
         let renderer = if props.transparent && cfg!(target_os = "windows") {
             RinchWindowRenderer::Transparent(TransparentWindowRenderer::with_options(
                 TransparentRendererOptions {
                     // Fully transparent base for true window transparency
                     base_color: Color::TRANSPARENT,
                     transparent: true,
+                    antialiasing_method,
                     ..Default::default()
                 },
             ))
         } else {
-            RinchWindowRenderer::Standard(VelloWindowRenderer::new())
+            RinchWindowRenderer::Standard(VelloWindowRenderer::with_options(VelloRendererOptions {
+                antialiasing_method,
+                ..Default::default()
+            }))
         };
 
         let is_visible = window.is_visible().unwrap_or(true);
+        let scroll_watch = ScrollWatch::new(&doc.inner());
+        let live_regions = LiveRegions::new(&doc.inner());
+        #[cfg(feature = "lottie")]
+        let lottie = {
+            let mut lottie = LottiePlayers::default();
+            lottie.find(&doc.inner());
+            lottie
+        };
 
         Ok(Self {
             doc,
@@ -213,6 +368,37 @@ impl ManagedWindow {
             animation_timer: None,
             is_visible,
             devtools: DevToolsState::new(),
+            selection: TextSelection::default(),
+            element_drag: None,
+            focused: None,
+            text_field: None,
+            find: FindState::default(),
+            text_effects,
+            scrollbars,
+            source_html: html_content,
+            rendered_html,
+            scroll_watch,
+            scroll_animations: ScrollAnimations::default(),
+            scroll_chaining,
+            sticky,
+            momentum: Momentum::default(),
+            bounce: Bounce::default(),
+            moves: MoveAnimations::default(),
+            animated_styles: AnimatedStyles::default(),
+            transitions,
+            css_animations,
+            mirrored_rows,
+            fonts,
+            revealed,
+            style_state: StyleState::default(),
+            #[cfg(feature = "lottie")]
+            lottie,
+            paint_flash: PaintFlash::default(),
+            live_regions,
+            focus: Focus::default(),
+            content_scale,
+            wheel_zoom: WheelZoom::default(),
+            alt_graph: false,
         })
     }
 
@@ -221,6 +407,36 @@ impl ManagedWindow {
         self.window.id()
     }
 
+    /// The window's content scale, where 1.0 is 100%.
+    pub fn content_scale(&self) -> f32 {
+        self.content_scale
+    }
+
+    /// Change the content scale. Layout runs again at the new scale on the
+    /// next redraw. Returns `false` if the scale didn't change.
+    pub fn zoom(&mut self, zoom: Zoom) -> bool {
+        let scale = match zoom {
+            Zoom::In => step_scale(self.content_scale, 1),
+            Zoom::Out => step_scale(self.content_scale, -1),
+            Zoom::Reset => 1.0,
+            Zoom::To(scale) => match clamp_scale(scale) {
+                Some(scale) => scale,
+                None => return false,
+            },
+        };
+        if scale == self.content_scale {
+            return false;
+        }
+        // Keep the pointer over the same content
+        let ratio = self.content_scale / scale;
+        self.mouse_pos = (self.mouse_pos.0 * ratio, self.mouse_pos.1 * ratio);
+        self.content_scale = scale;
+        self.doc.inner_mut().viewport_mut().set_zoom(scale);
+        crate::windows::update_content_scale(self.window_id(), scale);
+        self.request_redraw();
+        true
+    }
+
     /// Request a redraw.
     pub fn request_redraw(&self) {
         if self.renderer.is_active() {
@@ -231,9 +447,9 @@ impl ManagedWindow {
     /// Get current animation time.
     fn current_animation_time(&mut self) -> f64 {
         match &self.animation_timer {
-            Some(start) => Instant::now().duration_since(*start).as_secs_f64(),
+            Some(start) => clock::now().duration_since(*start).as_secs_f64(),
             None => {
-                self.animation_timer = Some(Instant::now());
+                self.animation_timer = Some(clock::now());
                 0.0
             }
         }
@@ -246,6 +462,7 @@ impl ManagedWindow {
 
         let mut inner = self.doc.inner_mut();
         inner.resolve(animation_time);
+        self.sticky.update(&mut inner);
 
         let (width, height) = inner.viewport().window_size;
         let scale = inner.viewport().scale_f64();
@@ -262,6 +479,12 @@ impl ManagedWindow {
 
         // Set up waker for async updates
         self.waker = Some(create_waker(&self.proxy, window_id));
+
+        // Lottie players are painted by redraw
+        #[cfg(feature = "lottie")]
+        if !self.lottie.is_empty() {
+            self.request_redraw();
+        }
     }
 
     /// Suspend rendering.
@@ -284,21 +507,89 @@ impl ManagedWindow {
 
     /// Redraw the window.
     pub fn redraw(&mut self) {
+        let span = tracing::debug_span!("frame", window = ?self.window_id(), nodes = Empty);
+        let _frame = span.enter();
         let animation_time = self.current_animation_time();
         let is_visible = self.is_visible;
+        let now = Instant::now();
 
         let mut inner = self.doc.inner_mut();
-        inner.resolve(animation_time);
+        self.transitions.tick(&mut inner, now);
+        tracing::debug_span!("style_layout").in_scope(|| inner.resolve(animation_time));
+        if !span.is_disabled() {
+            span.record("nodes", node_count(&inner));
+        }
+        self.scroll_animations.tick(&mut inner);
+
+        if let Some(delta) = self.momentum.step(now) {
+            let (x, y) = self.mouse_pos;
+            let (scroller, leftover) =
+                self.scroll_chaining
+                    .scroll(&mut inner, (x as f64, y as f64), delta, now);
+            if leftover != (0.0, 0.0) {
+                // A fling that hits an edge stops there, bouncing if enabled
+                if self.props.overscroll_bounce {
+                    let overscroll = self.scroll_chaining.overscroll(scroller, leftover);
+                    self.bounce.pull(&mut inner, scroller, overscroll, now);
+                }
+                self.momentum.stop();
+            }
+        }
+        self.bounce.tick(&mut inner, now);
+        self.moves.tick(&mut inner, now);
+        self.animated_styles.apply(&mut inner, &self.scroll_watch);
+        self.sticky.update(&mut inner);
 
         let (width, height) = inner.viewport().window_size;
         let scale = inner.viewport().scale_f64();
-        let is_animating = inner.is_animating();
+        let is_animating = inner.is_animating()
+            || self.scroll_animations.is_active()
+            || self.momentum.is_active()
+            || self.bounce.is_active()
+            || self.moves.is_active()
+            || self.transitions.is_active()
+            || self.paint_flash.is_active(now);
+        #[cfg(feature = "lottie")]
+        let is_animating = self.lottie.tick(now) || is_animating;
+
+        self.find.refresh(&inner);
+        send_watch_changes(
+            &self.proxy,
+            self.window.id(),
+            &mut self.scroll_watch,
+            &inner,
+        );
 
-        self.renderer.render(|scene| paint_scene(scene, &inner, scale, width, height));
+        let selection = &self.selection;
+        let find = &self.find;
+        let focus = &self.focus;
+        let text_effects = &self.text_effects;
+        let devtools = &self.devtools;
+        let paint_flash = &self.paint_flash;
+        #[cfg(feature = "lottie")]
+        let lottie = &self.lottie;
+        let scrollbars = &mut self.scrollbars;
+        let mut scrollbars_showing = false;
+        let render = tracing::debug_span!("render").entered();
+        self.renderer.render(|scene| {
+            let _scene = tracing::debug_span!("scene").entered();
+            paint_scene(scene, &inner, scale, width, height);
+            #[cfg(feature = "lottie")]
+            lottie.paint(scene, &inner, scale);
+            text_effects.paint(scene, &inner, scale);
+            find.paint(scene, &inner, scale);
+            selection.paint(scene, &inner, scale);
+            focus.paint(scene, &inner, scale);
+            scrollbars_showing = scrollbars.paint(scene, &inner, scale);
+            devtools.paint(scene, &inner, scale);
+            paint_flash.paint(scene, &inner, scale);
+        });
+        drop(render);
 
         drop(inner);
 
-        if is_visible && is_animating {
+        // Overlay scrollbars need frames to fade out
+        if is_visible && (is_animating || scrollbars_showing) {
             self.request_redraw();
         }
     }
@@ -343,32 +634,45 @@ impl ManagedWindow {
                 self.keyboard_modifiers = new_state;
             }
             WindowEvent::KeyboardInput { event, .. } => {
+                if event.logical_key == Key::Named(NamedKey::AltGraph) {
+                    self.alt_graph = event.state.is_pressed();
+                }
                 let PhysicalKey::Code(key_code) = event.physical_key else {
                     return;
                 };
+                self.send_key_event(&event);
 
                 if event.state.is_pressed() {
                     let ctrl = self.keyboard_modifiers.state().control_key();
                     let meta = self.keyboard_modifiers.state().super_key();
                     let alt = self.keyboard_modifiers.state().alt_key();
                     let shift = self.keyboard_modifiers.state().shift_key();
+                    // AltGr types characters; it isn't Ctrl+Alt
+                    let (ctrl, alt) = if self.alt_graph
+                        || typed_with_alt_graph(ctrl, alt, event.text.as_deref())
+                    {
+                        (false, false)
+                    } else {
+                        (ctrl, alt)
+                    };
+                    let press = KeyPress {
+                        ctrl_or_cmd: ctrl || meta,
+                        alt,
+                        shift,
+                        key: key_code,
+                        character: key_character(&event),
+                    };
 
-                    // Ctrl/Cmd keyboard shortcuts for zoom
+                    // Ctrl/Cmd keyboard shortcuts (zoom is handled by the
+                    // runtime, after menu shortcuts)
                     if ctrl || meta {
-                        match key_code {
-                            KeyCode::Equal => {
-                                self.doc.inner_mut().viewport_mut().zoom_by(0.1);
-                                self.request_redraw();
-                            }
-                            KeyCode::Minus => {
-                                self.doc.inner_mut().viewport_mut().zoom_by(-0.1);
-                                self.request_redraw();
-                            }
-                            KeyCode::Digit0 => {
-                                self.doc.inner_mut().viewport_mut().set_zoom(1.0);
-                                self.request_redraw();
-                            }
-                            _ => {}
+                        self.send_clipboard_event(&press);
+                        if press.is_letter('c') {
+                            self.copy_selection();
+                        } else if press.is_letter('f') {
+                            let _ = self.proxy.send_event(RinchEvent::ToggleFindBar {
+                                source_window: self.window_id(),
+                            });
                         }
                     }
 
@@ -386,6 +690,11 @@ impl ManagedWindow {
                                 tracing::info!("Inspect mode: {}", self.devtools.inspect_mode);
                                 self.request_redraw();
                             }
+                            KeyCode::KeyR => {
+                                self.paint_flash.toggle();
+                                tracing::info!("Paint flashing: {}", self.paint_flash.enabled);
+                                self.request_redraw();
+                            }
                             KeyCode::KeyT => {
                                 self.doc.inner().print_taffy_tree();
                             }
@@ -400,19 +709,64 @@ impl ManagedWindow {
                         });
                     }
 
+                    // Tab moves focus, and other keys show focus a click gave
+                    if !(ctrl || meta || alt) {
+                        let changed = match key_code {
+                            KeyCode::Tab => self.focus.tab(&mut self.doc.inner_mut(), shift),
+                            KeyCode::ShiftLeft
+                            | KeyCode::ShiftRight
+                            | KeyCode::ControlLeft
+                            | KeyCode::ControlRight
+                            | KeyCode::AltLeft
+                            | KeyCode::AltRight
+                            | KeyCode::SuperLeft
+                            | KeyCode::SuperRight
+                            | KeyCode::CapsLock => false,
+                            _ => self.focus.key_pressed(&mut self.doc.inner_mut()),
+                        };
+                        if changed {
+                            self.request_redraw();
+                        }
+                    }
+
                     // Send keyboard shortcut to runtime for menu accelerator matching
                     let _ = self.proxy.send_event(RinchEvent::KeyboardShortcut {
-                        ctrl,
-                        meta,
-                        alt,
-                        shift,
-                        key: key_code,
+                        window_id: self.window_id(),
+                        press,
+                        typing: self.text_field.is_some(),
                     });
                 }
+
+                // Typing edits the focused text field; Tab moved focus instead
+                if key_code != KeyCode::Tab && self.text_field.is_some() {
+                    let pressed = event.state.is_pressed();
+                    let key = winit_key_event_to_blitz(&event, self.keyboard_modifiers.state());
+                    self.doc.handle_ui_event(if pressed {
+                        UiEvent::KeyDown(key)
+                    } else {
+                        UiEvent::KeyUp(key)
+                    });
+                    let enter = matches!(key_code, KeyCode::Enter | KeyCode::NumpadEnter);
+                    self.field_edited(pressed && enter);
+                    self.request_redraw();
+                }
+                self.sync_focus();
+            }
+            WindowEvent::Ime(ime) => {
+                if self.text_field.is_some() {
+                    let ime = winit_ime_to_blitz(ime);
+                    self.doc.handle_ui_event(UiEvent::Ime(ime));
+                    self.field_edited(false);
+                    self.request_redraw();
+                }
             }
             WindowEvent::CursorMoved { position, .. } => {
                 let pos: winit::dpi::LogicalPosition<f32> = position.to_logical(self.window.scale_factor());
+                // Document coordinates, at the content scale
+                let scale = self.content_scale;
+                let pos = LogicalPosition::new(pos.x / scale, pos.y / scale);
                 self.mouse_pos = (pos.x, pos.y);
+                self.selection.extend(&self.doc.inner(), pos.x, pos.y);
 
                 let event = UiEvent::MouseMove(BlitzMouseButtonEvent {
                     x: pos.x,
@@ -422,6 +776,7 @@ impl ManagedWindow {
                     mods: Default::default(),
                 });
                 self.doc.handle_ui_event(event);
+                self.update_style_state();
 
                 // If in inspect mode, send hovered element info to DevTools
                 if self.devtools.inspect_mode {
@@ -444,6 +799,19 @@ impl ManagedWindow {
                     ElementState::Released => self.buttons ^= button.into(),
                 }
 
+                if button == MouseEventButton::Main {
+                    match state {
+                        ElementState::Pressed => {
+                            let (x, y) = self.mouse_pos;
+                            self.scroll_animations
+                                .cancel_at(&self.doc.inner(), (x as f64, y as f64));
+                            self.momentum.stop();
+                            self.selection.begin(&self.doc.inner(), x, y);
+                        }
+                        ElementState::Released => self.selection.end(),
+                    }
+                }
+
                 let event_data = BlitzMouseButtonEvent {
                     x: self.mouse_pos.0,
                     y: self.mouse_pos.1,
@@ -457,28 +825,43 @@ impl ManagedWindow {
                     ElementState::Released => UiEvent::MouseUp(event_data),
                 };
                 self.doc.handle_ui_event(event);
+                if button == MouseEventButton::Main && state == ElementState::Pressed {
+                    let hit = self.node_at_mouse();
+                    self.focus.pointer_down(&mut self.doc.inner_mut(), hit);
+                    self.sync_focus();
+                }
                 self.request_redraw();
             }
-            WindowEvent::MouseWheel { delta, .. } => {
-                let blitz_delta = match delta {
-                    winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                        BlitzWheelDelta::Lines(x as f64, y as f64)
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                // Trackpads report exact (physical) pixels; wheels report lines
+                let precise = matches!(delta, MouseScrollDelta::PixelDelta(_));
+                let mut delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        (x as f64 * LINE_HEIGHT, y as f64 * LINE_HEIGHT)
                     }
-                    winit::event::MouseScrollDelta::PixelDelta(pos) => {
-                        BlitzWheelDelta::Pixels(pos.x, pos.y)
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        let scale = self.window.scale_factor();
+                        (pos.x / scale, pos.y / scale)
                     }
                 };
-
-                let event = BlitzWheelEvent {
-                    delta: blitz_delta,
-                    x: self.mouse_pos.0,
-                    y: self.mouse_pos.1,
-                    button: MouseEventButton::Main,
-                    buttons: self.buttons,
-                    mods: Default::default(),
-                };
-
-                self.doc.handle_ui_event(UiEvent::Wheel(event));
+                // Ctrl+wheel zooms instead of scrolling
+                let modifiers = self.keyboard_modifiers.state();
+                if modifiers.control_key() || modifiers.super_key() {
+                    let steps = self.wheel_zoom.steps(delta.1);
+                    if steps != 0 {
+                        let scale = step_scale(self.content_scale, steps);
+                        let _ = self.proxy.send_event(RinchEvent::ZoomWindow {
+                            window_id: self.window_id(),
+                            zoom: Zoom::To(scale),
+                        });
+                    }
+                    return;
+                }
+                // Shift turns a vertical wheel horizontal
+                if !precise && self.keyboard_modifiers.state().shift_key() && delta.0 == 0.0 {
+                    delta = (delta.1, 0.0);
+                }
+                self.scroll_wheel(delta, precise, phase);
                 self.request_redraw();
             }
             _ => {}
@@ -486,33 +869,715 @@ impl ManagedWindow {
     }
 
     /// Update the window's HTML content and re-render.
+    ///
+    /// If only `<style>` content changed since the last update, the new styles
+    /// are applied to the live document instead of rebuilding it.
     pub fn update_content(&mut self, html_content: String) {
+        let span =
+            tracing::debug_span!("update_content", window = ?self.window_id(), nodes = Empty);
+        let _update = span.enter();
+        let before = self.paint_flash.before_render(&self.doc.inner());
+        let rendered_html = inline_local_stylesheets(&html_content);
+        super::relayout::record_window(self.window_id(), &self.props, &html_content);
+        self.source_html = html_content;
+
+        if let Some(styles) = styles_only_change(&self.rendered_html, &rendered_html)
+            && self.apply_styles(&rendered_html, &styles)
+        {
+            self.rendered_html = rendered_html;
+            self.flash_render(before);
+            return;
+        }
+        if self.rendered_html == rendered_html {
+            self.flash_render(before);
+            return;
+        }
+        // The document already shows what was typed into the focused field
+        if let Some(field) = &mut self.text_field
+            && field.keeps_document(&self.rendered_html, &rendered_html)
+        {
+            self.rendered_html = rendered_html;
+            self.flash_render(before);
+            return;
+        }
+        self.rendered_html = rendered_html;
+
+        // Node IDs from the old document are meaningless in the new one
+        self.selection.clear();
+        self.devtools.clear_selection();
+        self.find.invalidate();
+        self.bounce.clear();
+
         // Get current viewport settings
-        let (viewport, scale) = {
+        let (viewport, scale, scroll, moved) = {
             let inner = self.doc.inner();
-            (inner.viewport().clone(), inner.viewport().scale_f64())
+            (
+                inner.viewport().clone(),
+                inner.viewport().scale_f64(),
+                ScrollSnapshot::take(&inner),
+                MoveSnapshot::take(&inner),
+            )
         };
 
         // Create new document config with current viewport
         let config = DocumentConfig {
             viewport: Some(viewport),
+            ua_stylesheets: Some(user_agent_stylesheets(
+                blitz_dom::DEFAULT_CSS,
+                self.props.reset,
+                self.props.rtl,
+            )),
+            font_ctx: Some(
+                self.fonts
+                    .font_context(&split_styles(&self.rendered_html).1),
+            ),
             ..Default::default()
         };
 
         // Create new document with updated HTML
-        self.doc = Box::new(HtmlDocument::from_html(&html_content, config));
-
-        // Re-resolve and redraw
         let animation_time = self.current_animation_time();
+        let build = tracing::debug_span!("build_document").entered();
+        (
+            self.doc,
+            self.text_effects,
+            self.scrollbars,
+            self.scroll_chaining,
+            self.sticky,
+        ) = build_document(
+            &self.rendered_html,
+            config,
+            self.props.overlay_scrollbars,
+            animation_time,
+            &mut self.css_animations,
+            &mut self.mirrored_rows,
+        );
+        drop(build);
+        self.focus.restore(&mut self.doc.inner_mut());
+        self.restore_text_field();
+        self.focused = self.focus.node();
+        self.scroll_watch = ScrollWatch::new(&self.doc.inner());
+        self.live_regions.update(&self.doc.inner());
+        #[cfg(feature = "lottie")]
+        self.lottie.find(&self.doc.inner());
+
+        // Re-resolve, keeping scroll positions, and redraw
+        {
+            let mut inner = self.doc.inner_mut();
+            tracing::debug_span!("style_layout").in_scope(|| inner.resolve(animation_time));
+            if !span.is_disabled() {
+                span.record("nodes", node_count(&inner));
+            }
+        }
+        // The new document doesn't know what the pointer is over
+        self.hover_at_pointer();
         {
             let mut inner = self.doc.inner_mut();
+            let now = Instant::now();
+            // Transitions start from the styles with the pointer's hover
             inner.resolve(animation_time);
+            self.style_state = StyleState {
+                hovered: inner.get_hover_node_id(),
+                focused: self.focused,
+            };
+            if self.transitions.rebuilt(&inner, now) {
+                self.transitions.tick(&mut inner, now);
+            }
+            self.revealed.rebuilt(&inner);
+            inner.resolve(animation_time);
+            scroll.restore(&mut inner);
+            self.moves.start(&inner, &moved, now);
+            self.moves.tick(&mut inner, now);
+            self.animated_styles.clear_placed();
+            self.animated_styles.apply(&mut inner, &self.scroll_watch);
+            self.sticky.update(&mut inner);
+            send_watch_changes(
+                &self.proxy,
+                self.window.id(),
+                &mut self.scroll_watch,
+                &inner,
+            );
         }
 
         // Render the updated content
         let inner = self.doc.inner();
         let (width, height) = inner.viewport().window_size;
-        self.renderer.render(|scene| paint_scene(scene, &inner, scale, width, height));
+        tracing::debug_span!("render").in_scope(|| {
+            self.renderer.render(|scene| {
+                let _scene = tracing::debug_span!("scene").entered();
+                paint_scene(scene, &inner, scale, width, height);
+            })
+        });
+        drop(inner);
+        self.flash_render(before);
+
+        // Lottie players are painted by redraw
+        #[cfg(feature = "lottie")]
+        let has_players = !self.lottie.is_empty();
+        #[cfg(not(feature = "lottie"))]
+        let has_players = false;
+        if self.moves.is_active() || self.transitions.is_active() || has_players {
+            self.request_redraw();
+        }
+    }
+
+    /// Flash a re-render, if paint flashing is on. `before` is the snapshot
+    /// taken before it.
+    fn flash_render(&mut self, before: Option<Snapshot>) {
+        if before.is_some() {
+            self.paint_flash.rendered(before, &self.doc.inner());
+            self.request_redraw();
+        }
+    }
+
+    /// Re-read inlined local stylesheets and apply any changes.
+    pub fn reload_stylesheets(&mut self) {
+        self.update_content(self.source_html.clone());
+    }
+
+    /// Rebuild the document from the current content, picking up changed
+    /// user-agent stylesheets.
+    pub fn rebuild(&mut self) {
+        self.rendered_html.clear();
+        self.update_content(self.source_html.clone());
+    }
+
+    /// Rebuild the document, reading its asset files again.
+    pub fn reload_assets(&mut self) {
+        #[cfg(feature = "lottie")]
+        self.lottie.forget_animations();
+        self.rebuild();
+    }
+
+    /// Put the window where a saved state had it: position, size, and
+    /// whether it's maximized or minimized. Its zoom comes from its props.
+    pub(crate) fn restore_state(&self, state: &crate::windows::WindowState) {
+        self.window
+            .set_outer_position(winit::dpi::PhysicalPosition::new(state.x, state.y));
+        let _ = self
+            .window
+            .request_inner_size(winit::dpi::PhysicalSize::new(state.width, state.height));
+        self.window.set_maximized(state.maximized);
+        self.window.set_minimized(state.minimized);
+    }
+
+    /// The window's place, size and scroll positions, to hand to the app
+    /// that replaces this one in a hot restart.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn save_for_restart(&self) -> super::hot_restart::SavedWindow {
+        let size = self.window.inner_size();
+        super::hot_restart::SavedWindow {
+            title: self.props.title.clone(),
+            position: self.window.outer_position().ok().map(|p| (p.x, p.y)),
+            size: (size.width, size.height),
+            maximized: self.window.is_maximized(),
+            content_scale: self.content_scale,
+            scroll: ScrollSnapshot::take(&self.doc.inner()),
+        }
+    }
+
+    /// Put the window back as the app this one replaced left it.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn restore_after_restart(&mut self, saved: &super::hot_restart::SavedWindow) {
+        if let Some((x, y)) = saved.position {
+            self.window
+                .set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+        }
+        let (width, height) = saved.size;
+        let _ = self
+            .window
+            .request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+        self.window.set_maximized(saved.maximized);
+        self.zoom(Zoom::To(saved.content_scale));
+
+        let animation_time = self.current_animation_time();
+        let mut inner = self.doc.inner_mut();
+        inner.resolve(animation_time);
+        saved.scroll.restore(&mut inner);
+        drop(inner);
+        self.request_redraw();
+    }
+
+    /// Replace the contents of the document's `<style>` elements in place,
+    /// for a window's new `html` that differs only in them.
+    ///
+    /// `styles` must be in document order. Returns `false` without touching the
+    /// document if its style elements don't line up with `styles`, in which
+    /// case the caller should rebuild the document instead.
+    fn apply_styles(&mut self, html: &str, styles: &[String]) -> bool {
+        let paint_decorations = needs_decoration_painting(html);
+        if paint_decorations != needs_decoration_painting(&self.rendered_html) {
+            return false;
+        }
+        let generated_css =
+            generated_styles(styles, paint_decorations, self.props.overlay_scrollbars);
+        let animation_time = self.current_animation_time();
+        let mut inner = self.doc.inner_mut();
+
+        // Collect (style element, text child) pairs in document order, and
+        // the generated stylesheet's
+        let mut targets = Vec::new();
+        let mut generated = None;
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = inner.get_node(node_id) else {
+                continue;
+            };
+            if let Some(el) = node
+                .element_data()
+                .filter(|el| el.name.local.as_ref() == "style")
+            {
+                let text_id = node
+                    .children
+                    .first()
+                    .copied()
+                    .filter(|&id| inner.get_node(id).is_some_and(|n| n.is_text_node()));
+                if el
+                    .attrs()
+                    .iter()
+                    .any(|a| a.name.local.as_ref() == GENERATED_STYLE_ATTR)
+                {
+                    generated = Some((node_id, text_id));
+                } else {
+                    targets.push((node_id, text_id));
+                }
+            }
+            stack.extend(node.children.iter().rev());
+        }
+
+        // The generated stylesheet is only there when it has rules
+        let generated_text = generated.and_then(|(_, text)| text);
+        if targets.len() != styles.len()
+            || targets.iter().any(|(_, text)| text.is_none())
+            || generated_text.is_some() != !generated_css.is_empty()
+        {
+            return false;
+        }
+
+        self.transitions.unapply(&mut inner);
+        self.mirrored_rows.unmirror(&mut inner);
+        let updates = targets
+            .iter()
+            .zip(styles)
+            .chain(generated.iter().map(|target| (target, &generated_css)));
+        for (&(style_id, text_id), css) in updates {
+            if let Some(text) = text_id
+                .and_then(|id| inner.get_node_mut(id))
+                .and_then(|node| node.text_data_mut())
+            {
+                text.content = css.clone();
+            }
+            inner.upsert_stylesheet_for_node(style_id);
+        }
+
+        inner.resolve(animation_time);
+        if self.mirrored_rows.mirror(&mut inner) {
+            inner.resolve(animation_time);
+        }
+        let now = Instant::now();
+        self.transitions.update(&inner, now);
+        self.transitions.tick(&mut inner, now);
+        self.revealed.restyled(&mut inner);
+        inner.resolve(animation_time);
+        drop(inner);
+
+        tracing::debug!("Applied {} stylesheet(s) in place", styles.len());
+        self.request_redraw();
+        true
+    }
+
+    /// Send a key event to the key handlers of the focused element and those
+    /// above it.
+    fn send_key_event(&self, event: &winit::event::KeyEvent) {
+        let mut key = keyboard_event(event, self.keyboard_modifiers.state());
+        // AltGr types characters; it isn't Ctrl+Alt
+        if self.alt_graph {
+            (key.ctrl, key.alt) = (false, false);
+        }
+        let inner = self.doc.inner();
+        let Some(target) = key_target(&inner, self.focus.node()) else {
+            return;
+        };
+        let mut handlers = Vec::new();
+        if event.state.is_pressed() {
+            handlers.extend(bubbling_handlers(&inner, target, KEY_DOWN_ATTR));
+            if types_text(&key) {
+                handlers.extend(bubbling_handlers(&inner, target, KEY_PRESS_ATTR));
+            }
+        } else {
+            handlers.extend(bubbling_handlers(&inner, target, KEY_UP_ATTR));
+        }
+        if handlers.is_empty() {
+            return;
+        }
+        let calls = handlers
+            .into_iter()
+            .map(|handler_id| (handler_id, key.clone()))
+            .collect();
+        let _ = self.proxy.send_event(RinchEvent::KeyInput {
+            window_id: self.window_id(),
+            calls,
+        });
+    }
+
+    /// Send `oncopy`, `oncut` or `onpaste` for Ctrl/Cmd+C, X or V to the
+    /// focused element (or `body`) and the elements above it.
+    fn send_clipboard_event(&self, press: &KeyPress) {
+        let Some(attr) = clipboard_attr(press) else {
+            return;
+        };
+        let inner = self.doc.inner();
+        let Some(target) = key_target(&inner, self.focus.node()) else {
+            return;
+        };
+        let handlers = bubbling_handlers(&inner, target, attr);
+        if handlers.is_empty() {
+            return;
+        }
+        let event = if attr == PASTE_ATTR {
+            pasted()
+        } else {
+            ClipboardEvent {
+                text: self.selection.text(&inner),
+                image: None,
+            }
+        };
+        let _ = self.proxy.send_event(RinchEvent::ClipboardInput {
+            window_id: self.window_id(),
+            handlers,
+            event,
+        });
+    }
+
+    /// Catch up with focus moving: commit a text field it left, then send
+    /// `onblur` and `onfocus`.
+    fn sync_focus(&mut self) {
+        self.sync_text_field();
+        let focused = self.focus.node();
+        if focused == self.focused {
+            return;
+        }
+        let handlers: Vec<EventHandlerId> = {
+            let inner = self.doc.inner();
+            let blur = self
+                .focused
+                .and_then(|node_id| handler(&inner, node_id, BLUR_ATTR));
+            let focus = focused.and_then(|node_id| handler(&inner, node_id, FOCUS_ATTR));
+            blur.into_iter().chain(focus).collect()
+        };
+        self.focused = focused;
+        self.update_style_state();
+        if !handlers.is_empty() {
+            let _ = self.proxy.send_event(RinchEvent::FocusChanged {
+                window_id: self.window_id(),
+                handlers,
+            });
+        }
+    }
+
+    /// Hover what's under the pointer, as blitz does when it moves.
+    fn hover_at_pointer(&mut self) {
+        let (x, y) = self.mouse_pos;
+        self.doc
+            .handle_ui_event(UiEvent::MouseMove(BlitzMouseButtonEvent {
+                x,
+                y,
+                button: Default::default(),
+                buttons: self.buttons,
+                mods: Default::default(),
+            }));
+    }
+
+    /// Start transitions for styles that changed with the hovered or
+    /// focused element, and remeasure elements the change showed.
+    fn update_style_state(&mut self) {
+        let animation_time = self.current_animation_time();
+        let mut inner = self.doc.inner_mut();
+        let state = StyleState {
+            hovered: inner.get_hover_node_id(),
+            focused: self.focused,
+        };
+        if state == self.style_state {
+            return;
+        }
+        self.style_state = state;
+        self.transitions.unapply(&mut inner);
+        self.mirrored_rows.unmirror(&mut inner);
+        inner.resolve(animation_time);
+        if self.mirrored_rows.mirror(&mut inner) {
+            inner.resolve(animation_time);
+        }
+        let now = Instant::now();
+        let started = self.transitions.update(&inner, now);
+        // Write running transitions back before the next layout
+        self.transitions.tick(&mut inner, now);
+        let shown = self.revealed.restyled(&mut inner);
+        drop(inner);
+        if started || shown || self.transitions.is_active() {
+            self.request_redraw();
+        }
+    }
+
+    /// Follow focus into and out of text fields. Leaving one commits its
+    /// value.
+    fn sync_text_field(&mut self) {
+        let focused = self.focus.node();
+        if self.text_field.as_ref().map(TextField::node) == focused {
+            return;
+        }
+        if let Some(mut field) = self.text_field.take()
+            && let Some(value) = field.commit()
+        {
+            self.send_field_input(field.handlers().on_change(), value);
+        }
+        self.text_field = focused.and_then(|node_id| TextField::new(&self.doc.inner(), node_id));
+        self.window.set_ime_allowed(self.text_field.is_some());
+        self.place_ime_cursor();
+    }
+
+    /// Find the focused text field in a rebuilt document. If focus left it,
+    /// the field is gone, and its `onchange` goes with it.
+    fn restore_text_field(&mut self) {
+        let focused = self.focus.node();
+        let found = match (&mut self.text_field, focused) {
+            (Some(field), Some(node_id)) => field.rebuilt(&self.doc.inner(), node_id),
+            _ => false,
+        };
+        if !found && self.text_field.take().is_some() {
+            self.window.set_ime_allowed(false);
+        }
+    }
+
+    /// Pass the focused text field's value to the app if typing changed it,
+    /// and commit it if `commit` (Enter, in a single-line field).
+    fn field_edited(&mut self, commit: bool) {
+        let Some(field) = &mut self.text_field else {
+            return;
+        };
+        let Some(value) = field_value(&self.doc.inner(), field.node()) else {
+            return;
+        };
+        let handlers = field.handlers();
+        let edited = field.edited(value);
+        let committed = (commit && !field.is_multiline())
+            .then(|| field.commit())
+            .flatten();
+        if let Some(value) = edited {
+            self.send_field_input(handlers.on_input(), value);
+        }
+        if let Some(value) = committed {
+            self.send_field_input(handlers.on_change(), value);
+        }
+    }
+
+    fn send_field_input(&self, handlers: Vec<EventHandlerId>, value: String) {
+        if handlers.is_empty() {
+            return;
+        }
+        let _ = self.proxy.send_event(RinchEvent::FieldInput {
+            window_id: self.window_id(),
+            handlers,
+            value,
+        });
+    }
+
+    /// Show IME candidate windows under the focused text field.
+    fn place_ime_cursor(&self) {
+        let Some(field) = &self.text_field else {
+            return;
+        };
+        let inner = self.doc.inner();
+        let Some(node) = inner.get_node(field.node()) else {
+            return;
+        };
+        let position = node.absolute_position(0.0, 0.0);
+        let size = node.final_layout.size;
+        let scale = self.content_scale;
+        self.window.set_ime_cursor_area(
+            LogicalPosition::new(position.x * scale, position.y * scale),
+            LogicalSize::new(size.width * scale, size.height * scale),
+        );
+    }
+
+    /// Copy the selected text to the clipboard.
+    fn copy_selection(&self) {
+        let Some(text) = self.selection.text(&self.doc.inner()) else {
+            return;
+        };
+
+        #[cfg(feature = "clipboard")]
+        if let Err(e) = crate::clipboard::copy_text(text) {
+            tracing::warn!("Failed to copy selection: {}", e);
+        }
+
+        #[cfg(not(feature = "clipboard"))]
+        tracing::debug!(
+            "Enable the `clipboard` feature to copy selected text ({} bytes)",
+            text.len()
+        );
+    }
+
+    /// Scroll the current find match into view.
+    ///
+    /// Scrolling is driven through a synthetic wheel event at the match, so
+    /// the scroll container under it (or the viewport) scrolls.
+    pub fn scroll_to_current_match(&mut self) {
+        let (x, y, dy) = {
+            let inner = self.doc.inner();
+            let rects = self.find.current_rects(&inner);
+            let Some(first) = rects.first() else {
+                return;
+            };
+            let (width, height) = inner.viewport().window_size;
+            let scale = inner.viewport().scale_f64();
+            let (width, height) = (width as f64 / scale, height as f64 / scale);
+            if first.y0 >= 0.0 && first.y1 <= height {
+                return;
+            }
+            // Positive wheel deltas scroll up, so move by the distance from
+            // the viewport's center to the match's
+            let dy = height / 2.0 - first.center().y;
+            let x = first.center().x.clamp(0.0, width - 1.0) as f32;
+            let y = first.center().y.clamp(0.0, height - 1.0) as f32;
+            (x, y, dy)
+        };
+
+        let buttons = self.buttons;
+        let mouse_move = |x, y| {
+            UiEvent::MouseMove(BlitzMouseButtonEvent {
+                x,
+                y,
+                button: Default::default(),
+                buttons,
+                mods: Default::default(),
+            })
+        };
+        self.doc.handle_ui_event(mouse_move(x, y));
+        self.doc.handle_ui_event(UiEvent::Wheel(BlitzWheelEvent {
+            delta: BlitzWheelDelta::Pixels(0.0, dy),
+            x,
+            y,
+            button: MouseEventButton::Main,
+            buttons,
+            mods: Default::default(),
+        }));
+        // Restore hover to where the pointer really is
+        let (mouse_x, mouse_y) = self.mouse_pos;
+        self.doc.handle_ui_event(mouse_move(mouse_x, mouse_y));
+        self.request_redraw();
+    }
+
+    /// Scroll whatever is under the pointer by a wheel delta, in logical
+    /// pixels (positive values scroll towards the start).
+    ///
+    /// `precise` deltas come from trackpads, which feed momentum: a fast
+    /// gesture keeps gliding after the fingers lift. macOS does this itself,
+    /// sending the glide as further wheel events.
+    fn scroll_wheel(&mut self, delta: (f64, f64), precise: bool, phase: TouchPhase) {
+        let (x, y) = self.mouse_pos;
+        let point = (x as f64, y as f64);
+        let now = Instant::now();
+        let mut inner = self.doc.inner_mut();
+        self.scroll_animations.cancel_at(&inner, point);
+
+        let momentum = precise && !cfg!(target_os = "macos");
+        match phase {
+            TouchPhase::Started => {
+                self.momentum.stop();
+                self.scroll_chaining.unlatch();
+            }
+            _ if momentum => self.momentum.track(now, delta),
+            _ => {}
+        }
+
+        let (scroller, leftover) = self.scroll_chaining.scroll(&mut inner, point, delta, now);
+        if self.props.overscroll_bounce {
+            let overscroll = self.scroll_chaining.overscroll(scroller, leftover);
+            self.bounce.pull(&mut inner, scroller, overscroll, now);
+        }
+
+        if momentum && phase == TouchPhase::Ended {
+            self.momentum.release(now);
+        }
+    }
+
+    /// Carry out a scroll requested through a node ref.
+    ///
+    /// Returns `false` if the ref isn't attached to an element in this window.
+    pub fn scroll_node_ref(&mut self, node_ref: u64, request: ScrollRequest) -> bool {
+        let Some(node_id) = self.scroll_watch.node_for_ref(node_ref) else {
+            return false;
+        };
+        {
+            let mut inner = self.doc.inner_mut();
+            let (scrolls, behavior) = match request {
+                ScrollRequest::IntoView(options) => (
+                    scroll_into_view_offsets(&inner, node_id, options),
+                    options.behavior,
+                ),
+                ScrollRequest::To { x, y, behavior } => (
+                    vec![(Scroller::for_node(&inner, node_id), (x, y))],
+                    behavior,
+                ),
+            };
+            for (scroller, offset) in scrolls {
+                match behavior {
+                    ScrollBehavior::Instant => scroller.scroll_to(&mut inner, offset),
+                    ScrollBehavior::Smooth => {
+                        self.scroll_animations.start(&inner, scroller, offset)
+                    }
+                }
+            }
+        }
+        self.request_redraw();
+        true
+    }
+
+    /// Move focus, or a text field's caret, as requested through a node ref.
+    ///
+    /// Returns `false` if the ref isn't attached to an element in this window.
+    pub fn focus_node_ref(&mut self, node_ref: u64, request: FocusRequest) -> bool {
+        let Some(node_id) = self.scroll_watch.node_for_ref(node_ref) else {
+            return false;
+        };
+        {
+            let mut inner = self.doc.inner_mut();
+            match request {
+                FocusRequest::Focus => {
+                    if !self.focus.focus(&mut inner, node_id) {
+                        tracing::debug!("Node ref {} isn't focusable, not focusing it", node_ref);
+                    }
+                }
+                FocusRequest::Blur => self.focus.blur(&mut inner, node_id),
+                FocusRequest::Caret(position) => {
+                    if !set_caret(&mut inner, node_id, position) {
+                        tracing::debug!("Node ref {} isn't a text field, no caret", node_ref);
+                    }
+                }
+            }
+        }
+        self.sync_focus();
+        self.request_redraw();
+        true
+    }
+
+    /// Measure the element a node ref is attached to again. Returns `false`
+    /// if the ref isn't attached to an element in this window.
+    pub fn remeasure_node_ref(&mut self, node_ref: u64) -> bool {
+        let Some(node_id) = self.scroll_watch.node_for_ref(node_ref) else {
+            return false;
+        };
+        remeasure(&mut self.doc.inner_mut(), node_id);
+        self.request_redraw();
+        true
+    }
+
+    /// Replace the paint-time styles set through node refs, and repaint.
+    pub fn set_animated_styles(&mut self, styles: &HashMap<u64, AnimatedStyle>) {
+        if self.animated_styles.is_empty() && styles.is_empty() {
+            return;
+        }
+        self.animated_styles.set(styles);
+        self.request_redraw();
     }
 
     /// Get information about the element under the current mouse position.
@@ -597,15 +1662,52 @@ impl ManagedWindow {
             ));
         }
 
+        // Explain the cascade for the element, in its hover and focus state
+        let chain = match_chain_in(&inner, node_id, self.style_state);
+        let inline_style = chain
+            .first()
+            .and_then(|el| el.attrs.iter().find(|(n, _)| n == "style"))
+            .map(|(_, v)| v.clone());
+        let viewport = inner.viewport();
+        let media = MediaContext {
+            width: viewport.window_size.0 as f32 / viewport.scale(),
+            height: viewport.window_size.1 as f32 / viewport.scale(),
+            dark: viewport.color_scheme == ColorScheme::Dark,
+        };
+        let matched_rules = matched_rules(
+            &self.collect_stylesheets(),
+            inline_style.as_deref(),
+            &chain,
+            &media,
+        );
+        // What the rules set, as the style engine computed it
+        let computed_styles = declared_computed_values(
+            &inner,
+            node_id,
+            matched_rules
+                .iter()
+                .flat_map(|rule| &rule.declarations)
+                .map(|decl| decl.property.as_str()),
+        );
+        let source = find_source(&inner, node_id);
+
         Some(HoveredElementInfo {
             tag_name,
             id,
             classes,
             styles,
             layout,
+            matched_rules,
+            computed_styles,
+            source,
         })
     }
 
+    /// Collect the document's `<style>` contents in document order.
+    fn collect_stylesheets(&self) -> Vec<(StyleSource, String)> {
+        document_stylesheets(&self.doc.inner())
+    }
+
     /// Get the event handler ID of the element under the current mouse position.
     ///
     /// Returns `Some(id)` if there's an element with a `data-rid` attribute at the
@@ -640,6 +1742,41 @@ impl ManagedWindow {
         None
     }
 
+    /// The node under the current mouse position.
+    pub fn node_at_mouse(&self) -> Option<usize> {
+        let inner = self.doc.inner();
+        Some(inner.hit(self.mouse_pos.0, self.mouse_pos.1)?.node_id)
+    }
+
+    /// The value of `name` on the element under the current mouse position,
+    /// or on its nearest ancestor that has it.
+    pub fn attribute_at_mouse(&self, name: &str) -> Option<String> {
+        let inner = self.doc.inner();
+        let mut current = Some(inner.hit(self.mouse_pos.0, self.mouse_pos.1)?.node_id);
+        while let Some(node) = current.and_then(|id| inner.get_node(id)) {
+            let value = node.element_data().and_then(|element| {
+                element
+                    .attrs()
+                    .iter()
+                    .find(|attr| attr.name.local.as_ref() == name)
+                    .map(|attr| attr.value.to_string())
+            });
+            if value.is_some() {
+                return value;
+            }
+            current = node.parent;
+        }
+        None
+    }
+
+    /// The drag handlers of the element under the current mouse position,
+    /// or of its nearest ancestor with any.
+    pub(crate) fn drag_handlers_at_mouse(&self) -> Option<DragHandlers> {
+        let inner = self.doc.inner();
+        let node_id = inner.hit(self.mouse_pos.0, self.mouse_pos.1)?.node_id;
+        DragHandlers::find(&inner, node_id)
+    }
+
     /// Check if the element under the current mouse position should trigger window dragging.
     ///
     /// Returns `true` if there's an element with `data-drag-window` attribute at the
@@ -705,6 +1842,8 @@ impl WindowManager {
     ) -> Result<WindowId, Box<dyn std::error::Error>> {
         let window = ManagedWindow::new(event_loop, proxy, props, html_content)?;
         let window_id = window.window_id();
+        super::relayout::record_window(window_id, &window.props, &window.source_html);
+        crate::windows::record_native_window(window.window.clone());
         self.windows.insert(window_id, window);
         Ok(window_id)
     }
@@ -721,6 +1860,10 @@ impl WindowManager {
 
     /// Remove and close a window.
     pub fn close_window(&mut self, id: WindowId) -> Option<ManagedWindow> {
+        crate::windows::remove_content_scale(id);
+        crate::windows::remove_native_window(id);
+        crate::windows::unset_focused_window_id(id);
+        super::relayout::forget_window(id);
         self.windows.remove(&id)
     }
 
@@ -743,6 +1886,20 @@ impl WindowManager {
         }
     }
 
+    /// Pass every window the paint-time styles set through node refs.
+    pub fn set_animated_styles(&mut self, styles: &HashMap<u64, AnimatedStyle>) {
+        for window in self.windows.values_mut() {
+            window.set_animated_styles(styles);
+        }
+    }
+
+    /// Rebuild every window's document (see [`ManagedWindow::rebuild`]).
+    pub fn rebuild_all(&mut self) {
+        for window in self.windows.values_mut() {
+            window.rebuild();
+        }
+    }
+
     /// Iterate over all windows.
     pub fn windows_iter(&self) -> impl Iterator<Item = (&WindowId, &ManagedWindow)> {
         self.windows.iter()
@@ -760,6 +1917,193 @@ impl Default for WindowManager {
     }
 }
 
+/// The Vello antialiasing method for a window's setting.
+fn aa_config(antialiasing: Antialiasing) -> AaConfig {
+    match antialiasing {
+        Antialiasing::Area => AaConfig::Area,
+        Antialiasing::Msaa8 => AaConfig::Msaa8,
+        Antialiasing::Msaa16 => AaConfig::Msaa16,
+    }
+}
+
+/// The character a key types on the user's layout, ignoring modifiers.
+fn key_character(event: &winit::event::KeyEvent) -> Option<char> {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    let key = {
+        use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+        event.key_without_modifiers()
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let key = event.logical_key.clone();
+    match key {
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }
+        _ => None,
+    }
+}
+
+/// Parse a window's HTML into a document and style it, ready for layout.
+///
+/// `css_animations` carries on the animations of the document it replaces;
+/// they run on the animation clock. `mirrored_rows` gets the document's
+/// flipped right-to-left flex rows.
+pub(crate) fn build_document(
+    html: &str,
+    config: DocumentConfig,
+    overlay_scrollbars: bool,
+    animation_time: f64,
+    css_animations: &mut CssAnimations,
+    mirrored_rows: &mut MirroredRows,
+) -> (
+    Box<dyn Document>,
+    TextEffects,
+    Scrollbars,
+    ScrollChaining,
+    StickyElements,
+) {
+    // Portals' content goes where they point, before anything reads the tree
+    let html = hoist_portals(html);
+    let html = html.as_ref();
+    // Decorations the document painter can't draw are all painted by rinch
+    let paint_decorations = needs_decoration_painting(html);
+    let generated_css =
+        generated_styles(&split_styles(html).1, paint_decorations, overlay_scrollbars);
+    let doc: Box<dyn Document> = if generated_css.is_empty() {
+        Box::new(HtmlDocument::from_html(html, config))
+    } else {
+        let html = append_generated_styles(html, &generated_css);
+        Box::new(HtmlDocument::from_html(&html, config))
+    };
+
+    let (text_effects, scrollbars, scroll_chaining, sticky) = {
+        let mut inner = doc.inner_mut();
+        mirror_inline_styles(&mut inner);
+        css_animations.carry_on(&mut inner, clock::now());
+        inner.resolve(animation_time);
+
+        // Rewrites and animations that started over change the styles
+        let restarting = css_animations.styled(&mut inner, clock::now());
+        let rewritten = apply_text_rewrites(&mut inner);
+        let mirrored = mirrored_rows.mirror(&mut inner);
+        if restarting || rewritten || mirrored {
+            inner.resolve(animation_time);
+        }
+        if restarting {
+            css_animations.restarted(&mut inner);
+            inner.resolve(animation_time);
+        }
+        (
+            TextEffects::new(&inner, paint_decorations),
+            Scrollbars::new(&inner, overlay_scrollbars),
+            ScrollChaining::new(&inner),
+            StickyElements::new(&inner),
+        )
+    };
+    (doc, text_effects, scrollbars, scroll_chaining, sticky)
+}
+
+/// The generated stylesheet for a document with the app's `styles` (see
+/// [`append_generated_styles`]), empty if it needs none.
+fn generated_styles(
+    styles: &[String],
+    paint_decorations: bool,
+    overlay_scrollbars: bool,
+) -> String {
+    let mut css = String::new();
+    if paint_decorations {
+        css.push_str(DECORATION_RESET_CSS);
+    }
+    if overlay_scrollbars {
+        css.push_str(OVERLAY_SCROLLBAR_CSS);
+    }
+    // Copies of the app's rules in forms the style engine computes
+    for sheet in styles {
+        css.push_str(&mirrored_rules(sheet));
+        css.push_str(&scrollbar_rules(sheet));
+        css.push_str(&focus_rules(sheet));
+    }
+    css
+}
+
+/// Collect the app's `<style>` contents in document order.
+pub(crate) fn document_stylesheets(doc: &BaseDocument) -> Vec<(StyleSource, String)> {
+    let mut sheets = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if let Some(el) = node
+            .element_data()
+            .filter(|el| el.name.local.as_ref() == "style")
+            .filter(|el| {
+                !el.attrs()
+                    .iter()
+                    .any(|a| a.name.local.as_ref() == GENERATED_STYLE_ATTR)
+            })
+        {
+            let href = el
+                .attrs()
+                .iter()
+                .find(|a| a.name.local.as_ref() == INLINED_HREF_ATTR)
+                .map(|a| a.value.to_string());
+            sheets.push((
+                StyleSource::Stylesheet {
+                    index: sheets.len(),
+                    href,
+                },
+                node.text_content(),
+            ));
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    sheets
+}
+
+/// What's on the clipboard, for `onpaste` handlers.
+#[cfg(feature = "clipboard")]
+fn pasted() -> ClipboardEvent {
+    crate::clipboard::paste_event()
+}
+
+/// Nothing, without the `clipboard` feature to read the clipboard with.
+#[cfg(not(feature = "clipboard"))]
+fn pasted() -> ClipboardEvent {
+    ClipboardEvent::default()
+}
+
+/// Report watched elements' scroll, size, and visibility changes to the
+/// runtime.
+fn send_watch_changes(
+    proxy: &EventLoopProxy<RinchEvent>,
+    window_id: WindowId,
+    watch: &mut ScrollWatch,
+    doc: &BaseDocument,
+) {
+    for change in watch.changes(doc) {
+        let _ = proxy.send_event(RinchEvent::ElementScrolled {
+            window_id,
+            handler_id: change.handler_id,
+            node_ref: change.node_ref,
+            event: change.event,
+        });
+    }
+    for (node_ref, size) in watch.resizes(doc) {
+        let _ = proxy.send_event(RinchEvent::ElementResized { node_ref, size });
+    }
+    for (node_ref, rect) in watch.moves(doc) {
+        let _ = proxy.send_event(RinchEvent::ElementMoved { node_ref, rect });
+    }
+    for (node_ref, visibility) in watch.visibility_changes(doc) {
+        let _ = proxy.send_event(RinchEvent::ElementVisibility {
+            node_ref,
+            visibility,
+        });
+    }
+}
+
 /// Create a waker that sends poll events to the event loop.
 fn create_waker(proxy: &EventLoopProxy<RinchEvent>, id: WindowId) -> Waker {
     struct WakerHandle {
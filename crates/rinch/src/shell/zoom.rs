@@ -0,0 +1,99 @@
+//! Content scale (zoom) steps.
+//!
+//! A window's content scale multiplies the display's scale factor, as
+//! browser zoom does: at 1.5 a 16px font is drawn 24 logical pixels tall and
+//! the content lays out in two thirds of the width. Zooming in and out moves
+//! through the same preset levels browsers use, so repeated steps land on
+//! round percentages.
+
+use super::wheel::LINE_HEIGHT;
+
+/// Zoom levels that zooming in and out steps through.
+const LEVELS: [f32; 15] = [
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0,
+];
+
+/// Smallest content scale.
+pub(crate) const MIN_SCALE: f32 = LEVELS[0];
+/// Largest content scale.
+pub(crate) const MAX_SCALE: f32 = LEVELS[LEVELS.len() - 1];
+
+/// Ctrl+wheel movement, in logical pixels, that zooms one level.
+const WHEEL_STEP: f64 = LINE_HEIGHT;
+
+/// Limit a requested scale to what rinch supports; `None` for `NaN`.
+pub(crate) fn clamp_scale(scale: f32) -> Option<f32> {
+    (!scale.is_nan()).then(|| scale.clamp(MIN_SCALE, MAX_SCALE))
+}
+
+/// The level `steps` above `scale` (below, if negative). Scales between
+/// levels step to the nearest level in that direction.
+pub(crate) fn step_scale(scale: f32, steps: i32) -> f32 {
+    let mut scale = scale;
+    for _ in 0..steps.unsigned_abs() {
+        scale = if steps > 0 {
+            LEVELS.iter().copied().find(|&level| level > scale + 0.005)
+        } else {
+            LEVELS
+                .iter()
+                .rev()
+                .copied()
+                .find(|&level| level < scale - 0.005)
+        }
+        .unwrap_or(scale);
+    }
+    scale
+}
+
+/// Turns Ctrl+wheel movement into whole zoom steps.
+#[derive(Debug, Default)]
+pub(crate) struct WheelZoom {
+    /// Movement not yet turned into a step.
+    pending: f64,
+}
+
+impl WheelZoom {
+    /// Add a wheel delta (positive is away from the user) and return how
+    /// many levels to zoom in, or out if negative.
+    pub(crate) fn steps(&mut self, delta_y: f64) -> i32 {
+        // Turning the other way drops movement left from before
+        if self.pending * delta_y < 0.0 {
+            self.pending = 0.0;
+        }
+        self.pending += delta_y;
+        let steps = (self.pending / WHEEL_STEP).trunc();
+        self.pending -= steps * WHEEL_STEP;
+        steps as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_move_between_levels() {
+        assert_eq!(step_scale(1.0, 1), 1.1);
+        assert_eq!(step_scale(1.0, 2), 1.25);
+        assert_eq!(step_scale(1.0, -1), 0.9);
+        assert_eq!(step_scale(1.2, 1), 1.25);
+        assert_eq!(step_scale(1.2, -1), 1.1);
+        assert_eq!(step_scale(MAX_SCALE, 1), MAX_SCALE);
+        assert_eq!(step_scale(MIN_SCALE, -3), MIN_SCALE);
+        assert_eq!(clamp_scale(10.0), Some(MAX_SCALE));
+        assert_eq!(clamp_scale(f32::NAN), None);
+    }
+
+    #[test]
+    fn wheel_movement_adds_up_to_steps() {
+        let mut wheel = WheelZoom::default();
+        assert_eq!(wheel.steps(LINE_HEIGHT), 1);
+        assert_eq!(wheel.steps(-2.0 * LINE_HEIGHT), -2);
+        assert_eq!(wheel.steps(15.0), 0);
+        assert_eq!(wheel.steps(15.0), 0);
+        assert_eq!(wheel.steps(15.0), 1);
+        // A turn back starts over
+        assert_eq!(wheel.steps(-15.0), 0);
+        assert_eq!(wheel.steps(-30.0), -1);
+    }
+}
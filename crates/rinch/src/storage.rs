@@ -0,0 +1,382 @@
+//! Persistent key-value storage.
+//!
+//! [`use_storage`] keeps a value, such as a sidebar's width, between runs
+//! of the app. Values are stored as JSON, so any `serde` type works, in one
+//! file in the platform's config directory:
+//!
+//! - **Linux**: `$XDG_CONFIG_HOME/<app>/storage.json` (or `~/.config/...`)
+//! - **macOS**: `~/Library/Application Support/<app>/storage.json`
+//! - **Windows**: `%APPDATA%\<app>\storage.json`
+//!
+//! where `<app>` is the executable's name. [`set_storage_path`] moves it.
+//!
+//! Every handle to a key shares its value, so a change in one window
+//! re-renders the others reading it. Changes are written shortly after
+//! they're made (a drag that changes a value every frame writes once) to a
+//! temporary file that then replaces the old one, so a crash mid-write
+//! can't leave a half-written file. A file that can't be read is set aside
+//! as `storage.json.corrupt` rather than overwritten.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::storage::use_storage;
+//!
+//! fn app() -> Element {
+//!     let sidebar_width = use_storage("sidebar_width", 240.0);
+//!     rsx! {
+//!         div { style: format!("width: {}px", sidebar_width.get()) }
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use rinch_core::clock::{set_timeout, TimerId};
+use rinch_core::{untracked, Signal};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// How long after a change the file is written, to write once for a burst
+/// of changes.
+pub(crate) const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+thread_local! {
+    static STORE: RefCell<Option<Store>> = const { RefCell::new(None) };
+    /// Where [`set_storage_path`] put the file.
+    static PATH_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// The loaded file.
+struct Store {
+    path: Option<PathBuf>,
+    values: Map<String, Value>,
+    /// Changed whenever a key's value changes, for reactivity.
+    versions: HashMap<String, Signal<u64>>,
+    /// The pending write, if any.
+    save: Option<TimerId>,
+}
+
+impl Store {
+    fn load(path: Option<PathBuf>) -> Self {
+        let values = path.as_deref().map(read_values).unwrap_or_default();
+        Self {
+            path,
+            values,
+            versions: HashMap::new(),
+            save: None,
+        }
+    }
+
+    fn version(&mut self, key: &str) -> Signal<u64> {
+        self.versions
+            .entry(key.to_string())
+            .or_insert_with(|| Signal::new(0))
+            .clone()
+    }
+
+    /// Note a change to `key`, scheduling a write. Returns its version, to
+    /// bump once the store is released, since that re-runs readers.
+    fn changed(&mut self, key: &str) -> Signal<u64> {
+        if self.save.is_none() {
+            self.save = Some(set_timeout(SAVE_DELAY, || {
+                if let Err(e) = flush() {
+                    tracing::warn!("Couldn't save storage: {e}");
+                }
+            }));
+        }
+        self.version(key)
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_vec_pretty(&self.values).map_err(io::Error::other)?;
+        write_atomically(path, &contents)
+    }
+}
+
+/// Run `f` with the store, loading it first if need be.
+fn with_store<R>(f: impl FnOnce(&mut Store) -> R) -> R {
+    STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let store = store.get_or_insert_with(|| Store::load(storage_path()));
+        f(store)
+    })
+}
+
+/// A value kept between runs of the app, from [`use_storage`].
+///
+/// Handles are cheap to clone and share the value with every other handle
+/// to the same key.
+pub struct Stored<T> {
+    key: Rc<str>,
+    default: Rc<T>,
+}
+
+impl<T> Clone for Stored<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            default: self.default.clone(),
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Clone + 'static> Stored<T> {
+    /// The stored value, or the default if there's none (or it doesn't
+    /// fit `T`, such as after the app changed the type). Reactive: reading
+    /// it during render re-renders the app when it changes.
+    pub fn get(&self) -> T {
+        let (version, value) = with_store(|store| {
+            (
+                store.version(&self.key),
+                store.values.get(&*self.key).cloned(),
+            )
+        });
+        version.get();
+        value
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_else(|| (*self.default).clone())
+    }
+
+    /// Store a new value.
+    pub fn set(&self, value: T) {
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Couldn't store `{}`: {e}", self.key);
+                return;
+            }
+        };
+        let changed = with_store(|store| {
+            (store.values.get(&*self.key) != Some(&value)).then(|| {
+                store.values.insert(self.key.to_string(), value);
+                store.changed(&self.key)
+            })
+        });
+        bump(changed);
+    }
+
+    /// Change the stored value in place.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        let mut value = untracked(|| self.get());
+        f(&mut value);
+        self.set(value);
+    }
+
+    /// Forget the stored value, going back to the default.
+    pub fn reset(&self) {
+        let changed = with_store(|store| {
+            store
+                .values
+                .remove(&*self.key)
+                .map(|_| store.changed(&self.key))
+        });
+        bump(changed);
+    }
+}
+
+/// Re-render the readers of a changed key.
+fn bump(version: Option<Signal<u64>>) {
+    if let Some(version) = version {
+        version.update(|version| *version += 1);
+    }
+}
+
+/// A value stored under `key` between runs of the app, `default` until
+/// it's first set.
+///
+/// Unlike other hooks, it can be called anywhere, including conditionally
+/// and from event handlers: handles are found by key.
+pub fn use_storage<T: Serialize + DeserializeOwned + Clone + 'static>(
+    key: &str,
+    default: T,
+) -> Stored<T> {
+    Stored {
+        key: key.into(),
+        default: Rc::new(default),
+    }
+}
+
+/// Write pending changes now, rather than after the usual short delay.
+/// The runtime does this when the app exits.
+pub fn flush() -> io::Result<()> {
+    STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let Some(store) = store.as_mut() else {
+            return Ok(());
+        };
+        if let Some(timer) = store.save.take() {
+            rinch_core::clear_timeout(timer);
+            store.write()?;
+        }
+        Ok(())
+    })
+}
+
+/// Keep storage in `path` instead of the platform's config directory, such
+/// as for a portable install or a test. Pending changes are written to the
+/// old file first, and values are read from the new one.
+pub fn set_storage_path(path: impl Into<PathBuf>) {
+    if let Err(e) = flush() {
+        tracing::warn!("Couldn't save storage: {e}");
+    }
+    PATH_OVERRIDE.with(|current| *current.borrow_mut() = Some(path.into()));
+    let versions: Vec<_> = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let versions = store.take().map(|store| store.versions).unwrap_or_default();
+        let mut reloaded = Store::load(storage_path());
+        reloaded.versions = versions;
+        let versions = reloaded.versions.values().cloned().collect();
+        *store = Some(reloaded);
+        versions
+    });
+    // Readers of the old values re-render with the new ones
+    for version in versions {
+        bump(Some(version));
+    }
+}
+
+/// Where storage lives.
+fn storage_path() -> Option<PathBuf> {
+    if let Some(path) = PATH_OVERRIDE.with(|path| path.borrow().clone()) {
+        return Some(path);
+    }
+    Some(app_config_dir()?.join("storage.json"))
+}
+
+/// The app's directory in the platform's config directory, named after
+/// its executable.
+pub(crate) fn app_config_dir() -> Option<PathBuf> {
+    let app = std::env::current_exe()
+        .ok()?
+        .file_stem()?
+        .to_string_lossy()
+        .into_owned();
+    Some(config_dir()?.join(app))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+}
+
+#[cfg(windows)]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn config_dir() -> Option<PathBuf> {
+    None
+}
+
+/// The values in the file at `path`, or none if there's no file. A file
+/// that isn't a JSON object is moved aside so it isn't overwritten.
+fn read_values(path: &Path) -> Map<String, Value> {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                tracing::warn!("Couldn't read storage from {}: {e}", path.display());
+            }
+            return Map::new();
+        }
+    };
+    match serde_json::from_slice(&contents) {
+        Ok(values) => values,
+        Err(e) => {
+            let aside = path.with_extension("json.corrupt");
+            tracing::warn!(
+                "Storage in {} is unreadable ({e}); moving it to {}",
+                path.display(),
+                aside.display()
+            );
+            if let Err(e) = std::fs::rename(path, &aside) {
+                tracing::warn!("Couldn't move unreadable storage aside: {e}");
+            }
+            Map::new()
+        }
+    }
+}
+
+/// Write `contents` to `path` through a temporary file, so `path` always
+/// holds either the old contents or the new.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::Clock;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rinch-storage-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("storage.json")
+    }
+
+    #[test]
+    fn values_are_shared_and_saved_after_a_delay() {
+        let clock = Clock::manual();
+        let path = temp_path("save");
+        set_storage_path(&path);
+
+        let width = use_storage("sidebar_width", 240.0);
+        assert_eq!(width.get(), 240.0);
+        width.set(300.0);
+        use_storage("sidebar_width", 0.0).update(|width| *width += 20.0);
+        assert_eq!(width.get(), 320.0);
+        assert!(!path.exists());
+
+        clock.advance(SAVE_DELAY);
+        let saved: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved["sidebar_width"], 320.0);
+
+        // A fresh load reads it back
+        set_storage_path(&path);
+        assert_eq!(use_storage("sidebar_width", 0.0).get(), 320.0);
+        width.reset();
+        assert_eq!(width.get(), 240.0);
+        flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+    }
+
+    #[test]
+    fn unreadable_files_are_set_aside() {
+        let path = temp_path("corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{\"theme\": \"da").unwrap();
+        set_storage_path(&path);
+
+        assert_eq!(use_storage("theme", "light".to_string()).get(), "light");
+        assert!(!path.exists());
+        assert!(path.with_extension("json.corrupt").exists());
+    }
+}
@@ -0,0 +1,653 @@
+//! SVG export.
+//!
+//! [`export_svg`] turns the element a [`NodeRef`] is attached to into an SVG
+//! document, so charts and diagrams built in rinch can go into design tools
+//! and documents at vector quality:
+//!
+//! ```ignore
+//! use rinch::svg::export_svg;
+//!
+//! let chart = use_node_ref();
+//! let save = {
+//!     let chart = chart.clone();
+//!     move || {
+//!         if let Some(svg) = export_svg(&chart) {
+//!             let _ = std::fs::write("chart.svg", svg);
+//!         }
+//!     }
+//! };
+//! rsx! {
+//!     div { node_ref: chart, class: "chart", /* ... */ }
+//!     button { onclick: save, "Export" }
+//! }
+//! ```
+//!
+//! The element is laid out again at its window's size and written out box
+//! by box: backgrounds (colors and linear gradients) and borders as
+//! rectangles with their corner radius, text as `<text>` elements stretched
+//! to their laid-out width, `opacity` and `overflow` clipping as groups, and
+//! inline `<svg>` elements as they are. Images, shadows, text decorations,
+//! transforms and other gradients are left out.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use blitz_dom::BaseDocument;
+use parley::PositionedLayoutItem;
+use peniko::{Brush, Color};
+use rinch_core::NodeRef;
+
+use crate::shell::computed::{computed_value, computed_values};
+use crate::shell::relayout::{layout, window_with_ref};
+use crate::shell::scroll::{border_box_origin, ScrollWatch};
+use crate::shell::selection::content_origin;
+use crate::shell::text_effects::{element_of, split_outside_parens};
+
+/// Elements that are never painted.
+const UNPAINTED: &[&str] = &[
+    "head", "script", "style", "title", "meta", "link", "template",
+];
+
+/// The properties an element's box is written from.
+const BOX_PROPERTIES: &[&str] = &[
+    "display",
+    "visibility",
+    "opacity",
+    "overflow-x",
+    "overflow-y",
+    "border-top-left-radius",
+    "background-image",
+    "background-color",
+    "border-top-color",
+];
+
+/// The element `node_ref` is attached to as an SVG document, or `None` if
+/// it isn't rendered in any window.
+pub fn export_svg(node_ref: &NodeRef) -> Option<String> {
+    let (props, html) = window_with_ref(node_ref.id())?;
+    let (doc, _) = layout(&props, &html, props.width as f64, props.height as f64, 1.0);
+    let inner = doc.inner();
+    let node_id = ScrollWatch::new(&inner).node_for_ref(node_ref.id())?;
+    let node = inner.get_node(node_id)?;
+    let (x, y) = border_box_origin(&inner, node_id);
+    let (width, height) = (
+        node.final_layout.size.width as f64,
+        node.final_layout.size.height as f64,
+    );
+
+    let mut writer = SvgWriter {
+        doc: &inner,
+        defs: String::new(),
+        body: String::new(),
+        next_id: 0,
+    };
+    writer.element(node_id, false);
+
+    // The view box puts the element's corner at the origin
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+        number(width),
+        number(height),
+        number(x),
+        number(y),
+        number(width),
+        number(height)
+    );
+    if !writer.defs.is_empty() {
+        let _ = write!(svg, "<defs>\n{}</defs>\n", writer.defs);
+    }
+    svg.push_str(&writer.body);
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+/// Writes a document's boxes and text as SVG.
+struct SvgWriter<'a> {
+    doc: &'a BaseDocument,
+    defs: String,
+    body: String,
+    next_id: usize,
+}
+
+impl SvgWriter<'_> {
+    /// Write an element and everything inside it. Inline elements (inside
+    /// text) have no box of their own; their text is written with their
+    /// inline root's.
+    fn element(&mut self, node_id: usize, inline: bool) {
+        let doc = self.doc;
+        let Some(node) = doc.get_node(node_id) else {
+            return;
+        };
+        let Some(el) = node.element_data() else {
+            return;
+        };
+        let tag = el.name.local.as_ref();
+        if UNPAINTED.contains(&tag) {
+            return;
+        }
+
+        let values: HashMap<String, String> = computed_values(doc, node_id, BOX_PROPERTIES)
+            .into_iter()
+            .collect();
+        let value = |property: &str| values.get(property).map(String::as_str);
+        if value("display") == Some("none") || value("visibility") == Some("hidden") {
+            return;
+        }
+        let inline = inline && value("display").is_none_or(|display| display == "inline");
+
+        let (x, y) = border_box_origin(doc, node_id);
+        let layout = &node.final_layout;
+        let (width, height) = (layout.size.width as f64, layout.size.height as f64);
+        let radius = value("border-top-left-radius")
+            .and_then(|radius| corner_radius(radius, width.min(height)))
+            .unwrap_or(0.0);
+
+        // Groups for opacity and clipping
+        let mut groups = 0;
+        if let Some(opacity) = value("opacity")
+            .and_then(|opacity| opacity.parse::<f64>().ok())
+            .filter(|opacity| *opacity < 1.0)
+        {
+            let _ = writeln!(self.body, "<g opacity=\"{}\">", number(opacity));
+            groups += 1;
+        }
+        let clips = ["overflow-x", "overflow-y"]
+            .into_iter()
+            .any(|property| value(property).is_some_and(|overflow| overflow != "visible"));
+        if clips && !inline {
+            let id = self.new_id("clip");
+            let border = &layout.border;
+            let _ = writeln!(
+                self.defs,
+                "<clipPath id=\"{id}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/></clipPath>",
+                number(x + border.left as f64),
+                number(y + border.top as f64),
+                number(width - (border.left + border.right) as f64),
+                number(height - (border.top + border.bottom) as f64),
+                number(radius)
+            );
+            let _ = writeln!(self.body, "<g clip-path=\"url(#{id})\">");
+            groups += 1;
+        }
+
+        if tag == "svg" {
+            self.inline_svg(node_id, (x, y), (width, height));
+        } else {
+            if !inline && width > 0.0 && height > 0.0 {
+                self.background(node_id, &values, (x, y, width, height), radius);
+                self.border(node_id, &values, (x, y, width, height), radius);
+            }
+            let inline_root = el.inline_layout_data.is_some();
+            if inline_root {
+                self.text(node_id);
+            }
+            for &child in &node.children {
+                self.element(child, inline_root || inline);
+            }
+        }
+
+        for _ in 0..groups {
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    /// Write an element's background color or gradient.
+    fn background(
+        &mut self,
+        node_id: usize,
+        values: &HashMap<String, String>,
+        (x, y, width, height): (f64, f64, f64, f64),
+        radius: f64,
+    ) {
+        let background = values
+            .get("background-image")
+            .filter(|image| image.contains("gradient("))
+            .or_else(|| values.get("background-color"));
+        let Some(background) = background else {
+            return;
+        };
+
+        let fill = if let Some(gradient) = split_outside_parens(background, ' ')
+            .into_iter()
+            .find_map(LinearGradient::parse)
+        {
+            let id = self.new_id("gradient");
+            let ((x1, y1), (x2, y2)) = gradient.endpoints();
+            let _ = writeln!(
+                self.defs,
+                "<linearGradient id=\"{id}\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\">",
+                number(x1),
+                number(y1),
+                number(x2),
+                number(y2)
+            );
+            for (color, offset) in gradient.stops() {
+                let Some(color) = self.color(node_id, &color) else {
+                    continue;
+                };
+                let (color, opacity) = svg_color(color);
+                let _ = writeln!(
+                    self.defs,
+                    "<stop offset=\"{}\" stop-color=\"{color}\" stop-opacity=\"{}\"/>",
+                    number(offset),
+                    number(opacity)
+                );
+            }
+            self.defs.push_str("</linearGradient>\n");
+            format!("url(#{id})")
+        } else {
+            let Some(color) = self.color(node_id, background) else {
+                return;
+            };
+            let (color, opacity) = svg_color(color);
+            if opacity == 0.0 {
+                return;
+            }
+            format!("{color}\" fill-opacity=\"{}", number(opacity))
+        };
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{fill}\"/>",
+            number(x),
+            number(y),
+            number(width),
+            number(height),
+            number(radius)
+        );
+    }
+
+    /// Write an element's border in one color, from its laid-out widths.
+    fn border(
+        &mut self,
+        node_id: usize,
+        values: &HashMap<String, String>,
+        (x, y, width, height): (f64, f64, f64, f64),
+        radius: f64,
+    ) {
+        let Some(node) = self.doc.get_node(node_id) else {
+            return;
+        };
+        let border = node.final_layout.border;
+        let widths = [border.top, border.right, border.bottom, border.left].map(|w| w as f64);
+        if widths.iter().all(|w| *w <= 0.0) {
+            return;
+        }
+        let color = values
+            .get("border-top-color")
+            .and_then(|value| self.color(node_id, value));
+        let Some(color) = color else {
+            return;
+        };
+        let (color, opacity) = svg_color(color);
+
+        let [top, right, bottom, left] = widths;
+        if widths.iter().all(|w| *w == top) {
+            // A stroke centered inside the border box
+            let _ = writeln!(
+                self.body,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-opacity=\"{}\" stroke-width=\"{}\"/>",
+                number(x + top / 2.0),
+                number(y + top / 2.0),
+                number(width - top),
+                number(height - top),
+                number((radius - top / 2.0).max(0.0)),
+                number(opacity),
+                number(top)
+            );
+            return;
+        }
+        // Uneven borders are a rectangle per side, without rounding
+        let sides = [
+            (x, y, width, top),
+            (x + width - right, y, right, height),
+            (x, y + height - bottom, width, bottom),
+            (x, y, left, height),
+        ];
+        for (x, y, width, height) in sides {
+            if width > 0.0 && height > 0.0 {
+                let _ = writeln!(
+                    self.body,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{color}\" fill-opacity=\"{}\"/>",
+                    number(x),
+                    number(y),
+                    number(width),
+                    number(height),
+                    number(opacity)
+                );
+            }
+        }
+    }
+
+    /// Write an inline root's text, a `<text>` per glyph run.
+    fn text(&mut self, node_id: usize) {
+        let doc = self.doc;
+        let Some(text) = doc
+            .get_node(node_id)
+            .and_then(|node| node.element_data())
+            .and_then(|el| el.inline_layout_data.as_ref())
+        else {
+            return;
+        };
+        let (origin_x, origin_y) = content_origin(doc, node_id);
+
+        for line in text.layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+                let run = glyph_run.run();
+                let Some(content) = text.text.get(run.text_range()) else {
+                    continue;
+                };
+                if content.trim().is_empty() {
+                    continue;
+                }
+                let brush = &glyph_run.style().brush;
+                let color = match &brush.brush {
+                    Brush::Solid(color) => *color,
+                    _ => Color::BLACK,
+                };
+                let (color, opacity) = svg_color(color);
+                let element = element_of(doc, brush.id).unwrap_or(node_id);
+
+                let _ = write!(
+                    self.body,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{color}\"",
+                    number(origin_x + glyph_run.offset() as f64),
+                    number(origin_y + glyph_run.baseline() as f64),
+                    number(run.font_size() as f64)
+                );
+                if opacity < 1.0 {
+                    let _ = write!(self.body, " fill-opacity=\"{}\"", number(opacity));
+                }
+                for (attribute, property) in [
+                    ("font-family", "font-family"),
+                    ("font-weight", "font-weight"),
+                    ("font-style", "font-style"),
+                ] {
+                    if let Some(value) = computed_value(doc, element, property) {
+                        let _ = write!(self.body, " {attribute}=\"{}\"", escape(&value));
+                    }
+                }
+                // Stretch to the laid-out width, in case the viewer picks
+                // different fonts
+                let _ = writeln!(
+                    self.body,
+                    " textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\">{}</text>",
+                    number(glyph_run.advance() as f64),
+                    escape(content)
+                );
+            }
+        }
+    }
+
+    /// Copy an inline `<svg>` element, placed at its laid-out box.
+    fn inline_svg(&mut self, node_id: usize, (x, y): (f64, f64), (width, height): (f64, f64)) {
+        let Some(node) = self.doc.get_node(node_id) else {
+            return;
+        };
+        let Some(el) = node.element_data() else {
+            return;
+        };
+        let _ = write!(
+            self.body,
+            "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+            number(x),
+            number(y),
+            number(width),
+            number(height)
+        );
+        for attr in el.attrs() {
+            let name = attr.name.local.as_ref();
+            if !matches!(
+                name,
+                "x" | "y" | "width" | "height" | "xmlns" | "style" | "class"
+            ) && !name.starts_with("data-")
+            {
+                let _ = write!(self.body, " {name}=\"{}\"", escape(&attr.value));
+            }
+        }
+        self.body.push('>');
+        for &child in &node.children {
+            self.markup(child);
+        }
+        self.body.push_str("</svg>\n");
+    }
+
+    /// Copy a node inside an inline `<svg>` as it is.
+    fn markup(&mut self, node_id: usize) {
+        let Some(node) = self.doc.get_node(node_id) else {
+            return;
+        };
+        if let Some(text) = node.text_data() {
+            self.body.push_str(&escape(&text.content));
+            return;
+        }
+        let Some(el) = node.element_data() else {
+            return;
+        };
+        let tag = el.name.local.as_ref();
+        let _ = write!(self.body, "<{tag}");
+        for attr in el.attrs() {
+            let _ = write!(
+                self.body,
+                " {}=\"{}\"",
+                attr.name.local.as_ref(),
+                escape(&attr.value)
+            );
+        }
+        self.body.push('>');
+        for &child in &node.children {
+            self.markup(child);
+        }
+        let _ = write!(self.body, "</{tag}>");
+    }
+
+    /// A computed CSS color, with `currentColor` resolved.
+    fn color(&self, node_id: usize, value: &str) -> Option<Color> {
+        let value = if value.eq_ignore_ascii_case("currentcolor") {
+            computed_value(self.doc, node_id, "color").unwrap_or_else(|| "black".to_string())
+        } else {
+            value.to_string()
+        };
+        peniko::color::parse_color(&value)
+            .ok()
+            .map(|color| color.to_alpha_color())
+    }
+
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("rinch-{prefix}-{}", self.next_id)
+    }
+}
+
+/// A `linear-gradient()`.
+#[derive(Debug, Clone, PartialEq)]
+struct LinearGradient {
+    /// Direction in degrees, clockwise from up.
+    angle: f64,
+    /// Colors, with their positions as fractions where given.
+    stops: Vec<(String, Option<f64>)>,
+}
+
+impl LinearGradient {
+    fn parse(value: &str) -> Option<Self> {
+        let arguments = value
+            .trim()
+            .strip_prefix("linear-gradient(")?
+            .strip_suffix(')')?;
+        let mut parts = split_outside_parens(arguments, ',').into_iter().peekable();
+        let first = *parts.peek()?;
+        let angle = if let Some(degrees) = first.strip_suffix("deg") {
+            degrees.trim().parse().ok()
+        } else if let Some(turns) = first.strip_suffix("turn") {
+            turns.trim().parse::<f64>().ok().map(|turns| turns * 360.0)
+        } else if let Some(side) = first.strip_prefix("to ") {
+            let side: Vec<&str> = side.split_whitespace().collect();
+            let horizontal = if side.contains(&"right") {
+                90.0
+            } else if side.contains(&"left") {
+                -90.0
+            } else {
+                0.0
+            };
+            Some(match (side.contains(&"top"), side.contains(&"bottom")) {
+                (true, _) => horizontal / 2.0,
+                (_, true) => 180.0 - horizontal / 2.0,
+                _ => horizontal,
+            })
+        } else {
+            None
+        };
+        if angle.is_some() {
+            parts.next();
+        }
+
+        let stops: Vec<(String, Option<f64>)> = parts
+            .map(|stop| {
+                let pieces = split_outside_parens(stop, ' ');
+                let position = pieces
+                    .last()
+                    .and_then(|last| last.strip_suffix('%'))
+                    .and_then(|percent| percent.parse::<f64>().ok())
+                    .map(|percent| percent / 100.0);
+                let color = if position.is_some() {
+                    pieces[..pieces.len() - 1].join(" ")
+                } else {
+                    stop.to_string()
+                };
+                (color, position)
+            })
+            .collect();
+        (stops.len() >= 2).then_some(Self {
+            angle: angle.unwrap_or(180.0),
+            stops,
+        })
+    }
+
+    /// Start and end points as fractions of the box.
+    fn endpoints(&self) -> ((f64, f64), (f64, f64)) {
+        let radians = self.angle.to_radians();
+        let (dx, dy) = (radians.sin() / 2.0, -radians.cos() / 2.0);
+        ((0.5 - dx, 0.5 - dy), (0.5 + dx, 0.5 + dy))
+    }
+
+    /// Each stop with a position, spreading out those without one.
+    fn stops(&self) -> Vec<(String, f64)> {
+        let last = self.stops.len() - 1;
+        let mut positions: Vec<Option<f64>> =
+            self.stops.iter().map(|(_, position)| *position).collect();
+        positions[0] = positions[0].or(Some(0.0));
+        positions[last] = positions[last].or(Some(1.0));
+        // Each run of stops without positions is spaced evenly between the
+        // positioned stops around it
+        let mut i = 0;
+        while i < last {
+            let next = (i + 1..=last)
+                .find(|&j| positions[j].is_some())
+                .unwrap_or(last);
+            let (start, end) = (positions[i].unwrap_or(0.0), positions[next].unwrap_or(1.0));
+            for (step, position) in positions[i + 1..next].iter_mut().enumerate() {
+                *position = Some(start + (end - start) * (step + 1) as f64 / (next - i) as f64);
+            }
+            i = next;
+        }
+        self.stops
+            .iter()
+            .zip(positions)
+            .map(|((color, _), position)| (color.clone(), position.unwrap_or(1.0)))
+            .collect()
+    }
+}
+
+/// The first length of a corner's radius, in pixels.
+fn corner_radius(value: &str, size: f64) -> Option<f64> {
+    let first = value.split_whitespace().next()?;
+    let number = |suffix: &str| first.strip_suffix(suffix)?.parse::<f64>().ok();
+    number("px")
+        .or_else(|| number("rem").map(|rem| rem * 16.0))
+        .or_else(|| number("em").map(|em| em * 16.0))
+        .or_else(|| number("%").map(|percent| (percent / 100.0 * size).min(size / 2.0)))
+        .or_else(|| first.parse().ok())
+        .map(|radius: f64| radius.min(size / 2.0))
+}
+
+/// A color as an SVG color and opacity.
+fn svg_color(color: Color) -> (String, f64) {
+    let rgba = color.to_rgba8();
+    (
+        format!("#{:02x}{:02x}{:02x}", rgba.r, rgba.g, rgba.b),
+        rgba.a as f64 / 255.0,
+    )
+}
+
+/// A number with no more than two decimals and no trailing zeros.
+fn number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+/// Escape text for XML content and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradients_parse_to_svg_terms() {
+        let gradient =
+            LinearGradient::parse("linear-gradient(to right, #fff, rgb(0, 0, 0) 50%, red)")
+                .unwrap();
+        assert_eq!(gradient.angle, 90.0);
+        assert_eq!(
+            gradient.stops(),
+            vec![
+                ("#fff".to_string(), 0.0),
+                ("rgb(0, 0, 0)".to_string(), 0.5),
+                ("red".to_string(), 1.0)
+            ]
+        );
+        let ((x1, y1), (x2, y2)) = gradient.endpoints();
+        assert!((x1 - 0.0).abs() < 1e-9 && (y1 - 0.5).abs() < 1e-9);
+        assert!((x2 - 1.0).abs() < 1e-9 && (y2 - 0.5).abs() < 1e-9);
+
+        // Top to bottom by default, with stops spread evenly
+        let gradient = LinearGradient::parse("linear-gradient(red, green, blue, white)").unwrap();
+        assert_eq!(gradient.angle, 180.0);
+        let offsets: Vec<String> = gradient
+            .stops()
+            .iter()
+            .map(|(_, offset)| number(*offset))
+            .collect();
+        assert_eq!(offsets, ["0", "0.33", "0.67", "1"]);
+
+        assert_eq!(
+            LinearGradient::parse("linear-gradient(45deg, red, blue)")
+                .unwrap()
+                .angle,
+            45.0
+        );
+        assert!(LinearGradient::parse("radial-gradient(red, blue)").is_none());
+        assert!(LinearGradient::parse("linear-gradient(red)").is_none());
+    }
+
+    #[test]
+    fn text_is_escaped_and_numbers_are_short() {
+        assert_eq!(escape("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(1.0 / 3.0), "0.33");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(corner_radius("50%", 40.0), Some(20.0));
+        assert_eq!(corner_radius("6px 2px", 40.0), Some(6.0));
+    }
+}
@@ -0,0 +1,1091 @@
+//! Headless test harness.
+//!
+//! [`TestApp`] runs an app function without a display or GPU: the first
+//! window's content is parsed and laid out by blitz exactly as in a real
+//! window, but nothing is painted unless a test asks for a screenshot (see
+//! the `visual-testing` feature). [`TestApp::mount`] does the same for a
+//! single component. Tests find elements by their text, class, CSS
+//! selector or node ref, send synthetic clicks, key presses, menu
+//! shortcuts, drags, scrolls, pastes and deep links, and move the clock
+//! forward frame by frame, firing timers and animation frames.
+//! The app re-renders after each handled event, as it does in the runtime,
+//! and `spawn_local` tasks run whenever they're woken.
+//! Screen reader announcements are collected instead of spoken.
+//!
+//! [`TestApp::with_size`] does the same at a given window size, and
+//! `render_headless` (`visual-testing` feature) returns the frame it paints
+//! as RGBA pixels.
+//!
+//! Hooks, handlers and the animation clock are thread-local, so each test
+//! should own one `TestApp` at a time (Rust runs each test on its own
+//! thread).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use rinch::testing::TestApp;
+//!
+//! #[test]
+//! fn counter_increments() {
+//!     let mut app = TestApp::new(counter_app);
+//!     let button = app.find_by_text("Increment").unwrap();
+//!     assert!(app.click(button));
+//!     assert!(app.find_by_text("Count: 1").is_some());
+//! }
+//! ```
+
+#[cfg(feature = "visual-testing")]
+mod visual;
+
+#[cfg(feature = "visual-testing")]
+pub use visual::{render_headless, DiffOptions, Screenshot, ScreenshotDiff};
+
+use std::time::{Duration, Instant};
+
+use blitz_dom::{BaseDocument, Document, DocumentConfig};
+use blitz_traits::shell::{ColorScheme, Viewport};
+use rinch_core::a11y::{self, take_announcements, Announcement};
+use rinch_core::animation::{animation_frame_requested, run_animation_frame, FRAME_INTERVAL};
+use rinch_core::clock::Clock;
+use rinch_core::deep_link::push_deep_link;
+use rinch_core::element::{children_to_html, Element, MenuItemCallback, Shortcut, WindowProps};
+use rinch_core::events::{
+    clear_handlers, dispatch_clipboard_event, dispatch_drag_event, dispatch_event,
+    dispatch_input_event, dispatch_key_event, dispatch_scroll_event, ClipboardEvent,
+    EventHandlerId, KeyboardEvent,
+};
+use rinch_core::hooks::{begin_render, clear_hooks, end_render, run_effects};
+use rinch_core::node_ref::{
+    take_focus_requests, update_node_ref_rect, update_node_ref_scroll, update_node_ref_size,
+    update_node_ref_visibility, FocusRequest, NodeRef, NODE_REF_ATTR,
+};
+use rinch_core::power::{record_user_input, set_power_status, PowerStatus};
+use rinch_core::task::poll_tasks;
+use winit::keyboard::KeyCode;
+
+use crate::fonts::font_context;
+use crate::menu::{matching_hotkeys, parse_menu_shortcut, parse_shortcut_for_matching, KeyPress};
+use crate::shell::cascade::matches_selector_list;
+use crate::shell::computed::computed_value;
+use crate::shell::css_animations::CssAnimations;
+use crate::shell::drag::{DragHandlers, ElementDrag};
+use crate::shell::focus::Focus;
+use crate::shell::keyboard::{
+    bubbling_handlers, clipboard_attr, handler, key_target, press_event, types_text, BLUR_ATTR,
+    COPY_ATTR, CUT_ATTR, FOCUS_ATTR, KEY_DOWN_ATTR, KEY_PRESS_ATTR, KEY_UP_ATTR, PASTE_ATTR,
+};
+use crate::shell::live_regions::LiveRegions;
+use crate::shell::rtl::MirroredRows;
+use crate::shell::scroll::{border_box_origin, scroll_state, ScrollSnapshot, ScrollWatch};
+use crate::shell::selection::{match_chain, TextSelection};
+use crate::shell::stylesheets::{inline_local_stylesheets, split_styles, user_agent_stylesheets};
+use crate::shell::text_effects::TextEffects;
+use crate::shell::text_input::{field_value, set_caret, FieldHandlers};
+use crate::shell::window_manager::build_document;
+use crate::shell::zoom::clamp_scale;
+
+/// Computed properties layout snapshots show, with the values they leave
+/// out as the defaults.
+const SNAPSHOT_PROPERTIES: [(&str, &[&str]); 8] = [
+    ("display", &["block", "inline"]),
+    ("position", &["static"]),
+    ("overflow-x", &["visible"]),
+    ("overflow-y", &["visible"]),
+    ("visibility", &["visible"]),
+    ("opacity", &["1"]),
+    ("flex-direction", &["row"]),
+    ("z-index", &["auto"]),
+];
+
+/// Elements layout snapshots leave out, with their contents.
+const SNAPSHOT_SKIPPED: [&str; 3] = ["head", "style", "script"];
+
+/// Most times the app re-renders in a row because layout changed a node
+/// ref, before the harness gives up waiting for it to settle.
+const MAX_SETTLE_RENDERS: usize = 10;
+
+/// An element's border box, in window coordinates (logical pixels).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ElementRect {
+    /// The middle of the box, where [`TestApp::click_at`] would hit it.
+    pub fn center(&self) -> (f32, f32) {
+        (
+            (self.x + self.width / 2.0) as f32,
+            (self.y + self.height / 2.0) as f32,
+        )
+    }
+}
+
+/// An app running headless, for tests.
+pub struct TestApp {
+    app: Box<dyn Fn() -> Element>,
+    props: WindowProps,
+    /// The `AppMenu` from the latest render, for shortcuts.
+    menu: Option<Element>,
+    /// The window's HTML from the latest render.
+    html: String,
+    doc: Box<dyn Document>,
+    /// Decorations rinch paints itself, for screenshots.
+    #[cfg_attr(not(feature = "visual-testing"), allow(dead_code))]
+    text_effects: TextEffects,
+    watch: ScrollWatch,
+    live_regions: LiveRegions,
+    focus: Focus,
+    selection: TextSelection,
+    /// The app's clock, stopped except while advancing, and when the
+    /// harness started.
+    clock: Clock,
+    start: Instant,
+}
+
+impl TestApp {
+    /// Render `app` and lay out its first window at the size it asks for.
+    ///
+    /// # Panics
+    ///
+    /// If the app doesn't render a `Window`.
+    pub fn new(app: impl Fn() -> Element + 'static) -> Self {
+        Self::start(app, None)
+    }
+
+    /// Render `component` alone in a window `width` by `height`, to test it
+    /// apart from the app that uses it.
+    pub fn mount(component: impl Fn() -> Element + 'static, width: u32, height: u32) -> Self {
+        let props = WindowProps {
+            width,
+            height,
+            ..WindowProps::default()
+        };
+        Self::start(
+            move || Element::Window(props.clone(), vec![component()]),
+            None,
+        )
+    }
+
+    /// Render `app` and lay out its first window at `width` by `height`
+    /// logical pixels, whatever size it asks for, e.g. to test a layout on
+    /// a small screen.
+    ///
+    /// # Panics
+    ///
+    /// If the app doesn't render a `Window`.
+    pub fn with_size(app: impl Fn() -> Element + 'static, width: u32, height: u32) -> Self {
+        Self::start(app, Some((width, height)))
+    }
+
+    /// Render `app` and lay out its first window at `size`, whatever size
+    /// the window asks for.
+    fn start(app: impl Fn() -> Element + 'static, size: Option<(u32, u32)>) -> Self {
+        clear_handlers();
+        clear_hooks();
+        take_announcements();
+        let clock = Clock::manual();
+        let start = clock.now();
+
+        let (window, menu) = render(&app);
+        let (mut props, html) = window.expect("the app didn't render a Window");
+        if let Some((width, height)) = size {
+            props.width = width;
+            props.height = height;
+        }
+        let (doc, text_effects) = layout(&html, &props, 0.0);
+        let watch = ScrollWatch::new(&doc.inner());
+        let live_regions = LiveRegions::new(&doc.inner());
+        let mut test = Self {
+            app: Box::new(app),
+            props,
+            menu,
+            html,
+            doc,
+            text_effects,
+            watch,
+            live_regions,
+            focus: Focus::default(),
+            selection: TextSelection::default(),
+            clock,
+            start,
+        };
+        test.settle();
+        test
+    }
+
+    /// Resize the window, laying it out again.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.props.width = width;
+        self.props.height = height;
+        self.rebuild();
+        self.settle();
+    }
+
+    /// Zoom the window's content, as Ctrl+Plus and Ctrl+Minus do, laying it
+    /// out again. Points passed to [`click_at`](Self::click_at) and
+    /// [`drag`](Self::drag) stay in window coordinates.
+    pub fn set_content_scale(&mut self, scale: f32) {
+        self.props.content_scale = scale;
+        self.rebuild();
+        self.settle();
+    }
+
+    /// Re-run the app function, as the runtime does after an event. Call it
+    /// after changing signals from the test itself.
+    pub fn render(&mut self) {
+        self.render_once();
+        self.settle();
+    }
+
+    /// The window's HTML from the latest render.
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    /// The time the app sees.
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// The focused element, if any.
+    pub fn focused(&self) -> Option<usize> {
+        self.focus.node()
+    }
+
+    /// Whether focus is visible, as after Tab: the focused element matches
+    /// `:focus-visible` and gets a focus ring unless it styles its own.
+    pub fn focus_visible(&self) -> bool {
+        self.focus.is_visible()
+    }
+
+    /// The screen reader announcements made since the last call: messages
+    /// passed to `announce`, and live regions whose text changed.
+    pub fn announcements(&mut self) -> Vec<Announcement> {
+        take_announcements()
+    }
+
+    /// The innermost element whose text, with whitespace collapsed, is
+    /// `text`.
+    pub fn find_by_text(&self, text: &str) -> Option<usize> {
+        let doc = self.doc.inner();
+        let matches = |node_id: usize| {
+            doc.get_node(node_id).is_some_and(|node| {
+                node.element_data().is_some() && collapse(&node.text_content()) == text
+            })
+        };
+        let mut found = elements(&doc).into_iter().find(|&id| matches(id))?;
+        // Descend while a child has the same text
+        while let Some(child) = doc
+            .get_node(found)
+            .and_then(|node| node.children.iter().copied().find(|&id| matches(id)))
+        {
+            found = child;
+        }
+        Some(found)
+    }
+
+    /// The first element matching a CSS selector, such as
+    /// `"form .actions > button[type=submit]"`. Selectors are matched as
+    /// stylesheets are, without pseudo-classes other than `:root`.
+    pub fn find_by_selector(&self, selector: &str) -> Option<usize> {
+        self.find_all_by_selector(selector).into_iter().next()
+    }
+
+    /// Every element matching a CSS selector, in document order.
+    pub fn find_all_by_selector(&self, selector: &str) -> Vec<usize> {
+        let doc = self.doc.inner();
+        elements(&doc)
+            .into_iter()
+            .filter(|&id| matches_selector_list(selector, &match_chain(&doc, id)))
+            .collect()
+    }
+
+    /// The element a node ref is attached to.
+    pub fn find_by_ref(&self, node_ref: &NodeRef) -> Option<usize> {
+        let id = node_ref.id().to_string();
+        let doc = self.doc.inner();
+        elements(&doc)
+            .into_iter()
+            .find(|&e| attribute(&doc, e, NODE_REF_ATTR).as_deref() == Some(id.as_str()))
+    }
+
+    /// The first element with `class`.
+    pub fn find_by_class(&self, class: &str) -> Option<usize> {
+        self.find_all_by_class(class).into_iter().next()
+    }
+
+    /// Every element with `class`, in document order.
+    pub fn find_all_by_class(&self, class: &str) -> Vec<usize> {
+        let doc = self.doc.inner();
+        elements(&doc)
+            .into_iter()
+            .filter(|&id| {
+                attribute(&doc, id, "class")
+                    .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+            })
+            .collect()
+    }
+
+    /// The first element with ARIA `role` (given by its `role` attribute or
+    /// implied by its tag, as `button` is by `<button>`) and, if `name` is
+    /// given, that accessible name.
+    pub fn find_by_role(&self, role: &str, name: Option<&str>) -> Option<usize> {
+        elements(&self.doc.inner()).into_iter().find(|&id| {
+            self.role(id).as_deref() == Some(role)
+                && name.is_none_or(|name| self.accessible_name(id) == name)
+        })
+    }
+
+    /// An element's ARIA role, explicit or implied by its tag.
+    pub fn role(&self, node_id: usize) -> Option<String> {
+        let doc = self.doc.inner();
+        let element = doc.get_node(node_id)?.element_data()?;
+        let attr = |name: &str| {
+            element
+                .attrs()
+                .iter()
+                .find(|a| a.name.local.as_ref() == name)
+                .map(|a| a.value.as_str())
+        };
+        a11y::role(element.name.local.as_ref(), attr).map(str::to_string)
+    }
+
+    /// The name a screen reader gives an element: its `aria-labelledby`
+    /// elements' text, its `aria-label`, an image's `alt`, or else its own
+    /// text, with whitespace collapsed.
+    pub fn accessible_name(&self, node_id: usize) -> String {
+        let doc = self.doc.inner();
+        if let Some(ids) = attribute(&doc, node_id, "aria-labelledby") {
+            let labels: Vec<String> = ids
+                .split_whitespace()
+                .filter_map(|id| {
+                    elements(&doc)
+                        .into_iter()
+                        .find(|&e| attribute(&doc, e, "id").as_deref() == Some(id))
+                })
+                .map(|label| self.text(label))
+                .collect();
+            if !labels.is_empty() {
+                return labels.join(" ");
+            }
+        }
+        attribute(&doc, node_id, "aria-label")
+            .or_else(|| attribute(&doc, node_id, "alt"))
+            .map(|label| collapse(&label))
+            .unwrap_or_else(|| self.text(node_id))
+    }
+
+    /// An element's text, with whitespace collapsed.
+    pub fn text(&self, node_id: usize) -> String {
+        self.doc
+            .inner()
+            .get_node(node_id)
+            .map(|node| collapse(&node.text_content()))
+            .unwrap_or_default()
+    }
+
+    /// An element's attribute.
+    pub fn attribute(&self, node_id: usize, name: &str) -> Option<String> {
+        attribute(&self.doc.inner(), node_id, name)
+    }
+
+    /// An element's border box.
+    pub fn rect(&self, node_id: usize) -> Option<ElementRect> {
+        let doc = self.doc.inner();
+        let node = doc.get_node(node_id)?;
+        let (x, y) = border_box_origin(&doc, node_id);
+        let size = node.final_layout.size;
+        Some(ElementRect {
+            x,
+            y,
+            width: size.width as f64,
+            height: size.height as f64,
+        })
+    }
+
+    /// A textual dump of the laid-out window, for snapshot tests: one line
+    /// per element with its tag, id and classes, border box (`x,y wxh`, to a
+    /// tenth of a pixel), non-zero margin, border and padding (top, right,
+    /// bottom, left), and key computed properties, such as `display` and
+    /// `overflow-x`, unless they have their usual values (`block` or
+    /// `inline`, `visible`...). Text is shown quoted, with whitespace
+    /// collapsed, and children are indented under their parent.
+    ///
+    /// ```text
+    /// html 0,0 400x300
+    ///   body 8,8 384x18 margin=8 8 8 8
+    ///     p.count 8,8 384x18 display=flex
+    ///       "Count: 1"
+    /// ```
+    ///
+    /// Handler IDs and other attributes are left out, so the dump only
+    /// changes when the layout does.
+    pub fn snapshot_layout(&self) -> String {
+        let doc = self.doc.inner();
+        let mut snapshot = String::new();
+        snapshot_node(&doc, doc.root_element().id, 0, &mut snapshot);
+        snapshot
+    }
+
+    /// Click an element: its nearest `onclick`, if any, runs and the app
+    /// re-renders. Returns whether a handler ran.
+    pub fn click(&mut self, node_id: usize) -> bool {
+        self.user_input();
+        self.selection.clear();
+        let before = self.focus.node();
+        {
+            let animation_time = self.animation_time();
+            let mut doc = self.doc.inner_mut();
+            self.focus.pointer_down(&mut doc, Some(node_id));
+            doc.resolve(animation_time);
+        }
+        let focus_moved = self.focus_moved(before);
+        let handler = click_handler(&self.doc.inner(), node_id);
+        let called = handler.is_some_and(dispatch_event);
+        if called || focus_moved {
+            self.render();
+        }
+        called
+    }
+
+    /// Focus an element, as tabbing to it would, so keys sent with
+    /// [`press_key`](Self::press_key) go to it. Its `onfocus` (and the
+    /// `onblur` of the element that had focus) run and the app re-renders.
+    /// Returns whether the element can take focus.
+    pub fn focus(&mut self, node_id: usize) -> bool {
+        let before = self.focus.node();
+        let focused = {
+            let animation_time = self.animation_time();
+            let mut doc = self.doc.inner_mut();
+            let focused = self.focus.focus(&mut doc, node_id);
+            doc.resolve(animation_time);
+            focused
+        };
+        if self.focus_moved(before) {
+            self.render();
+        }
+        focused
+    }
+
+    /// Click whatever element is at a point in the window.
+    pub fn click_at(&mut self, x: f32, y: f32) -> bool {
+        let (x, y) = self.document_point((x, y));
+        let hit = self.doc.inner().hit(x, y).map(|hit| hit.node_id);
+        hit.is_some_and(|node_id| self.click(node_id))
+    }
+
+    /// Press the mouse at `from`, move to `to` and release. On an element
+    /// with drag handlers this runs its `ondragstart`, `ondrag` and
+    /// `ondragend`, re-rendering after each. Over text it selects it, as in
+    /// a window; a drag that selects nothing is a click where it's released.
+    pub fn drag(&mut self, from: (f32, f32), to: (f32, f32)) {
+        self.user_input();
+        let (from, to) = (self.document_point(from), self.document_point(to));
+        let handlers = {
+            let doc = self.doc.inner();
+            doc.hit(from.0, from.1)
+                .and_then(|hit| DragHandlers::find(&doc, hit.node_id))
+        };
+        if let Some(handlers) = handlers {
+            self.selection.clear();
+            let mut drag = ElementDrag::new(handlers, from);
+            let mut calls = drag.moved(to);
+            let dragged = drag.started();
+            calls.extend(drag.released(to));
+            for (handler_id, event) in calls {
+                if dispatch_drag_event(handler_id, event) {
+                    self.render();
+                }
+            }
+            if dragged {
+                return;
+            }
+        }
+        {
+            let doc = self.doc.inner();
+            self.selection.begin(&doc, from.0, from.1);
+            self.selection.extend(&doc, to.0, to.1);
+            self.selection.end();
+        }
+        if !self.selection.dragged() {
+            let hit = self.doc.inner().hit(to.0, to.1).map(|hit| hit.node_id);
+            hit.is_some_and(|node_id| self.click(node_id));
+        }
+    }
+
+    /// Type `value` into a text field, replacing what it held, and leave it:
+    /// its `bind` and `oninput` run, the app re-renders, then its `onchange`
+    /// runs and it re-renders again. Returns whether any handler ran.
+    pub fn type_text(&mut self, node_id: usize, value: &str) -> bool {
+        self.user_input();
+        let handlers = FieldHandlers::of(&self.doc.inner(), node_id);
+        let mut called = false;
+        for handlers in [handlers.on_input(), handlers.on_change()] {
+            let mut ran = false;
+            for handler_id in handlers {
+                ran |= dispatch_input_event(handler_id, value.to_string());
+            }
+            if ran {
+                self.render();
+            }
+            called |= ran;
+        }
+        called
+    }
+
+    /// A point in the window in document coordinates, at the content scale.
+    fn document_point(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let scale = clamp_scale(self.props.content_scale).unwrap_or(1.0);
+        (x / scale, y / scale)
+    }
+
+    /// The text selected by the last drag.
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection.text(&self.doc.inner())
+    }
+
+    /// Press a keyboard shortcut such as `"Ctrl+S"`: the enabled menu item
+    /// with that shortcut runs, or else the `use_hotkey` hotkeys with it,
+    /// and the app re-renders. Ctrl and Cmd are the
+    /// same, as in the runtime, and keys are typed as on a US keyboard.
+    /// Returns whether an item ran.
+    ///
+    /// `"Tab"` and `"Shift+Tab"` move focus instead, returning whether
+    /// anything took it.
+    ///
+    /// The key is pressed and released on the focused element (or `body`)
+    /// first, so `onkeydown`, `onkeypress` and `onkeyup` handlers there and
+    /// above it run; that counts as handling it too. `"Ctrl+C"` and
+    /// `"Ctrl+X"` run `oncopy` and `oncut` as [`copy`](Self::copy) and
+    /// [`cut`](Self::cut) do; there's no clipboard to paste from, so use
+    /// [`paste`](Self::paste) for `onpaste`.
+    pub fn press_key(&mut self, shortcut: &str) -> bool {
+        let Some(pressed) = parse_shortcut_for_matching(shortcut) else {
+            return false;
+        };
+        self.user_input();
+        let press = KeyPress::from(&pressed);
+        let mut key_handled = self.key_events(press_event(&press));
+        match clipboard_attr(&press) {
+            Some(COPY_ATTR) => key_handled |= self.copy(),
+            Some(CUT_ATTR) => key_handled |= self.cut(),
+            _ => {}
+        }
+        if !pressed.ctrl_or_cmd && !pressed.alt {
+            let animation_time = self.animation_time();
+            let before = self.focus.node();
+            let mut doc = self.doc.inner_mut();
+            if pressed.key == KeyCode::Tab {
+                let moved = self.focus.tab(&mut doc, pressed.shift);
+                if moved {
+                    doc.resolve(animation_time);
+                }
+                drop(doc);
+                if self.focus_moved(before) {
+                    self.render();
+                }
+                return moved || key_handled;
+            }
+            if self.focus.key_pressed(&mut doc) {
+                doc.resolve(animation_time);
+            }
+        }
+        let mut items = Vec::new();
+        if let Some(menu) = &self.menu {
+            menu_items(menu, &mut items);
+        }
+        let callback = items.into_iter().find_map(|(item_shortcut, callback)| {
+            let parsed = parse_menu_shortcut(&item_shortcut)?;
+            parsed.matches(&press).then_some(callback)
+        });
+        let Some(callback) = callback else {
+            return self.hotkeys(&press) || key_handled;
+        };
+        callback.invoke();
+        self.render();
+        true
+    }
+
+    /// Run the `use_hotkey` hotkeys a key press triggers, and re-render if
+    /// any ran.
+    fn hotkeys(&mut self, press: &KeyPress) -> bool {
+        let typing = {
+            let doc = self.doc.inner();
+            self.focus
+                .node()
+                .is_some_and(|node_id| field_value(&doc, node_id).is_some())
+        };
+        let hotkeys = matching_hotkeys(press, None, typing);
+        for hotkey in &hotkeys {
+            hotkey.invoke();
+        }
+        if !hotkeys.is_empty() {
+            self.render();
+        }
+        !hotkeys.is_empty()
+    }
+
+    /// Press and release a key on the focused element, running its key
+    /// handlers and those above it, and re-render if any ran.
+    fn key_events(&mut self, event: KeyboardEvent) -> bool {
+        let (down, up) = {
+            let doc = self.doc.inner();
+            let Some(target) = key_target(&doc, self.focus.node()) else {
+                return false;
+            };
+            let mut down = bubbling_handlers(&doc, target, KEY_DOWN_ATTR);
+            if types_text(&event) {
+                down.extend(bubbling_handlers(&doc, target, KEY_PRESS_ATTR));
+            }
+            (down, bubbling_handlers(&doc, target, KEY_UP_ATTR))
+        };
+        let mut called = false;
+        for handler_id in down.into_iter().chain(up) {
+            called |= dispatch_key_event(handler_id, event.clone());
+        }
+        if called {
+            self.render();
+        }
+        called
+    }
+
+    /// Run `oncopy` on the focused element (or `body`) and those above it,
+    /// with the selected text, as Ctrl+C does. Returns whether any ran.
+    pub fn copy(&mut self) -> bool {
+        let text = self.selected_text();
+        self.clipboard_events(COPY_ATTR, ClipboardEvent { text, image: None })
+    }
+
+    /// Run `oncut` as [`copy`](Self::copy) runs `oncopy`.
+    pub fn cut(&mut self) -> bool {
+        let text = self.selected_text();
+        self.clipboard_events(CUT_ATTR, ClipboardEvent { text, image: None })
+    }
+
+    /// Paste `event`'s text or image: run `onpaste` on the focused element
+    /// (or `body`) and those above it, as Ctrl+V does. Returns whether any
+    /// ran.
+    pub fn paste(&mut self, event: ClipboardEvent) -> bool {
+        self.user_input();
+        self.clipboard_events(PASTE_ATTR, event)
+    }
+
+    /// Run the clipboard handlers in `attr` on the focused element and
+    /// above it, and re-render if any ran.
+    fn clipboard_events(&mut self, attr: &str, event: ClipboardEvent) -> bool {
+        let handlers = {
+            let doc = self.doc.inner();
+            let Some(target) = key_target(&doc, self.focus.node()) else {
+                return false;
+            };
+            bubbling_handlers(&doc, target, attr)
+        };
+        let mut called = false;
+        for handler_id in handlers {
+            called |= dispatch_clipboard_event(handler_id, event.clone());
+        }
+        if called {
+            self.render();
+        }
+        called
+    }
+
+    /// Run `onblur` on `before`, the element that had focus, and `onfocus`
+    /// on the one with it now, if focus moved. Returns whether either ran.
+    fn focus_moved(&mut self, before: Option<usize>) -> bool {
+        let after = self.focus.node();
+        if after == before {
+            return false;
+        }
+        let handlers: Vec<EventHandlerId> = {
+            let doc = self.doc.inner();
+            let blur = before.and_then(|node_id| handler(&doc, node_id, BLUR_ATTR));
+            let focus = after.and_then(|node_id| handler(&doc, node_id, FOCUS_ATTR));
+            blur.into_iter().chain(focus).collect()
+        };
+        let mut called = false;
+        for handler_id in handlers {
+            called |= dispatch_event(handler_id);
+        }
+        called
+    }
+
+    /// Scroll a container by `(dx, dy)`, within how far it can scroll. Its
+    /// `onscroll` and node ref see the new position.
+    pub fn scroll(&mut self, node_id: usize, dx: f64, dy: f64) {
+        self.user_input();
+        {
+            let mut doc = self.doc.inner_mut();
+            let state = scroll_state(&doc, node_id);
+            if let Some(node) = doc.get_node_mut(node_id) {
+                node.scroll_offset.x = (state.scroll_x + dx).clamp(0.0, state.max_scroll_x());
+                node.scroll_offset.y = (state.scroll_y + dy).clamp(0.0, state.max_scroll_y());
+            }
+        }
+        self.settle();
+    }
+
+    /// Deliver a deep link, as when the OS opens a URL in the app's scheme,
+    /// and re-render.
+    pub fn open_deep_link(&mut self, url: &str) {
+        push_deep_link(url);
+        self.render();
+    }
+
+    /// Report a power source, as the OS would, and re-render.
+    pub fn set_power(&mut self, status: PowerStatus) {
+        set_power_status(status);
+        self.render();
+    }
+
+    /// Note input from the user, re-rendering if `use_idle` showed them as
+    /// idle.
+    fn user_input(&mut self) {
+        if record_user_input() {
+            self.render();
+        }
+    }
+
+    /// Move the clock forward, firing timers as they come due and running
+    /// animation frames every `FRAME_INTERVAL` along the way, re-rendering
+    /// after them.
+    pub fn advance(&mut self, duration: Duration) {
+        let end = self.clock.now() + duration;
+        while self.clock.now() + FRAME_INTERVAL <= end {
+            let timers = self.clock.advance(FRAME_INTERVAL);
+            let frame = animation_frame_requested() && run_animation_frame(self.clock.now());
+            if timers || frame {
+                self.render();
+            }
+        }
+        if self.clock.advance(end - self.clock.now()) {
+            self.render();
+        }
+        let time = self.animation_time();
+        self.doc.inner_mut().resolve(time);
+    }
+
+    /// Seconds since the harness started, for CSS animations.
+    fn animation_time(&self) -> f64 {
+        (self.clock.now() - self.start).as_secs_f64()
+    }
+
+    /// Re-run the app function and lay out its new content.
+    fn render_once(&mut self) {
+        clear_handlers();
+        let (window, menu) = render(&*self.app);
+        self.menu = menu;
+        let Some((_, html)) = window else {
+            tracing::warn!("The app stopped rendering a Window; keeping the last content");
+            return;
+        };
+        if html != self.html {
+            self.html = html;
+            self.rebuild();
+        }
+    }
+
+    /// Build the document again from the current HTML, keeping scroll
+    /// positions.
+    fn rebuild(&mut self) {
+        let scroll = ScrollSnapshot::take(&self.doc.inner());
+        (self.doc, self.text_effects) = layout(&self.html, &self.props, self.animation_time());
+        if self.focus.node().is_some() {
+            let animation_time = self.animation_time();
+            let mut doc = self.doc.inner_mut();
+            self.focus.restore(&mut doc);
+            doc.resolve(animation_time);
+        }
+        scroll.restore(&mut self.doc.inner_mut());
+        self.watch = ScrollWatch::new(&self.doc.inner());
+        self.live_regions.update(&self.doc.inner());
+        self.selection.clear();
+    }
+
+    /// Report layout changes to node refs and `onscroll` handlers, as the
+    /// runtime does after each layout, carry out focus calls made through
+    /// node refs, and run `use_effect` effects and woken `spawn_local`
+    /// tasks, re-rendering until nothing changes.
+    fn settle(&mut self) {
+        for _ in 0..MAX_SETTLE_RENDERS {
+            let (scrolls, resizes, moves, visibility) = {
+                let doc = self.doc.inner();
+                (
+                    self.watch.changes(&doc),
+                    self.watch.resizes(&doc),
+                    self.watch.moves(&doc),
+                    self.watch.visibility_changes(&doc),
+                )
+            };
+
+            let mut changed = false;
+            for change in scrolls {
+                changed |= change
+                    .node_ref
+                    .is_some_and(|id| update_node_ref_scroll(id, change.event));
+                changed |= change
+                    .handler_id
+                    .is_some_and(|id| dispatch_scroll_event(id, change.event));
+            }
+            for (node_ref, size) in resizes {
+                changed |= update_node_ref_size(node_ref, size);
+            }
+            for (node_ref, rect) in moves {
+                changed |= update_node_ref_rect(node_ref, rect);
+            }
+            for (node_ref, visible) in visibility {
+                changed |= update_node_ref_visibility(node_ref, visible);
+            }
+            for (node_ref, request) in take_focus_requests() {
+                let Some(node_id) = self.watch.node_for_ref(node_ref) else {
+                    continue;
+                };
+                let before = self.focus.node();
+                {
+                    let animation_time = self.animation_time();
+                    let mut doc = self.doc.inner_mut();
+                    match request {
+                        FocusRequest::Focus => {
+                            self.focus.focus(&mut doc, node_id);
+                        }
+                        FocusRequest::Blur => self.focus.blur(&mut doc, node_id),
+                        FocusRequest::Caret(position) => {
+                            set_caret(&mut doc, node_id, position);
+                        }
+                    }
+                    doc.resolve(animation_time);
+                }
+                changed |= self.focus_moved(before);
+            }
+            changed |= run_effects();
+            changed |= poll_tasks();
+
+            if !changed {
+                return;
+            }
+            self.render_once();
+        }
+        tracing::warn!(
+            "The app kept re-rendering after layout; giving up after {MAX_SETTLE_RENDERS} renders"
+        );
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        clear_handlers();
+        clear_hooks();
+        take_announcements();
+    }
+}
+
+/// Run the app function, returning its first window's props and HTML and
+/// its app menu.
+fn render(app: &dyn Fn() -> Element) -> (Option<(WindowProps, String)>, Option<Element>) {
+    begin_render();
+    let root = app();
+    end_render();
+
+    let mut window = None;
+    let mut menu = None;
+    let mut stack = vec![root];
+    while let Some(element) = stack.pop() {
+        match element {
+            Element::Window(props, children) if window.is_none() => {
+                window = Some((props, children_to_html(&children)));
+            }
+            Element::AppMenu(..) if menu.is_none() => menu = Some(element),
+            Element::Fragment(children) => stack.extend(children.into_iter().rev()),
+            _ => {}
+        }
+    }
+    (window, menu)
+}
+
+/// Parse and lay out a window's HTML, as the window manager does.
+fn layout(
+    html: &str,
+    props: &WindowProps,
+    animation_time: f64,
+) -> (Box<dyn Document>, TextEffects) {
+    let html = inline_local_stylesheets(html);
+    let mut viewport = Viewport::new(props.width, props.height, 1.0, ColorScheme::Light);
+    viewport.set_zoom(clamp_scale(props.content_scale).unwrap_or(1.0));
+    let config = DocumentConfig {
+        viewport: Some(viewport),
+        ua_stylesheets: Some(user_agent_stylesheets(
+            blitz_dom::DEFAULT_CSS,
+            props.reset,
+            props.rtl,
+        )),
+        font_ctx: Some(font_context(&split_styles(&html).1)),
+        ..Default::default()
+    };
+    let (doc, text_effects, ..) = build_document(
+        &html,
+        config,
+        props.overlay_scrollbars,
+        animation_time,
+        &mut CssAnimations::default(),
+        &mut MirroredRows::default(),
+    );
+    (doc, text_effects)
+}
+
+/// Add a node and its descendants to a layout snapshot.
+fn snapshot_node(doc: &BaseDocument, node_id: usize, depth: usize, snapshot: &mut String) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    let indent = "  ".repeat(depth);
+    if let Some(text) = node.text_data() {
+        let text = collapse(&text.content);
+        if !text.is_empty() {
+            snapshot.push_str(&format!("{indent}{text:?}\n"));
+        }
+        return;
+    }
+    let Some(element) = node.element_data() else {
+        return;
+    };
+    let tag = element.name.local.as_ref();
+    if SNAPSHOT_SKIPPED.contains(&tag) {
+        return;
+    }
+
+    let mut line = format!("{indent}{tag}");
+    if let Some(id) = attribute(doc, node_id, "id") {
+        line.push_str(&format!("#{id}"));
+    }
+    for class in attribute(doc, node_id, "class")
+        .iter()
+        .flat_map(|c| c.split_whitespace())
+    {
+        line.push_str(&format!(".{class}"));
+    }
+
+    let (x, y) = border_box_origin(doc, node_id);
+    let layout = &node.final_layout;
+    line.push_str(&format!(
+        " {},{} {}x{}",
+        px(x),
+        px(y),
+        px(layout.size.width as f64),
+        px(layout.size.height as f64)
+    ));
+    for (name, edges) in [
+        ("margin", &layout.margin),
+        ("border", &layout.border),
+        ("padding", &layout.padding),
+    ] {
+        let edges = [edges.top, edges.right, edges.bottom, edges.left];
+        if edges.iter().any(|&edge| edge != 0.0) {
+            let edges: Vec<_> = edges.iter().map(|&edge| px(edge as f64)).collect();
+            line.push_str(&format!(" {name}={}", edges.join(" ")));
+        }
+    }
+
+    for (property, defaults) in SNAPSHOT_PROPERTIES {
+        if let Some(value) = computed_value(doc, node_id, property)
+            && !defaults.contains(&value.as_str())
+        {
+            line.push_str(&format!(" {property}={value}"));
+        }
+    }
+
+    snapshot.push_str(&line);
+    snapshot.push('\n');
+    for &child in &node.children {
+        snapshot_node(doc, child, depth + 1, snapshot);
+    }
+}
+
+/// A length rounded to a tenth of a pixel, without a trailing `.0`.
+fn px(value: f64) -> String {
+    // Adding zero turns -0 into 0
+    format!("{}", (value * 10.0).round() / 10.0 + 0.0)
+}
+
+/// The elements of a document, in document order.
+fn elements(doc: &BaseDocument) -> Vec<usize> {
+    let mut elements = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        if node.element_data().is_some() {
+            elements.push(node_id);
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    elements
+}
+
+fn attribute(doc: &BaseDocument, node_id: usize, name: &str) -> Option<String> {
+    doc.get_node(node_id)?
+        .element_data()?
+        .attrs()
+        .iter()
+        .find(|a| a.name.local.as_ref() == name)
+        .map(|a| a.value.to_string())
+}
+
+/// The `onclick` a click on `node_id` runs: the nearest one at or above it.
+fn click_handler(doc: &BaseDocument, node_id: usize) -> Option<EventHandlerId> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if let Some(rid) = attribute(doc, id, "data-rid").and_then(|rid| rid.parse().ok()) {
+            return Some(EventHandlerId(rid));
+        }
+        current = doc.get_node(id)?.parent;
+    }
+    None
+}
+
+/// Collect the enabled menu items with shortcuts and callbacks.
+fn menu_items(element: &Element, items: &mut Vec<(Shortcut, MenuItemCallback)>) {
+    match element {
+        Element::AppMenu(_, children)
+        | Element::Menu(_, children)
+        | Element::Fragment(children) => {
+            for child in children {
+                menu_items(child, items);
+            }
+        }
+        Element::MenuItem(props) if props.enabled => {
+            if let (Some(shortcut), Some(callback)) = (&props.shortcut, &props.onclick) {
+                items.push((shortcut.clone(), callback.clone()));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collapse runs of whitespace to single spaces and trim the ends.
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_is_collapsed() {
+        assert_eq!(collapse("  Count:\n   1 "), "Count: 1");
+        assert_eq!(collapse(""), "");
+    }
+
+    #[test]
+    fn lengths_are_rounded() {
+        assert_eq!(px(8.0), "8");
+        assert_eq!(px(17.04), "17");
+        assert_eq!(px(17.06), "17.1");
+        assert_eq!(px(-0.01), "0");
+    }
+}
@@ -0,0 +1,365 @@
+//! Screenshot tests.
+//!
+//! [`TestApp::screenshot`] rasterizes the window on the CPU with vello_cpu,
+//! so it runs in CI without a GPU. [`TestApp::assert_screenshot`] compares
+//! the result against a baseline PNG in `tests/screenshots/`, recording the
+//! baseline on the first run (or whenever `RINCH_UPDATE_SCREENSHOTS=1`).
+//! Pixels are compared perceptually, by their distance in the YIQ color
+//! space as pixelmatch does, so antialiasing noise below the threshold
+//! doesn't fail a test. On a mismatch the actual image and a diff (changed
+//! pixels red over a faded copy of the baseline) are written next to the
+//! baseline for CI to upload.
+//!
+//! rinch's scrollbars aren't painted: overlay scrollbars fade on the real
+//! clock, which would make screenshots flaky.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use anyrender::{render_to_buffer, PaintScene};
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use blitz_paint::paint_scene;
+use peniko::kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+use rinch_core::element::Element;
+
+use super::TestApp;
+
+/// Largest possible YIQ distance between two colors.
+const MAX_DELTA: f64 = 35215.0;
+
+/// Set to `1` to overwrite baselines with the current screenshots.
+const UPDATE_ENV: &str = "RINCH_UPDATE_SCREENSHOTS";
+
+/// An RGBA image, 8 bits per channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    /// Rows of RGBA pixels, top to bottom.
+    pub pixels: Vec<u8>,
+}
+
+/// How closely a screenshot must match its baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// How different two pixels' colors may be before they count as
+    /// changed, from 0 (exactly equal) to 1 (anything goes).
+    pub threshold: f64,
+    /// How many changed pixels are allowed.
+    pub max_different_pixels: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_different_pixels: 0,
+        }
+    }
+}
+
+/// The result of comparing two screenshots.
+#[derive(Debug, Clone)]
+pub struct ScreenshotDiff {
+    /// How many pixels changed by more than the threshold. Every pixel, if
+    /// the sizes differ.
+    pub different_pixels: usize,
+    /// Whether the screenshots are different sizes.
+    pub size_changed: bool,
+    /// The changed pixels in red over a faded copy of the expected image.
+    pub image: Screenshot,
+}
+
+impl Screenshot {
+    /// The RGBA color of the pixel at `x`, `y`.
+    ///
+    /// # Panics
+    ///
+    /// If the pixel is outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({x}, {y}) is outside the image"
+        );
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[start..start + 4].try_into().unwrap()
+    }
+
+    /// Read a PNG.
+    pub fn load_png(path: impl AsRef<Path>) -> io::Result<Self> {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        let mut reader = decoder.read_info().map_err(io::Error::other)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (color, depth) => {
+                return Err(io::Error::other(format!(
+                    "unsupported PNG format {color:?} at {depth:?}"
+                )));
+            }
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// Write the image as a PNG, creating its directory if needed.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)
+    }
+
+    /// Compare with `expected`, counting pixels whose colors differ by
+    /// more than `threshold` (0 to 1).
+    pub fn diff(&self, expected: &Screenshot, threshold: f64) -> ScreenshotDiff {
+        if (self.width, self.height) != (expected.width, expected.height) {
+            let pixels =
+                (self.width.max(expected.width) * self.height.max(expected.height)) as usize;
+            return ScreenshotDiff {
+                different_pixels: pixels,
+                size_changed: true,
+                image: self.clone(),
+            };
+        }
+
+        let limit = MAX_DELTA * threshold * threshold;
+        let mut different_pixels = 0;
+        let mut image = Vec::with_capacity(self.pixels.len());
+        for (actual, expected) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(expected.pixels.chunks_exact(4))
+        {
+            let actual = [actual[0], actual[1], actual[2], actual[3]];
+            let expected = [expected[0], expected[1], expected[2], expected[3]];
+            if color_delta(actual, expected) > limit {
+                different_pixels += 1;
+                image.extend([255, 0, 0, 255]);
+            } else {
+                // Faded grey, so the changes stand out
+                let grey = (255.0 - (255.0 - brightness(expected)) * 0.1) as u8;
+                image.extend([grey, grey, grey, 255]);
+            }
+        }
+        ScreenshotDiff {
+            different_pixels,
+            size_changed: false,
+            image: Screenshot {
+                width: self.width,
+                height: self.height,
+                pixels: image,
+            },
+        }
+    }
+}
+
+/// Render `app` headless, laying out its first window at `width` by
+/// `height` logical pixels, and rasterize the frame on the CPU.
+///
+/// Use [`TestApp::with_size`] instead to query the laid-out elements or
+/// send input before taking a [`screenshot`](TestApp::screenshot).
+///
+/// # Panics
+///
+/// If the app doesn't render a `Window`.
+///
+/// # Example
+///
+/// ```ignore
+/// let frame = render_headless(dashboard, 1280, 800);
+/// assert_eq!((frame.width, frame.height), (1280, 800));
+/// assert_eq!(frame.pixel(0, 0), [255, 255, 255, 255]);
+/// ```
+pub fn render_headless(app: impl Fn() -> Element + 'static, width: u32, height: u32) -> Screenshot {
+    TestApp::with_size(app, width, height).screenshot()
+}
+
+impl TestApp {
+    /// Rasterize the window on the CPU.
+    pub fn screenshot(&self) -> Screenshot {
+        let doc = self.doc.inner();
+        let (width, height) = doc.viewport().window_size;
+        let scale = doc.viewport().scale_f64();
+        let pixels = render_to_buffer::<VelloCpuImageRenderer, _>(
+            |scene| {
+                let page = Rect::new(0.0, 0.0, width as f64, height as f64);
+                scene.fill(Fill::NonZero, Affine::IDENTITY, Color::WHITE, None, &page);
+                paint_scene(scene, &doc, scale, width, height);
+                self.text_effects.paint(scene, &doc, scale);
+                self.focus.paint(scene, &doc, scale);
+            },
+            width,
+            height,
+        );
+        Screenshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Compare the window with the baseline `tests/screenshots/{name}.png`,
+    /// allowing no changed pixels at the default threshold.
+    ///
+    /// # Panics
+    ///
+    /// If the screenshot doesn't match, after writing
+    /// `{name}.actual.png` and `{name}.diff.png` next to the baseline.
+    pub fn assert_screenshot(&self, name: &str) {
+        self.assert_screenshot_with(name, DiffOptions::default());
+    }
+
+    /// [`assert_screenshot`](Self::assert_screenshot) with a custom
+    /// threshold or tolerance.
+    pub fn assert_screenshot_with(&self, name: &str, options: DiffOptions) {
+        let actual = self.screenshot();
+        let baseline = screenshot_dir().join(format!("{name}.png"));
+        let update = std::env::var(UPDATE_ENV).is_ok_and(|value| value == "1");
+        if update || !baseline.exists() {
+            actual
+                .save_png(&baseline)
+                .unwrap_or_else(|e| panic!("couldn't write {}: {e}", baseline.display()));
+            return;
+        }
+
+        let expected = Screenshot::load_png(&baseline)
+            .unwrap_or_else(|e| panic!("couldn't read {}: {e}", baseline.display()));
+        let diff = actual.diff(&expected, options.threshold);
+        if !diff.size_changed && diff.different_pixels <= options.max_different_pixels {
+            return;
+        }
+
+        let actual_path = baseline.with_extension("actual.png");
+        let diff_path = baseline.with_extension("diff.png");
+        let _ = actual.save_png(&actual_path);
+        let _ = diff.image.save_png(&diff_path);
+        if diff.size_changed {
+            panic!(
+                "screenshot {name} is {}x{}, but the baseline is {}x{}; see {}",
+                actual.width,
+                actual.height,
+                expected.width,
+                expected.height,
+                actual_path.display()
+            );
+        }
+        panic!(
+            "screenshot {name} differs from its baseline in {} pixels (allowed: {}); see {} and {}. \
+             Set {UPDATE_ENV}=1 to accept the change.",
+            diff.different_pixels,
+            options.max_different_pixels,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+/// Where baselines live: `tests/screenshots/` in the crate under test.
+fn screenshot_dir() -> PathBuf {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    root.join("tests").join("screenshots")
+}
+
+/// A color blended onto white by its alpha, as floats.
+fn on_white(color: [u8; 4]) -> [f64; 3] {
+    let alpha = color[3] as f64 / 255.0;
+    [0, 1, 2].map(|i| 255.0 + (color[i] as f64 - 255.0) * alpha)
+}
+
+/// A color's brightness (the Y of YIQ), from 0 to 255.
+fn brightness(color: [u8; 4]) -> f64 {
+    let [r, g, b] = on_white(color);
+    r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+/// Squared perceptual distance between two colors in YIQ, weighted as in
+/// Kotsarenko and Ramos' "Measuring perceived color difference using YIQ".
+fn color_delta(a: [u8; 4], b: [u8; 4]) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+    let ([r1, g1, b1], [r2, g2, b2]) = (on_white(a), on_white(b));
+    let y = brightness(a) - brightness(b);
+    let i = (r1 * 0.59597799 - g1 * 0.2741761 - b1 * 0.32180189)
+        - (r2 * 0.59597799 - g2 * 0.2741761 - b2 * 0.32180189);
+    let q = (r1 * 0.21147017 - g1 * 0.52261711 + b1 * 0.31114694)
+        - (r2 * 0.21147017 - g2 * 0.52261711 + b2 * 0.31114694);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rinch_core::element::WindowProps;
+
+    fn image(pixels: &[[u8; 4]]) -> Screenshot {
+        Screenshot {
+            width: pixels.len() as u32,
+            height: 1,
+            pixels: pixels.concat(),
+        }
+    }
+
+    #[test]
+    fn black_and_white_are_far_apart() {
+        let delta = color_delta([0, 0, 0, 255], [255, 255, 255, 255]);
+        assert!(delta > MAX_DELTA * 0.9 && delta <= MAX_DELTA);
+        // Transparent is white
+        assert_eq!(color_delta([0, 0, 0, 0], [255, 255, 255, 255]), 0.0);
+    }
+
+    #[test]
+    fn small_changes_are_within_the_threshold() {
+        let expected = image(&[[200, 200, 200, 255], [0, 0, 0, 255]]);
+        let actual = image(&[[204, 200, 200, 255], [255, 0, 0, 255]]);
+        let diff = actual.diff(&expected, 0.1);
+        assert_eq!(diff.different_pixels, 1);
+        assert_eq!(&diff.image.pixels[4..], [255, 0, 0, 255]);
+        assert_eq!(actual.diff(&expected, 1.0).different_pixels, 0);
+        assert_eq!(actual.diff(&actual, 0.0).different_pixels, 0);
+    }
+
+    #[test]
+    fn size_changes_fail() {
+        let diff = image(&[[0, 0, 0, 255]]).diff(&image(&[[0, 0, 0, 255]; 2]), 0.1);
+        assert!(diff.size_changed);
+        assert_eq!(diff.different_pixels, 2);
+    }
+
+    #[test]
+    fn headless_frames_are_the_size_asked_for() {
+        let app = || {
+            Element::Window(
+                WindowProps::default(),
+                vec![Element::Html(
+                    r#"<div style="position: absolute; left: 0; top: 0; width: 10px; height: 10px; background: #0000ff"></div>"#.into(),
+                )],
+            )
+        };
+        let frame = render_headless(app, 64, 48);
+        assert_eq!((frame.width, frame.height), (64, 48));
+        assert_eq!(frame.pixels.len(), 64 * 48 * 4);
+        assert_eq!(frame.pixel(5, 5), [0, 0, 255, 255]);
+        assert_eq!(frame.pixel(40, 30), [255, 255, 255, 255]);
+    }
+}
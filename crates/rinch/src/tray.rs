@@ -1,30 +1,49 @@
 //! System tray icon support.
 //!
 //! This module provides system tray functionality using the `tray-icon` crate.
+//! Clicks on the icon and its menu items are delivered by the event loop, so
+//! their callbacks run on the UI thread like any other handler: they can set
+//! signals, and show or hide windows.
 //!
 //! # Example
 //!
 //! ```ignore
 //! use rinch::tray::{TrayIconBuilder, TrayMenu, TrayMenuItem};
+//! use rinch::windows::{quit, set_hide_on_close, show_main_window, toggle_main_window};
 //!
-//! // Create a tray menu
-//! let menu = TrayMenu::new()
-//!     .add_item(TrayMenuItem::new("Show Window"))
-//!     .add_separator()
-//!     .add_item(TrayMenuItem::new("Settings"))
-//!     .add_separator()
-//!     .add_item(TrayMenuItem::new("Quit"));
-//!
-//! // Create the tray icon
-//! let tray = TrayIconBuilder::new()
-//!     .with_tooltip("My App")
-//!     .with_menu(menu)
-//!     .build()
-//!     .unwrap();
+//! fn app() -> Element {
+//!     // Create the tray icon once, when the event loop is running, and
+//!     // keep it for as long as it should show
+//!     use_ref(|| {
+//!         set_hide_on_close(true);
+//!         let menu = TrayMenu::new()
+//!             .add_item(TrayMenuItem::new("Show Window").on_click(show_main_window))
+//!             .add_separator()
+//!             .add_item(TrayMenuItem::new("Quit").on_click(quit));
+//!         TrayIconBuilder::new()
+//!             .with_tooltip("My App")
+//!             .with_menu(menu)
+//!             .on_click(toggle_main_window)
+//!             .build()
+//!             .ok()
+//!     });
+//!     // ...
+//! }
 //! ```
 
-use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
-use tray_icon::{Icon, TrayIcon as TrayIconInner, TrayIconBuilder as TrayIconBuilderInner};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{
+    Icon, MouseButton, MouseButtonState, TrayIcon as TrayIconInner,
+    TrayIconBuilder as TrayIconBuilderInner, TrayIconEvent, TrayIconId,
+};
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::runtime::RinchEvent;
 
 /// Error type for tray operations.
 #[derive(Debug)]
@@ -70,30 +89,178 @@ impl From<tray_icon::BadIcon> for TrayError {
 /// Result type for tray operations.
 pub type TrayResult<T> = Result<T, TrayError>;
 
+/// Callback type for tray icon clicks and menu items.
+pub type TrayCallback = Rc<dyn Fn()>;
+
+/// A click on a tray icon or one of its menu items.
+#[derive(Debug)]
+enum TrayAction {
+    Menu(MenuId),
+    Click(TrayIconId),
+}
+
+thread_local! {
+    /// Callbacks of the menu items of live tray icons, with their labels.
+    static MENU_CALLBACKS: RefCell<HashMap<MenuId, (String, TrayCallback)>> =
+        RefCell::new(HashMap::new());
+    /// Click callbacks of live tray icons.
+    static CLICK_CALLBACKS: RefCell<HashMap<TrayIconId, TrayCallback>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Tray clicks waiting for the event loop.
+static ACTIONS: Mutex<Vec<TrayAction>> = Mutex::new(Vec::new());
+
+/// The event loop to wake for tray clicks.
+static EVENT_PROXY: Mutex<Option<EventLoopProxy<RinchEvent>>> = Mutex::new(None);
+
+/// Have tray clicks wake the event loop (called by the runtime as it
+/// starts). The icon's events arrive on whichever thread the platform
+/// delivers them on, so they're queued for [`deliver`].
+pub(crate) fn set_event_proxy(proxy: EventLoopProxy<RinchEvent>) {
+    *EVENT_PROXY.lock().unwrap_or_else(|e| e.into_inner()) = Some(proxy);
+    MenuEvent::set_event_handler(Some(|event: MenuEvent| {
+        queue(TrayAction::Menu(event.id));
+    }));
+    TrayIconEvent::set_event_handler(Some(|event: TrayIconEvent| {
+        if let TrayIconEvent::Click {
+            id,
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } = event
+        {
+            queue(TrayAction::Click(id));
+        }
+    }));
+}
+
+/// Queue a tray click for the UI thread, waking it if nothing else is
+/// queued.
+fn queue(action: TrayAction) {
+    let mut actions = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    // A queue with actions in it has woken the loop already
+    let wake = actions.is_empty();
+    actions.push(action);
+    drop(actions);
+    if wake
+        && let Some(proxy) = EVENT_PROXY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+    {
+        let _ = proxy.send_event(RinchEvent::TrayEvent);
+    }
+}
+
+/// Run the callbacks of queued tray clicks (called by the runtime on
+/// [`RinchEvent::TrayEvent`]). Returns whether any ran.
+pub(crate) fn deliver() -> bool {
+    let actions = std::mem::take(&mut *ACTIONS.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut ran = false;
+    for action in actions {
+        let callback = match &action {
+            TrayAction::Menu(id) => MENU_CALLBACKS.with(|callbacks| {
+                callbacks.borrow().get(id).map(|(label, callback)| {
+                    tracing::info!("Tray menu item activated: {}", label);
+                    callback.clone()
+                })
+            }),
+            TrayAction::Click(id) => {
+                CLICK_CALLBACKS.with(|callbacks| callbacks.borrow().get(id).cloned())
+            }
+        };
+        if let Some(callback) = callback {
+            callback();
+            ran = true;
+        }
+    }
+    ran
+}
+
 /// A system tray icon with optional menu.
+///
+/// The icon shows for as long as the `TrayIcon` (or a clone of it) is
+/// kept; dropping the last one removes it. Clones share the icon.
+#[derive(Clone)]
 pub struct TrayIcon {
-    _inner: TrayIconInner,
-    menu_items: Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>,
+    state: Rc<TrayState>,
+}
+
+struct TrayState {
+    inner: TrayIconInner,
+    menu_ids: Vec<MenuId>,
 }
 
-/// Callback type for tray menu items.
-pub type TrayCallback = Box<dyn Fn() + Send + Sync>;
+impl Drop for TrayState {
+    fn drop(&mut self) {
+        MENU_CALLBACKS.with(|callbacks| {
+            let mut callbacks = callbacks.borrow_mut();
+            for id in &self.menu_ids {
+                callbacks.remove(id);
+            }
+        });
+        CLICK_CALLBACKS.with(|callbacks| callbacks.borrow_mut().remove(self.inner.id()));
+    }
+}
 
 impl TrayIcon {
-    /// Poll for tray menu events and invoke callbacks.
+    /// Change the tooltip shown on hover, or remove it with `None`.
+    pub fn set_tooltip(&self, tooltip: Option<&str>) -> TrayResult<()> {
+        self.state.inner.set_tooltip(tooltip)?;
+        Ok(())
+    }
+
+    /// Change the icon to RGBA pixel data (4 bytes per pixel), such as to
+    /// show a status or an unread count.
+    pub fn set_icon_rgba(&self, rgba: Vec<u8>, width: u32, height: u32) -> TrayResult<()> {
+        self.state
+            .inner
+            .set_icon(Some(Icon::from_rgba(rgba, width, height)?))?;
+        Ok(())
+    }
+
+    /// Change the icon to an image file (PNG, ICO, etc.).
+    pub fn set_icon_path(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        size: Option<(u32, u32)>,
+    ) -> TrayResult<()> {
+        self.state
+            .inner
+            .set_icon(Some(Icon::from_path(path, size)?))?;
+        Ok(())
+    }
+
+    /// Show or hide the icon, keeping it to show again.
+    pub fn set_visible(&self, visible: bool) -> TrayResult<()> {
+        self.state.inner.set_visible(visible)?;
+        Ok(())
+    }
+
+    /// Poll for tray events and invoke callbacks.
     ///
-    /// Call this periodically (e.g., in your event loop's about_to_wait).
+    /// Apps started with rinch don't need this: the event loop delivers
+    /// tray events itself. It's for using a tray icon outside of it.
     pub fn poll_events(&self) {
-        while let Ok(event) = MenuEvent::receiver().try_recv() {
-            for (id, label, callback) in &self.menu_items {
-                if event.id() == id {
-                    tracing::info!("Tray menu item activated: {}", label);
-                    if let Some(cb) = callback {
-                        cb();
-                    }
+        {
+            let mut actions = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+            while let Ok(event) = MenuEvent::receiver().try_recv() {
+                actions.push(TrayAction::Menu(event.id));
+            }
+            while let Ok(event) = TrayIconEvent::receiver().try_recv() {
+                if let TrayIconEvent::Click {
+                    id,
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    actions.push(TrayAction::Click(id));
                 }
             }
         }
+        deliver();
     }
 }
 
@@ -102,6 +269,7 @@ pub struct TrayIconBuilder {
     tooltip: Option<String>,
     icon: Option<Icon>,
     menu: Option<TrayMenu>,
+    on_click: Option<TrayCallback>,
 }
 
 impl TrayIconBuilder {
@@ -111,6 +279,7 @@ impl TrayIconBuilder {
             tooltip: None,
             icon: None,
             menu: None,
+            on_click: None,
         }
     }
 
@@ -150,7 +319,19 @@ impl TrayIconBuilder {
         self
     }
 
+    /// Set the callback to invoke when the icon is left-clicked, such as
+    /// [`toggle_main_window`](crate::windows::toggle_main_window). The menu
+    /// then opens on right-click only. Linux doesn't report clicks on tray
+    /// icons, so give apps there a menu item for the same thing.
+    pub fn on_click<F: Fn() + 'static>(mut self, callback: F) -> Self {
+        self.on_click = Some(Rc::new(callback));
+        self
+    }
+
     /// Build the tray icon.
+    ///
+    /// On macOS the icon must be built once the event loop is running, such
+    /// as while rendering the app's first frame.
     pub fn build(self) -> TrayResult<TrayIcon> {
         let mut builder = TrayIconBuilderInner::new();
 
@@ -170,11 +351,28 @@ impl TrayIconBuilder {
             Vec::new()
         };
 
+        if self.on_click.is_some() {
+            builder = builder.with_menu_on_left_click(false);
+        }
+
         let inner = builder.build()?;
 
+        let menu_ids = menu_items.iter().map(|(id, _, _)| id.clone()).collect();
+        MENU_CALLBACKS.with(|callbacks| {
+            let mut callbacks = callbacks.borrow_mut();
+            for (id, label, callback) in menu_items {
+                if let Some(callback) = callback {
+                    callbacks.insert(id, (label, callback));
+                }
+            }
+        });
+        if let Some(callback) = self.on_click {
+            CLICK_CALLBACKS
+                .with(|callbacks| callbacks.borrow_mut().insert(inner.id().clone(), callback));
+        }
+
         Ok(TrayIcon {
-            _inner: inner,
-            menu_items,
+            state: Rc::new(TrayState { inner, menu_ids }),
         })
     }
 }
@@ -187,7 +385,7 @@ impl Default for TrayIconBuilder {
 
 /// A menu for the system tray.
 pub struct TrayMenu {
-    items: Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>,
+    items: Vec<(MenuId, String, Option<TrayCallback>)>,
     menu_items: Vec<TrayMenuEntry>,
 }
 
@@ -238,7 +436,7 @@ impl TrayMenu {
         self
     }
 
-    fn build(mut self) -> TrayResult<(Menu, Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>)> {
+    fn build(mut self) -> TrayResult<(Menu, Vec<(MenuId, String, Option<TrayCallback>)>)> {
         let menu = Menu::new();
 
         for entry in self.menu_items {
@@ -270,7 +468,10 @@ impl TrayMenu {
     }
 
     /// Build menu items directly into a submenu.
-    fn build_into(mut self, submenu: &Submenu) -> TrayResult<Vec<(tray_icon::menu::MenuId, String, Option<TrayCallback>)>> {
+    fn build_into(
+        mut self,
+        submenu: &Submenu,
+    ) -> TrayResult<Vec<(MenuId, String, Option<TrayCallback>)>> {
         for entry in self.menu_items {
             match entry {
                 TrayMenuEntry::Item {
@@ -328,8 +529,8 @@ impl TrayMenuItem {
     }
 
     /// Set the callback to invoke when the item is clicked.
-    pub fn on_click<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
-        self.callback = Some(Box::new(callback));
+    pub fn on_click<F: Fn() + 'static>(mut self, callback: F) -> Self {
+        self.callback = Some(Rc::new(callback));
         self
     }
 }
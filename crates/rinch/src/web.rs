@@ -0,0 +1,256 @@
+//! Browser backend.
+//!
+//! Built for `wasm32` with the `web` feature, rinch renders the same
+//! `rsx!` tree into a web page instead of a native window, so an app can
+//! ship as a desktop app and as an interactive demo. The first `Window`'s
+//! content goes into the page's `<div id="rinch">` (or its `<body>`), and
+//! its title becomes the page title:
+//!
+//! ```ignore
+//! use rinch::prelude::*;
+//! use wasm_bindgen::prelude::*;
+//!
+//! #[wasm_bindgen(start)]
+//! pub fn start() {
+//!     rinch::run(app);
+//! }
+//! ```
+//!
+//! The browser does the layout and painting, so pages look as they would
+//! in a browser rather than exactly as in the desktop renderer. This is a
+//! first cut: `onclick` handlers, timers, animation frames and
+//! `spawn_local` tasks work; menus, the window control functions,
+//! `onscroll`, node refs and the desktop-only modules (dialogs, tray,
+//! storage, fetch and so on) don't exist here. Content is replaced
+//! wholesale when it changes, so focus and scroll positions inside it
+//! aren't kept across renders.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use rinch_core::animation::{animation_frame_requested, run_animation_frame};
+use rinch_core::clock::{self, next_timer, run_timers};
+use rinch_core::element::{children_to_html, Element, WindowProps};
+use rinch_core::events::{clear_handlers, dispatch_event, EventHandlerId};
+use rinch_core::hooks::{begin_render, clear_hooks, end_render, run_effects};
+use rinch_core::portal::hoist_portals;
+use rinch_core::power::record_user_input;
+use rinch_core::task::{poll_tasks, set_wake_handler};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::Event;
+
+/// Id of the element [`run`] renders into, if the page has one.
+pub const ROOT_ID: &str = "rinch";
+
+thread_local! {
+    static APP: RefCell<Option<Rc<WebApp>>> = const { RefCell::new(None) };
+}
+
+/// An app running in the page.
+struct WebApp {
+    app: Box<dyn Fn() -> Element>,
+    /// The element the app renders into.
+    root: web_sys::Element,
+    /// The HTML and title last put in the page.
+    html: RefCell<String>,
+    title: RefCell<String>,
+    /// The `setTimeout` waiting for the next timer.
+    timer: Cell<Option<i32>>,
+    /// Whether an animation frame has been asked for.
+    frame_pending: Cell<bool>,
+}
+
+/// Run the application in the page, inside `<div id="rinch">`, or the
+/// whole `<body>` if there's none.
+///
+/// # Panics
+///
+/// If there's no page, as in a web worker.
+pub fn run<F>(app: F)
+where
+    F: Fn() -> Element + 'static,
+{
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("rinch::run needs a web page to render into");
+    let root = document
+        .get_element_by_id(ROOT_ID)
+        .or_else(|| document.body().map(Into::into))
+        .expect("the page has no body to render into");
+    mount(app, root);
+}
+
+/// Run the application inside `root`, replacing its contents.
+pub fn mount<F>(app: F, root: web_sys::Element)
+where
+    F: Fn() -> Element + 'static,
+{
+    clear_handlers();
+    clear_hooks();
+
+    let on_click = Closure::<dyn FnMut(Event)>::new(on_click);
+    if let Err(e) =
+        root.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())
+    {
+        tracing::error!("Couldn't listen for clicks: {:?}", e);
+    }
+    // The listener lives as long as the page
+    on_click.forget();
+
+    let web_app = WebApp {
+        app: Box::new(app),
+        root,
+        html: RefCell::new(String::new()),
+        title: RefCell::new(String::new()),
+        timer: Cell::new(None),
+        frame_pending: Cell::new(false),
+    };
+    APP.with(|current| *current.borrow_mut() = Some(Rc::new(web_app)));
+    set_wake_handler(wake_tasks);
+    render();
+}
+
+/// The running app, if any.
+fn current() -> Option<Rc<WebApp>> {
+    APP.with(|current| current.borrow().clone())
+}
+
+/// Run the `onclick` for a click: the nearest one at or above its target.
+fn on_click(event: Event) {
+    let handler_id = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .and_then(|target| target.closest("[data-rid]").ok().flatten())
+        .and_then(|element| element.get_attribute("data-rid"))
+        .and_then(|rid| rid.parse().ok());
+    let was_idle = record_user_input();
+    let called = handler_id.is_some_and(|id| dispatch_event(EventHandlerId(id)));
+    updated(called || was_idle);
+}
+
+/// Poll woken `spawn_local` tasks once the current event is handled.
+fn wake_tasks() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let callback = Closure::once_into_js(|| updated(poll_tasks()));
+    if let Err(e) = window.set_timeout_with_callback(callback.unchecked_ref()) {
+        tracing::warn!("Couldn't schedule woken tasks: {:?}", e);
+    }
+}
+
+/// Re-render if something changed, or else just wait for what's next.
+fn updated(changed: bool) {
+    if changed {
+        render();
+    } else if let Some(app) = current() {
+        app.schedule();
+    }
+}
+
+/// Re-run the app function and put its first window's content in the page.
+fn render() {
+    let Some(app) = current() else {
+        return;
+    };
+    clear_handlers();
+    begin_render();
+    let root = (app.app)();
+    end_render();
+
+    match first_window(root) {
+        Some((props, html)) => app.show(&props, html),
+        None => tracing::warn!("The app stopped rendering a Window; keeping the last content"),
+    }
+    if run_effects() {
+        render_soon();
+    }
+    app.schedule();
+}
+
+/// Re-render once the current render is done, for state `use_effect`
+/// effects changed.
+fn render_soon() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let callback = Closure::once_into_js(render);
+    if let Err(e) = window.set_timeout_with_callback(callback.unchecked_ref()) {
+        tracing::warn!("Couldn't schedule a re-render: {:?}", e);
+    }
+}
+
+/// The first window in the tree, with its HTML.
+fn first_window(root: Element) -> Option<(WindowProps, String)> {
+    let mut stack = vec![root];
+    while let Some(element) = stack.pop() {
+        match element {
+            Element::Window(props, children) => {
+                let html = hoist_portals(&children_to_html(&children)).into_owned();
+                return Some((props, html));
+            }
+            Element::Fragment(children) => stack.extend(children.into_iter().rev()),
+            _ => {}
+        }
+    }
+    None
+}
+
+impl WebApp {
+    /// Put the window's title and content in the page, if they changed.
+    fn show(&self, props: &WindowProps, html: String) {
+        if *self.title.borrow() != props.title {
+            if let Some(document) = self.root.owner_document() {
+                document.set_title(&props.title);
+            }
+            *self.title.borrow_mut() = props.title.clone();
+        }
+        if *self.html.borrow() != html {
+            self.root.set_inner_html(&html);
+            *self.html.borrow_mut() = html;
+        }
+    }
+
+    /// Wake up for the next timer and, if anything asked for one, the next
+    /// animation frame.
+    fn schedule(&self) {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+
+        if let Some(handle) = self.timer.take() {
+            window.clear_timeout_with_handle(handle);
+        }
+        if let Some(deadline) = next_timer() {
+            let delay = deadline.saturating_duration_since(clock::now());
+            let callback = Closure::once_into_js(|| {
+                if let Some(app) = current() {
+                    app.timer.set(None);
+                }
+                updated(run_timers(clock::now()));
+            });
+            let delay = i32::try_from(delay.as_millis()).unwrap_or(i32::MAX);
+            match window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.unchecked_ref(),
+                delay,
+            ) {
+                Ok(handle) => self.timer.set(Some(handle)),
+                Err(e) => tracing::warn!("Couldn't set a timeout for the next timer: {:?}", e),
+            }
+        }
+
+        if animation_frame_requested() && !self.frame_pending.replace(true) {
+            let callback = Closure::once_into_js(|| {
+                if let Some(app) = current() {
+                    app.frame_pending.set(false);
+                }
+                updated(run_animation_frame(clock::now()));
+            });
+            if let Err(e) = window.request_animation_frame(callback.unchecked_ref()) {
+                tracing::warn!("Couldn't request an animation frame: {:?}", e);
+                self.frame_pending.set(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! WebSocket connections.
+//!
+//! [`use_websocket`] keeps a connection open for as long as the component
+//! calling it is rendered, reconnecting when it drops:
+//!
+//! ```ignore
+//! use rinch::websocket::{use_websocket, ConnectionState};
+//!
+//! fn ticker() -> Element {
+//!     let prices = use_signal(Vec::<String>::new);
+//!     let socket = use_websocket("wss://example.com/prices");
+//!     for message in socket.received() {
+//!         if let Some(price) = message.as_text() {
+//!             prices.update(|prices| prices.push(price.to_string()));
+//!         }
+//!     }
+//!     let status = match socket.state() {
+//!         ConnectionState::Open => "Live".to_string(),
+//!         ConnectionState::Reconnecting { delay, .. } => {
+//!             format!("Reconnecting in {}s", delay.as_secs())
+//!         }
+//!         _ => "Connecting...".to_string(),
+//!     };
+//!     let subscribe = {
+//!         let socket = socket.clone();
+//!         move || {
+//!             let _ = socket.send("subscribe BTC");
+//!         }
+//!     };
+//!     rsx! {
+//!         p { {status} }
+//!         button { onclick: subscribe, "Subscribe" }
+//!     }
+//! }
+//! ```
+//!
+//! - Messages arrive on the UI thread: [`WebSocket::received`] returns
+//!   those that came in since it was last called, and the app re-renders
+//!   when more do.
+//! - After the connection drops (or fails to open), it's retried after
+//!   half a second, doubling each failed attempt up to 30 seconds.
+//! - A new URL closes the old connection and opens one to the new URL, and
+//!   the connection is closed when its component goes away.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use rinch_core::{untracked, use_ref, Signal};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::Message as WireMessage;
+use winit::event_loop::EventLoopProxy;
+
+use crate::shell::RinchEvent;
+
+/// The wait before the first reconnection attempt.
+const FIRST_RETRY: Duration = Duration::from_millis(500);
+/// The longest wait between reconnection attempts.
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+/// A WebSocket message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Message {
+    /// The text, for a text message.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Binary(_) => None,
+        }
+    }
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Message {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<Vec<u8>> for Message {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Binary(data)
+    }
+}
+
+impl From<Message> for WireMessage {
+    fn from(message: Message) -> Self {
+        match message {
+            Message::Text(text) => Self::Text(text),
+            Message::Binary(data) => Self::Binary(data),
+        }
+    }
+}
+
+/// Where a [`use_websocket`] connection stands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    /// Opening the first connection.
+    Connecting,
+    /// Connected: messages can be sent.
+    Open,
+    /// The connection dropped, or couldn't be opened; attempt `attempt`
+    /// (from 1) to reopen it starts after `delay`.
+    Reconnecting { attempt: u32, delay: Duration },
+    /// Closed with [`WebSocket::close`], or never opened because the app
+    /// isn't running.
+    Closed,
+}
+
+/// [`WebSocket::send`] was called while the connection isn't open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotConnected;
+
+impl fmt::Display for NotConnected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the WebSocket isn't connected")
+    }
+}
+
+impl std::error::Error for NotConnected {}
+
+/// What a connection task reports to the UI thread.
+#[derive(Debug)]
+enum SocketEvent {
+    Opened,
+    Received(Message),
+    Dropped { attempt: u32, delay: Duration },
+}
+
+thread_local! {
+    /// Open connections, by ID.
+    static SOCKETS: RefCell<HashMap<u64, Weak<SocketState>>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Connection events waiting for the event loop.
+static EVENTS: Mutex<Vec<(u64, SocketEvent)>> = Mutex::new(Vec::new());
+
+/// A connection made by [`use_websocket`]. Cheap to clone; clones share it.
+#[derive(Clone)]
+pub struct WebSocket {
+    state: Rc<SocketState>,
+}
+
+impl WebSocket {
+    /// Where the connection stands. Reactive.
+    pub fn state(&self) -> ConnectionState {
+        self.state.status.get()
+    }
+
+    /// Whether the connection is open.
+    pub fn is_open(&self) -> bool {
+        self.state() == ConnectionState::Open
+    }
+
+    /// The messages received since this was last called, oldest first.
+    /// Reactive: the app re-renders when messages arrive.
+    pub fn received(&self) -> Vec<Message> {
+        self.state.arrivals.get();
+        self.state.received.take()
+    }
+
+    /// Send a message, if the connection is open.
+    pub fn send(&self, message: impl Into<Message>) -> Result<(), NotConnected> {
+        if untracked(|| self.state()) != ConnectionState::Open {
+            return Err(NotConnected);
+        }
+        let connection = self.state.connection.borrow();
+        let connection = connection.as_ref().ok_or(NotConnected)?;
+        connection
+            .outgoing
+            .send(message.into())
+            .map_err(|_| NotConnected)
+    }
+
+    /// Close the connection and stop reconnecting. It's opened again if the
+    /// URL changes.
+    pub fn close(&self) {
+        self.state.close();
+    }
+}
+
+/// Connect to the WebSocket at `url`, staying connected while the calling
+/// component is rendered. When `url` changes, the old connection is closed
+/// and a new one opened.
+///
+/// This is a hook: call it at the top level of a component.
+pub fn use_websocket(url: impl Into<String>) -> WebSocket {
+    let state = use_ref(|| Rc::new(SocketState::new())).get();
+    let url = url.into();
+    if state.url.borrow().as_ref() != Some(&url) {
+        state.url.replace(Some(url.clone()));
+        state.connect(url);
+    }
+    WebSocket { state }
+}
+
+/// One [`use_websocket`]'s state.
+struct SocketState {
+    url: RefCell<Option<String>>,
+    connection: RefCell<Option<Connection>>,
+    status: Signal<ConnectionState>,
+    /// Messages not yet returned by [`WebSocket::received`].
+    received: RefCell<Vec<Message>>,
+    /// Changed when messages arrive.
+    arrivals: Signal<u64>,
+}
+
+/// The current connection of a [`use_websocket`].
+struct Connection {
+    id: u64,
+    /// The task keeping it open, once started.
+    task: Option<AbortHandle>,
+    /// Where to put messages to send on it.
+    outgoing: UnboundedSender<Message>,
+}
+
+impl SocketState {
+    fn new() -> Self {
+        Self {
+            url: RefCell::new(None),
+            connection: RefCell::new(None),
+            status: Signal::new(ConnectionState::Connecting),
+            received: RefCell::new(Vec::new()),
+            arrivals: Signal::new(0),
+        }
+    }
+
+    /// Open a connection to `url` in place of the current one.
+    fn connect(self: &Rc<Self>, url: String) {
+        let (id, outgoing) = self.begin();
+        let Some(proxy) = crate::windows::event_proxy() else {
+            self.close();
+            return;
+        };
+        let task = crate::net::runtime()
+            .spawn(run(id, url, outgoing, proxy))
+            .abort_handle();
+        if let Some(connection) = &mut *self.connection.borrow_mut()
+            && connection.id == id
+        {
+            connection.task = Some(task);
+        }
+    }
+
+    /// Note a new connection, closing the current one, and return its ID
+    /// and the receiver of messages to send on it.
+    fn begin(self: &Rc<Self>) -> (u64, UnboundedReceiver<Message>) {
+        self.abort();
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        let (sender, outgoing) = unbounded_channel();
+        SOCKETS.with(|sockets| sockets.borrow_mut().insert(id, Rc::downgrade(self)));
+        self.connection.replace(Some(Connection {
+            id,
+            task: None,
+            outgoing: sender,
+        }));
+        self.set_status(ConnectionState::Connecting);
+        (id, outgoing)
+    }
+
+    fn close(&self) {
+        self.abort();
+        self.set_status(ConnectionState::Closed);
+    }
+
+    /// Stop the connection task, if any, and forget it.
+    fn abort(&self) {
+        if let Some(connection) = self.connection.take() {
+            if let Some(task) = connection.task {
+                task.abort();
+            }
+            let _ = SOCKETS.try_with(|sockets| sockets.borrow_mut().remove(&connection.id));
+        }
+    }
+
+    fn set_status(&self, status: ConnectionState) {
+        if untracked(|| self.status.get()) != status {
+            self.status.set(status);
+        }
+    }
+
+    fn apply(&self, event: SocketEvent) {
+        match event {
+            SocketEvent::Opened => self.set_status(ConnectionState::Open),
+            SocketEvent::Received(message) => {
+                self.received.borrow_mut().push(message);
+                self.arrivals.update(|count| *count += 1);
+            }
+            SocketEvent::Dropped { attempt, delay } => {
+                self.set_status(ConnectionState::Reconnecting { attempt, delay })
+            }
+        }
+    }
+}
+
+impl Drop for SocketState {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+/// Hand connection events to their [`use_websocket`]s (called by the
+/// runtime on [`RinchEvent::WebSocketEvent`]).
+pub(crate) fn deliver() {
+    let events = std::mem::take(&mut *EVENTS.lock().unwrap_or_else(|e| e.into_inner()));
+    for (id, event) in events {
+        dispatch(id, event);
+    }
+}
+
+fn dispatch(id: u64, event: SocketEvent) {
+    let state = SOCKETS.with(|sockets| sockets.borrow().get(&id).and_then(Weak::upgrade));
+    if let Some(state) = state {
+        state.apply(event);
+    }
+}
+
+/// How long to wait before reconnection attempt `attempt` (from 1).
+fn retry_delay(attempt: u32) -> Duration {
+    FIRST_RETRY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_RETRY)
+}
+
+/// Keep connection `id` to `url` open, reconnecting when it drops.
+async fn run(
+    id: u64,
+    url: String,
+    mut outgoing: UnboundedReceiver<Message>,
+    proxy: EventLoopProxy<RinchEvent>,
+) {
+    let emit = |event: SocketEvent| {
+        EVENTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, event));
+        proxy.send_event(RinchEvent::WebSocketEvent).is_ok()
+    };
+    let mut attempt = 0;
+    loop {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((stream, _)) => {
+                attempt = 0;
+                if !emit(SocketEvent::Opened) {
+                    return;
+                }
+                // Drop what was sent while disconnected
+                while outgoing.try_recv().is_ok() {}
+                let (mut write, mut read) = stream.split();
+                loop {
+                    tokio::select! {
+                        incoming = read.next() => match incoming {
+                            Some(Ok(WireMessage::Text(text))) => {
+                                if !emit(SocketEvent::Received(Message::Text(text))) {
+                                    return;
+                                }
+                            }
+                            Some(Ok(WireMessage::Binary(data))) => {
+                                if !emit(SocketEvent::Received(Message::Binary(data))) {
+                                    return;
+                                }
+                            }
+                            // Pings and closes are answered by tungstenite
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                tracing::debug!("WebSocket {url} dropped: {e}");
+                                break;
+                            }
+                            None => break,
+                        },
+                        Some(message) = outgoing.recv() => {
+                            if let Err(e) = write.send(message.into()).await {
+                                tracing::debug!("WebSocket {url} dropped: {e}");
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => tracing::debug!("Couldn't connect to WebSocket {url}: {e}"),
+        }
+        attempt += 1;
+        let delay = retry_delay(attempt);
+        if !emit(SocketEvent::Dropped { attempt, delay }) {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let delays: Vec<_> = (1..=8).map(retry_delay).collect();
+        assert_eq!(delays[0], Duration::from_millis(500));
+        assert_eq!(delays[1], Duration::from_secs(1));
+        assert_eq!(delays[3], Duration::from_secs(4));
+        assert_eq!(delays[7], MAX_RETRY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY);
+    }
+
+    #[test]
+    fn events_reach_the_current_connection() {
+        let state = Rc::new(SocketState::new());
+        let socket = WebSocket {
+            state: state.clone(),
+        };
+        let (old, _) = state.begin();
+        let (id, mut outgoing) = state.begin();
+        assert_eq!(socket.state(), ConnectionState::Connecting);
+        assert_eq!(socket.send("early"), Err(NotConnected));
+
+        dispatch(id, SocketEvent::Opened);
+        assert!(socket.is_open());
+        socket.send("hello").unwrap();
+        assert_eq!(outgoing.try_recv().ok(), Some(Message::from("hello")));
+
+        // The replaced connection's messages are dropped
+        dispatch(old, SocketEvent::Received("stale".into()));
+        dispatch(id, SocketEvent::Received("one".into()));
+        dispatch(id, SocketEvent::Received(vec![2].into()));
+        assert_eq!(
+            socket.received(),
+            vec![Message::Text("one".into()), Message::Binary(vec![2])]
+        );
+        assert!(socket.received().is_empty());
+
+        let dropped = ConnectionState::Reconnecting {
+            attempt: 1,
+            delay: retry_delay(1),
+        };
+        dispatch(
+            id,
+            SocketEvent::Dropped {
+                attempt: 1,
+                delay: retry_delay(1),
+            },
+        );
+        assert_eq!(socket.state(), dropped);
+
+        socket.close();
+        assert_eq!(socket.state(), ConnectionState::Closed);
+        dispatch(id, SocketEvent::Opened);
+        assert_eq!(socket.state(), ConnectionState::Closed);
+    }
+}
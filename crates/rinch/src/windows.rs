@@ -37,12 +37,15 @@
 //! }
 //! ```
 
-use rinch_core::element::WindowProps;
-use std::cell::RefCell;
+use rinch_core::element::{Antialiasing, Element, WindowProps};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use winit::event_loop::EventLoopProxy;
-use winit::window::WindowId;
+use winit::window::{Window, WindowId};
 
 use crate::shell::runtime::RinchEvent;
 
@@ -62,6 +65,77 @@ impl WindowHandle {
     pub fn id(&self) -> u64 {
         self.0
     }
+
+    /// Set the window's content scale (zoom), where 1.0 is 100%. This
+    /// scales the window's layout and text, on top of the display's scale
+    /// factor, and is kept in its [`WindowState::zoom`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Menu { label: "View",
+    ///     MenuItem { label: "Zoom In", shortcut: "Cmd+=", onclick: move || preview.zoom_in() }
+    ///     MenuItem { label: "Zoom Out", shortcut: "Cmd+-", onclick: move || preview.zoom_out() }
+    ///     MenuItem { label: "Actual Size", shortcut: "Cmd+0", onclick: move || preview.reset_zoom() }
+    /// }
+    /// ```
+    pub fn set_zoom(&self, factor: f32) {
+        self.request_zoom(Zoom::To(factor));
+    }
+
+    /// Zoom the window in one level: 110%, 125%, 150%, ... up to 300%.
+    pub fn zoom_in(&self) {
+        self.request_zoom(Zoom::In);
+    }
+
+    /// Zoom the window out one level, to 25% at the least.
+    pub fn zoom_out(&self) {
+        self.request_zoom(Zoom::Out);
+    }
+
+    /// Set the window's zoom back to 100%.
+    pub fn reset_zoom(&self) {
+        self.request_zoom(Zoom::Reset);
+    }
+
+    /// The window's content scale, where 1.0 is 100%, or 1.0 if it's
+    /// closed.
+    pub fn zoom(&self) -> f32 {
+        get_window_state(*self).map_or(1.0, |state| state.zoom)
+    }
+
+    /// Show the window if it was hidden, and bring it to the front.
+    pub fn show(&self) {
+        self.request_visibility(true);
+    }
+
+    /// Hide the window, keeping it open to [`show`](Self::show) again.
+    pub fn hide(&self) {
+        self.request_visibility(false);
+    }
+
+    fn request_visibility(&self, visible: bool) {
+        WINDOW_REQUESTS.with(|r| {
+            r.borrow_mut()
+                .push(WindowRequest::Visibility(VisibilityWindowRequest {
+                    handle: *self,
+                    visible,
+                }));
+        });
+        send_event(RinchEvent::ProcessWindowRequests);
+    }
+
+    fn request_zoom(&self, zoom: Zoom) {
+        WINDOW_REQUESTS.with(|r| {
+            r.borrow_mut().push(WindowRequest::Zoom(ZoomWindowRequest {
+                handle: *self,
+                zoom,
+            }));
+        });
+
+        // Trigger processing of window requests
+        send_event(RinchEvent::ProcessWindowRequests);
+    }
 }
 
 /// A request to open a new window.
@@ -73,6 +147,28 @@ pub struct OpenWindowRequest {
     pub props: WindowProps,
     /// HTML content for the window.
     pub html_content: String,
+    /// The component rendering the window's content, instead of
+    /// `html_content`.
+    pub component: Option<WindowComponent>,
+    /// A saved state to put the window back in once it's open.
+    pub restore: Option<WindowState>,
+}
+
+/// A component rendering a window's content, re-rendered along with the app.
+#[derive(Clone)]
+pub struct WindowComponent(Rc<dyn Fn() -> Element>);
+
+impl WindowComponent {
+    /// Render the window's content.
+    pub fn render(&self) -> Element {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for WindowComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WindowComponent")
+    }
 }
 
 /// A request to close a window.
@@ -82,9 +178,30 @@ pub struct CloseWindowRequest {
     pub handle: WindowHandle,
 }
 
-/// Current state of a window (position, size).
+/// A request to change a window's content scale.
+#[derive(Debug, Clone, Copy)]
+pub struct ZoomWindowRequest {
+    /// The handle of the window to zoom.
+    pub handle: WindowHandle,
+    /// The change to make.
+    pub zoom: Zoom,
+}
+
+/// A request to show or hide a window.
+#[derive(Debug, Clone, Copy)]
+pub struct VisibilityWindowRequest {
+    /// The handle of the window to show or hide.
+    pub handle: WindowHandle,
+    /// Whether to show it.
+    pub visible: bool,
+}
+
+/// Current state of a window (position, size, zoom).
 ///
-/// This can be used by applications to save and restore window state.
+/// This can be used by applications to save and restore window state: save
+/// it from [`on_window_state_change`], and reopen the window as it was with
+/// [`WindowBuilder::restore_from`]. Positions and sizes are in physical
+/// pixels.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct WindowState {
     /// X position of the window (outer position).
@@ -99,6 +216,9 @@ pub struct WindowState {
     pub maximized: bool,
     /// Whether the window is minimized.
     pub minimized: bool,
+    /// The window's content scale, where 1.0 is 100%. Pass it to
+    /// [`WindowBuilder::content_scale`] to reopen the window at this zoom.
+    pub zoom: f32,
 }
 
 impl Default for WindowState {
@@ -110,6 +230,7 @@ impl Default for WindowState {
             height: 600,
             maximized: false,
             minimized: false,
+            zoom: 1.0,
         }
     }
 }
@@ -123,13 +244,29 @@ thread_local! {
     static WINDOW_STATES: RefCell<HashMap<WindowHandle, WindowState>> = RefCell::new(HashMap::new());
     /// The window ID that is currently handling an event (set by runtime during event dispatch).
     static CURRENT_WINDOW_ID: RefCell<Option<WindowId>> = RefCell::new(None);
+    /// The window with keyboard focus, the current window outside event dispatch.
+    static FOCUSED_WINDOW_ID: RefCell<Option<WindowId>> = RefCell::new(None);
+    /// Each window's content scale, updated by the runtime.
+    static CONTENT_SCALES: RefCell<HashMap<WindowId, f32>> = RefCell::new(HashMap::new());
+    /// Each open window's native window, for dialogs to attach to.
+    static NATIVE_WINDOWS: RefCell<HashMap<WindowId, Arc<Window>>> = RefCell::new(HashMap::new());
+    /// Whether closing the last window hides it instead of quitting.
+    static HIDE_ON_CLOSE: Cell<bool> = const { Cell::new(false) };
+    /// Callbacks for changes to each window's state.
+    static STATE_CALLBACKS: RefCell<HashMap<WindowHandle, Vec<StateCallback>>> =
+        RefCell::new(HashMap::new());
 }
 
+/// A callback for changes to a window's state.
+type StateCallback = Rc<dyn Fn(WindowState)>;
+
 /// Window request types.
 #[derive(Debug, Clone)]
 pub enum WindowRequest {
     Open(OpenWindowRequest),
     Close(CloseWindowRequest),
+    Zoom(ZoomWindowRequest),
+    Visibility(VisibilityWindowRequest),
 }
 
 /// Set the event loop proxy (called by runtime during initialization).
@@ -139,16 +276,44 @@ pub(crate) fn set_event_proxy(proxy: EventLoopProxy<RinchEvent>) {
     });
 }
 
+/// The event loop proxy, if the runtime is running, for background work to
+/// wake it.
+pub(crate) fn event_proxy() -> Option<EventLoopProxy<RinchEvent>> {
+    EVENT_PROXY.with(|p| p.borrow().clone())
+}
+
+/// Send an event to the runtime, if it's running.
+pub(crate) fn send_event(event: RinchEvent) {
+    EVENT_PROXY.with(|p| {
+        if let Some(proxy) = p.borrow().as_ref() {
+            let _ = proxy.send_event(event);
+        }
+    });
+}
+
 /// Take all pending window requests (called by runtime).
 pub(crate) fn take_window_requests() -> Vec<WindowRequest> {
     WINDOW_REQUESTS.with(|r| r.borrow_mut().drain(..).collect())
 }
 
 /// Update window state (called by runtime when window is moved/resized).
+///
+/// If the state changed, the window's state callbacks run, and the app
+/// re-renders after them.
 pub(crate) fn update_window_state(handle: WindowHandle, state: WindowState) {
-    WINDOW_STATES.with(|s| {
-        s.borrow_mut().insert(handle, state);
-    });
+    let previous = WINDOW_STATES.with(|s| s.borrow_mut().insert(handle, state));
+    if previous == Some(state) {
+        return;
+    }
+
+    // Cloned out, so a callback can register another
+    let callbacks = STATE_CALLBACKS.with(|c| c.borrow().get(&handle).cloned().unwrap_or_default());
+    for callback in &callbacks {
+        callback(state);
+    }
+    if !callbacks.is_empty() {
+        send_event(RinchEvent::ReRender);
+    }
 }
 
 /// Remove window state (called by runtime when window is closed).
@@ -156,6 +321,9 @@ pub(crate) fn remove_window_state(handle: WindowHandle) {
     WINDOW_STATES.with(|s| {
         s.borrow_mut().remove(&handle);
     });
+    STATE_CALLBACKS.with(|c| {
+        c.borrow_mut().remove(&handle);
+    });
 }
 
 /// Set the current window ID (called by runtime during event dispatch).
@@ -165,9 +333,65 @@ pub(crate) fn set_current_window_id(window_id: Option<WindowId>) {
     });
 }
 
-/// Get the current window ID (if any).
+/// Set the focused window ID (called by runtime when focus changes).
+pub(crate) fn set_focused_window_id(window_id: Option<WindowId>) {
+    FOCUSED_WINDOW_ID.with(|id| {
+        *id.borrow_mut() = window_id;
+    });
+}
+
+/// Forget the focused window if it's `window_id` (it lost focus or closed).
+pub(crate) fn unset_focused_window_id(window_id: WindowId) {
+    FOCUSED_WINDOW_ID.with(|id| {
+        let mut id = id.borrow_mut();
+        if *id == Some(window_id) {
+            *id = None;
+        }
+    });
+}
+
+/// Get the current window ID (if any): the window handling an event, else
+/// the focused window, for menu callbacks and rendering.
 pub(crate) fn get_current_window_id() -> Option<WindowId> {
-    CURRENT_WINDOW_ID.with(|id| *id.borrow())
+    CURRENT_WINDOW_ID
+        .with(|id| *id.borrow())
+        .or_else(|| FOCUSED_WINDOW_ID.with(|id| *id.borrow()))
+}
+
+/// Record a window's content scale (called by the window when it changes).
+pub(crate) fn update_content_scale(window_id: WindowId, scale: f32) {
+    CONTENT_SCALES.with(|s| {
+        s.borrow_mut().insert(window_id, scale);
+    });
+}
+
+/// Forget a closed window's content scale.
+pub(crate) fn remove_content_scale(window_id: WindowId) {
+    CONTENT_SCALES.with(|s| {
+        s.borrow_mut().remove(&window_id);
+    });
+}
+
+/// Record a new window's native window (called by the window manager).
+pub(crate) fn record_native_window(window: Arc<Window>) {
+    NATIVE_WINDOWS.with(|w| {
+        w.borrow_mut().insert(window.id(), window);
+    });
+}
+
+/// Forget a closed window's native window.
+pub(crate) fn remove_native_window(window_id: WindowId) {
+    NATIVE_WINDOWS.with(|w| {
+        w.borrow_mut().remove(&window_id);
+    });
+}
+
+/// The current window's native window (see [`get_current_window_id`]), for
+/// dialogs to be modal to.
+#[cfg_attr(not(feature = "file-dialogs"), allow(dead_code))]
+pub(crate) fn current_native_window() -> Option<Arc<Window>> {
+    let window_id = get_current_window_id()?;
+    NATIVE_WINDOWS.with(|w| w.borrow().get(&window_id).cloned())
 }
 
 /// Get the current state of a window.
@@ -203,6 +427,34 @@ pub fn get_all_window_states() -> Vec<(WindowHandle, WindowState)> {
     })
 }
 
+/// Call `callback` with a window's state whenever it's moved, resized,
+/// maximized, minimized or zoomed, and once with its first state when it
+/// opens (if registered before then). The callback is dropped when the
+/// window closes.
+///
+/// Use it to persist window layouts without polling [`get_window_state`].
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::windows::{on_window_state_change, WindowBuilder};
+///
+/// let handle = WindowBuilder::new()
+///     .title("Editor")
+///     .restore_from(config.editor_window.unwrap_or_default())
+///     .component(editor)
+///     .open();
+/// on_window_state_change(handle, |state| save_to_config("editor_window", state));
+/// ```
+pub fn on_window_state_change(handle: WindowHandle, callback: impl Fn(WindowState) + 'static) {
+    STATE_CALLBACKS.with(|c| {
+        c.borrow_mut()
+            .entry(handle)
+            .or_default()
+            .push(Rc::new(callback));
+    });
+}
+
 /// Open a new window with the given properties and HTML content.
 ///
 /// Returns a `WindowHandle` that can be used to close the window later.
@@ -224,6 +476,58 @@ pub fn get_all_window_states() -> Vec<(WindowHandle, WindowState)> {
 /// );
 /// ```
 pub fn open_window(props: WindowProps, html_content: String) -> WindowHandle {
+    request_open(props, html_content, None, None)
+}
+
+/// Open a new window whose content is rendered by a component.
+///
+/// The component is rendered like the app function: its signals, hooks and
+/// event handlers work as they do in the main window, and it re-renders
+/// whenever the app does. Its hooks are its own, so windows can be opened
+/// and closed in any order, and it sees the contexts the app created.
+///
+/// Returns a `WindowHandle` that can be used to close the window later.
+///
+/// # Example
+///
+/// ```ignore
+/// use rinch::windows::open_window_with;
+///
+/// fn settings() -> Element {
+///     // Created by the app with `create_context`
+///     let dark_mode = use_context::<Signal<bool>>().unwrap();
+///     rsx! {
+///         button {
+///             onclick: move || dark_mode.update(|dark| *dark = !*dark),
+///             {if dark_mode.get() { "Use light mode" } else { "Use dark mode" }}
+///         }
+///     }
+/// }
+///
+/// button {
+///     onclick: || {
+///         open_window_with(
+///             WindowProps { title: "Settings".into(), width: 400, height: 300, ..Default::default() },
+///             settings,
+///         );
+///     },
+///     "Settings..."
+/// }
+/// ```
+pub fn open_window_with(
+    props: WindowProps,
+    component: impl Fn() -> Element + 'static,
+) -> WindowHandle {
+    let component = WindowComponent(Rc::new(component));
+    request_open(props, String::new(), Some(component), None)
+}
+
+fn request_open(
+    props: WindowProps,
+    html_content: String,
+    component: Option<WindowComponent>,
+    restore: Option<WindowState>,
+) -> WindowHandle {
     let handle = WindowHandle::new();
 
     WINDOW_REQUESTS.with(|r| {
@@ -231,15 +535,13 @@ pub fn open_window(props: WindowProps, html_content: String) -> WindowHandle {
             handle,
             props,
             html_content,
+            component,
+            restore,
         }));
     });
 
     // Trigger processing of window requests
-    EVENT_PROXY.with(|p| {
-        if let Some(proxy) = p.borrow().as_ref() {
-            let _ = proxy.send_event(RinchEvent::ProcessWindowRequests);
-        }
-    });
+    send_event(RinchEvent::ProcessWindowRequests);
 
     handle
 }
@@ -261,11 +563,7 @@ pub fn close_window(handle: WindowHandle) {
     });
 
     // Trigger processing of window requests
-    EVENT_PROXY.with(|p| {
-        if let Some(proxy) = p.borrow().as_ref() {
-            let _ = proxy.send_event(RinchEvent::ProcessWindowRequests);
-        }
-    });
+    send_event(RinchEvent::ProcessWindowRequests);
 }
 
 /// Open a window using a builder pattern.
@@ -280,12 +578,15 @@ pub fn close_window(handle: WindowHandle) {
 ///     .size(400, 300)
 ///     .position(100, 100)
 ///     .resizable(false)
-///     .content("<div>Settings</div>")
+///     .component(settings)
 ///     .open();
 /// ```
 pub struct WindowBuilder {
     props: WindowProps,
     html_content: String,
+    component: Option<WindowComponent>,
+    restore: Option<WindowState>,
+    state_callbacks: Vec<StateCallback>,
 }
 
 impl WindowBuilder {
@@ -294,6 +595,9 @@ impl WindowBuilder {
         Self {
             props: WindowProps::default(),
             html_content: String::new(),
+            component: None,
+            restore: None,
+            state_callbacks: Vec::new(),
         }
     }
 
@@ -341,15 +645,88 @@ impl WindowBuilder {
         self
     }
 
+    /// Set whether the window starts from the structural reset stylesheet
+    /// instead of the default user-agent styles.
+    pub fn reset(mut self, reset: bool) -> Self {
+        self.props.reset = reset;
+        self
+    }
+
+    /// Set whether the window's content is laid out right-to-left.
+    pub fn rtl(mut self, rtl: bool) -> Self {
+        self.props.rtl = rtl;
+        self
+    }
+
+    /// Set how the window's content is antialiased.
+    pub fn antialiasing(mut self, antialiasing: Antialiasing) -> Self {
+        self.props.antialiasing = antialiasing;
+        self
+    }
+
+    /// Draw thin scrollbars over the content that fade out when idle.
+    pub fn overlay_scrollbars(mut self, overlay: bool) -> Self {
+        self.props.overlay_scrollbars = overlay;
+        self
+    }
+
+    /// Let scrolled content stretch past its edges and spring back.
+    pub fn overscroll_bounce(mut self, bounce: bool) -> Self {
+        self.props.overscroll_bounce = bounce;
+        self
+    }
+
+    /// Set the content scale (zoom), where 1.0 is 100%.
+    pub fn content_scale(mut self, scale: f32) -> Self {
+        self.props.content_scale = scale;
+        self
+    }
+
     /// Set the HTML content of the window.
     pub fn content(mut self, html: impl Into<String>) -> Self {
         self.html_content = html.into();
         self
     }
 
+    /// Render the window's content with a component, instead of static
+    /// HTML. See [`open_window_with`].
+    pub fn component(mut self, component: impl Fn() -> Element + 'static) -> Self {
+        self.component = Some(WindowComponent(Rc::new(component)));
+        self
+    }
+
+    /// Open the window as a saved [`WindowState`] left it: at its position
+    /// and size, maximized or minimized, and at its zoom.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let handle = WindowBuilder::new()
+    ///     .title("Editor")
+    ///     .restore_from(saved_state)
+    ///     .on_state_change(|state| save_to_config("editor_window", state))
+    ///     .open();
+    /// ```
+    pub fn restore_from(mut self, state: WindowState) -> Self {
+        self.props.content_scale = state.zoom;
+        self.restore = Some(state);
+        self
+    }
+
+    /// Call `callback` whenever the window's state changes. See
+    /// [`on_window_state_change`].
+    pub fn on_state_change(mut self, callback: impl Fn(WindowState) + 'static) -> Self {
+        self.state_callbacks.push(Rc::new(callback));
+        self
+    }
+
     /// Open the window and return a handle.
     pub fn open(self) -> WindowHandle {
-        open_window(self.props, self.html_content)
+        let handle = request_open(self.props, self.html_content, self.component, self.restore);
+        if !self.state_callbacks.is_empty() {
+            STATE_CALLBACKS.with(|c| c.borrow_mut().insert(handle, self.state_callbacks));
+        }
+        handle
     }
 }
 
@@ -422,3 +799,147 @@ pub fn close_current_window() {
         });
     }
 }
+
+/// Quit the app, as closing its last window would: its `on_before_quit`
+/// handlers run first and can cancel.
+///
+/// # Example
+///
+/// ```ignore
+/// MenuItem { label: "Quit", shortcut: "Ctrl+Q", onclick: || quit() }
+/// ```
+pub fn quit() {
+    EVENT_PROXY.with(|p| {
+        if let Some(proxy) = p.borrow().as_ref() {
+            let _ = proxy.send_event(RinchEvent::Quit);
+        }
+    });
+}
+
+/// Keep the app running when its last window is closed: the window is
+/// hidden instead, to be brought back with [`show_main_window`], such as
+/// from a tray icon. [`quit`] still quits.
+pub fn set_hide_on_close(hide: bool) {
+    HIDE_ON_CLOSE.with(|h| h.set(hide));
+}
+
+/// Whether closing the last window hides it (see [`set_hide_on_close`]).
+pub(crate) fn hides_on_close() -> bool {
+    HIDE_ON_CLOSE.with(Cell::get)
+}
+
+/// Show the app's main window if it's hidden or minimized, and bring it to
+/// the front.
+///
+/// # Example
+///
+/// ```ignore
+/// TrayMenuItem::new("Open").on_click(show_main_window)
+/// ```
+pub fn show_main_window() {
+    send_event(RinchEvent::SetMainWindowVisible(Some(true)));
+}
+
+/// Hide the app's main window, leaving the app running.
+pub fn hide_main_window() {
+    send_event(RinchEvent::SetMainWindowVisible(Some(false)));
+}
+
+/// Show the app's main window if it's hidden, or hide it if it's showing,
+/// as clicking a tray icon usually does.
+pub fn toggle_main_window() {
+    send_event(RinchEvent::SetMainWindowVisible(None));
+}
+
+// =============================================================================
+// Content Scale (for the current window)
+// =============================================================================
+
+/// A change to a window's content scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zoom {
+    /// One level up: 110%, 125%, 150%, ... up to 300%.
+    In,
+    /// One level down, to 25% at the least.
+    Out,
+    /// Back to 100%.
+    Reset,
+    /// A content scale, where 1.0 is 100%.
+    To(f32),
+}
+
+/// Zoom the current window's content, as Ctrl+Plus, Ctrl+Minus and Ctrl+0
+/// do. Layout runs again at the new scale, and the app re-renders.
+///
+/// From a menu item's `onclick`, the current window is the focused one.
+///
+/// # Example
+///
+/// ```ignore
+/// Menu { label: "View",
+///     MenuItem { label: "Zoom In", shortcut: "Cmd+=", onclick: || zoom_current_window(Zoom::In) }
+///     MenuItem { label: "Zoom Out", shortcut: "Cmd+-", onclick: || zoom_current_window(Zoom::Out) }
+///     MenuItem { label: "Actual Size", shortcut: "Cmd+0", onclick: || zoom_current_window(Zoom::Reset) }
+/// }
+/// ```
+pub fn zoom_current_window(zoom: Zoom) {
+    if let Some(window_id) = get_current_window_id() {
+        send_event(RinchEvent::ZoomWindow { window_id, zoom });
+    }
+}
+
+/// The current window's content scale, where 1.0 is 100%.
+///
+/// Read it while rendering to show the zoom level; the app re-renders
+/// whenever a window's scale changes. Outside an event handler, this is the
+/// focused window's.
+pub fn current_window_content_scale() -> f32 {
+    get_current_window_id()
+        .and_then(|window_id| CONTENT_SCALES.with(|s| s.borrow().get(&window_id).copied()))
+        .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_callbacks_run_on_changes_until_the_window_closes() {
+        let handle = WindowHandle::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let record = seen.clone();
+        on_window_state_change(handle, move |state| record.borrow_mut().push(state));
+
+        let state = WindowState::default();
+        update_window_state(handle, state);
+        update_window_state(handle, state);
+        let maximized = WindowState {
+            maximized: true,
+            ..state
+        };
+        update_window_state(handle, maximized);
+        assert_eq!(*seen.borrow(), [state, maximized]);
+
+        remove_window_state(handle);
+        update_window_state(handle, state);
+        assert_eq!(seen.borrow().len(), 2);
+    }
+
+    #[test]
+    fn show_and_hide_queue_visibility_requests() {
+        let handle = WindowHandle::new();
+        take_window_requests();
+        handle.hide();
+        handle.show();
+        let visible: Vec<bool> = take_window_requests()
+            .into_iter()
+            .filter_map(|request| match request {
+                WindowRequest::Visibility(request) if request.handle == handle => {
+                    Some(request.visible)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(visible, [false, true]);
+    }
+}
@@ -3,10 +3,14 @@
 //! This example demonstrates rinch's reactive system with:
 //! - Signals and event handlers
 //! - Menu item callbacks (onclick)
+//! - Async file dialogs for File > Open and Save
 //! - use_context for shared state
 //! - use_derived for computed state
 //! - Frameless window with custom chrome
 
+use std::path::PathBuf;
+
+use rinch::dialogs::{open_file, save_file};
 use rinch::prelude::*;
 
 /// Theme context shared across the application.
@@ -16,6 +20,55 @@ struct ThemeContext {
     background: String,
 }
 
+/// Ask for a text file and load it into `text`. The dialog doesn't block
+/// the event loop, so the editor keeps drawing while it's open.
+fn open_document(text: Signal<String>, path: Signal<Option<PathBuf>>) {
+    spawn_local(async move {
+        let Some(picked) = open_file()
+            .set_title("Open document")
+            .add_filter("Text Files", &["txt", "md"])
+            .add_filter("All Files", &["*"])
+            .pick_file_async()
+            .await
+        else {
+            return;
+        };
+        match std::fs::read_to_string(&picked) {
+            Ok(contents) => {
+                text.set(contents);
+                path.set(Some(picked));
+            }
+            Err(err) => eprintln!("Couldn't open {}: {err}", picked.display()),
+        }
+    });
+}
+
+/// Write `text` to the document's file, asking where first if it hasn't
+/// got one yet or `choose` is set (Save As).
+fn save_document(text: Signal<String>, path: Signal<Option<PathBuf>>, choose: bool) {
+    spawn_local(async move {
+        let target = match path.get() {
+            Some(existing) if !choose => existing,
+            _ => {
+                let Some(picked) = save_file()
+                    .set_title("Save document")
+                    .set_file_name("untitled.txt")
+                    .add_filter("Text Files", &["txt", "md"])
+                    .save_async()
+                    .await
+                else {
+                    return;
+                };
+                picked
+            }
+        };
+        match std::fs::write(&target, text.get()) {
+            Ok(()) => path.set(Some(target)),
+            Err(err) => eprintln!("Couldn't save {}: {err}", target.display()),
+        }
+    });
+}
+
 fn app() -> Element {
     // Create a theme context accessible from anywhere
     let theme = create_context(ThemeContext {
@@ -27,6 +80,7 @@ fn app() -> Element {
     let count = use_signal(|| 0);
     let text = use_signal(|| String::from("Hello, Rinch!"));
     let show_about = use_signal(|| false);
+    let file_path = use_signal(|| None::<PathBuf>);
 
     // Use derived to compute values automatically
     let doubled = use_derived({
@@ -48,6 +102,9 @@ fn app() -> Element {
     // Clones for menu callbacks
     let menu_count_reset = count.clone();
     let menu_show_about = show_about.clone();
+    let (open_text, open_path) = (text.clone(), file_path.clone());
+    let (save_text, save_path) = (text.clone(), file_path.clone());
+    let (save_as_text, save_as_path) = (text.clone(), file_path.clone());
 
     rsx! {
         Fragment {
@@ -56,14 +113,16 @@ fn app() -> Element {
                     MenuItem { label: "New", shortcut: "Cmd+N", onclick: || {
                         println!("File > New clicked!");
                     }}
-                    MenuItem { label: "Open...", shortcut: "Cmd+O", onclick: || {
-                        println!("File > Open clicked!");
+                    MenuItem { label: "Open...", shortcut: "Cmd+O", onclick: move || {
+                        open_document(open_text.clone(), open_path.clone());
                     }}
                     MenuSeparator {}
-                    MenuItem { label: "Save", shortcut: "Cmd+S", onclick: || {
-                        println!("File > Save clicked!");
+                    MenuItem { label: "Save", shortcut: "Cmd+S", onclick: move || {
+                        save_document(save_text.clone(), save_path.clone(), false);
+                    }}
+                    MenuItem { label: "Save As...", shortcut: "Cmd+Shift+S", onclick: move || {
+                        save_document(save_as_text.clone(), save_as_path.clone(), true);
                     }}
-                    MenuItem { label: "Save As...", shortcut: "Cmd+Shift+S" }
                     MenuSeparator {}
                     MenuItem { label: "Exit", shortcut: "Alt+F4" }
                 }
@@ -81,9 +140,9 @@ fn app() -> Element {
                     }}
                 }
                 Menu { label: "View",
-                    MenuItem { label: "Zoom In", shortcut: "Cmd+=" }
-                    MenuItem { label: "Zoom Out", shortcut: "Cmd+-" }
-                    MenuItem { label: "Reset Zoom", shortcut: "Cmd+0" }
+                    MenuItem { label: "Zoom In", shortcut: "Cmd+=", onclick: || zoom_current_window(Zoom::In) }
+                    MenuItem { label: "Zoom Out", shortcut: "Cmd+-", onclick: || zoom_current_window(Zoom::Out) }
+                    MenuItem { label: "Reset Zoom", shortcut: "Cmd+0", onclick: || zoom_current_window(Zoom::Reset) }
                 }
                 Menu { label: "Help",
                     MenuItem { label: "About smyeditor", onclick: move || {